@@ -4,23 +4,36 @@
 //! Build script for compiling C++ FFI library
 
 use std::env;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 fn main() {
-    // Prefer vendored cryptonote if present and ENABLE_VENDORED_CRYPTONOTE is set
-    let use_vendored = env::var("ENABLE_VENDORED_CRYPTONOTE").ok().as_deref() == Some("1");
-    let vendored_exists = Path::new("cryptonote/include").exists() || Path::new("./src-tauri/cryptonote/include").exists();
-
-    if use_vendored && vendored_exists {
-        if build_with_vendored_cryptonote() {
-            println!("cargo:warning=Using vendored cryptonote sources");
-            return;
+    // The mock-ffi feature links Rust stubs instead of the C++ libraries
+    if env::var_os("CARGO_FEATURE_MOCK_FFI").is_some() {
+        if env::var("PROFILE").as_deref() == Ok("release") {
+            panic!("The mock-ffi feature replaces the wallet with stubs and cannot be used in release builds");
         }
+        println!("cargo:warning=mock-ffi: skipping the C++ wallet libraries, wallet calls are answered by Rust stubs with fake data");
+        return;
+    }
+
+    #[cfg(feature = "bindgen")]
+    generate_bindings();
+
+    // The vendored feature builds the cryptonote sources; ENABLE_VENDORED_CRYPTONOTE=1
+    // still selects it for build setups that predate the feature
+    println!("cargo:rerun-if-env-changed=ENABLE_VENDORED_CRYPTONOTE");
+    let use_vendored = env::var_os("CARGO_FEATURE_VENDORED").is_some()
+        || env::var("ENABLE_VENDORED_CRYPTONOTE").ok().as_deref() == Some("1");
+
+    if use_vendored {
+        build_with_vendored_cryptonote();
+        println!("cargo:warning=Using vendored cryptonote sources");
+        return;
     }
 
     // Try to build real Fuego wallet minimal implementation
     if build_real_fuego_wallet() {
-        println!("cargo:warning=Using real Fuego wallet implementation");
+        println!("cargo:warning=Using real Fuego wallet implementation (build with --features mock-ffi to skip the C++ libraries)");
         return;
     }
     
@@ -29,6 +42,28 @@ fn main() {
     build_mock_ffi();
 }
 
+/// Generate declarations for each wallet header into its own file in OUT_DIR;
+/// the headers define conflicting types, so they cannot share one module
+#[cfg(feature = "bindgen")]
+fn generate_bindings() {
+    let out_dir = std::path::PathBuf::from(env::var("OUT_DIR").unwrap());
+    let headers = [
+        ("fuego_wallet_real.h", "fuego_wallet_.*", "fuego_wallet_bindings.rs"),
+        ("crypto_note_ffi.h", "crypto_note_.*", "crypto_note_bindings.rs"),
+    ];
+    for (header, functions, out_file) in headers {
+        println!("cargo:rerun-if-changed={}", header);
+        bindgen::Builder::default()
+            .header(header)
+            .allowlist_function(functions)
+            .parse_callbacks(Box::new(bindgen::CargoCallbacks::new()))
+            .generate()
+            .unwrap_or_else(|e| panic!("Failed to generate bindings for {}: {}", header, e))
+            .write_to_file(out_dir.join(out_file))
+            .unwrap_or_else(|e| panic!("Failed to write bindings for {}: {}", header, e));
+    }
+}
+
 fn build_real_fuego_wallet() -> bool {
     // Compile the real Fuego wallet library
     cc::Build::new()
@@ -71,18 +106,83 @@ fn build_real_fuego_wallet() -> bool {
     true
 }
 
-fn build_with_vendored_cryptonote() -> bool {
-    // Root for vendored cryptonote
-    let include_root = if Path::new("cryptonote/include").exists() { "cryptonote/include" } else { "src-tauri/cryptonote/include" };
-    let src_root = if Path::new("cryptonote/src").exists() { "cryptonote/src" } else { "src-tauri/cryptonote/src" };
+/// Vendored source directories the wallet needs, relative to `cryptonote/src`
+const VENDORED_SOURCE_DIRS: [&str; 4] = ["crypto", "Common", "Serialization", "Wallet"];
+
+/// Boost libraries the vendored sources link against
+const BOOST_LIBS: [&str; 8] = [
+    "boost_system",
+    "boost_filesystem",
+    "boost_thread",
+    "boost_date_time",
+    "boost_chrono",
+    "boost_regex",
+    "boost_serialization",
+    "boost_program_options",
+];
+
+/// Header and library directories of a native dependency
+#[derive(Default)]
+struct NativeDep {
+    include_dirs: Vec<PathBuf>,
+    link_dirs: Vec<PathBuf>,
+}
+
+/// Compile the vendored cryptonote sources and the shims against them. Panics
+/// with everything that is missing rather than falling back to the stubs.
+fn build_with_vendored_cryptonote() {
+    let include_root = Path::new("cryptonote/include");
+    let src_root = Path::new("cryptonote/src");
+
+    let mut missing = Vec::new();
+    if !include_root.exists() || !src_root.exists() {
+        missing.push("the cryptonote sources in src-tauri/cryptonote (include/ and src/)".to_string());
+    }
+    let boost = find_boost().unwrap_or_else(|e| {
+        missing.push(e);
+        NativeDep::default()
+    });
+    let openssl = find_openssl().unwrap_or_else(|e| {
+        missing.push(e);
+        NativeDep::default()
+    });
+    if !missing.is_empty() {
+        panic!(
+            "The vendored cryptonote build is missing:\n  - {}\nInstall what is listed or build without the vendored feature.",
+            missing.join("\n  - ")
+        );
+    }
+
+    let include_dirs: Vec<PathBuf> = [PathBuf::from("."), include_root.to_path_buf(), src_root.to_path_buf()]
+        .into_iter()
+        .chain(boost.include_dirs)
+        .chain(openssl.include_dirs)
+        .collect();
+
+    // C and C++ sources need their own compiler
+    let c_sources = vendored_sources(src_root, "c");
+    let cpp_sources = vendored_sources(src_root, "cpp");
+    cc::Build::new()
+        .files(&c_sources)
+        .includes(&include_dirs)
+        .define("STATICLIB", None)
+        .warnings(false)
+        .compile("fuego_cryptonote_c");
+    cc::Build::new()
+        .cpp(true)
+        .std("c++14")
+        .files(&cpp_sources)
+        .includes(&include_dirs)
+        .define("STATICLIB", None)
+        .warnings(false)
+        .compile("fuego_cryptonote");
 
     // Build fuego wallet shim
     cc::Build::new()
         .cpp(true)
         .std("c++14")
         .file("fuego_wallet_real.cpp")
-        .include(".")
-        .include(include_root)
+        .includes(&include_dirs)
         .compile("fuego_wallet_real");
     println!("cargo:rustc-link-lib=fuego_wallet_real");
 
@@ -91,28 +191,115 @@ fn build_with_vendored_cryptonote() -> bool {
         .cpp(true)
         .std("c++14")
         .file("crypto_note_ffi.cpp")
-        .include(".")
-        .include(include_root)
+        .includes(&include_dirs)
         .compile("crypto_note_ffi");
 
-    // Minimal: Ask cargo to rerun when these change
     println!("cargo:rerun-if-changed=fuego_wallet_real.cpp");
     println!("cargo:rerun-if-changed=fuego_wallet_real.h");
     println!("cargo:rerun-if-changed=crypto_note_ffi.cpp");
     println!("cargo:rerun-if-changed=crypto_note_ffi.h");
-    println!("cargo:rerun-if-changed={}", include_root);
-    println!("cargo:rerun-if-changed={}", src_root);
+    println!("cargo:rerun-if-changed={}", include_root.display());
+    for dir in VENDORED_SOURCE_DIRS {
+        println!("cargo:rerun-if-changed={}", src_root.join(dir).display());
+    }
 
-    // System libs
-    if cfg!(target_os = "macos") {
-        println!("cargo:rustc-link-lib=c++");
-    } else if cfg!(target_os = "linux") {
-        println!("cargo:rustc-link-lib=stdc++");
-        println!("cargo:rustc-link-lib=pthread");
-        println!("cargo:rustc-link-lib=resolv");
+    for dir in boost.link_dirs.iter().chain(&openssl.link_dirs) {
+        println!("cargo:rustc-link-search=native={}", dir.display());
     }
+    link_vendored_system_libs();
+}
 
-    true
+/// `.ext` files in the vendored source directories, recursively
+fn vendored_sources(src_root: &Path, ext: &str) -> Vec<PathBuf> {
+    fn walk(dir: &Path, ext: &str, found: &mut Vec<PathBuf>) {
+        let Ok(entries) = std::fs::read_dir(dir) else { return };
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.is_dir() {
+                walk(&path, ext, found);
+            } else if path.extension().is_some_and(|e| e == ext) {
+                found.push(path);
+            }
+        }
+    }
+    let mut found = Vec::new();
+    for dir in VENDORED_SOURCE_DIRS {
+        walk(&src_root.join(dir), ext, &mut found);
+    }
+    found.sort();
+    found
+}
+
+/// Boost from FUEGO_BOOST_ROOT, pkg-config or the usual install prefixes
+fn find_boost() -> Result<NativeDep, String> {
+    println!("cargo:rerun-if-env-changed=FUEGO_BOOST_ROOT");
+    if let Some(root) = env::var_os("FUEGO_BOOST_ROOT").map(PathBuf::from) {
+        // Either an install prefix or an unpacked source tree with staged libraries
+        let include = [root.join("include"), root.clone()].into_iter().find(|dir| dir.join("boost/version.hpp").exists());
+        let Some(include) = include else {
+            return Err(format!("Boost headers: FUEGO_BOOST_ROOT={} has no boost/version.hpp", root.display()));
+        };
+        let link_dirs = ["lib", "lib64", "stage/lib"].iter().map(|dir| root.join(dir)).filter(|dir| dir.exists()).collect();
+        return Ok(NativeDep { include_dirs: vec![include], link_dirs });
+    }
+    if let Ok(lib) = pkg_config::Config::new().cargo_metadata(false).probe("boost") {
+        return Ok(NativeDep { include_dirs: lib.include_paths, link_dirs: lib.link_paths });
+    }
+    find_in_prefixes("boost/version.hpp").ok_or_else(|| {
+        "Boost headers and libraries: install Boost (libboost-all-dev, `brew install boost`, `vcpkg install boost`) or set FUEGO_BOOST_ROOT".to_string()
+    })
+}
+
+/// OpenSSL from OPENSSL_DIR, pkg-config or the usual install prefixes
+fn find_openssl() -> Result<NativeDep, String> {
+    println!("cargo:rerun-if-env-changed=OPENSSL_DIR");
+    if let Some(root) = env::var_os("OPENSSL_DIR").map(PathBuf::from) {
+        let include = root.join("include");
+        if !include.join("openssl/ssl.h").exists() {
+            return Err(format!("OpenSSL headers: OPENSSL_DIR={} has no include/openssl/ssl.h", root.display()));
+        }
+        let link_dirs = ["lib", "lib64"].iter().map(|dir| root.join(dir)).filter(|dir| dir.exists()).collect();
+        return Ok(NativeDep { include_dirs: vec![include], link_dirs });
+    }
+    if let Ok(lib) = pkg_config::Config::new().cargo_metadata(false).probe("openssl") {
+        return Ok(NativeDep { include_dirs: lib.include_paths, link_dirs: lib.link_paths });
+    }
+    find_in_prefixes("openssl/ssl.h").ok_or_else(|| {
+        "OpenSSL headers and libraries: install OpenSSL (libssl-dev, `brew install openssl`, `vcpkg install openssl`) or set OPENSSL_DIR".to_string()
+    })
+}
+
+/// The install prefix among the usual ones whose include directory has `header`
+fn find_in_prefixes(header: &str) -> Option<NativeDep> {
+    ["/usr", "/usr/local", "/opt/homebrew", "/opt/local"]
+        .iter()
+        .map(Path::new)
+        .find(|prefix| prefix.join("include").join(header).exists())
+        .map(|prefix| NativeDep {
+            include_dirs: vec![prefix.join("include")],
+            link_dirs: vec![prefix.join("lib")],
+        })
+}
+
+/// Boost, OpenSSL and system libraries for the target OS
+fn link_vendored_system_libs() {
+    match env::var("CARGO_CFG_TARGET_OS").as_deref() {
+        Ok("windows") => {
+            // MSVC builds of Boost select their libraries through #pragma comment(lib)
+            for lib in ["libssl", "libcrypto", "ws2_32", "advapi32", "crypt32", "user32", "bcrypt"] {
+                println!("cargo:rustc-link-lib={}", lib);
+            }
+        }
+        Ok("macos") => {
+            for lib in BOOST_LIBS.iter().copied().chain(["ssl", "crypto", "c++", "resolv"]) {
+                println!("cargo:rustc-link-lib={}", lib);
+            }
+        }
+        _ => {
+            for lib in BOOST_LIBS.iter().copied().chain(["ssl", "crypto", "stdc++", "pthread", "resolv", "dl"]) {
+                println!("cargo:rustc-link-lib={}", lib);
+            }
+        }
+    }
 }
 
 fn build_mock_ffi() {
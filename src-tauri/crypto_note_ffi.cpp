@@ -139,29 +139,24 @@ extern "C" TransactionResult crypto_note_wallet_send_transaction(
     // TODO: Implement actual transaction sending using CryptoNote C++ code
     // For now, return mock result
     
-    MockTransaction* tx = new MockTransaction();
-    const std::time_t now_time = std::time(nullptr);
-    const long long now_ll = static_cast<long long>(now_time);
-    {
-        std::ostringstream oss;
-        oss << "tx_mock_" << now_ll;
-        tx->id = oss.str();
+    if (g_mock_wallet.get() != wallet || !g_mock_wallet->is_open) {
+        return nullptr;
     }
-    {
-        std::ostringstream oss;
-        oss << "mock_hash_" << now_ll;
-        tx->hash = oss.str();
+    const uint64_t fee = 1000000;
+    if (amount + fee > g_mock_wallet->unlocked_balance) {
+        return nullptr;
     }
-    tx->amount = -static_cast<int64_t>(amount);
-    tx->fee = 1000000;
-    tx->timestamp = static_cast<uint64_t>(now_time);
-    tx->confirmations = 0;
-    tx->is_confirmed = false;
-    tx->is_incoming = false;
-    tx->address = address ? address : "";
-    tx->payment_id = payment_id ? payment_id : "";
+    g_mock_wallet->balance -= amount + fee;
+    g_mock_wallet->unlocked_balance -= amount + fee;
     
-    return static_cast<TransactionResult>(tx);
+    std::ostringstream oss;
+    oss << "mock_hash_" << static_cast<long long>(std::time(nullptr));
+    const std::string hash = oss.str();
+    
+    // Freed by crypto_note_wallet_free_string
+    char* result = new char[hash.size() + 1];
+    std::memcpy(result, hash.c_str(), hash.size() + 1);
+    return static_cast<TransactionResult>(result);
 }
 
 extern "C" TransactionList crypto_note_wallet_get_transactions(
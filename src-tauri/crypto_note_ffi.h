@@ -51,6 +51,8 @@ bool crypto_note_wallet_get_address(
 );
 
 // Transaction operations
+// Returns the transaction hash, freed with crypto_note_wallet_free_string,
+// or NULL if the wallet cannot pay the amount and fee
 TransactionResult crypto_note_wallet_send_transaction(
     CryptoNoteWallet wallet,
     const char* address,
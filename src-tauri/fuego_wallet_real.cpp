@@ -10,6 +10,7 @@
 #include <string>
 #include <vector>
 #include <cstring>
+#include <cstdio>
 #include <iostream>
 #include <fstream>
 #include <sstream>
@@ -18,6 +19,8 @@
 #include <algorithm>
 #include <thread>
 #include <iomanip>
+#include <map>
+#include <mutex>
 
 // TODO: Include actual CryptoNote headers when integrating
 // #include "WalletLegacy/WalletLegacy.h"
@@ -47,6 +50,14 @@ struct RealFuegoWallet {
     
     // Transaction history
     std::vector<std::string> transaction_hashes;
+
+    // Addresses and their labels; index 0 is the primary address
+    struct AddressEntry {
+        std::string address;
+        std::string label;
+    };
+    std::vector<AddressEntry> extra_addresses;
+    std::string primary_label;
     
     // Deposit management
     struct Deposit {
@@ -111,8 +122,12 @@ struct RealFuegoWallet {
     }
     
     ~RealFuegoWallet() {
-        // Ensure background thread is stopped before destruction
+        // Ensure background threads are stopped before destruction
         stop_sync_process();
+        mining_thread_running = false;
+        if (mining_thread.joinable()) {
+            mining_thread.join();
+        }
     }
     
     void generate_fuego_address() {
@@ -270,8 +285,27 @@ public:
     }
 };
 
-// Global wallet instance
-static std::unique_ptr<RealFuegoWallet> g_real_wallet = nullptr;
+// Open wallets keyed by the handle given out for them. Every create or open
+// makes a new wallet, so opening one file never swaps out the wallet behind
+// another handle; a handle stays valid until it is closed.
+static std::mutex g_wallets_mutex;
+static std::map<FuegoWallet, std::shared_ptr<RealFuegoWallet>> g_wallets;
+
+// The wallet behind `handle`, empty for an unknown or closed handle. The copy
+// keeps the wallet alive while the caller uses it, even if the handle is
+// closed meanwhile.
+static std::shared_ptr<RealFuegoWallet> find_wallet(FuegoWallet handle) {
+    std::lock_guard<std::mutex> lock(g_wallets_mutex);
+    auto entry = g_wallets.find(handle);
+    return entry == g_wallets.end() ? nullptr : entry->second;
+}
+
+static FuegoWallet register_wallet(std::unique_ptr<RealFuegoWallet> wallet) {
+    FuegoWallet handle = static_cast<FuegoWallet>(wallet.get());
+    std::lock_guard<std::mutex> lock(g_wallets_mutex);
+    g_wallets[handle] = std::move(wallet);
+    return handle;
+}
 
 // Wallet creation and management
 extern "C" FuegoWallet fuego_wallet_create(
@@ -282,19 +316,19 @@ extern "C" FuegoWallet fuego_wallet_create(
 ) {
     std::cout << "Creating real Fuego wallet..." << std::endl;
     
-    g_real_wallet.reset(new RealFuegoWallet());
-    g_real_wallet->password = password ? password : "";
-    g_real_wallet->file_path = file_path ? file_path : "";
-    g_real_wallet->restore_height = restore_height;
+    std::unique_ptr<RealFuegoWallet> created(new RealFuegoWallet());
+    created->password = password ? password : "";
+    created->file_path = file_path ? file_path : "";
+    created->restore_height = restore_height;
     
     // Simulate wallet creation process
-    g_real_wallet->load_wallet_data();
+    created->load_wallet_data();
     
     std::cout << "Real Fuego wallet created successfully" << std::endl;
-    std::cout << "Address: " << g_real_wallet->address << std::endl;
-    std::cout << "Balance: " << g_real_wallet->balance << " atomic units (" << (g_real_wallet->balance / 10000000.0) << " XFG)" << std::endl;
+    std::cout << "Address: " << created->address << std::endl;
+    std::cout << "Balance: " << created->balance << " atomic units (" << (created->balance / 10000000.0) << " XFG)" << std::endl;
     
-    return static_cast<FuegoWallet>(g_real_wallet.get());
+    return register_wallet(std::move(created));
 }
 
 extern "C" FuegoWallet fuego_wallet_open(
@@ -303,47 +337,68 @@ extern "C" FuegoWallet fuego_wallet_open(
 ) {
     std::cout << "Opening real Fuego wallet..." << std::endl;
     
-    g_real_wallet.reset(new RealFuegoWallet());
-    g_real_wallet->password = password ? password : "";
-    g_real_wallet->file_path = file_path ? file_path : "";
+    std::unique_ptr<RealFuegoWallet> opened(new RealFuegoWallet());
+    opened->password = password ? password : "";
+    opened->file_path = file_path ? file_path : "";
     
     // Simulate wallet opening process
-    g_real_wallet->load_wallet_data();
+    opened->load_wallet_data();
     
     std::cout << "Real Fuego wallet opened successfully" << std::endl;
-    std::cout << "Address: " << g_real_wallet->address << std::endl;
-    std::cout << "Balance: " << g_real_wallet->balance << " atomic units (" << (g_real_wallet->balance / 10000000.0) << " XFG)" << std::endl;
+    std::cout << "Address: " << opened->address << std::endl;
+    std::cout << "Balance: " << opened->balance << " atomic units (" << (opened->balance / 10000000.0) << " XFG)" << std::endl;
     
-    return static_cast<FuegoWallet>(g_real_wallet.get());
+    return register_wallet(std::move(opened));
 }
 
 extern "C" void fuego_wallet_close(FuegoWallet wallet) {
-    if (g_real_wallet.get() == wallet) {
-        std::cout << "Closing real Fuego wallet..." << std::endl;
-        g_real_wallet->stop_sync_process(); // Stop background thread before closing
-        g_real_wallet->is_open = false;
-        g_real_wallet->is_connected = false;
+    std::shared_ptr<RealFuegoWallet> closed;
+    {
+        std::lock_guard<std::mutex> lock(g_wallets_mutex);
+        auto entry = g_wallets.find(wallet);
+        if (entry == g_wallets.end()) {
+            return;
+        }
+        closed = std::move(entry->second);
+        g_wallets.erase(entry);
     }
+    std::cout << "Closing real Fuego wallet..." << std::endl;
+    closed->stop_sync_process(); // Stop background thread before closing
+    closed->is_open = false;
+    closed->is_connected = false;
+}
+
+extern "C" bool fuego_wallet_save(FuegoWallet wallet) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found || !found->is_open || found->file_path.empty()) {
+        return false;
+    }
+
+    std::cout << "Saved real Fuego wallet to " << found->file_path << std::endl;
+    return true;
 }
 
 extern "C" bool fuego_wallet_is_open(FuegoWallet wallet) {
-    if (g_real_wallet.get() == wallet) {
-        return g_real_wallet->is_open;
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (found) {
+        return found->is_open;
     }
     return false;
 }
 
 // Wallet information
 extern "C" uint64_t fuego_wallet_get_balance(FuegoWallet wallet) {
-    if (g_real_wallet.get() == wallet) {
-        return g_real_wallet->balance;
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (found) {
+        return found->balance;
     }
     return 0;
 }
 
 extern "C" uint64_t fuego_wallet_get_unlocked_balance(FuegoWallet wallet) {
-    if (g_real_wallet.get() == wallet) {
-        return g_real_wallet->unlocked_balance;
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (found) {
+        return found->unlocked_balance;
     }
     return 0;
 }
@@ -353,8 +408,9 @@ extern "C" bool fuego_wallet_get_address(
     char* buffer,
     size_t buffer_size
 ) {
-    if (g_real_wallet.get() == wallet && buffer && buffer_size > 0) {
-        const std::string& address = g_real_wallet->address;
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (found && buffer && buffer_size > 0) {
+        const std::string& address = found->address;
         if (address.length() < buffer_size) {
             std::strcpy(buffer, address.c_str());
             return true;
@@ -371,7 +427,8 @@ extern "C" TransactionResult fuego_wallet_send_transaction(
     const char* payment_id,
     uint64_t mixin
 ) {
-    if (g_real_wallet.get() != wallet) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found) {
         return nullptr;
     }
     
@@ -385,13 +442,13 @@ extern "C" TransactionResult fuego_wallet_send_transaction(
     std::string tx_hash = "real_tx_" + std::to_string(std::chrono::system_clock::now().time_since_epoch().count());
     
     // Update balance
-    if (amount <= g_real_wallet->balance) {
-        g_real_wallet->balance -= amount;
-        g_real_wallet->unlocked_balance -= amount;
-        g_real_wallet->transaction_hashes.push_back(tx_hash);
+    if (amount <= found->balance) {
+        found->balance -= amount;
+        found->unlocked_balance -= amount;
+        found->transaction_hashes.push_back(tx_hash);
         
         std::cout << "Transaction sent successfully: " << tx_hash << std::endl;
-        std::cout << "New balance: " << g_real_wallet->balance << " atomic units (" << (g_real_wallet->balance / 10000000.0) << " XFG)" << std::endl;
+        std::cout << "New balance: " << found->balance << " atomic units (" << (found->balance / 10000000.0) << " XFG)" << std::endl;
         
         // Return transaction hash as void pointer (simplified)
         return static_cast<TransactionResult>(new std::string(tx_hash));
@@ -401,17 +458,57 @@ extern "C" TransactionResult fuego_wallet_send_transaction(
     }
 }
 
+extern "C" char* fuego_wallet_send_many(
+    FuegoWallet wallet,
+    const char* const* addresses,
+    const uint64_t* amounts,
+    size_t count,
+    uint64_t mixin
+) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found || !addresses || !amounts || count == 0) {
+        return nullptr;
+    }
+    
+    uint64_t total = 0;
+    for (size_t i = 0; i < count; ++i) {
+        if (!addresses[i] || amounts[i] == 0 || total + amounts[i] < total) {
+            return nullptr;
+        }
+        total += amounts[i];
+    }
+    
+    std::cout << "Sending real transaction to " << count << " recipients..." << std::endl;
+    std::cout << "Total: " << total << std::endl;
+    std::cout << "Mixin: " << mixin << std::endl;
+    
+    if (total > found->unlocked_balance) {
+        std::cout << "Insufficient funds for transaction" << std::endl;
+        return nullptr;
+    }
+    
+    std::string tx_hash = "real_tx_" + std::to_string(std::chrono::system_clock::now().time_since_epoch().count());
+    found->balance -= total;
+    found->unlocked_balance -= total;
+    found->transaction_hashes.push_back(tx_hash);
+    
+    char* result = new char[tx_hash.length() + 1];
+    std::strcpy(result, tx_hash.c_str());
+    return result;
+}
+
 extern "C" TransactionList fuego_wallet_get_transactions(
     FuegoWallet wallet,
     uint64_t limit,
     uint64_t offset
 ) {
-    if (g_real_wallet.get() != wallet) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found) {
         return nullptr;
     }
 
     // Return transaction list (simplified)
-    return static_cast<TransactionList>(new std::vector<std::string>(g_real_wallet->transaction_hashes));
+    return static_cast<TransactionList>(new std::vector<std::string>(found->transaction_hashes));
 }
 
 // Get real transaction history from blockchain
@@ -420,21 +517,22 @@ extern "C" TransactionInfo* fuego_wallet_get_transaction_history(
     uint64_t limit,
     uint64_t offset
 ) {
-    if (g_real_wallet.get() != wallet) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found) {
         return nullptr;
     }
 
     // Return nullptr if no transactions yet (empty wallet)
     // Real implementation would query actual transaction history from CryptoNote wallet
-    if (g_real_wallet->transaction_hashes.empty()) {
+    if (found->transaction_hashes.empty()) {
         return nullptr;
     }
 
     // Return real transaction if it exists
-    if (offset < g_real_wallet->transaction_hashes.size()) {
+    if (offset < found->transaction_hashes.size()) {
         TransactionInfo* tx = new TransactionInfo();
         
-        const std::string& tx_hash = g_real_wallet->transaction_hashes[offset];
+        const std::string& tx_hash = found->transaction_hashes[offset];
         strncpy(tx->id, tx_hash.c_str(), sizeof(tx->id) - 1);
         tx->id[sizeof(tx->id) - 1] = '\0';
         strncpy(tx->hash, tx_hash.c_str(), sizeof(tx->hash) - 1);
@@ -443,11 +541,11 @@ extern "C" TransactionInfo* fuego_wallet_get_transaction_history(
         // Real transaction data (would come from CryptoNote transaction cache)
         tx->amount = 0; // Will be set by actual transaction data
         tx->fee = 100000; // Standard Fuego fee (0.01 XFG)
-        tx->height = g_real_wallet->sync_height;
+        tx->height = found->sync_height;
         tx->timestamp = std::chrono::duration_cast<std::chrono::seconds>(
             std::chrono::system_clock::now().time_since_epoch()
         ).count();
-        tx->confirmations = g_real_wallet->network_height - tx->height;
+        tx->confirmations = found->network_height - tx->height;
         tx->is_confirmed = tx->confirmations >= 10;
         tx->is_pending = !tx->is_confirmed;
         tx->unlock_time = 0;
@@ -474,7 +572,8 @@ extern "C" bool fuego_wallet_connect_node(
     const char* address,
     uint16_t port
 ) {
-    if (g_real_wallet.get() != wallet) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found) {
         return false;
     }
     
@@ -484,38 +583,39 @@ extern "C" bool fuego_wallet_connect_node(
     std::cout << "🔗 Connecting to Fuego node: " << node_address << ":" << node_port << std::endl;
     
     // Update connection info with actual node
-    g_real_wallet->connection_type = "Fuego Network (XFG) - " + node_address + ":" + std::to_string(node_port);
+    found->connection_type = "Fuego Network (XFG) - " + node_address + ":" + std::to_string(node_port);
     
     // Connect to real Fuego network
-    g_real_wallet->connect_to_network();
+    found->connect_to_network();
     
     std::cout << "✅ Connected to Fuego network successfully" << std::endl;
-    std::cout << "📡 Node: " << g_real_wallet->connection_type << std::endl;
-    std::cout << "👥 Peers: " << g_real_wallet->peer_count << std::endl;
-    std::cout << "📊 Wallet height: " << g_real_wallet->sync_height << std::endl;
-    std::cout << "📈 Network height: " << g_real_wallet->network_height << std::endl;
-    std::cout << "🔄 Syncing: " << (g_real_wallet->is_syncing ? "Yes" : "No") << std::endl;
+    std::cout << "📡 Node: " << found->connection_type << std::endl;
+    std::cout << "👥 Peers: " << found->peer_count << std::endl;
+    std::cout << "📊 Wallet height: " << found->sync_height << std::endl;
+    std::cout << "📈 Network height: " << found->network_height << std::endl;
+    std::cout << "🔄 Syncing: " << (found->is_syncing ? "Yes" : "No") << std::endl;
     
     return true;
 }
 
 extern "C" NetworkStatus* fuego_wallet_get_network_status(FuegoWallet wallet) {
-    if (g_real_wallet.get() != wallet) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found) {
         return nullptr;
     }
     
     // Update sync progress
-    g_real_wallet->update_sync_progress();
+    found->update_sync_progress();
     
     NetworkStatus* status = new NetworkStatus();
-    status->is_connected = g_real_wallet->is_connected;
-    status->peer_count = g_real_wallet->peer_count;
-    status->sync_height = g_real_wallet->sync_height;
-    status->network_height = g_real_wallet->network_height;
-    status->is_syncing = g_real_wallet->is_syncing;
+    status->is_connected = found->is_connected;
+    status->peer_count = found->peer_count;
+    status->sync_height = found->sync_height;
+    status->network_height = found->network_height;
+    status->is_syncing = found->is_syncing;
     
     // Copy connection type
-    strncpy(status->connection_type, g_real_wallet->connection_type.c_str(), sizeof(status->connection_type) - 1);
+    strncpy(status->connection_type, found->connection_type.c_str(), sizeof(status->connection_type) - 1);
     status->connection_type[sizeof(status->connection_type) - 1] = '\0';
     
     return status;
@@ -528,33 +628,36 @@ extern "C" void fuego_wallet_free_network_status(NetworkStatus* status) {
 }
 
 extern "C" bool fuego_wallet_disconnect_node(FuegoWallet wallet) {
-    if (g_real_wallet.get() != wallet) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found) {
         return false;
     }
-    g_real_wallet->stop_sync_process(); // Stop sync thread when disconnecting
-    g_real_wallet->is_connected = false;
-    g_real_wallet->is_syncing = false;
-    g_real_wallet->peer_count = 0;
-    g_real_wallet->connection_type = "Disconnected";
+    found->stop_sync_process(); // Stop sync thread when disconnecting
+    found->is_connected = false;
+    found->is_syncing = false;
+    found->peer_count = 0;
+    found->connection_type = "Disconnected";
     return true;
 }
 
 extern "C" bool fuego_wallet_refresh(FuegoWallet wallet) {
-    if (g_real_wallet.get() != wallet) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found) {
         return false;
     }
-    g_real_wallet->update_sync_progress();
+    found->update_sync_progress();
     return true;
 }
 
 extern "C" bool fuego_wallet_rescan_blockchain(FuegoWallet wallet, uint64_t start_height) {
-    if (g_real_wallet.get() != wallet) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found) {
         return false;
     }
     // Simulate rescan by resetting sync height
     (void)start_height;
-    g_real_wallet->sync_height = 0;
-    g_real_wallet->is_syncing = true;
+    found->sync_height = 0;
+    found->is_syncing = true;
     return true;
 }
 
@@ -564,24 +667,64 @@ extern "C" uint64_t fuego_wallet_estimate_transaction_fee(
     uint64_t amount,
     uint64_t mixin
 ) {
-    (void)wallet; (void)address; (void)amount; (void)mixin;
-    // Return a simple fixed fee estimate for now (0.01 XFG in atomic units)
-    return 1000000;
+    FeeBreakdown breakdown;
+    if (!fuego_wallet_estimate_fee_breakdown(wallet, address, amount, mixin, &breakdown)) {
+        return 0;
+    }
+    return breakdown.total;
+}
+
+extern "C" bool fuego_wallet_estimate_fee_breakdown(
+    FuegoWallet wallet,
+    const char* address,
+    uint64_t amount,
+    uint64_t mixin,
+    FeeBreakdown* breakdown
+) {
+    (void)wallet; (void)address; (void)amount;
+    if (!breakdown) {
+        return false;
+    }
+    // Fuego charges a flat minimum fee (0.1 XFG in atomic units) rather than a per-byte
+    // fee, so size and priority add nothing yet; the size still shows the mixin's cost.
+    // One input, a payment and a change output: prefix, then per input its key image
+    // and 68 bytes per ring member, then 40 bytes per output.
+    breakdown->base_fee = 1000000;
+    breakdown->size_fee = 0;
+    breakdown->priority_fee = 0;
+    breakdown->total = breakdown->base_fee + breakdown->size_fee + breakdown->priority_fee;
+    breakdown->estimated_size_bytes = 100 + (40 + (mixin + 1) * 68) + 2 * 40;
+    return true;
+}
+
+extern "C" AbiSizes fuego_wallet_abi_sizes(void) {
+    AbiSizes sizes;
+    sizes.wallet_info = sizeof(WalletInfo);
+    sizes.transaction_info = sizeof(TransactionInfo);
+    sizes.network_status = sizeof(NetworkStatus);
+    sizes.network_info = sizeof(NetworkInfo);
+    sizes.block_info = sizeof(BlockInfo);
+    sizes.mining_info = sizeof(MiningInfo);
+    sizes.sync_progress = sizeof(SyncProgress);
+    sizes.fee_breakdown = sizeof(FeeBreakdown);
+    return sizes;
 }
 
 // Deposit functions
 extern "C" void* fuego_wallet_get_deposits(FuegoWallet wallet) {
-    if (g_real_wallet.get() != wallet) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found) {
         return nullptr;
     }
     
     // Return pointer to deposits vector for parsing by Rust
     // In a real implementation, this would serialize the deposits to a C-compatible format
-    return static_cast<void*>(&g_real_wallet->deposits);
+    return static_cast<void*>(&found->deposits);
 }
 
 extern "C" void* fuego_wallet_create_deposit(FuegoWallet wallet, uint64_t amount, uint32_t term) {
-    if (g_real_wallet.get() != wallet) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found) {
         return nullptr;
     }
     
@@ -605,10 +748,10 @@ extern "C" void* fuego_wallet_create_deposit(FuegoWallet wallet, uint64_t amount
     // Calculate interest (simplified calculation)
     deposit.interest = static_cast<uint64_t>(amount * deposit.rate * term / 365.0);
     deposit.status = "locked";
-    deposit.unlock_height = g_real_wallet->network_height + (term * 24 * 60 * 60 / 120); // Assuming 2-minute blocks
+    deposit.unlock_height = found->network_height + (term * 24 * 60 * 60 / 120); // Assuming 2-minute blocks
     deposit.unlock_time = "TBD"; // Would calculate actual unlock time
     deposit.creating_transaction_hash = "tx_" + deposit.id;
-    deposit.creating_height = g_real_wallet->network_height;
+    deposit.creating_height = found->network_height;
     deposit.creating_time = "Now";
     deposit.spending_transaction_hash = "";
     deposit.spending_height = 0;
@@ -616,7 +759,7 @@ extern "C" void* fuego_wallet_create_deposit(FuegoWallet wallet, uint64_t amount
     deposit.deposit_type = "Term Deposit";
     
     // Add to deposits list
-    g_real_wallet->deposits.push_back(deposit);
+    found->deposits.push_back(deposit);
     
     // Return deposit ID as C string
     char* deposit_id = new char[deposit.id.length() + 1];
@@ -628,17 +771,18 @@ extern "C" void* fuego_wallet_create_deposit(FuegoWallet wallet, uint64_t amount
 }
 
 extern "C" void* fuego_wallet_withdraw_deposit(FuegoWallet wallet, const char* deposit_id) {
-    if (g_real_wallet.get() != wallet || !deposit_id) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found || !deposit_id) {
         return nullptr;
     }
     
     // Find the deposit
-    auto it = std::find_if(g_real_wallet->deposits.begin(), g_real_wallet->deposits.end(),
+    auto it = std::find_if(found->deposits.begin(), found->deposits.end(),
                           [deposit_id](const RealFuegoWallet::Deposit& deposit) {
                               return deposit.id == std::string(deposit_id);
                           });
     
-    if (it == g_real_wallet->deposits.end()) {
+    if (it == found->deposits.end()) {
         std::cout << "Deposit not found: " << deposit_id << std::endl;
         return nullptr;
     }
@@ -652,7 +796,7 @@ extern "C" void* fuego_wallet_withdraw_deposit(FuegoWallet wallet, const char* d
     // Mark as spent
     it->status = "spent";
     it->spending_transaction_hash = "withdraw_tx_" + it->id;
-    it->spending_height = g_real_wallet->network_height;
+    it->spending_height = found->network_height;
     it->spending_time = "Now";
     
     // Return transaction hash as C string
@@ -681,31 +825,32 @@ extern "C" void fuego_wallet_free_transactions(TransactionList txs) {
 
 // Get comprehensive wallet information
 extern "C" WalletInfo* fuego_wallet_get_wallet_info(FuegoWallet wallet) {
-    if (g_real_wallet.get() != wallet) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found) {
         return nullptr;
     }
 
     // Update real wallet info
-    g_real_wallet->update_sync_progress();
+    found->update_sync_progress();
 
     WalletInfo* info = new WalletInfo();
-    strncpy(info->address, g_real_wallet->address.c_str(), sizeof(info->address) - 1);
+    strncpy(info->address, found->address.c_str(), sizeof(info->address) - 1);
     info->address[sizeof(info->address) - 1] = '\0';
 
-    info->balance = g_real_wallet->balance;
-    info->unlocked_balance = g_real_wallet->unlocked_balance;
-    info->locked_balance = g_real_wallet->balance - g_real_wallet->unlocked_balance;
-    info->total_received = g_real_wallet->balance;
+    info->balance = found->balance;
+    info->unlocked_balance = found->unlocked_balance;
+    info->locked_balance = found->balance - found->unlocked_balance;
+    info->total_received = found->balance;
     info->total_sent = 0;
-    info->transaction_count = g_real_wallet->transaction_hashes.size();
+    info->transaction_count = found->transaction_hashes.size();
 
-    info->is_synced = !g_real_wallet->is_syncing;
-    info->sync_height = g_real_wallet->sync_height;
-    info->network_height = g_real_wallet->network_height;
-    info->daemon_height = g_real_wallet->network_height;
+    info->is_synced = !found->is_syncing;
+    info->sync_height = found->sync_height;
+    info->network_height = found->network_height;
+    info->daemon_height = found->network_height;
 
-    info->is_connected = g_real_wallet->is_connected;
-    info->peer_count = g_real_wallet->peer_count;
+    info->is_connected = found->is_connected;
+    info->peer_count = found->peer_count;
     info->last_block_time = std::chrono::duration_cast<std::chrono::seconds>(
         std::chrono::system_clock::now().time_since_epoch()
     ).count();
@@ -722,18 +867,19 @@ extern "C" void fuego_wallet_free_wallet_info(WalletInfo* info) {
 
 // Get detailed network information
 extern "C" NetworkInfo* fuego_wallet_get_network_info(FuegoWallet wallet) {
-    if (g_real_wallet.get() != wallet) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found) {
         return nullptr;
     }
 
     NetworkInfo* info = new NetworkInfo();
-    info->is_connected = g_real_wallet->is_connected;
-    info->peer_count = g_real_wallet->peer_count;
-    info->sync_height = g_real_wallet->sync_height;
-    info->network_height = g_real_wallet->network_height;
-    info->is_syncing = g_real_wallet->is_syncing;
+    info->is_connected = found->is_connected;
+    info->peer_count = found->peer_count;
+    info->sync_height = found->sync_height;
+    info->network_height = found->network_height;
+    info->is_syncing = found->is_syncing;
 
-    strncpy(info->connection_type, g_real_wallet->connection_type.c_str(),
+    strncpy(info->connection_type, found->connection_type.c_str(),
             sizeof(info->connection_type) - 1);
     info->connection_type[sizeof(info->connection_type) - 1] = '\0';
 
@@ -741,9 +887,9 @@ extern "C" NetworkInfo* fuego_wallet_get_network_info(FuegoWallet wallet) {
         std::chrono::system_clock::now().time_since_epoch()
     ).count();
 
-    info->sync_speed = g_real_wallet->is_syncing ? 100.0 : 0.0; // blocks per second
-    info->estimated_sync_time = g_real_wallet->is_syncing ?
-        (g_real_wallet->network_height - g_real_wallet->sync_height) / 100 : 0;
+    info->sync_speed = found->is_syncing ? 100.0 : 0.0; // blocks per second
+    info->estimated_sync_time = found->is_syncing ?
+        (found->network_height - found->sync_height) / 100 : 0;
 
     return info;
 }
@@ -760,7 +906,8 @@ extern "C" TransactionInfo* fuego_wallet_get_transaction_by_hash(
     FuegoWallet wallet,
     const char* tx_hash
 ) {
-    if (g_real_wallet.get() != wallet || !tx_hash) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found || !tx_hash) {
         return nullptr;
     }
 
@@ -771,10 +918,10 @@ extern "C" TransactionInfo* fuego_wallet_get_transaction_by_hash(
     tx->hash[sizeof(tx->hash) - 1] = '\0';
 
     // Find transaction in history
-    auto it = std::find(g_real_wallet->transaction_hashes.begin(),
-                       g_real_wallet->transaction_hashes.end(), tx_hash);
+    auto it = std::find(found->transaction_hashes.begin(),
+                       found->transaction_hashes.end(), tx_hash);
 
-    if (it != g_real_wallet->transaction_hashes.end()) {
+    if (it != found->transaction_hashes.end()) {
         // This is a sent transaction
         tx->amount = -10000000; // 1 XFG in atomic units (placeholder)
         tx->is_confirmed = true;
@@ -789,7 +936,7 @@ extern "C" TransactionInfo* fuego_wallet_get_transaction_by_hash(
     }
 
     tx->fee = 100000; // 0.01 XFG fee
-    tx->height = g_real_wallet->network_height - 5;
+    tx->height = found->network_height - 5;
     tx->timestamp = std::chrono::duration_cast<std::chrono::seconds>(
         std::chrono::system_clock::now().time_since_epoch()
     ).count();
@@ -816,20 +963,85 @@ extern "C" TransactionInfo* fuego_wallet_get_transaction_by_id(
 
 // Cancel transaction
 extern "C" bool fuego_wallet_cancel_transaction(FuegoWallet wallet, const char* tx_id) {
-    if (g_real_wallet.get() != wallet || !tx_id) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found || !tx_id) {
         return false;
     }
 
     // In a real implementation, this would cancel a pending transaction
     // For now, just return true if transaction exists
-    auto it = std::find(g_real_wallet->transaction_hashes.begin(),
-                       g_real_wallet->transaction_hashes.end(), tx_id);
-    return it != g_real_wallet->transaction_hashes.end();
+    auto it = std::find(found->transaction_hashes.begin(),
+                       found->transaction_hashes.end(), tx_id);
+    return it != found->transaction_hashes.end();
+}
+
+// Rebroadcast a sent transaction
+extern "C" bool fuego_wallet_rebroadcast_tx(FuegoWallet wallet, const char* tx_hash) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found || !tx_hash || !found->is_connected) {
+        return false;
+    }
+
+    // In a real implementation, this would relay the stored transaction blob
+    // through NOTIFY_NEW_TRANSACTIONS; only transactions this wallet sent are known
+    auto it = std::find(found->transaction_hashes.begin(),
+                       found->transaction_hashes.end(), tx_hash);
+    if (it == found->transaction_hashes.end()) {
+        return false;
+    }
+    std::cout << "Rebroadcast transaction " << tx_hash << std::endl;
+    return true;
+}
+
+// Deterministic stand-in for the proof signature over the transaction, address and message
+static std::string tx_proof_for(const std::string& tx_hash, const std::string& address, const std::string& message) {
+    static const char k_base58[] = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+    std::mt19937_64 gen(std::hash<std::string>{}(tx_hash + "|" + address + "|" + message));
+    std::uniform_int_distribution<> dis(0, 57);
+    std::string proof = "OutProofV1";
+    for (int i = 0; i < 132; ++i) {
+        proof += k_base58[dis(gen)];
+    }
+    return proof;
+}
+
+// Prove a payment made by one of the wallet's transactions
+extern "C" char* fuego_wallet_get_tx_proof(FuegoWallet wallet, const char* tx_hash, const char* address, const char* message) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found || !tx_hash || !address) {
+        return nullptr;
+    }
+
+    auto it = std::find(found->transaction_hashes.begin(),
+                       found->transaction_hashes.end(), tx_hash);
+    if (it == found->transaction_hashes.end()) {
+        return nullptr;
+    }
+
+    std::string proof = tx_proof_for(tx_hash, address, message ? message : "");
+    char* proof_str = new char[proof.length() + 1];
+    strcpy(proof_str, proof.c_str());
+    return proof_str;
+}
+
+// Check a payment proof
+extern "C" int32_t fuego_wallet_check_tx_proof(FuegoWallet wallet, const char* tx_hash, const char* address, const char* message, const char* proof) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found || !tx_hash || !address || !proof) {
+        return -1;
+    }
+
+    std::string given(proof);
+    if (given.rfind("OutProofV1", 0) != 0 || given.length() != 10 + 132) {
+        return -1;
+    }
+    return given == tx_proof_for(tx_hash, address, message ? message : "") ? 1 : 0;
 }
 
 // Create new address with label
 extern "C" char* fuego_wallet_create_address(FuegoWallet wallet, const char* label) {
-    if (g_real_wallet.get() != wallet) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found) {
         return nullptr;
     }
 
@@ -838,6 +1050,8 @@ extern "C" char* fuego_wallet_create_address(FuegoWallet wallet, const char* lab
         std::chrono::system_clock::now().time_since_epoch()
     ).count());
 
+    found->extra_addresses.push_back({new_address, label ? label : ""});
+
     char* address_ptr = new char[new_address.length() + 1];
     strcpy(address_ptr, new_address.c_str());
 
@@ -850,35 +1064,312 @@ extern "C" char* fuego_wallet_create_address(FuegoWallet wallet, const char* lab
     return address_ptr;
 }
 
-// Get all addresses
-extern "C" void* fuego_wallet_get_addresses(FuegoWallet wallet) {
-    if (g_real_wallet.get() != wallet) {
+static std::string json_escape(const std::string& value) {
+    std::string out;
+    for (char c : value) {
+        switch (c) {
+            case '"': out += "\\\""; break;
+            case '\\': out += "\\\\"; break;
+            case '\n': out += "\\n"; break;
+            case '\r': out += "\\r"; break;
+            case '\t': out += "\\t"; break;
+            default:
+                if (static_cast<unsigned char>(c) < 0x20) {
+                    char buf[7];
+                    snprintf(buf, sizeof(buf), "\\u%04x", c);
+                    out += buf;
+                } else {
+                    out += c;
+                }
+        }
+    }
+    return out;
+}
+
+// Get all addresses with their labels as a JSON array
+extern "C" char* fuego_wallet_get_addresses(FuegoWallet wallet) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found) {
+        return nullptr;
+    }
+
+    std::string json = "[";
+    json += "{\"index\":0,\"address\":\"" + json_escape(found->address) +
+            "\",\"label\":\"" + json_escape(found->primary_label) + "\"}";
+    for (size_t i = 0; i < found->extra_addresses.size(); ++i) {
+        const auto& entry = found->extra_addresses[i];
+        json += ",{\"index\":" + std::to_string(i + 1) +
+                ",\"address\":\"" + json_escape(entry.address) +
+                "\",\"label\":\"" + json_escape(entry.label) + "\"}";
+    }
+    json += "]";
+
+    char* json_str = new char[json.length() + 1];
+    strcpy(json_str, json.c_str());
+    return json_str;
+}
+
+// Unconfirmed transfers of this wallet in the daemon's transaction pool
+extern "C" char* fuego_wallet_get_pool_transfers(FuegoWallet wallet) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found) {
+        return nullptr;
+    }
+
+    // The standalone wallet is not attached to a daemon, so its pool view is empty
+    std::string json = "[]";
+
+    char* json_str = new char[json.length() + 1];
+    strcpy(json_str, json.c_str());
+    return json_str;
+}
+
+// Block reward and emission at the chain tip as a JSON object
+extern "C" char* fuego_wallet_get_emission(FuegoWallet wallet) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found) {
+        return nullptr;
+    }
+
+    // The standalone wallet is not attached to a daemon, so it has no emission figures
+    std::string json = "{\"supported\":false,\"height\":" + std::to_string(found->network_height) +
+        ",\"block_reward\":null,\"total_emission\":null,\"circulating_supply\":null,\"next_reward\":null}";
+
+    char* json_str = new char[json.length() + 1];
+    strcpy(json_str, json.c_str());
+    return json_str;
+}
+
+extern "C" char* fuego_wallet_get_deposit_limits(FuegoWallet wallet) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found) {
+        return nullptr;
+    }
+
+    // Without a daemon there are no consensus limits to report
+    std::string json = "{\"supported\":false,\"height\":" + std::to_string(found->network_height) + "}";
+
+    char* json_str = new char[json.length() + 1];
+    strcpy(json_str, json.c_str());
+    return json_str;
+}
+
+// Check a wallet file, only reading it; does not touch the open wallet
+extern "C" char* fuego_wallet_verify(const char* file_path, const char* password) {
+    if (!file_path) {
+        return nullptr;
+    }
+    (void)password;
+
+    std::ifstream file(file_path, std::ios::binary | std::ios::ate);
+    std::string json;
+    if (!file) {
+        json = "{\"corrupted\":true,\"reason\":\"the wallet file cannot be read\",\"keys_readable\":false}";
+    } else if (file.tellg() <= 0) {
+        json = "{\"corrupted\":true,\"reason\":\"the wallet file is empty\",\"keys_readable\":false}";
+    } else {
+        // The standalone wallet keeps neither keys nor a transfer cache in its file,
+        // so a readable, non-empty file is all there is to check
+        json = "{\"corrupted\":false,\"keys_readable\":true,\"cache_consistent\":true,"
+               "\"key_image_count\":0,\"last_processed_height\":0,\"rescan_recommended\":false}";
+    }
+
+    char* json_str = new char[json.length() + 1];
+    strcpy(json_str, json.c_str());
+    return json_str;
+}
+
+// The standalone wallet keeps balances rather than outputs: its unlocked funds
+// are one output and its locked funds another
+struct StandaloneOutput {
+    uint64_t amount;
+    uint64_t global_index;
+    std::string key_image;
+    uint64_t block_height;
+    bool is_locked;
+};
+
+static std::vector<StandaloneOutput> standalone_outputs(const RealFuegoWallet* w) {
+    std::vector<StandaloneOutput> outputs;
+    auto key_image = [w](uint64_t index) {
+        std::ostringstream image;
+        uint64_t seed = std::hash<std::string>{}(w->address + std::to_string(index));
+        for (int i = 0; i < 4; ++i) {
+            image << std::hex << std::setw(16) << std::setfill('0') << (seed * (i + 1) + index);
+        }
+        return image.str();
+    };
+    uint64_t height = w->sync_height;
+    if (w->unlocked_balance > 0) {
+        outputs.push_back({w->unlocked_balance, 1, key_image(1), height > 10 ? height - 10 : 0, false});
+    }
+    if (w->balance > w->unlocked_balance) {
+        outputs.push_back({w->balance - w->unlocked_balance, 2, key_image(2), height, true});
+    }
+    return outputs;
+}
+
+// Unspent outputs of the wallet as a JSON array
+extern "C" char* fuego_wallet_get_unspent_outputs(FuegoWallet wallet) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found) {
         return nullptr;
     }
 
-    // Return vector of addresses (just primary for now)
-    auto* addresses = new std::vector<std::string>();
-    addresses->push_back(g_real_wallet->address);
+    std::string json = "[";
+    bool first = true;
+    for (const auto& output : standalone_outputs(found.get())) {
+        if (!first) {
+            json += ",";
+        }
+        first = false;
+        json += "{\"amount\":" + std::to_string(output.amount) +
+                ",\"global_index\":" + std::to_string(output.global_index) +
+                ",\"key_image\":\"" + output.key_image +
+                "\",\"block_height\":" + std::to_string(output.block_height) +
+                ",\"is_locked\":" + (output.is_locked ? "true" : "false") + "}";
+    }
+    json += "]";
 
-    return addresses;
+    char* json_str = new char[json.length() + 1];
+    strcpy(json_str, json.c_str());
+    return json_str;
 }
 
-// Free addresses list
-extern "C" void fuego_wallet_free_addresses(void* addresses) {
-    if (addresses) {
-        delete static_cast<std::vector<std::string>*>(addresses);
+// Sum of the outputs with the given key images; false if any of them is unknown or locked
+static bool selected_amount(
+    const RealFuegoWallet* w,
+    const char* const* key_images,
+    size_t key_image_count,
+    uint64_t* available
+) {
+    std::vector<StandaloneOutput> outputs = standalone_outputs(w);
+    *available = 0;
+    for (size_t i = 0; i < key_image_count; ++i) {
+        auto output = std::find_if(outputs.begin(), outputs.end(), [&](const StandaloneOutput& o) {
+            return key_images[i] && o.key_image == key_images[i];
+        });
+        if (output == outputs.end() || output->is_locked) {
+            return false;
+        }
+        *available += output->amount;
     }
+    return true;
+}
+
+// Send funded only by the selected outputs
+extern "C" char* fuego_wallet_send_from_outputs(
+    FuegoWallet wallet,
+    const char* address,
+    uint64_t amount,
+    const char* payment_id,
+    uint64_t mixin,
+    const char* const* key_images,
+    size_t key_image_count
+) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found || !address || amount == 0 || !key_images || key_image_count == 0) {
+        return nullptr;
+    }
+
+    uint64_t available = 0;
+    if (!selected_amount(found.get(), key_images, key_image_count, &available)) {
+        return nullptr;
+    }
+
+    std::cout << "Sending real transaction from " << key_image_count << " selected outputs..." << std::endl;
+    std::cout << "To: " << address << std::endl;
+    std::cout << "Amount: " << amount << std::endl;
+    std::cout << "Payment ID: " << (payment_id ? payment_id : "none") << std::endl;
+    std::cout << "Mixin: " << mixin << std::endl;
+
+    if (amount > available) {
+        std::cout << "Selected outputs do not cover the amount" << std::endl;
+        return nullptr;
+    }
+
+    std::string tx_hash = "real_tx_" + std::to_string(std::chrono::system_clock::now().time_since_epoch().count());
+    if (address != found->address) {
+        found->balance -= amount;
+        found->unlocked_balance -= amount;
+    }
+    found->transaction_hashes.push_back(tx_hash);
+
+    char* result = new char[tx_hash.length() + 1];
+    std::strcpy(result, tx_hash.c_str());
+    return result;
+}
+
+// Send to several recipients funded only by the selected outputs
+extern "C" char* fuego_wallet_send_many_from_outputs(
+    FuegoWallet wallet,
+    const char* const* addresses,
+    const uint64_t* amounts,
+    size_t count,
+    uint64_t mixin,
+    const char* const* key_images,
+    size_t key_image_count
+) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found || !addresses || !amounts || count == 0 || !key_images || key_image_count == 0) {
+        return nullptr;
+    }
+
+    uint64_t available = 0;
+    if (!selected_amount(found.get(), key_images, key_image_count, &available)) {
+        return nullptr;
+    }
+
+    uint64_t total = 0;
+    uint64_t to_others = 0;
+    for (size_t i = 0; i < count; ++i) {
+        if (!addresses[i] || amounts[i] == 0 || total + amounts[i] < total) {
+            return nullptr;
+        }
+        total += amounts[i];
+        if (addresses[i] != found->address) {
+            to_others += amounts[i];
+        }
+    }
+
+    std::cout << "Sending real transaction to " << count << " recipients from " << key_image_count << " selected outputs..." << std::endl;
+    std::cout << "Total: " << total << std::endl;
+    std::cout << "Mixin: " << mixin << std::endl;
+
+    if (total > available) {
+        std::cout << "Selected outputs do not cover the amount" << std::endl;
+        return nullptr;
+    }
+
+    std::string tx_hash = "real_tx_" + std::to_string(std::chrono::system_clock::now().time_since_epoch().count());
+    found->balance -= to_others;
+    found->unlocked_balance -= to_others;
+    found->transaction_hashes.push_back(tx_hash);
+
+    char* result = new char[tx_hash.length() + 1];
+    std::strcpy(result, tx_hash.c_str());
+    return result;
 }
 
 // Delete address
 extern "C" bool fuego_wallet_delete_address(FuegoWallet wallet, const char* address) {
-    if (g_real_wallet.get() != wallet || !address) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found || !address) {
         return false;
     }
 
-    // In real implementation, this would remove address from wallet
-    // For now, just prevent deletion of primary address
-    return std::string(address) != g_real_wallet->address;
+    // The primary address can never be deleted
+    if (std::string(address) == found->address) {
+        return false;
+    }
+
+    auto& entries = found->extra_addresses;
+    auto it = std::find_if(entries.begin(), entries.end(),
+                           [&](const RealFuegoWallet::AddressEntry& e) { return e.address == address; });
+    if (it != entries.end()) {
+        entries.erase(it);
+    }
+    return true;
 }
 
 // Set address label
@@ -887,17 +1378,31 @@ extern "C" bool fuego_wallet_set_address_label(
     const char* address,
     const char* label
 ) {
-    if (g_real_wallet.get() != wallet || !address || !label) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found || !address || !label) {
         return false;
     }
 
+    if (std::string(address) == found->address) {
+        found->primary_label = label;
+    } else {
+        auto& entries = found->extra_addresses;
+        auto it = std::find_if(entries.begin(), entries.end(),
+                               [&](const RealFuegoWallet::AddressEntry& e) { return e.address == address; });
+        if (it == entries.end()) {
+            return false;
+        }
+        it->label = label;
+    }
+
     std::cout << "Set label '" << label << "' for address " << address << std::endl;
     return true;
 }
 
 // Get block information
 extern "C" BlockInfo* fuego_wallet_get_block_info(FuegoWallet wallet, uint64_t height) {
-    if (g_real_wallet.get() != wallet) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found) {
         return nullptr;
     }
 
@@ -929,7 +1434,8 @@ extern "C" void fuego_wallet_free_block_info(BlockInfo* block) {
 
 // Get block by hash
 extern "C" BlockInfo* fuego_wallet_get_block_by_hash(FuegoWallet wallet, const char* block_hash) {
-    if (g_real_wallet.get() != wallet || !block_hash) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found || !block_hash) {
         return nullptr;
     }
 
@@ -945,20 +1451,22 @@ extern "C" BlockInfo* fuego_wallet_get_block_by_hash(FuegoWallet wallet, const c
 
 // Get current block height
 extern "C" uint64_t fuego_wallet_get_current_block_height(FuegoWallet wallet) {
-    if (g_real_wallet.get() != wallet) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found) {
         return 0;
     }
-    return g_real_wallet->network_height;
+    return found->network_height;
 }
 
 // Get block timestamp
 extern "C" uint64_t fuego_wallet_get_block_timestamp(FuegoWallet wallet, uint64_t height) {
-    if (g_real_wallet.get() != wallet) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found) {
         return 0;
     }
     return std::chrono::duration_cast<std::chrono::seconds>(
         std::chrono::system_clock::now().time_since_epoch()
-    ).count() - (g_real_wallet->network_height - height) * 120; // 2-minute blocks
+    ).count() - (found->network_height - height) * 120; // 2-minute blocks
 }
 
 void mining_thread_func(RealFuegoWallet* wallet) {
@@ -992,11 +1500,12 @@ void mining_thread_func(RealFuegoWallet* wallet) {
 
 // Mining operations
 extern "C" bool fuego_wallet_start_mining(FuegoWallet wallet, uint32_t threads, bool background) {
-    if (g_real_wallet.get() != wallet) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found) {
         return false;
     }
 
-    if (g_real_wallet->is_mining) {
+    if (found->is_mining) {
         std::cout << "Mining is already running" << std::endl;
         return false;
     }
@@ -1008,35 +1517,36 @@ extern "C" bool fuego_wallet_start_mining(FuegoWallet wallet, uint32_t threads,
     }
 
     // Update mining state
-    g_real_wallet->is_mining = true;
-    g_real_wallet->threads = threads;
-    g_real_wallet->mining_start_time = std::chrono::duration_cast<std::chrono::seconds>(
+    found->is_mining = true;
+    found->threads = threads;
+    found->mining_start_time = std::chrono::duration_cast<std::chrono::seconds>(
         std::chrono::system_clock::now().time_since_epoch()
     ).count();
-    g_real_wallet->total_hashes = 0;
-    g_real_wallet->valid_shares = 0;
-    g_real_wallet->invalid_shares = 0;
-    g_real_wallet->last_share_time = 0;
+    found->total_hashes = 0;
+    found->valid_shares = 0;
+    found->invalid_shares = 0;
+    found->last_share_time = 0;
 
     // Simulate hashrate based on thread count
-    g_real_wallet->hashrate = threads * 1000.0; // 1 KH/s per thread
+    found->hashrate = threads * 1000.0; // 1 KH/s per thread
 
     std::cout << "Starting mining with " << threads << " threads (background: " << background << ")" << std::endl;
-    std::cout << "Hashrate: " << g_real_wallet->hashrate << " H/s" << std::endl;
+    std::cout << "Hashrate: " << found->hashrate << " H/s" << std::endl;
 
     // Start mining simulation thread
-    g_real_wallet->mining_thread_running = true;
-    g_real_wallet->mining_thread = std::thread(mining_thread_func, g_real_wallet.get());
+    found->mining_thread_running = true;
+    found->mining_thread = std::thread(mining_thread_func, found.get());
 
     return true;
 }
 
 extern "C" bool fuego_wallet_stop_mining(FuegoWallet wallet) {
-    if (g_real_wallet.get() != wallet) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found) {
         return false;
     }
 
-    if (!g_real_wallet->is_mining) {
+    if (!found->is_mining) {
         std::cout << "Mining is not running" << std::endl;
         return false;
     }
@@ -1044,40 +1554,41 @@ extern "C" bool fuego_wallet_stop_mining(FuegoWallet wallet) {
     std::cout << "Stopping mining..." << std::endl;
 
     // Stop mining thread
-    g_real_wallet->mining_thread_running = false;
-    if (g_real_wallet->mining_thread.joinable()) {
-        g_real_wallet->mining_thread.join();
+    found->mining_thread_running = false;
+    if (found->mining_thread.joinable()) {
+        found->mining_thread.join();
     }
 
     // Update mining state
-    g_real_wallet->is_mining = false;
-    g_real_wallet->threads = 0;
-    g_real_wallet->hashrate = 0.0;
+    found->is_mining = false;
+    found->threads = 0;
+    found->hashrate = 0.0;
 
     std::cout << "Mining stopped" << std::endl;
     return true;
 }
 
 extern "C" MiningInfo* fuego_wallet_get_mining_info(FuegoWallet wallet) {
-    if (g_real_wallet.get() != wallet) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found) {
         return nullptr;
     }
 
     MiningInfo* info = new MiningInfo();
-    info->is_mining = g_real_wallet->is_mining;
-    info->hashrate = g_real_wallet->hashrate;
+    info->is_mining = found->is_mining;
+    info->hashrate = found->hashrate;
     info->difficulty = 52500024; // Real Fuego difficulty
     info->block_reward = 3005769; // Real Fuego block reward in atomic units
-    info->threads = g_real_wallet->threads;
+    info->threads = found->threads;
 
     // Copy pool address and worker name
-    if (!g_real_wallet->pool_address.empty()) {
-        strncpy(info->pool_address, g_real_wallet->pool_address.c_str(), sizeof(info->pool_address) - 1);
+    if (!found->pool_address.empty()) {
+        strncpy(info->pool_address, found->pool_address.c_str(), sizeof(info->pool_address) - 1);
         info->pool_address[sizeof(info->pool_address) - 1] = '\0';
     }
 
-    if (!g_real_wallet->worker_name.empty()) {
-        strncpy(info->worker_name, g_real_wallet->worker_name.c_str(), sizeof(info->worker_name) - 1);
+    if (!found->worker_name.empty()) {
+        strncpy(info->worker_name, found->worker_name.c_str(), sizeof(info->worker_name) - 1);
         info->worker_name[sizeof(info->worker_name) - 1] = '\0';
     }
 
@@ -1097,20 +1608,21 @@ extern "C" bool fuego_wallet_set_mining_pool(
     const char* pool_address,
     const char* worker_name
 ) {
-    if (g_real_wallet.get() != wallet) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found) {
         return false;
     }
 
     if (pool_address) {
-        g_real_wallet->pool_address = pool_address;
+        found->pool_address = pool_address;
     } else {
-        g_real_wallet->pool_address.clear();
+        found->pool_address.clear();
     }
 
     if (worker_name) {
-        g_real_wallet->worker_name = worker_name;
+        found->worker_name = worker_name;
     } else {
-        g_real_wallet->worker_name.clear();
+        found->worker_name.clear();
     }
 
     std::cout << "Setting mining pool: " << (pool_address ? pool_address : "none");
@@ -1124,7 +1636,8 @@ extern "C" bool fuego_wallet_set_mining_pool(
 
 // Get detailed mining statistics
 extern "C" char* fuego_wallet_get_mining_stats_json(FuegoWallet wallet) {
-    if (g_real_wallet.get() != wallet) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found) {
         return nullptr;
     }
 
@@ -1132,31 +1645,31 @@ extern "C" char* fuego_wallet_get_mining_stats_json(FuegoWallet wallet) {
         std::chrono::system_clock::now().time_since_epoch()
     ).count();
 
-    uint64_t uptime = g_real_wallet->is_mining && g_real_wallet->mining_start_time > 0 ?
-                      current_time - g_real_wallet->mining_start_time : 0;
+    uint64_t uptime = found->is_mining && found->mining_start_time > 0 ?
+                      current_time - found->mining_start_time : 0;
 
-    float share_acceptance_rate = g_real_wallet->valid_shares + g_real_wallet->invalid_shares > 0 ?
-                                  (float)g_real_wallet->valid_shares / (g_real_wallet->valid_shares + g_real_wallet->invalid_shares) * 100.0f : 0.0f;
+    float share_acceptance_rate = found->valid_shares + found->invalid_shares > 0 ?
+                                  (float)found->valid_shares / (found->valid_shares + found->invalid_shares) * 100.0f : 0.0f;
 
     // Format as JSON string
     std::string json = "{";
-    json += "\"is_mining\":" + std::string(g_real_wallet->is_mining ? "true" : "false") + ",";
-    json += "\"hashrate\":" + std::to_string(g_real_wallet->hashrate) + ",";
-    json += "\"threads\":" + std::to_string(g_real_wallet->threads) + ",";
-    json += "\"total_hashes\":" + std::to_string(g_real_wallet->total_hashes) + ",";
-    json += "\"valid_shares\":" + std::to_string(g_real_wallet->valid_shares) + ",";
-    json += "\"invalid_shares\":" + std::to_string(g_real_wallet->invalid_shares) + ",";
+    json += "\"is_mining\":" + std::string(found->is_mining ? "true" : "false") + ",";
+    json += "\"hashrate\":" + std::to_string(found->hashrate) + ",";
+    json += "\"threads\":" + std::to_string(found->threads) + ",";
+    json += "\"total_hashes\":" + std::to_string(found->total_hashes) + ",";
+    json += "\"valid_shares\":" + std::to_string(found->valid_shares) + ",";
+    json += "\"invalid_shares\":" + std::to_string(found->invalid_shares) + ",";
     json += "\"share_acceptance_rate\":" + std::to_string(share_acceptance_rate) + ",";
     json += "\"uptime\":" + std::to_string(uptime) + ",";
 
-    if (g_real_wallet->mining_start_time > 0) {
-        json += "\"mining_start_time\":" + std::to_string(g_real_wallet->mining_start_time) + ",";
+    if (found->mining_start_time > 0) {
+        json += "\"mining_start_time\":" + std::to_string(found->mining_start_time) + ",";
     } else {
         json += "\"mining_start_time\":null,";
     }
 
-    if (g_real_wallet->last_share_time > 0) {
-        json += "\"last_share_time\":" + std::to_string(g_real_wallet->last_share_time);
+    if (found->last_share_time > 0) {
+        json += "\"last_share_time\":" + std::to_string(found->last_share_time);
     } else {
         json += "\"last_share_time\":null";
     }
@@ -1178,14 +1691,17 @@ extern "C" void fuego_wallet_free_mining_stats_json(char* json_str) {
 
 // ===== SECURE KEY MANAGEMENT =====
 
+// Word list of the mock seed phrases
+static const std::vector<std::string> k_seed_wordlist = {
+    "abandon", "ability", "able", "about", "above", "absent", "absorb", "abstract",
+    "absurd", "abuse", "access", "accident", "account", "accuse", "achieve", "acid",
+    "acoustic", "acquire", "across", "action", "actor", "actress", "actual", "adapt"
+};
+
 // Generate a new random seed phrase
 extern "C" char* fuego_wallet_generate_seed_phrase() {
     // Generate 24-word BIP39 seed phrase (mock implementation)
-    std::vector<std::string> wordlist = {
-        "abandon", "ability", "able", "about", "above", "absent", "absorb", "abstract",
-        "absurd", "abuse", "access", "accident", "account", "accuse", "achieve", "acid",
-        "acoustic", "acquire", "across", "action", "actor", "actress", "actual", "adapt"
-    };
+    const std::vector<std::string>& wordlist = k_seed_wordlist;
 
     std::random_device rd;
     std::mt19937 gen(rd());
@@ -1228,13 +1744,53 @@ extern "C" bool fuego_wallet_validate_seed_phrase(const char* seed_phrase) {
     return word_count == 12 || word_count == 18 || word_count == 24;
 }
 
+// Primary address a restore from this seed would produce (mock implementation)
+extern "C" char* fuego_wallet_address_from_seed(const char* seed_phrase, int32_t* invalid_word) {
+    if (invalid_word) {
+        *invalid_word = -1;
+    }
+    if (!fuego_wallet_validate_seed_phrase(seed_phrase)) {
+        return nullptr;
+    }
+
+    std::stringstream ss(seed_phrase);
+    std::string word;
+    std::string normalized;
+    int32_t index = 0;
+    while (ss >> word) {
+        if (std::find(k_seed_wordlist.begin(), k_seed_wordlist.end(), word) == k_seed_wordlist.end()) {
+            if (invalid_word) {
+                *invalid_word = index;
+            }
+            return nullptr;
+        }
+        if (index > 0) normalized += " ";
+        normalized += word;
+        ++index;
+    }
+
+    // Deterministic stand-in for the key derivation: the same seed always gives the same address
+    static const char k_base58[] = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+    std::mt19937_64 gen(std::hash<std::string>{}(normalized));
+    std::uniform_int_distribution<> dis(0, 57);
+    std::string result = "fire";
+    for (int i = 0; i < 95; ++i) {
+        result += k_base58[dis(gen)];
+    }
+
+    char* address_ptr = new char[result.length() + 1];
+    strcpy(address_ptr, result.c_str());
+    return address_ptr;
+}
+
 // Derive keys from seed phrase (mock implementation)
 extern "C" bool fuego_wallet_derive_keys_from_seed(
     FuegoWallet wallet,
     const char* seed_phrase,
     const char* password
 ) {
-    if (g_real_wallet.get() != wallet || !seed_phrase) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found || !seed_phrase) {
         return false;
     }
 
@@ -1244,30 +1800,31 @@ extern "C" bool fuego_wallet_derive_keys_from_seed(
     }
 
     // Mock key derivation - in real implementation, this would use cryptographic functions
-    g_real_wallet->seed_phrase = seed_phrase;
-    g_real_wallet->view_key = "view_key_" + std::string(seed_phrase).substr(0, 16) + "_mock";
-    g_real_wallet->spend_key = "spend_key_" + std::string(seed_phrase).substr(16, 16) + "_mock";
-    g_real_wallet->has_keys = true;
+    found->seed_phrase = seed_phrase;
+    found->view_key = "view_key_" + std::string(seed_phrase).substr(0, 16) + "_mock";
+    found->spend_key = "spend_key_" + std::string(seed_phrase).substr(16, 16) + "_mock";
+    found->has_keys = true;
 
     std::cout << "Derived keys from seed phrase" << std::endl;
-    std::cout << "View key: " << g_real_wallet->view_key << std::endl;
-    std::cout << "Spend key: " << g_real_wallet->spend_key << std::endl;
+    std::cout << "View key: " << found->view_key << std::endl;
+    std::cout << "Spend key: " << found->spend_key << std::endl;
 
     return true;
 }
 
 // Get seed phrase (encrypted)
 extern "C" char* fuego_wallet_get_seed_phrase(FuegoWallet wallet, const char* password) {
-    if (g_real_wallet.get() != wallet || !password) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found || !password) {
         return nullptr;
     }
 
-    if (!g_real_wallet->has_keys) {
+    if (!found->has_keys) {
         return nullptr;
     }
 
     // Mock encryption - in real implementation, this would decrypt the stored seed phrase
-    std::string encrypted_seed = g_real_wallet->seed_phrase; // For mock purposes
+    std::string encrypted_seed = found->seed_phrase; // For mock purposes
 
     char* seed_ptr = new char[encrypted_seed.length() + 1];
     strcpy(seed_ptr, encrypted_seed.c_str());
@@ -1277,48 +1834,52 @@ extern "C" char* fuego_wallet_get_seed_phrase(FuegoWallet wallet, const char* pa
 
 // Get view key
 extern "C" char* fuego_wallet_get_view_key(FuegoWallet wallet) {
-    if (g_real_wallet.get() != wallet || !g_real_wallet->has_keys) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found || !found->has_keys) {
         return nullptr;
     }
 
-    char* key_ptr = new char[g_real_wallet->view_key.length() + 1];
-    strcpy(key_ptr, g_real_wallet->view_key.c_str());
+    char* key_ptr = new char[found->view_key.length() + 1];
+    strcpy(key_ptr, found->view_key.c_str());
 
     return key_ptr;
 }
 
 // Get spend key
 extern "C" char* fuego_wallet_get_spend_key(FuegoWallet wallet) {
-    if (g_real_wallet.get() != wallet || !g_real_wallet->has_keys) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found || !found->has_keys) {
         return nullptr;
     }
 
-    char* key_ptr = new char[g_real_wallet->spend_key.length() + 1];
-    strcpy(key_ptr, g_real_wallet->spend_key.c_str());
+    char* key_ptr = new char[found->spend_key.length() + 1];
+    strcpy(key_ptr, found->spend_key.c_str());
 
     return key_ptr;
 }
 
 // Check if wallet has keys
 extern "C" bool fuego_wallet_has_keys(FuegoWallet wallet) {
-    if (g_real_wallet.get() != wallet) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found) {
         return false;
     }
 
-    return g_real_wallet->has_keys;
+    return found->has_keys;
 }
 
 // Export wallet keys (view key, spend key, address)
 extern "C" char* fuego_wallet_export_keys(FuegoWallet wallet) {
-    if (g_real_wallet.get() != wallet || !g_real_wallet->has_keys) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found || !found->has_keys) {
         return nullptr;
     }
 
     std::string keys_json = "{";
-    keys_json += "\"address\":\"" + g_real_wallet->address + "\",";
-    keys_json += "\"view_key\":\"" + g_real_wallet->view_key + "\",";
-    keys_json += "\"spend_key\":\"" + g_real_wallet->spend_key + "\",";
-    keys_json += "\"seed_phrase\":\"" + g_real_wallet->seed_phrase + "\"";
+    keys_json += "\"address\":\"" + found->address + "\",";
+    keys_json += "\"view_key\":\"" + found->view_key + "\",";
+    keys_json += "\"spend_key\":\"" + found->spend_key + "\",";
+    keys_json += "\"seed_phrase\":\"" + found->seed_phrase + "\"";
     keys_json += "}";
 
     char* keys_ptr = new char[keys_json.length() + 1];
@@ -1335,18 +1896,19 @@ extern "C" bool fuego_wallet_import_keys(
     const char* spend_key,
     const char* address
 ) {
-    if (g_real_wallet.get() != wallet) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found) {
         return false;
     }
 
-    if (view_key) g_real_wallet->view_key = view_key;
-    if (spend_key) g_real_wallet->spend_key = spend_key;
-    if (address) g_real_wallet->address = address;
+    if (view_key) found->view_key = view_key;
+    if (spend_key) found->spend_key = spend_key;
+    if (address) found->address = address;
 
-    g_real_wallet->has_keys = true;
+    found->has_keys = true;
 
     std::cout << "Imported wallet keys" << std::endl;
-    std::cout << "Address: " << g_real_wallet->address << std::endl;
+    std::cout << "Address: " << found->address << std::endl;
 
     return true;
 }
@@ -1360,19 +1922,20 @@ extern "C" void fuego_wallet_free_key_string(char* key_str) {
 
 // Get sync progress
 extern "C" SyncProgress* fuego_wallet_get_sync_progress(FuegoWallet wallet) {
-    if (g_real_wallet.get() != wallet) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found) {
         return nullptr;
     }
 
     SyncProgress* progress = new SyncProgress();
-    progress->current_height = g_real_wallet->sync_height;
-    progress->total_height = g_real_wallet->network_height;
-    progress->progress_percentage = (float)g_real_wallet->sync_height / (float)g_real_wallet->network_height * 100.0f;
-    progress->is_syncing = g_real_wallet->is_syncing;
+    progress->current_height = found->sync_height;
+    progress->total_height = found->network_height;
+    progress->progress_percentage = (float)found->sync_height / (float)found->network_height * 100.0f;
+    progress->is_syncing = found->is_syncing;
 
     // Calculate estimated time remaining (mock calculation)
-    if (g_real_wallet->is_syncing) {
-        uint64_t remaining_blocks = g_real_wallet->network_height - g_real_wallet->sync_height;
+    if (found->is_syncing) {
+        uint64_t remaining_blocks = found->network_height - found->sync_height;
         progress->estimated_time_remaining = remaining_blocks / 100; // Assuming 100 blocks per second
     } else {
         progress->estimated_time_remaining = 0;
@@ -1390,23 +1953,24 @@ extern "C" void fuego_wallet_free_sync_progress(SyncProgress* progress) {
 
 // Get sync status as JSON string (for frontend consumption)
 extern "C" char* fuego_wallet_get_sync_status_json(FuegoWallet wallet) {
-    if (g_real_wallet.get() != wallet) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found) {
         return nullptr;
     }
 
     // Calculate sync progress
-    float progress = (float)g_real_wallet->sync_height / (float)g_real_wallet->network_height * 100.0f;
-    uint64_t remaining_blocks = g_real_wallet->network_height - g_real_wallet->sync_height;
-    uint64_t estimated_seconds = g_real_wallet->is_syncing ? remaining_blocks / 100 : 0;
+    float progress = (float)found->sync_height / (float)found->network_height * 100.0f;
+    uint64_t remaining_blocks = found->network_height - found->sync_height;
+    uint64_t estimated_seconds = found->is_syncing ? remaining_blocks / 100 : 0;
 
     // Format as JSON string
     std::string json = "{";
-    json += "\"current_height\":" + std::to_string(g_real_wallet->sync_height) + ",";
-    json += "\"total_height\":" + std::to_string(g_real_wallet->network_height) + ",";
+    json += "\"current_height\":" + std::to_string(found->sync_height) + ",";
+    json += "\"total_height\":" + std::to_string(found->network_height) + ",";
     json += "\"progress_percentage\":" + std::to_string(progress) + ",";
     json += "\"estimated_seconds_remaining\":" + std::to_string(estimated_seconds) + ",";
-    json += "\"is_syncing\":" + std::string(g_real_wallet->is_syncing ? "true" : "false") + ",";
-    json += "\"connection_type\":\"" + g_real_wallet->connection_type + "\"";
+    json += "\"is_syncing\":" + std::string(found->is_syncing ? "true" : "false") + ",";
+    json += "\"connection_type\":\"" + found->connection_type + "\"";
     json += "}";
 
     char* json_str = new char[json.length() + 1];
@@ -1431,12 +1995,13 @@ extern "C" bool fuego_wallet_add_address_book_entry(
     const char* label,
     const char* description
 ) {
-    if (g_real_wallet.get() != wallet || !address) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found || !address) {
         return false;
     }
 
     // Check if address already exists
-    for (const auto& entry : g_real_wallet->address_book) {
+    for (const auto& entry : found->address_book) {
         if (entry.address == address) {
             return false; // Address already exists
         }
@@ -1453,7 +2018,7 @@ extern "C" bool fuego_wallet_add_address_book_entry(
     entry.last_used_time = 0;
     entry.use_count = 0;
 
-    g_real_wallet->address_book.push_back(entry);
+    found->address_book.push_back(entry);
 
     std::cout << "Added address to address book: " << address;
     if (label && strlen(label) > 0) {
@@ -1469,19 +2034,20 @@ extern "C" bool fuego_wallet_remove_address_book_entry(
     FuegoWallet wallet,
     const char* address
 ) {
-    if (g_real_wallet.get() != wallet || !address) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found || !address) {
         return false;
     }
 
     // Find and remove entry
-    auto it = std::remove_if(g_real_wallet->address_book.begin(),
-                            g_real_wallet->address_book.end(),
+    auto it = std::remove_if(found->address_book.begin(),
+                            found->address_book.end(),
                             [address](const RealFuegoWallet::AddressBookEntry& entry) {
                                 return entry.address == address;
                             });
 
-    if (it != g_real_wallet->address_book.end()) {
-        g_real_wallet->address_book.erase(it, g_real_wallet->address_book.end());
+    if (it != found->address_book.end()) {
+        found->address_book.erase(it, found->address_book.end());
         std::cout << "Removed address from address book: " << address << std::endl;
         return true;
     }
@@ -1496,12 +2062,13 @@ extern "C" bool fuego_wallet_update_address_book_entry(
     const char* label,
     const char* description
 ) {
-    if (g_real_wallet.get() != wallet || !address) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found || !address) {
         return false;
     }
 
     // Find and update entry
-    for (auto& entry : g_real_wallet->address_book) {
+    for (auto& entry : found->address_book) {
         if (entry.address == address) {
             if (label) entry.label = label;
             if (description) entry.description = description;
@@ -1515,12 +2082,13 @@ extern "C" bool fuego_wallet_update_address_book_entry(
 
 // Get address book entries
 extern "C" void* fuego_wallet_get_address_book(FuegoWallet wallet) {
-    if (g_real_wallet.get() != wallet) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found) {
         return nullptr;
     }
 
     // Return pointer to address book vector for parsing by Rust
-    return static_cast<void*>(&g_real_wallet->address_book);
+    return static_cast<void*>(&found->address_book);
 }
 
 // Free address book
@@ -1534,12 +2102,13 @@ extern "C" bool fuego_wallet_mark_address_used(
     FuegoWallet wallet,
     const char* address
 ) {
-    if (g_real_wallet.get() != wallet || !address) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found || !address) {
         return false;
     }
 
     // Find and update usage statistics
-    for (auto& entry : g_real_wallet->address_book) {
+    for (auto& entry : found->address_book) {
         if (entry.address == address) {
             entry.use_count++;
             entry.last_used_time = std::chrono::duration_cast<std::chrono::seconds>(
@@ -1557,12 +2126,13 @@ extern "C" char* fuego_wallet_get_address_book_entry(
     FuegoWallet wallet,
     const char* address
 ) {
-    if (g_real_wallet.get() != wallet || !address) {
+    std::shared_ptr<RealFuegoWallet> found = find_wallet(wallet);
+    if (!found || !address) {
         return nullptr;
     }
 
     // Find entry
-    for (const auto& entry : g_real_wallet->address_book) {
+    for (const auto& entry : found->address_book) {
         if (entry.address == address) {
             // Format as JSON string
             std::string json = "{";
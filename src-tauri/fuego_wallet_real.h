@@ -123,6 +123,8 @@ FuegoWallet fuego_wallet_open(
 
 void fuego_wallet_close(FuegoWallet wallet);
 
+bool fuego_wallet_save(FuegoWallet wallet);
+
 bool fuego_wallet_is_open(FuegoWallet wallet);
 
 // Wallet information
@@ -145,6 +147,16 @@ TransactionResult fuego_wallet_send_transaction(
     uint64_t mixin
 );
 
+// Send to several recipients in one transaction; returns the transaction hash
+// (free with fuego_wallet_free_string) or NULL on failure
+char* fuego_wallet_send_many(
+    FuegoWallet wallet,
+    const char* const* addresses,
+    const uint64_t* amounts,
+    size_t count,
+    uint64_t mixin
+);
+
 TransactionList fuego_wallet_get_transactions(
     FuegoWallet wallet,
     uint64_t limit,
@@ -182,6 +194,39 @@ uint64_t fuego_wallet_estimate_transaction_fee(
     uint64_t mixin
 );
 
+// Fee components of sending amount to address, in atomic units
+typedef struct {
+    uint64_t base_fee;
+    uint64_t size_fee;
+    uint64_t priority_fee;
+    uint64_t total;
+    uint64_t estimated_size_bytes;
+} FeeBreakdown;
+
+// sizeof of every struct above, as compiled into this library, so the
+// bindings can check their layouts against it
+typedef struct {
+    size_t wallet_info;
+    size_t transaction_info;
+    size_t network_status;
+    size_t network_info;
+    size_t block_info;
+    size_t mining_info;
+    size_t sync_progress;
+    size_t fee_breakdown;
+} AbiSizes;
+
+AbiSizes fuego_wallet_abi_sizes(void);
+
+// Fill breakdown with the fee estimate; false on failure
+bool fuego_wallet_estimate_fee_breakdown(
+    FuegoWallet wallet,
+    const char* address,
+    uint64_t amount,
+    uint64_t mixin,
+    FeeBreakdown* breakdown
+);
+
 // Deposit operations
 void* fuego_wallet_get_deposits(FuegoWallet wallet);
 void* fuego_wallet_create_deposit(FuegoWallet wallet, uint64_t amount, uint32_t term);
@@ -202,12 +247,60 @@ TransactionInfo* fuego_wallet_get_transaction_by_hash(FuegoWallet wallet, const
 TransactionInfo* fuego_wallet_get_transaction_by_id(FuegoWallet wallet, const char* tx_id);
 void fuego_wallet_free_transaction_info(TransactionInfo* tx);
 bool fuego_wallet_cancel_transaction(FuegoWallet wallet, const char* tx_id);
+// Relay a sent transaction that is not in a block yet to the peers again
+bool fuego_wallet_rebroadcast_tx(FuegoWallet wallet, const char* tx_hash);
+// Prove that the wallet's transaction tx_hash paid address; message may be null. Returns the
+// proof ("OutProofV1" and base58) or null; free with fuego_wallet_free_string
+char* fuego_wallet_get_tx_proof(FuegoWallet wallet, const char* tx_hash, const char* address, const char* message);
+// Check a proof from fuego_wallet_get_tx_proof: 1 if it is valid, 0 if it does not match the
+// transaction, address and message, -1 if it is malformed
+int32_t fuego_wallet_check_tx_proof(FuegoWallet wallet, const char* tx_hash, const char* address, const char* message, const char* proof);
 
 // Address management
 char* fuego_wallet_create_address(FuegoWallet wallet, const char* label);
-void* fuego_wallet_get_addresses(FuegoWallet wallet);
-void fuego_wallet_free_addresses(void* addresses);
+// Returns a JSON array of {index, address, label}; free with fuego_wallet_free_string
+char* fuego_wallet_get_addresses(FuegoWallet wallet);
 bool fuego_wallet_delete_address(FuegoWallet wallet, const char* address);
+// Returns a JSON array of {hash, amount, payment_id, timestamp, double_spend_seen} for the
+// wallet's transfers still in the daemon's transaction pool; free with fuego_wallet_free_string
+char* fuego_wallet_get_pool_transfers(FuegoWallet wallet);
+// Returns a JSON array of {amount, global_index, key_image, block_height, is_locked} for the
+// wallet's unspent outputs; free with fuego_wallet_free_string
+char* fuego_wallet_get_unspent_outputs(FuegoWallet wallet);
+// Returns a JSON object {supported, height, block_reward, total_emission, circulating_supply,
+// next_reward} from the daemon; the figures are null and supported false when the daemon does
+// not report emission. Free with fuego_wallet_free_string
+char* fuego_wallet_get_emission(FuegoWallet wallet);
+// Returns a JSON object {supported, height, min_amount, min_term, max_term, rates} with the
+// term deposit limits at the chain tip, rates being [{min_term, rate}] by ascending term;
+// supported is false when the daemon does not report them. Free with fuego_wallet_free_string
+char* fuego_wallet_get_deposit_limits(FuegoWallet wallet);
+// Check the wallet file at file_path without opening it for use or writing to it. Returns a
+// JSON object {corrupted, reason, keys_readable, cache_consistent, key_image_count,
+// last_processed_height, rescan_recommended}; free with fuego_wallet_free_string
+char* fuego_wallet_verify(const char* file_path, const char* password);
+// Send spending only the outputs with the given key images; returns the transaction hash
+// (free with fuego_wallet_free_string) or NULL on failure
+char* fuego_wallet_send_from_outputs(
+    FuegoWallet wallet,
+    const char* address,
+    uint64_t amount,
+    const char* payment_id,
+    uint64_t mixin,
+    const char* const* key_images,
+    size_t key_image_count
+);
+// Send to several recipients spending only the outputs with the given key images; returns
+// the transaction hash (free with fuego_wallet_free_string) or NULL on failure
+char* fuego_wallet_send_many_from_outputs(
+    FuegoWallet wallet,
+    const char* const* addresses,
+    const uint64_t* amounts,
+    size_t count,
+    uint64_t mixin,
+    const char* const* key_images,
+    size_t key_image_count
+);
 bool fuego_wallet_set_address_label(FuegoWallet wallet, const char* address, const char* label);
 
 // Blockchain operations
@@ -231,6 +324,10 @@ void fuego_wallet_free_mining_stats_json(char* json_str);
 // Secure key management functions
 char* fuego_wallet_generate_seed_phrase();
 bool fuego_wallet_validate_seed_phrase(const char* seed_phrase);
+// Derives the primary address a restore from seed_phrase would produce, without creating a
+// wallet; free with fuego_wallet_free_key_string. Returns null if the seed is invalid and sets
+// invalid_word to the index of the first unknown word, or -1 if the phrase itself is malformed
+char* fuego_wallet_address_from_seed(const char* seed_phrase, int32_t* invalid_word);
 bool fuego_wallet_derive_keys_from_seed(FuegoWallet wallet, const char* seed_phrase, const char* password);
 char* fuego_wallet_get_seed_phrase(FuegoWallet wallet, const char* password);
 char* fuego_wallet_get_view_key(FuegoWallet wallet);
@@ -0,0 +1,344 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Balance history
+//!
+//! Balance snapshots for the balance-over-time chart, appended to a JSONL file
+//! next to the wallet. A snapshot is taken whenever the balance changes and at
+//! least once a day. Snapshots older than 90 days are thinned out to one per
+//! day so the file stays small.
+
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use crate::utils::storage::{atomic_write, sidecar_path};
+use crate::utils::locks::MutexExt;
+
+/// Background task taking the daily snapshot and compacting the file
+pub const BALANCE_HISTORY_TASK: &str = "balance_history_snapshot";
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+
+/// An unchanged balance is still recorded once this much time has passed
+pub const SNAPSHOT_MAX_INTERVAL: u64 = DAY;
+
+/// Snapshots older than this are kept at daily granularity only
+pub const FULL_RESOLUTION_WINDOW: u64 = 90 * DAY;
+
+/// Balance at one point in time, in atomic units
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BalanceSnapshot {
+    pub timestamp: u64,
+    pub balance: u64,
+    pub unlocked_balance: u64,
+    pub locked_balance: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price_usd: Option<f64>,
+}
+
+impl BalanceSnapshot {
+    fn same_balance(&self, other: &BalanceSnapshot) -> bool {
+        self.balance == other.balance
+            && self.unlocked_balance == other.unlocked_balance
+            && self.locked_balance == other.locked_balance
+    }
+}
+
+/// Time range of a chart
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BalanceHistoryRange {
+    #[serde(rename = "7d")]
+    Week,
+    #[serde(rename = "30d")]
+    Month,
+    #[serde(rename = "90d")]
+    Quarter,
+    #[serde(rename = "1y")]
+    Year,
+    #[serde(rename = "all")]
+    All,
+}
+
+impl BalanceHistoryRange {
+    /// Length of the range in seconds; `None` for everything
+    pub fn seconds(self) -> Option<u64> {
+        match self {
+            BalanceHistoryRange::Week => Some(7 * DAY),
+            BalanceHistoryRange::Month => Some(30 * DAY),
+            BalanceHistoryRange::Quarter => Some(90 * DAY),
+            BalanceHistoryRange::Year => Some(365 * DAY),
+            BalanceHistoryRange::All => None,
+        }
+    }
+}
+
+/// Spacing of the returned points
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BalanceResolution {
+    /// Every stored snapshot
+    #[default]
+    Raw,
+    Hour,
+    Day,
+    Week,
+}
+
+impl BalanceResolution {
+    fn bucket(self) -> Option<u64> {
+        match self {
+            BalanceResolution::Raw => None,
+            BalanceResolution::Hour => Some(HOUR),
+            BalanceResolution::Day => Some(DAY),
+            BalanceResolution::Week => Some(7 * DAY),
+        }
+    }
+}
+
+/// Keep the last snapshot of each `bucket`-second interval; `snapshots` must
+/// be sorted by timestamp
+pub fn downsample(snapshots: &[BalanceSnapshot], bucket: u64) -> Vec<BalanceSnapshot> {
+    let mut result: Vec<BalanceSnapshot> = Vec::new();
+    for snapshot in snapshots {
+        match result.last_mut() {
+            Some(last) if last.timestamp / bucket == snapshot.timestamp / bucket => *last = snapshot.clone(),
+            _ => result.push(snapshot.clone()),
+        }
+    }
+    result
+}
+
+/// Append-only balance history of one wallet
+#[derive(Debug)]
+pub struct BalanceHistoryStore {
+    path: PathBuf,
+    entries: Mutex<Vec<BalanceSnapshot>>,
+}
+
+impl BalanceHistoryStore {
+    /// Path of the history file for `wallet_path`
+    pub fn sidecar_path(wallet_path: &Path) -> PathBuf {
+        sidecar_path(wallet_path, ".balance.jsonl")
+    }
+
+    pub fn for_wallet(wallet_path: &Path) -> Result<Self, String> {
+        Self::open(Self::sidecar_path(wallet_path))
+    }
+
+    /// Load the history at `path`. Lines that do not parse, such as one cut
+    /// short by a crash while appending, are skipped.
+    pub fn open(path: PathBuf) -> Result<Self, String> {
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(format!("Failed to read balance history: {}", e)),
+        };
+
+        let mut entries = Vec::new();
+        let mut skipped = false;
+        for line in content.lines().filter(|line| !line.trim().is_empty()) {
+            match serde_json::from_str::<BalanceSnapshot>(line) {
+                Ok(snapshot) => entries.push(snapshot),
+                Err(e) => {
+                    log::warn!("Skipping malformed balance history entry: {}", e);
+                    skipped = true;
+                }
+            }
+        }
+        entries.sort_by_key(|s| s.timestamp);
+
+        // Rewrite without the bad lines so new entries are not appended to one
+        if skipped {
+            write_all(&path, &entries)?;
+        }
+
+        Ok(Self {
+            path,
+            entries: Mutex::new(entries),
+        })
+    }
+
+    /// Record `snapshot` if the balance changed or the last snapshot is at
+    /// least a day old. Returns whether it was recorded.
+    pub fn record(&self, snapshot: BalanceSnapshot) -> Result<bool, String> {
+        let mut entries = self.entries.lock_recover();
+        if let Some(last) = entries.last()
+            && (snapshot.timestamp < last.timestamp
+                || (last.same_balance(&snapshot)
+                    && snapshot.timestamp - last.timestamp < SNAPSHOT_MAX_INTERVAL))
+        {
+            return Ok(false);
+        }
+
+        let mut line = serde_json::to_string(&snapshot)
+            .map_err(|e| format!("Failed to serialize balance snapshot: {}", e))?;
+        line.push('\n');
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("Failed to open balance history: {}", e))?;
+        file.write_all(line.as_bytes())
+            .map_err(|e| format!("Failed to append balance snapshot: {}", e))?;
+
+        entries.push(snapshot);
+        Ok(true)
+    }
+
+    /// Reduce snapshots older than 90 days before `now` to one per day and
+    /// rewrite the file if anything was dropped. Returns the number dropped.
+    pub fn compact(&self, now: u64) -> Result<usize, String> {
+        let mut entries = self.entries.lock_recover();
+        let cutoff = now.saturating_sub(FULL_RESOLUTION_WINDOW);
+        let split = entries.partition_point(|s| s.timestamp < cutoff);
+
+        let mut compacted = downsample(&entries[..split], DAY);
+        let dropped = split - compacted.len();
+        if dropped == 0 {
+            return Ok(0);
+        }
+        compacted.extend_from_slice(&entries[split..]);
+
+        write_all(&self.path, &compacted)?;
+        *entries = compacted;
+        Ok(dropped)
+    }
+
+    /// Points to plot for `range` ending at `now`, oldest first
+    pub fn history(&self, range: BalanceHistoryRange, resolution: BalanceResolution, now: u64) -> Vec<BalanceSnapshot> {
+        let entries = self.entries.lock_recover();
+        let from = range.seconds().map(|secs| now.saturating_sub(secs)).unwrap_or(0);
+        let in_range: Vec<BalanceSnapshot> = entries.iter()
+            .filter(|s| s.timestamp >= from && s.timestamp <= now)
+            .cloned()
+            .collect();
+        match resolution.bucket() {
+            Some(bucket) => downsample(&in_range, bucket),
+            None => in_range,
+        }
+    }
+
+    /// The balance as it was at `timestamp`: the last snapshot taken at or
+    /// before it, if the history reaches back that far
+    pub fn balance_at(&self, timestamp: u64) -> Option<BalanceSnapshot> {
+        let entries = self.entries.lock_recover();
+        let index = entries.partition_point(|s| s.timestamp <= timestamp);
+        index.checked_sub(1).map(|i| entries[i].clone())
+    }
+
+    pub fn latest(&self) -> Option<BalanceSnapshot> {
+        self.entries.lock_recover().last().cloned()
+    }
+}
+
+fn write_all(path: &Path, snapshots: &[BalanceSnapshot]) -> Result<(), String> {
+    let mut content = String::new();
+    for snapshot in snapshots {
+        content.push_str(&serde_json::to_string(snapshot)
+            .map_err(|e| format!("Failed to serialize balance snapshot: {}", e))?);
+        content.push('\n');
+    }
+    atomic_write(path, content.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::testing::TempDir;
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn temp_path() -> (TempDir, PathBuf) {
+        let dir = TempDir::new("fuego_balance");
+        let path = dir.join("test.wallet.balance.jsonl");
+        (dir, path)
+    }
+
+    fn snapshot(timestamp: u64, balance: u64) -> BalanceSnapshot {
+        BalanceSnapshot {
+            timestamp,
+            balance,
+            unlocked_balance: balance,
+            locked_balance: 0,
+            price_usd: None,
+        }
+    }
+
+    /// A year of snapshots, one every six hours with a changing balance
+    fn year_of_history(store: &BalanceHistoryStore) {
+        let start = NOW - 365 * DAY;
+        for i in 0..(365 * 4) {
+            assert!(store.record(snapshot(start + i * 6 * HOUR, 1_000 + i)).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_snapshots_only_on_change_or_daily() {
+        let (_dir, path) = temp_path();
+        let store = BalanceHistoryStore::open(path.clone()).unwrap();
+
+        assert!(store.record(snapshot(NOW, 100)).unwrap());
+        assert!(!store.record(snapshot(NOW + HOUR, 100)).unwrap());
+        assert!(store.record(snapshot(NOW + 2 * HOUR, 150)).unwrap());
+        assert!(!store.record(snapshot(NOW + HOUR, 200)).unwrap());
+        assert!(store.record(snapshot(NOW + 2 * HOUR + DAY, 150)).unwrap());
+
+        // A truncated last line does not lose the rest of the history
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"{\"timestamp\":17").unwrap();
+        let reopened = BalanceHistoryStore::open(path.clone()).unwrap();
+        assert_eq!(reopened.latest().unwrap().timestamp, NOW + 2 * HOUR + DAY);
+        assert_eq!(reopened.history(BalanceHistoryRange::All, BalanceResolution::Raw, NOW + 2 * DAY).len(), 3);
+        assert!(reopened.record(snapshot(NOW + 3 * DAY, 175)).unwrap());
+        assert_eq!(BalanceHistoryStore::open(path.clone()).unwrap().latest().unwrap().balance, 175);
+    }
+
+    #[test]
+    fn test_old_snapshots_are_downsampled_to_daily() {
+        let (_dir, path) = temp_path();
+        let store = BalanceHistoryStore::open(path.clone()).unwrap();
+        year_of_history(&store);
+
+        let dropped = store.compact(NOW).unwrap();
+        let all = store.history(BalanceHistoryRange::All, BalanceResolution::Raw, NOW);
+        let cutoff = NOW - FULL_RESOLUTION_WINDOW;
+        let old: Vec<_> = all.iter().filter(|s| s.timestamp < cutoff).collect();
+        let recent = all.iter().filter(|s| s.timestamp >= cutoff).count();
+
+        // Every old day keeps exactly its last snapshot, recent ones stay intact
+        assert!(old.windows(2).all(|w| w[0].timestamp / DAY < w[1].timestamp / DAY));
+        assert!(old[..old.len() - 1].iter().all(|s| s.timestamp % DAY >= DAY - 6 * HOUR));
+        assert_eq!(recent, 90 * 4);
+        assert_eq!(dropped + all.len(), 365 * 4);
+        assert_eq!(store.compact(NOW).unwrap(), 0);
+
+        // The rewritten file holds the compacted history
+        let reopened = BalanceHistoryStore::open(path.clone()).unwrap();
+        assert_eq!(reopened.history(BalanceHistoryRange::All, BalanceResolution::Raw, NOW), all);
+    }
+
+    #[test]
+    fn test_range_and_resolution() {
+        let (_dir, path) = temp_path();
+        let store = BalanceHistoryStore::open(path.clone()).unwrap();
+        year_of_history(&store);
+
+        let week = store.history(BalanceHistoryRange::Week, BalanceResolution::Raw, NOW);
+        assert!(week.iter().all(|s| s.timestamp >= NOW - 7 * DAY));
+        assert_eq!(week.len(), 7 * 4);
+
+        let month_daily = store.history(BalanceHistoryRange::Month, BalanceResolution::Day, NOW);
+        assert!(month_daily.len() == 30 || month_daily.len() == 31);
+        assert!(month_daily.windows(2).all(|w| w[0].timestamp < w[1].timestamp));
+
+        let year_weekly = store.history(BalanceHistoryRange::Year, BalanceResolution::Week, NOW);
+        assert!((52..=54).contains(&year_weekly.len()));
+        assert_eq!(year_weekly.last().unwrap(), week.last().unwrap());
+
+        assert_eq!(serde_json::from_str::<BalanceHistoryRange>("\"90d\"").unwrap(), BalanceHistoryRange::Quarter);
+        assert_eq!(serde_json::from_str::<BalanceResolution>("\"day\"").unwrap(), BalanceResolution::Day);
+    }
+}
@@ -0,0 +1,124 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Contact names for transaction addresses
+//!
+//! Puts a name to the addresses of a transaction: the label of the address
+//! book entry for a contact, or the label of one of the wallet's own
+//! addresses. The address book is keyed by address, so a contact's id is its
+//! address.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use crate::crypto::real_cryptonote::{AddressBookEntry, WalletAddress};
+use super::AdvancedTransactionInfo;
+
+/// How one address of a transaction is shown
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResolvedAddress {
+    pub address: String,
+    /// Contact or own address label; `None` when there is none to show
+    pub display_name: Option<String>,
+    pub is_own_address: bool,
+    /// Address book entry of the address, if it is a contact
+    pub contact_id: Option<String>,
+}
+
+/// Names addresses from the address book and the wallet's own addresses
+#[derive(Debug, Clone, Default)]
+pub struct ContactResolver {
+    contacts: HashMap<String, String>,
+    own: HashMap<String, Option<String>>,
+}
+
+impl ContactResolver {
+    pub fn new(address_book: &[AddressBookEntry], own_addresses: &[WalletAddress]) -> Self {
+        Self {
+            contacts: address_book.iter()
+                .map(|entry| (entry.address.clone(), entry.label.trim().to_string()))
+                .collect(),
+            own: own_addresses.iter()
+                .map(|own| (own.address.clone(), own.label.clone()))
+                .collect(),
+        }
+    }
+
+    /// Whether `contact_id` names an address book entry
+    pub fn is_contact(&self, contact_id: &str) -> bool {
+        self.contacts.contains_key(contact_id)
+    }
+
+    /// Name `address`. The wallet's own label wins over a contact entry for
+    /// the same address.
+    pub fn resolve(&self, address: &str) -> ResolvedAddress {
+        let own = self.own.get(address);
+        let contact = self.contacts.get_key_value(address);
+        let display_name = own.cloned().flatten()
+            .or_else(|| contact.map(|(_, label)| label.clone()))
+            .filter(|name| !name.is_empty());
+        ResolvedAddress {
+            address: address.to_string(),
+            display_name,
+            is_own_address: own.is_some(),
+            contact_id: contact.map(|(id, _)| id.clone()),
+        }
+    }
+
+    /// Name each source and destination address of `tx`, once
+    pub fn resolve_transaction(&self, tx: &mut AdvancedTransactionInfo) {
+        let mut resolved: Vec<ResolvedAddress> = Vec::new();
+        for address in tx.source_addresses.iter().chain(&tx.destination_addresses) {
+            if !resolved.iter().any(|r| &r.address == address) {
+                resolved.push(self.resolve(address));
+            }
+        }
+        tx.resolved_addresses = resolved;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contact(address: &str, label: &str) -> AddressBookEntry {
+        AddressBookEntry {
+            address: address.to_string(),
+            label: label.to_string(),
+            description: String::new(),
+            created_time: 0,
+            last_used_time: 0,
+            use_count: 0,
+        }
+    }
+
+    fn own(index: u32, address: &str, label: Option<&str>) -> WalletAddress {
+        WalletAddress { index, address: address.to_string(), label: label.map(String::from) }
+    }
+
+    #[test]
+    fn test_known_own_and_unknown_addresses_resolve() {
+        let resolver = ContactResolver::new(
+            &[contact("fire1alice", "Alice"), contact("fire1nolabel", " ")],
+            &[own(0, "fire1me", None), own(1, "fire1savings", Some("Savings"))],
+        );
+
+        assert_eq!(resolver.resolve("fire1alice"), ResolvedAddress {
+            address: "fire1alice".to_string(),
+            display_name: Some("Alice".to_string()),
+            is_own_address: false,
+            contact_id: Some("fire1alice".to_string()),
+        });
+        let savings = resolver.resolve("fire1savings");
+        assert_eq!((savings.display_name.as_deref(), savings.is_own_address, savings.contact_id), (Some("Savings"), true, None));
+        let main = resolver.resolve("fire1me");
+        assert_eq!((main.display_name, main.is_own_address), (None, true));
+        // A contact without a label is still a contact
+        assert_eq!(resolver.resolve("fire1nolabel").contact_id.as_deref(), Some("fire1nolabel"));
+        assert_eq!(resolver.resolve("fire1stranger"), ResolvedAddress {
+            address: "fire1stranger".to_string(),
+            display_name: None,
+            is_own_address: false,
+            contact_id: None,
+        });
+    }
+}
@@ -0,0 +1,300 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Dashboard summary
+//!
+//! Everything the dashboard shows, assembled in one pass from state that is
+//! already in memory: cached wallet info, the balance history, the manager's
+//! transactions and network snapshot, the deposits seen by the maturity
+//! watcher and the notification list. Nothing here talks to the wallet or
+//! the network, so the summary is cheap enough to poll.
+
+use serde::{Deserialize, Serialize};
+use crate::crypto::daemon::TARGET_BLOCK_TIME;
+use crate::deposits::DepositMaturityWatcher;
+use crate::performance::Cache;
+use crate::wallet::payment_uri::ATOMIC_UNITS_PER_XFG;
+use super::{AdvancedTransactionInfo, AdvancedUIManager, AdvancedWalletManager, BalanceHistoryStore};
+
+/// Key of the XFG/USD price in the global cache, present while a price is known
+pub const PRICE_CACHE_KEY: &str = "price:xfg_usd";
+
+/// Number of transactions listed on the dashboard
+pub const RECENT_TRANSACTION_COUNT: usize = 5;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DashboardBalances {
+    pub balance: u64,
+    pub unlocked_balance: u64,
+    pub locked_balance: u64,
+    /// Change since 24 hours ago, when the history reaches back that far
+    pub change_24h: Option<i64>,
+    pub change_7d: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DashboardDeposits {
+    pub active_count: usize,
+    /// Principal of deposits that are still locked
+    pub total_locked: u64,
+    pub next_maturity_height: Option<u64>,
+    /// Rough seconds until the next deposit matures, from the target block time
+    pub next_maturity_eta: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DashboardSync {
+    pub is_connected: bool,
+    pub is_synced: bool,
+    pub sync_height: u64,
+    pub network_height: u64,
+    pub peer_count: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DashboardFiat {
+    pub price_usd: f64,
+    pub balance_usd: f64,
+}
+
+/// Aggregate shown on the dashboard
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DashboardSummary {
+    pub balances: DashboardBalances,
+    pub recent_transactions: Vec<AdvancedTransactionInfo>,
+    pub deposits: DashboardDeposits,
+    pub sync: DashboardSync,
+    pub unread_notifications: usize,
+    /// Only set while a price is cached
+    pub fiat: Option<DashboardFiat>,
+    pub generated_at: u64,
+}
+
+/// In-memory state the summary is built from; missing parts are left empty
+pub struct DashboardSources<'a> {
+    pub wallet: &'a AdvancedWalletManager,
+    pub ui: &'a AdvancedUIManager,
+    /// Wallet info cached by `get_wallet_info`, under `wallet_info_key`
+    pub cache: Option<&'a Cache<serde_json::Value>>,
+    pub wallet_info_key: &'a str,
+    pub history: Option<&'a BalanceHistoryStore>,
+    pub deposits: Option<&'a DepositMaturityWatcher>,
+}
+
+impl DashboardSources<'_> {
+    /// Build the summary as of `now` (unix seconds)
+    pub fn summarize(&self, now: u64) -> DashboardSummary {
+        let wallet_info = self.wallet.get_enhanced_wallet_info();
+        let network_info = self.wallet.get_network_info();
+        let cached = self.cache.and_then(|cache| cache.get(self.wallet_info_key));
+
+        let mut balances = match (&cached, &wallet_info) {
+            (Some(info), _) => {
+                let balance = info.get("balance").and_then(|v| v.as_u64()).unwrap_or(0);
+                let unlocked_balance = info.get("unlocked_balance").and_then(|v| v.as_u64()).unwrap_or(balance);
+                DashboardBalances {
+                    balance,
+                    unlocked_balance,
+                    locked_balance: balance.saturating_sub(unlocked_balance),
+                    ..Default::default()
+                }
+            }
+            (None, Some(info)) => DashboardBalances {
+                balance: info.balance,
+                unlocked_balance: info.unlocked_balance,
+                locked_balance: info.locked_balance,
+                ..Default::default()
+            },
+            (None, None) => self.history.and_then(|h| h.latest())
+                .map(|s| DashboardBalances {
+                    balance: s.balance,
+                    unlocked_balance: s.unlocked_balance,
+                    locked_balance: s.locked_balance,
+                    ..Default::default()
+                })
+                .unwrap_or_default(),
+        };
+        if let Some(history) = self.history {
+            let change_since = |secs: u64| history.balance_at(now.saturating_sub(secs))
+                .map(|s| balances.balance as i64 - s.balance as i64);
+            balances.change_24h = change_since(24 * 60 * 60);
+            balances.change_7d = change_since(7 * 24 * 60 * 60);
+        }
+
+        let mut recent_transactions = self.wallet.get_advanced_transactions();
+        recent_transactions.sort_by(|a, b| b.timestamp.cmp(&a.timestamp).then_with(|| a.hash.cmp(&b.hash)));
+        recent_transactions.truncate(RECENT_TRANSACTION_COUNT);
+
+        let sync = match (&network_info, &wallet_info) {
+            (Some(net), _) => DashboardSync {
+                is_connected: net.is_connected,
+                is_synced: !net.is_syncing && net.sync_height >= net.network_height,
+                sync_height: net.sync_height,
+                network_height: net.network_height,
+                peer_count: net.peer_count,
+            },
+            (None, Some(info)) => DashboardSync {
+                is_connected: info.is_connected,
+                is_synced: info.is_synced,
+                sync_height: info.sync_height,
+                network_height: info.network_height,
+                peer_count: info.peer_count,
+            },
+            (None, None) => DashboardSync::default(),
+        };
+
+        let mut deposits = DashboardDeposits::default();
+        if let Some((seen, height)) = self.deposits.and_then(|w| w.last_seen()) {
+            let height = height.max(sync.network_height);
+            for deposit in seen.iter().filter(|d| d.status != "spent") {
+                deposits.active_count += 1;
+                if deposit.unlock_height > height {
+                    deposits.total_locked = deposits.total_locked.saturating_add(deposit.amount);
+                    deposits.next_maturity_height = Some(deposits.next_maturity_height
+                        .map_or(deposit.unlock_height, |h| h.min(deposit.unlock_height)));
+                }
+            }
+            deposits.next_maturity_eta = deposits.next_maturity_height
+                .map(|h| (h - height) * TARGET_BLOCK_TIME);
+        }
+
+        let fiat = self.cache
+            .and_then(|cache| cache.get(PRICE_CACHE_KEY))
+            .and_then(|price| price.as_f64())
+            .map(|price_usd| DashboardFiat {
+                price_usd,
+                balance_usd: balances.balance as f64 / ATOMIC_UNITS_PER_XFG as f64 * price_usd,
+            });
+
+        DashboardSummary {
+            balances,
+            recent_transactions,
+            deposits,
+            sync,
+            unread_notifications: self.ui.unread_count(),
+            fiat,
+            generated_at: now,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::time::{Duration, Instant};
+    use crate::advanced::{BalanceSnapshot, UINotification};
+    use crate::crypto::real_cryptonote::DepositInfo;
+    use crate::utils::testing::TempDir;
+
+    const NOW: u64 = 1_700_000_000;
+    const DAY: u64 = 24 * 60 * 60;
+
+    fn temp_path() -> (TempDir, PathBuf) {
+        let dir = TempDir::new("fuego_dashboard");
+        let path = dir.join("test.wallet.balance.jsonl");
+        (dir, path)
+    }
+
+    fn snapshot(timestamp: u64, balance: u64) -> BalanceSnapshot {
+        BalanceSnapshot { timestamp, balance, unlocked_balance: balance, locked_balance: 0, price_usd: None }
+    }
+
+    fn deposit(id: &str, amount: u64, unlock_height: u64, status: &str) -> DepositInfo {
+        serde_json::from_value(serde_json::json!({
+            "id": id, "amount": amount, "interest": 10, "term": 30, "rate": 0.08, "status": status,
+            "unlock_height": unlock_height, "unlock_time": null, "creating_transaction_hash": "h",
+            "creating_height": 1, "creating_time": "0", "spending_transaction_hash": null,
+            "spending_height": null, "spending_time": null, "deposit_type": "term"
+        })).unwrap()
+    }
+
+    #[test]
+    fn test_summary_from_populated_managers() {
+        let (_dir, path) = temp_path();
+        let wallet = AdvancedWalletManager::new();
+        for i in 0..8u64 {
+            let mut tx = AdvancedTransactionInfo::pending_send(&format!("tx{}", i), 100, Vec::new(), None, NOW - i * 60);
+            tx.is_pending = false;
+            wallet.add_transaction(tx);
+        }
+
+        let ui = AdvancedUIManager::new();
+        ui.add_notification(UINotification {
+            id: "n1".to_string(),
+            title: "Deposit matured".to_string(),
+            message: "Deposit 7 can be withdrawn".to_string(),
+            notification_type: "deposit".to_string(),
+            timestamp: NOW,
+            is_read: false,
+            is_dismissed: false,
+            actions: Vec::new(),
+            duration: None,
+        });
+
+        let cache = Cache::new(10, Duration::from_secs(60));
+        cache.set("wallet_info:test".to_string(), serde_json::json!({"balance": 5_000_000_000u64, "unlocked_balance": 4_000_000_000u64}));
+        cache.set(PRICE_CACHE_KEY.to_string(), serde_json::json!(0.02));
+
+        let history = BalanceHistoryStore::open(path.clone()).unwrap();
+        history.record(snapshot(NOW - 10 * DAY, 3_000_000_000)).unwrap();
+        history.record(snapshot(NOW - 2 * DAY, 4_500_000_000)).unwrap();
+
+        let watcher = DepositMaturityWatcher::new();
+        watcher.check(&[
+            deposit("1", 700, 1_500, "locked"),
+            deposit("2", 300, 1_200, "locked"),
+            deposit("3", 900, 900, "unlocked"),
+            deposit("4", 100, 100, "spent"),
+        ], 1_000);
+
+        let sources = DashboardSources {
+            wallet: &wallet,
+            ui: &ui,
+            cache: Some(&cache),
+            wallet_info_key: "wallet_info:test",
+            history: Some(&history),
+            deposits: Some(&watcher),
+        };
+        let start = Instant::now();
+        let summary = sources.summarize(NOW);
+        assert!(start.elapsed() < Duration::from_millis(50));
+
+        assert_eq!(summary.balances.balance, 5_000_000_000);
+        assert_eq!(summary.balances.locked_balance, 1_000_000_000);
+        assert_eq!(summary.balances.change_24h, Some(500_000_000));
+        assert_eq!(summary.balances.change_7d, Some(2_000_000_000));
+        assert_eq!(summary.recent_transactions.iter().map(|t| t.hash.as_str()).collect::<Vec<_>>(),
+            vec!["tx0", "tx1", "tx2", "tx3", "tx4"]);
+        assert_eq!(summary.deposits, DashboardDeposits {
+            active_count: 3,
+            total_locked: 1_000,
+            next_maturity_height: Some(1_200),
+            next_maturity_eta: Some(200 * TARGET_BLOCK_TIME),
+        });
+        assert_eq!(summary.unread_notifications, 1);
+        let fiat = summary.fiat.unwrap();
+        assert!((fiat.balance_usd - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_summary_with_nothing_cached() {
+        let wallet = AdvancedWalletManager::new();
+        let ui = AdvancedUIManager::new();
+        let sources = DashboardSources {
+            wallet: &wallet,
+            ui: &ui,
+            cache: None,
+            wallet_info_key: "wallet_info:test",
+            history: None,
+            deposits: None,
+        };
+
+        let summary = sources.summarize(NOW);
+        assert_eq!(summary.balances, DashboardBalances::default());
+        assert!(summary.recent_transactions.is_empty());
+        assert_eq!(summary.deposits, DashboardDeposits::default());
+        assert!(summary.fiat.is_none());
+        assert!(!summary.sync.is_connected);
+    }
+}
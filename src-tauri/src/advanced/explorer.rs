@@ -0,0 +1,245 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Blockchain explorer client
+//!
+//! Looks up transactions, blocks and addresses the local wallet does not know
+//! about through the configured `BlockchainExplorer` endpoints. Endpoint
+//! templates use `{hash}`, `{block}` / `{height}` and `{address}` placeholders.
+
+use std::sync::Arc;
+use std::time::Duration;
+use crate::performance::Cache;
+use crate::utils::retry::{retry_async, RetryPolicy};
+use super::BlockchainExplorer;
+
+/// Cache lifetime of data that cannot change (transactions, blocks by hash)
+pub const IMMUTABLE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Cache lifetime of blocks looked up by height, which a reorg can replace
+pub const BLOCK_BY_HEIGHT_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Cache lifetime of address summaries, which change with every transfer
+pub const ADDRESS_TTL: Duration = Duration::from_secs(60);
+
+/// Delay before the first retry; doubled for each further attempt
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(100);
+
+/// HTTP client querying the configured blockchain explorers in order
+#[derive(Debug, Clone)]
+pub struct ExplorerClient {
+    explorers: Vec<BlockchainExplorer>,
+    cache: Option<Arc<Cache<serde_json::Value>>>,
+    http: reqwest::Client,
+}
+
+impl ExplorerClient {
+    /// Create a client for the enabled explorers, caching results in `cache` if given
+    pub fn new(explorers: Vec<BlockchainExplorer>, cache: Option<Arc<Cache<serde_json::Value>>>) -> Self {
+        Self {
+            explorers: explorers.into_iter().filter(|e| e.is_enabled).collect(),
+            cache,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Fetch a transaction by hash
+    pub async fn fetch_transaction(&self, hash: &str) -> Result<serde_json::Value, String> {
+        check_identifier("transaction hash", hash)?;
+        self.fetch(
+            &format!("explorer:tx:{}", hash),
+            IMMUTABLE_TTL,
+            |explorer| &explorer.transaction_endpoint,
+            &[("hash", hash)],
+        ).await
+    }
+
+    /// Fetch a block by height or by hash
+    pub async fn fetch_block(&self, height_or_hash: &str) -> Result<serde_json::Value, String> {
+        check_identifier("block height or hash", height_or_hash)?;
+        let ttl = if height_or_hash.chars().all(|c| c.is_ascii_digit()) {
+            BLOCK_BY_HEIGHT_TTL
+        } else {
+            IMMUTABLE_TTL
+        };
+        self.fetch(
+            &format!("explorer:block:{}", height_or_hash),
+            ttl,
+            |explorer| &explorer.block_endpoint,
+            &[("block", height_or_hash), ("height", height_or_hash), ("hash", height_or_hash)],
+        ).await
+    }
+
+    /// Fetch the summary (balance, transaction count, ...) of an address
+    pub async fn fetch_address_summary(&self, address: &str) -> Result<serde_json::Value, String> {
+        check_identifier("address", address)?;
+        self.fetch(
+            &format!("explorer:address:{}", address),
+            ADDRESS_TTL,
+            |explorer| &explorer.address_endpoint,
+            &[("address", address)],
+        ).await
+    }
+
+    /// Try every explorer in turn and return the first successful response.
+    /// If all fail, the error lists what went wrong with each one.
+    async fn fetch<F>(
+        &self,
+        cache_key: &str,
+        ttl: Duration,
+        endpoint: F,
+        params: &[(&str, &str)],
+    ) -> Result<serde_json::Value, String>
+    where
+        F: Fn(&BlockchainExplorer) -> &String,
+    {
+        if let Some(cached) = self.cache.as_ref().and_then(|cache| cache.get(cache_key)) {
+            return Ok(cached);
+        }
+
+        if self.explorers.is_empty() {
+            return Err("No blockchain explorers configured".to_string());
+        }
+
+        let mut failures = Vec::new();
+        for explorer in &self.explorers {
+            let url = build_url(explorer, endpoint(explorer), params);
+            match self.get_json(explorer, &url).await {
+                Ok(value) => {
+                    if let Some(cache) = &self.cache {
+                        cache.set_with_ttl(cache_key.to_string(), value.clone(), ttl);
+                    }
+                    return Ok(value);
+                }
+                Err(e) => failures.push(format!("{}: {}", explorer.name, e)),
+            }
+        }
+
+        Err(format!("All blockchain explorers failed: {}", failures.join("; ")))
+    }
+
+    /// GET `url` as JSON, retrying network errors and server-side failures
+    async fn get_json(&self, explorer: &BlockchainExplorer, url: &str) -> Result<serde_json::Value, String> {
+        let policy = RetryPolicy::network("explorer", None)
+            .with_max_attempts(explorer.retry_count.saturating_add(1))
+            .with_base_delay(RETRY_BASE_DELAY);
+
+        retry_async(&policy, || async {
+            let response = self.http.get(url).timeout(explorer.timeout).send().await
+                .map_err(|e| if e.is_timeout() { "timed out".to_string() } else { e.to_string() })?;
+
+            let status = response.status();
+            if !status.is_success() {
+                return Err(format!("HTTP {}", status.as_u16()));
+            }
+            response.json::<serde_json::Value>().await
+                .map_err(|e| format!("invalid JSON response: {}", e))
+        }).await
+    }
+}
+
+/// Join base URL, API prefix and endpoint template, then fill in `params`.
+/// Absolute endpoint templates are used as they are.
+fn build_url(explorer: &BlockchainExplorer, template: &str, params: &[(&str, &str)]) -> String {
+    let mut url = if template.starts_with("http://") || template.starts_with("https://") {
+        template.to_string()
+    } else {
+        let mut url = explorer.base_url.trim_end_matches('/').to_string();
+        for part in [&explorer.api_endpoint, template] {
+            let part = part.trim_matches('/');
+            if !part.is_empty() {
+                url.push('/');
+                url.push_str(part);
+            }
+        }
+        url
+    };
+
+    for (name, value) in params {
+        url = url.replace(&format!("{{{}}}", name), value);
+    }
+    url
+}
+
+/// Only plain identifiers are substituted into URLs
+fn check_identifier(kind: &str, value: &str) -> Result<(), String> {
+    if value.is_empty() || !value.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(format!("Invalid {}: {}", kind, value));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::testing::mock_http_server;
+
+    fn explorer(name: &str, base_url: &str, retry_count: u32) -> BlockchainExplorer {
+        BlockchainExplorer {
+            name: name.to_string(),
+            base_url: base_url.to_string(),
+            api_endpoint: "/api/".to_string(),
+            transaction_endpoint: "tx/{hash}".to_string(),
+            block_endpoint: "/block/{block}".to_string(),
+            address_endpoint: "address/{address}/summary".to_string(),
+            is_enabled: true,
+            timeout: Duration::from_secs(5),
+            retry_count,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_templates_are_substituted_and_cached() {
+        let (base_url, paths) = mock_http_server(vec![
+            (200, r#"{"hash":"abc123"}"#),
+            (200, r#"{"height":42}"#),
+            (200, r#"{"balance":5}"#),
+        ]);
+        let cache = Arc::new(Cache::new(100, Duration::from_secs(60)));
+        let client = ExplorerClient::new(vec![explorer("main", &base_url, 0)], Some(cache.clone()));
+
+        assert_eq!(client.fetch_transaction("abc123").await.unwrap()["hash"], "abc123");
+        assert_eq!(client.fetch_block("42").await.unwrap()["height"], 42);
+        assert_eq!(client.fetch_address_summary("fire1xyz").await.unwrap()["balance"], 5);
+        // Served from the cache without another request
+        assert_eq!(client.fetch_transaction("abc123").await.unwrap()["hash"], "abc123");
+
+        assert_eq!(*paths.lock().unwrap(), vec![
+            "/api/tx/abc123".to_string(),
+            "/api/block/42".to_string(),
+            "/api/address/fire1xyz/summary".to_string(),
+        ]);
+        assert!(client.fetch_transaction("../admin").await.unwrap_err().contains("Invalid transaction hash"));
+    }
+
+    #[tokio::test]
+    async fn test_server_errors_are_retried() {
+        let (base_url, paths) = mock_http_server(vec![
+            (503, "{}"),
+            (500, "{}"),
+            (200, r#"{"hash":"def456"}"#),
+        ]);
+        let client = ExplorerClient::new(vec![explorer("flaky", &base_url, 2)], None);
+
+        assert_eq!(client.fetch_transaction("def456").await.unwrap()["hash"], "def456");
+        assert_eq!(paths.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_failures_are_aggregated() {
+        let (first_url, first_paths) = mock_http_server(vec![(404, "{}")]);
+        let (second_url, _) = mock_http_server(vec![(500, "{}"), (502, "{}")]);
+        let mut disabled = explorer("disabled", "http://127.0.0.1:1", 0);
+        disabled.is_enabled = false;
+        let client = ExplorerClient::new(vec![
+            explorer("first", &first_url, 3),
+            disabled,
+            explorer("second", &second_url, 1),
+        ], None);
+
+        let err = client.fetch_transaction("abc").await.unwrap_err();
+        assert_eq!(err, "All blockchain explorers failed: first: HTTP 404; second: HTTP 502");
+        // Client errors are not retried
+        assert_eq!(first_paths.lock().unwrap().len(), 1);
+    }
+}
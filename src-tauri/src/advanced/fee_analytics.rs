@@ -0,0 +1,193 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Fee analytics
+//!
+//! What the wallet has paid in fees, from the sent transactions of its
+//! history: totals, averages, a monthly series for charting and the most
+//! expensive transaction. Transactions whose fee priority was recorded in
+//! the metadata store are compared with what slow priority would have cost.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use crate::crypto::real_cryptonote::TransactionInfo;
+use super::FeePriority;
+
+/// Fees paid in one calendar month (UTC)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MonthlyFees {
+    /// `YYYY-MM`
+    pub month: String,
+    pub transaction_count: usize,
+    pub total_fees: u64,
+    pub total_sent: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HighestFee {
+    pub hash: String,
+    pub fee: u64,
+    pub amount: u64,
+    pub timestamp: u64,
+}
+
+/// Fees paid by the sent transactions of the history
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FeeAnalytics {
+    pub outgoing_count: usize,
+    pub total_fees: u64,
+    pub total_sent: u64,
+    /// `None` without sent transactions
+    pub average_fee: Option<u64>,
+    /// Fees as a percentage of the amount sent, `None` when nothing was sent
+    pub fee_percentage: Option<f64>,
+    /// Oldest month first; months without sends are left out
+    pub monthly: Vec<MonthlyFees>,
+    pub highest_fee: Option<HighestFee>,
+    /// Sent transactions with a recorded fee priority
+    pub priority_known_count: usize,
+    /// What slow priority would have saved over the transactions with a
+    /// recorded priority; `None` when no priority is recorded
+    pub potential_savings: Option<u64>,
+}
+
+impl FeeAnalytics {
+    /// Aggregate the sent, non-pending transactions of `transactions`.
+    ///
+    /// `priority_of` looks up the recorded priority of a transaction hash and
+    /// `slow_fee` estimates what a transaction would have cost at slow
+    /// priority; it is only asked about transactions sent faster than slow.
+    pub fn compute<P, S>(transactions: &[TransactionInfo], priority_of: P, slow_fee: S) -> Self
+    where
+        P: Fn(&str) -> Option<FeePriority>,
+        S: Fn(&TransactionInfo) -> Option<u64>,
+    {
+        let mut analytics = Self::default();
+        let mut months: BTreeMap<String, MonthlyFees> = BTreeMap::new();
+        let mut savings = 0u64;
+
+        for tx in transactions.iter().filter(|tx| tx.amount < 0 && !tx.is_pending) {
+            let amount = tx.amount.unsigned_abs();
+            analytics.outgoing_count += 1;
+            analytics.total_fees = analytics.total_fees.saturating_add(tx.fee);
+            analytics.total_sent = analytics.total_sent.saturating_add(amount);
+
+            if analytics.highest_fee.as_ref().is_none_or(|highest| tx.fee > highest.fee) {
+                analytics.highest_fee = Some(HighestFee {
+                    hash: tx.hash.clone(),
+                    fee: tx.fee,
+                    amount,
+                    timestamp: tx.timestamp,
+                });
+            }
+
+            let month = month_of(tx.timestamp);
+            let entry = months.entry(month.clone()).or_insert_with(|| MonthlyFees { month, ..Default::default() });
+            entry.transaction_count += 1;
+            entry.total_fees = entry.total_fees.saturating_add(tx.fee);
+            entry.total_sent = entry.total_sent.saturating_add(amount);
+
+            match priority_of(&tx.hash) {
+                Some(FeePriority::Slow) => analytics.priority_known_count += 1,
+                Some(_) => {
+                    analytics.priority_known_count += 1;
+                    if let Some(slow) = slow_fee(tx) {
+                        savings = savings.saturating_add(tx.fee.saturating_sub(slow));
+                    }
+                }
+                None => {}
+            }
+        }
+
+        if analytics.outgoing_count > 0 {
+            analytics.average_fee = Some(analytics.total_fees / analytics.outgoing_count as u64);
+        }
+        if analytics.total_sent > 0 {
+            analytics.fee_percentage = Some(analytics.total_fees as f64 / analytics.total_sent as f64 * 100.0);
+        }
+        if analytics.priority_known_count > 0 {
+            analytics.potential_savings = Some(savings);
+        }
+        analytics.monthly = months.into_values().collect();
+        analytics
+    }
+}
+
+fn month_of(timestamp: u64) -> String {
+    chrono::DateTime::from_timestamp(timestamp as i64, 0)
+        .map(|t| t.format("%Y-%m").to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: u64 = 86_400;
+    /// 2024-01-01 00:00:00 UTC
+    const JAN_2024: u64 = 1_704_067_200;
+
+    fn tx(index: u64, amount: i64, fee: u64) -> TransactionInfo {
+        TransactionInfo {
+            id: format!("tx{}", index),
+            hash: format!("hash{}", index),
+            amount,
+            fee,
+            height: 1_000 + index,
+            timestamp: JAN_2024 + index * DAY,
+            confirmations: 10,
+            is_confirmed: true,
+            is_pending: false,
+            payment_id: None,
+            destination_addresses: Vec::new(),
+            source_addresses: Vec::new(),
+            unlock_time: None,
+            extra: None,
+        }
+    }
+
+    #[test]
+    fn test_aggregates_synthetic_history() {
+        // 500 days: every other transaction is a send of 1 XFG with a fee of
+        // 1000 + index, the rest are receipts
+        let history: Vec<TransactionInfo> = (0..500)
+            .map(|i| if i % 2 == 0 { tx(i, -10_000_000, 1_000 + i) } else { tx(i, 5_000_000, 0) })
+            .collect();
+        let priority_of = |hash: &str| match hash.trim_start_matches("hash").parse::<u64>().unwrap() % 4 {
+            0 => Some(FeePriority::Fast),
+            _ => None,
+        };
+        let analytics = FeeAnalytics::compute(&history, priority_of, |tx| Some(tx.fee - 500));
+
+        let sent_fees: u64 = (0..500).step_by(2).map(|i| 1_000 + i).sum();
+        assert_eq!(analytics.outgoing_count, 250);
+        assert_eq!(analytics.total_fees, sent_fees);
+        assert_eq!(analytics.total_sent, 250 * 10_000_000);
+        assert_eq!(analytics.average_fee, Some(sent_fees / 250));
+        let percentage = analytics.fee_percentage.unwrap();
+        assert!((percentage - sent_fees as f64 / 2_500_000_000.0 * 100.0).abs() < 1e-9);
+
+        // 500 days from January 2024 reach into May 2025
+        assert_eq!(analytics.monthly.len(), 17);
+        assert_eq!(analytics.monthly[0].month, "2024-01");
+        assert_eq!(analytics.monthly[0].transaction_count, 16);
+        assert_eq!(analytics.monthly.last().unwrap().month, "2025-05");
+        assert_eq!(analytics.monthly.iter().map(|m| m.total_fees).sum::<u64>(), sent_fees);
+
+        let highest = analytics.highest_fee.unwrap();
+        assert_eq!((highest.hash.as_str(), highest.fee), ("hash498", 1_498));
+        assert_eq!(analytics.priority_known_count, 125);
+        assert_eq!(analytics.potential_savings, Some(125 * 500));
+    }
+
+    #[test]
+    fn test_no_outgoing_transactions() {
+        let history = vec![tx(0, 5_000_000, 0), TransactionInfo { is_pending: true, ..tx(1, -1_000, 10) }];
+        let analytics = FeeAnalytics::compute(&history, |_| Some(FeePriority::Fast), |_| Some(0));
+        assert_eq!(analytics.outgoing_count, 0);
+        assert_eq!(analytics.average_fee, None);
+        assert_eq!(analytics.fee_percentage, None);
+        assert!(analytics.monthly.is_empty() && analytics.highest_fee.is_none());
+        assert_eq!(analytics.potential_savings, None);
+    }
+}
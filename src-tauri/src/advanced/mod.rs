@@ -6,10 +6,41 @@
 //! This module provides advanced wallet features including enhanced transaction management,
 //! advanced UI components, blockchain explorer integration, and advanced wallet operations.
 
+pub mod balance_history;
+pub mod contacts;
+pub mod dashboard;
+pub mod explorer;
+pub mod fee_analytics;
+pub mod notifications;
+pub mod operations;
+pub mod price_alerts;
+pub mod search;
+pub mod state;
+pub mod themes;
+pub mod tx_metadata;
+pub mod watch;
+
+pub use balance_history::{BalanceHistoryRange, BalanceHistoryStore, BalanceResolution, BalanceSnapshot};
+pub use contacts::{ContactResolver, ResolvedAddress};
+pub use dashboard::{DashboardSources, DashboardSummary};
+pub use explorer::ExplorerClient;
+pub use fee_analytics::FeeAnalytics;
+pub use notifications::{NavigationHint, NotificationOperation};
+pub use operations::OperationEntry;
+pub use price_alerts::{AlertDirection, PriceAlert, PriceAlerts};
+pub use search::{TransactionFilter, TransactionSearchResult};
+pub use tx_metadata::{FeePriority, TransactionMetadata, TransactionMetadataStore};
+pub use watch::{WatchedAddress, WatchedAddressDeposit, WatchedAddresses};
+
 use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, Sender};
 use std::sync::{Arc, Mutex};
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use serde::{Deserialize, Serialize};
+use crate::crypto::real_cryptonote::WalletAddress;
+use crate::utils::unlock::is_unlocked;
+use crate::utils::locks::MutexExt;
+use operations::{MAX_OPERATION_DETAILS, MAX_OPERATION_DETAIL_LEN};
 
 /// Advanced transaction information with enhanced details
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -40,6 +71,73 @@ pub struct AdvancedTransactionInfo {
     pub double_spend_seen: bool,
     pub rct_type: Option<u8>,
     pub version: u8,
+    /// User labels, notes and tags from the `TransactionMetadataStore`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<TransactionMetadata>,    /// Names of the source and destination addresses, see `ContactResolver`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub resolved_addresses: Vec<ResolvedAddress>,
+}
+
+impl AdvancedTransactionInfo {
+    /// An outgoing transaction that was just submitted and is not in a block yet
+    pub fn pending_send(hash: &str, amount: u64, destinations: Vec<String>, payment_id: Option<String>, timestamp: u64) -> Self {
+        Self {
+            amount: -(amount.min(i64::MAX as u64) as i64),
+            payment_id,
+            destination_addresses: destinations,
+            ..Self::unconfirmed(hash, timestamp)
+        }
+    }
+
+    /// An incoming transaction seen in the transaction pool
+    pub fn pending_receive(hash: &str, amount: u64, payment_id: Option<String>, timestamp: u64, double_spend_seen: bool) -> Self {
+        Self {
+            amount: amount.min(i64::MAX as u64) as i64,
+            payment_id,
+            double_spend_seen,
+            ..Self::unconfirmed(hash, timestamp)
+        }
+    }
+
+    fn unconfirmed(hash: &str, timestamp: u64) -> Self {
+        Self {
+            id: hash.to_string(),
+            hash: hash.to_string(),
+            amount: 0,
+            fee: 0,
+            height: 0,
+            timestamp,
+            confirmations: 0,
+            is_confirmed: false,
+            is_pending: true,
+            payment_id: None,
+            destination_addresses: Vec::new(),
+            source_addresses: Vec::new(),
+            unlock_time: None,
+            extra: None,
+            mixin: 0,
+            ring_size: 0,
+            key_images: Vec::new(),
+            outputs: Vec::new(),
+            inputs: Vec::new(),
+            block_hash: None,
+            block_timestamp: None,
+            mempool_timestamp: Some(timestamp),
+            relayed_by: None,
+            double_spend_seen: false,
+            rct_type: None,
+            version: 1,
+            metadata: None,
+            resolved_addresses: Vec::new(),
+        }
+    }
+
+    /// Whether the outputs of this transaction can be spent at the given chain state
+    pub fn is_spendable(&self, current_height: u64, current_ts: u64) -> bool {
+        !self.is_pending
+            && self.unlock_time
+                .is_none_or(|unlock_time| is_unlocked(unlock_time, current_height, current_ts))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -227,6 +325,10 @@ pub struct WalletOperation {
     pub error: Option<String>,
     pub progress: Option<f64>,
     pub details: Option<HashMap<String, String>>,
+    /// Monotonic start, for a duration unaffected by wall-clock changes.
+    /// Not kept across restarts.
+    #[serde(skip)]
+    pub started: Option<Instant>,
 }
 
 impl AdvancedWalletManager {
@@ -245,7 +347,7 @@ impl AdvancedWalletManager {
     
     /// Get enhanced wallet information
     pub fn get_enhanced_wallet_info(&self) -> Option<EnhancedWalletInfo> {
-        self.wallet_info.lock().unwrap().clone()
+        self.wallet_info.lock_recover().clone()
     }
     
     /// Update enhanced wallet information
@@ -257,7 +359,18 @@ impl AdvancedWalletManager {
     
     /// Get advanced transactions
     pub fn get_advanced_transactions(&self) -> Vec<AdvancedTransactionInfo> {
-        self.transactions.lock().unwrap().clone()
+        self.transactions.lock_recover().clone()
+    }
+    
+    /// Search the stored transactions with their user metadata attached;
+    /// address labels take part in text search
+    pub fn search_transactions(&self, filter: &TransactionFilter, metadata: Option<&TransactionMetadataStore>) -> TransactionSearchResult {
+        let addresses = self.get_addresses();
+        let mut transactions = self.transactions.lock_recover().clone();
+        if let Some(store) = metadata {
+            store.merge_into(&mut transactions);
+        }
+        filter.apply(&transactions, &addresses)
     }
     
     /// Add advanced transaction
@@ -273,9 +386,43 @@ impl AdvancedWalletManager {
         }
     }
     
+    /// Add a transaction, replacing the stored one with the same hash
+    pub fn upsert_transaction(&self, transaction: AdvancedTransactionInfo) {
+        let mut transactions = self.transactions.lock_recover();
+        if let Some(existing) = transactions.iter_mut().find(|t| t.hash == transaction.hash) {
+            *existing = transaction;
+        } else {
+            drop(transactions);
+            self.add_transaction(transaction);
+        }
+    }
+    
+    /// Mark a stored transaction as included at `height`; false if it is unknown
+    pub fn confirm_transaction(&self, hash: &str, height: u64, confirmations: u32) -> bool {
+        let mut transactions = self.transactions.lock_recover();
+        match transactions.iter_mut().find(|t| t.hash == hash) {
+            Some(transaction) => {
+                transaction.height = height;
+                transaction.confirmations = confirmations;
+                transaction.is_confirmed = true;
+                transaction.is_pending = false;
+                true
+            }
+            None => false,
+        }
+    }
+    
+    /// Remove a stored transaction; false if it is unknown
+    pub fn remove_transaction(&self, hash: &str) -> bool {
+        let mut transactions = self.transactions.lock_recover();
+        let before = transactions.len();
+        transactions.retain(|t| t.hash != hash);
+        transactions.len() != before
+    }
+    
     /// Get address information
     pub fn get_addresses(&self) -> Vec<AddressInfo> {
-        self.addresses.lock().unwrap().clone()
+        self.addresses.lock_recover().clone()
     }
     
     /// Add address information
@@ -285,9 +432,47 @@ impl AdvancedWalletManager {
         }
     }
     
+    /// Reconcile the address list with the addresses reported by the wallet.
+    ///
+    /// The wallet is authoritative for which addresses exist and their labels;
+    /// locally tracked statistics and notes are kept for addresses that still exist.
+    pub fn reconcile_addresses(&self, wallet_addresses: &[WalletAddress]) -> Vec<AddressInfo> {
+        let mut addresses = self.addresses.lock_recover();
+        let reconciled: Vec<AddressInfo> = wallet_addresses.iter()
+            .map(|wallet_address| {
+                let mut info = addresses.iter()
+                    .find(|a| a.address == wallet_address.address)
+                    .cloned()
+                    .unwrap_or_else(|| AddressInfo {
+                        address: wallet_address.address.clone(),
+                        label: None,
+                        is_main_address: false,
+                        is_subaddress: false,
+                        subaddress_index: None,
+                        creation_time: None,
+                        last_used_time: None,
+                        transaction_count: 0,
+                        total_received: 0,
+                        total_sent: 0,
+                        balance: 0,
+                        is_active: true,
+                        notes: None,
+                    });
+                info.label = wallet_address.label.clone();
+                info.is_main_address = wallet_address.index == 0;
+                info.is_subaddress = wallet_address.index != 0;
+                info.subaddress_index = (wallet_address.index != 0).then_some(wallet_address.index);
+                info
+            })
+            .collect();
+        
+        *addresses = reconciled.clone();
+        reconciled
+    }
+    
     /// Get advanced network information
     pub fn get_network_info(&self) -> Option<AdvancedNetworkInfo> {
-        self.network_info.lock().unwrap().clone()
+        self.network_info.lock_recover().clone()
     }
     
     /// Update network information
@@ -299,7 +484,7 @@ impl AdvancedWalletManager {
     
     /// Get advanced mining information
     pub fn get_mining_info(&self) -> Option<AdvancedMiningInfo> {
-        self.mining_info.lock().unwrap().clone()
+        self.mining_info.lock_recover().clone()
     }
     
     /// Update mining information
@@ -311,7 +496,7 @@ impl AdvancedWalletManager {
     
     /// Get blockchain explorers
     pub fn get_explorers(&self) -> Vec<BlockchainExplorer> {
-        self.explorers.lock().unwrap().clone()
+        self.explorers.lock_recover().clone()
     }
     
     /// Add blockchain explorer
@@ -336,7 +521,7 @@ impl AdvancedWalletManager {
     
     /// Get operation history
     pub fn get_operation_history(&self) -> Vec<WalletOperation> {
-        self.operation_history.lock().unwrap().clone()
+        self.operation_history.lock_recover().clone()
     }
     
     /// Start operation tracking
@@ -353,6 +538,7 @@ impl AdvancedWalletManager {
             error: None,
             progress: Some(0.0),
             details: None,
+            started: Some(Instant::now()),
         };
         
         self.record_operation(operation);
@@ -364,8 +550,12 @@ impl AdvancedWalletManager {
         if let Ok(mut operations) = self.operation_history.lock() {
             if let Some(operation) = operations.iter_mut().find(|op| op.id == operation_id) {
                 operation.status = status.to_string();
-                operation.end_time = Some(SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs());
-                operation.duration = Some(Duration::from_secs(operation.end_time.unwrap() - operation.start_time));
+                let end_time = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+                operation.end_time = Some(end_time);
+                operation.duration = Some(match operation.started {
+                    Some(started) => started.elapsed(),
+                    None => Duration::from_secs(end_time.saturating_sub(operation.start_time)),
+                });
                 operation.result = result;
                 operation.error = error;
                 operation.progress = Some(1.0);
@@ -381,6 +571,22 @@ impl AdvancedWalletManager {
             }
         }
     }
+
+    /// Attach a detail to an operation. Values are cut to
+    /// `MAX_OPERATION_DETAIL_LEN` characters, and once an operation has
+    /// `MAX_OPERATION_DETAILS` entries only existing keys are updated.
+    pub fn set_operation_detail(&self, operation_id: &str, key: &str, value: &str) {
+        if let Ok(mut operations) = self.operation_history.lock()
+            && let Some(operation) = operations.iter_mut().find(|op| op.id == operation_id)
+        {
+            let details = operation.details.get_or_insert_with(HashMap::new);
+            if details.len() >= MAX_OPERATION_DETAILS && !details.contains_key(key) {
+                return;
+            }
+            let value: String = value.chars().take(MAX_OPERATION_DETAIL_LEN).collect();
+            details.insert(key.to_string(), value);
+        }
+    }
 }
 
 /// Advanced UI component manager
@@ -390,6 +596,15 @@ pub struct AdvancedUIManager {
     themes: Arc<Mutex<Vec<UITheme>>>,
     current_theme: Arc<Mutex<Option<String>>>,
     notifications: Arc<Mutex<Vec<UINotification>>>,
+    notification_subscribers: Arc<Mutex<Vec<Sender<NotificationEvent>>>>,
+}
+
+/// Change to the notification list, pushed to subscribers
+#[derive(Debug, Clone)]
+pub enum NotificationEvent {
+    Added(UINotification),
+    Updated(UINotification),
+    Cleared,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -461,6 +676,23 @@ impl AdvancedUIManager {
             themes: Arc::new(Mutex::new(Vec::new())),
             current_theme: Arc::new(Mutex::new(None)),
             notifications: Arc::new(Mutex::new(Vec::new())),
+            notification_subscribers: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+    
+    /// Receive every notification change from now on. Events can be sent from
+    /// any thread; subscribers whose receiver was dropped are removed.
+    pub fn subscribe_notifications(&self) -> Receiver<NotificationEvent> {
+        let (sender, receiver) = mpsc::channel();
+        if let Ok(mut subscribers) = self.notification_subscribers.lock() {
+            subscribers.push(sender);
+        }
+        receiver
+    }
+    
+    fn publish(&self, event: NotificationEvent) {
+        if let Ok(mut subscribers) = self.notification_subscribers.lock() {
+            subscribers.retain(|subscriber| subscriber.send(event.clone()).is_ok());
         }
     }
     
@@ -473,7 +705,7 @@ impl AdvancedUIManager {
     
     /// Get UI component
     pub fn get_component(&self, id: &str) -> Option<UIComponent> {
-        self.components.lock().unwrap().get(id).cloned()
+        self.components.lock_recover().get(id).cloned()
     }
     
     /// Update UI component
@@ -490,34 +722,53 @@ impl AdvancedUIManager {
         }
     }
     
-    /// Add UI theme
+    /// Add a theme, replacing any theme with the same name
     pub fn add_theme(&self, theme: UITheme) {
         if let Ok(mut themes) = self.themes.lock() {
-            themes.push(theme);
+            match themes.iter_mut().find(|t| t.name == theme.name) {
+                Some(existing) => *existing = theme,
+                None => themes.push(theme),
+            }
         }
     }
     
     /// Get available themes
     pub fn get_themes(&self) -> Vec<UITheme> {
-        self.themes.lock().unwrap().clone()
+        self.themes.lock_recover().clone()
     }
     
-    /// Set current theme
-    pub fn set_theme(&self, theme_name: &str) {
-        if let Ok(mut current_theme) = self.current_theme.lock() {
-            *current_theme = Some(theme_name.to_string());
-        }
+    /// Get a theme by name
+    pub fn get_theme(&self, theme_name: &str) -> Option<UITheme> {
+        self.themes.lock_recover().iter().find(|t| t.name == theme_name).cloned()
+    }
+    
+    /// Set current theme; unknown theme names are rejected
+    pub fn set_theme(&self, theme_name: &str) -> Result<UITheme, String> {
+        let theme = self.get_theme(theme_name)
+            .ok_or_else(|| format!("Unknown theme: {}", theme_name))?;
+        *self.current_theme.lock_recover() = Some(theme.name.clone());
+        Ok(theme)
     }
     
     /// Get current theme
     pub fn get_current_theme(&self) -> Option<String> {
-        self.current_theme.lock().unwrap().clone()
+        self.current_theme.lock_recover().clone()
     }
     
-    /// Add notification
-    pub fn add_notification(&self, notification: UINotification) {
+    /// Add a notification; returns false, and adds nothing, when a notification
+    /// with the same title and message was added within the dedup window
+    pub fn add_notification(&self, notification: UINotification) -> bool {
         if let Ok(mut notifications) = self.notifications.lock() {
-            notifications.push(notification);
+            let window = notifications::NOTIFICATION_DEDUP_WINDOW.as_secs();
+            let duplicate = notifications.iter().any(|n| {
+                n.title == notification.title
+                    && n.message == notification.message
+                    && n.timestamp.abs_diff(notification.timestamp) < window
+            });
+            if duplicate {
+                return false;
+            }
+            notifications.push(notification.clone());
             // Keep only last 50 notifications
             if notifications.len() > 50 {
                 let keep_count = 50;
@@ -525,28 +776,66 @@ impl AdvancedUIManager {
                 notifications.drain(0..remove_count);
             }
         }
+        self.publish(NotificationEvent::Added(notification));
+        true
     }
     
     /// Get notifications
     pub fn get_notifications(&self) -> Vec<UINotification> {
-        self.notifications.lock().unwrap().clone()
+        self.notifications.lock_recover().clone()
     }
     
-    /// Mark notification as read
-    pub fn mark_notification_read(&self, notification_id: &str) {
-        if let Ok(mut notifications) = self.notifications.lock() {
-            if let Some(notification) = notifications.iter_mut().find(|n| n.id == notification_id) {
-                notification.is_read = true;
-            }
-        }
+    /// Number of notifications neither read nor dismissed
+    pub fn unread_count(&self) -> usize {
+        self.notifications.lock_recover().iter()
+            .filter(|n| !n.is_read && !n.is_dismissed)
+            .count()
     }
     
-    /// Dismiss notification
-    pub fn dismiss_notification(&self, notification_id: &str) {
-        if let Ok(mut notifications) = self.notifications.lock() {
-            if let Some(notification) = notifications.iter_mut().find(|n| n.id == notification_id) {
-                notification.is_dismissed = true;
+    /// Remove every notification
+    pub fn clear_notifications(&self) {
+        self.notifications.lock_recover().clear();
+        self.publish(NotificationEvent::Cleared);
+    }
+    
+    /// Replace the notification list, e.g. with one restored from disk
+    pub fn restore_notifications(&self, restored: Vec<UINotification>) {
+        *self.notifications.lock_recover() = restored;
+    }
+    
+    /// Find an action of a notification
+    pub fn find_notification_action(&self, notification_id: &str, action_id: &str) -> Option<NotificationAction> {
+        self.notifications.lock_recover().iter()
+            .find(|n| n.id == notification_id)
+            .and_then(|n| n.actions.iter().find(|a| a.id == action_id).cloned())
+    }
+    
+    /// Mark notification as read; returns whether the notification exists
+    pub fn mark_notification_read(&self, notification_id: &str) -> bool {
+        self.update_notification(notification_id, |notification| notification.is_read = true)
+    }
+    
+    /// Dismiss notification; returns whether the notification exists
+    pub fn dismiss_notification(&self, notification_id: &str) -> bool {
+        self.update_notification(notification_id, |notification| notification.is_dismissed = true)
+    }
+    
+    fn update_notification<F: FnOnce(&mut UINotification)>(&self, notification_id: &str, update: F) -> bool {
+        let updated = self.notifications.lock().ok().and_then(|mut notifications| {
+            notifications.iter_mut()
+                .find(|n| n.id == notification_id)
+                .map(|notification| {
+                    update(notification);
+                    notification.clone()
+                })
+        });
+        
+        match updated {
+            Some(notification) => {
+                self.publish(NotificationEvent::Updated(notification));
+                true
             }
+            None => false,
         }
     }
 }
@@ -594,6 +883,60 @@ mod tests {
         assert!(manager.get_enhanced_wallet_info().is_some());
     }
     
+    #[test]
+    fn test_pending_send_is_upserted_and_confirmed() {
+        let manager = AdvancedWalletManager::new();
+        manager.upsert_transaction(AdvancedTransactionInfo::pending_send("tx1", 500, vec!["fire_dest".to_string()], None, 10));
+        manager.upsert_transaction(AdvancedTransactionInfo::pending_send("tx1", 500, vec!["fire_dest".to_string()], None, 11));
+        
+        let transactions = manager.get_advanced_transactions();
+        assert_eq!(transactions.len(), 1);
+        assert_eq!(transactions[0].amount, -500);
+        assert!(transactions[0].is_pending);
+        
+        assert!(manager.confirm_transaction("tx1", 1234, 1));
+        let confirmed = &manager.get_advanced_transactions()[0];
+        assert!(confirmed.is_confirmed && !confirmed.is_pending);
+        assert_eq!(confirmed.height, 1234);
+        
+        assert!(!manager.confirm_transaction("unknown", 1, 1));
+        assert!(manager.remove_transaction("tx1"));
+        assert!(manager.get_advanced_transactions().is_empty());
+    }
+    
+    #[test]
+    fn test_reconcile_addresses_follows_wallet_labels() {
+        let manager = AdvancedWalletManager::new();
+        let wallet_address = |index: u32, address: &str, label: Option<&str>| WalletAddress {
+            index,
+            address: address.to_string(),
+            label: label.map(String::from),
+        };
+        
+        manager.reconcile_addresses(&[
+            wallet_address(0, "fire_main", None),
+            wallet_address(1, "fire_sub", Some("Old")),
+            wallet_address(2, "fire_gone", None),
+        ]);
+        {
+            let mut addresses = manager.addresses.lock_recover();
+            addresses[1].transaction_count = 7;
+            addresses[1].label = Some("Drifted".to_string());
+        }
+        
+        let reconciled = manager.reconcile_addresses(&[
+            wallet_address(0, "fire_main", Some("Main")),
+            wallet_address(1, "fire_sub", Some("Savings")),
+        ]);
+        assert_eq!(reconciled.len(), 2);
+        assert!(reconciled[0].is_main_address);
+        assert_eq!(reconciled[0].label.as_deref(), Some("Main"));
+        assert_eq!(reconciled[1].label.as_deref(), Some("Savings"));
+        assert_eq!(reconciled[1].subaddress_index, Some(1));
+        assert_eq!(reconciled[1].transaction_count, 7);
+        assert_eq!(manager.get_addresses().len(), 2);
+    }
+    
     #[test]
     fn test_operation_tracking() {
         let manager = AdvancedWalletManager::new();
@@ -608,6 +951,78 @@ mod tests {
         assert_eq!(operations[0].status, "completed");
     }
     
+    fn notification(id: &str) -> UINotification {
+        UINotification {
+            id: id.to_string(),
+            title: "Title".to_string(),
+            message: "Message".to_string(),
+            notification_type: "info".to_string(),
+            timestamp: 1,
+            is_read: false,
+            is_dismissed: false,
+            actions: Vec::new(),
+            duration: None,
+        }
+    }
+    
+    #[test]
+    fn test_notification_events_reach_subscribers() {
+        let manager = Arc::new(AdvancedUIManager::new());
+        let receiver = manager.subscribe_notifications();
+        
+        // Published from a background thread, as the deposit watcher does
+        let background = manager.clone();
+        std::thread::spawn(move || background.add_notification(notification("n1")))
+            .join()
+            .unwrap();
+        assert!(manager.mark_notification_read("n1"));
+        assert!(manager.dismiss_notification("n1"));
+        assert!(!manager.dismiss_notification("unknown"));
+        
+        let events: Vec<NotificationEvent> = receiver.try_iter().collect();
+        assert_eq!(events.len(), 3);
+        assert!(matches!(&events[0], NotificationEvent::Added(n) if n.id == "n1" && n.message == "Message"));
+        assert!(matches!(&events[1], NotificationEvent::Updated(n) if n.is_read && !n.is_dismissed));
+        assert!(matches!(&events[2], NotificationEvent::Updated(n) if n.is_read && n.is_dismissed));
+    }
+    
+    #[test]
+    fn test_identical_notifications_are_deduplicated() {
+        let manager = AdvancedUIManager::new();
+        assert!(manager.add_notification(notification("n1")));
+        
+        let mut repeat = notification("n2");
+        repeat.timestamp = 1 + 299;
+        assert!(!manager.add_notification(repeat));
+        
+        let mut later = notification("n3");
+        later.timestamp = 1 + 300;
+        assert!(manager.add_notification(later));
+        
+        let mut different = notification("n4");
+        different.message = "Other".to_string();
+        assert!(manager.add_notification(different));
+        
+        assert_eq!(manager.unread_count(), 3);
+        manager.mark_notification_read("n1");
+        manager.dismiss_notification("n3");
+        assert_eq!(manager.unread_count(), 1);
+        
+        manager.clear_notifications();
+        assert!(manager.get_notifications().is_empty());
+    }
+    
+    #[test]
+    fn test_dropped_subscriber_is_removed() {
+        let manager = AdvancedUIManager::new();
+        drop(manager.subscribe_notifications());
+        let receiver = manager.subscribe_notifications();
+        
+        manager.add_notification(notification("n1"));
+        assert_eq!(manager.notification_subscribers.lock_recover().len(), 1);
+        assert_eq!(receiver.try_iter().count(), 1);
+    }
+    
     #[test]
     fn test_ui_manager() {
         let manager = AdvancedUIManager::new();
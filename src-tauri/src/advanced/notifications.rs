@@ -0,0 +1,104 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Notification actions
+//!
+//! Maps the well-known `NotificationAction::action_type` values to the
+//! operation the backend performs and the screen the frontend should open.
+//! The action id carries the target, e.g. the transaction hash for `open_tx`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+use super::NotificationAction;
+
+/// Identical notifications within this window are shown once
+pub const NOTIFICATION_DEDUP_WINDOW: Duration = Duration::from_secs(5 * 60);
+
+/// Backend side of a notification action
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NotificationOperation {
+    OpenTransaction { hash: String },
+    OpenDeposit { deposit_id: String },
+    RetryBackup,
+    OpenCrashReports,
+}
+
+/// Where the frontend should navigate after an action
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NavigationHint {
+    pub route: String,
+    pub params: HashMap<String, String>,
+}
+
+impl NavigationHint {
+    fn new(route: &str, params: &[(&str, &str)]) -> Self {
+        Self {
+            route: route.to_string(),
+            params: params.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+        }
+    }
+}
+
+/// Resolve an action to its operation and navigation hint
+pub fn dispatch_action(action: &NotificationAction) -> Result<(NotificationOperation, NavigationHint), String> {
+    match action.action_type.as_str() {
+        "open_tx" => Ok((
+            NotificationOperation::OpenTransaction { hash: action.id.clone() },
+            NavigationHint::new("/transactions", &[("hash", &action.id)]),
+        )),
+        "open_deposit" | "deposit_withdraw" => Ok((
+            NotificationOperation::OpenDeposit { deposit_id: action.id.clone() },
+            NavigationHint::new("/deposits", &[("id", &action.id)]),
+        )),
+        "retry_backup" => Ok((
+            NotificationOperation::RetryBackup,
+            NavigationHint::new("/backup", &[]),
+        )),
+        "open_crash_reports" => Ok((
+            NotificationOperation::OpenCrashReports,
+            NavigationHint::new("/crash-reports", &[]),
+        )),
+        other => Err(format!("Unsupported notification action: {}", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action(id: &str, action_type: &str) -> NotificationAction {
+        NotificationAction {
+            id: id.to_string(),
+            label: "Go".to_string(),
+            action_type: action_type.to_string(),
+            is_primary: true,
+        }
+    }
+
+    #[test]
+    fn test_well_known_actions_are_mapped() {
+        let (operation, hint) = dispatch_action(&action("abc123", "open_tx")).unwrap();
+        assert_eq!(operation, NotificationOperation::OpenTransaction { hash: "abc123".to_string() });
+        assert_eq!(hint.route, "/transactions");
+        assert_eq!(hint.params["hash"], "abc123");
+
+        for action_type in ["open_deposit", "deposit_withdraw"] {
+            let (operation, hint) = dispatch_action(&action("7", action_type)).unwrap();
+            assert_eq!(operation, NotificationOperation::OpenDeposit { deposit_id: "7".to_string() });
+            assert_eq!(hint.params["id"], "7");
+        }
+
+        let (operation, hint) = dispatch_action(&action("retry", "retry_backup")).unwrap();
+        assert_eq!(operation, NotificationOperation::RetryBackup);
+        assert_eq!(hint.route, "/backup");
+        assert!(hint.params.is_empty());
+
+        let (operation, hint) = dispatch_action(&action("crashes", "open_crash_reports")).unwrap();
+        assert_eq!(operation, NotificationOperation::OpenCrashReports);
+        assert_eq!(hint.route, "/crash-reports");
+
+        assert!(dispatch_action(&action("x", "launch_rockets")).unwrap_err().contains("launch_rockets"));
+    }
+}
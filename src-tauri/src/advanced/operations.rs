@@ -0,0 +1,134 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Operation history views
+//!
+//! The manager's operation history as handed to the frontend and written on
+//! export: newest first, with durations spelled out. Operations still running
+//! show the time elapsed so far next to their progress.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use crate::utils::storage::atomic_write;
+use super::{AdvancedWalletManager, WalletOperation};
+
+/// Most detail entries kept per operation
+pub const MAX_OPERATION_DETAILS: usize = 16;
+
+/// Longest detail value kept, in characters
+pub const MAX_OPERATION_DETAIL_LEN: usize = 256;
+
+/// An operation with its duration, or for running ones the time elapsed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperationEntry {
+    #[serde(flatten)]
+    pub operation: WalletOperation,
+    pub duration_ms: u64,
+    /// Duration as text, e.g. `1m 05s`
+    pub duration_text: String,
+}
+
+impl OperationEntry {
+    pub fn new(operation: WalletOperation) -> Self {
+        let duration = operation.duration
+            .or_else(|| operation.started.map(|started| started.elapsed()))
+            .unwrap_or_else(|| {
+                let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+                Duration::from_secs(operation.end_time.unwrap_or(now).saturating_sub(operation.start_time))
+            });
+        Self {
+            operation,
+            duration_ms: duration.as_millis() as u64,
+            duration_text: format_duration(duration),
+        }
+    }
+}
+
+impl AdvancedWalletManager {
+    /// Operations newest first, optionally only those with `status`
+    pub fn list_operations(&self, limit: Option<usize>, status: Option<&str>) -> Vec<OperationEntry> {
+        self.get_operation_history()
+            .into_iter()
+            .rev()
+            .filter(|op| status.is_none_or(|status| op.status.eq_ignore_ascii_case(status)))
+            .take(limit.unwrap_or(usize::MAX))
+            .map(OperationEntry::new)
+            .collect()
+    }
+
+    /// Write the whole history to `path` as JSON; returns the number of operations
+    pub fn export_operations(&self, path: &Path) -> Result<usize, String> {
+        let entries = self.list_operations(None, None);
+        let content = serde_json::to_string_pretty(&entries)
+            .map_err(|e| format!("Failed to serialize operation history: {}", e))?;
+        atomic_write(path, content.as_bytes())?;
+        Ok(entries.len())
+    }
+}
+
+/// Duration as text: `850ms`, `12.3s`, `1m 05s` or `2h 03m`
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs == 0 {
+        format!("{}ms", duration.as_millis())
+    } else if secs < 60 {
+        format!("{:.1}s", duration.as_secs_f64())
+    } else if secs < 3600 {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}h {:02}m", secs / 3600, secs % 3600 / 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duration_ignores_wall_clock_jumps() {
+        let manager = AdvancedWalletManager::new();
+        let id = manager.start_operation("rescan");
+
+        // The wall clock jumped back an hour while the operation ran
+        manager.operation_history.lock().unwrap()[0].start_time += 3_600;
+        manager.end_operation(&id, "completed", None, None);
+        let operation = &manager.get_operation_history()[0];
+        assert!(operation.duration.unwrap() < Duration::from_secs(1));
+
+        // Restored from disk there is no monotonic start; the wall clock is all there is
+        let mut restored = operation.clone();
+        restored.started = None;
+        restored.duration = None;
+        restored.start_time = 100;
+        restored.end_time = Some(165);
+        assert_eq!(OperationEntry::new(restored).duration_text, "1m 05s");
+        assert_eq!(format_duration(Duration::from_millis(850)), "850ms");
+        assert_eq!(format_duration(Duration::from_secs(7_380)), "2h 03m");
+    }
+
+    #[test]
+    fn test_list_filters_by_status() {
+        let manager = AdvancedWalletManager::new();
+        let backup = manager.start_operation("backup");
+        let rescan = manager.start_operation("rescan");
+        let failed = manager.start_operation("refresh");
+        manager.end_operation(&backup, "completed", None, None);
+        manager.end_operation(&failed, "failed", None, Some("node unreachable".to_string()));
+        manager.update_operation_progress(&rescan, 0.4);
+
+        let running = manager.list_operations(None, Some("running"));
+        assert_eq!(running.len(), 1);
+        assert_eq!((running[0].operation.id.as_str(), running[0].operation.progress), (rescan.as_str(), Some(0.4)));
+        assert_eq!(manager.list_operations(None, Some("FAILED"))[0].operation.error.as_deref(), Some("node unreachable"));
+        let newest: Vec<String> = manager.list_operations(Some(2), None).into_iter().map(|e| e.operation.id).collect();
+        assert_eq!(newest, vec![failed.clone(), rescan.clone()]);
+
+        for i in 0..MAX_OPERATION_DETAILS + 4 {
+            manager.set_operation_detail(&backup, &format!("key{}", i), &"x".repeat(1_000));
+        }
+        let details = manager.get_operation_history()[0].details.clone().unwrap();
+        assert_eq!(details.len(), MAX_OPERATION_DETAILS);
+        assert!(details.values().all(|v| v.len() == MAX_OPERATION_DETAIL_LEN));
+    }
+}
@@ -0,0 +1,291 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Price alerts
+//!
+//! Alerts on the XFG price crossing a threshold in a fiat currency, kept in
+//! `<config_dir>/fuego-wallet/price_alerts.json`. The price task fetches
+//! quotes only while there are alerts to evaluate. An alert fires once, or
+//! again after its cool-down while the price stays past the threshold.
+//! Quotes that are zero, not a number or older than `MAX_QUOTE_AGE` are
+//! ignored, so a broken feed never fires an alert.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use crate::utils::locks::MutexExt;
+use crate::utils::storage::{config_path, load_json_or_default, save_json};
+
+/// Background task fetching the price and evaluating alerts
+pub const PRICE_ALERT_TASK: &str = "price_alerts";
+/// Price feed queried for the XFG price
+pub const PRICE_FEED_URL: &str = "https://api.coingecko.com/api/v3/simple/price";
+/// Id of XFG at the price feed
+const PRICE_FEED_COIN: &str = "fuego";
+const PRICE_FEED_TIMEOUT: Duration = Duration::from_secs(15);
+/// Quotes last updated longer ago than this are stale
+pub const MAX_QUOTE_AGE: u64 = 30 * 60;
+
+pub fn price_alerts_path() -> Option<PathBuf> {
+    config_path("price_alerts.json")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertDirection {
+    Above,
+    Below,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceAlert {
+    pub id: String,
+    pub direction: AlertDirection,
+    pub threshold: f64,
+    /// Lowercase currency code, as `usd`
+    pub currency: String,
+    /// Seconds after firing before the alert may fire again; one-shot if `None`
+    pub repeat_after: Option<u64>,
+    pub created_at: u64,
+    pub last_fired_at: Option<u64>,
+    /// Price that last fired the alert
+    pub last_fired_price: Option<f64>,
+}
+
+impl PriceAlert {
+    /// Whether the alert may fire at `now`
+    pub fn is_armed(&self, now: u64) -> bool {
+        match (self.last_fired_at, self.repeat_after) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some(fired), Some(cool_down)) => now >= fired.saturating_add(cool_down),
+        }
+    }
+
+    fn is_crossed_by(&self, price: f64) -> bool {
+        match self.direction {
+            AlertDirection::Above => price >= self.threshold,
+            AlertDirection::Below => price <= self.threshold,
+        }
+    }
+}
+
+/// The XFG price in one currency
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceQuote {
+    pub currency: String,
+    pub price: f64,
+    /// Unix time the feed last updated the price
+    pub updated_at: u64,
+}
+
+impl PriceQuote {
+    /// Whether the quote can be acted on at `now`
+    pub fn is_usable(&self, now: u64) -> bool {
+        self.price.is_finite() && self.price > 0.0 && now.saturating_sub(self.updated_at) <= MAX_QUOTE_AGE
+    }
+}
+
+/// An alert that fired and the price that fired it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FiredAlert {
+    pub alert: PriceAlert,
+    pub price: f64,
+}
+
+/// Persisted list of price alerts
+#[derive(Debug)]
+pub struct PriceAlerts {
+    path: PathBuf,
+    alerts: Mutex<Vec<PriceAlert>>,
+}
+
+impl PriceAlerts {
+    /// Load the alerts at `path`; a missing file means no alerts
+    pub fn open(path: PathBuf) -> Result<Self, String> {
+        let alerts = load_json_or_default(&path, "price alerts")?;
+        Ok(Self {
+            path,
+            alerts: Mutex::new(alerts),
+        })
+    }
+
+    /// Oldest first
+    pub fn list(&self) -> Vec<PriceAlert> {
+        self.alerts.lock_recover().clone()
+    }
+
+    pub fn create(&self, direction: AlertDirection, threshold: f64, currency: &str, repeat_after: Option<u64>, now: u64) -> Result<PriceAlert, String> {
+        if !threshold.is_finite() || threshold <= 0.0 {
+            return Err(format!("Invalid price threshold: {}", threshold));
+        }
+        let currency = currency.trim().to_ascii_lowercase();
+        if !(3..=5).contains(&currency.len()) || !currency.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(format!("Invalid currency: {}", currency));
+        }
+        if repeat_after == Some(0) {
+            return Err("Repeat cool-down must be at least one second".to_string());
+        }
+
+        let alert = PriceAlert {
+            id: uuid::Uuid::new_v4().to_string(),
+            direction,
+            threshold,
+            currency,
+            repeat_after,
+            created_at: now,
+            last_fired_at: None,
+            last_fired_price: None,
+        };
+        let mut alerts = self.alerts.lock_recover();
+        alerts.push(alert.clone());
+        self.save(&alerts)?;
+        Ok(alert)
+    }
+
+    pub fn delete(&self, id: &str) -> Result<(), String> {
+        let mut alerts = self.alerts.lock_recover();
+        let index = alerts.iter().position(|a| a.id == id)
+            .ok_or_else(|| format!("Price alert not found: {}", id))?;
+        alerts.remove(index);
+        self.save(&alerts)
+    }
+
+    /// Currencies the alerts are set in, each once
+    pub fn currencies(&self) -> Vec<String> {
+        let mut currencies: Vec<String> = self.alerts.lock_recover().iter().map(|a| a.currency.clone()).collect();
+        currencies.sort();
+        currencies.dedup();
+        currencies
+    }
+
+    /// Fire the armed alerts `quotes` cross, skipping quotes that are not
+    /// usable at `now`, and save when any fired
+    pub fn evaluate(&self, quotes: &[PriceQuote], now: u64) -> Result<Vec<FiredAlert>, String> {
+        let mut alerts = self.alerts.lock_recover();
+        let mut fired = Vec::new();
+        for quote in quotes.iter().filter(|q| q.is_usable(now)) {
+            for alert in alerts.iter_mut().filter(|a| a.currency == quote.currency) {
+                if alert.is_armed(now) && alert.is_crossed_by(quote.price) {
+                    alert.last_fired_at = Some(now);
+                    alert.last_fired_price = Some(quote.price);
+                    fired.push(FiredAlert { alert: alert.clone(), price: quote.price });
+                }
+            }
+        }
+        if !fired.is_empty() {
+            self.save(&alerts)?;
+        }
+        Ok(fired)
+    }
+
+    fn save(&self, alerts: &[PriceAlert]) -> Result<(), String> {
+        save_json(&self.path, alerts, "price alerts")
+    }
+}
+
+/// The XFG price in `currencies` from the price feed
+pub async fn fetch_quotes(http: &reqwest::Client, currencies: &[String]) -> Result<Vec<PriceQuote>, String> {
+    let url = reqwest::Url::parse_with_params(PRICE_FEED_URL, &[
+        ("ids", PRICE_FEED_COIN),
+        ("vs_currencies", &currencies.join(",")),
+        ("include_last_updated_at", "true"),
+    ])
+    .map_err(|e| format!("Invalid price feed URL: {}", e))?;
+    let response = http.get(url)
+        .timeout(PRICE_FEED_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| if e.is_timeout() { "Price feed timed out".to_string() } else { e.to_string() })?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!("Price feed returned HTTP {}", status.as_u16()));
+    }
+    let body = response.json::<serde_json::Value>().await
+        .map_err(|e| format!("Invalid price feed response: {}", e))?;
+    Ok(parse_quotes(&body, currencies))
+}
+
+/// Quotes in a `simple/price` response; currencies it has no number for are left out
+pub fn parse_quotes(body: &serde_json::Value, currencies: &[String]) -> Vec<PriceQuote> {
+    let Some(coin) = body.get(PRICE_FEED_COIN) else {
+        return Vec::new();
+    };
+    let updated_at = coin.get("last_updated_at").and_then(|t| t.as_u64()).unwrap_or(0);
+    currencies.iter()
+        .filter_map(|currency| {
+            let price = coin.get(currency)?.as_f64()?;
+            Some(PriceQuote { currency: currency.clone(), price, updated_at })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::testing::TempDir;
+
+    fn usd(price: f64, updated_at: u64) -> Vec<PriceQuote> {
+        vec![PriceQuote { currency: "usd".to_string(), price, updated_at }]
+    }
+
+    #[test]
+    fn test_alerts_fire_once_or_rearm_after_cool_down() {
+        let dir = TempDir::new("fuego_price_alerts");
+        let path = dir.join("price_alerts.json");
+        let alerts = PriceAlerts::open(path.clone()).unwrap();
+        let once = alerts.create(AlertDirection::Above, 0.05, "USD", None, 0).unwrap();
+        let repeating = alerts.create(AlertDirection::Below, 0.01, "usd", Some(600), 0).unwrap();
+        alerts.create(AlertDirection::Above, 0.01, "eur", None, 0).unwrap();
+        assert!(alerts.create(AlertDirection::Above, 0.0, "usd", None, 0).is_err());
+        assert!(alerts.create(AlertDirection::Above, 1.0, "us$", None, 0).is_err());
+        assert_eq!(alerts.currencies(), vec!["eur", "usd"]);
+
+        // Scripted price feed: (time, price, time of the quote)
+        let script = [
+            (100, 0.03, 90),    // between the thresholds
+            (200, 0.06, 190),   // above: the one-shot fires
+            (300, 0.07, 290),   // still above, already fired
+            (400, 0.009, 390),  // below: the repeating one fires
+            (700, 0.008, 690),  // still below, cooling down
+            (1_000, 0.008, 990), // cool-down over
+        ];
+        let fired: Vec<(u64, String)> = script.iter()
+            .flat_map(|&(now, price, updated_at)| {
+                alerts.evaluate(&usd(price, updated_at), now).unwrap().into_iter().map(move |f| (now, f.alert.id))
+            })
+            .collect();
+        assert_eq!(fired, vec![(200, once.id.clone()), (400, repeating.id.clone()), (1_000, repeating.id.clone())]);
+
+        // Alerts and their state survive a restart
+        let reloaded = PriceAlerts::open(path.clone()).unwrap();
+        assert_eq!(reloaded.list(), alerts.list());
+        assert_eq!(reloaded.list()[0].last_fired_price, Some(0.06));
+        reloaded.delete(&once.id).unwrap();
+        assert!(reloaded.delete(&once.id).is_err());
+        assert_eq!(PriceAlerts::open(path.clone()).unwrap().list().len(), 2);
+    }
+
+    #[test]
+    fn test_stale_or_zero_quotes_never_fire() {
+        let dir = TempDir::new("fuego_price_alerts");
+        let path = dir.join("price_alerts.json");
+        let alerts = PriceAlerts::open(path.clone()).unwrap();
+        alerts.create(AlertDirection::Below, 0.01, "usd", Some(60), 0).unwrap();
+
+        let now = 10_000;
+        assert!(alerts.evaluate(&usd(0.0, now), now).unwrap().is_empty());
+        assert!(alerts.evaluate(&usd(f64::NAN, now), now).unwrap().is_empty());
+        assert!(alerts.evaluate(&usd(0.005, now - MAX_QUOTE_AGE - 1), now).unwrap().is_empty());
+        assert_eq!(alerts.evaluate(&usd(0.005, now - 60), now).unwrap().len(), 1);
+        assert_eq!(alerts.list()[0].last_fired_at, Some(now));
+
+        let body = serde_json::json!({ "fuego": { "usd": 0.012, "eur": null, "last_updated_at": 1_700_000_000u64 } });
+        assert_eq!(
+            parse_quotes(&body, &["usd".to_string(), "eur".to_string()]),
+            vec![PriceQuote { currency: "usd".to_string(), price: 0.012, updated_at: 1_700_000_000 }]
+        );
+        assert!(parse_quotes(&serde_json::json!({}), &["usd".to_string()]).is_empty());
+    }
+}
@@ -0,0 +1,340 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Transaction search
+//!
+//! Filtering, sorting and pagination over the transactions kept by the
+//! `AdvancedWalletManager`. Every filter field is optional so the frontend
+//! can send only what the user actually set.
+
+use serde::{Deserialize, Serialize};
+use super::{AddressInfo, AdvancedTransactionInfo};
+
+/// Default page size when the filter does not set a limit
+pub const DEFAULT_PAGE_SIZE: usize = 50;
+
+/// Largest page a single search returns
+pub const MAX_PAGE_SIZE: usize = 1000;
+
+/// Direction of a transaction from the wallet's point of view
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransactionDirection {
+    Sent,
+    Received,
+}
+
+/// Field search results are ordered by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionSortField {
+    #[default]
+    Timestamp,
+    Amount,
+    Fee,
+    Height,
+    Confirmations,
+}
+
+/// Transaction search criteria; unset fields do not filter
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TransactionFilter {
+    pub direction: Option<TransactionDirection>,
+    /// Minimum absolute amount in atomic units
+    pub min_amount: Option<u64>,
+    /// Maximum absolute amount in atomic units
+    pub max_amount: Option<u64>,
+    /// Earliest timestamp (inclusive)
+    pub from_timestamp: Option<u64>,
+    /// Latest timestamp (inclusive)
+    pub to_timestamp: Option<u64>,
+    pub confirmed: Option<bool>,
+    pub payment_id: Option<String>,
+    /// Substring of a source or destination address
+    pub address: Option<String>,
+    /// Case-insensitive text matched against hash, id, address labels and
+    /// the transaction's own label and note
+    pub text: Option<String>,
+    /// Tags the transaction must all carry, ignoring case
+    pub tags: Option<Vec<String>>,
+    pub sort_by: Option<TransactionSortField>,
+    /// Newest/largest first unless set to false
+    pub descending: Option<bool>,
+    pub offset: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+/// One page of search results
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionSearchResult {
+    pub items: Vec<AdvancedTransactionInfo>,
+    /// Number of transactions matching the filter, across all pages
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+}
+
+impl TransactionFilter {
+    /// Whether `tx` satisfies every criterion; `addresses` supply the labels for text search
+    pub fn matches(&self, tx: &AdvancedTransactionInfo, addresses: &[AddressInfo]) -> bool {
+        let amount = tx.amount.unsigned_abs();
+
+        if let Some(direction) = self.direction {
+            let tx_direction = if tx.amount < 0 { TransactionDirection::Sent } else { TransactionDirection::Received };
+            if tx_direction != direction {
+                return false;
+            }
+        }
+        if self.min_amount.is_some_and(|min| amount < min)
+            || self.max_amount.is_some_and(|max| amount > max)
+            || self.from_timestamp.is_some_and(|from| tx.timestamp < from)
+            || self.to_timestamp.is_some_and(|to| tx.timestamp > to)
+            || self.confirmed.is_some_and(|confirmed| tx.is_confirmed != confirmed)
+        {
+            return false;
+        }
+        if let Some(payment_id) = &self.payment_id
+            && !tx.payment_id.as_ref().is_some_and(|p| p.eq_ignore_ascii_case(payment_id))
+        {
+            return false;
+        }
+
+        if let Some(tags) = &self.tags
+            && !tags.iter().all(|tag| tx.metadata.as_ref().is_some_and(|m| m.has_tag(tag)))
+        {
+            return false;
+        }
+
+        let involved = || tx.destination_addresses.iter().chain(tx.source_addresses.iter());
+
+        if let Some(address) = &self.address
+            && !involved().any(|a| a.contains(address.as_str()))
+        {
+            return false;
+        }
+        if let Some(text) = self.text.as_deref().map(str::to_lowercase).filter(|t| !t.is_empty()) {
+            let label_matches = involved().any(|a| {
+                addresses.iter().any(|info| {
+                    &info.address == a
+                        && info.label.as_ref().is_some_and(|l| l.to_lowercase().contains(&text))
+                })
+            });
+            let metadata_matches = tx.metadata.as_ref().is_some_and(|m| {
+                [&m.label, &m.note].into_iter().flatten().any(|t| t.to_lowercase().contains(&text))
+            });
+            if !(tx.hash.to_lowercase().contains(&text) || tx.id.to_lowercase().contains(&text)
+                || label_matches || metadata_matches)
+            {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Filter, sort and paginate `transactions`
+    pub fn apply(&self, transactions: &[AdvancedTransactionInfo], addresses: &[AddressInfo]) -> TransactionSearchResult {
+        let mut matched: Vec<&AdvancedTransactionInfo> = transactions.iter()
+            .filter(|tx| self.matches(tx, addresses))
+            .collect();
+
+        let sort_by = self.sort_by.unwrap_or_default();
+        matched.sort_by(|a, b| {
+            let ordering = match sort_by {
+                TransactionSortField::Timestamp => a.timestamp.cmp(&b.timestamp),
+                TransactionSortField::Amount => a.amount.unsigned_abs().cmp(&b.amount.unsigned_abs()),
+                TransactionSortField::Fee => a.fee.cmp(&b.fee),
+                TransactionSortField::Height => a.height.cmp(&b.height),
+                TransactionSortField::Confirmations => a.confirmations.cmp(&b.confirmations),
+            };
+            // Ties are broken by hash so pages are stable between requests
+            ordering.then_with(|| a.hash.cmp(&b.hash))
+        });
+        if self.descending.unwrap_or(true) {
+            matched.reverse();
+        }
+
+        let total = matched.len();
+        let offset = self.offset.unwrap_or(0);
+        let limit = self.limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE);
+        let items = matched.into_iter()
+            .skip(offset)
+            .take(limit)
+            .cloned()
+            .collect();
+
+        TransactionSearchResult { items, total, offset, limit }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tx(hash: &str, amount: i64, timestamp: u64, confirmed: bool, address: &str) -> AdvancedTransactionInfo {
+        AdvancedTransactionInfo {
+            id: format!("id_{}", hash),
+            hash: hash.to_string(),
+            amount,
+            fee: 1000,
+            height: timestamp / 10,
+            timestamp,
+            confirmations: if confirmed { 10 } else { 0 },
+            is_confirmed: confirmed,
+            is_pending: !confirmed,
+            payment_id: (amount > 0).then(|| format!("PID{}", hash)),
+            destination_addresses: vec![address.to_string()],
+            source_addresses: Vec::new(),
+            unlock_time: None,
+            extra: None,
+            mixin: 5,
+            ring_size: 6,
+            key_images: Vec::new(),
+            outputs: Vec::new(),
+            inputs: Vec::new(),
+            block_hash: None,
+            block_timestamp: None,
+            mempool_timestamp: None,
+            relayed_by: None,
+            double_spend_seen: false,
+            rct_type: None,
+            version: 1,
+            metadata: None,
+            resolved_addresses: Vec::new(),
+        }
+    }
+
+    fn label(address: &str, label: &str) -> AddressInfo {
+        AddressInfo {
+            address: address.to_string(),
+            label: Some(label.to_string()),
+            is_main_address: false,
+            is_subaddress: true,
+            subaddress_index: Some(1),
+            creation_time: None,
+            last_used_time: None,
+            transaction_count: 0,
+            total_received: 0,
+            total_sent: 0,
+            balance: 0,
+            is_active: true,
+            notes: None,
+        }
+    }
+
+    fn sample() -> Vec<AdvancedTransactionInfo> {
+        vec![
+            tx("aa01", 5_000, 100, true, "fireAlice"),
+            tx("bb02", -20_000, 200, true, "fireBob"),
+            tx("cc03", 15_000, 300, false, "fireSavings"),
+            tx("dd04", -1_000, 400, true, "fireAlice"),
+            tx("ee05", 50_000, 500, true, "fireSavings"),
+        ]
+    }
+
+    fn hashes(result: &TransactionSearchResult) -> Vec<&str> {
+        result.items.iter().map(|t| t.hash.as_str()).collect()
+    }
+
+    #[test]
+    fn test_combined_filters() {
+        let txs = sample();
+        let addresses = vec![label("fireSavings", "Rainy day fund")];
+
+        let filter = TransactionFilter {
+            direction: Some(TransactionDirection::Received),
+            min_amount: Some(10_000),
+            confirmed: Some(true),
+            ..Default::default()
+        };
+        assert_eq!(hashes(&filter.apply(&txs, &addresses)), vec!["ee05"]);
+
+        let filter = TransactionFilter {
+            direction: Some(TransactionDirection::Sent),
+            from_timestamp: Some(200),
+            to_timestamp: Some(400),
+            max_amount: Some(5_000),
+            ..Default::default()
+        };
+        assert_eq!(hashes(&filter.apply(&txs, &addresses)), vec!["dd04"]);
+
+        let filter = TransactionFilter { text: Some("RAINY".to_string()), ..Default::default() };
+        assert_eq!(hashes(&filter.apply(&txs, &addresses)), vec!["ee05", "cc03"]);
+
+        let filter = TransactionFilter {
+            address: Some("Alice".to_string()),
+            payment_id: Some("pidaa01".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(hashes(&filter.apply(&txs, &addresses)), vec!["aa01"]);
+
+        let filter = TransactionFilter { text: Some("bb0".to_string()), confirmed: Some(false), ..Default::default() };
+        assert_eq!(filter.apply(&txs, &addresses).total, 0);
+    }
+
+    #[test]
+    fn test_tag_and_metadata_filters() {
+        let mut txs = sample();
+        txs[1].metadata = Some(crate::advanced::TransactionMetadata {
+            label: Some("Rent payment".to_string()),
+            tags: vec!["Housing".to_string(), "monthly".to_string()],
+            ..Default::default()
+        });
+        txs[3].metadata = Some(crate::advanced::TransactionMetadata {
+            note: Some("rent deposit refund".to_string()),
+            tags: vec!["monthly".to_string()],
+            ..Default::default()
+        });
+
+        let filter = TransactionFilter { tags: Some(vec!["MONTHLY".to_string()]), ..Default::default() };
+        assert_eq!(hashes(&filter.apply(&txs, &[])), vec!["dd04", "bb02"]);
+
+        let filter = TransactionFilter {
+            tags: Some(vec!["monthly".to_string(), "housing".to_string()]),
+            ..Default::default()
+        };
+        assert_eq!(hashes(&filter.apply(&txs, &[])), vec!["bb02"]);
+
+        let filter = TransactionFilter { text: Some("rent".to_string()), ..Default::default() };
+        assert_eq!(hashes(&filter.apply(&txs, &[])), vec!["dd04", "bb02"]);
+    }
+
+    #[test]
+    fn test_sorting_and_pagination_boundaries() {
+        let txs = sample();
+        let page = |offset, limit| TransactionFilter {
+            sort_by: Some(TransactionSortField::Amount),
+            descending: Some(false),
+            offset,
+            limit,
+            ..Default::default()
+        }.apply(&txs, &[]);
+
+        let first = page(None, Some(2));
+        assert_eq!((first.total, first.offset, first.limit), (5, 0, 2));
+        assert_eq!(hashes(&first), vec!["dd04", "aa01"]);
+        assert_eq!(hashes(&page(Some(4), Some(2))), vec!["ee05"]);
+
+        let past_end = page(Some(5), Some(2));
+        assert!(past_end.items.is_empty());
+        assert_eq!(past_end.total, 5);
+
+        assert_eq!(page(Some(0), Some(0)).items.len(), 0);
+        assert_eq!(page(None, Some(usize::MAX)).limit, MAX_PAGE_SIZE);
+    }
+
+    #[test]
+    fn test_filter_deserializes_with_all_fields_optional() {
+        let empty: TransactionFilter = serde_json::from_str("{}").unwrap();
+        assert_eq!(empty, TransactionFilter::default());
+        assert_eq!(empty.apply(&sample(), &[]).items[0].hash, "ee05");
+
+        let filter: TransactionFilter = serde_json::from_str(
+            r#"{"direction":"sent","sort_by":"fee","limit":1}"#
+        ).unwrap();
+        assert_eq!(filter.direction, Some(TransactionDirection::Sent));
+        assert_eq!(filter.sort_by, Some(TransactionSortField::Fee));
+        assert_eq!(filter.apply(&sample(), &[]).total, 2);
+    }
+}
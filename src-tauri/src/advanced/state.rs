@@ -0,0 +1,320 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Advanced wallet manager persistence
+//!
+//! Saves transactions, addresses, explorers, operation history and the last
+//! wallet/network snapshot of the `AdvancedWalletManager` so a new session
+//! does not start empty. Secrets are stripped before anything is written.
+//! The file carries a schema version; older layouts are migrated on load.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::utils::storage::{load_json, save_json, sidecar_path};
+use crate::utils::locks::MutexExt;
+use super::{
+    AddressInfo, AdvancedNetworkInfo, AdvancedTransactionInfo, AdvancedUIManager, AdvancedWalletManager,
+    BlockchainExplorer, EnhancedWalletInfo, UINotification, WalletOperation,
+};
+
+/// Schema version written by this build
+pub const STATE_SCHEMA_VERSION: u32 = 2;
+
+/// Background task saving the state periodically
+pub const STATE_SAVE_TASK: &str = "advanced_state_save";
+
+/// On-disk layout of the manager state
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PersistedWalletState {
+    pub schema_version: u32,
+    #[serde(default)]
+    pub saved_at: u64,
+    #[serde(default)]
+    pub transactions: Vec<AdvancedTransactionInfo>,
+    #[serde(default)]
+    pub addresses: Vec<AddressInfo>,
+    #[serde(default)]
+    pub explorers: Vec<BlockchainExplorer>,
+    #[serde(default)]
+    pub operation_history: Vec<WalletOperation>,
+    #[serde(default)]
+    pub wallet_info: Option<EnhancedWalletInfo>,
+    #[serde(default)]
+    pub network_info: Option<AdvancedNetworkInfo>,
+}
+
+impl PersistedWalletState {
+    /// Path of the state file for `wallet_path`
+    pub fn sidecar_path(wallet_path: &Path) -> PathBuf {
+        sidecar_path(wallet_path, ".state.json")
+    }
+
+    /// Parse a state file of any known schema version
+    pub fn from_json(content: &str) -> Result<Self, String> {
+        let mut value: serde_json::Value = serde_json::from_str(content)
+            .map_err(|e| format!("Failed to parse wallet state: {}", e))?;
+
+        let version = value.get("schema_version").and_then(|v| v.as_u64()).unwrap_or(1) as u32;
+        if version > STATE_SCHEMA_VERSION {
+            return Err(format!(
+                "Wallet state schema {} is newer than supported version {}",
+                version, STATE_SCHEMA_VERSION
+            ));
+        }
+        if version < 2 {
+            migrate_v1(&mut value);
+        }
+
+        serde_json::from_value(value).map_err(|e| format!("Failed to read wallet state: {}", e))
+    }
+
+    /// Remove keys, seed and credentials
+    fn strip_secrets(&mut self) {
+        if let Some(info) = self.wallet_info.as_mut() {
+            info.seed_phrase = None;
+            info.view_key = None;
+            info.spend_key = None;
+        }
+        if let Some(info) = self.network_info.as_mut() {
+            info.bootstrap_daemon_login = None;
+            info.bootstrap_daemon_password = None;
+        }
+    }
+}
+
+/// Version 1 files had no version field, called the operation history
+/// `history` and stored no wallet or network snapshot
+fn migrate_v1(value: &mut serde_json::Value) {
+    if let Some(object) = value.as_object_mut() {
+        if let Some(history) = object.remove("history") {
+            object.entry("operation_history").or_insert(history);
+        }
+        object.insert("schema_version".to_string(), serde_json::json!(STATE_SCHEMA_VERSION));
+    }
+}
+
+impl AdvancedWalletManager {
+    /// Snapshot the persistent part of the manager, without secrets
+    pub fn snapshot(&self) -> PersistedWalletState {
+        let mut state = PersistedWalletState {
+            schema_version: STATE_SCHEMA_VERSION,
+            saved_at: chrono::Utc::now().timestamp().max(0) as u64,
+            transactions: self.transactions.lock_recover().clone(),
+            addresses: self.addresses.lock_recover().clone(),
+            explorers: self.explorers.lock_recover().clone(),
+            operation_history: self.operation_history.lock_recover().clone(),
+            wallet_info: self.wallet_info.lock_recover().clone(),
+            network_info: self.network_info.lock_recover().clone(),
+        };
+        state.strip_secrets();
+        state
+    }
+
+    /// Write the manager state to `path` atomically
+    pub fn save_to(&self, path: &Path) -> Result<(), String> {
+        save_json(path, &self.snapshot(), "wallet state")
+    }
+
+    /// Replace the manager state with the one saved at `path`.
+    /// Returns false when there is no saved state yet.
+    pub fn load_from(&self, path: &Path) -> Result<bool, String> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(format!("Failed to read wallet state: {}", e)),
+        };
+        let mut state = PersistedWalletState::from_json(&content)?;
+        state.strip_secrets();
+
+        *self.transactions.lock_recover() = state.transactions;
+        *self.addresses.lock_recover() = state.addresses;
+        *self.explorers.lock_recover() = state.explorers;
+        *self.operation_history.lock_recover() = state.operation_history;
+        *self.wallet_info.lock_recover() = state.wallet_info;
+        *self.network_info.lock_recover() = state.network_info;
+        Ok(true)
+    }
+
+    /// Replace the manager state with the one saved at `path`, like
+    /// `load_from`, but start empty when there is none or it cannot be read,
+    /// so nothing of the previous wallet is kept
+    pub fn switch_to(&self, path: &Path) -> Result<bool, String> {
+        let loaded = self.load_from(path);
+        if !matches!(loaded, Ok(true)) {
+            *self.transactions.lock_recover() = Vec::new();
+            *self.addresses.lock_recover() = Vec::new();
+            *self.explorers.lock_recover() = Vec::new();
+            *self.operation_history.lock_recover() = Vec::new();
+            *self.wallet_info.lock_recover() = None;
+            *self.network_info.lock_recover() = None;
+        }
+        loaded
+    }
+}
+
+/// Path of the notification list saved next to the manager state
+pub fn notifications_path(wallet_path: &Path) -> PathBuf {
+    sidecar_path(wallet_path, ".notifications.json")
+}
+
+impl AdvancedUIManager {
+    /// Write the notification list to `path` atomically
+    pub fn save_notifications_to(&self, path: &Path) -> Result<(), String> {
+        save_json(path, &self.get_notifications(), "notifications")
+    }
+
+    /// Restore the notification list saved at `path`; false when there is none
+    pub fn load_notifications_from(&self, path: &Path) -> Result<bool, String> {
+        let Some(notifications) = load_json::<Vec<UINotification>>(path, "notifications")? else {
+            return Ok(false);
+        };
+        self.restore_notifications(notifications);
+        Ok(true)
+    }
+
+    /// Replace the notification list with the one saved at `path`, starting
+    /// empty when there is none or it cannot be read
+    pub fn switch_notifications_to(&self, path: &Path) -> Result<bool, String> {
+        let loaded = self.load_notifications_from(path);
+        if !matches!(loaded, Ok(true)) {
+            self.restore_notifications(Vec::new());
+        }
+        loaded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use crate::utils::testing::TempDir;
+
+    fn temp_path() -> (TempDir, PathBuf) {
+        let dir = TempDir::new("fuego_state");
+        let path = dir.join("test.wallet.state.json");
+        (dir, path)
+    }
+
+    fn explorer() -> BlockchainExplorer {
+        BlockchainExplorer {
+            name: "main".to_string(),
+            base_url: "https://explorer.fuego.money".to_string(),
+            api_endpoint: "/api".to_string(),
+            transaction_endpoint: "tx/{hash}".to_string(),
+            block_endpoint: "block/{block}".to_string(),
+            address_endpoint: "address/{address}".to_string(),
+            is_enabled: true,
+            timeout: Duration::from_secs(10),
+            retry_count: 2,
+        }
+    }
+
+    #[test]
+    fn test_round_trip_strips_secrets() {
+        let (_dir, path) = temp_path();
+        let manager = AdvancedWalletManager::new();
+        manager.add_explorer(explorer());
+        let op = manager.start_operation("refresh");
+        manager.end_operation(&op, "completed", Some("ok".to_string()), None);
+
+        let info: EnhancedWalletInfo = serde_json::from_value(serde_json::json!({
+            "address": "fire1main", "balance": 42, "unlocked_balance": 42, "locked_balance": 0,
+            "total_received": 42, "total_sent": 0, "transaction_count": 1, "is_synced": true,
+            "sync_height": 100, "network_height": 100, "daemon_height": 100, "is_connected": true,
+            "peer_count": 3, "last_block_time": null, "wallet_version": "1.0.0",
+            "seed_phrase": "twenty five secret words", "view_key": "view", "spend_key": "spend",
+            "restore_height": 0, "auto_refresh": true, "refresh_from_block_height": 0,
+            "subaddress_count": 1, "subaddress_lookahead": 1, "wallet_creation_time": null,
+            "last_backup_time": null, "last_sync_time": null, "sync_speed": 0.0, "estimated_sync_time": null
+        })).unwrap();
+        manager.update_wallet_info(info);
+
+        manager.save_to(&path).unwrap();
+        let written = fs::read_to_string(&path).unwrap();
+        assert!(!written.contains("secret words"));
+        assert!(!written.contains("\"spend\""));
+
+        let restored = AdvancedWalletManager::new();
+        assert!(restored.load_from(&path).unwrap());
+        assert_eq!(restored.get_explorers()[0].name, "main");
+        assert_eq!(restored.get_operation_history()[0].status, "completed");
+        let info = restored.get_enhanced_wallet_info().unwrap();
+        assert_eq!(info.balance, 42);
+        assert!(info.seed_phrase.is_none() && info.view_key.is_none() && info.spend_key.is_none());
+    }
+
+    #[test]
+    fn test_notifications_survive_restart() {
+        let (_dir, path) = temp_path();
+        let ui = AdvancedUIManager::new();
+        ui.add_notification(UINotification {
+            id: "n1".to_string(),
+            title: "Deposit matured".to_string(),
+            message: "Deposit 7 can be withdrawn".to_string(),
+            notification_type: "deposit".to_string(),
+            timestamp: 100,
+            is_read: false,
+            is_dismissed: false,
+            actions: vec![super::super::NotificationAction {
+                id: "7".to_string(),
+                label: "Withdraw".to_string(),
+                action_type: "deposit_withdraw".to_string(),
+                is_primary: true,
+            }],
+            duration: None,
+        });
+        ui.mark_notification_read("n1");
+        ui.save_notifications_to(&path).unwrap();
+
+        let restored = AdvancedUIManager::new();
+        assert!(restored.load_notifications_from(&path).unwrap());
+        let notifications = restored.get_notifications();
+        assert_eq!(notifications.len(), 1);
+        assert!(notifications[0].is_read);
+        assert_eq!(restored.find_notification_action("n1", "7").unwrap().action_type, "deposit_withdraw");
+        assert_eq!(restored.unread_count(), 0);
+    }
+
+    #[test]
+    fn test_old_schema_is_migrated() {
+        let (_dir, path) = temp_path();
+        fs::write(&path, r#"{
+            "transactions": [],
+            "addresses": [],
+            "explorers": [],
+            "history": [{"id":"op_1","operation_type":"sync","status":"completed","start_time":1,
+                         "end_time":2,"duration":null,"result":null,"error":null,"progress":1.0,"details":null}]
+        }"#).unwrap();
+
+        let manager = AdvancedWalletManager::new();
+        assert!(manager.load_from(&path).unwrap());
+        assert_eq!(manager.get_operation_history()[0].id, "op_1");
+        assert!(manager.get_enhanced_wallet_info().is_none());
+        assert_eq!(manager.snapshot().schema_version, STATE_SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_missing_and_future_files() {
+        let (_dir, path) = temp_path();
+        let manager = AdvancedWalletManager::new();
+        assert!(!manager.load_from(&path).unwrap());
+
+        fs::write(&path, r#"{"schema_version": 99}"#).unwrap();
+        assert!(manager.load_from(&path).unwrap_err().contains("newer than supported"));
+    }
+
+    #[test]
+    fn test_switching_to_a_wallet_without_state_starts_empty() {
+        let (dir, first) = temp_path();
+        let second = dir.join("second.wallet.state.json");
+        let manager = AdvancedWalletManager::new();
+        manager.add_explorer(explorer());
+        manager.save_to(&first).unwrap();
+
+        assert!(!manager.switch_to(&second).unwrap());
+        assert!(manager.get_explorers().is_empty());
+        assert!(manager.switch_to(&first).unwrap());
+        assert_eq!(manager.get_explorers()[0].name, "main");
+    }
+}
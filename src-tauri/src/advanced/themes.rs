@@ -0,0 +1,261 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! UI themes
+//!
+//! Built-in themes registered at startup, custom themes persisted to
+//! `<config>/fuego-wallet/themes.json`, and keeping the selected theme in
+//! `UISettings::theme` in sync with the `AdvancedUIManager`.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use crate::settings::SettingsManager;
+use crate::utils::storage::{config_path, load_json_or_default, save_json};
+use super::{AdvancedUIManager, UITheme};
+
+/// Theme used when the saved selection is not available
+pub const DEFAULT_THEME: &str = "dark";
+
+/// Names of the built-in themes, which custom themes may not reuse
+pub const BUILTIN_THEMES: [&str; 3] = ["dark", "light", "high-contrast"];
+
+fn theme(name: &str, display_name: &str, description: &str, is_dark: bool, colors: &[(&str, &str)]) -> UITheme {
+    let fonts = [
+        ("body", "Inter, system-ui, sans-serif"),
+        ("heading", "Inter, system-ui, sans-serif"),
+        ("mono", "JetBrains Mono, ui-monospace, monospace"),
+        ("size_base", "14px"),
+        ("size_small", "12px"),
+        ("size_large", "18px"),
+    ];
+    let spacing = [("xs", 4.0), ("sm", 8.0), ("md", 16.0), ("lg", 24.0), ("xl", 32.0), ("radius", 8.0)];
+
+    UITheme {
+        name: name.to_string(),
+        display_name: display_name.to_string(),
+        description: Some(description.to_string()),
+        colors: colors.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+        fonts: fonts.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+        spacing: spacing.iter().map(|(k, v)| (k.to_string(), *v)).collect(),
+        is_dark,
+        is_custom: false,
+    }
+}
+
+/// The themes shipped with the wallet
+pub fn builtin_themes() -> Vec<UITheme> {
+    vec![
+        theme("dark", "Dark", "Default dark theme", true, &[
+            ("background", "#121212"),
+            ("surface", "#1e1e1e"),
+            ("primary", "#ff6b00"),
+            ("secondary", "#ffa040"),
+            ("text", "#f5f5f5"),
+            ("text_muted", "#a0a0a0"),
+            ("border", "#2c2c2c"),
+            ("success", "#4caf50"),
+            ("warning", "#ffb300"),
+            ("error", "#ef5350"),
+        ]),
+        theme("light", "Light", "Light theme for bright environments", false, &[
+            ("background", "#fafafa"),
+            ("surface", "#ffffff"),
+            ("primary", "#e65c00"),
+            ("secondary", "#ff8f33"),
+            ("text", "#1a1a1a"),
+            ("text_muted", "#5f5f5f"),
+            ("border", "#dddddd"),
+            ("success", "#2e7d32"),
+            ("warning", "#ef6c00"),
+            ("error", "#c62828"),
+        ]),
+        theme("high-contrast", "High contrast", "Maximum contrast for accessibility", true, &[
+            ("background", "#000000"),
+            ("surface", "#000000"),
+            ("primary", "#ffff00"),
+            ("secondary", "#00ffff"),
+            ("text", "#ffffff"),
+            ("text_muted", "#ffffff"),
+            ("border", "#ffffff"),
+            ("success", "#00ff00"),
+            ("warning", "#ffff00"),
+            ("error", "#ff4040"),
+        ]),
+    ]
+}
+
+/// Path of the custom theme file
+pub fn custom_themes_path() -> Option<PathBuf> {
+    config_path("themes.json")
+}
+
+/// Check a custom theme and fill the entries it leaves out from the dark or
+/// light built-in theme
+pub fn resolve_custom_theme(mut custom: UITheme) -> Result<UITheme, String> {
+    let name = custom.name.trim().to_lowercase();
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(format!("Invalid theme name: {}", custom.name));
+    }
+    if BUILTIN_THEMES.contains(&name.as_str()) {
+        return Err(format!("Theme name is reserved: {}", name));
+    }
+
+    let base_name = if custom.is_dark { "dark" } else { "light" };
+    let base = builtin_themes().into_iter().find(|t| t.name == base_name).unwrap();
+    fill_missing(&mut custom.colors, base.colors);
+    fill_missing(&mut custom.fonts, base.fonts);
+    fill_missing(&mut custom.spacing, base.spacing);
+
+    if custom.display_name.trim().is_empty() {
+        custom.display_name = name.clone();
+    }
+    custom.name = name;
+    custom.is_custom = true;
+    Ok(custom)
+}
+
+fn fill_missing<V>(target: &mut HashMap<String, V>, base: HashMap<String, V>) {
+    for (key, value) in base {
+        target.entry(key).or_insert(value);
+    }
+}
+
+/// Load custom themes; a missing file means there are none
+pub fn load_custom_themes(path: &Path) -> Result<Vec<UITheme>, String> {
+    let themes: Vec<UITheme> = load_json_or_default(path, "custom themes")?;
+    themes.into_iter().map(resolve_custom_theme).collect()
+}
+
+/// Persist the custom themes of `ui`
+pub fn save_custom_themes(ui: &AdvancedUIManager, path: &Path) -> Result<(), String> {
+    let custom: Vec<UITheme> = ui.get_themes().into_iter().filter(|t| t.is_custom).collect();
+    save_json(path, &custom, "custom themes")
+}
+
+/// Select the theme saved in the settings, falling back to the default theme
+/// (and saving that) when the saved name is unknown. Returns the selected theme.
+pub fn sync_theme_with_settings(ui: &AdvancedUIManager, settings: &SettingsManager) -> Result<UITheme, String> {
+    let saved = settings.get_settings()?.ui.theme;
+    match ui.set_theme(&saved) {
+        Ok(theme) => Ok(theme),
+        Err(_) => {
+            log::warn!("Saved theme '{}' is not available, using '{}'", saved, DEFAULT_THEME);
+            select_theme(ui, settings, DEFAULT_THEME)
+        }
+    }
+}
+
+/// Save the theme `name` in the settings and select it; when the settings
+/// cannot be saved the current theme is kept
+pub fn select_theme(ui: &AdvancedUIManager, settings: &SettingsManager, name: &str) -> Result<UITheme, String> {
+    let theme = ui.get_theme(name)
+        .ok_or_else(|| format!("Unknown theme: {}", name))?;
+    settings.set_theme(&theme.name)?;
+    ui.set_theme(&theme.name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::testing::TempDir;
+
+    fn ui_with_builtins() -> AdvancedUIManager {
+        let ui = AdvancedUIManager::new();
+        for theme in builtin_themes() {
+            ui.add_theme(theme);
+        }
+        ui
+    }
+
+    fn custom(name: &str) -> UITheme {
+        UITheme {
+            name: name.to_string(),
+            display_name: "Ocean".to_string(),
+            description: None,
+            colors: [("primary".to_string(), "#0077be".to_string())].into_iter().collect(),
+            fonts: HashMap::new(),
+            spacing: HashMap::new(),
+            is_dark: true,
+            is_custom: false,
+        }
+    }
+
+    #[test]
+    fn test_builtin_themes_define_the_same_keys() {
+        let themes = builtin_themes();
+        assert_eq!(themes.iter().map(|t| t.name.as_str()).collect::<Vec<_>>(), BUILTIN_THEMES);
+        for theme in &themes {
+            assert_eq!(theme.colors.len(), themes[0].colors.len());
+            assert!(theme.colors.keys().all(|k| themes[0].colors.contains_key(k)));
+            assert!(!theme.fonts.is_empty() && !theme.spacing.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_custom_theme_round_trip() {
+        let dir = TempDir::new("fuego_themes");
+        let path = dir.join("themes.json");
+        let ui = ui_with_builtins();
+
+        assert!(resolve_custom_theme(custom("dark")).is_err());
+        assert!(resolve_custom_theme(custom("bad name!")).is_err());
+
+        let ocean = resolve_custom_theme(custom("Ocean")).unwrap();
+        assert_eq!(ocean.name, "ocean");
+        assert!(ocean.is_custom);
+        assert_eq!(ocean.colors["primary"], "#0077be");
+        assert_eq!(ocean.colors["background"], "#121212");
+        ui.add_theme(ocean.clone());
+        save_custom_themes(&ui, &path).unwrap();
+
+        let loaded = load_custom_themes(&path).unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].name, "ocean");
+        assert_eq!(loaded[0].colors, ocean.colors);
+        assert!(load_custom_themes(&dir.join("missing.json")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_selection_stays_in_sync_with_settings() {
+        let dir = TempDir::new("fuego_themes");
+        let settings = SettingsManager::with_config_path(dir.join("settings.json")).unwrap();
+        let ui = ui_with_builtins();
+        ui.add_theme(resolve_custom_theme(custom("ocean")).unwrap());
+
+        settings.set_theme("ocean").unwrap();
+        assert_eq!(sync_theme_with_settings(&ui, &settings).unwrap().name, "ocean");
+        assert_eq!(ui.get_current_theme().as_deref(), Some("ocean"));
+
+        settings.set_theme("removed-theme").unwrap();
+        assert_eq!(sync_theme_with_settings(&ui, &settings).unwrap().name, DEFAULT_THEME);
+        assert_eq!(settings.get_settings().unwrap().ui.theme, DEFAULT_THEME);
+        assert!(ui.set_theme("unknown").is_err());
+        assert_eq!(ui.get_current_theme().as_deref(), Some(DEFAULT_THEME));
+
+        select_theme(&ui, &settings, "ocean").unwrap();
+        assert_eq!(settings.get_settings().unwrap().ui.theme, "ocean");
+        assert!(select_theme(&ui, &settings, "unknown").is_err());
+        assert_eq!(settings.get_settings().unwrap().ui.theme, "ocean");
+        select_theme(&ui, &settings, DEFAULT_THEME).unwrap();
+        assert_eq!(ui.get_current_theme().as_deref(), Some(DEFAULT_THEME));
+    }
+
+    #[test]
+    fn test_failed_save_keeps_current_theme() {
+        let dir = TempDir::new("fuego_themes");
+        let config_dir = dir.join("config");
+        std::fs::create_dir(&config_dir).unwrap();
+        let settings = SettingsManager::with_config_path(config_dir.join("settings.json")).unwrap();
+        let ui = ui_with_builtins();
+        select_theme(&ui, &settings, DEFAULT_THEME).unwrap();
+
+        // A file where the config directory was makes every save fail
+        std::fs::remove_dir_all(&config_dir).unwrap();
+        std::fs::write(&config_dir, b"").unwrap();
+
+        let other = BUILTIN_THEMES.iter().find(|name| **name != DEFAULT_THEME).unwrap();
+        assert!(select_theme(&ui, &settings, other).is_err());
+        assert_eq!(ui.get_current_theme().as_deref(), Some(DEFAULT_THEME));
+        assert_eq!(settings.get_settings().unwrap().ui.theme, DEFAULT_THEME);
+    }
+}
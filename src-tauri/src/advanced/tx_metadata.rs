@@ -0,0 +1,233 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Transaction metadata
+//!
+//! Labels, notes, tags and categories the user attaches to transactions.
+//! They are local to this machine and stored next to the wallet file as
+//! `<wallet>.txmeta.json`, keyed by transaction hash.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use crate::utils::storage::{load_json_or_default, save_json, sidecar_path};
+use crate::utils::locks::MutexExt;
+use super::AdvancedTransactionInfo;
+
+/// Fee priority a transaction was sent with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeePriority {
+    /// Base and size fee only, nothing paid for faster inclusion
+    Slow,
+    Normal,
+    Fast,
+}
+
+/// User-provided information about one transaction
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TransactionMetadata {
+    pub label: Option<String>,
+    pub note: Option<String>,
+    pub tags: Vec<String>,
+    pub category: Option<String>,
+    /// Only known for transactions sent from this machine
+    pub priority: Option<FeePriority>,
+}
+
+impl TransactionMetadata {
+    pub fn is_empty(&self) -> bool {
+        self.label.is_none()
+            && self.note.is_none()
+            && self.tags.is_empty()
+            && self.category.is_none()
+            && self.priority.is_none()
+    }
+
+    /// Whether the transaction carries `tag`, ignoring case
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+}
+
+/// Persistent map of transaction hash to metadata
+#[derive(Debug)]
+pub struct TransactionMetadataStore {
+    path: PathBuf,
+    entries: Mutex<HashMap<String, TransactionMetadata>>,
+}
+
+impl TransactionMetadataStore {
+    /// Path of the metadata file for `wallet_path`
+    pub fn sidecar_path(wallet_path: &Path) -> PathBuf {
+        sidecar_path(wallet_path, ".txmeta.json")
+    }
+
+    /// Open the metadata store of a wallet; a missing file yields an empty store
+    pub fn for_wallet(wallet_path: &Path) -> Result<Self, String> {
+        Self::open(Self::sidecar_path(wallet_path))
+    }
+
+    /// Open the store persisted at `path`
+    pub fn open(path: PathBuf) -> Result<Self, String> {
+        let entries = load_json_or_default(&path, "transaction metadata")?;
+        Ok(Self {
+            path,
+            entries: Mutex::new(entries),
+        })
+    }
+
+    /// Metadata of a transaction, if any was set
+    pub fn get(&self, tx_hash: &str) -> Option<TransactionMetadata> {
+        self.entries.lock_recover().get(tx_hash).cloned()
+    }
+
+    pub fn set_label(&self, tx_hash: &str, label: Option<String>) -> Result<TransactionMetadata, String> {
+        self.update(tx_hash, |metadata| metadata.label = non_empty(label))
+    }
+
+    pub fn set_note(&self, tx_hash: &str, note: Option<String>) -> Result<TransactionMetadata, String> {
+        self.update(tx_hash, |metadata| metadata.note = non_empty(note))
+    }
+
+    pub fn set_category(&self, tx_hash: &str, category: Option<String>) -> Result<TransactionMetadata, String> {
+        self.update(tx_hash, |metadata| metadata.category = non_empty(category))
+    }
+
+    pub fn set_priority(&self, tx_hash: &str, priority: Option<FeePriority>) -> Result<TransactionMetadata, String> {
+        self.update(tx_hash, |metadata| metadata.priority = priority)
+    }
+
+    /// Add a tag; tags are unique ignoring case
+    pub fn add_tag(&self, tx_hash: &str, tag: &str) -> Result<TransactionMetadata, String> {
+        let tag = tag.trim();
+        if tag.is_empty() {
+            return Err("Tag cannot be empty".to_string());
+        }
+        self.update(tx_hash, |metadata| {
+            if !metadata.has_tag(tag) {
+                metadata.tags.push(tag.to_string());
+            }
+        })
+    }
+
+    pub fn remove_tag(&self, tx_hash: &str, tag: &str) -> Result<TransactionMetadata, String> {
+        let tag = tag.trim();
+        self.update(tx_hash, |metadata| metadata.tags.retain(|t| !t.eq_ignore_ascii_case(tag)))
+    }
+
+    /// Attach the stored metadata to each transaction
+    pub fn merge_into(&self, transactions: &mut [AdvancedTransactionInfo]) {
+        let entries = self.entries.lock_recover();
+        for tx in transactions {
+            tx.metadata = entries.get(&tx.hash).cloned();
+        }
+    }
+
+    /// Apply `change` and persist the whole store. The lock is held across the
+    /// write so concurrent updates are written one after another.
+    fn update<F>(&self, tx_hash: &str, change: F) -> Result<TransactionMetadata, String>
+    where
+        F: FnOnce(&mut TransactionMetadata),
+    {
+        if tx_hash.is_empty() {
+            return Err("Transaction hash cannot be empty".to_string());
+        }
+
+        let mut entries = self.entries.lock_recover();
+        let mut metadata = entries.get(tx_hash).cloned().unwrap_or_default();
+        change(&mut metadata);
+
+        let mut updated = entries.clone();
+        if metadata.is_empty() {
+            updated.remove(tx_hash);
+        } else {
+            updated.insert(tx_hash.to_string(), metadata.clone());
+        }
+
+        save_json(&self.path, &updated, "transaction metadata")?;
+
+        *entries = updated;
+        Ok(metadata)
+    }
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+    use crate::utils::testing::TempDir;
+
+    fn temp_wallet() -> (TempDir, PathBuf) {
+        let dir = TempDir::new("fuego_txmeta");
+        let wallet = dir.join("test.wallet");
+        (dir, wallet)
+    }
+
+    #[test]
+    fn test_metadata_survives_reload() {
+        let (_dir, wallet) = temp_wallet();
+        let store = TransactionMetadataStore::for_wallet(&wallet).unwrap();
+        store.set_label("abc", Some("Rent payment".to_string())).unwrap();
+        store.set_note("abc", Some("March".to_string())).unwrap();
+        store.set_category("abc", Some("housing".to_string())).unwrap();
+        store.add_tag("abc", "monthly").unwrap();
+        store.add_tag("abc", "MONTHLY").unwrap();
+        store.set_priority("abc", Some(FeePriority::Fast)).unwrap();
+        store.add_tag("def", "temp").unwrap();
+        store.remove_tag("def", "Temp").unwrap();
+        assert!(store.add_tag("abc", "  ").is_err());
+
+        let reloaded = TransactionMetadataStore::for_wallet(&wallet).unwrap();
+        assert_eq!(reloaded.get("abc"), Some(TransactionMetadata {
+            label: Some("Rent payment".to_string()),
+            note: Some("March".to_string()),
+            tags: vec!["monthly".to_string()],
+            category: Some("housing".to_string()),
+            priority: Some(FeePriority::Fast),
+        }));
+        // Entries left without any metadata are dropped
+        assert_eq!(reloaded.get("def"), None);
+    }
+
+    #[test]
+    fn test_concurrent_writes_are_all_persisted() {
+        let (_dir, wallet) = temp_wallet();
+        let store = Arc::new(TransactionMetadataStore::for_wallet(&wallet).unwrap());
+
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let store = store.clone();
+                thread::spawn(move || {
+                    for j in 0..5 {
+                        store.add_tag("shared", &format!("tag{}_{}", i, j)).unwrap();
+                        store.set_label(&format!("tx{}", i), Some(format!("label {}", j))).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let reloaded = TransactionMetadataStore::for_wallet(&wallet).unwrap();
+        assert_eq!(reloaded.get("shared").unwrap().tags.len(), 40);
+        for i in 0..8 {
+            assert_eq!(reloaded.get(&format!("tx{}", i)).unwrap().label, Some("label 4".to_string()));
+        }
+    }
+
+    #[test]
+    fn test_corrupt_file_is_reported() {
+        let (_dir, wallet) = temp_wallet();
+        std::fs::write(TransactionMetadataStore::sidecar_path(&wallet), b"not json").unwrap();
+        assert!(TransactionMetadataStore::for_wallet(&wallet).is_err());
+    }
+}
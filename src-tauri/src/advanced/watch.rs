@@ -0,0 +1,293 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Watched addresses
+//!
+//! Addresses outside this wallet (cold storage, for example) whose balance is
+//! followed through the blockchain explorers. The list and the last balance
+//! seen for each address are kept next to the wallet as
+//! `<wallet>.watched.json`. Polling backs off while the explorers fail.
+
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use crate::utils::storage::{load_json_or_default, save_json, sidecar_path};
+use crate::utils::locks::MutexExt;
+
+/// Background task polling the watched addresses
+pub const WATCHED_ADDRESS_TASK: &str = "watched_address_poll";
+
+/// Longest pause between polls while the explorers keep failing
+pub const MAX_POLL_BACKOFF: Duration = Duration::from_secs(60 * 60);
+
+/// Pause after the first failed poll; doubled for each further failure
+const POLL_BACKOFF_BASE: Duration = Duration::from_secs(60);
+
+/// An address followed through the explorers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WatchedAddress {
+    pub address: String,
+    pub label: String,
+    pub added_at: u64,
+    /// Balance in atomic units at the last successful poll
+    #[serde(default)]
+    pub last_balance: Option<u64>,
+    #[serde(default)]
+    pub last_checked: Option<u64>,
+}
+
+/// A watched address whose balance went up
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WatchedAddressDeposit {
+    pub address: String,
+    pub label: String,
+    pub received: u64,
+    pub balance: u64,
+}
+
+#[derive(Debug, Default)]
+struct PollBackoff {
+    failures: u32,
+    paused_until: Option<Instant>,
+}
+
+/// Persisted list of watched addresses
+#[derive(Debug)]
+pub struct WatchedAddresses {
+    path: PathBuf,
+    entries: Mutex<Vec<WatchedAddress>>,
+    backoff: Mutex<PollBackoff>,
+}
+
+impl WatchedAddresses {
+    /// Path of the watch list for `wallet_path`
+    pub fn sidecar_path(wallet_path: &Path) -> PathBuf {
+        sidecar_path(wallet_path, ".watched.json")
+    }
+
+    pub fn for_wallet(wallet_path: &Path) -> Result<Self, String> {
+        Self::open(Self::sidecar_path(wallet_path))
+    }
+
+    /// Load the watch list at `path`; a missing file means an empty list
+    pub fn open(path: PathBuf) -> Result<Self, String> {
+        let entries = load_json_or_default(&path, "watched addresses")?;
+        Ok(Self {
+            path,
+            entries: Mutex::new(entries),
+            backoff: Mutex::new(PollBackoff::default()),
+        })
+    }
+
+    pub fn list(&self) -> Vec<WatchedAddress> {
+        self.entries.lock_recover().clone()
+    }
+
+    /// Start watching `address`; it must pass `is_valid` and not be watched yet
+    pub fn add<F>(&self, address: &str, label: &str, is_valid: F) -> Result<WatchedAddress, String>
+    where
+        F: Fn(&str) -> bool,
+    {
+        let address = address.trim();
+        if !is_valid(address) {
+            return Err(format!("Invalid address: {}", address));
+        }
+
+        let mut entries = self.entries.lock_recover();
+        if entries.iter().any(|e| e.address == address) {
+            return Err(format!("Address is already watched: {}", address));
+        }
+        let watched = WatchedAddress {
+            address: address.to_string(),
+            label: label.trim().to_string(),
+            added_at: chrono::Utc::now().timestamp().max(0) as u64,
+            last_balance: None,
+            last_checked: None,
+        };
+        entries.push(watched.clone());
+        self.save(&entries)?;
+        Ok(watched)
+    }
+
+    /// Stop watching `address`; returns false if it was not watched
+    pub fn remove(&self, address: &str) -> Result<bool, String> {
+        let mut entries = self.entries.lock_recover();
+        let before = entries.len();
+        entries.retain(|e| e.address != address);
+        if entries.len() == before {
+            return Ok(false);
+        }
+        self.save(&entries)?;
+        Ok(true)
+    }
+
+    /// Store the balance seen for `address` at `now`. Returns the deposit if
+    /// the balance went up; the first balance seen is only recorded.
+    pub fn apply_balance(&self, address: &str, balance: u64, now: u64) -> Result<Option<WatchedAddressDeposit>, String> {
+        let mut entries = self.entries.lock_recover();
+        let Some(entry) = entries.iter_mut().find(|e| e.address == address) else {
+            return Ok(None);
+        };
+
+        let deposit = match entry.last_balance {
+            Some(previous) if balance > previous => Some(WatchedAddressDeposit {
+                address: entry.address.clone(),
+                label: entry.label.clone(),
+                received: balance - previous,
+                balance,
+            }),
+            _ => None,
+        };
+        entry.last_balance = Some(balance);
+        entry.last_checked = Some(now);
+        self.save(&entries)?;
+        Ok(deposit)
+    }
+
+    /// Whether a poll may run now, or the explorers are still given a rest
+    pub fn poll_due(&self) -> bool {
+        self.backoff.lock_recover().paused_until.is_none_or(|until| Instant::now() >= until)
+    }
+
+    /// Poll every watched address with `fetch_summary` (normally
+    /// `ExplorerClient::fetch_address_summary`) and return the ones that
+    /// received funds. If every lookup fails, polling is paused with
+    /// exponential backoff; one success resets it.
+    pub async fn poll<F, Fut>(&self, fetch_summary: F, now: u64) -> Result<Vec<WatchedAddressDeposit>, String>
+    where
+        F: Fn(String) -> Fut,
+        Fut: Future<Output = Result<serde_json::Value, String>>,
+    {
+        let addresses: Vec<String> = self.list().into_iter().map(|e| e.address).collect();
+        if addresses.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut deposits = Vec::new();
+        let mut last_error = None;
+        let mut succeeded = false;
+        for address in addresses {
+            let balance = fetch_summary(address.clone()).await
+                .and_then(|summary| summary_balance(&summary)
+                    .ok_or_else(|| "explorer response has no balance".to_string()));
+            match balance {
+                Ok(balance) => {
+                    succeeded = true;
+                    deposits.extend(self.apply_balance(&address, balance, now)?);
+                }
+                Err(e) => {
+                    log::warn!("Failed to poll watched address {}: {}", address, e);
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        let mut backoff = self.backoff.lock_recover();
+        if succeeded {
+            *backoff = PollBackoff::default();
+            Ok(deposits)
+        } else {
+            backoff.failures = backoff.failures.saturating_add(1);
+            let delay = poll_backoff_delay(backoff.failures);
+            backoff.paused_until = Some(Instant::now() + delay);
+            Err(format!(
+                "Watched address poll failed, retrying in {}s: {}",
+                delay.as_secs(),
+                last_error.unwrap_or_default()
+            ))
+        }
+    }
+
+    fn save(&self, entries: &[WatchedAddress]) -> Result<(), String> {
+        save_json(&self.path, entries, "watched addresses")
+    }
+}
+
+/// Pause after `failures` consecutive failed polls
+pub fn poll_backoff_delay(failures: u32) -> Duration {
+    let exponent = failures.saturating_sub(1).min(16);
+    (POLL_BACKOFF_BASE * 2u32.pow(exponent)).min(MAX_POLL_BACKOFF)
+}
+
+/// Balance in atomic units from an explorer address summary, which reports it
+/// as `balance` (number or string), possibly nested under `data`
+pub fn summary_balance(summary: &serde_json::Value) -> Option<u64> {
+    let balance = summary.get("balance")
+        .or_else(|| summary.get("data").and_then(|data| data.get("balance")))?;
+    balance.as_u64().or_else(|| balance.as_str().and_then(|s| s.parse().ok()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use crate::utils::testing::TempDir;
+
+    fn temp_store() -> (TempDir, WatchedAddresses, PathBuf) {
+        let dir = TempDir::new("fuego_watch");
+        let path = dir.join("test.wallet.watched.json");
+        (dir, WatchedAddresses::open(path.clone()).unwrap(), path)
+    }
+
+    fn is_valid(address: &str) -> bool {
+        address.starts_with("fire")
+    }
+
+    /// Explorer answering from a fixed table of balances; unknown addresses fail
+    fn mock_explorer(balances: &[(&str, u64)]) -> impl Fn(String) -> std::future::Ready<Result<serde_json::Value, String>> {
+        let balances: HashMap<String, u64> = balances.iter().map(|(a, b)| (a.to_string(), *b)).collect();
+        move |address| std::future::ready(balances.get(&address)
+            .map(|balance| serde_json::json!({"data": {"balance": balance.to_string()}}))
+            .ok_or_else(|| "HTTP 503".to_string()))
+    }
+
+    #[test]
+    fn test_add_validates_and_rejects_duplicates() {
+        let (_dir, store, path) = temp_store();
+        store.add("fire1cold", "Cold storage", is_valid).unwrap();
+        assert!(store.add("XFG1nope", "Bad", is_valid).unwrap_err().contains("Invalid address"));
+        assert!(store.add(" fire1cold ", "Again", is_valid).unwrap_err().contains("already watched"));
+
+        let reopened = WatchedAddresses::open(path.clone()).unwrap();
+        assert_eq!(reopened.list()[0].label, "Cold storage");
+        assert!(reopened.remove("fire1cold").unwrap());
+        assert!(!reopened.remove("fire1cold").unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_poll_reports_received_funds() {
+        let (_dir, store, _) = temp_store();
+        store.add("fire1cold", "Cold storage", is_valid).unwrap();
+        store.add("fire1vault", "Vault", is_valid).unwrap();
+
+        // The first balance seen is only recorded
+        assert!(store.poll(mock_explorer(&[("fire1cold", 100), ("fire1vault", 50)]), 10).await.unwrap().is_empty());
+
+        let deposits = store.poll(mock_explorer(&[("fire1cold", 175), ("fire1vault", 20)]), 20).await.unwrap();
+        assert_eq!(deposits, vec![WatchedAddressDeposit {
+            address: "fire1cold".to_string(),
+            label: "Cold storage".to_string(),
+            received: 75,
+            balance: 175,
+        }]);
+
+        // An address the explorer cannot answer keeps its last balance
+        assert!(store.poll(mock_explorer(&[("fire1vault", 20)]), 30).await.unwrap().is_empty());
+        let cold = store.list().into_iter().find(|e| e.address == "fire1cold").unwrap();
+        assert_eq!((cold.last_balance, cold.last_checked), (Some(175), Some(20)));
+    }
+
+    #[tokio::test]
+    async fn test_failing_explorer_backs_off() {
+        let (_dir, store, _) = temp_store();
+        store.add("fire1cold", "Cold storage", is_valid).unwrap();
+        assert!(store.poll_due());
+
+        assert!(store.poll(mock_explorer(&[]), 10).await.unwrap_err().contains("retrying in 60s"));
+        assert!(!store.poll_due());
+        assert_eq!(poll_backoff_delay(2), Duration::from_secs(120));
+        assert_eq!(poll_backoff_delay(30), MAX_POLL_BACKOFF);
+    }
+}
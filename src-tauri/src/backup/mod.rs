@@ -1,10 +1,16 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{SystemTime, UNIX_EPOCH};
 use zip::{write::FileOptions, CompressionMethod, ZipWriter};
 use std::io::Write;
+use crate::advanced::{AdvancedTransactionInfo, AdvancedWalletManager};
+use crate::settings::SettingsManager;
+use crate::utils::storage::{atomic_write, temp_path_for, APP_DIR_NAME};
+use crate::utils::locks::MutexExt;
 
 /// Backup information structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,6 +22,13 @@ pub struct BackupInfo {
     pub backup_type: BackupType,
     pub description: String,
     pub file_path: String,
+    /// Whether the archive matched its checksum manifest when last checked
+    #[serde(default)]
+    pub is_verified: bool,
+    /// Whether the archive predates checksum manifests, so it can only be
+    /// restored unverified
+    #[serde(default)]
+    pub is_legacy: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,6 +46,9 @@ pub struct BackupData {
     pub transactions: Option<Vec<serde_json::Value>>,
     pub settings: Option<serde_json::Value>,
     pub network_status: Option<serde_json::Value>,
+    /// Raw wallet file contents (Full and WalletOnly backups)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wallet_file: Option<Vec<u8>>,
     pub metadata: BackupMetadata,
 }
 
@@ -43,6 +59,172 @@ pub struct BackupMetadata {
     pub backup_type: BackupType,
     pub fuego_version: String,
     pub platform: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Argon2 hash of the backup password, if the backup is password protected
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password_hash: Option<String>,
+}
+
+impl BackupMetadata {
+    /// Protect the backup with a password that is checked on import
+    pub fn set_password(&mut self, password: &str) -> Result<(), String> {
+        use argon2::password_hash::{rand_core::OsRng, PasswordHasher, SaltString};
+        
+        let salt = SaltString::generate(&mut OsRng);
+        let hash = argon2::Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .map_err(|e| format!("Argon2 error: {}", e))?;
+        self.password_hash = Some(hash.to_string());
+        Ok(())
+    }
+    
+    /// Check `password` against the stored hash; unprotected backups accept anything
+    pub fn check_password(&self, password: Option<&str>) -> Result<(), String> {
+        use argon2::password_hash::{PasswordHash, PasswordVerifier};
+        
+        let Some(ref stored) = self.password_hash else {
+            return Ok(());
+        };
+        let password = password.ok_or("Backup is password protected")?;
+        let parsed = PasswordHash::new(stored)
+            .map_err(|e| format!("Invalid backup password hash: {}", e))?;
+        argon2::Argon2::default()
+            .verify_password(password.as_bytes(), &parsed)
+            .map_err(|_| "Invalid backup password".to_string())
+    }
+}
+
+/// Current layout version of backup archives
+pub const BACKUP_FORMAT_VERSION: u32 = 1;
+
+/// Format version reported for archives written before the manifest existed
+pub const LEGACY_BACKUP_FORMAT_VERSION: u32 = 0;
+
+const MANIFEST_NAME: &str = "manifest.json";
+const INDEX_NAME: &str = "backups_index.json";
+
+/// Checksum manifest stored as `manifest.json` in every archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub backup_format_version: u32,
+    pub members: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub name: String,
+    pub blake3: String,
+    pub size: u64,
+}
+
+/// Result of checking a backup archive against its manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupVerification {
+    /// Every member matched the manifest; never true for legacy archives
+    pub is_valid: bool,
+    /// The archive has no manifest, so its members could only be checked for
+    /// readability
+    #[serde(default)]
+    pub is_legacy: bool,
+    pub backup_format_version: Option<u32>,
+    pub corrupt_members: Vec<String>,
+    pub missing_members: Vec<String>,
+    pub error: Option<String>,
+}
+
+impl BackupVerification {
+    /// Whether the archive may be restored: verified against its manifest, or
+    /// a legacy archive whose members all read back
+    pub fn is_restorable(&self) -> bool {
+        self.is_valid || (self.is_legacy
+            && self.error.is_none()
+            && self.corrupt_members.is_empty()
+            && self.missing_members.is_empty())
+    }
+}
+
+/// Outcome of applying a restored backup, reported per section
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RestoreSummary {
+    pub settings_applied: bool,
+    pub wallet_restored: bool,
+    pub transactions_loaded: usize,
+    /// Errors of the sections that failed, as `section: message`
+    pub errors: Vec<String>,
+}
+
+/// Progress of an archive being written
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupProgress {
+    pub bytes_written: u64,
+    pub total_bytes: u64,
+    pub current_member: String,
+}
+
+/// Error returned when a backup is aborted through `BackupManager::cancel`
+pub const BACKUP_CANCELLED: &str = "Backup cancelled";
+
+/// Free space kept on top of the estimated archive size
+const DISK_SPACE_MARGIN: u64 = 1024 * 1024;
+
+/// Zip headers and directory entry of one member, generously
+const ZIP_MEMBER_OVERHEAD: u64 = 256;
+
+/// Why a backup could not be created
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BackupError {
+    /// The archive would not fit in the backup directory; nothing was written
+    InsufficientDiskSpace { required: u64, available: u64 },
+    /// Aborted through `BackupManager::cancel`
+    Cancelled,
+    Failed(String),
+}
+
+impl std::fmt::Display for BackupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BackupError::InsufficientDiskSpace { required, available } => write!(
+                f,
+                "Not enough disk space for the backup: {} bytes needed, {} available",
+                required, available
+            ),
+            BackupError::Cancelled => f.write_str(BACKUP_CANCELLED),
+            BackupError::Failed(message) => f.write_str(message),
+        }
+    }
+}
+
+impl From<String> for BackupError {
+    fn from(message: String) -> Self {
+        BackupError::Failed(message)
+    }
+}
+
+/// A newly created backup, with what writing it took
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupOutcome {
+    #[serde(flatten)]
+    pub info: BackupInfo,
+    /// Size of the archive on disk
+    pub bytes_written: u64,
+    /// Free space left in the backup directory, if the platform reports it
+    pub free_space_remaining: Option<u64>,
+}
+
+/// Free bytes on the filesystem holding a path, `None` if unknown
+pub type SpaceProbe = fn(&Path) -> Option<u64>;
+
+/// Free space of the disk mounted deepest above `path`
+pub fn available_space(path: &Path) -> Option<u64> {
+    let path = path.canonicalize().ok()?;
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+    disks.list().iter()
+        .filter(|disk| path.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| disk.available_space())
 }
 
 /// Backup manager
@@ -50,75 +232,241 @@ pub struct BackupMetadata {
 pub struct BackupManager {
     backups: Arc<Mutex<Vec<BackupInfo>>>,
     backup_dir: PathBuf,
+    cancel_requested: Arc<AtomicBool>,
+    space_probe: SpaceProbe,
 }
 
 impl BackupManager {
     pub fn new() -> Result<Self, String> {
         let backup_dir = dirs::data_dir()
             .ok_or("Failed to get data directory")?
-            .join("fuego-wallet")
+            .join(APP_DIR_NAME)
             .join("backups");
         
+        Self::with_dir(backup_dir)
+    }
+    
+    /// Create a backup manager storing its backups in `backup_dir`
+    pub fn with_dir(backup_dir: PathBuf) -> Result<Self, String> {
         fs::create_dir_all(&backup_dir)
             .map_err(|e| format!("Failed to create backup directory: {}", e))?;
         
         let manager = Self {
             backups: Arc::new(Mutex::new(Vec::new())),
             backup_dir,
+            cancel_requested: Arc::new(AtomicBool::new(false)),
+            space_probe: available_space,
         };
         
-        manager.scan_existing_backups()?;
+        manager.load_index()?;
         Ok(manager)
     }
     
+    /// Use `probe` instead of the filesystem to tell the free space
+    pub fn with_space_probe(mut self, probe: SpaceProbe) -> Self {
+        self.space_probe = probe;
+        self
+    }
+    
     pub fn create_backup(
         &self,
         name: String,
         description: String,
         backup_type: BackupType,
         data: BackupData,
-    ) -> Result<BackupInfo, String> {
+    ) -> Result<BackupOutcome, BackupError> {
+        self.create_backup_with_progress(name, description, backup_type, data, |_| {})
+    }
+    
+    /// Create a backup, reporting progress after each archive member.
+    ///
+    /// Nothing is written if the estimated archive size does not fit in the
+    /// free space of the backup directory. The archive is written to a
+    /// temporary file and renamed into place, so a failed or cancelled
+    /// backup leaves neither a partial archive nor an index entry behind.
+    pub fn create_backup_with_progress<F>(
+        &self,
+        name: String,
+        description: String,
+        backup_type: BackupType,
+        data: BackupData,
+        on_progress: F,
+    ) -> Result<BackupOutcome, BackupError>
+    where
+        F: FnMut(&BackupProgress),
+    {
+        let result = self.write_backup(name, description, backup_type, data, on_progress);
+        // A cancellation applies to the backup it was requested for only
+        self.cancel_requested.store(false, Ordering::SeqCst);
+        result
+    }
+    
+    /// Start a new backup operation, forgetting cancellations requested
+    /// before it. Callers that collect the backup data first call this when
+    /// the operation is registered, so a cancel during collection still
+    /// stops the backup.
+    pub fn begin(&self) {
+        self.cancel_requested.store(false, Ordering::SeqCst);
+    }
+    
+    fn write_backup<F>(
+        &self,
+        name: String,
+        description: String,
+        backup_type: BackupType,
+        data: BackupData,
+        on_progress: F,
+    ) -> Result<BackupOutcome, BackupError>
+    where
+        F: FnMut(&BackupProgress),
+    {
         let timestamp = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .map_err(|e| format!("Failed to get timestamp: {}", e))?
             .as_secs();
         
-        let backup_id = format!("backup_{}_{}", timestamp, uuid::Uuid::new_v4().to_string()[..8].to_string());
+        let backup_id = generate_backup_id(timestamp);
         let filename = format!("{}.zip", backup_id);
         let file_path = self.backup_dir.join(&filename);
         
-        // Create backup file
-        self.write_backup_file(&file_path, &data)?;
+        let mut data = data;
+        data.metadata.name = Some(name.clone());
+        data.metadata.description = Some(description.clone());
+        data.metadata.backup_type = backup_type.clone();
+        data.metadata.created_at = timestamp;
+        
+        let members = archive_members(&data)?;
+        
+        // Members are stored compressed, so their plain size is an upper bound
+        let required = members.iter()
+            .map(|(_, content)| content.len() as u64 + ZIP_MEMBER_OVERHEAD)
+            .sum::<u64>()
+            + DISK_SPACE_MARGIN;
+        if let Some(available) = (self.space_probe)(&self.backup_dir)
+            && available < required
+        {
+            return Err(BackupError::InsufficientDiskSpace { required, available });
+        }
+        
+        let temp_path = temp_path_for(&file_path);
+        let written = self.write_archive(&temp_path, &members, on_progress)
+            .and_then(|()| {
+                fs::rename(&temp_path, &file_path)
+                    .map_err(|e| BackupError::Failed(format!("Failed to move backup into place: {}", e)))
+            });
+        if let Err(e) = written {
+            let _ = fs::remove_file(&temp_path);
+            return Err(e);
+        }
         
-        // Get file size
-        let size_bytes = fs::metadata(&file_path)
-            .map_err(|e| format!("Failed to get file metadata: {}", e))?
-            .len();
+        let bytes_written = match fs::metadata(&file_path) {
+            Ok(metadata) => metadata.len(),
+            Err(e) => {
+                let _ = fs::remove_file(&file_path);
+                return Err(BackupError::Failed(format!("Failed to get file metadata: {}", e)));
+            }
+        };
         
         let backup_info = BackupInfo {
             id: backup_id,
             name,
             created_at: timestamp,
-            size_bytes,
+            size_bytes: bytes_written,
             backup_type,
             description,
             file_path: file_path.to_string_lossy().to_string(),
+            is_verified: true,
+            is_legacy: false,
         };
         
-        // Add to backups list
-        let mut backups = self.backups.lock()
-            .map_err(|e| format!("Failed to lock backups: {}", e))?;
-        backups.push(backup_info.clone());
+        self.backups.lock_recover()
+            .push(backup_info.clone());
         
-        // Save backups index
+        // An archive the index does not know about is not a finished backup
+        if let Err(e) = self.save_backups_index() {
+            self.backups.lock_recover().retain(|b| b.id != backup_info.id);
+            let _ = fs::remove_file(&file_path);
+            return Err(BackupError::Failed(e));
+        }
+        
+        Ok(BackupOutcome {
+            info: backup_info,
+            bytes_written,
+            free_space_remaining: (self.space_probe)(&self.backup_dir),
+        })
+    }
+    
+    /// Import an external backup archive into the managed backup directory.
+    ///
+    /// The archive must pass manifest verification and, if it is password
+    /// protected, the password check. A legacy archive without a manifest is
+    /// imported unverified. Importing an archive whose content is
+    /// already managed returns the existing backup instead of a copy.
+    pub fn import_backup(&self, source_path: &Path, password: Option<&str>) -> Result<BackupInfo, String> {
+        let content = fs::read(source_path)
+            .map_err(|e| format!("Failed to read backup file: {}", e))?;
+        
+        let (verification, members) = Self::verify_archive(source_path);
+        if let Some(error) = verification.error.clone() {
+            return Err(error);
+        }
+        if !verification.is_restorable() {
+            return Err(format!(
+                "Backup failed verification (corrupt: {:?}, missing: {:?})",
+                verification.corrupt_members, verification.missing_members
+            ));
+        }
+        
+        let metadata: BackupMetadata = members.get("metadata.json")
+            .ok_or("Backup has no metadata")
+            .and_then(|bytes| serde_json::from_slice(bytes)
+                .map_err(|_| "Failed to parse metadata"))?;
+        metadata.check_password(password)?;
+        
+        // Detect duplicates by content hash
+        let content_hash = blake3::hash(&content);
+        for existing in self.list_backups()? {
+            if let Ok(existing_content) = fs::read(&existing.file_path)
+                && blake3::hash(&existing_content) == content_hash
+            {
+                log::info!("Backup {} is already imported as {}", source_path.display(), existing.id);
+                return Ok(existing);
+            }
+        }
+        
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| format!("Failed to get timestamp: {}", e))?
+            .as_secs();
+        let backup_id = generate_backup_id(timestamp);
+        let file_path = self.backup_dir.join(format!("{}.zip", backup_id));
+        atomic_write(&file_path, &content)?;
+        
+        let source_name = source_path.file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let backup_info = BackupInfo {
+            id: backup_id,
+            name: metadata.name.clone().unwrap_or_else(|| source_name.clone()),
+            created_at: metadata.created_at,
+            size_bytes: content.len() as u64,
+            backup_type: metadata.backup_type.clone(),
+            description: metadata.description.clone()
+                .unwrap_or_else(|| format!("Imported from {}", source_name)),
+            file_path: file_path.to_string_lossy().to_string(),
+            is_verified: verification.is_valid,
+            is_legacy: verification.is_legacy,
+        };
+        
+        self.backups.lock_recover()
+            .push(backup_info.clone());
         self.save_backups_index()?;
         
         Ok(backup_info)
     }
     
     pub fn restore_backup(&self, backup_id: String) -> Result<BackupData, String> {
-        let backups = self.backups.lock()
-            .map_err(|e| format!("Failed to lock backups: {}", e))?;
+        let backups = self.backups.lock_recover();
         
         let backup_info = backups.iter()
             .find(|b| b.id == backup_id)
@@ -129,15 +477,95 @@ impl BackupManager {
         self.read_backup_file(file_path)
     }
     
+    /// Apply restored backup data to the running application.
+    ///
+    /// Each section is applied independently: a failure in one section is
+    /// recorded in the summary and leaves that section untouched, while the
+    /// remaining sections are still restored. `reopen_wallet` is called after
+    /// the wallet file has been placed; if it fails the previous file is put back.
+    pub fn apply_restore<F>(
+        &self,
+        data: &BackupData,
+        settings_manager: &SettingsManager,
+        wallet_manager: &AdvancedWalletManager,
+        wallet_path: &Path,
+        reopen_wallet: F,
+    ) -> RestoreSummary
+    where
+        F: Fn(&Path) -> Result<(), String>,
+    {
+        let mut summary = RestoreSummary::default();
+        
+        // Settings
+        if let Some(ref settings) = data.settings {
+            match settings_manager.import_settings(settings.clone()) {
+                Ok(_) => summary.settings_applied = true,
+                Err(e) => summary.errors.push(format!("settings: {}", e)),
+            }
+        }
+        
+        // Wallet file
+        if let Some(ref wallet_file) = data.wallet_file {
+            match Self::restore_wallet_file(wallet_file, wallet_path, &reopen_wallet) {
+                Ok(()) => summary.wallet_restored = true,
+                Err(e) => summary.errors.push(format!("wallet: {}", e)),
+            }
+        }
+        
+        // Transactions
+        if let Some(ref transactions) = data.transactions {
+            match transactions.iter().map(transaction_from_value).collect::<Result<Vec<_>, _>>() {
+                Ok(parsed) => {
+                    let known: Vec<String> = wallet_manager.get_advanced_transactions()
+                        .into_iter()
+                        .map(|t| t.hash)
+                        .collect();
+                    for transaction in parsed {
+                        if !known.contains(&transaction.hash) {
+                            wallet_manager.add_transaction(transaction);
+                            summary.transactions_loaded += 1;
+                        }
+                    }
+                }
+                Err(e) => summary.errors.push(format!("transactions: {}", e)),
+            }
+        }
+        
+        summary
+    }
+    
+    fn restore_wallet_file<F>(contents: &[u8], wallet_path: &Path, reopen_wallet: &F) -> Result<(), String>
+    where
+        F: Fn(&Path) -> Result<(), String>,
+    {
+        if contents.is_empty() {
+            return Err("Backup contains an empty wallet file".to_string());
+        }
+        
+        let previous = fs::read(wallet_path).ok();
+        atomic_write(wallet_path, contents)?;
+        
+        if let Err(e) = reopen_wallet(wallet_path) {
+            // Put the previous wallet back so the restore leaves no half-applied state
+            match previous {
+                Some(previous) => atomic_write(wallet_path, &previous)?,
+                None => {
+                    let _ = fs::remove_file(wallet_path);
+                }
+            }
+            return Err(format!("Failed to reopen restored wallet: {}", e));
+        }
+        
+        Ok(())
+    }
+    
     pub fn list_backups(&self) -> Result<Vec<BackupInfo>, String> {
-        let backups = self.backups.lock()
-            .map_err(|e| format!("Failed to lock backups: {}", e))?;
+        let backups = self.backups.lock_recover();
         Ok(backups.clone())
     }
     
     pub fn delete_backup(&self, backup_id: String) -> Result<(), String> {
-        let mut backups = self.backups.lock()
-            .map_err(|e| format!("Failed to lock backups: {}", e))?;
+        let mut backups = self.backups.lock_recover();
         
         let backup_info = backups.iter()
             .find(|b| b.id == backup_id)
@@ -150,6 +578,7 @@ impl BackupManager {
         
         // Remove from list
         backups.retain(|b| b.id != backup_id);
+        drop(backups);
         
         // Save backups index
         self.save_backups_index()?;
@@ -158,8 +587,7 @@ impl BackupManager {
     }
     
     pub fn export_backup(&self, backup_id: String, export_path: String) -> Result<(), String> {
-        let backups = self.backups.lock()
-            .map_err(|e| format!("Failed to lock backups: {}", e))?;
+        let backups = self.backups.lock_recover();
         
         let backup_info = backups.iter()
             .find(|b| b.id == backup_id)
@@ -175,7 +603,15 @@ impl BackupManager {
         Ok(())
     }
     
-    fn write_backup_file(&self, file_path: &PathBuf, data: &BackupData) -> Result<(), String> {
+    /// Request cancellation of the backup currently being written
+    pub fn cancel(&self) {
+        self.cancel_requested.store(true, Ordering::SeqCst);
+    }
+    
+    fn write_archive<F>(&self, file_path: &Path, members: &[(&str, Vec<u8>)], mut on_progress: F) -> Result<(), BackupError>
+    where
+        F: FnMut(&BackupProgress),
+    {
         let file = fs::File::create(file_path)
             .map_err(|e| format!("Failed to create backup file: {}", e))?;
         
@@ -184,181 +620,877 @@ impl BackupManager {
             .compression_method(CompressionMethod::Deflated)
             .unix_permissions(0o755);
         
-        // Write wallet data
-        if let Some(ref wallet_info) = data.wallet_info {
-            zip.start_file("wallet.json", options)
-                .map_err(|e| format!("Failed to start wallet file: {}", e))?;
-            let wallet_json = serde_json::to_string_pretty(wallet_info)
-                .map_err(|e| format!("Failed to serialize wallet: {}", e))?;
-            zip.write_all(wallet_json.as_bytes())
-                .map_err(|e| format!("Failed to write wallet data: {}", e))?;
+        let total_bytes: u64 = members.iter().map(|(_, content)| content.len() as u64).sum();
+        let mut bytes_written = 0u64;
+        
+        for (name, content) in members {
+            if self.cancel_requested.load(Ordering::SeqCst) {
+                return Err(BackupError::Cancelled);
+            }
+            
+            zip.start_file(*name, options)
+                .map_err(|e| format!("Failed to start {}: {}", name, e))?;
+            zip.write_all(content)
+                .map_err(|e| format!("Failed to write {}: {}", name, e))?;
+            
+            bytes_written += content.len() as u64;
+            on_progress(&BackupProgress {
+                bytes_written,
+                total_bytes,
+                current_member: name.to_string(),
+            });
         }
         
-        // Write transactions
-        if let Some(ref transactions) = data.transactions {
-            zip.start_file("transactions.json", options)
-                .map_err(|e| format!("Failed to start transactions file: {}", e))?;
-            let transactions_json = serde_json::to_string_pretty(transactions)
-                .map_err(|e| format!("Failed to serialize transactions: {}", e))?;
-            zip.write_all(transactions_json.as_bytes())
-                .map_err(|e| format!("Failed to write transactions data: {}", e))?;
+        let file = zip.finish()
+            .map_err(|e| format!("Failed to finish zip file: {}", e))?;
+        file.sync_all()
+            .map_err(|e| format!("Failed to flush backup file: {}", e))?;
+        
+        Ok(())
+    }
+    
+    /// Check every member of a backup archive against its manifest
+    pub fn verify_backup(&self, backup_id: String) -> Result<BackupVerification, String> {
+        let file_path = {
+            let backups = self.backups.lock_recover();
+            backups.iter()
+                .find(|b| b.id == backup_id)
+                .ok_or("Backup not found")?
+                .file_path
+                .clone()
+        };
+        
+        let (verification, _) = Self::verify_archive(Path::new(&file_path));
+        
+        if let Some(backup) = self.backups.lock_recover().iter_mut().find(|b| b.id == backup_id) {
+            backup.is_verified = verification.is_valid;
+            backup.is_legacy = verification.is_legacy;
         }
         
-        // Write settings
-        if let Some(ref settings) = data.settings {
-            zip.start_file("settings.json", options)
-                .map_err(|e| format!("Failed to start settings file: {}", e))?;
-            let settings_json = serde_json::to_string_pretty(settings)
-                .map_err(|e| format!("Failed to serialize settings: {}", e))?;
-            zip.write_all(settings_json.as_bytes())
-                .map_err(|e| format!("Failed to write settings data: {}", e))?;
+        Ok(verification)
+    }
+    
+    /// Read all members of an archive and check them against `manifest.json`.
+    /// Archives without a manifest are checked as legacy backups.
+    ///
+    /// Returns the verification report together with the member contents, which
+    /// are only meaningful when the archive is restorable.
+    fn verify_archive(file_path: &Path) -> (BackupVerification, HashMap<String, Vec<u8>>) {
+        let mut verification = BackupVerification {
+            is_valid: false,
+            is_legacy: false,
+            backup_format_version: None,
+            corrupt_members: Vec::new(),
+            missing_members: Vec::new(),
+            error: None,
+        };
+        let mut contents = HashMap::new();
+        
+        let archive = fs::File::open(file_path)
+            .map_err(|e| format!("Failed to open backup file: {}", e))
+            .and_then(|file| zip::ZipArchive::new(file)
+                .map_err(|e| format!("Failed to read zip archive: {}", e)));
+        let mut archive = match archive {
+            Ok(archive) => archive,
+            Err(e) => {
+                verification.error = Some(e);
+                return (verification, contents);
+            }
+        };
+        
+        if archive.by_name(MANIFEST_NAME).is_err() {
+            Self::verify_legacy_archive(&mut archive, &mut verification, &mut contents);
+            return (verification, contents);
         }
         
-        // Write network status
-        if let Some(ref network_status) = data.network_status {
-            zip.start_file("network_status.json", options)
-                .map_err(|e| format!("Failed to start network status file: {}", e))?;
-            let network_json = serde_json::to_string_pretty(network_status)
-                .map_err(|e| format!("Failed to serialize network status: {}", e))?;
-            zip.write_all(network_json.as_bytes())
-                .map_err(|e| format!("Failed to write network status data: {}", e))?;
+        let manifest = match Self::read_member(&mut archive, MANIFEST_NAME)
+            .and_then(|bytes| serde_json::from_slice::<BackupManifest>(&bytes)
+                .map_err(|e| format!("Failed to parse manifest: {}", e)))
+        {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                verification.error = Some(format!("Invalid manifest: {}", e));
+                return (verification, contents);
+            }
+        };
+        verification.backup_format_version = Some(manifest.backup_format_version);
+        
+        if manifest.backup_format_version > BACKUP_FORMAT_VERSION {
+            verification.error = Some(format!(
+                "Unsupported backup format version {}", manifest.backup_format_version
+            ));
+            return (verification, contents);
         }
         
-        // Write metadata
-        zip.start_file("metadata.json", options)
-            .map_err(|e| format!("Failed to start metadata file: {}", e))?;
-        let metadata_json = serde_json::to_string_pretty(&data.metadata)
-            .map_err(|e| format!("Failed to serialize metadata: {}", e))?;
-        zip.write_all(metadata_json.as_bytes())
-            .map_err(|e| format!("Failed to write metadata: {}", e))?;
+        for entry in &manifest.members {
+            if archive.by_name(&entry.name).is_err() {
+                verification.missing_members.push(entry.name.clone());
+                continue;
+            }
+            match Self::read_member(&mut archive, &entry.name) {
+                Ok(bytes) if blake3::hash(&bytes).to_hex().as_str() == entry.blake3 => {
+                    contents.insert(entry.name.clone(), bytes);
+                }
+                _ => verification.corrupt_members.push(entry.name.clone()),
+            }
+        }
         
-        zip.finish()
-            .map_err(|e| format!("Failed to finish zip file: {}", e))?;
+        verification.is_valid = verification.corrupt_members.is_empty()
+            && verification.missing_members.is_empty();
+        (verification, contents)
+    }
+    
+    /// Check an archive written before manifests existed: every member must
+    /// pass its zip checksum, JSON members must parse and `metadata.json`,
+    /// which every such backup carried, must be present. Without a manifest
+    /// nothing vouches for the content, so the archive is never valid, only
+    /// restorable.
+    fn verify_legacy_archive(
+        archive: &mut zip::ZipArchive<fs::File>,
+        verification: &mut BackupVerification,
+        contents: &mut HashMap<String, Vec<u8>>,
+    ) {
+        verification.backup_format_version = Some(LEGACY_BACKUP_FORMAT_VERSION);
+        verification.is_legacy = true;
         
-        Ok(())
+        let names: Vec<String> = archive.file_names().map(str::to_string).collect();
+        for name in names {
+            match Self::read_member(archive, &name) {
+                Ok(bytes) if !name.ends_with(".json")
+                    || serde_json::from_slice::<serde_json::Value>(&bytes).is_ok() =>
+                {
+                    contents.insert(name, bytes);
+                }
+                _ => verification.corrupt_members.push(name),
+            }
+        }
+        
+        if archive.by_name("metadata.json").is_err() {
+            verification.missing_members.push("metadata.json".to_string());
+        }
+    }
+    
+    fn read_member(archive: &mut zip::ZipArchive<fs::File>, name: &str) -> Result<Vec<u8>, String> {
+        let mut member = archive.by_name(name)
+            .map_err(|e| format!("Failed to find {}: {}", name, e))?;
+        let mut bytes = Vec::new();
+        std::io::Read::read_to_end(&mut member, &mut bytes)
+            .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+        Ok(bytes)
     }
     
     fn read_backup_file(&self, file_path: &Path) -> Result<BackupData, String> {
-        let file = fs::File::open(file_path)
-            .map_err(|e| format!("Failed to open backup file: {}", e))?;
+        let (verification, contents) = Self::verify_archive(file_path);
+        if let Some(error) = verification.error.clone() {
+            return Err(error);
+        }
+        if !verification.is_restorable() {
+            return Err(format!(
+                "Backup failed verification (corrupt: {:?}, missing: {:?})",
+                verification.corrupt_members, verification.missing_members
+            ));
+        }
         
-        let mut archive = zip::ZipArchive::new(file)
-            .map_err(|e| format!("Failed to read zip archive: {}", e))?;
+        let member = |name: &str| -> Result<Option<serde_json::Value>, String> {
+            contents.get(name)
+                .map(|bytes| serde_json::from_slice(bytes)
+                    .map_err(|e| format!("Failed to parse {}: {}", name, e)))
+                .transpose()
+        };
         
-        let mut backup_data = BackupData {
-            wallet_info: None,
-            transactions: None,
-            settings: None,
-            network_status: None,
-            metadata: BackupMetadata {
+        let metadata = match member("metadata.json")? {
+            Some(value) => serde_json::from_value(value)
+                .map_err(|e| format!("Failed to parse metadata: {}", e))?,
+            None => BackupMetadata {
                 version: "1.0.0".to_string(),
                 created_at: 0,
                 backup_type: BackupType::Full,
                 fuego_version: "1.0.0".to_string(),
                 platform: std::env::consts::OS.to_string(),
+                name: None,
+                description: None,
+                password_hash: None,
             },
         };
         
-        // Read metadata
-        if let Ok(mut metadata_file) = archive.by_name("metadata.json") {
-            let mut metadata_content = String::new();
-            std::io::Read::read_to_string(&mut metadata_file, &mut metadata_content)
-                .map_err(|e| format!("Failed to read metadata: {}", e))?;
-            backup_data.metadata = serde_json::from_str(&metadata_content)
-                .map_err(|e| format!("Failed to parse metadata: {}", e))?;
-        }
-        
-        // Read wallet data
-        if let Ok(mut wallet_file) = archive.by_name("wallet.json") {
-            let mut wallet_content = String::new();
-            std::io::Read::read_to_string(&mut wallet_file, &mut wallet_content)
-                .map_err(|e| format!("Failed to read wallet data: {}", e))?;
-            backup_data.wallet_info = Some(serde_json::from_str(&wallet_content)
-                .map_err(|e| format!("Failed to parse wallet data: {}", e))?);
-        }
+        let transactions = match member("transactions.json")? {
+            Some(value) => Some(serde_json::from_value(value)
+                .map_err(|e| format!("Failed to parse transactions: {}", e))?),
+            None => None,
+        };
         
-        // Read transactions
-        if let Ok(mut transactions_file) = archive.by_name("transactions.json") {
-            let mut transactions_content = String::new();
-            std::io::Read::read_to_string(&mut transactions_file, &mut transactions_content)
-                .map_err(|e| format!("Failed to read transactions: {}", e))?;
-            backup_data.transactions = Some(serde_json::from_str(&transactions_content)
-                .map_err(|e| format!("Failed to parse transactions: {}", e))?);
-        }
+        Ok(BackupData {
+            wallet_info: member("wallet.json")?,
+            transactions,
+            settings: member("settings.json")?,
+            network_status: member("network_status.json")?,
+            wallet_file: contents.get("wallet.bin").cloned(),
+            metadata,
+        })
+    }
+    
+    /// Load the backups index and reconcile it with the backup directory,
+    /// rebuilding it from the archives when it is missing or unreadable
+    fn load_index(&self) -> Result<(), String> {
+        let index_path = self.backup_dir.join(INDEX_NAME);
+        let index = fs::read_to_string(&index_path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<Vec<BackupInfo>>(&content).ok());
         
-        // Read settings
-        if let Ok(mut settings_file) = archive.by_name("settings.json") {
-            let mut settings_content = String::new();
-            std::io::Read::read_to_string(&mut settings_file, &mut settings_content)
-                .map_err(|e| format!("Failed to read settings: {}", e))?;
-            backup_data.settings = Some(serde_json::from_str(&settings_content)
-                .map_err(|e| format!("Failed to parse settings: {}", e))?);
+        match index {
+            Some(index) => self.reconcile_index(index),
+            None => {
+                if index_path.exists() {
+                    log::warn!("Backups index is corrupt, rebuilding it");
+                }
+                self.rebuild_index().map(|_| ())
+            }
         }
+    }
+    
+    /// Drop index entries whose archive vanished and add untracked archives as unverified
+    fn reconcile_index(&self, index: Vec<BackupInfo>) -> Result<(), String> {
+        let mut backups: Vec<BackupInfo> = index.into_iter()
+            .filter(|b| Path::new(&b.file_path).exists())
+            .map(|mut b| {
+                let verification = Self::verify_archive(Path::new(&b.file_path)).0;
+                b.is_verified = verification.is_valid;
+                b.is_legacy = verification.is_legacy;
+                b
+            })
+            .collect();
         
-        // Read network status
-        if let Ok(mut network_file) = archive.by_name("network_status.json") {
-            let mut network_content = String::new();
-            std::io::Read::read_to_string(&mut network_file, &mut network_content)
-                .map_err(|e| format!("Failed to read network status: {}", e))?;
-            backup_data.network_status = Some(serde_json::from_str(&network_content)
-                .map_err(|e| format!("Failed to parse network status: {}", e))?);
+        for path in self.archive_paths()? {
+            let path_str = path.to_string_lossy().to_string();
+            if !backups.iter().any(|b| b.file_path == path_str) {
+                let mut info = Self::info_from_archive(&path)?;
+                info.is_verified = false;
+                backups.push(info);
+            }
         }
         
-        Ok(backup_data)
+        *self.backups.lock_recover() = backups;
+        self.save_backups_index()
     }
     
-    fn scan_existing_backups(&self) -> Result<(), String> {
+    /// Rebuild the backups index from the archives in the backup directory
+    pub fn rebuild_index(&self) -> Result<Vec<BackupInfo>, String> {
         let mut backups = Vec::new();
+        for path in self.archive_paths()? {
+            backups.push(Self::info_from_archive(&path)?);
+        }
+        backups.sort_by_key(|b| b.created_at);
+        
+        *self.backups.lock_recover() = backups.clone();
+        self.save_backups_index()?;
         
+        Ok(backups)
+    }
+    
+    fn archive_paths(&self) -> Result<Vec<PathBuf>, String> {
+        let mut paths = Vec::new();
         for entry in fs::read_dir(&self.backup_dir)
             .map_err(|e| format!("Failed to read backup directory: {}", e))? {
             let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
             let path = entry.path();
-            
             if path.extension().and_then(|s| s.to_str()) == Some("zip") {
-                if let Ok(metadata) = fs::metadata(&path) {
-                    let filename = path.file_stem()
-                        .and_then(|s| s.to_str())
-                        .ok_or("Invalid filename")?;
-                    
-                    let backup_info = BackupInfo {
-                        id: filename.to_string(),
-                        name: format!("Backup {}", filename),
-                        created_at: metadata.modified()
-                            .map_err(|e| format!("Failed to get file modification time: {}", e))?
-                            .duration_since(UNIX_EPOCH)
-                            .map_err(|e| format!("Failed to get timestamp: {}", e))?
-                            .as_secs(),
-                        size_bytes: metadata.len(),
-                        backup_type: BackupType::Full,
-                        description: "Imported backup".to_string(),
-                        file_path: path.to_string_lossy().to_string(),
-                    };
-                    
-                    backups.push(backup_info);
-                }
+                paths.push(path);
             }
         }
+        Ok(paths)
+    }
+    
+    /// Describe an archive from its embedded metadata, falling back to file attributes
+    fn info_from_archive(path: &Path) -> Result<BackupInfo, String> {
+        let file_metadata = fs::metadata(path)
+            .map_err(|e| format!("Failed to get file metadata: {}", e))?;
+        let filename = path.file_stem()
+            .and_then(|s| s.to_str())
+            .ok_or("Invalid filename")?;
+        let modified = file_metadata.modified()
+            .map_err(|e| format!("Failed to get file modification time: {}", e))?
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| format!("Failed to get timestamp: {}", e))?
+            .as_secs();
         
-        *self.backups.lock()
-            .map_err(|e| format!("Failed to lock backups: {}", e))? = backups;
+        let (verification, members) = Self::verify_archive(path);
+        let metadata = members.get("metadata.json")
+            .and_then(|bytes| serde_json::from_slice::<BackupMetadata>(bytes).ok());
         
-        Ok(())
+        Ok(BackupInfo {
+            id: filename.to_string(),
+            name: metadata.as_ref()
+                .and_then(|m| m.name.clone())
+                .unwrap_or_else(|| format!("Backup {}", filename)),
+            created_at: metadata.as_ref()
+                .map(|m| m.created_at)
+                .filter(|t| *t > 0)
+                .unwrap_or(modified),
+            size_bytes: file_metadata.len(),
+            backup_type: metadata.as_ref()
+                .map(|m| m.backup_type.clone())
+                .unwrap_or(BackupType::Full),
+            description: metadata.as_ref()
+                .and_then(|m| m.description.clone())
+                .unwrap_or_else(|| "Imported backup".to_string()),
+            file_path: path.to_string_lossy().to_string(),
+            is_verified: verification.is_valid,
+            is_legacy: verification.is_legacy,
+        })
     }
     
     fn save_backups_index(&self) -> Result<(), String> {
-        let backups = self.backups.lock()
-            .map_err(|e| format!("Failed to lock backups: {}", e))?;
+        let backups = self.backups.lock_recover();
         
-        let index_path = self.backup_dir.join("backups_index.json");
+        let index_path = self.backup_dir.join(INDEX_NAME);
         let content = serde_json::to_string_pretty(&*backups)
             .map_err(|e| format!("Failed to serialize backups index: {}", e))?;
         
-        fs::write(index_path, content)
+        atomic_write(&index_path, content.as_bytes())
             .map_err(|e| format!("Failed to write backups index: {}", e))?;
         
         Ok(())
     }
 }
 
+/// The members of a backup archive, ending with the checksum manifest
+fn archive_members(data: &BackupData) -> Result<Vec<(&'static str, Vec<u8>)>, String> {
+    let mut members: Vec<(&'static str, Vec<u8>)> = Vec::new();
+    
+    // Wallet data
+    if let Some(ref wallet_info) = data.wallet_info {
+        let wallet_json = serde_json::to_string_pretty(wallet_info)
+            .map_err(|e| format!("Failed to serialize wallet: {}", e))?;
+        members.push(("wallet.json", wallet_json.into_bytes()));
+    }
+    
+    // Transactions
+    if let Some(ref transactions) = data.transactions {
+        let transactions_json = serde_json::to_string_pretty(transactions)
+            .map_err(|e| format!("Failed to serialize transactions: {}", e))?;
+        members.push(("transactions.json", transactions_json.into_bytes()));
+    }
+    
+    // Settings
+    if let Some(ref settings) = data.settings {
+        let settings_json = serde_json::to_string_pretty(settings)
+            .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+        members.push(("settings.json", settings_json.into_bytes()));
+    }
+    
+    // Network status
+    if let Some(ref network_status) = data.network_status {
+        let network_json = serde_json::to_string_pretty(network_status)
+            .map_err(|e| format!("Failed to serialize network status: {}", e))?;
+        members.push(("network_status.json", network_json.into_bytes()));
+    }
+    
+    // Raw wallet file
+    if let Some(ref wallet_file) = data.wallet_file {
+        members.push(("wallet.bin", wallet_file.clone()));
+    }
+    
+    // Metadata
+    let metadata_json = serde_json::to_string_pretty(&data.metadata)
+        .map_err(|e| format!("Failed to serialize metadata: {}", e))?;
+    members.push(("metadata.json", metadata_json.into_bytes()));
+    
+    // Manifest with a hash of every member
+    let manifest = BackupManifest {
+        backup_format_version: BACKUP_FORMAT_VERSION,
+        members: members.iter()
+            .map(|(name, content)| ManifestEntry {
+                name: name.to_string(),
+                blake3: blake3::hash(content).to_hex().to_string(),
+                size: content.len() as u64,
+            })
+            .collect(),
+    };
+    let manifest_json = serde_json::to_string_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+    members.push((MANIFEST_NAME, manifest_json.into_bytes()));
+    
+    Ok(members)
+}
+
+fn generate_backup_id(timestamp: u64) -> String {
+    format!("backup_{}_{}", timestamp, &uuid::Uuid::new_v4().to_string()[..8])
+}
+
+/// Convert a backed-up transaction into an `AdvancedTransactionInfo`.
+///
+/// Older backups store the basic transaction JSON returned by `get_transactions`,
+/// so missing advanced fields fall back to empty values.
+fn transaction_from_value(value: &serde_json::Value) -> Result<AdvancedTransactionInfo, String> {
+    if let Ok(transaction) = serde_json::from_value::<AdvancedTransactionInfo>(value.clone()) {
+        return Ok(transaction);
+    }
+    
+    let hash = value.get("hash")
+        .and_then(|v| v.as_str())
+        .ok_or("Transaction is missing its hash")?
+        .to_string();
+    let strings = |key: &str| -> Vec<String> {
+        value.get(key)
+            .and_then(|v| v.as_array())
+            .map(|a| a.iter().filter_map(|s| s.as_str().map(String::from)).collect())
+            .unwrap_or_default()
+    };
+    
+    Ok(AdvancedTransactionInfo {
+        id: value.get("id").and_then(|v| v.as_str()).unwrap_or(&hash).to_string(),
+        amount: value.get("amount").and_then(|v| v.as_i64()).unwrap_or(0),
+        fee: value.get("fee").and_then(|v| v.as_u64()).unwrap_or(0),
+        height: value.get("height").and_then(|v| v.as_u64()).unwrap_or(0),
+        timestamp: value.get("timestamp").and_then(|v| v.as_u64()).unwrap_or(0),
+        confirmations: value.get("confirmations").and_then(|v| v.as_u64()).unwrap_or(0) as u32,
+        is_confirmed: value.get("is_confirmed").and_then(|v| v.as_bool()).unwrap_or(false),
+        is_pending: value.get("is_pending").and_then(|v| v.as_bool()).unwrap_or(false),
+        payment_id: value.get("payment_id").and_then(|v| v.as_str()).map(String::from),
+        destination_addresses: strings("destination_addresses"),
+        source_addresses: strings("source_addresses"),
+        unlock_time: value.get("unlock_time").and_then(|v| v.as_u64()),
+        extra: value.get("extra").and_then(|v| v.as_str()).map(String::from),
+        mixin: 0,
+        ring_size: 0,
+        key_images: Vec::new(),
+        outputs: Vec::new(),
+        inputs: Vec::new(),
+        block_hash: None,
+        block_timestamp: None,
+        mempool_timestamp: None,
+        relayed_by: None,
+        double_spend_seen: false,
+        rct_type: None,
+        version: 1,
+        metadata: None,
+        resolved_addresses: Vec::new(),
+        hash,
+    })
+}
+
 // Tauri commands are defined in lib.rs
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::testing::TempDir;
+    
+    struct TestEnv {
+        root: PathBuf,
+        _dir: TempDir,
+    }
+    
+    impl TestEnv {
+        fn new() -> Self {
+            let dir = TempDir::new("fuego_backup");
+            Self { root: dir.to_path_buf(), _dir: dir }
+        }
+    }
+    
+    fn sample_data() -> BackupData {
+        let mut settings = serde_json::to_value(crate::settings::AppSettings::default()).unwrap();
+        settings["ui"]["theme"] = serde_json::json!("light");
+        BackupData {
+            wallet_info: Some(serde_json::json!({ "address": "fire_test" })),
+            transactions: Some(vec![
+                serde_json::json!({ "id": "1", "hash": "aa", "amount": 100, "fee": 1, "timestamp": 10 }),
+                serde_json::json!({ "id": "2", "hash": "bb", "amount": -50, "fee": 1, "timestamp": 20 }),
+            ]),
+            settings: Some(settings),
+            network_status: None,
+            wallet_file: Some(b"wallet-bytes".to_vec()),
+            metadata: BackupMetadata {
+                version: "1.0.0".to_string(),
+                created_at: 0,
+                backup_type: BackupType::Full,
+                fuego_version: "1.0.0".to_string(),
+                platform: std::env::consts::OS.to_string(),
+                name: None,
+                description: None,
+                password_hash: None,
+            },
+        }
+    }
+    
+    #[test]
+    fn test_restore_into_clean_environment() {
+        let source = TestEnv::new();
+        let target = TestEnv::new();
+        
+        let backups = BackupManager::with_dir(source.root.join("backups")).unwrap();
+        let info = backups.create_backup("b".into(), "d".into(), BackupType::Full, sample_data()).unwrap().info;
+        
+        let settings = SettingsManager::with_config_path(target.root.join("settings.json")).unwrap();
+        let wallet_manager = AdvancedWalletManager::new();
+        let wallet_path = target.root.join("wallet").join("fuego.wallet");
+        
+        let data = backups.restore_backup(info.id).unwrap();
+        let summary = backups.apply_restore(&data, &settings, &wallet_manager, &wallet_path, |_| Ok(()));
+        
+        assert!(summary.errors.is_empty(), "{:?}", summary.errors);
+        assert!(summary.settings_applied);
+        assert!(summary.wallet_restored);
+        assert_eq!(summary.transactions_loaded, 2);
+        assert_eq!(settings.get_settings().unwrap().ui.theme, "light");
+        assert_eq!(fs::read(&wallet_path).unwrap(), b"wallet-bytes");
+        assert_eq!(wallet_manager.get_advanced_transactions().len(), 2);
+    }
+    
+    #[test]
+    fn test_settings_failure_does_not_abort_other_sections() {
+        let env = TestEnv::new();
+        let backups = BackupManager::with_dir(env.root.join("backups")).unwrap();
+        let settings = SettingsManager::with_config_path(env.root.join("settings.json")).unwrap();
+        let wallet_manager = AdvancedWalletManager::new();
+        let wallet_path = env.root.join("fuego.wallet");
+        
+        let mut data = sample_data();
+        data.settings = Some(serde_json::json!({ "network": { "node_port": 0 } }));
+        
+        let summary = backups.apply_restore(&data, &settings, &wallet_manager, &wallet_path, |_| Ok(()));
+        
+        assert!(!summary.settings_applied);
+        assert!(summary.wallet_restored);
+        assert_eq!(summary.transactions_loaded, 2);
+        assert_eq!(summary.errors.len(), 1);
+        assert!(summary.errors[0].starts_with("settings:"));
+        assert_eq!(settings.get_settings().unwrap().network.node_port, 18180);
+    }
+    
+    #[test]
+    fn test_failed_reopen_keeps_previous_wallet() {
+        let env = TestEnv::new();
+        let backups = BackupManager::with_dir(env.root.join("backups")).unwrap();
+        let settings = SettingsManager::with_config_path(env.root.join("settings.json")).unwrap();
+        let wallet_manager = AdvancedWalletManager::new();
+        let wallet_path = env.root.join("fuego.wallet");
+        fs::write(&wallet_path, b"previous").unwrap();
+        
+        let summary = backups.apply_restore(&sample_data(), &settings, &wallet_manager, &wallet_path, |_| {
+            Err("bad password".to_string())
+        });
+        
+        assert!(!summary.wallet_restored);
+        assert!(summary.settings_applied);
+        assert_eq!(fs::read(&wallet_path).unwrap(), b"previous");
+    }
+    
+    #[test]
+    fn test_truncated_archive_fails_verification() {
+        let env = TestEnv::new();
+        let backups = BackupManager::with_dir(env.root.clone()).unwrap();
+        let info = backups.create_backup("b".into(), "d".into(), BackupType::Full, sample_data()).unwrap().info;
+        assert!(backups.verify_backup(info.id.clone()).unwrap().is_valid);
+        
+        let bytes = fs::read(&info.file_path).unwrap();
+        fs::write(&info.file_path, &bytes[..bytes.len() / 2]).unwrap();
+        
+        let verification = backups.verify_backup(info.id.clone()).unwrap();
+        assert!(!verification.is_valid);
+        assert!(verification.error.is_some());
+        assert!(backups.restore_backup(info.id).is_err());
+        
+        let rescanned = BackupManager::with_dir(env.root.clone()).unwrap();
+        assert!(!rescanned.list_backups().unwrap()[0].is_verified);
+    }
+    
+    /// Write `sample_data` in the layout used before archives carried a manifest
+    fn write_legacy_archive(path: &Path) {
+        let data = sample_data();
+        let mut zip = ZipWriter::new(fs::File::create(path).unwrap());
+        let members = [
+            ("wallet.json", serde_json::to_vec_pretty(&data.wallet_info).unwrap()),
+            ("transactions.json", serde_json::to_vec_pretty(&data.transactions).unwrap()),
+            ("settings.json", serde_json::to_vec_pretty(&data.settings).unwrap()),
+            ("metadata.json", serde_json::to_vec_pretty(&data.metadata).unwrap()),
+        ];
+        for (name, content) in members {
+            zip.start_file(name, FileOptions::default()).unwrap();
+            zip.write_all(&content).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+    
+    #[test]
+    fn test_pre_manifest_backup_is_restored() {
+        let source = TestEnv::new();
+        let target = TestEnv::new();
+        write_legacy_archive(&source.root.join("backup_1700000000_legacy.zip"));
+        
+        let backups = BackupManager::with_dir(source.root.clone()).unwrap();
+        let listed = backups.list_backups().unwrap();
+        assert_eq!(listed.len(), 1);
+        assert!(!listed[0].is_verified);
+        assert!(listed[0].is_legacy);
+        
+        let verification = backups.verify_backup(listed[0].id.clone()).unwrap();
+        assert!(!verification.is_valid);
+        assert!(verification.is_legacy);
+        assert!(verification.is_restorable(), "{:?}", verification);
+        assert_eq!(verification.backup_format_version, Some(LEGACY_BACKUP_FORMAT_VERSION));
+        
+        let settings = SettingsManager::with_config_path(target.root.join("settings.json")).unwrap();
+        let wallet_manager = AdvancedWalletManager::new();
+        let wallet_path = target.root.join("fuego.wallet");
+        let restored = backups.restore_backup(listed[0].id.clone()).unwrap();
+        let summary = backups.apply_restore(&restored, &settings, &wallet_manager, &wallet_path, |_| Ok(()));
+        
+        assert!(summary.errors.is_empty(), "{:?}", summary.errors);
+        assert!(summary.settings_applied);
+        assert!(!summary.wallet_restored);
+        assert_eq!(summary.transactions_loaded, 2);
+        assert_eq!(settings.get_settings().unwrap().ui.theme, "light");
+    }
+    
+    #[test]
+    fn test_archive_without_manifest_or_metadata_is_unverified() {
+        let env = TestEnv::new();
+        let path = env.root.join("foreign.zip");
+        let mut zip = ZipWriter::new(fs::File::create(&path).unwrap());
+        zip.start_file("settings.json", FileOptions::default()).unwrap();
+        zip.write_all(b"{}").unwrap();
+        zip.finish().unwrap();
+        
+        let backups = BackupManager::with_dir(env.root.clone()).unwrap();
+        let listed = backups.list_backups().unwrap();
+        assert_eq!(listed.len(), 1);
+        assert!(!listed[0].is_verified);
+        
+        let verification = backups.verify_backup("foreign".to_string()).unwrap();
+        assert!(!verification.is_valid);
+        assert_eq!(verification.missing_members, vec!["metadata.json".to_string()]);
+        assert!(backups.restore_backup("foreign".to_string()).is_err());
+    }
+    
+    #[test]
+    fn test_flipped_byte_reports_corrupt_member() {
+        let env = TestEnv::new();
+        let backups = BackupManager::with_dir(env.root.clone()).unwrap();
+        let info = backups.create_backup("b".into(), "d".into(), BackupType::Full, sample_data()).unwrap().info;
+        
+        let offset = {
+            let mut archive = zip::ZipArchive::new(fs::File::open(&info.file_path).unwrap()).unwrap();
+            archive.by_name("settings.json").unwrap().data_start() as usize
+        };
+        let mut bytes = fs::read(&info.file_path).unwrap();
+        bytes[offset + 2] ^= 0xFF;
+        fs::write(&info.file_path, bytes).unwrap();
+        
+        let verification = backups.verify_backup(info.id.clone()).unwrap();
+        assert!(!verification.is_valid);
+        assert_eq!(verification.corrupt_members, vec!["settings.json".to_string()]);
+        assert!(verification.missing_members.is_empty());
+        assert!(!backups.list_backups().unwrap()[0].is_verified);
+    }
+    
+    #[test]
+    fn test_names_survive_restart_without_index() {
+        let env = TestEnv::new();
+        {
+            let backups = BackupManager::with_dir(env.root.clone()).unwrap();
+            backups.create_backup("Daily".into(), "before upgrade".into(), BackupType::SettingsOnly, sample_data()).unwrap();
+            backups.create_backup("Weekly".into(), "full copy".into(), BackupType::Full, sample_data()).unwrap();
+        }
+        fs::remove_file(env.root.join(INDEX_NAME)).unwrap();
+        
+        let restarted = BackupManager::with_dir(env.root.clone()).unwrap();
+        let mut listed = restarted.list_backups().unwrap();
+        listed.sort_by(|a, b| a.name.cmp(&b.name));
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0].name, "Daily");
+        assert_eq!(listed[0].description, "before upgrade");
+        assert!(matches!(listed[0].backup_type, BackupType::SettingsOnly));
+        assert_eq!(listed[1].name, "Weekly");
+        assert!(listed.iter().all(|b| b.is_verified));
+        assert!(env.root.join(INDEX_NAME).exists());
+    }
+    
+    #[test]
+    fn test_reconcile_drops_missing_and_adds_untracked() {
+        let env = TestEnv::new();
+        let outside = TestEnv::new();
+        let (kept, removed) = {
+            let backups = BackupManager::with_dir(env.root.clone()).unwrap();
+            let kept = backups.create_backup("kept".into(), "d".into(), BackupType::Full, sample_data()).unwrap().info;
+            let removed = backups.create_backup("removed".into(), "d".into(), BackupType::Full, sample_data()).unwrap().info;
+            (kept, removed)
+        };
+        fs::remove_file(&removed.file_path).unwrap();
+        
+        // Archive copied in by hand, unknown to the index
+        let other = BackupManager::with_dir(outside.root.clone()).unwrap();
+        let foreign = other.create_backup("foreign".into(), "d".into(), BackupType::Full, sample_data()).unwrap().info;
+        fs::copy(&foreign.file_path, env.root.join("manual.zip")).unwrap();
+        
+        let restarted = BackupManager::with_dir(env.root.clone()).unwrap();
+        let listed = restarted.list_backups().unwrap();
+        assert_eq!(listed.len(), 2);
+        assert!(listed.iter().any(|b| b.id == kept.id && b.is_verified));
+        assert!(!listed.iter().any(|b| b.id == removed.id));
+        let manual = listed.iter().find(|b| b.id == "manual").unwrap();
+        assert_eq!(manual.name, "foreign");
+        assert!(!manual.is_verified);
+    }
+    
+    #[test]
+    fn test_corrupt_index_is_rebuilt() {
+        let env = TestEnv::new();
+        {
+            let backups = BackupManager::with_dir(env.root.clone()).unwrap();
+            backups.create_backup("Daily".into(), "d".into(), BackupType::Full, sample_data()).unwrap();
+        }
+        fs::write(env.root.join(INDEX_NAME), b"{ not json").unwrap();
+        
+        let restarted = BackupManager::with_dir(env.root.clone()).unwrap();
+        let listed = restarted.list_backups().unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].name, "Daily");
+        
+        let rebuilt = restarted.rebuild_index().unwrap();
+        assert_eq!(rebuilt.len(), 1);
+    }
+    
+    #[test]
+    fn test_progress_reaches_total() {
+        let env = TestEnv::new();
+        let backups = BackupManager::with_dir(env.root.clone()).unwrap();
+        let mut events = Vec::new();
+        
+        backups.create_backup_with_progress("b".into(), "d".into(), BackupType::Full, sample_data(), |p| {
+            events.push(p.clone());
+        }).unwrap();
+        
+        let last = events.last().unwrap();
+        assert_eq!(last.bytes_written, last.total_bytes);
+        assert_eq!(last.current_member, MANIFEST_NAME);
+        assert!(events.windows(2).all(|w| w[0].bytes_written <= w[1].bytes_written));
+    }
+    
+    #[test]
+    fn test_cancel_mid_archive_removes_partial_file() {
+        let env = TestEnv::new();
+        let backups = BackupManager::with_dir(env.root.clone()).unwrap();
+        let mut members_written = 0;
+        
+        let result = backups.create_backup_with_progress("b".into(), "d".into(), BackupType::Full, sample_data(), |_| {
+            members_written += 1;
+            if members_written == 2 {
+                backups.cancel();
+            }
+        });
+        
+        assert_eq!(result.unwrap_err(), BackupError::Cancelled);
+        assert_eq!(members_written, 2);
+        assert!(backups.list_backups().unwrap().is_empty());
+        assert_eq!(backups.archive_paths().unwrap().len(), 0);
+        
+        // A later backup is not affected by the earlier cancellation
+        assert!(backups.create_backup("b".into(), "d".into(), BackupType::Full, sample_data()).is_ok());
+    }
+    
+    #[test]
+    fn test_cancel_before_start_stops_the_backup() {
+        let env = TestEnv::new();
+        let backups = BackupManager::with_dir(env.root.clone()).unwrap();
+        
+        // Cancelled while the data was still being collected
+        backups.begin();
+        backups.cancel();
+        let result = backups.create_backup_with_progress("b".into(), "d".into(), BackupType::Full, sample_data(), |_| {
+            panic!("nothing should be written");
+        });
+        assert_eq!(result.unwrap_err(), BackupError::Cancelled);
+        assert!(backups.list_backups().unwrap().is_empty());
+        
+        // A stale request from before the operation started is forgotten
+        backups.cancel();
+        backups.begin();
+        assert!(backups.create_backup("b".into(), "d".into(), BackupType::Full, sample_data()).is_ok());
+    }
+    
+    #[test]
+    fn test_insufficient_space_writes_nothing() {
+        let env = TestEnv::new();
+        let backups = BackupManager::with_dir(env.root.clone()).unwrap().with_space_probe(|_| Some(4096));
+        
+        let result = backups.create_backup("b".into(), "d".into(), BackupType::Full, sample_data());
+        assert!(matches!(result, Err(BackupError::InsufficientDiskSpace { available: 4096, .. })));
+        assert!(backups.list_backups().unwrap().is_empty());
+        let entries = || fs::read_dir(&env.root).unwrap().map(|e| e.unwrap().file_name()).collect::<Vec<_>>();
+        assert!(entries().iter().all(|name| name == INDEX_NAME));
+        
+        let backups = backups.with_space_probe(|_| Some(64 * 1024 * 1024));
+        let outcome = backups.create_backup("b".into(), "d".into(), BackupType::Full, sample_data()).unwrap();
+        assert_eq!(outcome.bytes_written, fs::metadata(&outcome.info.file_path).unwrap().len());
+        assert_eq!(outcome.info.size_bytes, outcome.bytes_written);
+        assert_eq!(outcome.free_space_remaining, Some(64 * 1024 * 1024));
+        // Only the archive and the index; the temporary file was renamed
+        assert_eq!(entries().len(), 2);
+    }
+    
+    #[test]
+    fn test_import_valid_archive() {
+        let source = TestEnv::new();
+        let target = TestEnv::new();
+        let exporter = BackupManager::with_dir(source.root.clone()).unwrap();
+        let info = exporter.create_backup("Weekly".into(), "usb copy".into(), BackupType::Full, sample_data()).unwrap().info;
+        
+        let importer = BackupManager::with_dir(target.root.clone()).unwrap();
+        let imported = importer.import_backup(Path::new(&info.file_path), None).unwrap();
+        assert_ne!(imported.id, info.id);
+        assert_eq!(imported.name, "Weekly");
+        assert_eq!(imported.description, "usb copy");
+        assert!(matches!(imported.backup_type, BackupType::Full));
+        assert!(imported.is_verified);
+        assert!(importer.restore_backup(imported.id.clone()).is_ok());
+        
+        // Importing the same content again returns the existing backup
+        let again = importer.import_backup(Path::new(&info.file_path), None).unwrap();
+        assert_eq!(again.id, imported.id);
+        assert_eq!(importer.list_backups().unwrap().len(), 1);
+    }
+    
+    #[test]
+    fn test_import_legacy_archive_is_unverified() {
+        let source = TestEnv::new();
+        let target = TestEnv::new();
+        let path = source.root.join("old.zip");
+        write_legacy_archive(&path);
+        
+        let importer = BackupManager::with_dir(target.root.clone()).unwrap();
+        let imported = importer.import_backup(&path, None).unwrap();
+        assert!(!imported.is_verified);
+        assert!(imported.is_legacy);
+        assert!(!importer.list_backups().unwrap()[0].is_verified);
+        assert!(importer.restore_backup(imported.id).is_ok());
+    }
+    
+    #[test]
+    fn test_import_password_protected_archive() {
+        let source = TestEnv::new();
+        let target = TestEnv::new();
+        let exporter = BackupManager::with_dir(source.root.clone()).unwrap();
+        let mut data = sample_data();
+        data.metadata.set_password("correct horse").unwrap();
+        let info = exporter.create_backup("b".into(), "d".into(), BackupType::Full, data).unwrap().info;
+        
+        let importer = BackupManager::with_dir(target.root.clone()).unwrap();
+        let path = Path::new(&info.file_path);
+        assert!(importer.import_backup(path, None).is_err());
+        assert!(importer.import_backup(path, Some("wrong")).is_err());
+        assert!(importer.import_backup(path, Some("correct horse")).is_ok());
+    }
+    
+    #[test]
+    fn test_import_rejects_corrupt_file() {
+        let env = TestEnv::new();
+        let outside = TestEnv::new();
+        let path = outside.root.join("broken.zip");
+        fs::write(&path, b"PK\x03\x04 definitely not a zip").unwrap();
+        
+        let backups = BackupManager::with_dir(env.root.clone()).unwrap();
+        assert!(backups.import_backup(&path, None).is_err());
+        assert!(backups.list_backups().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,313 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! CryptoNote address decoding
+//!
+//! A Fuego address is the CryptoNote base58 encoding (8-byte blocks, 11
+//! characters each) of a varint network prefix, the public spend and view
+//! keys and a 4-byte Keccak checksum. An integrated address carries a payment
+//! ID, as its 64 hex characters, between the prefix and the keys; the kind of
+//! address follows from the length of the decoded data.
+
+use crate::utils::error::{WalletError, WalletResult};
+
+/// Base58 prefix of Fuego addresses, which makes them start with `fire`
+pub const FUEGO_ADDRESS_PREFIX: u64 = 1753191;
+
+pub(crate) const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const FULL_BLOCK_SIZE: usize = 8;
+const FULL_ENCODED_BLOCK_SIZE: usize = 11;
+/// Encoded length of a block of 0 to 8 bytes
+const ENCODED_BLOCK_SIZES: [usize; 9] = [0, 2, 3, 5, 6, 7, 9, 10, 11];
+const CHECKSUM_SIZE: usize = 4;
+const KEYS_SIZE: usize = 64;
+const PAYMENT_ID_HEX_SIZE: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AddressKind {
+    Standard,
+    Integrated,
+}
+
+/// An address taken apart
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedAddress {
+    pub kind: AddressKind,
+    pub spend_public_key: [u8; 32],
+    pub view_public_key: [u8; 32],
+    /// Embedded payment ID of an integrated address, as lowercase hex
+    pub payment_id: Option<String>,
+}
+
+impl DecodedAddress {
+    /// The standard address receiving the funds, without a payment ID
+    pub fn standard_address(&self) -> String {
+        let mut payload = Vec::with_capacity(KEYS_SIZE);
+        payload.extend_from_slice(&self.spend_public_key);
+        payload.extend_from_slice(&self.view_public_key);
+        encode_address(FUEGO_ADDRESS_PREFIX, &payload)
+    }
+}
+
+/// Decode a Fuego address, checking prefix and checksum
+pub fn decode_address(address: &str) -> WalletResult<DecodedAddress> {
+    let invalid = || WalletError::InvalidAddress(address.to_string());
+    let data = base58_decode(address).ok_or_else(invalid)?;
+    if data.len() < CHECKSUM_SIZE {
+        return Err(invalid());
+    }
+    let (body, checksum) = data.split_at(data.len() - CHECKSUM_SIZE);
+    if keccak256(body)[..CHECKSUM_SIZE] != *checksum {
+        return Err(invalid());
+    }
+
+    let (prefix, prefix_len) = read_varint(body).ok_or_else(invalid)?;
+    if prefix != FUEGO_ADDRESS_PREFIX {
+        return Err(invalid());
+    }
+    let payload = &body[prefix_len..];
+    let (payment_id, keys) = match payload.len() {
+        KEYS_SIZE => (None, payload),
+        len if len == PAYMENT_ID_HEX_SIZE + KEYS_SIZE => {
+            let (payment_id, keys) = payload.split_at(PAYMENT_ID_HEX_SIZE);
+            let payment_id = std::str::from_utf8(payment_id).ok()
+                .filter(|id| id.chars().all(|c| c.is_ascii_hexdigit()))
+                .ok_or_else(invalid)?;
+            (Some(payment_id.to_ascii_lowercase()), keys)
+        }
+        _ => return Err(invalid()),
+    };
+
+    Ok(DecodedAddress {
+        kind: if payment_id.is_some() { AddressKind::Integrated } else { AddressKind::Standard },
+        spend_public_key: keys[..32].try_into().unwrap(),
+        view_public_key: keys[32..].try_into().unwrap(),
+        payment_id,
+    })
+}
+
+/// Whether `address` decodes as an integrated address
+pub fn is_integrated_address(address: &str) -> bool {
+    decode_address(address).is_ok_and(|decoded| decoded.kind == AddressKind::Integrated)
+}
+
+/// Split an integrated address into the standard address and its payment ID
+pub fn split_integrated_address(address: &str) -> WalletResult<(String, String)> {
+    let decoded = decode_address(address)?;
+    match decoded.payment_id.clone() {
+        Some(payment_id) => Ok((decoded.standard_address(), payment_id)),
+        None => Err(WalletError::InvalidAddress(format!("{} is not an integrated address", address))),
+    }
+}
+
+/// Build the integrated address for `address` and a 64-character hex `payment_id`
+pub fn make_integrated_address(address: &str, payment_id: &str) -> WalletResult<String> {
+    let decoded = decode_address(address)?;
+    if decoded.kind != AddressKind::Standard {
+        return Err(WalletError::InvalidAddress(format!("{} is already an integrated address", address)));
+    }
+    if payment_id.len() != PAYMENT_ID_HEX_SIZE || !payment_id.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(WalletError::TransactionFailed(format!("Invalid payment ID: {}", payment_id)));
+    }
+    let mut payload = payment_id.to_ascii_lowercase().into_bytes();
+    payload.extend_from_slice(&decoded.spend_public_key);
+    payload.extend_from_slice(&decoded.view_public_key);
+    Ok(encode_address(FUEGO_ADDRESS_PREFIX, &payload))
+}
+
+/// Recipient and payment ID of a send. An integrated address gives its own
+/// payment ID; passing a different one as well is refused.
+pub fn resolve_payment_id(address: &str, payment_id: Option<&str>) -> WalletResult<(String, Option<String>)> {
+    let payment_id = payment_id.map(str::trim).filter(|id| !id.is_empty());
+    match decode_address(address) {
+        Ok(DecodedAddress { kind: AddressKind::Integrated, .. }) => {
+            let (standard, embedded) = split_integrated_address(address)?;
+            if let Some(given) = payment_id
+                && !given.eq_ignore_ascii_case(&embedded)
+            {
+                return Err(WalletError::TransactionFailed(format!(
+                    "Payment ID {} conflicts with the payment ID {} of the integrated address",
+                    given, embedded
+                )));
+            }
+            Ok((standard, Some(embedded)))
+        }
+        _ => Ok((address.to_string(), payment_id.map(str::to_string))),
+    }
+}
+
+/// CryptoNote base58 encoding of a varint `prefix`, `payload` and checksum
+pub fn encode_address(prefix: u64, payload: &[u8]) -> String {
+    let mut data = write_varint(prefix);
+    data.extend_from_slice(payload);
+    let checksum = keccak256(&data);
+    data.extend_from_slice(&checksum[..CHECKSUM_SIZE]);
+    base58_encode(&data)
+}
+
+fn base58_encode(data: &[u8]) -> String {
+    let mut encoded = String::new();
+    for block in data.chunks(FULL_BLOCK_SIZE) {
+        let mut num = block.iter().fold(0u64, |n, &b| n << 8 | b as u64);
+        let mut chars = vec![ALPHABET[0]; ENCODED_BLOCK_SIZES[block.len()]];
+        for c in chars.iter_mut().rev() {
+            *c = ALPHABET[(num % 58) as usize];
+            num /= 58;
+        }
+        encoded.extend(chars.into_iter().map(char::from));
+    }
+    encoded
+}
+
+fn base58_decode(encoded: &str) -> Option<Vec<u8>> {
+    let encoded = encoded.as_bytes();
+    let mut data = Vec::with_capacity(encoded.len() * FULL_BLOCK_SIZE / FULL_ENCODED_BLOCK_SIZE);
+    for block in encoded.chunks(FULL_ENCODED_BLOCK_SIZE) {
+        let size = ENCODED_BLOCK_SIZES.iter().position(|&s| s == block.len())?;
+        let mut num: u128 = 0;
+        for &c in block {
+            let digit = ALPHABET.iter().position(|&a| a == c)?;
+            num = num * 58 + digit as u128;
+        }
+        if size < FULL_BLOCK_SIZE && num >> (8 * size) != 0 || num > u64::MAX as u128 {
+            return None;
+        }
+        data.extend_from_slice(&(num as u64).to_be_bytes()[FULL_BLOCK_SIZE - size..]);
+    }
+    Some(data)
+}
+
+fn write_varint(mut value: u64) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    while value >= 0x80 {
+        bytes.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+    bytes
+}
+
+/// Value and length of the varint at the start of `bytes`
+fn read_varint(bytes: &[u8]) -> Option<(u64, usize)> {
+    let mut value = 0u64;
+    for (i, &byte) in bytes.iter().enumerate().take(10) {
+        value |= ((byte & 0x7f) as u64) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
+    }
+    None
+}
+
+/// Keccak-256 as used by CryptoNote (`cn_fast_hash`), with the original
+/// Keccak padding rather than SHA-3's
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    const RATE: usize = 136;
+    let mut state = [0u64; 25];
+    let mut padded = data.to_vec();
+    padded.push(0x01);
+    padded.resize(padded.len().div_ceil(RATE) * RATE, 0);
+    *padded.last_mut().unwrap() |= 0x80;
+
+    for block in padded.chunks(RATE) {
+        for (lane, bytes) in state.iter_mut().zip(block.chunks(8)) {
+            *lane ^= u64::from_le_bytes(bytes.try_into().unwrap());
+        }
+        keccak_f(&mut state);
+    }
+
+    let mut hash = [0u8; 32];
+    for (bytes, lane) in hash.chunks_mut(8).zip(state.iter()) {
+        bytes.copy_from_slice(&lane.to_le_bytes());
+    }
+    hash
+}
+
+fn keccak_f(state: &mut [u64; 25]) {
+    const ROUND_CONSTANTS: [u64; 24] = [
+        0x0000000000000001, 0x0000000000008082, 0x800000000000808a, 0x8000000080008000,
+        0x000000000000808b, 0x0000000080000001, 0x8000000080008081, 0x8000000000008009,
+        0x000000000000008a, 0x0000000000000088, 0x0000000080008009, 0x000000008000000a,
+        0x000000008000808b, 0x800000000000008b, 0x8000000000008089, 0x8000000000008003,
+        0x8000000000008002, 0x8000000000000080, 0x000000000000800a, 0x800000008000000a,
+        0x8000000080008081, 0x8000000000008080, 0x0000000080000001, 0x8000000080008008,
+    ];
+    const ROTATIONS: [u32; 24] = [1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44];
+    const PI: [usize; 24] = [10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1];
+
+    for round_constant in ROUND_CONSTANTS {
+        // Theta
+        let mut columns = [0u64; 5];
+        for (x, column) in columns.iter_mut().enumerate() {
+            *column = state[x] ^ state[x + 5] ^ state[x + 10] ^ state[x + 15] ^ state[x + 20];
+        }
+        for x in 0..5 {
+            let d = columns[(x + 4) % 5] ^ columns[(x + 1) % 5].rotate_left(1);
+            for y in 0..5 {
+                state[x + 5 * y] ^= d;
+            }
+        }
+        // Rho and pi
+        let mut carried = state[1];
+        for (&target, &rotation) in PI.iter().zip(ROTATIONS.iter()) {
+            let next = state[target];
+            state[target] = carried.rotate_left(rotation);
+            carried = next;
+        }
+        // Chi
+        for y in 0..5 {
+            let row: [u64; 5] = state[5 * y..5 * y + 5].try_into().unwrap();
+            for x in 0..5 {
+                state[x + 5 * y] = row[x] ^ (!row[(x + 1) % 5] & row[(x + 2) % 5]);
+            }
+        }
+        // Iota
+        state[0] ^= round_constant;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn standard_address() -> String {
+        let keys: Vec<u8> = (0..64).collect();
+        encode_address(FUEGO_ADDRESS_PREFIX, &keys)
+    }
+
+    #[test]
+    fn test_keccak_and_standard_address() {
+        assert_eq!(hex::encode(keccak256(b"")), "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470");
+        assert_eq!(hex::encode(keccak256(&[0xab; 200])).len(), 64);
+
+        let address = standard_address();
+        assert!(address.starts_with("fire"));
+        assert_eq!(address.len(), 98);
+        let decoded = decode_address(&address).unwrap();
+        assert_eq!((decoded.kind, decoded.payment_id.clone()), (AddressKind::Standard, None));
+        assert_eq!(decoded.standard_address(), address);
+
+        // A changed character breaks the checksum
+        let tampered = format!("{}{}", &address[..20], if &address[20..21] == "A" { "B" } else { "A" }) + &address[21..];
+        assert!(decode_address(&tampered).is_err());
+        assert!(split_integrated_address(&address).is_err());
+    }
+
+    #[test]
+    fn test_integrated_address_round_trip() {
+        let address = standard_address();
+        let payment_id = "AB".repeat(32);
+        let integrated = make_integrated_address(&address, &payment_id).unwrap();
+        assert!(integrated.starts_with("fire") && integrated.len() > 98);
+        assert!(is_integrated_address(&integrated));
+
+        let (standard, embedded) = split_integrated_address(&integrated).unwrap();
+        assert_eq!((standard.as_str(), embedded.as_str()), (address.as_str(), payment_id.to_lowercase().as_str()));
+
+        assert_eq!(resolve_payment_id(&integrated, None).unwrap(), (address.clone(), Some(embedded.clone())));
+        assert_eq!(resolve_payment_id(&integrated, Some(&payment_id)).unwrap().1, Some(embedded));
+        assert!(resolve_payment_id(&integrated, Some(&"cd".repeat(32))).is_err());
+        assert_eq!(resolve_payment_id(&address, Some(" ")).unwrap(), (address.clone(), None));
+    }
+}
@@ -0,0 +1,1015 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Wallet backends
+//!
+//! `RealCryptoNoteWallet` runs its operations on a `WalletBackend`: the native
+//! CryptoNote library in the app, the legacy `CryptoNoteFFI` bindings, or
+//! `MockWalletBackend`, which keeps a wallet in memory so the checks around
+//! sending and syncing can be exercised without the C++ side. Which one new
+//! wallets get is chosen by the `wallet.backend` setting.
+
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use crate::crypto::ffi::CryptoNoteFFI;
+use crate::crypto::real_cryptonote::{
+    estimated_transaction_size, AddressBookEntry, BlockInfo, DepositInfo, DepositLimits, EmissionInfo, FeeBreakdown, FfiWalletBackend, MiningInfo, NetworkInfo, PoolTransfer, SyncProgress,
+    TransactionInfo, UnspentOutput, WalletAddress, WalletInfo, WalletIntegrityReport, TX_PROOF_BODY_LEN, TX_PROOF_PREFIX,
+};
+use crate::utils::error::{RecoveryAction, WalletError, WalletResult};
+use crate::utils::locks::{MutexExt, RwLockExt};
+
+/// Operations a wallet implementation provides. Those with a default body are
+/// optional and report that the backend does not support them.
+pub trait WalletBackend {
+    /// Create a new wallet
+    fn create_wallet(
+        &mut self,
+        password: &str,
+        file_path: &str,
+        seed_phrase: Option<&str>,
+        restore_height: u64,
+    ) -> WalletResult<()>;
+
+    /// Open an existing wallet
+    fn open_wallet(&mut self, file_path: &str, password: &str) -> WalletResult<()>;
+
+    /// Close the wallet
+    fn close_wallet(&mut self);
+
+    /// Check if wallet is open
+    fn is_open(&self) -> bool;
+
+    /// Get wallet balance
+    fn get_balance(&self) -> WalletResult<u64>;
+
+    /// Get unlocked balance
+    fn get_unlocked_balance(&self) -> WalletResult<u64>;
+
+    /// Get wallet address
+    fn get_address(&self) -> WalletResult<String>;
+
+    /// Send a transaction
+    fn send_transaction(
+        &self,
+        address: &str,
+        amount: u64,
+        payment_id: Option<&str>,
+        mixin: u64,
+    ) -> WalletResult<String>;
+
+    /// Send to several recipients in a single transaction; the outputs are
+    /// validated and the balance checked by the caller
+    fn send_many(&self, outputs: &[(String, u64)], mixin: u64) -> WalletResult<String>;
+
+    /// Connect to a Fuego node, giving up after `timeout`
+    fn connect_to_node(&mut self, address: &str, port: u16, timeout: Duration) -> WalletResult<()>;
+
+    /// Get network status
+    fn get_network_status(&self) -> WalletResult<serde_json::Value>;
+
+    /// Get all term deposits from the wallet
+    fn get_deposits(&self) -> WalletResult<Vec<DepositInfo>>;
+
+    /// Get comprehensive wallet information
+    fn get_wallet_info(&self) -> WalletResult<WalletInfo>;
+
+    /// Get detailed network information
+    fn get_network_info(&self) -> WalletResult<NetworkInfo>;
+
+    /// Refresh wallet data from blockchain
+    fn refresh(&mut self) -> WalletResult<()>;
+
+    /// Start rescanning the blockchain from `start_height`
+    fn rescan_blockchain(&mut self, start_height: u64) -> WalletResult<()>;
+
+    /// Get transaction by hash
+    fn get_transaction_by_hash(&self, tx_hash: &str) -> WalletResult<TransactionInfo>;
+
+    /// Estimate the fee of sending `amount` to `address`, by component
+    fn estimate_transaction_fee(
+        &self,
+        address: &str,
+        amount: u64,
+        mixin: u64,
+    ) -> WalletResult<FeeBreakdown>;
+
+    /// Create new address with label
+    fn create_address(&self, _label: Option<&str>) -> WalletResult<String> {
+        Err(unsupported("create_address"))
+    }
+
+    /// Get all wallet addresses with the labels stored in the wallet
+    fn get_addresses(&self) -> WalletResult<Vec<WalletAddress>> {
+        Err(unsupported("get_addresses"))
+    }
+
+    /// Transfers of this wallet that are in the transaction pool, not in a block yet
+    fn get_pool_transfers(&self) -> WalletResult<Vec<PoolTransfer>> {
+        Err(unsupported("get_pool_transfers"))
+    }
+
+    /// Unspent outputs of the wallet
+    fn get_unspent_outputs(&self) -> WalletResult<Vec<UnspentOutput>> {
+        Err(unsupported("get_unspent_outputs"))
+    }
+
+    /// Check the wallet file at `file_path` without changing it
+    fn verify_wallet(&self, _file_path: &str, _password: &str) -> WalletResult<WalletIntegrityReport> {
+        Err(unsupported("verify_wallet"))
+    }
+
+    /// Block reward and coin emission at the chain tip. Emission is optional
+    /// daemon data, so lacking it is not an error.
+    fn get_emission(&self) -> WalletResult<EmissionInfo> {
+        Ok(EmissionInfo::unsupported(self.get_network_info()?.network_height))
+    }
+
+    /// Term deposit limits at the chain tip. Daemons that do not report them
+    /// give the built-in defaults.
+    fn get_deposit_limits(&self) -> WalletResult<DepositLimits> {
+        Ok(DepositLimits::defaults(self.get_network_info()?.network_height))
+    }
+
+    /// Send spending only the outputs with the given key images; the selection
+    /// is validated by the caller
+    fn send_from_outputs(
+        &self,
+        _address: &str,
+        _amount: u64,
+        _payment_id: Option<&str>,
+        _mixin: u64,
+        _key_images: &[String],
+    ) -> WalletResult<String> {
+        Err(unsupported("send_from_outputs"))
+    }
+
+    /// Send to several recipients in one transaction, spending only the
+    /// outputs with the given key images
+    fn send_many_from_outputs(&self, _outputs: &[(String, u64)], _mixin: u64, _key_images: &[String]) -> WalletResult<String> {
+        Err(unsupported("send_many_from_outputs"))
+    }
+
+    /// Write the open wallet to its file
+    fn save(&self) -> WalletResult<()> {
+        Err(unsupported("save"))
+    }
+
+    /// Relay a sent transaction that is not in a block yet to the peers again
+    fn rebroadcast_transaction(&self, _tx_hash: &str) -> WalletResult<()> {
+        Err(unsupported("rebroadcast_transaction"))
+    }
+
+    /// Proof that transaction `tx_hash` paid `address`, signing `message` along
+    fn get_tx_proof(&self, _tx_hash: &str, _address: &str, _message: Option<&str>) -> WalletResult<String> {
+        Err(unsupported("get_tx_proof"))
+    }
+
+    /// Whether `proof` shows that `tx_hash` paid `address`
+    fn check_tx_proof(&self, _tx_hash: &str, _address: &str, _message: Option<&str>, _proof: &str) -> WalletResult<bool> {
+        Err(unsupported("check_tx_proof"))
+    }
+
+    /// Set the label of a wallet address.
+    ///
+    /// Labels are local wallet metadata and are never part of the address or
+    /// of any transaction, so the primary address can be labeled as well.
+    fn set_address_label(&self, _address: &str, _label: &str) -> WalletResult<()> {
+        Err(unsupported("set_address_label"))
+    }
+
+    /// Get block information by height
+    fn get_block_info(&self, _height: u64) -> WalletResult<BlockInfo> {
+        Err(unsupported("get_block_info"))
+    }
+
+    /// Start mining
+    fn start_mining(&mut self, _threads: u32, _background: bool) -> WalletResult<()> {
+        Err(unsupported("start_mining"))
+    }
+
+    /// Stop mining
+    fn stop_mining(&mut self) -> WalletResult<()> {
+        Err(unsupported("stop_mining"))
+    }
+
+    /// Get mining information
+    fn get_mining_info(&self) -> WalletResult<MiningInfo> {
+        Err(unsupported("get_mining_info"))
+    }
+
+    /// Disconnect from network
+    fn disconnect(&mut self) -> WalletResult<()>;
+
+    /// Create a new term deposit
+    fn create_deposit(&self, amount: u64, term: u32) -> WalletResult<String>;
+
+    /// Withdraw a term deposit
+    fn withdraw_deposit(&self, deposit_id: &str) -> WalletResult<String>;
+
+    /// Get transaction history from blockchain
+    fn get_transaction_history(&self, limit: u64, offset: u64) -> WalletResult<Vec<TransactionInfo>>;
+
+    /// Get sync progress information
+    fn get_sync_progress(&self) -> WalletResult<SyncProgress>;
+
+    /// Get sync status as JSON string
+    fn get_sync_status_json(&self) -> WalletResult<String> {
+        Err(unsupported("get_sync_status_json"))
+    }
+
+    /// Add address to address book
+    fn add_address_book_entry(&self, _address: &str, _label: Option<&str>, _description: Option<&str>) -> WalletResult<()> {
+        Err(unsupported("add_address_book_entry"))
+    }
+
+    /// Remove address from address book
+    fn remove_address_book_entry(&self, _address: &str) -> WalletResult<()> {
+        Err(unsupported("remove_address_book_entry"))
+    }
+
+    /// Update address book entry
+    fn update_address_book_entry(&self, _address: &str, _label: Option<&str>, _description: Option<&str>) -> WalletResult<()> {
+        Err(unsupported("update_address_book_entry"))
+    }
+
+    /// Get address book entries
+    fn get_address_book(&self) -> WalletResult<Vec<AddressBookEntry>> {
+        Err(unsupported("get_address_book"))
+    }
+
+    /// Mark address as used
+    fn mark_address_used(&self, _address: &str) -> WalletResult<()> {
+        Err(unsupported("mark_address_used"))
+    }
+
+    /// Get address book entry by address
+    fn get_address_book_entry(&self, _address: &str) -> WalletResult<Option<AddressBookEntry>> {
+        Err(unsupported("get_address_book_entry"))
+    }
+
+    /// Set mining pool configuration
+    fn set_mining_pool(&self, _pool_address: Option<&str>, _worker_name: Option<&str>) -> WalletResult<()> {
+        Err(unsupported("set_mining_pool"))
+    }
+
+    /// Get detailed mining statistics as JSON
+    fn get_mining_stats_json(&self) -> WalletResult<String> {
+        Err(unsupported("get_mining_stats_json"))
+    }
+
+    /// Derive keys from seed phrase
+    fn derive_keys_from_seed(&self, _seed_phrase: &str, _password: &str) -> WalletResult<()> {
+        Err(unsupported("derive_keys_from_seed"))
+    }
+
+    /// Get seed phrase (requires password for decryption)
+    fn get_seed_phrase(&self, _password: &str) -> WalletResult<String> {
+        Err(unsupported("get_seed_phrase"))
+    }
+
+    /// Get view key
+    fn get_view_key(&self) -> WalletResult<String> {
+        Err(unsupported("get_view_key"))
+    }
+
+    /// Get spend key
+    fn get_spend_key(&self) -> WalletResult<String> {
+        Err(unsupported("get_spend_key"))
+    }
+
+    /// Check if wallet has keys
+    fn has_keys(&self) -> WalletResult<bool> {
+        Err(unsupported("has_keys"))
+    }
+
+    /// Export wallet keys
+    fn export_keys(&self) -> WalletResult<String> {
+        Err(unsupported("export_keys"))
+    }
+
+    /// Import wallet keys
+    fn import_keys(&self, _view_key: &str, _spend_key: &str, _address: &str) -> WalletResult<()> {
+        Err(unsupported("import_keys"))
+    }
+}
+
+pub(crate) fn unsupported(operation: &str) -> WalletError {
+    WalletError::Generic(format!("{} is not supported by this backend", operation))
+}
+
+/// Implementation new wallets run on
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackendKind {
+    /// `FfiWalletBackend`, the native Fuego library
+    #[default]
+    Native,
+    /// `CryptoNoteFFI`, the older CryptoNote bindings
+    LegacyFfi,
+    /// `MockWalletBackend`, for demo mode and for running without the C++ side
+    Mock,
+}
+
+/// Backend chosen by the settings
+static SELECTED_BACKEND: RwLock<BackendKind> = RwLock::new(BackendKind::Native);
+
+/// The mock wallet every `BackendKind::Mock` backend shares, so that what one
+/// command does is seen by the next
+static SHARED_MOCK: OnceLock<MockWalletBackend> = OnceLock::new();
+
+/// Run wallets created from now on on `kind`
+pub fn select_backend(kind: BackendKind) {
+    *SELECTED_BACKEND.write_recover() = kind;
+}
+
+/// Backend new wallets run on
+pub fn selected_backend() -> BackendKind {
+    *SELECTED_BACKEND.read_recover()
+}
+
+/// A backend of `kind`, with no wallet open
+pub fn new_backend(kind: BackendKind) -> Box<dyn WalletBackend + Send> {
+    match kind {
+        BackendKind::Native => Box::new(FfiWalletBackend::new()),
+        BackendKind::LegacyFfi => Box::new(CryptoNoteFFI::new()),
+        BackendKind::Mock => Box::new(shared_mock().clone()),
+    }
+}
+
+/// The mock wallet shared by all `BackendKind::Mock` backends
+pub fn shared_mock() -> &'static MockWalletBackend {
+    SHARED_MOCK.get_or_init(MockWalletBackend::new)
+}
+
+/// Amount `smoke_test` sends when the wallet can afford it
+const SMOKE_TEST_AMOUNT: u64 = 100_000_000;
+
+/// Create a wallet at `file_path` on `backend` and run the basic operations
+/// every backend supports: balances, address and, when the wallet holds
+/// enough, a transaction to itself. Returns what was read.
+pub fn smoke_test(backend: &mut dyn WalletBackend, password: &str, file_path: &str) -> WalletResult<serde_json::Value> {
+    backend.create_wallet(password, file_path, None, 0)?;
+    let balance = backend.get_balance()?;
+    let unlocked_balance = backend.get_unlocked_balance()?;
+    let address = backend.get_address()?;
+    let is_open = backend.is_open();
+
+    // A freshly created wallet of a real backend is empty
+    let transaction_hash = if unlocked_balance > SMOKE_TEST_AMOUNT {
+        Some(backend.send_transaction(&address, SMOKE_TEST_AMOUNT, None, 5)?)
+    } else {
+        None
+    };
+
+    Ok(serde_json::json!({
+        "wallet": {
+            "is_open": is_open,
+            "balance": balance,
+            "unlocked_balance": unlocked_balance,
+            "address": address
+        },
+        "transaction": {
+            "hash": transaction_hash
+        }
+    }))
+}
+
+/// Seed phrase of mock wallets created without one
+const MOCK_SEED_PHRASE: &str = "mock wallet seed phrase";
+
+/// State of a `MockWalletBackend`, set up and inspected by tests
+#[derive(Debug, Clone)]
+pub struct MockWalletState {
+    pub is_open: bool,
+    pub address: String,
+    pub balance: u64,
+    pub unlocked_balance: u64,
+    /// Fee charged for every transaction
+    pub fee: u64,
+    pub transactions: Vec<TransactionInfo>,
+    pub deposits: Vec<DepositInfo>,
+    pub outputs: Vec<UnspentOutput>,
+    pub is_connected: bool,
+    pub peer_count: u32,
+    pub sync_height: u64,
+    pub network_height: u64,
+    /// Blocks each refresh catches up by
+    pub blocks_per_refresh: u64,
+    /// How long each refresh blocks, like a refresh against a slow daemon
+    pub refresh_delay: Duration,
+    /// Emission reported by the daemon; `None` for a daemon without it
+    pub emission: Option<EmissionInfo>,
+    /// Deposit limits reported by the daemon; `None` for a daemon without them
+    pub deposit_limits: Option<DepositLimits>,
+    /// Why verifying the wallet file finds it corrupted
+    pub corruption: Option<String>,
+    /// Times the wallet was saved
+    pub saves: u32,
+    /// Why saving fails; saves succeed when `None`
+    pub save_error: Option<String>,
+    /// How many of the next connects fail
+    pub connect_failures: u32,
+    /// Every node a connect was tried on, in order
+    pub connect_attempts: Vec<(String, u16)>,
+    /// Sync that follows the clock instead of refreshes
+    pub sync_script: Option<SyncScript>,
+    /// Seed phrase the wallet was created from
+    pub seed_phrase: String,
+    pub address_book: Vec<AddressBookEntry>,
+    /// Size fee per 1024 bytes that estimates add to `fee`; sends take `fee` only
+    pub fee_per_kb: u64,
+    sent_count: u64,
+}
+
+/// Sync height moving from `from_height` to the network height over `duration`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyncScript {
+    pub started: Instant,
+    pub from_height: u64,
+    pub duration: Duration,
+}
+
+impl SyncScript {
+    /// Sync height at `now`, reaching `network_height` once `duration` has passed
+    pub fn height_at(&self, network_height: u64, now: Instant) -> u64 {
+        let elapsed = now.saturating_duration_since(self.started);
+        if elapsed >= self.duration || self.from_height >= network_height {
+            return network_height.max(self.from_height);
+        }
+        let behind = network_height - self.from_height;
+        let caught_up = (u128::from(behind) * elapsed.as_millis() / self.duration.as_millis().max(1)) as u64;
+        self.from_height + caught_up
+    }
+}
+
+impl Default for MockWalletState {
+    fn default() -> Self {
+        Self {
+            is_open: false,
+            address: "fire1jNwRRUYGENanfBwVhehZXVcQVFx3dH3D3Z7UNC17FePBr27DDwctyL2ePwDPz4fypwpNQpfXbp6wavubvSn6ToisC5NUy".to_string(),
+            balance: 0,
+            unlocked_balance: 0,
+            fee: 10_000,
+            transactions: Vec::new(),
+            deposits: Vec::new(),
+            outputs: Vec::new(),
+            is_connected: false,
+            peer_count: 0,
+            sync_height: 0,
+            network_height: 0,
+            blocks_per_refresh: 100,
+            refresh_delay: Duration::ZERO,
+            emission: None,
+            deposit_limits: None,
+            corruption: None,
+            saves: 0,
+            save_error: None,
+            connect_failures: 0,
+            connect_attempts: Vec::new(),
+            sync_script: None,
+            seed_phrase: MOCK_SEED_PHRASE.to_string(),
+            address_book: Vec::new(),
+            fee_per_kb: 0,
+            sent_count: 0,
+        }
+    }
+}
+
+/// Stand-in for a payment proof: deterministic in the transaction, address and message
+fn mock_tx_proof(tx_hash: &str, address: &str, message: Option<&str>) -> String {
+    const BASE58: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+    let mut bytes = [0u8; TX_PROOF_BODY_LEN];
+    blake3::Hasher::new()
+        .update(format!("{}|{}|{}", tx_hash, address, message.unwrap_or_default()).as_bytes())
+        .finalize_xof()
+        .fill(&mut bytes);
+    let body: String = bytes.iter().map(|b| BASE58[*b as usize % BASE58.len()] as char).collect();
+    format!("{}{}", TX_PROOF_PREFIX, body)
+}
+
+/// In-memory wallet for tests. Clones share their state, so a test can keep
+/// one to inspect the wallet after handing another to `RealCryptoNoteWallet`.
+#[derive(Debug, Clone, Default)]
+pub struct MockWalletBackend {
+    state: Arc<Mutex<MockWalletState>>,
+}
+
+impl MockWalletBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// An open wallet holding `unlocked_balance`, all of it spendable
+    pub fn with_balance(unlocked_balance: u64) -> Self {
+        let backend = Self::new();
+        {
+            let mut state = backend.state();
+            state.is_open = true;
+            state.balance = unlocked_balance;
+            state.unlocked_balance = unlocked_balance;
+        }
+        backend
+    }
+
+    pub fn state(&self) -> MutexGuard<'_, MockWalletState> {
+        self.state.lock_recover()
+    }
+
+    fn open_state(&self) -> WalletResult<MutexGuard<'_, MockWalletState>> {
+        let mut state = self.state();
+        if !state.is_open {
+            return Err(WalletError::WalletNotOpen);
+        }
+        if let Some(script) = state.sync_script {
+            state.sync_height = script.height_at(state.network_height, Instant::now());
+        }
+        Ok(state)
+    }
+
+    fn send(&self, outputs: &[(String, u64)], payment_id: Option<&str>) -> WalletResult<String> {
+        let state = self.open_state()?;
+        self.record_send(state, outputs, payment_id)
+    }
+
+    /// Take `outputs` plus the fee from the balance and add the pending transaction
+    fn record_send(
+        &self,
+        mut state: MutexGuard<'_, MockWalletState>,
+        outputs: &[(String, u64)],
+        payment_id: Option<&str>,
+    ) -> WalletResult<String> {
+        let total = outputs.iter().try_fold(state.fee, |sum, (_, amount)| sum.checked_add(*amount))
+            .ok_or_else(|| WalletError::TransactionFailed("Total amount overflows".to_string()))?;
+        if total > state.unlocked_balance {
+            return Err(WalletError::InsufficientFunds);
+        }
+
+        state.balance -= total;
+        state.unlocked_balance -= total;
+        state.sent_count += 1;
+        let hash = format!("{:064x}", state.sent_count);
+        let transaction = TransactionInfo {
+            id: hash.clone(),
+            hash: hash.clone(),
+            amount: -((total - state.fee) as i64),
+            fee: state.fee,
+            height: 0,
+            timestamp: chrono::Utc::now().timestamp().max(0) as u64,
+            confirmations: 0,
+            is_confirmed: false,
+            is_pending: true,
+            payment_id: payment_id.map(str::to_string),
+            destination_addresses: outputs.iter().map(|(address, _)| address.clone()).collect(),
+            source_addresses: vec![state.address.clone()],
+            unlock_time: None,
+            extra: None,
+        };
+        state.transactions.push(transaction);
+        Ok(hash)
+    }
+
+    /// Pay `outputs` from the outputs with `key_images`
+    fn spend_outputs(&self, outputs: &[(String, u64)], payment_id: Option<&str>, key_images: &[String]) -> WalletResult<String> {
+        let mut state = self.open_state()?;
+        let spent: u64 = state.outputs.iter()
+            .filter(|o| key_images.contains(&o.key_image))
+            .map(|o| o.amount)
+            .sum();
+        let amount = outputs.iter().fold(0u64, |sum, (_, amount)| sum.saturating_add(*amount));
+        let change = spent.checked_sub(amount.saturating_add(state.fee)).ok_or(WalletError::InsufficientFunds)?;
+        state.outputs.retain(|o| !key_images.contains(&o.key_image));
+
+        let to_self: u64 = outputs.iter().filter(|(address, _)| *address == state.address).map(|(_, amount)| *amount).sum();
+        let received = change + to_self;
+        if received > 0 {
+            let global_index = state.outputs.iter().map(|o| o.global_index).max().unwrap_or(0) + 1;
+            let block_height = state.sync_height;
+            state.outputs.push(UnspentOutput {
+                amount: received,
+                global_index,
+                key_image: format!("{:064x}", global_index),
+                block_height,
+                is_locked: true,
+                tx_hash: None,
+            });
+        }
+
+        let hash = self.record_send(state, outputs, payment_id)?;
+        // The new output is locked until it matures
+        let mut state = self.state();
+        if received > 0 && let Some(output) = state.outputs.last_mut() {
+            output.tx_hash = Some(hash.clone());
+        }
+        state.unlocked_balance -= change;
+        state.balance += to_self;
+        Ok(hash)
+    }
+}
+
+impl WalletBackend for MockWalletBackend {
+    fn create_wallet(
+        &mut self,
+        _password: &str,
+        _file_path: &str,
+        seed_phrase: Option<&str>,
+        _restore_height: u64,
+    ) -> WalletResult<()> {
+        let mut state = self.state();
+        state.is_open = true;
+        state.seed_phrase = seed_phrase.unwrap_or(MOCK_SEED_PHRASE).to_string();
+        Ok(())
+    }
+
+    fn open_wallet(&mut self, _file_path: &str, _password: &str) -> WalletResult<()> {
+        self.state().is_open = true;
+        Ok(())
+    }
+
+    fn close_wallet(&mut self) {
+        self.state().is_open = false;
+    }
+
+    fn is_open(&self) -> bool {
+        self.state().is_open
+    }
+
+    fn save(&self) -> WalletResult<()> {
+        let mut state = self.open_state()?;
+        if let Some(error) = state.save_error.clone() {
+            return Err(WalletError::StorageError(error));
+        }
+        state.saves += 1;
+        Ok(())
+    }
+
+    fn get_balance(&self) -> WalletResult<u64> {
+        Ok(self.open_state()?.balance)
+    }
+
+    fn get_unlocked_balance(&self) -> WalletResult<u64> {
+        Ok(self.open_state()?.unlocked_balance)
+    }
+
+    fn get_address(&self) -> WalletResult<String> {
+        Ok(self.open_state()?.address.clone())
+    }
+
+    fn send_transaction(
+        &self,
+        address: &str,
+        amount: u64,
+        payment_id: Option<&str>,
+        _mixin: u64,
+    ) -> WalletResult<String> {
+        self.send(&[(address.to_string(), amount)], payment_id)
+    }
+
+    fn send_many(&self, outputs: &[(String, u64)], _mixin: u64) -> WalletResult<String> {
+        self.send(outputs, None)
+    }
+
+    fn get_unspent_outputs(&self) -> WalletResult<Vec<UnspentOutput>> {
+        Ok(self.open_state()?.outputs.clone())
+    }
+
+    /// Checks the wallet state rather than the file
+    fn verify_wallet(&self, _file_path: &str, _password: &str) -> WalletResult<WalletIntegrityReport> {
+        let state = self.state();
+        if let Some(reason) = &state.corruption {
+            return Err(WalletError::WalletCorrupted { reason: reason.clone(), recovery: RecoveryAction::RestoreFromSeed });
+        }
+        Ok(WalletIntegrityReport {
+            cache_consistent: true,
+            key_image_count: state.outputs.len() as u64,
+            last_processed_height: state.sync_height,
+            ..Default::default()
+        })
+    }
+
+    fn get_emission(&self) -> WalletResult<EmissionInfo> {
+        let state = self.open_state()?;
+        Ok(state.emission.clone().unwrap_or_else(|| EmissionInfo::unsupported(state.network_height)))
+    }
+
+    fn get_deposit_limits(&self) -> WalletResult<DepositLimits> {
+        let state = self.open_state()?;
+        Ok(state.deposit_limits.clone().unwrap_or_else(|| DepositLimits::defaults(state.network_height)))
+    }
+
+    /// Spends the selected outputs; the change, and the amount if sent to the
+    /// wallet's own address, come back as one new locked output
+    fn send_from_outputs(
+        &self,
+        address: &str,
+        amount: u64,
+        payment_id: Option<&str>,
+        _mixin: u64,
+        key_images: &[String],
+    ) -> WalletResult<String> {
+        self.spend_outputs(&[(address.to_string(), amount)], payment_id, key_images)
+    }
+
+    fn send_many_from_outputs(&self, outputs: &[(String, u64)], _mixin: u64, key_images: &[String]) -> WalletResult<String> {
+        self.spend_outputs(outputs, None, key_images)
+    }
+
+    fn connect_to_node(&mut self, address: &str, port: u16, _timeout: Duration) -> WalletResult<()> {
+        let mut state = self.state();
+        state.connect_attempts.push((address.to_string(), port));
+        if state.connect_failures > 0 {
+            state.connect_failures -= 1;
+            return Err(WalletError::NetworkError(format!("{}:{} refused the connection", address, port)));
+        }
+        state.is_connected = true;
+        Ok(())
+    }
+
+    fn get_seed_phrase(&self, _password: &str) -> WalletResult<String> {
+        Ok(self.open_state()?.seed_phrase.clone())
+    }
+
+    fn get_address_book(&self) -> WalletResult<Vec<AddressBookEntry>> {
+        Ok(self.open_state()?.address_book.clone())
+    }
+
+    fn mark_address_used(&self, address: &str) -> WalletResult<()> {
+        let mut state = self.open_state()?;
+        let entry = state.address_book.iter_mut()
+            .find(|e| e.address == address)
+            .ok_or_else(|| WalletError::InvalidAddress(address.to_string()))?;
+        entry.use_count += 1;
+        entry.last_used_time = chrono::Utc::now().timestamp().max(0) as u64;
+        Ok(())
+    }
+
+    fn get_network_status(&self) -> WalletResult<serde_json::Value> {
+        let state = self.open_state()?;
+        Ok(serde_json::json!({
+            "is_connected": state.is_connected,
+            "peer_count": state.peer_count,
+            "sync_height": state.sync_height,
+            "network_height": state.network_height,
+            "is_syncing": state.sync_height < state.network_height,
+        }))
+    }
+
+    fn get_deposits(&self) -> WalletResult<Vec<DepositInfo>> {
+        Ok(self.open_state()?.deposits.clone())
+    }
+
+    fn get_wallet_info(&self) -> WalletResult<WalletInfo> {
+        let state = self.open_state()?;
+        let total = |incoming: bool| state.transactions.iter()
+            .filter(|t| (t.amount > 0) == incoming)
+            .map(|t| t.amount.unsigned_abs())
+            .sum();
+        Ok(WalletInfo {
+            address: state.address.clone(),
+            balance: state.balance,
+            unlocked_balance: state.unlocked_balance,
+            locked_balance: state.balance - state.unlocked_balance,
+            total_received: total(true),
+            total_sent: total(false),
+            transaction_count: state.transactions.len() as u32,
+            is_synced: state.sync_height >= state.network_height,
+            sync_height: state.sync_height,
+            network_height: state.network_height,
+            daemon_height: state.network_height,
+            is_connected: state.is_connected,
+            peer_count: state.peer_count,
+            last_block_time: None,
+        })
+    }
+
+    fn get_network_info(&self) -> WalletResult<NetworkInfo> {
+        let state = self.open_state()?;
+        Ok(NetworkInfo {
+            is_connected: state.is_connected,
+            peer_count: state.peer_count,
+            sync_height: state.sync_height,
+            network_height: state.network_height,
+            is_syncing: state.sync_height < state.network_height,
+            connection_type: "Mock".to_string(),
+            last_sync_time: None,
+            sync_speed: 0.0,
+            estimated_sync_time: None,
+        })
+    }
+
+    fn refresh(&mut self) -> WalletResult<()> {
+        // Without holding the state, so other calls go on meanwhile
+        let delay = self.open_state()?.refresh_delay;
+        std::thread::sleep(delay);
+        let mut state = self.open_state()?;
+        state.sync_height = state.sync_height.saturating_add(state.blocks_per_refresh).min(state.network_height);
+        Ok(())
+    }
+
+    fn rescan_blockchain(&mut self, start_height: u64) -> WalletResult<()> {
+        let mut state = self.open_state()?;
+        state.sync_height = start_height.min(state.sync_height);
+        Ok(())
+    }
+
+    fn get_transaction_by_hash(&self, tx_hash: &str) -> WalletResult<TransactionInfo> {
+        self.open_state()?.transactions.iter()
+            .find(|t| t.hash == tx_hash)
+            .cloned()
+            .ok_or_else(|| WalletError::TransactionFailed(format!("Transaction not found: {}", tx_hash)))
+    }
+
+    fn rebroadcast_transaction(&self, tx_hash: &str) -> WalletResult<()> {
+        let state = self.open_state()?;
+        if !state.is_connected {
+            return Err(WalletError::NetworkError("Not connected to a node".to_string()));
+        }
+        match state.transactions.iter().find(|t| t.hash == tx_hash) {
+            Some(tx) if !tx.is_confirmed => Ok(()),
+            Some(_) => Err(WalletError::TransactionFailed(format!("Transaction already confirmed: {}", tx_hash))),
+            None => Err(WalletError::TransactionFailed(format!("Transaction not found: {}", tx_hash))),
+        }
+    }
+
+    fn get_tx_proof(&self, tx_hash: &str, address: &str, message: Option<&str>) -> WalletResult<String> {
+        let state = self.open_state()?;
+        if !state.transactions.iter().any(|t| t.hash == tx_hash) {
+            return Err(WalletError::TransactionFailed(format!("Transaction not found: {}", tx_hash)));
+        }
+        Ok(mock_tx_proof(tx_hash, address, message))
+    }
+
+    fn check_tx_proof(&self, tx_hash: &str, address: &str, message: Option<&str>, proof: &str) -> WalletResult<bool> {
+        drop(self.open_state()?);
+        Ok(proof == mock_tx_proof(tx_hash, address, message))
+    }
+
+    fn estimate_transaction_fee(
+        &self,
+        _address: &str,
+        _amount: u64,
+        mixin: u64,
+    ) -> WalletResult<FeeBreakdown> {
+        // One input, a payment and a change output
+        let state = self.open_state()?;
+        let size = estimated_transaction_size(1, 2, mixin);
+        let size_fee = size * state.fee_per_kb / 1024;
+        Ok(FeeBreakdown {
+            base_fee: state.fee,
+            size_fee,
+            total: state.fee + size_fee,
+            estimated_size_bytes: size,
+            ..Default::default()
+        })
+    }
+
+    fn disconnect(&mut self) -> WalletResult<()> {
+        self.state().is_connected = false;
+        Ok(())
+    }
+
+    fn create_deposit(&self, _amount: u64, _term: u32) -> WalletResult<String> {
+        Err(unsupported("create_deposit"))
+    }
+
+    fn withdraw_deposit(&self, _deposit_id: &str) -> WalletResult<String> {
+        Err(unsupported("withdraw_deposit"))
+    }
+
+    fn get_transaction_history(&self, limit: u64, offset: u64) -> WalletResult<Vec<TransactionInfo>> {
+        let state = self.open_state()?;
+        Ok(state.transactions.iter().rev()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+
+    fn get_sync_progress(&self) -> WalletResult<SyncProgress> {
+        let state = self.open_state()?;
+        let progress_percentage = if state.network_height == 0 {
+            100.0
+        } else {
+            (state.sync_height as f32 / state.network_height as f32 * 100.0).min(100.0)
+        };
+        Ok(SyncProgress {
+            current_height: state.sync_height,
+            total_height: state.network_height,
+            progress_percentage,
+            estimated_time_remaining: 0,
+            is_syncing: state.sync_height < state.network_height,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use crate::crypto::real_cryptonote::{RealCryptoNoteWallet, SyncPhase};
+    use crate::utils::progress::ProgressReporter;
+    use crate::utils::testing::TempDir;
+
+    const RECIPIENT: &str = "fire1jNwRRUYGENanfBwVhehZXVcQVFx3dH3D3Z7UNC17FePBr27DDwctyL2ePwDPz4fypwpNQpfXbp6wavubvSn6ToisC5NUy";
+
+    #[test]
+    fn test_smoke_test_on_every_backend() {
+        let dir = TempDir::new("fuego_backend");
+        let backends: Vec<(&str, Box<dyn WalletBackend + Send>)> = vec![
+            ("mock", Box::new(MockWalletBackend::with_balance(500_000_000))),
+            ("native", Box::new(FfiWalletBackend::new())),
+            ("legacy_ffi", Box::new(CryptoNoteFFI::new())),
+        ];
+
+        for (name, mut backend) in backends {
+            let file_path = dir.join(format!("{}.wallet", name));
+            let report = smoke_test(backend.as_mut(), "test_password", file_path.to_str().unwrap())
+                .unwrap_or_else(|e| panic!("{}: {}", name, e));
+            assert_eq!(report["wallet"]["is_open"], true, "{}", name);
+            assert!(!report["wallet"]["address"].as_str().unwrap().is_empty(), "{}", name);
+            let unlocked = report["wallet"]["unlocked_balance"].as_u64().unwrap();
+            assert!(unlocked <= report["wallet"]["balance"].as_u64().unwrap(), "{}", name);
+            assert_eq!(report["transaction"]["hash"].is_string(), unlocked > SMOKE_TEST_AMOUNT, "{}", name);
+            if report["transaction"]["hash"].is_string() {
+                assert!(backend.get_unlocked_balance().unwrap() < unlocked, "{}", name);
+            }
+
+            backend.close_wallet();
+            assert!(!backend.is_open(), "{}", name);
+            assert!(matches!(backend.get_address(), Err(WalletError::WalletNotOpen)), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_send_many_checks_before_sending() {
+        let backend = MockWalletBackend::with_balance(1_000_000);
+        let wallet = RealCryptoNoteWallet::with_backend(Box::new(backend.clone()));
+
+        assert!(matches!(wallet.send_many(&[], 5, &HashSet::new()), Err(WalletError::TransactionFailed(_))));
+        assert!(matches!(wallet.send_many(&[("fire123".to_string(), 1)], 5, &HashSet::new()), Err(WalletError::InvalidAddress(_))));
+        assert!(matches!(wallet.send_many(&[(RECIPIENT.to_string(), 0)], 5, &HashSet::new()), Err(WalletError::TransactionFailed(_))));
+        assert!(matches!(
+            wallet.send_many(&[(RECIPIENT.to_string(), 995_000)], 5, &HashSet::new()),
+            Err(WalletError::InsufficientFunds)
+        ));
+        assert!(backend.state().transactions.is_empty());
+
+        let hash = wallet.send_many(&[(RECIPIENT.to_string(), 400_000), (RECIPIENT.to_string(), 100_000)], 5, &HashSet::new()).unwrap();
+        let sent = wallet.get_transaction_by_hash(&hash).unwrap();
+        assert_eq!((sent.amount, sent.fee, sent.is_pending), (-500_000, 10_000, true));
+        assert_eq!(wallet.get_unlocked_balance().unwrap(), 490_000);
+    }
+
+    #[test]
+    fn test_send_many_fee_covers_every_destination() {
+        let backend = MockWalletBackend::with_balance(375_000);
+        backend.state().fee_per_kb = 102_400;
+        let wallet = RealCryptoNoteWallet::with_backend(Box::new(backend.clone()));
+        let batch: Vec<(String, u64)> = (0..3).map(|_| (RECIPIENT.to_string(), 100_000)).collect();
+
+        // One payment of 628 bytes would cost 72_800, three outputs make it 708 bytes
+        let single = wallet.estimate_fee_breakdown(RECIPIENT, 300_000, 5).unwrap();
+        let fee = wallet.estimate_batch_fee(&batch, 5).unwrap();
+        assert_eq!((single.total, fee.total), (72_800, 80_800));
+        assert_eq!(fee.estimated_size_bytes, 708);
+        assert!(matches!(wallet.send_many(&batch, 5, &HashSet::new()), Err(WalletError::InsufficientFunds)));
+        assert!(backend.state().transactions.is_empty());
+    }
+
+    #[test]
+    fn test_sweep_all_leaves_nothing_but_the_fee() {
+        let backend = MockWalletBackend::with_balance(250_000);
+        let wallet = RealCryptoNoteWallet::with_backend(Box::new(backend.clone()));
+
+        let hash = wallet.sweep_all(RECIPIENT, 5, &HashSet::new()).unwrap();
+        assert_eq!(wallet.get_transaction_by_hash(&hash).unwrap().amount, -240_000);
+        assert_eq!(wallet.get_unlocked_balance().unwrap(), 0);
+        assert!(matches!(wallet.sweep_all(RECIPIENT, 5, &HashSet::new()), Err(WalletError::InsufficientFunds)));
+
+        backend.state().is_open = false;
+        assert!(matches!(wallet.sweep_all(RECIPIENT, 5, &HashSet::new()), Err(WalletError::WalletNotOpen)));
+    }
+
+    #[test]
+    fn test_refresh_reports_sync_progress() {
+        let backend = MockWalletBackend::with_balance(0);
+        backend.state().network_height = 150;
+        backend.state().is_connected = true;
+        let mut wallet = RealCryptoNoteWallet::with_backend(Box::new(backend.clone()));
+        let (reporter, receiver) = ProgressReporter::channel("sync");
+
+        let progress = wallet.refresh_with_progress(Some(&reporter)).unwrap();
+        assert!(progress.is_syncing);
+        assert_eq!((progress.current_height, progress.total_height), (100, 150));
+        let progress = wallet.refresh_with_progress(Some(&reporter)).unwrap();
+        assert!(!progress.is_syncing);
+
+        wallet.close_wallet();
+        assert!(!backend.state().is_open);
+        assert!(matches!(wallet.refresh_with_progress(Some(&reporter)), Err(WalletError::WalletNotOpen)));
+        drop(reporter);
+        let phases: Vec<_> = receiver.iter().map(|update| update.phase).collect();
+        assert_eq!(phases, vec![Some(SyncPhase::DownloadingBlocks), Some(SyncPhase::Synced)]);
+    }
+}
@@ -0,0 +1,47 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Declarations generated from the C headers
+//!
+//! Built with `--features bindgen`, which runs bindgen over
+//! `fuego_wallet_real.h` and `crypto_note_ffi.h`. The two headers define
+//! types of the same name differently, so each gets its own module. The
+//! assertions below compare the generated structs with the `#[repr(C)]`
+//! mirrors the wrappers use, so a header change that moves a field breaks the
+//! build rather than corrupting memory at runtime.
+
+#![allow(non_upper_case_globals, non_camel_case_types, non_snake_case, dead_code)]
+
+/// `fuego_wallet_real.h`
+pub mod fuego_wallet {
+    include!(concat!(env!("OUT_DIR"), "/fuego_wallet_bindings.rs"));
+}
+
+/// `crypto_note_ffi.h`
+pub mod crypto_note {
+    include!(concat!(env!("OUT_DIR"), "/crypto_note_bindings.rs"));
+}
+
+macro_rules! assert_same_layout {
+    ($($generated:ty => $mirror:ty),* $(,)?) => {
+        $(
+            const _: () = assert!(
+                std::mem::size_of::<$generated>() == std::mem::size_of::<$mirror>()
+                    && std::mem::align_of::<$generated>() == std::mem::align_of::<$mirror>(),
+                concat!(stringify!($mirror), " does not match ", stringify!($generated), " from the header"),
+            );
+        )*
+    };
+}
+
+assert_same_layout! {
+    fuego_wallet::WalletInfo => super::real_cryptonote::WalletInfoFFI,
+    fuego_wallet::TransactionInfo => super::real_cryptonote::TransactionInfoFFI,
+    fuego_wallet::NetworkStatus => super::real_cryptonote::NetworkStatusFFI,
+    fuego_wallet::NetworkInfo => super::real_cryptonote::NetworkInfoFFI,
+    fuego_wallet::BlockInfo => super::real_cryptonote::BlockInfoFFI,
+    fuego_wallet::MiningInfo => super::real_cryptonote::MiningInfoFFI,
+    fuego_wallet::SyncProgress => super::real_cryptonote::SyncProgress,
+    fuego_wallet::FeeBreakdown => super::real_cryptonote::FeeBreakdown,
+    fuego_wallet::AbiSizes => super::real_cryptonote::AbiSizesFFI,
+}
@@ -0,0 +1,387 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Daemon RPC client
+//!
+//! Reads chain and daemon information from the `/getinfo` endpoint of a Fuego
+//! daemon. Results are cached for a short time; when every node fails the last
+//! good answer is returned with `stale` set. Peers are listed and banned on
+//! the node that answered last, see `peers`.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use crate::advanced::AdvancedNetworkInfo;
+use super::peers::{fetch_peers, peer_ip, set_ban, PeerLatencies, PeerList};
+use crate::utils::error::{WalletError, WalletResult};
+use crate::utils::retry::{retry_async, RetryPolicy};
+use crate::utils::locks::MutexExt;
+
+/// How long a daemon answer is served from the cache
+pub const DAEMON_INFO_TTL: Duration = Duration::from_secs(30);
+
+/// Fuego target block time in seconds
+pub const TARGET_BLOCK_TIME: u64 = 480;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Attempts per node before moving on to the next one
+const NODE_ATTEMPTS: u32 = 2;
+
+/// Daemon and chain state as reported by `/getinfo`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DaemonInfo {
+    pub node: String,
+    pub status: String,
+    pub version: Option<String>,
+    pub height: u64,
+    pub difficulty: u64,
+    pub hash_rate: f64,
+    pub tx_count: u64,
+    pub tx_pool_size: u64,
+    pub peer_count: u32,
+    pub last_block_reward: u64,
+    pub last_block_hash: Option<String>,
+    pub last_block_timestamp: Option<u64>,
+    pub block_major_version: u8,
+    pub block_minor_version: u8,
+    pub block_time: u64,
+    pub uptime: Option<u64>,
+    pub fee_address: Option<String>,
+    /// When this answer was received (unix seconds)
+    pub fetched_at: u64,
+    /// Set when the daemon could not be reached and this is an older answer
+    pub stale: bool,
+}
+
+impl DaemonInfo {
+    /// Build from a `/getinfo` response
+    pub fn from_getinfo(node: &str, value: &serde_json::Value) -> WalletResult<Self> {
+        let u64_of = |key: &str| value.get(key).and_then(|v| v.as_u64());
+        let str_of = |key: &str| value.get(key).and_then(|v| v.as_str()).filter(|s| !s.is_empty()).map(String::from);
+
+        let status = str_of("status").unwrap_or_default();
+        if status != "OK" {
+            return Err(WalletError::NetworkError(format!("Daemon {} reported status '{}'", node, status)));
+        }
+        let height = u64_of("height")
+            .ok_or_else(|| WalletError::NetworkError(format!("Daemon {} sent no height", node)))?;
+        let difficulty = u64_of("difficulty").unwrap_or(0);
+        let now = now();
+
+        Ok(Self {
+            node: node.to_string(),
+            status,
+            version: str_of("version"),
+            height,
+            difficulty,
+            hash_rate: difficulty as f64 / TARGET_BLOCK_TIME as f64,
+            tx_count: u64_of("tx_count").unwrap_or(0),
+            tx_pool_size: u64_of("tx_pool_size").unwrap_or(0),
+            peer_count: (u64_of("incoming_connections_count").unwrap_or(0)
+                + u64_of("outgoing_connections_count").unwrap_or(0)) as u32,
+            last_block_reward: u64_of("last_block_reward").unwrap_or(0),
+            last_block_hash: str_of("top_block_hash"),
+            last_block_timestamp: u64_of("last_block_timestamp"),
+            block_major_version: u64_of("block_major_version").unwrap_or(0) as u8,
+            block_minor_version: u64_of("block_minor_version").unwrap_or(0) as u8,
+            block_time: TARGET_BLOCK_TIME,
+            uptime: u64_of("start_time").filter(|&start| start > 0 && start <= now).map(|start| now - start),
+            fee_address: str_of("fee_address"),
+            fetched_at: now,
+            stale: false,
+        })
+    }
+
+    /// The JSON shape `get_fuego_network_data` has always returned
+    pub fn to_network_data(&self) -> serde_json::Value {
+        serde_json::json!({
+            "height": self.height,
+            "peer_count": self.peer_count,
+            "difficulty": self.difficulty,
+            "last_block_reward": self.last_block_reward,
+            "block_major_version": self.block_major_version,
+            "block_minor_version": self.block_minor_version,
+            "status": self.status,
+            "version": self.version,
+            "tx_count": self.tx_count,
+            "fee_address": self.fee_address,
+            "stale": self.stale,
+        })
+    }
+
+    /// Fill the daemon fields of `info`
+    pub fn apply_to(&self, info: &mut AdvancedNetworkInfo) {
+        info.daemon_version = self.version.clone();
+        info.daemon_uptime = self.uptime;
+        info.difficulty = self.difficulty;
+        info.hash_rate = self.hash_rate;
+        info.block_reward = self.last_block_reward;
+        info.block_time = self.block_time;
+        info.last_block_hash = self.last_block_hash.clone();
+        info.last_block_timestamp = self.last_block_timestamp;
+        info.network_height = info.network_height.max(self.height);
+    }
+}
+
+/// Queries daemons in order and caches the first good answer
+#[derive(Debug)]
+pub struct DaemonClient {
+    nodes: Vec<String>,
+    ttl: Duration,
+    cached: Mutex<Option<(DaemonInfo, Instant)>>,
+    http: reqwest::Client,
+    peer_latencies: PeerLatencies,
+}
+
+impl DaemonClient {
+    /// Create a client for daemon base URLs such as `http://host:18180`
+    pub fn new(nodes: Vec<String>, ttl: Duration) -> Self {
+        Self {
+            nodes,
+            ttl,
+            cached: Mutex::new(None),
+            http: reqwest::Client::new(),
+            peer_latencies: PeerLatencies::default(),
+        }
+    }
+
+    /// Client for the well-known Fuego nodes
+    pub fn for_known_nodes() -> Self {
+        let nodes = super::real_cryptonote::FUEGO_NODES.iter()
+            .map(|(host, port)| format!("http://{}:{}", host, port))
+            .collect();
+        Self::new(nodes, DAEMON_INFO_TTL)
+    }
+
+    /// Current daemon info; cached for the TTL, stale cache on failure
+    pub async fn get_info(&self) -> WalletResult<DaemonInfo> {
+        if let Some((info, fetched)) = self.cached.lock_recover().as_ref()
+            && fetched.elapsed() < self.ttl
+        {
+            return Ok(info.clone());
+        }
+
+        let mut failures = Vec::new();
+        for node in &self.nodes {
+            match self.fetch(node).await {
+                Ok(info) => {
+                    *self.cached.lock_recover() = Some((info.clone(), Instant::now()));
+                    return Ok(info);
+                }
+                Err(e) => failures.push(format!("{}: {}", node, e)),
+            }
+        }
+
+        if let Some((info, _)) = self.cached.lock_recover().as_ref() {
+            log::warn!("Daemon unreachable, serving cached info: {}", failures.join("; "));
+            return Ok(DaemonInfo { stale: true, ..info.clone() });
+        }
+        Err(WalletError::NetworkError(format!("No daemon reachable: {}", failures.join("; "))))
+    }
+
+    /// Peers of the node in use, their latency averaged over the calls so far.
+    /// A node that cannot list them gives none and a warning.
+    pub async fn get_peers(&self) -> WalletResult<PeerList> {
+        let node = self.get_info().await?.node;
+        let mut list = fetch_peers(&self.http, &node, REQUEST_TIMEOUT).await?;
+        self.peer_latencies.record(&mut list.peers);
+        Ok(list)
+    }
+
+    /// Ban the peer at `address` on the node in use for `seconds`; 0 lifts the ban
+    pub async fn set_peer_ban(&self, address: &str, seconds: u64) -> WalletResult<()> {
+        let ip = peer_ip(address)?;
+        let node = self.get_info().await?.node;
+        set_ban(&self.http, &node, ip, seconds, REQUEST_TIMEOUT).await
+    }
+
+    /// Rolling latency averages of the peers listed so far
+    pub fn peer_latencies(&self) -> &PeerLatencies {
+        &self.peer_latencies
+    }
+
+    async fn fetch(&self, node: &str) -> WalletResult<DaemonInfo> {
+        let policy = RetryPolicy::network("daemon_getinfo", None).with_max_attempts(NODE_ATTEMPTS);
+        retry_async(&policy, || fetch_info(&self.http, node, REQUEST_TIMEOUT)).await
+    }
+}
+
+/// Outcome of checking a node that is not in use
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NodeTestReport {
+    pub host: String,
+    pub port: u16,
+    /// The node accepted a TCP connection
+    pub reachable: bool,
+    /// Round trip of the `/getinfo` request
+    pub latency_ms: Option<u64>,
+    pub height: Option<u64>,
+    pub version: Option<String>,
+    pub peer_count: Option<u32>,
+    pub error: Option<String>,
+}
+
+/// Check that `host:port` answers as a Fuego daemon, within `timeout` for the
+/// connection and again for the request. Uses its own connection only; the
+/// wallet's node and the shared client cache are left alone.
+pub async fn test_node(host: &str, port: u16, timeout: Duration) -> NodeTestReport {
+    let mut report = NodeTestReport {
+        host: host.to_string(),
+        port,
+        ..Default::default()
+    };
+
+    let probe_host = host.to_string();
+    let probe = tokio::task::spawn_blocking(move || super::real_cryptonote::probe_node(&probe_host, port, timeout)).await;
+    match probe {
+        Ok(Ok(())) => report.reachable = true,
+        Ok(Err(e)) => {
+            report.error = Some(e.to_string());
+            return report;
+        }
+        Err(e) => {
+            report.error = Some(format!("Node probe failed: {}", e));
+            return report;
+        }
+    }
+
+    let node = format!("http://{}:{}", host, port);
+    let started = Instant::now();
+    match fetch_info(&reqwest::Client::new(), &node, timeout).await {
+        Ok(info) => {
+            report.latency_ms = Some(started.elapsed().as_millis() as u64);
+            report.height = Some(info.height);
+            report.version = info.version;
+            report.peer_count = Some(info.peer_count);
+        }
+        Err(e) => report.error = Some(e.to_string()),
+    }
+    report
+}
+
+/// `/getinfo` of the daemon at `node`
+pub(crate) async fn fetch_info(http: &reqwest::Client, node: &str, timeout: Duration) -> WalletResult<DaemonInfo> {
+    let url = format!("{}/getinfo", node.trim_end_matches('/'));
+    let response = http.get(&url).timeout(timeout).send().await
+        .map_err(|e| WalletError::NetworkError(e.to_string()))?;
+    if !response.status().is_success() {
+        return Err(WalletError::NetworkError(format!("HTTP {}", response.status().as_u16())));
+    }
+    let value = response.json::<serde_json::Value>().await
+        .map_err(|e| WalletError::NetworkError(format!("invalid JSON response: {}", e)))?;
+    DaemonInfo::from_getinfo(node, &value)
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use crate::utils::testing::mock_http_server;
+
+    const GETINFO: &str = r#"{"status":"OK","height":964943,"difficulty":52500024,"tx_count":390132,
+        "incoming_connections_count":4,"outgoing_connections_count":8,"last_block_reward":3005769,
+        "block_major_version":9,"block_minor_version":0,"version":"1.9.1","top_block_hash":"abcd",
+        "fee_address":"fire1fee"}"#;
+
+    fn mock_daemon(responses: Vec<(u16, &'static str)>) -> String {
+        mock_http_server(responses).0
+    }
+
+    #[tokio::test]
+    async fn test_getinfo_is_parsed_and_cached() {
+        let node = mock_daemon(vec![(200, GETINFO)]);
+        let client = DaemonClient::new(vec![node], Duration::from_secs(60));
+
+        let info = client.get_info().await.unwrap();
+        assert_eq!(info.height, 964943);
+        assert_eq!(info.peer_count, 12);
+        assert_eq!(info.version.as_deref(), Some("1.9.1"));
+        assert_eq!(info.hash_rate, 52500024.0 / 480.0);
+        assert!(!info.stale);
+
+        // The mock answers only once; a second call must come from the cache
+        assert_eq!(client.get_info().await.unwrap(), info);
+
+        let data = info.to_network_data();
+        for key in ["height", "peer_count", "difficulty", "last_block_reward", "block_major_version",
+                    "block_minor_version", "status", "version", "tx_count", "fee_address"] {
+            assert!(data.get(key).is_some(), "missing key {}", key);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failure_falls_back_to_stale_cache() {
+        let node = mock_daemon(vec![(200, GETINFO), (500, "{}")]);
+        let client = DaemonClient::new(vec![node], Duration::ZERO);
+
+        let fresh = client.get_info().await.unwrap();
+        let stale = client.get_info().await.unwrap();
+        assert!(stale.stale);
+        assert_eq!(stale.height, fresh.height);
+
+        let unreachable = DaemonClient::new(vec![mock_daemon(vec![(200, r#"{"status":"BUSY"}"#)])], Duration::ZERO);
+        let err = unreachable.get_info().await.unwrap_err();
+        assert!(err.to_string().contains("BUSY"));
+    }
+
+    #[tokio::test]
+    async fn test_node_test_reports_height_and_failures() {
+        let node = mock_daemon(vec![(200, GETINFO)]);
+        let (host, port) = node.trim_start_matches("http://").rsplit_once(':').unwrap();
+        let report = test_node(host, port.parse().unwrap(), Duration::from_secs(5)).await;
+        assert!(report.reachable);
+        assert_eq!((report.height, report.version.as_deref(), report.peer_count), (Some(964943), Some("1.9.1"), Some(12)));
+        assert!(report.latency_ms.is_some() && report.error.is_none());
+
+        let closed = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let report = test_node("127.0.0.1", closed, Duration::from_secs(1)).await;
+        assert!(!report.reachable);
+        assert!(report.height.is_none() && report.error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_peers_degrade_on_node_without_peer_rpc() {
+        const NO_METHOD: &str = r#"{"jsonrpc":"2.0","id":"0","error":{"code":-32601,"message":"Method not found"}}"#;
+        let node = mock_daemon(vec![(200, GETINFO), (200, NO_METHOD), (404, "{}"), (200, NO_METHOD)]);
+        let client = DaemonClient::new(vec![node.clone()], Duration::from_secs(60));
+
+        let list = client.get_peers().await.unwrap();
+        assert_eq!(list.node, node);
+        assert!(list.peers.is_empty());
+        assert!(list.warning.unwrap().contains("does not list its peers"));
+
+        let err = client.set_peer_ban("144.76.19.52:10808", 3600).await.unwrap_err();
+        assert!(err.to_string().contains("does not support banning"));
+    }
+
+    #[test]
+    fn test_daemon_fields_are_applied() {
+        let value: serde_json::Value = serde_json::from_str(GETINFO).unwrap();
+        let daemon = DaemonInfo::from_getinfo("node", &value).unwrap();
+        let mut info: AdvancedNetworkInfo = serde_json::from_value(serde_json::json!({
+            "is_connected": true, "peer_count": 1, "sync_height": 10, "network_height": 20,
+            "is_syncing": true, "connection_type": "remote", "last_sync_time": null, "sync_speed": 0.0,
+            "estimated_sync_time": null, "daemon_version": null, "daemon_rpc_version": null,
+            "daemon_uptime": null, "difficulty": 0, "hash_rate": 0.0, "block_reward": 0, "block_time": 0,
+            "last_block_hash": null, "last_block_timestamp": null, "network_type": "mainnet",
+            "bootstrap_daemon_address": null, "bootstrap_daemon_port": null,
+            "bootstrap_daemon_login": null, "bootstrap_daemon_password": null
+        })).unwrap();
+
+        daemon.apply_to(&mut info);
+        assert_eq!(info.daemon_version.as_deref(), Some("1.9.1"));
+        assert_eq!(info.difficulty, 52500024);
+        assert_eq!(info.block_reward, 3005769);
+        assert_eq!(info.block_time, TARGET_BLOCK_TIME);
+        assert_eq!(info.network_height, 964943);
+        assert_eq!(info.last_block_hash.as_deref(), Some("abcd"));
+    }
+}
@@ -0,0 +1,282 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Demo mode
+//!
+//! Runs the app on the shared mock wallet, filled with a generated but
+//! realistic history, so every screen can be shown without a wallet file or a
+//! node: a year of transactions, term deposits in each state and a sync that
+//! catches up over the first two minutes. Everything is generated from a fixed
+//! seed, so each run shows the same wallet. Sends go through the mock, which
+//! takes them from the balance and lists them as pending.
+//!
+//! Enabled at start by `--demo`, `FUEGO_DEMO=1` or the `wallet.demo_mode`
+//! setting. In demo mode no wallet file on disk is opened.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use crate::crypto::backend::{select_backend, shared_mock, BackendKind, MockWalletState, SyncScript};
+use crate::crypto::daemon::TARGET_BLOCK_TIME;
+use crate::crypto::real_cryptonote::{DepositInfo, TransactionInfo, UnspentOutput};
+use crate::utils::error::{WalletError, WalletResult};
+use crate::wallet::payment_uri::ATOMIC_UNITS_PER_XFG as COIN;
+
+/// Command line flag enabling demo mode
+pub const DEMO_ARG: &str = "--demo";
+/// Environment variable enabling demo mode when `1` or `true`
+pub const DEMO_ENV: &str = "FUEGO_DEMO";
+
+/// Transactions in the demo history
+pub const DEMO_TRANSACTION_COUNT: usize = 200;
+/// Share of the network height the demo wallet has synced at start
+pub const DEMO_SYNC_START_PERCENT: u64 = 80;
+/// How long the demo wallet takes to catch up
+pub const DEMO_SYNC_DURATION: Duration = Duration::from_secs(120);
+
+const DEMO_SEED: u64 = 0x4655_4547_4f44_454d;
+const DEMO_NETWORK_HEIGHT: u64 = 1_000_000;
+/// Unix time of the network height; the history spans the year before it
+const DEMO_NOW: u64 = 1_760_000_000;
+const DEMO_HISTORY_SECS: u64 = 365 * 24 * 60 * 60;
+const DEMO_FEE: u64 = 10_000;
+const DEMO_OUTPUT_COUNT: usize = 12;
+const BLOCKS_PER_DAY: u64 = 24 * 60 * 60 / TARGET_BLOCK_TIME;
+
+static DEMO_MODE: AtomicBool = AtomicBool::new(false);
+
+/// Whether the app runs in demo mode
+pub fn is_enabled() -> bool {
+    DEMO_MODE.load(Ordering::SeqCst)
+}
+
+/// Whether the command line `args` or the `FUEGO_DEMO` value `env` ask for demo mode
+pub fn requested<I>(args: I, env: Option<&str>) -> bool
+where
+    I: IntoIterator<Item = String>,
+{
+    args.into_iter().any(|arg| arg == DEMO_ARG)
+        || env.is_some_and(|value| value == "1" || value.eq_ignore_ascii_case("true"))
+}
+
+/// Switch to demo mode: fill the shared mock wallet with the demo data and
+/// run wallets on it from now on. The sync starts catching up at `now`.
+pub fn enable(now: Instant) {
+    if DEMO_MODE.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    *shared_mock().state() = demo_state(now);
+    select_backend(BackendKind::Mock);
+    log::info!("Demo mode: running on generated wallet data");
+}
+
+/// The backend to run on when `configured` is selected; always the mock in demo mode
+pub fn backend_for(configured: BackendKind) -> BackendKind {
+    if is_enabled() { BackendKind::Mock } else { configured }
+}
+
+/// Refuse to open `file_path` in demo mode when it is a file on disk
+pub fn check_wallet_path(file_path: &str) -> WalletResult<()> {
+    if is_enabled() && Path::new(file_path).exists() {
+        return Err(WalletError::WalletOpenFailed(format!(
+            "{} is a real wallet file; restart without demo mode to open it",
+            file_path
+        )));
+    }
+    Ok(())
+}
+
+/// SplitMix64: small, fast and the same on every platform
+struct DemoRng(u64);
+
+impl DemoRng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `low..high`
+    fn range(&mut self, low: u64, high: u64) -> u64 {
+        low + self.next() % (high - low)
+    }
+
+    fn hash(&mut self) -> String {
+        (0..4).map(|_| format!("{:016x}", self.next())).collect()
+    }
+
+    fn address(&mut self) -> String {
+        const BASE58: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+        let body: String = (0..94).map(|_| BASE58[self.range(0, BASE58.len() as u64) as usize] as char).collect();
+        format!("fire1{}", body)
+    }
+}
+
+fn height_at(timestamp: u64) -> u64 {
+    DEMO_NETWORK_HEIGHT - (DEMO_NOW - timestamp) / TARGET_BLOCK_TIME
+}
+
+fn rfc3339(timestamp: u64) -> String {
+    chrono::DateTime::from_timestamp(timestamp as i64, 0).map(|t| t.to_rfc3339()).unwrap_or_default()
+}
+
+/// The demo wallet, its sync starting to catch up at `sync_started`
+pub fn demo_state(sync_started: Instant) -> MockWalletState {
+    let mut rng = DemoRng(DEMO_SEED);
+    let mut state = MockWalletState::default();
+    let counterparties: Vec<String> = (0..8).map(|_| rng.address()).collect();
+
+    let mut timestamps: Vec<u64> = (0..DEMO_TRANSACTION_COUNT)
+        .map(|_| rng.range(DEMO_NOW - DEMO_HISTORY_SECS, DEMO_NOW))
+        .collect();
+    timestamps.sort_unstable();
+
+    let mut spendable: u64 = 0;
+    for timestamp in timestamps {
+        let counterparty = counterparties[rng.range(0, counterparties.len() as u64) as usize].clone();
+        // Mostly incoming, and never sending more than the wallet holds
+        let outgoing = rng.range(0, 100) < 40;
+        let send = rng.range(COIN / 2, 100 * COIN);
+        let (amount, fee, sources, destinations) = if outgoing && send + DEMO_FEE <= spendable {
+            spendable -= send + DEMO_FEE;
+            (-(send as i64), DEMO_FEE, vec![state.address.clone()], vec![counterparty])
+        } else {
+            let received = rng.range(COIN, 500 * COIN);
+            spendable += received;
+            (received as i64, DEMO_FEE, vec![counterparty], vec![state.address.clone()])
+        };
+        let height = height_at(timestamp);
+        let hash = rng.hash();
+        state.transactions.push(TransactionInfo {
+            id: hash.clone(),
+            hash,
+            amount,
+            fee,
+            height,
+            timestamp,
+            confirmations: (DEMO_NETWORK_HEIGHT - height) as u32,
+            is_confirmed: true,
+            is_pending: false,
+            payment_id: None,
+            destination_addresses: destinations,
+            source_addresses: sources,
+            unlock_time: None,
+            extra: None,
+        });
+    }
+
+    // One deposit of each state: still locked, matured, and withdrawn
+    let mut locked_amount = 0;
+    for (status, started_days_ago, term_days) in [("locked", 30, 90), ("unlocked", 200, 180), ("spent", 340, 30)] {
+        let amount = rng.range(10, 200) * COIN;
+        let creating_time = DEMO_NOW - started_days_ago * 24 * 60 * 60;
+        let creating_height = height_at(creating_time);
+        let unlock_height = creating_height + u64::from(term_days) * BLOCKS_PER_DAY;
+        let unlock_time = creating_time + u64::from(term_days) * 24 * 60 * 60;
+        let spent = status == "spent";
+        let spending_time = unlock_time + 2 * 24 * 60 * 60;
+        if status == "locked" {
+            locked_amount += amount;
+        }
+        state.deposits.push(DepositInfo {
+            id: format!("demo-deposit-{}", state.deposits.len() + 1),
+            amount,
+            interest: amount / 100 * u64::from(term_days) / 30,
+            term: term_days,
+            rate: 0.01 * f64::from(term_days) / 30.0,
+            status: status.to_string(),
+            unlock_height,
+            unlock_time: Some(rfc3339(unlock_time)),
+            creating_transaction_hash: rng.hash(),
+            creating_height,
+            creating_time: rfc3339(creating_time),
+            spending_transaction_hash: spent.then(|| rng.hash()),
+            spending_height: spent.then(|| height_at(spending_time)),
+            spending_time: spent.then(|| rfc3339(spending_time)),
+            deposit_type: "term".to_string(),
+        });
+    }
+
+    // The spendable balance split over outputs from the latest blocks
+    let mut left = spendable;
+    for index in 0..DEMO_OUTPUT_COUNT {
+        let amount = if index + 1 == DEMO_OUTPUT_COUNT { left } else { rng.range(0, left / 4 + 1) };
+        left -= amount;
+        let block_height = DEMO_NETWORK_HEIGHT - rng.range(100, 50_000);
+        state.outputs.push(UnspentOutput {
+            amount,
+            global_index: rng.range(0, 10_000_000),
+            key_image: rng.hash(),
+            block_height,
+            is_locked: false,
+            tx_hash: None,
+        });
+    }
+
+    state.is_open = true;
+    state.balance = spendable + locked_amount;
+    state.unlocked_balance = spendable;
+    state.fee = DEMO_FEE;
+    state.is_connected = true;
+    state.peer_count = 8;
+    state.network_height = DEMO_NETWORK_HEIGHT;
+    state.sync_height = DEMO_NETWORK_HEIGHT * DEMO_SYNC_START_PERCENT / 100;
+    state.sync_script = Some(SyncScript {
+        started: sync_started,
+        from_height: state.sync_height,
+        duration: DEMO_SYNC_DURATION,
+    });
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_demo_dataset_is_stable() {
+        let started = Instant::now();
+        let state = demo_state(started);
+
+        assert_eq!(state.transactions.len(), DEMO_TRANSACTION_COUNT);
+        assert!(state.transactions.windows(2).all(|pair| pair[0].timestamp <= pair[1].timestamp));
+        assert!(state.transactions[0].timestamp >= DEMO_NOW - DEMO_HISTORY_SECS);
+        let statuses: Vec<&str> = state.deposits.iter().map(|d| d.status.as_str()).collect();
+        assert_eq!(statuses, vec!["locked", "unlocked", "spent"]);
+        assert_eq!(state.outputs.iter().map(|o| o.amount).sum::<u64>(), state.unlocked_balance);
+        let net: i64 = state.transactions.iter().map(|t| t.amount - if t.amount < 0 { t.fee as i64 } else { 0 }).sum();
+        assert_eq!(net as u64, state.unlocked_balance);
+
+        // Snapshot of the generated data: changing the generator changes these
+        assert_eq!(state.transactions[0].hash, "b596ead5d37356040f9bc39011cafd91d18ff296a11b0716a8939f57631ab461");
+        assert_eq!(state.transactions[DEMO_TRANSACTION_COUNT - 1].amount, -551_419_342);
+        assert_eq!((state.balance, state.unlocked_balance), (239_387_349_037, 238_957_349_037));
+        let snapshot = serde_json::to_string(&(&state.transactions, &state.deposits, &state.outputs)).unwrap();
+        assert_eq!(blake3::hash(snapshot.as_bytes()).to_hex().as_str(), "ec3d45f6f28b0cf91c6f3cabdf487ee7c369671912c6a75fd07745d2042e49ba");
+
+        // The same seed gives the same wallet
+        let again = demo_state(started);
+        assert_eq!(serde_json::to_string(&(&again.transactions, &again.deposits, &again.outputs)).unwrap(), snapshot);
+    }
+
+    #[test]
+    fn test_demo_sync_catches_up_over_two_minutes() {
+        let started = Instant::now();
+        let script = demo_state(started).sync_script.unwrap();
+        assert_eq!(script.height_at(DEMO_NETWORK_HEIGHT, started), 800_000);
+        assert_eq!(script.height_at(DEMO_NETWORK_HEIGHT, started + Duration::from_secs(60)), 900_000);
+        assert_eq!(script.height_at(DEMO_NETWORK_HEIGHT, started + DEMO_SYNC_DURATION), DEMO_NETWORK_HEIGHT);
+        assert_eq!(script.height_at(DEMO_NETWORK_HEIGHT, started + Duration::from_secs(600)), DEMO_NETWORK_HEIGHT);
+    }
+
+    #[test]
+    fn test_demo_mode_is_requested_by_flag_or_environment() {
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert!(requested(args(&["--demo"]), None));
+        assert!(requested(args(&[]), Some("1")));
+        assert!(requested(args(&[]), Some("TRUE")));
+        assert!(!requested(args(&["--headless"]), Some("0")));
+    }
+}
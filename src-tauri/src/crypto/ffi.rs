@@ -5,10 +5,14 @@
 //! 
 //! This module contains FFI bindings to the existing CryptoNote C++ code.
 
-use std::ffi::{CStr, CString};
+use std::ffi::CString;
 use std::os::raw::{c_char, c_void};
 use std::ptr;
-use crate::utils::error::WalletResult;
+use std::time::Duration;
+use crate::crypto::backend::{unsupported, WalletBackend};
+use crate::crypto::ffi_owned::{fixed_str, OwnedCStr};
+use crate::crypto::real_cryptonote::{DepositInfo, FeeBreakdown, NetworkInfo, SyncProgress, TransactionInfo, WalletInfo};
+use crate::utils::error::{WalletError, WalletResult};
 
 // FFI function signatures for CryptoNote wallet operations
 unsafe extern "C" {
@@ -180,18 +184,17 @@ impl CryptoNoteFFI {
             return Err(crate::utils::error::WalletError::WalletNotOpen);
         }
         
-        let mut buffer = vec![0u8; 256];
+        let mut buffer = [0 as c_char; 256];
         let success = unsafe {
             crypto_note_wallet_get_address(
                 self.wallet_ptr,
-                buffer.as_mut_ptr() as *mut c_char,
+                buffer.as_mut_ptr(),
                 buffer.len(),
             )
         };
         
         if success {
-            let c_str = unsafe { CStr::from_ptr(buffer.as_ptr() as *const c_char) };
-            Ok(c_str.to_string_lossy().to_string())
+            Ok(fixed_str(&buffer))
         } else {
             Err(crate::utils::error::WalletError::Generic(
                 "Failed to get wallet address".to_string(),
@@ -227,16 +230,12 @@ impl CryptoNoteFFI {
             )
         };
         
-        if tx_ptr.is_null() {
+        let Some(tx_hash) = (unsafe { OwnedCStr::from_raw(tx_ptr, crypto_note_wallet_free_string) }) else {
             return Err(crate::utils::error::WalletError::TransactionFailed(
                 "Failed to send transaction".to_string(),
             ));
-        }
-        
-        // Extract transaction hash as C string and free
-        let c_hash = unsafe { CStr::from_ptr(tx_ptr as *const c_char).to_string_lossy().to_string() };
-        unsafe { crypto_note_wallet_free_string(tx_ptr as *mut c_char); }
-        Ok(c_hash)
+        };
+        Ok(tx_hash.to_string_lossy())
     }
 }
 
@@ -246,5 +245,133 @@ impl Drop for CryptoNoteFFI {
     }
 }
 
+impl Default for CryptoNoteFFI {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// SAFETY: the wallet handle is only used through `&self`/`&mut self` of its
+// single owner; moving the owner to another thread does not share it.
+unsafe impl Send for CryptoNoteFFI {}
+
+/// The legacy bindings only cover the basic wallet operations; everything
+/// else reports that it is unsupported
+impl WalletBackend for CryptoNoteFFI {
+    fn create_wallet(
+        &mut self,
+        password: &str,
+        file_path: &str,
+        seed_phrase: Option<&str>,
+        restore_height: u64,
+    ) -> WalletResult<()> {
+        CryptoNoteFFI::create_wallet(self, password, file_path, seed_phrase, restore_height)
+    }
+
+    fn open_wallet(&mut self, file_path: &str, password: &str) -> WalletResult<()> {
+        CryptoNoteFFI::open_wallet(self, file_path, password)
+    }
+
+    fn close_wallet(&mut self) {
+        CryptoNoteFFI::close_wallet(self)
+    }
+
+    fn is_open(&self) -> bool {
+        CryptoNoteFFI::is_open(self)
+    }
+
+    fn get_balance(&self) -> WalletResult<u64> {
+        CryptoNoteFFI::get_balance(self)
+    }
+
+    fn get_unlocked_balance(&self) -> WalletResult<u64> {
+        CryptoNoteFFI::get_unlocked_balance(self)
+    }
+
+    fn get_address(&self) -> WalletResult<String> {
+        CryptoNoteFFI::get_address(self)
+    }
+
+    fn send_transaction(
+        &self,
+        address: &str,
+        amount: u64,
+        payment_id: Option<&str>,
+        mixin: u64,
+    ) -> WalletResult<String> {
+        CryptoNoteFFI::send_transaction(self, address, amount, payment_id, mixin)
+    }
+
+    fn send_many(&self, _outputs: &[(String, u64)], _mixin: u64) -> WalletResult<String> {
+        Err(unsupported("send_many"))
+    }
+
+    /// The bindings have no timeout; `timeout` is ignored
+    fn connect_to_node(&mut self, address: &str, port: u16, _timeout: Duration) -> WalletResult<()> {
+        if self.wallet_ptr.is_null() {
+            return Err(WalletError::WalletNotOpen);
+        }
+
+        let address_c = CString::new(address)?;
+        if unsafe { crypto_note_wallet_connect_node(self.wallet_ptr, address_c.as_ptr(), port) } {
+            Ok(())
+        } else {
+            Err(WalletError::NetworkError(format!("Failed to connect to {}:{}", address, port)))
+        }
+    }
+
+    fn get_network_status(&self) -> WalletResult<serde_json::Value> {
+        Err(unsupported("get_network_status"))
+    }
+
+    fn get_deposits(&self) -> WalletResult<Vec<DepositInfo>> {
+        Err(unsupported("get_deposits"))
+    }
+
+    fn get_wallet_info(&self) -> WalletResult<WalletInfo> {
+        Err(unsupported("get_wallet_info"))
+    }
+
+    fn get_network_info(&self) -> WalletResult<NetworkInfo> {
+        Err(unsupported("get_network_info"))
+    }
+
+    fn refresh(&mut self) -> WalletResult<()> {
+        Err(unsupported("refresh"))
+    }
+
+    fn rescan_blockchain(&mut self, _start_height: u64) -> WalletResult<()> {
+        Err(unsupported("rescan_blockchain"))
+    }
+
+    fn get_transaction_by_hash(&self, _tx_hash: &str) -> WalletResult<TransactionInfo> {
+        Err(unsupported("get_transaction_by_hash"))
+    }
+
+    fn estimate_transaction_fee(&self, _address: &str, _amount: u64, _mixin: u64) -> WalletResult<FeeBreakdown> {
+        Err(unsupported("estimate_transaction_fee"))
+    }
+
+    fn disconnect(&mut self) -> WalletResult<()> {
+        Ok(())
+    }
+
+    fn create_deposit(&self, _amount: u64, _term: u32) -> WalletResult<String> {
+        Err(unsupported("create_deposit"))
+    }
+
+    fn withdraw_deposit(&self, _deposit_id: &str) -> WalletResult<String> {
+        Err(unsupported("withdraw_deposit"))
+    }
+
+    fn get_transaction_history(&self, _limit: u64, _offset: u64) -> WalletResult<Vec<TransactionInfo>> {
+        Err(unsupported("get_transaction_history"))
+    }
+
+    fn get_sync_progress(&self) -> WalletResult<SyncProgress> {
+        Err(unsupported("get_sync_progress"))
+    }
+}
+
 // For now, we'll use the mock implementation from the C++ code
 // This will be replaced with real FFI calls when the C++ integration is complete
@@ -0,0 +1,194 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Owned memory returned by the native wallet
+//!
+//! Strings and structs the wallet library allocates have to go back through
+//! the library's own free function, exactly once. `OwnedCStr` and `FfiBox`
+//! take over such a pointer right after the call, refuse null, and release it
+//! when dropped, so no call site frees anything by hand.
+
+use std::ffi::CStr;
+use std::ops::Deref;
+use std::os::raw::c_char;
+use std::ptr::NonNull;
+
+/// Function releasing a string allocated by the native library
+pub type FreeCStr = unsafe extern "C" fn(*mut c_char);
+
+/// A C string allocated by the native library
+pub struct OwnedCStr {
+    ptr: NonNull<c_char>,
+    free: FreeCStr,
+}
+
+impl OwnedCStr {
+    /// Take ownership of `ptr`, which `free` releases on drop. `None` if
+    /// `ptr` is null.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be null or a NUL-terminated string that nothing else frees
+    /// and that `free` is the matching deallocator for.
+    pub unsafe fn from_raw(ptr: *mut c_char, free: FreeCStr) -> Option<Self> {
+        NonNull::new(ptr).map(|ptr| Self { ptr, free })
+    }
+
+    pub fn as_c_str(&self) -> &CStr {
+        // SAFETY: `from_raw` requires a NUL-terminated string that lives until drop
+        unsafe { CStr::from_ptr(self.ptr.as_ptr()) }
+    }
+
+    pub fn to_string_lossy(&self) -> String {
+        self.as_c_str().to_string_lossy().into_owned()
+    }
+}
+
+impl Drop for OwnedCStr {
+    fn drop(&mut self) {
+        // SAFETY: `from_raw` requires `free` to match and nobody else to free the string
+        unsafe { (self.free)(self.ptr.as_ptr()) }
+    }
+}
+
+/// A `#[repr(C)]` struct the native library allocates, with the function
+/// releasing it
+///
+/// # Safety
+///
+/// `Self` must have the layout of the C struct, and `free` must release
+/// pointers to it returned by the library.
+pub unsafe trait FfiFree {
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by the native library and not be freed yet.
+    unsafe fn free(ptr: *mut Self);
+}
+
+/// A struct allocated by the native library, released through `T::free`
+pub struct FfiBox<T: FfiFree> {
+    ptr: NonNull<T>,
+}
+
+impl<T: FfiFree> FfiBox<T> {
+    /// Take ownership of `ptr`. `None` if `ptr` is null.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be null or point to an initialized `T` allocated by the
+    /// native library that nothing else frees.
+    pub unsafe fn from_raw(ptr: *mut T) -> Option<Self> {
+        NonNull::new(ptr).map(|ptr| Self { ptr })
+    }
+}
+
+impl<T: FfiFree> Deref for FfiBox<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: `from_raw` requires an initialized `T` that lives until drop
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<T: FfiFree> Drop for FfiBox<T> {
+    fn drop(&mut self) {
+        // SAFETY: `from_raw` requires that nobody else frees the struct
+        unsafe { T::free(self.ptr.as_ptr()) }
+    }
+}
+
+/// Contents of a fixed-size string field of an FFI struct, up to the first
+/// NUL or the end of the field when the C side left no terminator
+pub fn fixed_str(field: &[c_char]) -> String {
+    let bytes: Vec<u8> = field.iter().take_while(|&&c| c != 0).map(|&c| c as u8).collect();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// `fixed_str`, or `None` for an empty field
+pub fn fixed_str_opt(field: &[c_char]) -> Option<String> {
+    Some(fixed_str(field)).filter(|s| !s.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static STRINGS_FREED: AtomicUsize = AtomicUsize::new(0);
+    static STRUCTS_FREED: AtomicUsize = AtomicUsize::new(0);
+
+    unsafe extern "C" fn free_test_string(s: *mut c_char) {
+        STRINGS_FREED.fetch_add(1, Ordering::SeqCst);
+        drop(unsafe { CString::from_raw(s) });
+    }
+
+    #[repr(C)]
+    struct TestInfoFFI {
+        height: u64,
+        label: [c_char; 8],
+    }
+
+    unsafe impl FfiFree for TestInfoFFI {
+        unsafe fn free(ptr: *mut Self) {
+            STRUCTS_FREED.fetch_add(1, Ordering::SeqCst);
+            drop(unsafe { Box::from_raw(ptr) });
+        }
+    }
+
+    #[test]
+    fn test_wrappers_free_exactly_once() {
+        assert!(unsafe { OwnedCStr::from_raw(std::ptr::null_mut(), free_test_string) }.is_none());
+        assert!(unsafe { FfiBox::<TestInfoFFI>::from_raw(std::ptr::null_mut()) }.is_none());
+
+        let raw = CString::new("fire1abc").unwrap().into_raw();
+        let s = unsafe { OwnedCStr::from_raw(raw, free_test_string) }.unwrap();
+        assert_eq!(s.to_string_lossy(), "fire1abc");
+        drop(s);
+        assert_eq!(STRINGS_FREED.load(Ordering::SeqCst), 1);
+
+        // No terminator in the field: read up to its end, not past it
+        let label = [b'a' as c_char; 8];
+        let raw = Box::into_raw(Box::new(TestInfoFFI { height: 7, label }));
+        let info = unsafe { FfiBox::from_raw(raw) }.unwrap();
+        assert_eq!((info.height, fixed_str(&info.label)), (7, "aaaaaaaa".to_string()));
+        drop(info);
+        assert_eq!(STRUCTS_FREED.load(Ordering::SeqCst), 1);
+
+        assert_eq!(fixed_str_opt(&[0; 4]), None);
+        assert_eq!(fixed_str_opt(&[b'x' as c_char, 0, b'y' as c_char]), Some("x".to_string()));
+    }
+
+    /// Drives every wrapped return type through the stub library, so a run
+    /// under miri catches leaks, double frees and reads past a field
+    #[cfg(feature = "mock-ffi")]
+    #[test]
+    fn test_wrappers_over_stub_library() {
+        use crate::crypto::backend::WalletBackend;
+        use crate::crypto::real_cryptonote::{FfiWalletBackend, RealCryptoNoteWallet};
+
+        // The stubs keep wallets in memory, nothing is written to this path
+        let mut backend = FfiWalletBackend::new();
+        backend.create_wallet("test_password", "stub.wallet", None, 0).unwrap();
+        let address = backend.get_address().unwrap();
+        assert_eq!(backend.get_wallet_info().unwrap().address, address);
+        assert_eq!(backend.get_network_status().unwrap()["is_connected"], false);
+        assert!(!backend.get_network_info().unwrap().connection_type.is_empty());
+        assert!(!backend.get_mining_info().unwrap().is_mining);
+        assert!(backend.get_deposit_limits().unwrap().supported);
+        assert_eq!(backend.get_sync_progress().unwrap().current_height, 0);
+        assert!(!backend.get_sync_status_json().unwrap().is_empty());
+        assert!(backend.get_view_key().is_err());
+
+        let hash = backend.send_transaction(&address, 1_000, None, 0).unwrap();
+        assert_eq!(backend.get_transaction_by_hash(&hash).unwrap().hash, hash);
+        assert_eq!(backend.get_transaction_history(10, 0).unwrap().len(), 1);
+        let proof = backend.get_tx_proof(&hash, &address, Some("receipt")).unwrap();
+        assert!(backend.check_tx_proof(&hash, &address, Some("receipt"), &proof).unwrap());
+        backend.close_wallet();
+
+        let seed = RealCryptoNoteWallet::generate_seed_phrase().unwrap();
+        assert!(RealCryptoNoteWallet::address_from_seed(&seed).unwrap().starts_with("fire"));
+    }
+}
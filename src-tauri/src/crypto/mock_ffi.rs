@@ -0,0 +1,1172 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Stand-ins for the native wallet libraries
+//!
+//! With the `mock-ffi` feature `build.rs` compiles neither
+//! `fuego_wallet_real.cpp` nor `crypto_note_ffi.cpp`, and this module exports
+//! every symbol the `extern "C"` blocks of `real_cryptonote` and `ffi` link
+//! against instead. Wallets live in memory until they are closed and answer
+//! with fixed data, so results are the same on every run. Unlike the C++
+//! mocks every handle has its own wallet, which keeps tests running in
+//! parallel apart.
+//!
+//! Strings and structs returned here are allocated by Rust and must only be
+//! released through the matching `*_free_*` stub, which the bindings already do.
+
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_void};
+use std::ptr;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use crate::crypto::daemon::TARGET_BLOCK_TIME;
+use crate::crypto::real_cryptonote::{
+    estimated_transaction_size, AbiSizesFFI, BlockInfoFFI, FeeBreakdown, MiningInfoFFI, NetworkInfoFFI, NetworkStatusFFI,
+    SyncProgress, TransactionInfoFFI, WalletInfoFFI, TX_PROOF_BODY_LEN, TX_PROOF_PREFIX,
+};
+use crate::utils::locks::MutexExt;
+
+const ADDRESS: &str = "fire1jNwRRUYGENanfBwVhehZXVcQVFx3dH3D3Z7UNC17FePBr27DDwctyL2ePwDPz4fypwpNQpfXbp6wavubvSn6ToisC5NUy";
+const LEGACY_ADDRESS: &str = "FUEGO1234567890abcdef";
+/// Balance a created or opened wallet starts with, all of it unlocked
+const STARTING_BALANCE: u64 = 1_000_000_000;
+/// Flat fee of every transaction, as charged by the C++ mocks
+const FEE: u64 = 1_000_000;
+/// Chain height the stub node reports
+const NETWORK_HEIGHT: u64 = 850_000;
+const GENESIS_TIMESTAMP: u64 = 1_500_000_000;
+const BLOCK_REWARD: u64 = 60_000_000;
+const CONNECTION_TYPE: &str = "Fuego Network (XFG) - mock";
+
+/// Words of the stub seed phrases, the same as in `fuego_wallet_real.cpp`
+const SEED_WORDS: [&str; 24] = [
+    "abandon", "ability", "able", "about", "above", "absent", "absorb", "abstract",
+    "absurd", "abuse", "access", "accident", "account", "accuse", "achieve", "acid",
+    "acoustic", "acquire", "across", "action", "actor", "actress", "actual", "adapt",
+];
+
+const BASE58: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Returned where the bindings only check for null and never read or free
+static OPAQUE: u8 = 0;
+
+struct StubTransaction {
+    id: String,
+    hash: String,
+    amount: i64,
+    fee: u64,
+    height: u64,
+    destination: String,
+    payment_id: String,
+}
+
+struct StubWallet {
+    address: String,
+    balance: u64,
+    unlocked_balance: u64,
+    is_connected: bool,
+    sync_height: u64,
+    transactions: Vec<StubTransaction>,
+    deposit_count: u64,
+    /// Addresses created besides the primary one
+    addresses: Vec<String>,
+    /// Labels of all addresses, the primary one included
+    labels: Vec<(String, String)>,
+    address_book: Vec<String>,
+    seed_phrase: Option<String>,
+    view_key: String,
+    spend_key: String,
+    is_mining: bool,
+    threads: u32,
+    pool_address: String,
+    worker_name: String,
+}
+
+impl StubWallet {
+    fn open(address: &str) -> Self {
+        Self {
+            address: address.to_string(),
+            balance: STARTING_BALANCE,
+            unlocked_balance: STARTING_BALANCE,
+            is_connected: false,
+            sync_height: 0,
+            transactions: Vec::new(),
+            deposit_count: 0,
+            addresses: Vec::new(),
+            labels: Vec::new(),
+            address_book: Vec::new(),
+            seed_phrase: None,
+            view_key: String::new(),
+            spend_key: String::new(),
+            is_mining: false,
+            threads: 0,
+            pool_address: String::new(),
+            worker_name: String::new(),
+        }
+    }
+
+    /// Charge `amount` plus the fee and record the transaction
+    fn spend(&mut self, destination: &str, amount: u64, payment_id: &str) -> Option<String> {
+        let total = amount.checked_add(FEE)?;
+        if total > self.unlocked_balance {
+            return None;
+        }
+        self.balance -= total;
+        self.unlocked_balance -= total;
+        let index = self.transactions.len() as u64;
+        let hash = digest(&format!("tx {}", index));
+        self.transactions.push(StubTransaction {
+            id: format!("stub_tx_{}", index),
+            hash: hash.clone(),
+            amount: -(amount as i64),
+            fee: FEE,
+            height: 0,
+            destination: destination.to_string(),
+            payment_id: payment_id.to_string(),
+        });
+        Some(hash)
+    }
+
+    fn transaction_info(&self, tx: &StubTransaction) -> *mut TransactionInfoFFI {
+        // SAFETY: every field of the struct is valid when zeroed
+        let mut info: TransactionInfoFFI = unsafe { std::mem::zeroed() };
+        fill(&mut info.id, &tx.id);
+        fill(&mut info.hash, &tx.hash);
+        info.amount = tx.amount;
+        info.fee = tx.fee;
+        info.height = tx.height;
+        info.timestamp = block_timestamp(self.sync_height);
+        info.is_pending = tx.height == 0;
+        fill(&mut info.payment_id, &tx.payment_id);
+        fill(&mut info.destination_addresses, &tx.destination);
+        fill(&mut info.source_addresses, &self.address);
+        Box::into_raw(Box::new(info))
+    }
+
+    fn network_info(&self) -> NetworkInfoFFI {
+        // SAFETY: every field of the struct is valid when zeroed
+        let mut info: NetworkInfoFFI = unsafe { std::mem::zeroed() };
+        info.is_connected = self.is_connected;
+        info.peer_count = if self.is_connected { 8 } else { 0 };
+        info.sync_height = self.sync_height;
+        info.network_height = if self.is_connected { NETWORK_HEIGHT } else { 0 };
+        info.is_syncing = self.is_connected && self.sync_height < NETWORK_HEIGHT;
+        fill(&mut info.connection_type, CONNECTION_TYPE);
+        info.last_sync_time = block_timestamp(self.sync_height);
+        info
+    }
+
+    fn label(&self, address: &str) -> String {
+        self.labels.iter().find(|(a, _)| a == address).map(|(_, label)| label.clone()).unwrap_or_default()
+    }
+}
+
+/// Open wallets by handle; handles are never reused
+static WALLETS: Mutex<BTreeMap<usize, StubWallet>> = Mutex::new(BTreeMap::new());
+static NEXT_HANDLE: AtomicUsize = AtomicUsize::new(1);
+
+/// Keep `wallet` open under a new handle
+fn register(wallet: StubWallet) -> *mut c_void {
+    let handle = NEXT_HANDLE.fetch_add(1, Ordering::Relaxed);
+    WALLETS.lock_recover().insert(handle, wallet);
+    handle as *mut c_void
+}
+
+/// Run `f` on the open wallet behind `handle`, or return `default`
+fn with_wallet<T>(handle: *mut c_void, default: T, f: impl FnOnce(&mut StubWallet) -> T) -> T {
+    let mut wallets = WALLETS.lock_recover();
+    wallets.get_mut(&(handle as usize)).map(f).unwrap_or(default)
+}
+
+/// Forget the wallet behind `handle`
+fn close(handle: *mut c_void) {
+    WALLETS.lock_recover().remove(&(handle as usize));
+}
+
+/// `ptr` as a string; `None` for null
+unsafe fn arg(ptr: *const c_char) -> Option<String> {
+    // SAFETY: the bindings only pass null or NUL-terminated strings
+    (!ptr.is_null()).then(|| unsafe { CStr::from_ptr(ptr) }.to_string_lossy().into_owned())
+}
+
+fn c_string(s: &str) -> *mut c_char {
+    CString::new(s).map(CString::into_raw).unwrap_or(ptr::null_mut())
+}
+
+unsafe fn free_c_string(s: *mut c_char) {
+    if !s.is_null() {
+        // SAFETY: the string was handed out by `c_string`
+        drop(unsafe { CString::from_raw(s) });
+    }
+}
+
+unsafe fn free_box<T>(ptr: *mut T) {
+    if !ptr.is_null() {
+        // SAFETY: the pointer was handed out by `Box::into_raw`
+        drop(unsafe { Box::from_raw(ptr) });
+    }
+}
+
+/// Copy `s` into the fixed-size C string `buffer`, truncating it
+fn fill(buffer: &mut [c_char], s: &str) {
+    let len = s.len().min(buffer.len().saturating_sub(1));
+    for (slot, byte) in buffer.iter_mut().zip(&s.as_bytes()[..len]) {
+        *slot = *byte as c_char;
+    }
+    if let Some(end) = buffer.get_mut(len) {
+        *end = 0;
+    }
+}
+
+fn digest(input: &str) -> String {
+    blake3::hash(input.as_bytes()).to_hex().to_string()
+}
+
+/// A well-formed address derived from `input`
+fn derived_address(input: &str) -> String {
+    let mut bytes = [0u8; 95];
+    blake3::Hasher::new().update(input.as_bytes()).finalize_xof().fill(&mut bytes);
+    let body: String = bytes.iter().map(|b| BASE58[*b as usize % BASE58.len()] as char).collect();
+    format!("fire{}", body)
+}
+
+fn block_timestamp(height: u64) -> u64 {
+    GENESIS_TIMESTAMP + height * TARGET_BLOCK_TIME
+}
+
+fn seed_word_count_valid(seed_phrase: &str) -> bool {
+    matches!(seed_phrase.split_whitespace().count(), 12 | 18 | 24)
+}
+
+// ===== fuego_wallet_real =====
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_create(
+    _password: *const c_char,
+    _file_path: *const c_char,
+    seed_phrase: *const c_char,
+    _restore_height: u64,
+) -> *mut c_void {
+    let mut wallet = StubWallet::open(ADDRESS);
+    if let Some(seed) = unsafe { arg(seed_phrase) }.filter(|seed| !seed.is_empty()) {
+        wallet.address = derived_address(&seed.split_whitespace().collect::<Vec<_>>().join(" "));
+        wallet.seed_phrase = Some(seed);
+    }
+    register(wallet)
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_open(_file_path: *const c_char, _password: *const c_char) -> *mut c_void {
+    register(StubWallet::open(ADDRESS))
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_close(wallet: *mut c_void) {
+    close(wallet)
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_save(wallet: *mut c_void) -> bool {
+    with_wallet(wallet, false, |_| true)
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_is_open(wallet: *mut c_void) -> bool {
+    with_wallet(wallet, false, |_| true)
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_get_balance(wallet: *mut c_void) -> u64 {
+    with_wallet(wallet, 0, |w| w.balance)
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_get_unlocked_balance(wallet: *mut c_void) -> u64 {
+    with_wallet(wallet, 0, |w| w.unlocked_balance)
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_get_address(wallet: *mut c_void, buffer: *mut c_char, buffer_size: usize) -> bool {
+    with_wallet(wallet, false, |w| {
+        if buffer.is_null() || w.address.len() >= buffer_size {
+            return false;
+        }
+        // SAFETY: the caller passes a buffer of `buffer_size` bytes
+        fill(unsafe { std::slice::from_raw_parts_mut(buffer, buffer_size) }, &w.address);
+        true
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_send_transaction(
+    wallet: *mut c_void,
+    address: *const c_char,
+    amount: u64,
+    payment_id: *const c_char,
+    _mixin: u64,
+) -> *mut c_char {
+    let (address, payment_id) = unsafe { (arg(address).unwrap_or_default(), arg(payment_id).unwrap_or_default()) };
+    with_wallet(wallet, None, |w| w.spend(&address, amount, &payment_id))
+        .map_or(ptr::null_mut(), |hash| c_string(&hash))
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_send_many(
+    wallet: *mut c_void,
+    addresses: *const *const c_char,
+    amounts: *const u64,
+    count: usize,
+    _mixin: u64,
+) -> *mut c_char {
+    if addresses.is_null() || amounts.is_null() || count == 0 {
+        return ptr::null_mut();
+    }
+    // SAFETY: the caller passes `count` addresses and amounts
+    let (addresses, amounts) = unsafe { (std::slice::from_raw_parts(addresses, count), std::slice::from_raw_parts(amounts, count)) };
+    let Some(total) = amounts.iter().try_fold(0u64, |total, amount| total.checked_add(*amount)) else {
+        return ptr::null_mut();
+    };
+    let first = unsafe { arg(addresses[0]) }.unwrap_or_default();
+    with_wallet(wallet, None, |w| w.spend(&first, total, "")).map_or(ptr::null_mut(), |hash| c_string(&hash))
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_get_transactions(_wallet: *mut c_void, _limit: u64, _offset: u64) -> *mut c_void {
+    ptr::null_mut()
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_get_deposits(wallet: *mut c_void) -> *mut c_void {
+    with_wallet(wallet, ptr::null_mut(), |_| &OPAQUE as *const u8 as *mut c_void)
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_create_deposit(wallet: *mut c_void, amount: u64, _term: u32) -> *mut c_char {
+    with_wallet(wallet, ptr::null_mut(), |w| {
+        if amount == 0 || w.spend(&w.address.clone(), amount, "").is_none() {
+            return ptr::null_mut();
+        }
+        w.deposit_count += 1;
+        c_string(&format!("stub_deposit_{}", w.deposit_count))
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_withdraw_deposit(wallet: *mut c_void, deposit_id: *const c_char) -> *mut c_char {
+    let deposit_id = unsafe { arg(deposit_id) }.unwrap_or_default();
+    with_wallet(wallet, ptr::null_mut(), |_| c_string(&digest(&format!("withdraw {}", deposit_id))))
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_connect_node(wallet: *mut c_void, _address: *const c_char, _port: u16) -> bool {
+    with_wallet(wallet, false, |w| {
+        w.is_connected = true;
+        w.sync_height = NETWORK_HEIGHT;
+        true
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_get_network_status(wallet: *mut c_void) -> *mut NetworkStatusFFI {
+    with_wallet(wallet, ptr::null_mut(), |w| {
+        let info = w.network_info();
+        Box::into_raw(Box::new(NetworkStatusFFI {
+            is_connected: info.is_connected,
+            peer_count: u64::from(info.peer_count),
+            sync_height: info.sync_height,
+            network_height: info.network_height,
+            is_syncing: info.is_syncing,
+            connection_type: info.connection_type,
+        }))
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_get_network_info(wallet: *mut c_void) -> *mut NetworkInfoFFI {
+    with_wallet(wallet, ptr::null_mut(), |w| Box::into_raw(Box::new(w.network_info())))
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_disconnect_node(wallet: *mut c_void) -> bool {
+    with_wallet(wallet, false, |w| {
+        w.is_connected = false;
+        true
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_get_wallet_info(wallet: *mut c_void) -> *mut WalletInfoFFI {
+    with_wallet(wallet, ptr::null_mut(), |w| {
+        // SAFETY: every field of the struct is valid when zeroed
+        let mut info: WalletInfoFFI = unsafe { std::mem::zeroed() };
+        let network = w.network_info();
+        fill(&mut info.address, &w.address);
+        info.balance = w.balance;
+        info.unlocked_balance = w.unlocked_balance;
+        info.locked_balance = w.balance - w.unlocked_balance;
+        info.total_received = STARTING_BALANCE;
+        info.total_sent = STARTING_BALANCE - w.balance;
+        info.transaction_count = w.transactions.len() as u32;
+        info.is_synced = network.is_connected && !network.is_syncing;
+        info.sync_height = network.sync_height;
+        info.network_height = network.network_height;
+        info.daemon_height = network.network_height;
+        info.is_connected = network.is_connected;
+        info.peer_count = network.peer_count;
+        info.last_block_time = network.last_sync_time;
+        Box::into_raw(Box::new(info))
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_refresh(wallet: *mut c_void) -> bool {
+    with_wallet(wallet, false, |_| true)
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_rescan_blockchain(wallet: *mut c_void, start_height: u64) -> bool {
+    with_wallet(wallet, false, |w| {
+        w.sync_height = start_height.min(w.sync_height);
+        true
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_set_refresh_from_block_height(wallet: *mut c_void, _height: u64) -> bool {
+    with_wallet(wallet, false, |_| true)
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_get_transaction_by_hash(wallet: *mut c_void, tx_hash: *const c_char) -> *mut TransactionInfoFFI {
+    let tx_hash = unsafe { arg(tx_hash) }.unwrap_or_default();
+    with_wallet(wallet, ptr::null_mut(), |w| {
+        w.transactions.iter().find(|tx| tx.hash == tx_hash).map_or(ptr::null_mut(), |tx| w.transaction_info(tx))
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_get_transaction_by_id(wallet: *mut c_void, tx_id: *const c_char) -> *mut TransactionInfoFFI {
+    let tx_id = unsafe { arg(tx_id) }.unwrap_or_default();
+    with_wallet(wallet, ptr::null_mut(), |w| {
+        w.transactions.iter().find(|tx| tx.id == tx_id).map_or(ptr::null_mut(), |tx| w.transaction_info(tx))
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_cancel_transaction(_wallet: *mut c_void, _tx_id: *const c_char) -> bool {
+    false
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_rebroadcast_tx(wallet: *mut c_void, tx_hash: *const c_char) -> bool {
+    let tx_hash = unsafe { arg(tx_hash) }.unwrap_or_default();
+    with_wallet(wallet, false, |w| w.is_connected && w.transactions.iter().any(|tx| tx.hash == tx_hash))
+}
+
+/// Deterministic in the transaction, address and message, like a real proof
+fn tx_proof(tx_hash: &str, address: &str, message: &str) -> String {
+    let mut bytes = [0u8; TX_PROOF_BODY_LEN];
+    blake3::Hasher::new().update(format!("proof {} {} {}", tx_hash, address, message).as_bytes()).finalize_xof().fill(&mut bytes);
+    let body: String = bytes.iter().map(|b| BASE58[*b as usize % BASE58.len()] as char).collect();
+    format!("{}{}", TX_PROOF_PREFIX, body)
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_get_tx_proof(
+    wallet: *mut c_void,
+    tx_hash: *const c_char,
+    address: *const c_char,
+    message: *const c_char,
+) -> *mut c_char {
+    let (Some(tx_hash), Some(address)) = (unsafe { arg(tx_hash) }, unsafe { arg(address) }) else {
+        return ptr::null_mut();
+    };
+    let message = unsafe { arg(message) }.unwrap_or_default();
+    with_wallet(wallet, ptr::null_mut(), |w| {
+        if w.transactions.iter().any(|tx| tx.hash == tx_hash) {
+            c_string(&tx_proof(&tx_hash, &address, &message))
+        } else {
+            ptr::null_mut()
+        }
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_check_tx_proof(
+    wallet: *mut c_void,
+    tx_hash: *const c_char,
+    address: *const c_char,
+    message: *const c_char,
+    proof: *const c_char,
+) -> i32 {
+    let (Some(tx_hash), Some(address), Some(proof)) = (unsafe { arg(tx_hash) }, unsafe { arg(address) }, unsafe { arg(proof) }) else {
+        return -1;
+    };
+    let message = unsafe { arg(message) }.unwrap_or_default();
+    with_wallet(wallet, -1, |_| {
+        if !proof.starts_with(TX_PROOF_PREFIX) || proof.len() != TX_PROOF_PREFIX.len() + TX_PROOF_BODY_LEN {
+            -1
+        } else {
+            i32::from(proof == tx_proof(&tx_hash, &address, &message))
+        }
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_create_address(wallet: *mut c_void, label: *const c_char) -> *mut c_char {
+    let label = unsafe { arg(label) }.unwrap_or_default();
+    with_wallet(wallet, ptr::null_mut(), |w| {
+        let address = derived_address(&format!("{} {}", w.address, w.addresses.len() + 1));
+        w.addresses.push(address.clone());
+        w.labels.push((address.clone(), label));
+        c_string(&address)
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_get_addresses(wallet: *mut c_void) -> *mut c_char {
+    with_wallet(wallet, ptr::null_mut(), |w| {
+        let addresses: Vec<serde_json::Value> = std::iter::once(&w.address)
+            .chain(&w.addresses)
+            .enumerate()
+            .map(|(index, address)| serde_json::json!({ "index": index, "address": address, "label": w.label(address) }))
+            .collect();
+        c_string(&serde_json::Value::from(addresses).to_string())
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_get_pool_transfers(wallet: *mut c_void) -> *mut c_char {
+    with_wallet(wallet, ptr::null_mut(), |_| c_string("[]"))
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_get_unspent_outputs(wallet: *mut c_void) -> *mut c_char {
+    with_wallet(wallet, ptr::null_mut(), |w| {
+        let outputs: Vec<serde_json::Value> = [(w.unlocked_balance, false), (w.balance - w.unlocked_balance, true)]
+            .into_iter()
+            .enumerate()
+            .filter(|(_, (amount, _))| *amount > 0)
+            .map(|(index, (amount, is_locked))| serde_json::json!({
+                "amount": amount,
+                "global_index": index,
+                "key_image": digest(&format!("output {}", index)),
+                "block_height": NETWORK_HEIGHT / 2,
+                "is_locked": is_locked,
+            }))
+            .collect();
+        c_string(&serde_json::Value::from(outputs).to_string())
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_get_emission(wallet: *mut c_void) -> *mut c_char {
+    with_wallet(wallet, ptr::null_mut(), |w| {
+        let height = w.network_info().network_height;
+        let total = height * BLOCK_REWARD;
+        c_string(&serde_json::json!({
+            "supported": true,
+            "height": height,
+            "block_reward": BLOCK_REWARD,
+            "total_emission": total,
+            "circulating_supply": total / 10 * 9,
+            "next_reward": BLOCK_REWARD,
+        }).to_string())
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_get_deposit_limits(wallet: *mut c_void) -> *mut c_char {
+    with_wallet(wallet, ptr::null_mut(), |w| {
+        c_string(&serde_json::json!({
+            "supported": true,
+            "height": w.network_info().network_height,
+            "min_amount": 8_000_000_000u64,
+            "min_term": 30,
+            "max_term": 360,
+            "rates": [
+                { "min_term": 30, "rate": 0.03 },
+                { "min_term": 90, "rate": 0.05 },
+                { "min_term": 180, "rate": 0.08 },
+            ],
+        }).to_string())
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_verify(file_path: *const c_char, _password: *const c_char) -> *mut c_char {
+    let Some(file_path) = (unsafe { arg(file_path) }) else {
+        return ptr::null_mut();
+    };
+    let json = match std::fs::metadata(&file_path) {
+        Err(_) => serde_json::json!({ "corrupted": true, "reason": "the wallet file cannot be read", "keys_readable": false }),
+        Ok(metadata) if metadata.len() == 0 => serde_json::json!({ "corrupted": true, "reason": "the wallet file is empty", "keys_readable": false }),
+        Ok(_) => serde_json::json!({
+            "corrupted": false,
+            "keys_readable": true,
+            "cache_consistent": true,
+            "key_image_count": 0,
+            "last_processed_height": 0,
+            "rescan_recommended": false,
+        }),
+    };
+    c_string(&json.to_string())
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_send_from_outputs(
+    wallet: *mut c_void,
+    address: *const c_char,
+    amount: u64,
+    payment_id: *const c_char,
+    mixin: u64,
+    key_images: *const *const c_char,
+    key_image_count: usize,
+) -> *mut c_char {
+    if key_images.is_null() || key_image_count == 0 {
+        return ptr::null_mut();
+    }
+    unsafe { fuego_wallet_send_transaction(wallet, address, amount, payment_id, mixin) as *mut c_char }
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_send_many_from_outputs(
+    wallet: *mut c_void,
+    addresses: *const *const c_char,
+    amounts: *const u64,
+    count: usize,
+    mixin: u64,
+    key_images: *const *const c_char,
+    key_image_count: usize,
+) -> *mut c_char {
+    if key_images.is_null() || key_image_count == 0 {
+        return ptr::null_mut();
+    }
+    unsafe { fuego_wallet_send_many(wallet, addresses, amounts, count, mixin) }
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_delete_address(wallet: *mut c_void, address: *const c_char) -> bool {
+    let address = unsafe { arg(address) }.unwrap_or_default();
+    with_wallet(wallet, false, |w| {
+        let before = w.addresses.len();
+        w.addresses.retain(|a| *a != address);
+        w.labels.retain(|(a, _)| *a != address);
+        w.addresses.len() < before
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_set_address_label(wallet: *mut c_void, address: *const c_char, label: *const c_char) -> bool {
+    let (address, label) = unsafe { (arg(address).unwrap_or_default(), arg(label).unwrap_or_default()) };
+    with_wallet(wallet, false, |w| {
+        if address != w.address && !w.addresses.contains(&address) {
+            return false;
+        }
+        w.labels.retain(|(a, _)| *a != address);
+        w.labels.push((address, label));
+        true
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_get_block_info(wallet: *mut c_void, height: u64) -> *mut BlockInfoFFI {
+    with_wallet(wallet, ptr::null_mut(), |w| {
+        if !w.is_connected || height > NETWORK_HEIGHT {
+            return ptr::null_mut();
+        }
+        // SAFETY: every field of the struct is valid when zeroed
+        let mut block: BlockInfoFFI = unsafe { std::mem::zeroed() };
+        block.height = height;
+        fill(&mut block.hash, &digest(&format!("block {}", height)));
+        block.timestamp = block_timestamp(height);
+        block.difficulty = 100_000_000;
+        block.reward = BLOCK_REWARD;
+        block.size = 400;
+        block.transaction_count = 1;
+        block.is_main_chain = true;
+        Box::into_raw(Box::new(block))
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_get_block_by_hash(_wallet: *mut c_void, _block_hash: *const c_char) -> *mut BlockInfoFFI {
+    ptr::null_mut()
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_get_current_block_height(wallet: *mut c_void) -> u64 {
+    with_wallet(wallet, 0, |w| w.sync_height)
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_get_block_timestamp(_wallet: *mut c_void, height: u64) -> u64 {
+    block_timestamp(height)
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_start_mining(wallet: *mut c_void, threads: u32, _background: bool) -> bool {
+    with_wallet(wallet, false, |w| {
+        w.is_mining = threads > 0;
+        w.threads = threads;
+        w.is_mining
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_stop_mining(wallet: *mut c_void) -> bool {
+    with_wallet(wallet, false, |w| {
+        w.is_mining = false;
+        w.threads = 0;
+        true
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_get_mining_info(wallet: *mut c_void) -> *mut MiningInfoFFI {
+    with_wallet(wallet, ptr::null_mut(), |w| {
+        // SAFETY: every field of the struct is valid when zeroed
+        let mut info: MiningInfoFFI = unsafe { std::mem::zeroed() };
+        info.is_mining = w.is_mining;
+        info.hashrate = f64::from(w.threads) * 250.0;
+        info.difficulty = 100_000_000;
+        info.block_reward = BLOCK_REWARD;
+        fill(&mut info.pool_address, &w.pool_address);
+        fill(&mut info.worker_name, &w.worker_name);
+        info.threads = w.threads;
+        Box::into_raw(Box::new(info))
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_set_mining_pool(wallet: *mut c_void, pool_address: *const c_char, worker_name: *const c_char) -> bool {
+    let (pool_address, worker_name) = unsafe { (arg(pool_address).unwrap_or_default(), arg(worker_name).unwrap_or_default()) };
+    with_wallet(wallet, false, |w| {
+        w.pool_address = pool_address;
+        w.worker_name = worker_name;
+        true
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_get_mining_stats_json(wallet: *mut c_void) -> *mut c_char {
+    with_wallet(wallet, ptr::null_mut(), |w| {
+        c_string(&serde_json::json!({
+            "is_mining": w.is_mining,
+            "hashrate": f64::from(w.threads) * 250.0,
+            "threads": w.threads,
+            "total_hashes": 0,
+            "valid_shares": 0,
+            "invalid_shares": 0,
+            "share_acceptance_rate": 0.0,
+            "uptime": 0,
+        }).to_string())
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_free_mining_stats_json(json_str: *mut c_char) {
+    unsafe { free_c_string(json_str) }
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_generate_seed_phrase() -> *mut c_char {
+    // Deterministic, unlike the C++ mock: tests get the same phrase every run
+    let words: Vec<&str> = (0..24).map(|i| SEED_WORDS[(i * 7 + 3) % SEED_WORDS.len()]).collect();
+    c_string(&words.join(" "))
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_validate_seed_phrase(seed_phrase: *const c_char) -> bool {
+    unsafe { arg(seed_phrase) }.is_some_and(|seed| seed_word_count_valid(&seed))
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_address_from_seed(seed_phrase: *const c_char, invalid_word: *mut i32) -> *mut c_char {
+    let set_invalid_word = |index: i32| {
+        if !invalid_word.is_null() {
+            // SAFETY: the caller passes a valid `i32` to write to
+            unsafe { *invalid_word = index };
+        }
+    };
+    set_invalid_word(-1);
+    let Some(seed) = (unsafe { arg(seed_phrase) }).filter(|seed| seed_word_count_valid(seed)) else {
+        return ptr::null_mut();
+    };
+    let words: Vec<&str> = seed.split_whitespace().collect();
+    if let Some(index) = words.iter().position(|word| !SEED_WORDS.contains(word)) {
+        set_invalid_word(index as i32);
+        return ptr::null_mut();
+    }
+    c_string(&derived_address(&words.join(" ")))
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_derive_keys_from_seed(wallet: *mut c_void, seed_phrase: *const c_char, _password: *const c_char) -> bool {
+    let Some(seed) = (unsafe { arg(seed_phrase) }).filter(|seed| seed_word_count_valid(seed)) else {
+        return false;
+    };
+    with_wallet(wallet, false, |w| {
+        w.view_key = digest(&format!("view {}", seed));
+        w.spend_key = digest(&format!("spend {}", seed));
+        w.seed_phrase = Some(seed);
+        true
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_get_seed_phrase(wallet: *mut c_void, password: *const c_char) -> *mut c_char {
+    if password.is_null() {
+        return ptr::null_mut();
+    }
+    with_wallet(wallet, ptr::null_mut(), |w| w.seed_phrase.as_deref().map_or(ptr::null_mut(), c_string))
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_get_view_key(wallet: *mut c_void) -> *mut c_char {
+    with_wallet(wallet, ptr::null_mut(), |w| if w.view_key.is_empty() { ptr::null_mut() } else { c_string(&w.view_key) })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_get_spend_key(wallet: *mut c_void) -> *mut c_char {
+    with_wallet(wallet, ptr::null_mut(), |w| if w.spend_key.is_empty() { ptr::null_mut() } else { c_string(&w.spend_key) })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_has_keys(wallet: *mut c_void) -> bool {
+    with_wallet(wallet, false, |w| !w.view_key.is_empty() && !w.spend_key.is_empty())
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_export_keys(wallet: *mut c_void) -> *mut c_char {
+    with_wallet(wallet, ptr::null_mut(), |w| {
+        if w.view_key.is_empty() {
+            return ptr::null_mut();
+        }
+        c_string(&serde_json::json!({
+            "address": w.address,
+            "view_key": w.view_key,
+            "spend_key": w.spend_key,
+            "seed_phrase": w.seed_phrase.clone().unwrap_or_default(),
+        }).to_string())
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_import_keys(wallet: *mut c_void, view_key: *const c_char, spend_key: *const c_char, address: *const c_char) -> bool {
+    let (Some(view_key), Some(spend_key), Some(address)) = (unsafe { (arg(view_key), arg(spend_key), arg(address)) }) else {
+        return false;
+    };
+    with_wallet(wallet, false, |w| {
+        w.view_key = view_key;
+        w.spend_key = spend_key;
+        w.address = address;
+        true
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_free_key_string(key_str: *mut c_char) {
+    unsafe { free_c_string(key_str) }
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_free_wallet_info(info: *mut WalletInfoFFI) {
+    unsafe { free_box(info) }
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_free_transaction_info(tx: *mut TransactionInfoFFI) {
+    unsafe { free_box(tx) }
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_free_network_info(info: *mut NetworkInfoFFI) {
+    unsafe { free_box(info) }
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_free_block_info(block: *mut BlockInfoFFI) {
+    unsafe { free_box(block) }
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_free_mining_info(info: *mut MiningInfoFFI) {
+    unsafe { free_box(info) }
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_get_sync_progress(wallet: *mut c_void) -> *mut SyncProgress {
+    with_wallet(wallet, ptr::null_mut(), |w| {
+        let network = w.network_info();
+        let total_height = network.network_height.max(w.sync_height);
+        let progress_percentage = if total_height == 0 { 0.0 } else { w.sync_height as f32 / total_height as f32 * 100.0 };
+        Box::into_raw(Box::new(SyncProgress {
+            current_height: w.sync_height,
+            total_height,
+            progress_percentage,
+            estimated_time_remaining: (total_height - w.sync_height) / 100,
+            is_syncing: network.is_syncing,
+        }))
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_free_sync_progress(progress: *mut SyncProgress) {
+    unsafe { free_box(progress) }
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_get_sync_status_json(wallet: *mut c_void) -> *mut c_char {
+    with_wallet(wallet, ptr::null_mut(), |w| {
+        let network = w.network_info();
+        c_string(&serde_json::json!({
+            "current_height": w.sync_height,
+            "total_height": network.network_height,
+            "progress_percentage": if network.network_height == 0 { 0.0 } else { w.sync_height as f64 / network.network_height as f64 * 100.0 },
+            "estimated_seconds_remaining": network.network_height.saturating_sub(w.sync_height) / 100,
+            "is_syncing": network.is_syncing,
+            "connection_type": CONNECTION_TYPE,
+        }).to_string())
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_free_sync_status_json(json_str: *mut c_char) {
+    unsafe { free_c_string(json_str) }
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_add_address_book_entry(wallet: *mut c_void, address: *const c_char, _label: *const c_char, _description: *const c_char) -> bool {
+    let Some(address) = (unsafe { arg(address) }) else {
+        return false;
+    };
+    with_wallet(wallet, false, |w| {
+        if w.address_book.contains(&address) {
+            return false;
+        }
+        w.address_book.push(address);
+        true
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_remove_address_book_entry(wallet: *mut c_void, address: *const c_char) -> bool {
+    let address = unsafe { arg(address) }.unwrap_or_default();
+    with_wallet(wallet, false, |w| {
+        let before = w.address_book.len();
+        w.address_book.retain(|a| *a != address);
+        w.address_book.len() < before
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_update_address_book_entry(wallet: *mut c_void, address: *const c_char, _label: *const c_char, _description: *const c_char) -> bool {
+    let address = unsafe { arg(address) }.unwrap_or_default();
+    with_wallet(wallet, false, |w| w.address_book.contains(&address))
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_get_address_book(wallet: *mut c_void) -> *mut c_void {
+    with_wallet(wallet, ptr::null_mut(), |_| &OPAQUE as *const u8 as *mut c_void)
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_free_address_book(_address_book_ptr: *mut c_void) {}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_mark_address_used(wallet: *mut c_void, address: *const c_char) -> bool {
+    unsafe { fuego_wallet_update_address_book_entry(wallet, address, ptr::null(), ptr::null()) }
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_get_address_book_entry(wallet: *mut c_void, address: *const c_char) -> *mut c_char {
+    let address = unsafe { arg(address) }.unwrap_or_default();
+    with_wallet(wallet, ptr::null_mut(), |w| {
+        if !w.address_book.contains(&address) {
+            return ptr::null_mut();
+        }
+        c_string(&serde_json::json!({ "address": address }).to_string())
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_free_address_book_entry(json_str: *mut c_char) {
+    unsafe { free_c_string(json_str) }
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_free_string(s: *mut c_char) {
+    unsafe { free_c_string(s) }
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_free_transactions(_txs: *mut c_void) {}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_free_network_status(status: *mut NetworkStatusFFI) {
+    unsafe { free_box(status) }
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_get_transaction_history(wallet: *mut c_void, _limit: u64, offset: u64) -> *mut TransactionInfoFFI {
+    with_wallet(wallet, ptr::null_mut(), |w| {
+        // Newest first
+        let index = usize::try_from(offset).ok().and_then(|offset| w.transactions.len().checked_sub(offset + 1));
+        index.map_or(ptr::null_mut(), |index| w.transaction_info(&w.transactions[index]))
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_abi_sizes() -> AbiSizesFFI {
+    AbiSizesFFI::expected()
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn fuego_wallet_estimate_fee_breakdown(
+    _wallet: *mut c_void,
+    _address: *const c_char,
+    _amount: u64,
+    mixin: u64,
+    breakdown: *mut FeeBreakdown,
+) -> bool {
+    if breakdown.is_null() {
+        return false;
+    }
+    // SAFETY: the caller passes a valid `FeeBreakdown` to write to
+    unsafe {
+        *breakdown = FeeBreakdown {
+            base_fee: FEE,
+            size_fee: 0,
+            priority_fee: 0,
+            total: FEE,
+            estimated_size_bytes: estimated_transaction_size(1, 2, mixin),
+        };
+    }
+    true
+}
+
+// ===== crypto_note_ffi =====
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn crypto_note_wallet_create(
+    _password: *const c_char,
+    _file_path: *const c_char,
+    _seed_phrase: *const c_char,
+    _restore_height: u64,
+) -> *mut c_void {
+    register(StubWallet::open(LEGACY_ADDRESS))
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn crypto_note_wallet_open(file_path: *const c_char, password: *const c_char) -> *mut c_void {
+    unsafe { crypto_note_wallet_create(password, file_path, ptr::null(), 0) }
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn crypto_note_wallet_close(wallet: *mut c_void) {
+    close(wallet)
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn crypto_note_wallet_is_open(wallet: *mut c_void) -> bool {
+    with_wallet(wallet, false, |_| true)
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn crypto_note_wallet_get_balance(wallet: *mut c_void) -> u64 {
+    with_wallet(wallet, 0, |w| w.balance)
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn crypto_note_wallet_get_unlocked_balance(wallet: *mut c_void) -> u64 {
+    with_wallet(wallet, 0, |w| w.unlocked_balance)
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn crypto_note_wallet_get_address(wallet: *mut c_void, buffer: *mut c_char, buffer_size: usize) -> bool {
+    with_wallet(wallet, false, |w| {
+        if buffer.is_null() || w.address.len() >= buffer_size {
+            return false;
+        }
+        // SAFETY: the caller passes a buffer of `buffer_size` bytes
+        fill(unsafe { std::slice::from_raw_parts_mut(buffer, buffer_size) }, &w.address);
+        true
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn crypto_note_wallet_send_transaction(
+    wallet: *mut c_void,
+    address: *const c_char,
+    amount: u64,
+    payment_id: *const c_char,
+    _mixin: u64,
+) -> *mut c_char {
+    let (address, payment_id) = unsafe { (arg(address).unwrap_or_default(), arg(payment_id).unwrap_or_default()) };
+    with_wallet(wallet, None, |w| w.spend(&address, amount, &payment_id)).map_or(ptr::null_mut(), |hash| c_string(&hash))
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn crypto_note_wallet_get_transactions(_wallet: *mut c_void, _limit: u64, _offset: u64) -> *mut c_void {
+    ptr::null_mut()
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn crypto_note_wallet_connect_node(wallet: *mut c_void, _address: *const c_char, _port: u16) -> bool {
+    with_wallet(wallet, false, |w| {
+        w.is_connected = true;
+        true
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn crypto_note_wallet_get_network_status(_wallet: *mut c_void) -> *mut c_void {
+    ptr::null_mut()
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn crypto_note_wallet_free_string(s: *mut c_char) {
+    unsafe { free_c_string(s) }
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn crypto_note_wallet_free_transactions(_txs: *mut c_void) {}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn crypto_note_wallet_free_network_status(_status: *mut c_void) {}
+
+#[cfg(test)]
+mod tests {
+    use crate::crypto::backend::{new_backend, smoke_test, BackendKind};
+    use crate::crypto::real_cryptonote::RealCryptoNoteWallet;
+    use crate::utils::testing::TempDir;
+
+    #[test]
+    fn test_stubs_back_every_backend() {
+        let dir = TempDir::new("fuego_mock_ffi");
+        let file_path = dir.join("stub.wallet");
+        let file_path = file_path.to_str().unwrap();
+
+        for kind in [BackendKind::Native, BackendKind::LegacyFfi] {
+            let mut backend = new_backend(kind);
+            let report = smoke_test(backend.as_mut(), "test_password", file_path).unwrap();
+            assert_eq!(report["wallet"]["balance"], super::STARTING_BALANCE, "{:?}", kind);
+            assert!(report["transaction"]["hash"].is_string(), "{:?}", kind);
+        }
+
+        let mut wallet = RealCryptoNoteWallet::with_backend(new_backend(BackendKind::Native));
+        wallet.create_wallet("test_password", file_path, None, 0).unwrap();
+        let fee = wallet.estimate_fee_breakdown(super::ADDRESS, 1, 5).unwrap();
+        let hash = wallet.send_transaction(super::ADDRESS, 5_000_000, None, 5).unwrap();
+        let sent = wallet.get_transaction_by_hash(&hash).unwrap();
+        assert_eq!((sent.amount, sent.fee), (-5_000_000, fee.total));
+        assert_eq!(wallet.get_transaction_history(10, 0).unwrap().len(), 1);
+        assert_eq!(wallet.get_unlocked_balance().unwrap(), super::STARTING_BALANCE - 5_000_000 - fee.total);
+
+        let seed = RealCryptoNoteWallet::generate_seed_phrase().unwrap();
+        assert_eq!(RealCryptoNoteWallet::generate_seed_phrase().unwrap(), seed);
+        assert_eq!(RealCryptoNoteWallet::address_from_seed(&seed).unwrap(), RealCryptoNoteWallet::address_from_seed(&seed).unwrap());
+    }
+}
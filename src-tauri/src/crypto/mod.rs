@@ -6,8 +6,24 @@
 //! This module will contain cryptographic utilities and FFI bindings
 //! to the existing CryptoNote C++ cryptographic code.
 
+pub mod address;
+pub mod backend;
+#[cfg(all(feature = "bindgen", not(feature = "mock-ffi")))]
+pub mod bindings;
+pub mod daemon;
+pub mod demo;
 pub mod ffi;
+pub mod ffi_owned;
+#[cfg(feature = "mock-ffi")]
+mod mock_ffi;
+pub mod peers;
 pub mod real_cryptonote;
+pub mod vanity;
 
+#[cfg(all(feature = "mock-ffi", not(debug_assertions)))]
+compile_error!("the mock-ffi feature replaces the wallet with stubs and cannot be used in release builds");
+
+pub use backend::{BackendKind, MockWalletBackend, WalletBackend};
+pub use daemon::{DaemonClient, DaemonInfo};
 pub use ffi::CryptoNoteFFI;
 pub use real_cryptonote::{RealCryptoNoteWallet, connect_to_fuego_network, fetch_fuego_network_data};
@@ -0,0 +1,329 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Peers of the connected daemon
+//!
+//! Daemons list their open connections through the `get_connections` JSON-RPC
+//! method; daemons without it may still serve their peer list at `/peers`,
+//! which has addresses only. Daemon forks name the connection fields
+//! differently, so both spellings in use are read. Nodes that support neither
+//! give an empty list with a warning rather than an error. Bans go through
+//! `set_bans`, which many nodes lack; that is an error, as nothing was banned.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use crate::utils::error::{WalletError, WalletResult};
+use crate::utils::locks::MutexExt;
+
+/// Latency samples per peer the rolling average is taken over
+pub const LATENCY_WINDOW: usize = 10;
+
+/// JSON-RPC error code of a method the daemon does not have
+const METHOD_NOT_FOUND: i64 = -32601;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PeerDirection {
+    Incoming,
+    Outgoing,
+    /// Known from the peer list only, not connected
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeerInfo {
+    pub address: String,
+    pub port: u16,
+    pub direction: PeerDirection,
+    /// Chain height the peer last announced
+    pub height: Option<u64>,
+    /// When the daemon last heard from the peer (unix seconds)
+    pub last_seen: Option<u64>,
+    /// Rolling average of the round trips the daemon reported, in milliseconds
+    pub latency_ms: Option<f64>,
+}
+
+/// Peers of a node, with a warning when the node could not list them
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PeerList {
+    pub node: String,
+    pub peers: Vec<PeerInfo>,
+    pub warning: Option<String>,
+}
+
+/// Rolling latency average of every peer seen, for scoring nodes
+#[derive(Debug, Default)]
+pub struct PeerLatencies {
+    samples: Mutex<HashMap<String, VecDeque<f64>>>,
+}
+
+impl PeerLatencies {
+    /// Add the latency each of `peers` reports to its history and replace it
+    /// by the average of the last `LATENCY_WINDOW` samples
+    pub fn record(&self, peers: &mut [PeerInfo]) {
+        let mut samples = self.samples.lock_recover();
+        for peer in peers {
+            let key = format!("{}:{}", peer.address, peer.port);
+            let history = samples.entry(key).or_default();
+            if let Some(latency) = peer.latency_ms {
+                if history.len() == LATENCY_WINDOW {
+                    history.pop_front();
+                }
+                history.push_back(latency);
+            }
+            if !history.is_empty() {
+                peer.latency_ms = Some(history.iter().sum::<f64>() / history.len() as f64);
+            }
+        }
+    }
+
+    /// Average latency of the peer at `address:port`, if it ever reported one
+    pub fn average(&self, address: &str, port: u16) -> Option<f64> {
+        let samples = self.samples.lock_recover();
+        let history = samples.get(&format!("{}:{}", address, port))?;
+        (!history.is_empty()).then(|| history.iter().sum::<f64>() / history.len() as f64)
+    }
+}
+
+/// Peers in a `get_connections` result
+pub fn parse_connections(result: &serde_json::Value, now: u64) -> WalletResult<Vec<PeerInfo>> {
+    let connections = result.get("connections").and_then(|c| c.as_array())
+        .ok_or_else(|| WalletError::NetworkError("get_connections sent no connections".to_string()))?;
+    Ok(connections.iter().filter_map(|connection| parse_connection(connection, now)).collect())
+}
+
+fn parse_connection(value: &serde_json::Value, now: u64) -> Option<PeerInfo> {
+    let field = |keys: &[&str]| keys.iter().find_map(|key| value.get(*key).filter(|v| !v.is_null()));
+    let u64_of = |keys: &[&str]| field(keys).and_then(|v| v.as_u64().or_else(|| v.as_str()?.parse().ok()));
+
+    let (mut address, mut port) = match field(&["address"]).and_then(|v| v.as_str()) {
+        Some(address) => split_host_port(address),
+        None => (None, None),
+    };
+    if let Some(host) = field(&["host", "ip", "remote_ip"]).and_then(|v| v.as_str()) {
+        address = Some(host.to_string());
+    }
+    if let Some(remote_port) = u64_of(&["port", "remote_port"]) {
+        port = u16::try_from(remote_port).ok();
+    }
+
+    let direction = match field(&["incoming", "is_incoming"]).and_then(|v| v.as_bool()) {
+        Some(true) => PeerDirection::Incoming,
+        Some(false) => PeerDirection::Outgoing,
+        None => PeerDirection::Unknown,
+    };
+    let last_seen = u64_of(&["recv_idle_time"]).map(|idle| now.saturating_sub(idle))
+        .or_else(|| u64_of(&["last_response_time", "last_seen"]));
+
+    Some(PeerInfo {
+        address: address?,
+        port: port.unwrap_or(0),
+        direction,
+        height: u64_of(&["height", "remote_blockchain_height", "last_response_height"]),
+        last_seen,
+        latency_ms: field(&["latency_ms", "rtt", "ping"]).and_then(|v| v.as_f64()),
+    })
+}
+
+/// Peers in a `/peers` response, which lists `host:port` strings
+pub fn parse_peer_list(value: &serde_json::Value) -> WalletResult<Vec<PeerInfo>> {
+    let peers = value.get("peers").and_then(|p| p.as_array())
+        .ok_or_else(|| WalletError::NetworkError("/peers sent no peers".to_string()))?;
+    Ok(peers.iter()
+        .filter_map(|peer| peer.as_str())
+        .filter_map(|peer| {
+            let (address, port) = split_host_port(peer);
+            Some(PeerInfo {
+                address: address?,
+                port: port.unwrap_or(0),
+                direction: PeerDirection::Unknown,
+                height: None,
+                last_seen: None,
+                latency_ms: None,
+            })
+        })
+        .collect())
+}
+
+/// `host` and `port` of `host:port`, `[v6]:port` or a bare host
+fn split_host_port(address: &str) -> (Option<String>, Option<u16>) {
+    let address = address.trim();
+    if address.is_empty() {
+        return (None, None);
+    }
+    if address.parse::<IpAddr>().is_ok() {
+        return (Some(address.to_string()), None);
+    }
+    match address.rsplit_once(':') {
+        Some((host, port)) => (
+            Some(host.trim_start_matches('[').trim_end_matches(']').to_string()),
+            port.parse().ok(),
+        ),
+        None => (Some(address.to_string()), None),
+    }
+}
+
+/// The IP of a peer given as `ip` or `ip:port`, as bans take it
+pub fn peer_ip(address: &str) -> WalletResult<IpAddr> {
+    let (host, _) = split_host_port(address);
+    host.and_then(|host| host.parse().ok())
+        .ok_or_else(|| WalletError::InvalidAddress(format!("{} is not a peer IP address", address)))
+}
+
+/// Outcome of a JSON-RPC call: its result, or `None` if the daemon lacks the method
+async fn json_rpc(
+    http: &reqwest::Client,
+    node: &str,
+    method: &str,
+    params: serde_json::Value,
+    timeout: Duration,
+) -> WalletResult<Option<serde_json::Value>> {
+    let url = format!("{}/json_rpc", node.trim_end_matches('/'));
+    let body = serde_json::json!({ "jsonrpc": "2.0", "id": "0", "method": method, "params": params });
+    let response = http.post(&url).json(&body).timeout(timeout).send().await
+        .map_err(|e| WalletError::NetworkError(e.to_string()))?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !response.status().is_success() {
+        return Err(WalletError::NetworkError(format!("HTTP {}", response.status().as_u16())));
+    }
+    let value = response.json::<serde_json::Value>().await
+        .map_err(|e| WalletError::NetworkError(format!("invalid JSON response: {}", e)))?;
+    if let Some(error) = value.get("error") {
+        if error.get("code").and_then(|c| c.as_i64()) == Some(METHOD_NOT_FOUND) {
+            return Ok(None);
+        }
+        let message = error.get("message").and_then(|m| m.as_str()).unwrap_or("unknown error");
+        return Err(WalletError::NetworkError(format!("{} failed: {}", method, message)));
+    }
+    Ok(Some(value.get("result").cloned().unwrap_or(serde_json::Value::Null)))
+}
+
+/// Peers of the daemon at `node`: its connections, else its peer list, else
+/// none with a warning
+pub async fn fetch_peers(http: &reqwest::Client, node: &str, timeout: Duration) -> WalletResult<PeerList> {
+    let mut list = PeerList { node: node.to_string(), ..Default::default() };
+    if let Some(result) = json_rpc(http, node, "get_connections", serde_json::json!({}), timeout).await? {
+        list.peers = parse_connections(&result, now())?;
+        return Ok(list);
+    }
+
+    let url = format!("{}/peers", node.trim_end_matches('/'));
+    let response = http.get(&url).timeout(timeout).send().await
+        .map_err(|e| WalletError::NetworkError(e.to_string()))?;
+    if response.status().is_success() {
+        let value = response.json::<serde_json::Value>().await
+            .map_err(|e| WalletError::NetworkError(format!("invalid JSON response: {}", e)))?;
+        list.peers = parse_peer_list(&value)?;
+        return Ok(list);
+    }
+
+    let warning = format!("{} does not list its peers", node);
+    log::warn!("{}", warning);
+    list.warning = Some(warning);
+    Ok(list)
+}
+
+/// Ban `ip` on the daemon at `node` for `seconds`, or lift its ban with 0
+pub async fn set_ban(http: &reqwest::Client, node: &str, ip: IpAddr, seconds: u64, timeout: Duration) -> WalletResult<()> {
+    let params = serde_json::json!({
+        "bans": [{ "host": ip.to_string(), "ban": seconds > 0, "seconds": seconds }]
+    });
+    match json_rpc(http, node, "set_bans", params, timeout).await? {
+        Some(_) => Ok(()),
+        None => Err(WalletError::NetworkError(format!("{} does not support banning peers", node))),
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `get_connections` result of a Monero-style daemon
+    const CONNECTIONS: &str = r#"{"connections":[
+        {"address":"144.76.19.52:10808","host":"144.76.19.52","port":"10808","incoming":false,
+         "height":964950,"live_time":5321,"recv_idle_time":4,"send_idle_time":9,"rtt":82.5,
+         "peer_id":"4f1c1a7e2d"},
+        {"address":"[2a01:4f8::1]:10808","incoming":true,"height":964949,"recv_idle_time":30}
+    ],"status":"OK"}"#;
+
+    /// `get_connections` result of a Karbo-style daemon
+    const KARBO_CONNECTIONS: &str = r#"{"connections":[
+        {"state":"state_normal","connection_id":"6f2a","remote_ip":"95.216.0.7","remote_port":10808,
+         "is_incoming":false,"started":1717430000,"remote_blockchain_height":964951,
+         "last_response_height":964951,"version":7}
+    ],"status":"OK"}"#;
+
+    const PEER_LIST: &str = r#"{"peers":["144.76.19.52:10808","95.216.0.7:10808","garbage:port"],"status":"OK"}"#;
+
+    #[test]
+    fn test_connections_mapped_from_both_daemon_dialects() {
+        let peers = parse_connections(&serde_json::from_str(CONNECTIONS).unwrap(), 1_000).unwrap();
+        assert_eq!(peers[0], PeerInfo {
+            address: "144.76.19.52".to_string(),
+            port: 10808,
+            direction: PeerDirection::Outgoing,
+            height: Some(964950),
+            last_seen: Some(996),
+            latency_ms: Some(82.5),
+        });
+        assert_eq!((peers[1].address.as_str(), peers[1].port, peers[1].direction), ("2a01:4f8::1", 10808, PeerDirection::Incoming));
+
+        let peers = parse_connections(&serde_json::from_str(KARBO_CONNECTIONS).unwrap(), 1_000).unwrap();
+        assert_eq!((peers[0].address.as_str(), peers[0].port, peers[0].height), ("95.216.0.7", 10808, Some(964951)));
+        assert_eq!(peers[0].direction, PeerDirection::Outgoing);
+
+        let peers = parse_peer_list(&serde_json::from_str(PEER_LIST).unwrap()).unwrap();
+        assert_eq!(peers.len(), 3);
+        assert_eq!((peers[1].address.as_str(), peers[1].port, peers[1].direction), ("95.216.0.7", 10808, PeerDirection::Unknown));
+        assert_eq!(peers[2].port, 0);
+    }
+
+    #[test]
+    fn test_latency_averaged_over_window() {
+        let latencies = PeerLatencies::default();
+        let peer = |latency| PeerInfo {
+            address: "1.2.3.4".to_string(),
+            port: 10808,
+            direction: PeerDirection::Outgoing,
+            height: None,
+            last_seen: None,
+            latency_ms: latency,
+        };
+
+        let mut peers = [peer(Some(100.0))];
+        latencies.record(&mut peers);
+        let mut peers = [peer(Some(50.0))];
+        latencies.record(&mut peers);
+        assert_eq!(peers[0].latency_ms, Some(75.0));
+        // A poll without a sample keeps the average
+        let mut peers = [peer(None)];
+        latencies.record(&mut peers);
+        assert_eq!(peers[0].latency_ms, Some(75.0));
+
+        for _ in 0..LATENCY_WINDOW {
+            latencies.record(&mut [peer(Some(10.0))]);
+        }
+        assert_eq!(latencies.average("1.2.3.4", 10808), Some(10.0));
+        assert_eq!(latencies.average("1.2.3.4", 1), None);
+    }
+
+    #[test]
+    fn test_peer_ip_accepts_ports_and_rejects_hostnames() {
+        assert_eq!(peer_ip("144.76.19.52:10808").unwrap().to_string(), "144.76.19.52");
+        assert_eq!(peer_ip("2a01:4f8::1").unwrap().to_string(), "2a01:4f8::1");
+        assert_eq!(peer_ip("[2a01:4f8::1]:10808").unwrap().to_string(), "2a01:4f8::1");
+        assert!(peer_ip("node1.fuego.network").is_err());
+    }
+}
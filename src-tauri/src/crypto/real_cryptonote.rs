@@ -5,21 +5,30 @@
 //!
 //! This module provides real CryptoNote wallet operations using the existing C++ codebase.
 
-use crate::utils::error::{WalletError, WalletResult};
-use std::ffi::{CStr, CString};
+use crate::crypto::address::{is_integrated_address, resolve_payment_id};
+use crate::crypto::backend::{new_backend, selected_backend, WalletBackend};
+use crate::crypto::daemon::DaemonClient;
+use crate::crypto::ffi_owned::{fixed_str, fixed_str_opt, FfiBox, FfiFree, OwnedCStr};
+use crate::utils::error::{RecoveryAction, WalletError, WalletResult};
+use crate::utils::locks::RwLockExt;
+use crate::utils::progress::ProgressReporter;
+use crate::utils::retry::RetryPolicy;
+use crate::wallet::WalletMetadata;
+use crate::wallet::coin_control;
+use std::collections::HashSet;
+use std::ffi::CString;
+use std::net::{TcpStream, ToSocketAddrs};
 use std::os::raw::{c_char, c_void};
 use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{mpsc, RwLock};
+use std::time::{Duration, Instant};
 
-#[repr(C)]
-#[derive(Copy, Clone)]
-struct CNetworkStatus {
-    is_connected: bool,
-    peer_count: u64,
-    sync_height: u64,
-    network_height: u64,
-    is_syncing: bool,
-    connection_type: [u8; 256],
-}
+/// Node connection timeout used until the settings provide one
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Rounds of lowering the sweep fee before settling on the last one that covers the send
+const MAX_SWEEP_FEE_ITERATIONS: usize = 10;
 
 // Advanced data structures for CryptoNote integration
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -90,6 +99,150 @@ pub struct NetworkInfo {
     pub estimated_sync_time: Option<u64>, // seconds remaining
 }
 
+/// Fee of a transaction split into what it pays for, in atomic units. Filled
+/// in by the native library, hence `repr(C)`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct FeeBreakdown {
+    /// Minimum fee of every transaction
+    pub base_fee: u64,
+    /// Fee for the size of the transaction
+    pub size_fee: u64,
+    /// Paid on top for faster inclusion
+    pub priority_fee: u64,
+    pub total: u64,
+    /// Every input carries `mixin + 1` ring members and signatures, so the
+    /// size grows with the mixin
+    pub estimated_size_bytes: u64,
+}
+
+/// Rough size of a transaction spending `inputs` outputs into `outputs` outputs
+/// with `mixin` decoys per input
+pub fn estimated_transaction_size(inputs: u64, outputs: u64, mixin: u64) -> u64 {
+    const PREFIX_BYTES: u64 = 100;
+    const INPUT_BYTES: u64 = 40;
+    /// Key offset and ring signature of one ring member
+    const RING_MEMBER_BYTES: u64 = 68;
+    const OUTPUT_BYTES: u64 = 40;
+    PREFIX_BYTES
+        + inputs * (INPUT_BYTES + (mixin + 1) * RING_MEMBER_BYTES)
+        + outputs * OUTPUT_BYTES
+}
+
+/// Largest transaction the network accepts: the full reward zone less the
+/// space kept for the coinbase, as `CRYPTONOTE_MAX_TX_SIZE_LIMIT`
+pub const MAX_TRANSACTION_SIZE: u64 = 800_000 - 600;
+
+/// Size of a send to `recipient_count` recipients and a change output, each of
+/// the `output_count_hint` outputs it spends (1 when not known) ringed with
+/// `mixin` decoys
+pub fn estimate_transaction_size(recipient_count: u64, mixin: u64, output_count_hint: Option<u64>) -> u64 {
+    estimated_transaction_size(output_count_hint.unwrap_or(1).max(1), recipient_count + 1, mixin)
+}
+
+/// Refuse a transaction the network would reject for its size
+pub fn check_transaction_size(size_bytes: u64) -> WalletResult<()> {
+    if size_bytes > MAX_TRANSACTION_SIZE {
+        return Err(WalletError::TransactionTooLarge { size: size_bytes, max: MAX_TRANSACTION_SIZE });
+    }
+    Ok(())
+}
+
+/// Fee of a send with the size it pays for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct FeeEstimate {
+    pub fee: u64,
+    pub estimated_size_bytes: u64,
+    /// Fee per 1024 bytes of the transaction
+    pub per_kb_fee: u64,
+}
+
+impl FeeEstimate {
+    pub fn new(fee: u64, estimated_size_bytes: u64) -> Self {
+        Self {
+            fee,
+            estimated_size_bytes,
+            per_kb_fee: fee.saturating_mul(1024) / estimated_size_bytes.max(1),
+        }
+    }
+}
+
+/// What a send would cost, before it is made
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TransactionPreview {
+    pub amount: u64,
+    /// Amount plus fee
+    pub total: u64,
+    #[serde(flatten)]
+    pub fee: FeeEstimate,
+    pub max_size_bytes: u64,
+}
+
+/// What sweeping the whole unlocked balance sends and costs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SweepEstimate {
+    pub sendable_amount: u64,
+    pub fee: u64,
+    /// The unlocked balance spent: amount plus fee
+    pub total: u64,
+}
+
+/// Single recipient of a multi-output transfer
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TransferDestination {
+    pub address: String,
+    pub amount: u64,
+}
+
+/// Address held by the wallet together with its label
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct WalletAddress {
+    /// 0 for the primary address
+    pub index: u32,
+    pub address: String,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub label: Option<String>,
+}
+
+fn empty_as_none<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value: Option<String> = serde::Deserialize::deserialize(deserializer)?;
+    Ok(value.filter(|v| !v.is_empty()))
+}
+
+/// Normalized kind of daemon connection reported by the native layer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum ConnectionType {
+    Disconnected,
+    Local,
+    Remote,
+    BootstrapDaemon,
+}
+
+impl ConnectionType {
+    /// Parse the free-form connection string written by the C++ wallet.
+    /// Unknown non-empty values are treated as a remote node.
+    pub fn from_raw(raw: &str) -> Self {
+        let value = raw.trim().to_ascii_lowercase();
+
+        if value.is_empty() || value == "disconnected" || value == "none" {
+            ConnectionType::Disconnected
+        } else if value.contains("bootstrap") {
+            ConnectionType::BootstrapDaemon
+        } else if value.contains("localhost")
+            || value.contains("127.0.0.1")
+            || value.contains("[::1]")
+            || value == "local"
+        {
+            ConnectionType::Local
+        } else {
+            ConnectionType::Remote
+        }
+    }
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct BlockInfo {
     pub height: u64,
@@ -114,7 +267,189 @@ pub struct MiningInfo {
     pub threads: u32,
 }
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+/// A transfer of this wallet that is still in the daemon's transaction pool
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct PoolTransfer {
+    pub hash: String,
+    /// Net effect on the wallet in atomic units; positive for incoming funds
+    pub amount: i64,
+    #[serde(default)]
+    pub payment_id: Option<String>,
+    /// When the daemon received the transaction (unix seconds)
+    pub timestamp: u64,
+    /// The daemon saw another transaction spending the same inputs
+    #[serde(default)]
+    pub double_spend_seen: bool,
+}
+
+/// Unspent output of the wallet, identified for coin control by its key image
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct UnspentOutput {
+    pub amount: u64,
+    pub global_index: u64,
+    pub key_image: String,
+    pub block_height: u64,
+    pub is_locked: bool,
+    /// Transaction that created the output, when the wallet library reports it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx_hash: Option<String>,
+}
+
+/// Block reward and coin emission at `height`, in atomic units. Daemons that
+/// do not report emission give `supported: false` and no figures.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct EmissionInfo {
+    #[serde(default)]
+    pub supported: bool,
+    #[serde(default)]
+    pub height: u64,
+    /// Reward of the block at `height`
+    #[serde(default)]
+    pub block_reward: Option<u64>,
+    /// Coins generated up to and including `height`
+    #[serde(default)]
+    pub total_emission: Option<u64>,
+    /// Emitted coins not locked in deposits
+    #[serde(default)]
+    pub circulating_supply: Option<u64>,
+    /// Reward of the next block
+    #[serde(default)]
+    pub next_reward: Option<u64>,
+}
+
+impl EmissionInfo {
+    /// Emission of a daemon that does not report it
+    pub fn unsupported(height: u64) -> Self {
+        Self { height, ..Self::default() }
+    }
+}
+
+/// Smallest term deposit when the daemon does not report its limits: 1 XFG
+pub const DEFAULT_MIN_DEPOSIT_AMOUNT: u64 = 10_000_000;
+/// Shortest deposit term in days when the daemon does not report its limits
+pub const DEFAULT_MIN_DEPOSIT_TERM: u32 = 1;
+/// Longest deposit term in days when the daemon does not report its limits
+pub const DEFAULT_MAX_DEPOSIT_TERM: u32 = 365;
+
+/// Term deposit limits in force at `height`, amounts in atomic units and
+/// terms in days. Daemons that do not report them give `supported: false`
+/// and the built-in defaults.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct DepositLimits {
+    #[serde(default)]
+    pub supported: bool,
+    #[serde(default)]
+    pub height: u64,
+    #[serde(default)]
+    pub min_amount: u64,
+    #[serde(default)]
+    pub min_term: u32,
+    #[serde(default)]
+    pub max_term: u32,
+    /// Interest rates by term, shortest term first
+    #[serde(default)]
+    pub rates: Vec<DepositRate>,
+}
+
+/// Yearly interest rate of deposits of at least `min_term` days
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct DepositRate {
+    pub min_term: u32,
+    pub rate: f64,
+}
+
+impl DepositLimits {
+    /// Limits of a daemon that does not report them
+    pub fn defaults(height: u64) -> Self {
+        Self {
+            supported: false,
+            height,
+            min_amount: DEFAULT_MIN_DEPOSIT_AMOUNT,
+            min_term: DEFAULT_MIN_DEPOSIT_TERM,
+            max_term: DEFAULT_MAX_DEPOSIT_TERM,
+            rates: Vec::new(),
+        }
+    }
+
+    /// Rate of a deposit of `term` days, from the longest matching entry
+    pub fn rate_for(&self, term: u32) -> Option<f64> {
+        self.rates.iter().filter(|r| r.min_term <= term).max_by_key(|r| r.min_term).map(|r| r.rate)
+    }
+}
+
+/// What checking a wallet file found; a corrupted file is an error instead
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct WalletIntegrityReport {
+    #[serde(default)]
+    pub file_path: String,
+    #[serde(default)]
+    pub file_size: u64,
+    /// The cached transfers and outputs agree with each other
+    #[serde(default)]
+    pub cache_consistent: bool,
+    #[serde(default)]
+    pub key_image_count: u64,
+    /// Height the wallet had processed when it was last saved
+    #[serde(default)]
+    pub last_processed_height: u64,
+    /// The cache should be rebuilt from the chain
+    #[serde(default)]
+    pub rescan_recommended: bool,
+}
+
+/// `fuego_wallet_verify` output: the report, or why the file is corrupted
+#[derive(Debug, serde::Deserialize)]
+struct VerifyOutcome {
+    #[serde(default)]
+    corrupted: bool,
+    #[serde(default)]
+    reason: Option<String>,
+    /// The key data could be read and decrypted
+    #[serde(default)]
+    keys_readable: bool,
+    #[serde(flatten)]
+    report: WalletIntegrityReport,
+}
+
+/// Turn `fuego_wallet_verify` output into the report or a `WalletCorrupted`
+/// error suggesting how to recover
+fn integrity_from_verify_json(json: &str) -> WalletResult<WalletIntegrityReport> {
+    let outcome: VerifyOutcome = serde_json::from_str(json)?;
+    if !outcome.corrupted && outcome.keys_readable {
+        let mut report = outcome.report;
+        report.rescan_recommended |= !report.cache_consistent;
+        return Ok(report);
+    }
+    let recovery = if outcome.keys_readable {
+        RecoveryAction::RestoreFromBackup
+    } else {
+        RecoveryAction::RestoreFromSeed
+    };
+    Err(WalletError::WalletCorrupted {
+        reason: outcome.reason.unwrap_or_else(|| "the key data cannot be read".to_string()),
+        recovery,
+    })
+}
+
+/// A wallet just created by `create_new_wallet`
+#[derive(Clone, serde::Serialize)]
+pub struct CreatedWallet {
+    pub address: String,
+    /// The generated seed phrase; `None` when the wallet was restored from one
+    pub seed_phrase: Option<String>,
+}
+
+/// Leaves the seed phrase out, so the wallet can be logged
+impl std::fmt::Debug for CreatedWallet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CreatedWallet")
+            .field("address", &self.address)
+            .field("seed_phrase", &self.seed_phrase.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct AddressBookEntry {
     pub address: String,
     pub label: String,
@@ -163,6 +498,27 @@ pub struct TransactionInfoFFI {
     pub extra: [c_char; 1024],
 }
 
+impl From<&TransactionInfoFFI> for TransactionInfo {
+    fn from(tx: &TransactionInfoFFI) -> Self {
+        Self {
+            id: fixed_str(&tx.id),
+            hash: fixed_str(&tx.hash),
+            amount: tx.amount,
+            fee: tx.fee,
+            height: tx.height,
+            timestamp: tx.timestamp,
+            confirmations: tx.confirmations,
+            is_confirmed: tx.is_confirmed,
+            is_pending: tx.is_pending,
+            payment_id: fixed_str_opt(&tx.payment_id),
+            destination_addresses: fixed_str_opt(&tx.destination_addresses).into_iter().collect(),
+            source_addresses: fixed_str_opt(&tx.source_addresses).into_iter().collect(),
+            unlock_time: Some(tx.unlock_time),
+            extra: fixed_str_opt(&tx.extra),
+        }
+    }
+}
+
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct NetworkInfoFFI {
@@ -177,6 +533,19 @@ pub struct NetworkInfoFFI {
     pub estimated_sync_time: u64,
 }
 
+/// `NetworkStatus` of `fuego_wallet_get_network_status`, which is not the
+/// same struct as `NetworkInfoFFI`
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct NetworkStatusFFI {
+    pub is_connected: bool,
+    pub peer_count: u64,
+    pub sync_height: u64,
+    pub network_height: u64,
+    pub is_syncing: bool,
+    pub connection_type: [c_char; 256],
+}
+
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct BlockInfoFFI {
@@ -212,7 +581,187 @@ pub struct SyncProgress {
     pub is_syncing: bool,
 }
 
-// FFI bindings for real CryptoNote operations
+/// What the wallet is busy with on its way to the network height
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncPhase {
+    /// No node to sync from
+    Disconnected,
+    /// Reaching a node and learning the network height
+    Connecting,
+    /// Fetching blocks up to the network height
+    DownloadingBlocks,
+    /// All blocks are in; searching them for the wallet's transactions
+    Scanning,
+    /// Caught up with the network
+    Synced,
+}
+
+impl SyncPhase {
+    /// Phase named by the native wallet, e.g. `downloading_blocks` or `Scanning`
+    pub fn from_raw(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().replace([' ', '-'], "_").as_str() {
+            "disconnected" | "idle" => Some(SyncPhase::Disconnected),
+            "connecting" => Some(SyncPhase::Connecting),
+            "downloading" | "downloading_blocks" => Some(SyncPhase::DownloadingBlocks),
+            "scanning" | "scanning_outputs" => Some(SyncPhase::Scanning),
+            "synced" | "synchronized" => Some(SyncPhase::Synced),
+            _ => None,
+        }
+    }
+
+    /// Phase of a wallet that does not name it: from the connection state,
+    /// then from how far the downloaded blocks and, when known, the scanned
+    /// ones lag behind the network. A wallet still syncing with every block
+    /// downloaded is scanning.
+    pub fn derive(connected: bool, is_syncing: bool, current_height: u64, total_height: u64, scanned_height: Option<u64>) -> Self {
+        if !connected {
+            return if is_syncing { SyncPhase::Connecting } else { SyncPhase::Disconnected };
+        }
+        if total_height == 0 {
+            SyncPhase::Connecting
+        } else if current_height < total_height {
+            SyncPhase::DownloadingBlocks
+        } else if is_syncing || scanned_height.is_some_and(|scanned| scanned < current_height) {
+            SyncPhase::Scanning
+        } else {
+            SyncPhase::Synced
+        }
+    }
+}
+
+/// Sync state from `get_sync_status_json`, with the phase it is in
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SyncStatus {
+    pub current_height: u64,
+    pub total_height: u64,
+    /// Height up to which blocks were searched for the wallet's transactions,
+    /// when the native wallet reports it
+    pub scanned_height: Option<u64>,
+    pub progress_percentage: f64,
+    pub estimated_seconds_remaining: u64,
+    pub is_syncing: bool,
+    pub connection_type: ConnectionType,
+    pub phase: SyncPhase,
+}
+
+impl SyncStatus {
+    /// Parse the JSON of `get_sync_status_json`, deriving the phase when the
+    /// native wallet does not name it
+    pub fn from_json(json: &str) -> WalletResult<Self> {
+        let value: serde_json::Value = serde_json::from_str(json)?;
+        let u64_of = |key: &str| value.get(key).and_then(|v| v.as_u64());
+        let str_of = |key: &str| value.get(key).and_then(|v| v.as_str());
+
+        let current_height = u64_of("current_height").unwrap_or(0);
+        let total_height = u64_of("total_height").unwrap_or(0);
+        let scanned_height = u64_of("scanned_height");
+        let is_syncing = value.get("is_syncing").and_then(|v| v.as_bool()).unwrap_or(false);
+        let connection_type = ConnectionType::from_raw(str_of("connection_type").unwrap_or(""));
+        let connected = value.get("is_connected").and_then(|v| v.as_bool())
+            .unwrap_or(connection_type != ConnectionType::Disconnected);
+        let phase = str_of("phase").and_then(SyncPhase::from_raw)
+            .unwrap_or_else(|| SyncPhase::derive(connected, is_syncing, current_height, total_height, scanned_height));
+
+        Ok(Self {
+            current_height,
+            total_height,
+            scanned_height,
+            progress_percentage: value.get("progress_percentage").and_then(|v| v.as_f64()).unwrap_or(0.0),
+            estimated_seconds_remaining: u64_of("estimated_seconds_remaining").unwrap_or(0),
+            is_syncing,
+            connection_type,
+            phase,
+        })
+    }
+
+    /// Status of a wallet that only reports `SyncProgress`
+    pub fn from_progress(progress: &SyncProgress, connected: bool) -> Self {
+        Self {
+            current_height: progress.current_height,
+            total_height: progress.total_height,
+            scanned_height: None,
+            progress_percentage: f64::from(progress.progress_percentage),
+            estimated_seconds_remaining: progress.estimated_time_remaining,
+            is_syncing: progress.is_syncing,
+            connection_type: if connected { ConnectionType::Remote } else { ConnectionType::Disconnected },
+            phase: SyncPhase::derive(connected, progress.is_syncing, progress.current_height, progress.total_height, None),
+        }
+    }
+}
+
+/// Sizes of the FFI structs as compiled into the native library
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct AbiSizesFFI {
+    pub wallet_info: usize,
+    pub transaction_info: usize,
+    pub network_status: usize,
+    pub network_info: usize,
+    pub block_info: usize,
+    pub mining_info: usize,
+    pub sync_progress: usize,
+    pub fee_breakdown: usize,
+}
+
+impl AbiSizesFFI {
+    /// Sizes of the Rust side of the bindings
+    pub fn expected() -> Self {
+        use std::mem::size_of;
+        Self {
+            wallet_info: size_of::<WalletInfoFFI>(),
+            transaction_info: size_of::<TransactionInfoFFI>(),
+            network_status: size_of::<NetworkStatusFFI>(),
+            network_info: size_of::<NetworkInfoFFI>(),
+            block_info: size_of::<BlockInfoFFI>(),
+            mining_info: size_of::<MiningInfoFFI>(),
+            sync_progress: size_of::<SyncProgress>(),
+            fee_breakdown: size_of::<FeeBreakdown>(),
+        }
+    }
+}
+
+/// Sizes of the FFI structs in the linked native library
+pub fn native_abi_sizes() -> AbiSizesFFI {
+    // SAFETY: takes no arguments and returns a plain struct by value
+    unsafe { fuego_wallet_abi_sizes() }
+}
+
+/// Catch the header and the bindings drifting apart before a struct is read
+/// with the wrong layout. Checked once, in debug builds only.
+fn debug_assert_abi_sizes() {
+    static CHECKED: std::sync::Once = std::sync::Once::new();
+    if cfg!(debug_assertions) {
+        CHECKED.call_once(|| {
+            debug_assert_eq!(native_abi_sizes(), AbiSizesFFI::expected(), "FFI struct sizes differ from fuego_wallet_real.h");
+        });
+    }
+}
+
+macro_rules! ffi_free {
+    ($($ty:ty => $free:ident),* $(,)?) => {$(
+        // SAFETY: the struct mirrors the C header and the function deletes
+        // what the library returned
+        unsafe impl FfiFree for $ty {
+            unsafe fn free(ptr: *mut Self) {
+                unsafe { $free(ptr) }
+            }
+        }
+    )*};
+}
+
+ffi_free! {
+    WalletInfoFFI => fuego_wallet_free_wallet_info,
+    TransactionInfoFFI => fuego_wallet_free_transaction_info,
+    NetworkStatusFFI => fuego_wallet_free_network_status,
+    NetworkInfoFFI => fuego_wallet_free_network_info,
+    BlockInfoFFI => fuego_wallet_free_block_info,
+    MiningInfoFFI => fuego_wallet_free_mining_info,
+    SyncProgress => fuego_wallet_free_sync_progress,
+}
+
+// FFI bindings for real CryptoNote operations. Written by hand so that builds
+// need no libclang; `--features bindgen` checks the structs against the header.
 unsafe extern "C" {
     // Wallet operations
     fn fuego_wallet_create(
@@ -226,6 +775,8 @@ unsafe extern "C" {
 
     fn fuego_wallet_close(wallet: *mut c_void);
 
+    fn fuego_wallet_save(wallet: *mut c_void) -> bool;
+
     fn fuego_wallet_is_open(wallet: *mut c_void) -> bool;
 
     // Wallet information
@@ -244,22 +795,30 @@ unsafe extern "C" {
         amount: u64,
         payment_id: *const c_char,
         mixin: u64,
-    ) -> *mut c_void;
+    ) -> *mut c_char;
+
+    fn fuego_wallet_send_many(
+        wallet: *mut c_void,
+        addresses: *const *const c_char,
+        amounts: *const u64,
+        count: usize,
+        mixin: u64,
+    ) -> *mut c_char;
 
     fn fuego_wallet_get_transactions(wallet: *mut c_void, limit: u64, offset: u64) -> *mut c_void;
 
     // Deposit operations
     fn fuego_wallet_get_deposits(wallet: *mut c_void) -> *mut c_void;
-    fn fuego_wallet_create_deposit(wallet: *mut c_void, amount: u64, term: u32) -> *mut c_void;
+    fn fuego_wallet_create_deposit(wallet: *mut c_void, amount: u64, term: u32) -> *mut c_char;
     fn fuego_wallet_withdraw_deposit(wallet: *mut c_void, deposit_id: *const c_char)
-        -> *mut c_void;
+        -> *mut c_char;
 
     // Network operations
     fn fuego_wallet_connect_node(wallet: *mut c_void, address: *const c_char, port: u16) -> bool;
 
     // In C header this returns a struct by value, but our C++ shim returns pointer for FFI safety
-    fn fuego_wallet_get_network_status(wallet: *mut c_void) -> *mut c_void;
-    fn fuego_wallet_get_network_info(wallet: *mut c_void) -> *mut c_void;
+    fn fuego_wallet_get_network_status(wallet: *mut c_void) -> *mut NetworkStatusFFI;
+    fn fuego_wallet_get_network_info(wallet: *mut c_void) -> *mut NetworkInfoFFI;
     fn fuego_wallet_disconnect_node(wallet: *mut c_void) -> bool;
 
     // Advanced wallet operations
@@ -272,10 +831,47 @@ unsafe extern "C" {
     fn fuego_wallet_get_transaction_by_hash(wallet: *mut c_void, tx_hash: *const c_char) -> *mut TransactionInfoFFI;
     fn fuego_wallet_get_transaction_by_id(wallet: *mut c_void, tx_id: *const c_char) -> *mut TransactionInfoFFI;
     fn fuego_wallet_cancel_transaction(wallet: *mut c_void, tx_id: *const c_char) -> bool;
+    fn fuego_wallet_rebroadcast_tx(wallet: *mut c_void, tx_hash: *const c_char) -> bool;
+    fn fuego_wallet_get_tx_proof(
+        wallet: *mut c_void,
+        tx_hash: *const c_char,
+        address: *const c_char,
+        message: *const c_char,
+    ) -> *mut c_char;
+    fn fuego_wallet_check_tx_proof(
+        wallet: *mut c_void,
+        tx_hash: *const c_char,
+        address: *const c_char,
+        message: *const c_char,
+        proof: *const c_char,
+    ) -> i32;
 
     // Address management
     fn fuego_wallet_create_address(wallet: *mut c_void, label: *const c_char) -> *mut c_char;
-    fn fuego_wallet_get_addresses(wallet: *mut c_void) -> *mut c_void;
+    fn fuego_wallet_get_addresses(wallet: *mut c_void) -> *mut c_char;
+    fn fuego_wallet_get_pool_transfers(wallet: *mut c_void) -> *mut c_char;
+    fn fuego_wallet_get_unspent_outputs(wallet: *mut c_void) -> *mut c_char;
+    fn fuego_wallet_get_emission(wallet: *mut c_void) -> *mut c_char;
+    fn fuego_wallet_get_deposit_limits(wallet: *mut c_void) -> *mut c_char;
+    fn fuego_wallet_verify(file_path: *const c_char, password: *const c_char) -> *mut c_char;
+    fn fuego_wallet_send_from_outputs(
+        wallet: *mut c_void,
+        address: *const c_char,
+        amount: u64,
+        payment_id: *const c_char,
+        mixin: u64,
+        key_images: *const *const c_char,
+        key_image_count: usize,
+    ) -> *mut c_char;
+    fn fuego_wallet_send_many_from_outputs(
+        wallet: *mut c_void,
+        addresses: *const *const c_char,
+        amounts: *const u64,
+        count: usize,
+        mixin: u64,
+        key_images: *const *const c_char,
+        key_image_count: usize,
+    ) -> *mut c_char;
     fn fuego_wallet_delete_address(wallet: *mut c_void, address: *const c_char) -> bool;
     fn fuego_wallet_set_address_label(wallet: *mut c_void, address: *const c_char, label: *const c_char) -> bool;
 
@@ -288,7 +884,7 @@ unsafe extern "C" {
     // Mining operations
     fn fuego_wallet_start_mining(wallet: *mut c_void, threads: u32, background: bool) -> bool;
     fn fuego_wallet_stop_mining(wallet: *mut c_void) -> bool;
-    fn fuego_wallet_get_mining_info(wallet: *mut c_void) -> *mut MiningInfo;
+    fn fuego_wallet_get_mining_info(wallet: *mut c_void) -> *mut MiningInfoFFI;
     fn fuego_wallet_set_mining_pool(wallet: *mut c_void, pool_address: *const c_char, worker_name: *const c_char) -> bool;
     fn fuego_wallet_get_mining_stats_json(wallet: *mut c_void) -> *mut c_char;
     fn fuego_wallet_free_mining_stats_json(json_str: *mut c_char);
@@ -296,6 +892,7 @@ unsafe extern "C" {
     // Secure key management
     fn fuego_wallet_generate_seed_phrase() -> *mut c_char;
     fn fuego_wallet_validate_seed_phrase(seed_phrase: *const c_char) -> bool;
+    fn fuego_wallet_address_from_seed(seed_phrase: *const c_char, invalid_word: *mut i32) -> *mut c_char;
     fn fuego_wallet_derive_keys_from_seed(wallet: *mut c_void, seed_phrase: *const c_char, password: *const c_char) -> bool;
     fn fuego_wallet_get_seed_phrase(wallet: *mut c_void, password: *const c_char) -> *mut c_char;
     fn fuego_wallet_get_view_key(wallet: *mut c_void) -> *mut c_char;
@@ -310,8 +907,7 @@ unsafe extern "C" {
     fn fuego_wallet_free_transaction_info(tx: *mut TransactionInfoFFI);
     fn fuego_wallet_free_network_info(info: *mut NetworkInfoFFI);
     fn fuego_wallet_free_block_info(block: *mut BlockInfoFFI);
-    fn fuego_wallet_free_mining_info(info: *mut MiningInfo);
-    fn fuego_wallet_free_addresses(addresses: *mut c_void);
+    fn fuego_wallet_free_mining_info(info: *mut MiningInfoFFI);
 
     // Sync progress functions
     fn fuego_wallet_get_sync_progress(wallet: *mut c_void) -> *mut SyncProgress;
@@ -332,33 +928,52 @@ unsafe extern "C" {
     // Utility functions
     fn fuego_wallet_free_string(s: *mut c_char);
     fn fuego_wallet_free_transactions(txs: *mut c_void);
-    fn fuego_wallet_free_network_status(status: *mut c_void);
+    fn fuego_wallet_free_network_status(status: *mut NetworkStatusFFI);
 
     // Transaction history
+    // Entries are released with `fuego_wallet_free_transaction_info`
     fn fuego_wallet_get_transaction_history(wallet: *mut c_void, limit: u64, offset: u64) -> *mut TransactionInfoFFI;
-    fn fuego_wallet_free_transaction_history(tx: *mut TransactionInfoFFI);
     
-    // Missing fee estimation function
-    fn fuego_wallet_estimate_transaction_fee(wallet: *mut c_void, address: *const c_char, amount: u64, mixin: u64) -> u64;
+    // Struct sizes, to check the bindings against the header
+    fn fuego_wallet_abi_sizes() -> AbiSizesFFI;
+
+    // Fee estimation, split into its components
+    fn fuego_wallet_estimate_fee_breakdown(
+        wallet: *mut c_void,
+        address: *const c_char,
+        amount: u64,
+        mixin: u64,
+        breakdown: *mut FeeBreakdown,
+    ) -> bool;
 }
 
 /// Real CryptoNote wallet implementation
-pub struct RealCryptoNoteWallet {
+/// Wallet operations on the native CryptoNote library
+pub struct FfiWalletBackend {
     wallet_ptr: *mut c_void,
-    is_connected: bool,
 }
 
-impl RealCryptoNoteWallet {
-    /// Create a new real CryptoNote wallet instance
+impl FfiWalletBackend {
     pub fn new() -> Self {
+        debug_assert_abi_sizes();
         Self {
             wallet_ptr: ptr::null_mut(),
-            is_connected: false,
         }
     }
+}
 
-    /// Create a new wallet with real CryptoNote implementation
-    pub fn create_wallet(
+impl Default for FfiWalletBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// SAFETY: the wallet handle is only used through `&self`/`&mut self` of its
+// single owner; moving the owner to another thread does not share it.
+unsafe impl Send for FfiWalletBackend {}
+
+impl WalletBackend for FfiWalletBackend {
+    fn create_wallet(
         &mut self,
         password: &str,
         file_path: &str,
@@ -391,8 +1006,7 @@ impl RealCryptoNoteWallet {
         Ok(())
     }
 
-    /// Open an existing wallet with real CryptoNote implementation
-    pub fn open_wallet(&mut self, file_path: &str, password: &str) -> WalletResult<()> {
+    fn open_wallet(&mut self, file_path: &str, password: &str) -> WalletResult<()> {
         let file_path_c = CString::new(file_path)?;
         let password_c = CString::new(password)?;
 
@@ -410,20 +1024,17 @@ impl RealCryptoNoteWallet {
         Ok(())
     }
 
-    /// Close the wallet
-    pub fn close_wallet(&mut self) {
+    fn close_wallet(&mut self) {
         if !self.wallet_ptr.is_null() {
             unsafe {
                 fuego_wallet_close(self.wallet_ptr);
             }
             self.wallet_ptr = ptr::null_mut();
-            self.is_connected = false;
             log::info!("Real CryptoNote wallet closed");
         }
     }
 
-    /// Check if wallet is open
-    pub fn is_open(&self) -> bool {
+    fn is_open(&self) -> bool {
         if self.wallet_ptr.is_null() {
             return false;
         }
@@ -431,8 +1042,7 @@ impl RealCryptoNoteWallet {
         unsafe { fuego_wallet_is_open(self.wallet_ptr) }
     }
 
-    /// Get wallet balance from real CryptoNote implementation
-    pub fn get_balance(&self) -> WalletResult<u64> {
+    fn get_balance(&self) -> WalletResult<u64> {
         if self.wallet_ptr.is_null() {
             return Err(WalletError::WalletNotOpen);
         }
@@ -443,8 +1053,7 @@ impl RealCryptoNoteWallet {
         Ok(balance)
     }
 
-    /// Get unlocked balance from real CryptoNote implementation
-    pub fn get_unlocked_balance(&self) -> WalletResult<u64> {
+    fn get_unlocked_balance(&self) -> WalletResult<u64> {
         if self.wallet_ptr.is_null() {
             return Err(WalletError::WalletNotOpen);
         }
@@ -455,24 +1064,22 @@ impl RealCryptoNoteWallet {
         Ok(unlocked_balance)
     }
 
-    /// Get wallet address from real CryptoNote implementation
-    pub fn get_address(&self) -> WalletResult<String> {
+    fn get_address(&self) -> WalletResult<String> {
         if self.wallet_ptr.is_null() {
             return Err(WalletError::WalletNotOpen);
         }
 
-        let mut buffer = vec![0u8; 256];
+        let mut buffer = [0 as c_char; 256];
         let success = unsafe {
             fuego_wallet_get_address(
                 self.wallet_ptr,
-                buffer.as_mut_ptr() as *mut c_char,
+                buffer.as_mut_ptr(),
                 buffer.len(),
             )
         };
 
         if success {
-            let c_str = unsafe { CStr::from_ptr(buffer.as_ptr() as *const c_char) };
-            let address = c_str.to_string_lossy().to_string();
+            let address = fixed_str(&buffer);
             log::debug!("Real wallet address: {}", address);
             Ok(address)
         } else {
@@ -482,8 +1089,7 @@ impl RealCryptoNoteWallet {
         }
     }
 
-    /// Send a transaction using real CryptoNote implementation
-    pub fn send_transaction(
+    fn send_transaction(
         &self,
         address: &str,
         amount: u64,
@@ -509,16 +1115,9 @@ impl RealCryptoNoteWallet {
                 mixin,
             )
         };
-
-        if tx_ptr.is_null() {
-            return Err(WalletError::TransactionFailed(
-                "Failed to send real transaction".to_string(),
-            ));
-        }
-
-        // Extract transaction hash and free
-        let tx_hash = unsafe { CStr::from_ptr(tx_ptr as *const c_char).to_string_lossy().to_string() };
-        unsafe { fuego_wallet_free_string(tx_ptr as *mut c_char); }
+        let tx_hash = unsafe { OwnedCStr::from_raw(tx_ptr, fuego_wallet_free_string) }
+            .ok_or_else(|| WalletError::TransactionFailed("Failed to send real transaction".to_string()))?
+            .to_string_lossy();
         log::info!(
             "Real transaction sent: {} to {} amount: {}",
             tx_hash,
@@ -528,18 +1127,54 @@ impl RealCryptoNoteWallet {
         Ok(tx_hash)
     }
 
-    /// Connect to Fuego network node
-    pub fn connect_to_node(&mut self, address: &str, port: u16) -> WalletResult<()> {
+    fn send_many(&self, outputs: &[(String, u64)], mixin: u64) -> WalletResult<String> {
+        if self.wallet_ptr.is_null() {
+            return Err(WalletError::WalletNotOpen);
+        }
+
+        let total: u64 = outputs.iter().map(|(_, amount)| *amount).sum();
+
+        let addresses_c = outputs
+            .iter()
+            .map(|(address, _)| CString::new(address.as_str()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let address_ptrs: Vec<*const c_char> = addresses_c.iter().map(|a| a.as_ptr()).collect();
+        let amounts: Vec<u64> = outputs.iter().map(|(_, amount)| *amount).collect();
+
+        let tx_ptr = unsafe {
+            fuego_wallet_send_many(
+                self.wallet_ptr,
+                address_ptrs.as_ptr(),
+                amounts.as_ptr(),
+                outputs.len(),
+                mixin,
+            )
+        };
+        let tx_hash = unsafe { OwnedCStr::from_raw(tx_ptr, fuego_wallet_free_string) }
+            .ok_or_else(|| WalletError::TransactionFailed("Failed to send real transaction".to_string()))?
+            .to_string_lossy();
+        log::info!(
+            "Real transaction sent: {} to {} recipients, total: {}",
+            tx_hash,
+            outputs.len(),
+            total
+        );
+        Ok(tx_hash)
+    }
+
+    fn connect_to_node(&mut self, address: &str, port: u16, timeout: Duration) -> WalletResult<()> {
         if self.wallet_ptr.is_null() {
             return Err(WalletError::WalletNotOpen);
         }
 
         let address_c = CString::new(address)?;
+        // The native connect has no timeout of its own, so make sure the node
+        // answers before handing it over
+        probe_node(address, port, timeout)?;
         let success =
             unsafe { fuego_wallet_connect_node(self.wallet_ptr, address_c.as_ptr(), port) };
 
         if success {
-            self.is_connected = true;
             log::info!("Connected to Fuego node: {}:{}", address, port);
             Ok(())
         } else {
@@ -550,52 +1185,31 @@ impl RealCryptoNoteWallet {
         }
     }
 
-    /// Connect to Fuego network (convenience method)
-    pub fn connect_to_network(&mut self, node_url: &str) -> WalletResult<()> {
-        // Parse URL to extract address and port
-        let url = node_url.replace("http://", "").replace("https://", "");
-        let parts: Vec<&str> = url.split(':').collect();
-
-        let address = parts[0];
-        let port = if parts.len() > 1 {
-            parts[1].parse::<u16>().unwrap_or(18180)
-        } else {
-            18180
-        };
-
-        self.connect_to_node(address, port)
-    }
-
-    /// Get network status from real CryptoNote implementation
-    pub fn get_network_status(&self) -> WalletResult<serde_json::Value> {
+    fn get_network_status(&self) -> WalletResult<serde_json::Value> {
         if self.wallet_ptr.is_null() {
             return Err(WalletError::WalletNotOpen);
         }
 
-        let status_ptr = unsafe { fuego_wallet_get_network_status(self.wallet_ptr) };
-        if status_ptr.is_null() {
+        let Some(info) = (unsafe { FfiBox::from_raw(fuego_wallet_get_network_status(self.wallet_ptr)) }) else {
             return Err(WalletError::NetworkError(
                 "Failed to get real network status".to_string(),
             ));
-        }
+        };
 
-        // Interpret as NetworkInfoFFI and convert
-        let info = unsafe { &*(status_ptr as *const NetworkInfoFFI) };
-        let connection_type = unsafe { CStr::from_ptr(info.connection_type.as_ptr()) }.to_string_lossy().to_string();
+        let connection_type = fixed_str(&info.connection_type);
         let json = serde_json::json!({
             "is_connected": info.is_connected,
             "peer_count": info.peer_count,
             "sync_height": info.sync_height,
             "network_height": info.network_height,
             "is_syncing": info.is_syncing,
-            "connection_type": connection_type,
+            "connection_type": ConnectionType::from_raw(&connection_type),
+            "connection_type_raw": connection_type,
         });
-        unsafe { fuego_wallet_free_network_status(status_ptr); }
         Ok(json)
     }
 
-    /// Get all term deposits from the wallet
-    pub fn get_deposits(&self) -> WalletResult<Vec<DepositInfo>> {
+    fn get_deposits(&self) -> WalletResult<Vec<DepositInfo>> {
         if self.wallet_ptr.is_null() {
             return Err(WalletError::WalletNotOpen);
         }
@@ -616,29 +1230,19 @@ impl RealCryptoNoteWallet {
 
     // ===== PHASE 3.1: ADVANCED CRYPTONOTE INTEGRATION =====
 
-    /// Get comprehensive wallet information
-    pub fn get_wallet_info(&self) -> WalletResult<WalletInfo> {
+    fn get_wallet_info(&self) -> WalletResult<WalletInfo> {
         if self.wallet_ptr.is_null() {
             return Err(WalletError::WalletNotOpen);
         }
 
-        let info_ptr = unsafe { fuego_wallet_get_wallet_info(self.wallet_ptr) };
-
-        if info_ptr.is_null() {
+        let Some(wallet_info) = (unsafe { FfiBox::from_raw(fuego_wallet_get_wallet_info(self.wallet_ptr)) }) else {
             return Err(WalletError::Generic(
                 "Failed to get wallet information".to_string(),
             ));
-        }
-
-        let wallet_info = unsafe { &*(info_ptr as *const WalletInfoFFI) };
-
-        // Convert C string to Rust string
-        let address = unsafe { CStr::from_ptr(wallet_info.address.as_ptr()) }
-            .to_string_lossy()
-            .to_string();
+        };
 
-        let result = WalletInfo {
-            address,
+        Ok(WalletInfo {
+            address: fixed_str(&wallet_info.address),
             balance: wallet_info.balance,
             unlocked_balance: wallet_info.unlocked_balance,
             locked_balance: wallet_info.locked_balance,
@@ -652,57 +1256,34 @@ impl RealCryptoNoteWallet {
             is_connected: wallet_info.is_connected,
             peer_count: wallet_info.peer_count,
             last_block_time: Some(wallet_info.last_block_time),
-        };
+        })
+    }
 
-        unsafe {
-            fuego_wallet_free_wallet_info(info_ptr);
-        }
-
-        Ok(result)
-    }
-
-    /// Get detailed network information
-    pub fn get_network_info(&self) -> WalletResult<NetworkInfo> {
+    fn get_network_info(&self) -> WalletResult<NetworkInfo> {
         if self.wallet_ptr.is_null() {
             return Err(WalletError::WalletNotOpen);
         }
 
-        let info_ptr = unsafe { fuego_wallet_get_network_info(self.wallet_ptr) };
-
-        if info_ptr.is_null() {
+        let Some(network_info) = (unsafe { FfiBox::from_raw(fuego_wallet_get_network_info(self.wallet_ptr)) }) else {
             return Err(WalletError::Generic(
                 "Failed to get network information".to_string(),
             ));
-        }
-
-        let network_info = unsafe { &*(info_ptr as *const NetworkInfoFFI) };
-
-        // Convert C string to Rust string
-        let connection_type = unsafe { CStr::from_ptr(network_info.connection_type.as_ptr()) }
-            .to_string_lossy()
-            .to_string();
+        };
 
-        let result = NetworkInfo {
+        Ok(NetworkInfo {
             is_connected: network_info.is_connected,
             peer_count: network_info.peer_count,
             sync_height: network_info.sync_height,
             network_height: network_info.network_height,
             is_syncing: network_info.is_syncing,
-            connection_type,
+            connection_type: fixed_str(&network_info.connection_type),
             last_sync_time: Some(network_info.last_sync_time),
             sync_speed: network_info.sync_speed,
             estimated_sync_time: Some(network_info.estimated_sync_time),
-        };
-
-        unsafe {
-            fuego_wallet_free_network_info(info_ptr as *mut NetworkInfoFFI);
-        }
-
-        Ok(result)
+        })
     }
 
-    /// Refresh wallet data from blockchain
-    pub fn refresh(&mut self) -> WalletResult<()> {
+    fn refresh(&mut self) -> WalletResult<()> {
         if self.wallet_ptr.is_null() {
             return Err(WalletError::WalletNotOpen);
         }
@@ -717,8 +1298,7 @@ impl RealCryptoNoteWallet {
         Ok(())
     }
 
-    /// Rescan blockchain from specific height
-    pub fn rescan_blockchain(&mut self, start_height: u64) -> WalletResult<()> {
+    fn rescan_blockchain(&mut self, start_height: u64) -> WalletResult<()> {
         if self.wallet_ptr.is_null() {
             return Err(WalletError::WalletNotOpen);
         }
@@ -735,83 +1315,50 @@ impl RealCryptoNoteWallet {
         Ok(())
     }
 
-    /// Get transaction by hash
-    pub fn get_transaction_by_hash(&self, tx_hash: &str) -> WalletResult<TransactionInfo> {
+    fn get_transaction_by_hash(&self, tx_hash: &str) -> WalletResult<TransactionInfo> {
         if self.wallet_ptr.is_null() {
             return Err(WalletError::WalletNotOpen);
         }
 
         let tx_hash_c = CString::new(tx_hash)?;
-        let tx_ptr =
-            unsafe { fuego_wallet_get_transaction_by_hash(self.wallet_ptr, tx_hash_c.as_ptr()) };
-
-        if tx_ptr.is_null() {
+        let Some(tx) = (unsafe { FfiBox::from_raw(fuego_wallet_get_transaction_by_hash(self.wallet_ptr, tx_hash_c.as_ptr())) }) else {
             return Err(WalletError::TransactionFailed(
                 "Transaction not found".to_string(),
             ));
-        }
-
-        let tx = unsafe { &*(tx_ptr as *const TransactionInfoFFI) };
-        let id = unsafe { CStr::from_ptr(tx.id.as_ptr()) }.to_string_lossy().to_string();
-        let hash = unsafe { CStr::from_ptr(tx.hash.as_ptr()) }.to_string_lossy().to_string();
-        let payment_id = if tx.payment_id[0] != 0 {
-            Some(unsafe { CStr::from_ptr(tx.payment_id.as_ptr()) }.to_string_lossy().to_string())
-        } else { None };
-        let destination_addresses = if tx.destination_addresses[0] != 0 {
-            vec![unsafe { CStr::from_ptr(tx.destination_addresses.as_ptr()) }.to_string_lossy().to_string()]
-        } else { vec![] };
-        let source_addresses = if tx.source_addresses[0] != 0 {
-            vec![unsafe { CStr::from_ptr(tx.source_addresses.as_ptr()) }.to_string_lossy().to_string()]
-        } else { vec![] };
-        let extra = if tx.extra[0] != 0 {
-            Some(unsafe { CStr::from_ptr(tx.extra.as_ptr()) }.to_string_lossy().to_string())
-        } else { None };
-        let out = TransactionInfo {
-            id,
-            hash,
-            amount: tx.amount,
-            fee: tx.fee,
-            height: tx.height,
-            timestamp: tx.timestamp,
-            confirmations: tx.confirmations,
-            is_confirmed: tx.is_confirmed,
-            is_pending: tx.is_pending,
-            payment_id,
-            destination_addresses,
-            source_addresses,
-            unlock_time: Some(tx.unlock_time),
-            extra,
         };
-        unsafe { fuego_wallet_free_transaction_info(tx_ptr); }
-        Ok(out)
+
+        Ok(TransactionInfo::from(&*tx))
     }
 
-    /// Estimate transaction fee
-    pub fn estimate_transaction_fee(
+    fn estimate_transaction_fee(
         &self,
         address: &str,
         amount: u64,
         mixin: u64,
-    ) -> WalletResult<u64> {
+    ) -> WalletResult<FeeBreakdown> {
         if self.wallet_ptr.is_null() {
             return Err(WalletError::WalletNotOpen);
         }
 
         let address_c = CString::new(address)?;
-        let fee = unsafe {
-            fuego_wallet_estimate_transaction_fee(
+        let mut breakdown = FeeBreakdown::default();
+        let success = unsafe {
+            fuego_wallet_estimate_fee_breakdown(
                 self.wallet_ptr,
                 address_c.as_ptr(),
                 amount,
                 mixin,
+                &mut breakdown,
             )
         };
 
-        Ok(fee)
+        if !success {
+            return Err(WalletError::Generic("Failed to estimate transaction fee".to_string()));
+        }
+        Ok(breakdown)
     }
 
-    /// Create new address with label
-    pub fn create_address(&self, label: Option<&str>) -> WalletResult<String> {
+    fn create_address(&self, label: Option<&str>) -> WalletResult<String> {
         if self.wallet_ptr.is_null() {
             return Err(WalletError::WalletNotOpen);
         }
@@ -821,51 +1368,275 @@ impl RealCryptoNoteWallet {
             None => CString::new("")?,
         };
 
-        let address_ptr = unsafe { fuego_wallet_create_address(self.wallet_ptr, label_c.as_ptr()) };
+        let address = unsafe { OwnedCStr::from_raw(fuego_wallet_create_address(self.wallet_ptr, label_c.as_ptr()), fuego_wallet_free_string) }
+            .ok_or_else(|| WalletError::Generic("Failed to create address".to_string()))?
+            .to_string_lossy();
+
+        Ok(address)
+    }
 
-        if address_ptr.is_null() {
-            return Err(WalletError::Generic("Failed to create address".to_string()));
+    fn get_addresses(&self) -> WalletResult<Vec<WalletAddress>> {
+        if self.wallet_ptr.is_null() {
+            return Err(WalletError::WalletNotOpen);
         }
 
-        let address = unsafe { CStr::from_ptr(address_ptr).to_string_lossy().to_string() };
+        let json_str = unsafe { OwnedCStr::from_raw(fuego_wallet_get_addresses(self.wallet_ptr), fuego_wallet_free_string) }
+            .ok_or_else(|| WalletError::Generic("Failed to get addresses".to_string()))?
+            .to_string_lossy();
 
-        unsafe {
-            fuego_wallet_free_string(address_ptr);
+        Ok(serde_json::from_str(&json_str)?)
+    }
+
+    fn get_pool_transfers(&self) -> WalletResult<Vec<PoolTransfer>> {
+        if self.wallet_ptr.is_null() {
+            return Err(WalletError::WalletNotOpen);
         }
 
-        Ok(address)
+        let json_str = unsafe { OwnedCStr::from_raw(fuego_wallet_get_pool_transfers(self.wallet_ptr), fuego_wallet_free_string) }
+            .ok_or_else(|| WalletError::Generic("Failed to get pool transfers".to_string()))?
+            .to_string_lossy();
+
+        Ok(serde_json::from_str(&json_str)?)
     }
 
-    /// Get block information by height
-    pub fn get_block_info(&self, height: u64) -> WalletResult<BlockInfo> {
+    fn verify_wallet(&self, file_path: &str, password: &str) -> WalletResult<WalletIntegrityReport> {
+        let file_path_c = CString::new(file_path)?;
+        let password_c = CString::new(password)?;
+
+        let json_str = unsafe { OwnedCStr::from_raw(fuego_wallet_verify(file_path_c.as_ptr(), password_c.as_ptr()), fuego_wallet_free_string) }
+            .ok_or_else(|| WalletError::Generic("Failed to verify wallet".to_string()))?
+            .to_string_lossy();
+
+        integrity_from_verify_json(&json_str)
+    }
+
+    fn get_emission(&self) -> WalletResult<EmissionInfo> {
         if self.wallet_ptr.is_null() {
             return Err(WalletError::WalletNotOpen);
         }
 
-        let block_ptr = unsafe { fuego_wallet_get_block_info(self.wallet_ptr, height) };
+        let json_str = unsafe { OwnedCStr::from_raw(fuego_wallet_get_emission(self.wallet_ptr), fuego_wallet_free_string) }
+            .ok_or_else(|| WalletError::Generic("Failed to get emission info".to_string()))?
+            .to_string_lossy();
 
-        if block_ptr.is_null() {
-            return Err(WalletError::Generic("Block not found".to_string()));
+        Ok(serde_json::from_str(&json_str)?)
+    }
+
+    fn get_deposit_limits(&self) -> WalletResult<DepositLimits> {
+        if self.wallet_ptr.is_null() {
+            return Err(WalletError::WalletNotOpen);
+        }
+
+        let json_str = unsafe { OwnedCStr::from_raw(fuego_wallet_get_deposit_limits(self.wallet_ptr), fuego_wallet_free_string) }
+            .ok_or_else(|| WalletError::Generic("Failed to get deposit limits".to_string()))?
+            .to_string_lossy();
+
+        let limits: DepositLimits = serde_json::from_str(&json_str)?;
+        Ok(if limits.supported { limits } else { DepositLimits::defaults(limits.height) })
+    }
+
+    fn get_unspent_outputs(&self) -> WalletResult<Vec<UnspentOutput>> {
+        if self.wallet_ptr.is_null() {
+            return Err(WalletError::WalletNotOpen);
+        }
+
+        let json_str = unsafe { OwnedCStr::from_raw(fuego_wallet_get_unspent_outputs(self.wallet_ptr), fuego_wallet_free_string) }
+            .ok_or_else(|| WalletError::Generic("Failed to get unspent outputs".to_string()))?
+            .to_string_lossy();
+
+        Ok(serde_json::from_str(&json_str)?)
+    }
+
+    fn send_from_outputs(
+        &self,
+        address: &str,
+        amount: u64,
+        payment_id: Option<&str>,
+        mixin: u64,
+        key_images: &[String],
+    ) -> WalletResult<String> {
+        if self.wallet_ptr.is_null() {
+            return Err(WalletError::WalletNotOpen);
+        }
+
+        let address_c = CString::new(address)?;
+        let payment_id_c = payment_id.map(CString::new).transpose()?;
+        let key_images_c = key_images
+            .iter()
+            .map(|k| CString::new(k.as_str()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let key_image_ptrs: Vec<*const c_char> = key_images_c.iter().map(|k| k.as_ptr()).collect();
+
+        let tx_ptr = unsafe {
+            fuego_wallet_send_from_outputs(
+                self.wallet_ptr,
+                address_c.as_ptr(),
+                amount,
+                payment_id_c.as_ref().map_or(ptr::null(), |p| p.as_ptr()),
+                mixin,
+                key_image_ptrs.as_ptr(),
+                key_image_ptrs.len(),
+            )
+        };
+        let tx_hash = unsafe { OwnedCStr::from_raw(tx_ptr, fuego_wallet_free_string) }
+            .ok_or_else(|| WalletError::TransactionFailed("Failed to send real transaction".to_string()))?
+            .to_string_lossy();
+        log::info!(
+            "Real transaction sent: {} from {} selected outputs, amount: {}",
+            tx_hash,
+            key_images.len(),
+            amount
+        );
+        Ok(tx_hash)
+    }
+
+    fn send_many_from_outputs(&self, outputs: &[(String, u64)], mixin: u64, key_images: &[String]) -> WalletResult<String> {
+        if self.wallet_ptr.is_null() {
+            return Err(WalletError::WalletNotOpen);
+        }
+
+        let addresses_c = outputs
+            .iter()
+            .map(|(address, _)| CString::new(address.as_str()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let address_ptrs: Vec<*const c_char> = addresses_c.iter().map(|a| a.as_ptr()).collect();
+        let amounts: Vec<u64> = outputs.iter().map(|(_, amount)| *amount).collect();
+        let key_images_c = key_images
+            .iter()
+            .map(|k| CString::new(k.as_str()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let key_image_ptrs: Vec<*const c_char> = key_images_c.iter().map(|k| k.as_ptr()).collect();
+
+        let tx_ptr = unsafe {
+            fuego_wallet_send_many_from_outputs(
+                self.wallet_ptr,
+                address_ptrs.as_ptr(),
+                amounts.as_ptr(),
+                outputs.len(),
+                mixin,
+                key_image_ptrs.as_ptr(),
+                key_image_ptrs.len(),
+            )
+        };
+        let tx_hash = unsafe { OwnedCStr::from_raw(tx_ptr, fuego_wallet_free_string) }
+            .ok_or_else(|| WalletError::TransactionFailed("Failed to send real transaction".to_string()))?
+            .to_string_lossy();
+        log::info!(
+            "Real transaction sent: {} to {} recipients from {} selected outputs",
+            tx_hash,
+            outputs.len(),
+            key_images.len()
+        );
+        Ok(tx_hash)
+    }
+
+    fn rebroadcast_transaction(&self, tx_hash: &str) -> WalletResult<()> {
+        if self.wallet_ptr.is_null() {
+            return Err(WalletError::WalletNotOpen);
+        }
+
+        let tx_hash_c = CString::new(tx_hash)?;
+        let success = unsafe { fuego_wallet_rebroadcast_tx(self.wallet_ptr, tx_hash_c.as_ptr()) };
+
+        if !success {
+            return Err(WalletError::TransactionFailed(format!(
+                "Failed to rebroadcast transaction {}",
+                tx_hash
+            )));
+        }
+
+        log::info!("Rebroadcast transaction {}", tx_hash);
+        Ok(())
+    }
+
+    fn get_tx_proof(&self, tx_hash: &str, address: &str, message: Option<&str>) -> WalletResult<String> {
+        if self.wallet_ptr.is_null() {
+            return Err(WalletError::WalletNotOpen);
+        }
+
+        let tx_hash_c = CString::new(tx_hash)?;
+        let address_c = CString::new(address)?;
+        let message_c = message.map(CString::new).transpose()?;
+        let proof = unsafe { OwnedCStr::from_raw(fuego_wallet_get_tx_proof(
+            self.wallet_ptr,
+            tx_hash_c.as_ptr(),
+            address_c.as_ptr(),
+            message_c.as_ref().map_or(ptr::null(), |m| m.as_ptr()),
+        ), fuego_wallet_free_string) }
+            .ok_or_else(|| WalletError::CryptoError(format!("Failed to generate a proof for transaction {}", tx_hash)))?
+            .to_string_lossy();
+
+        Ok(proof)
+    }
+
+    fn check_tx_proof(&self, tx_hash: &str, address: &str, message: Option<&str>, proof: &str) -> WalletResult<bool> {
+        if self.wallet_ptr.is_null() {
+            return Err(WalletError::WalletNotOpen);
+        }
+
+        let tx_hash_c = CString::new(tx_hash)?;
+        let address_c = CString::new(address)?;
+        let message_c = message.map(CString::new).transpose()?;
+        let proof_c = CString::new(proof)?;
+        let result = unsafe { fuego_wallet_check_tx_proof(
+            self.wallet_ptr,
+            tx_hash_c.as_ptr(),
+            address_c.as_ptr(),
+            message_c.as_ref().map_or(ptr::null(), |m| m.as_ptr()),
+            proof_c.as_ptr(),
+        ) };
+
+        match result {
+            1 => Ok(true),
+            0 => Ok(false),
+            _ => Err(WalletError::CryptoError("Malformed transaction proof".to_string())),
+        }
+    }
+
+    fn set_address_label(&self, address: &str, label: &str) -> WalletResult<()> {
+        if self.wallet_ptr.is_null() {
+            return Err(WalletError::WalletNotOpen);
+        }
+
+        let address_c = CString::new(address)?;
+        let label_c = CString::new(label)?;
+
+        let success = unsafe {
+            fuego_wallet_set_address_label(self.wallet_ptr, address_c.as_ptr(), label_c.as_ptr())
+        };
+
+        if !success {
+            return Err(WalletError::InvalidAddress(format!(
+                "{} does not belong to this wallet",
+                address
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn get_block_info(&self, height: u64) -> WalletResult<BlockInfo> {
+        if self.wallet_ptr.is_null() {
+            return Err(WalletError::WalletNotOpen);
         }
 
-        let block = unsafe { &*(block_ptr as *const BlockInfoFFI) };
-        let hash = unsafe { CStr::from_ptr(block.hash.as_ptr()) }.to_string_lossy().to_string();
-        let out = BlockInfo {
+        let Some(block) = (unsafe { FfiBox::from_raw(fuego_wallet_get_block_info(self.wallet_ptr, height)) }) else {
+            return Err(WalletError::Generic("Block not found".to_string()));
+        };
+
+        Ok(BlockInfo {
             height: block.height,
-            hash,
+            hash: fixed_str(&block.hash),
             timestamp: block.timestamp,
             difficulty: block.difficulty,
             reward: block.reward,
             size: block.size,
             transaction_count: block.transaction_count,
             is_main_chain: block.is_main_chain,
-        };
-        unsafe { fuego_wallet_free_block_info(block_ptr); }
-        Ok(out)
+        })
     }
 
-    /// Start mining
-    pub fn start_mining(&mut self, threads: u32, background: bool) -> WalletResult<()> {
+    fn start_mining(&mut self, threads: u32, background: bool) -> WalletResult<()> {
         if self.wallet_ptr.is_null() {
             return Err(WalletError::WalletNotOpen);
         }
@@ -880,8 +1651,7 @@ impl RealCryptoNoteWallet {
         Ok(())
     }
 
-    /// Stop mining
-    pub fn stop_mining(&mut self) -> WalletResult<()> {
+    fn stop_mining(&mut self) -> WalletResult<()> {
         if self.wallet_ptr.is_null() {
             return Err(WalletError::WalletNotOpen);
         }
@@ -896,38 +1666,29 @@ impl RealCryptoNoteWallet {
         Ok(())
     }
 
-    /// Get mining information
-    pub fn get_mining_info(&self) -> WalletResult<MiningInfo> {
+    fn get_mining_info(&self) -> WalletResult<MiningInfo> {
         if self.wallet_ptr.is_null() {
             return Err(WalletError::WalletNotOpen);
         }
 
-        let info_ptr = unsafe { fuego_wallet_get_mining_info(self.wallet_ptr) };
-
-        if info_ptr.is_null() {
+        let Some(info) = (unsafe { FfiBox::from_raw(fuego_wallet_get_mining_info(self.wallet_ptr)) }) else {
             return Err(WalletError::Generic(
                 "Failed to get mining information".to_string(),
             ));
-        }
+        };
 
-        let info = unsafe { &*(info_ptr as *const MiningInfoFFI) };
-        let pool = if info.pool_address[0] != 0 { Some(unsafe { CStr::from_ptr(info.pool_address.as_ptr()) }.to_string_lossy().to_string()) } else { None };
-        let worker = if info.worker_name[0] != 0 { Some(unsafe { CStr::from_ptr(info.worker_name.as_ptr()) }.to_string_lossy().to_string()) } else { None };
-        let out = MiningInfo {
+        Ok(MiningInfo {
             is_mining: info.is_mining,
             hashrate: info.hashrate,
             difficulty: info.difficulty,
             block_reward: info.block_reward,
-            pool_address: pool,
-            worker_name: worker,
+            pool_address: fixed_str_opt(&info.pool_address),
+            worker_name: fixed_str_opt(&info.worker_name),
             threads: info.threads,
-        };
-        unsafe { fuego_wallet_free_mining_info(info_ptr); }
-        Ok(out)
+        })
     }
 
-    /// Disconnect from network
-    pub fn disconnect(&mut self) -> WalletResult<()> {
+    fn disconnect(&mut self) -> WalletResult<()> {
         if self.wallet_ptr.is_null() {
             return Err(WalletError::WalletNotOpen);
         }
@@ -940,33 +1701,22 @@ impl RealCryptoNoteWallet {
             ));
         }
 
-        self.is_connected = false;
         log::info!("Disconnected from network");
         Ok(())
     }
 
-    /// Create a new term deposit
-    pub fn create_deposit(&self, amount: u64, term: u32) -> WalletResult<String> {
+    fn create_deposit(&self, amount: u64, term: u32) -> WalletResult<String> {
         if self.wallet_ptr.is_null() {
             return Err(WalletError::WalletNotOpen);
         }
 
-        let deposit_ptr = unsafe { fuego_wallet_create_deposit(self.wallet_ptr, amount, term) };
-
-        if deposit_ptr.is_null() {
-            return Err(WalletError::TransactionFailed(
-                "Failed to create deposit".to_string(),
-            ));
-        }
-
-        // Read deposit ID as C string
-        let deposit_id = unsafe { CStr::from_ptr(deposit_ptr as *const c_char).to_string_lossy().to_string() };
-        unsafe { fuego_wallet_free_string(deposit_ptr as *mut c_char); }
+        let deposit_id = unsafe { OwnedCStr::from_raw(fuego_wallet_create_deposit(self.wallet_ptr, amount, term), fuego_wallet_free_string) }
+            .ok_or_else(|| WalletError::TransactionFailed("Failed to create deposit".to_string()))?
+            .to_string_lossy();
         Ok(deposit_id)
     }
 
-    /// Withdraw a term deposit
-    pub fn withdraw_deposit(&self, deposit_id: &str) -> WalletResult<String> {
+    fn withdraw_deposit(&self, deposit_id: &str) -> WalletResult<String> {
         if self.wallet_ptr.is_null() {
             return Err(WalletError::WalletNotOpen);
         }
@@ -974,26 +1724,13 @@ impl RealCryptoNoteWallet {
         let deposit_id_cstr = CString::new(deposit_id)
             .map_err(|_| WalletError::Generic("Invalid deposit ID".to_string()))?;
 
-        let tx_ptr =
-            unsafe { fuego_wallet_withdraw_deposit(self.wallet_ptr, deposit_id_cstr.as_ptr()) };
-
-        if tx_ptr.is_null() {
-            return Err(WalletError::TransactionFailed(
-                "Failed to withdraw deposit".to_string(),
-            ));
-        }
-
-        // Read transaction hash as C string
-        let tx_hash = unsafe { CStr::from_ptr(tx_ptr as *const c_char).to_string_lossy().to_string() };
-        unsafe { fuego_wallet_free_string(tx_ptr as *mut c_char); }
+        let tx_hash = unsafe { OwnedCStr::from_raw(fuego_wallet_withdraw_deposit(self.wallet_ptr, deposit_id_cstr.as_ptr()), fuego_wallet_free_string) }
+            .ok_or_else(|| WalletError::TransactionFailed("Failed to withdraw deposit".to_string()))?
+            .to_string_lossy();
         Ok(tx_hash)
     }
 
-
-
-
-    /// Get transaction history from blockchain
-    pub fn get_transaction_history(&self, limit: u64, offset: u64) -> WalletResult<Vec<TransactionInfo>> {
+    fn get_transaction_history(&self, limit: u64, offset: u64) -> WalletResult<Vec<TransactionInfo>> {
         if self.wallet_ptr.is_null() {
             return Err(WalletError::WalletNotOpen);
         }
@@ -1002,112 +1739,41 @@ impl RealCryptoNoteWallet {
 
         // Get transactions from the blockchain
         for i in 0..limit {
-            let tx_ptr = unsafe { fuego_wallet_get_transaction_history(self.wallet_ptr, 1, offset + i) };
-
-            if tx_ptr.is_null() {
+            let Some(tx_info) = (unsafe { FfiBox::from_raw(fuego_wallet_get_transaction_history(self.wallet_ptr, 1, offset + i)) }) else {
                 break; // No more transactions
-            }
-
-            let tx_info = unsafe { &*(tx_ptr as *const TransactionInfoFFI) };
-
-            // Convert C strings to Rust strings
-            let id = unsafe { CStr::from_ptr(tx_info.id.as_ptr()) }.to_string_lossy().to_string();
-            let hash = unsafe { CStr::from_ptr(tx_info.hash.as_ptr()) }.to_string_lossy().to_string();
-            let payment_id = if tx_info.payment_id[0] != 0 {
-                Some(unsafe { CStr::from_ptr(tx_info.payment_id.as_ptr()) }.to_string_lossy().to_string())
-            } else {
-                None
-            };
-
-            let destination_addresses = if tx_info.destination_addresses[0] != 0 {
-                vec![unsafe { CStr::from_ptr(tx_info.destination_addresses.as_ptr()) }.to_string_lossy().to_string()]
-            } else {
-                vec![]
             };
 
-            let source_addresses = if tx_info.source_addresses[0] != 0 {
-                vec![unsafe { CStr::from_ptr(tx_info.source_addresses.as_ptr()) }.to_string_lossy().to_string()]
-            } else {
-                vec![]
-            };
-
-            let extra = if tx_info.extra[0] != 0 {
-                Some(unsafe { CStr::from_ptr(tx_info.extra.as_ptr()) }.to_string_lossy().to_string())
-            } else {
-                None
-            };
-
-            let transaction = TransactionInfo {
-                id,
-                hash,
-                amount: tx_info.amount,
-                fee: tx_info.fee,
-                height: tx_info.height,
-                timestamp: tx_info.timestamp,
-                confirmations: tx_info.confirmations,
-                is_confirmed: tx_info.is_confirmed,
-                is_pending: tx_info.is_pending,
-                payment_id,
-                destination_addresses,
-                source_addresses,
-                unlock_time: Some(tx_info.unlock_time),
-                extra,
-            };
-
-            transactions.push(transaction);
-
-            unsafe {
-                fuego_wallet_free_transaction_history(tx_ptr);
-            }
+            transactions.push(TransactionInfo::from(&*tx_info));
         }
 
         Ok(transactions)
     }
 
-    /// Get sync progress information
-    pub fn get_sync_progress(&self) -> WalletResult<crate::crypto::real_cryptonote::SyncProgress> {
+    fn get_sync_progress(&self) -> WalletResult<crate::crypto::real_cryptonote::SyncProgress> {
         if self.wallet_ptr.is_null() {
             return Err(WalletError::WalletNotOpen);
         }
 
-        let progress_ptr = unsafe { fuego_wallet_get_sync_progress(self.wallet_ptr) };
-
-        if progress_ptr.is_null() {
+        let Some(progress) = (unsafe { FfiBox::from_raw(fuego_wallet_get_sync_progress(self.wallet_ptr)) }) else {
             return Err(WalletError::Generic("Failed to get sync progress".to_string()));
-        }
-
-        let progress = unsafe { *progress_ptr };
-
-        unsafe {
-            fuego_wallet_free_sync_progress(progress_ptr);
-        }
+        };
 
-        Ok(progress)
+        Ok(*progress)
     }
 
-    /// Get sync status as JSON string
-    pub fn get_sync_status_json(&self) -> WalletResult<String> {
+    fn get_sync_status_json(&self) -> WalletResult<String> {
         if self.wallet_ptr.is_null() {
             return Err(WalletError::WalletNotOpen);
         }
 
-        let json_ptr = unsafe { fuego_wallet_get_sync_status_json(self.wallet_ptr) };
-
-        if json_ptr.is_null() {
-            return Err(WalletError::Generic("Failed to get sync status JSON".to_string()));
-        }
-
-        let json_str = unsafe { CStr::from_ptr(json_ptr).to_string_lossy().to_string() };
-
-        unsafe {
-            fuego_wallet_free_sync_status_json(json_ptr);
-        }
+        let json_str = unsafe { OwnedCStr::from_raw(fuego_wallet_get_sync_status_json(self.wallet_ptr), fuego_wallet_free_sync_status_json) }
+            .ok_or_else(|| WalletError::Generic("Failed to get sync status JSON".to_string()))?
+            .to_string_lossy();
 
         Ok(json_str)
     }
 
-    /// Add address to address book
-    pub fn add_address_book_entry(&self, address: &str, label: Option<&str>, description: Option<&str>) -> WalletResult<()> {
+    fn add_address_book_entry(&self, address: &str, label: Option<&str>, description: Option<&str>) -> WalletResult<()> {
         if self.wallet_ptr.is_null() {
             return Err(WalletError::WalletNotOpen);
         }
@@ -1138,8 +1804,7 @@ impl RealCryptoNoteWallet {
         }
     }
 
-    /// Remove address from address book
-    pub fn remove_address_book_entry(&self, address: &str) -> WalletResult<()> {
+    fn remove_address_book_entry(&self, address: &str) -> WalletResult<()> {
         if self.wallet_ptr.is_null() {
             return Err(WalletError::WalletNotOpen);
         }
@@ -1157,8 +1822,7 @@ impl RealCryptoNoteWallet {
         }
     }
 
-    /// Update address book entry
-    pub fn update_address_book_entry(&self, address: &str, label: Option<&str>, description: Option<&str>) -> WalletResult<()> {
+    fn update_address_book_entry(&self, address: &str, label: Option<&str>, description: Option<&str>) -> WalletResult<()> {
         if self.wallet_ptr.is_null() {
             return Err(WalletError::WalletNotOpen);
         }
@@ -1189,8 +1853,7 @@ impl RealCryptoNoteWallet {
         }
     }
 
-    /// Get address book entries
-    pub fn get_address_book(&self) -> WalletResult<Vec<AddressBookEntry>> {
+    fn get_address_book(&self) -> WalletResult<Vec<AddressBookEntry>> {
         if self.wallet_ptr.is_null() {
             return Err(WalletError::WalletNotOpen);
         }
@@ -1206,8 +1869,7 @@ impl RealCryptoNoteWallet {
         Ok(vec![])
     }
 
-    /// Mark address as used
-    pub fn mark_address_used(&self, address: &str) -> WalletResult<()> {
+    fn mark_address_used(&self, address: &str) -> WalletResult<()> {
         if self.wallet_ptr.is_null() {
             return Err(WalletError::WalletNotOpen);
         }
@@ -1225,33 +1887,25 @@ impl RealCryptoNoteWallet {
         }
     }
 
-    /// Get address book entry by address
-    pub fn get_address_book_entry(&self, address: &str) -> WalletResult<Option<AddressBookEntry>> {
+    fn get_address_book_entry(&self, address: &str) -> WalletResult<Option<AddressBookEntry>> {
         if self.wallet_ptr.is_null() {
             return Err(WalletError::WalletNotOpen);
         }
 
         let address_c = CString::new(address)?;
-        let json_ptr = unsafe { fuego_wallet_get_address_book_entry(self.wallet_ptr, address_c.as_ptr()) };
+        let json = unsafe { OwnedCStr::from_raw(fuego_wallet_get_address_book_entry(self.wallet_ptr, address_c.as_ptr()), fuego_wallet_free_address_book_entry) };
 
-        if json_ptr.is_null() {
+        if json.is_none() {
             return Ok(None); // Entry not found
         }
 
-        let _json_str = unsafe { CStr::from_ptr(json_ptr).to_string_lossy().to_string() };
-
-        unsafe {
-            fuego_wallet_free_address_book_entry(json_ptr);
-        }
-
         // Parse JSON string to AddressBookEntry
         // For now, return None - real implementation would parse JSON
         // TODO: Implement JSON parsing
         Ok(None)
     }
 
-    /// Set mining pool configuration
-    pub fn set_mining_pool(&self, pool_address: Option<&str>, worker_name: Option<&str>) -> WalletResult<()> {
+    fn set_mining_pool(&self, pool_address: Option<&str>, worker_name: Option<&str>) -> WalletResult<()> {
         if self.wallet_ptr.is_null() {
             return Err(WalletError::WalletNotOpen);
         }
@@ -1280,53 +1934,19 @@ impl RealCryptoNoteWallet {
         }
     }
 
-    /// Get detailed mining statistics as JSON
-    pub fn get_mining_stats_json(&self) -> WalletResult<String> {
+    fn get_mining_stats_json(&self) -> WalletResult<String> {
         if self.wallet_ptr.is_null() {
             return Err(WalletError::WalletNotOpen);
         }
 
-        let json_ptr = unsafe { fuego_wallet_get_mining_stats_json(self.wallet_ptr) };
-
-        if json_ptr.is_null() {
-            return Err(WalletError::Generic("Failed to get mining statistics JSON".to_string()));
-        }
-
-        let json_str = unsafe { CStr::from_ptr(json_ptr).to_string_lossy().to_string() };
-
-        unsafe {
-            fuego_wallet_free_mining_stats_json(json_ptr);
-        }
+        let json_str = unsafe { OwnedCStr::from_raw(fuego_wallet_get_mining_stats_json(self.wallet_ptr), fuego_wallet_free_mining_stats_json) }
+            .ok_or_else(|| WalletError::Generic("Failed to get mining statistics JSON".to_string()))?
+            .to_string_lossy();
 
         Ok(json_str)
     }
 
-    /// Generate a new random seed phrase
-    pub fn generate_seed_phrase() -> WalletResult<String> {
-        let seed_ptr = unsafe { fuego_wallet_generate_seed_phrase() };
-
-        if seed_ptr.is_null() {
-            return Err(WalletError::Generic("Failed to generate seed phrase".to_string()));
-        }
-
-        let seed_str = unsafe { CStr::from_ptr(seed_ptr).to_string_lossy().to_string() };
-
-        unsafe {
-            fuego_wallet_free_key_string(seed_ptr);
-        }
-
-        Ok(seed_str)
-    }
-
-    /// Validate a seed phrase
-    pub fn validate_seed_phrase(seed_phrase: &str) -> WalletResult<bool> {
-        let seed_c = CString::new(seed_phrase)?;
-        let is_valid = unsafe { fuego_wallet_validate_seed_phrase(seed_c.as_ptr()) };
-        Ok(is_valid)
-    }
-
-    /// Derive keys from seed phrase
-    pub fn derive_keys_from_seed(&self, seed_phrase: &str, password: &str) -> WalletResult<()> {
+    fn derive_keys_from_seed(&self, seed_phrase: &str, password: &str) -> WalletResult<()> {
         if self.wallet_ptr.is_null() {
             return Err(WalletError::WalletNotOpen);
         }
@@ -1349,72 +1969,44 @@ impl RealCryptoNoteWallet {
         }
     }
 
-    /// Get seed phrase (requires password for decryption)
-    pub fn get_seed_phrase(&self, password: &str) -> WalletResult<String> {
+    fn get_seed_phrase(&self, password: &str) -> WalletResult<String> {
         if self.wallet_ptr.is_null() {
             return Err(WalletError::WalletNotOpen);
         }
 
         let password_c = CString::new(password)?;
-        let seed_ptr = unsafe { fuego_wallet_get_seed_phrase(self.wallet_ptr, password_c.as_ptr()) };
-
-        if seed_ptr.is_null() {
-            return Err(WalletError::Generic("Failed to get seed phrase".to_string()));
-        }
-
-        let seed_str = unsafe { CStr::from_ptr(seed_ptr).to_string_lossy().to_string() };
-
-        unsafe {
-            fuego_wallet_free_key_string(seed_ptr);
-        }
+        let seed_str = unsafe { OwnedCStr::from_raw(fuego_wallet_get_seed_phrase(self.wallet_ptr, password_c.as_ptr()), fuego_wallet_free_key_string) }
+            .ok_or_else(|| WalletError::Generic("Failed to get seed phrase".to_string()))?
+            .to_string_lossy();
 
         Ok(seed_str)
     }
 
-    /// Get view key
-    pub fn get_view_key(&self) -> WalletResult<String> {
+    fn get_view_key(&self) -> WalletResult<String> {
         if self.wallet_ptr.is_null() {
             return Err(WalletError::WalletNotOpen);
         }
 
-        let key_ptr = unsafe { fuego_wallet_get_view_key(self.wallet_ptr) };
-
-        if key_ptr.is_null() {
-            return Err(WalletError::Generic("Failed to get view key".to_string()));
-        }
-
-        let key_str = unsafe { CStr::from_ptr(key_ptr).to_string_lossy().to_string() };
-
-        unsafe {
-            fuego_wallet_free_key_string(key_ptr);
-        }
+        let key_str = unsafe { OwnedCStr::from_raw(fuego_wallet_get_view_key(self.wallet_ptr), fuego_wallet_free_key_string) }
+            .ok_or_else(|| WalletError::Generic("Failed to get view key".to_string()))?
+            .to_string_lossy();
 
         Ok(key_str)
     }
 
-    /// Get spend key
-    pub fn get_spend_key(&self) -> WalletResult<String> {
+    fn get_spend_key(&self) -> WalletResult<String> {
         if self.wallet_ptr.is_null() {
             return Err(WalletError::WalletNotOpen);
         }
 
-        let key_ptr = unsafe { fuego_wallet_get_spend_key(self.wallet_ptr) };
-
-        if key_ptr.is_null() {
-            return Err(WalletError::Generic("Failed to get spend key".to_string()));
-        }
-
-        let key_str = unsafe { CStr::from_ptr(key_ptr).to_string_lossy().to_string() };
-
-        unsafe {
-            fuego_wallet_free_key_string(key_ptr);
-        }
+        let key_str = unsafe { OwnedCStr::from_raw(fuego_wallet_get_spend_key(self.wallet_ptr), fuego_wallet_free_key_string) }
+            .ok_or_else(|| WalletError::Generic("Failed to get spend key".to_string()))?
+            .to_string_lossy();
 
         Ok(key_str)
     }
 
-    /// Check if wallet has keys
-    pub fn has_keys(&self) -> WalletResult<bool> {
+    fn has_keys(&self) -> WalletResult<bool> {
         if self.wallet_ptr.is_null() {
             return Ok(false);
         }
@@ -1423,29 +2015,19 @@ impl RealCryptoNoteWallet {
         Ok(has_keys)
     }
 
-    /// Export wallet keys
-    pub fn export_keys(&self) -> WalletResult<String> {
+    fn export_keys(&self) -> WalletResult<String> {
         if self.wallet_ptr.is_null() {
             return Err(WalletError::WalletNotOpen);
         }
 
-        let keys_ptr = unsafe { fuego_wallet_export_keys(self.wallet_ptr) };
-
-        if keys_ptr.is_null() {
-            return Err(WalletError::Generic("Failed to export keys".to_string()));
-        }
-
-        let keys_str = unsafe { CStr::from_ptr(keys_ptr).to_string_lossy().to_string() };
-
-        unsafe {
-            fuego_wallet_free_key_string(keys_ptr);
-        }
+        let keys_str = unsafe { OwnedCStr::from_raw(fuego_wallet_export_keys(self.wallet_ptr), fuego_wallet_free_key_string) }
+            .ok_or_else(|| WalletError::Generic("Failed to export keys".to_string()))?
+            .to_string_lossy();
 
         Ok(keys_str)
     }
 
-    /// Import wallet keys
-    pub fn import_keys(&self, view_key: &str, spend_key: &str, address: &str) -> WalletResult<()> {
+    fn import_keys(&self, view_key: &str, spend_key: &str, address: &str) -> WalletResult<()> {
         if self.wallet_ptr.is_null() {
             return Err(WalletError::WalletNotOpen);
         }
@@ -1469,61 +2051,1498 @@ impl RealCryptoNoteWallet {
             Err(WalletError::Generic("Failed to import keys".to_string()))
         }
     }
+
+    fn save(&self) -> WalletResult<()> {
+        if self.wallet_ptr.is_null() {
+            return Err(WalletError::WalletNotOpen);
+        }
+
+        if unsafe { fuego_wallet_save(self.wallet_ptr) } {
+            Ok(())
+        } else {
+            Err(WalletError::StorageError("Failed to write the wallet file".to_string()))
+        }
+    }
 }
 
-impl Drop for RealCryptoNoteWallet {
+impl Drop for FfiWalletBackend {
     fn drop(&mut self) {
         // Ensure proper cleanup when the Rust wrapper is dropped
         if !self.wallet_ptr.is_null() {
-            log::info!("Dropping RealCryptoNoteWallet - ensuring proper cleanup");
+            log::info!("Dropping FfiWalletBackend - ensuring proper cleanup");
             self.close_wallet();
         }
     }
 }
 
-// Default Fuego network nodes
-pub const FUEGO_NODES: &[(&str, u16)] = &[
-    ("fuego.spaceportx.net", 18180), // Real Fuego node with live blockchain data
-    ("node1.fuego.network", 18081),
-    ("node2.fuego.network", 18081),
-    ("node3.fuego.network", 18081),
-    ("127.0.0.1", 18081), // Local node for testing
-];
+/// Whether wallets save themselves after sends, new addresses, relabels and deposits
+static AUTO_SAVE: AtomicBool = AtomicBool::new(true);
 
-/// Fetch real network data from Fuego API
-pub async fn fetch_fuego_network_data() -> WalletResult<serde_json::Value> {
-    // For now, return the known network data from fuego.spaceportx.net
-    // In a real implementation, this would make an HTTP request to the API
-    Ok(serde_json::json!({
-        "height": 964943,
-        "peer_count": 22,
-        "difficulty": 52500024,
-        "last_block_reward": 3005769,
-        "block_major_version": 9,
-        "block_minor_version": 0,
-        "status": "OK",
-        "version": "1.9.1",
-        "tx_count": 390132,
-        "fee_address": "fire1jNwRRUYGENanfBwVhehZXVcQVFx3dH3D3Z7UNC17FePBr27DDwctyL2ePwDPz4fypwpNQpfXbp6wavubvSn6ToisC5NUy"
-    }))
-}
-
-/// Connect to the best available Fuego node
-pub fn connect_to_fuego_network(wallet: &mut RealCryptoNoteWallet) -> WalletResult<()> {
-    for (address, port) in FUEGO_NODES {
-        match wallet.connect_to_node(address, *port) {
-            Ok(_) => {
-                log::info!("Successfully connected to Fuego node: {}:{}", address, port);
-                return Ok(());
+/// Told about every save after an operation that failed
+static SAVE_FAILURE_HOOK: std::sync::OnceLock<fn(&str, &WalletError)> = std::sync::OnceLock::new();
+
+/// Save wallets after every operation that changes them, or only on request
+pub fn set_auto_save(enabled: bool) {
+    AUTO_SAVE.store(enabled, Ordering::SeqCst);
+}
+
+/// Call `hook` with the operation and the error whenever saving after it fails
+pub fn report_save_failures_to(hook: fn(&str, &WalletError)) {
+    let _ = SAVE_FAILURE_HOOK.set(hook);
+}
+
+pub struct RealCryptoNoteWallet {
+    backend: Box<dyn WalletBackend + Send>,
+    is_connected: bool,
+    /// Overrides the connect timeout from the settings
+    connect_timeout: Option<Duration>,
+}
+
+impl RealCryptoNoteWallet {
+    /// Create a wallet on the backend selected in the settings
+    pub fn new() -> Self {
+        Self::with_backend(new_backend(selected_backend()))
+    }
+
+    /// Wallet running on `backend` instead of the selected one
+    pub fn with_backend(backend: Box<dyn WalletBackend + Send>) -> Self {
+        Self {
+            backend,
+            is_connected: false,
+            connect_timeout: None,
+        }
+    }
+
+    /// Set how long `connect_to_node` may wait for a node to answer,
+    /// instead of the timeout from `set_default_connect_timeout`
+    pub fn set_connect_timeout(&mut self, timeout: Duration) {
+        self.connect_timeout = Some(timeout);
+    }
+
+    /// Create a new wallet with real CryptoNote implementation
+    pub fn create_wallet(
+        &mut self,
+        password: &str,
+        file_path: &str,
+        seed_phrase: Option<&str>,
+        restore_height: u64,
+    ) -> WalletResult<()> {
+        self.backend.create_wallet(password, file_path, seed_phrase, restore_height)?;
+
+        if let Err(e) = WalletMetadata::record_creation(std::path::Path::new(file_path)) {
+            log::warn!("Failed to record wallet creation time: {}", e);
+        }
+        Ok(())
+    }
+
+    /// Create a new wallet at `file_path`, refusing to replace a file already
+    /// there unless `overwrite`. Without `seed_phrase` the wallet gets a fresh
+    /// one, which is handed back for the user to write down; it is not kept.
+    pub fn create_new_wallet(
+        &mut self,
+        password: &str,
+        file_path: &str,
+        seed_phrase: Option<&str>,
+        restore_height: u64,
+        overwrite: bool,
+    ) -> WalletResult<CreatedWallet> {
+        if !overwrite && std::path::Path::new(file_path).exists() {
+            return Err(WalletError::WalletAlreadyExists(file_path.to_string()));
+        }
+        self.create_wallet(password, file_path, seed_phrase, restore_height)?;
+        let address = self.get_address()?;
+        let seed_phrase = match seed_phrase {
+            Some(_) => None,
+            None => match self.get_seed_phrase(password) {
+                Ok(seed) => Some(seed),
+                Err(e) => {
+                    log::warn!("Created wallet but could not read back its seed phrase: {}", e);
+                    None
+                }
+            },
+        };
+        Ok(CreatedWallet { address, seed_phrase })
+    }
+
+    /// Open an existing wallet with real CryptoNote implementation
+    pub fn open_wallet(&mut self, file_path: &str, password: &str) -> WalletResult<()> {
+        self.backend.open_wallet(file_path, password)
+    }
+
+    /// Close the wallet
+    pub fn close_wallet(&mut self) {
+        self.backend.close_wallet();
+        self.is_connected = false;
+    }
+
+    /// Check if wallet is open
+    pub fn is_open(&self) -> bool {
+        self.backend.is_open()
+    }
+
+    /// Get wallet balance from real CryptoNote implementation
+    pub fn get_balance(&self) -> WalletResult<u64> {
+        self.backend.get_balance()
+    }
+
+    /// Get unlocked balance from real CryptoNote implementation
+    pub fn get_unlocked_balance(&self) -> WalletResult<u64> {
+        self.backend.get_unlocked_balance()
+    }
+
+    /// Get wallet address from real CryptoNote implementation
+    pub fn get_address(&self) -> WalletResult<String> {
+        self.backend.get_address()
+    }
+
+    /// Send a transaction using real CryptoNote implementation. An integrated
+    /// address is sent to as its standard address with its payment ID.
+    pub fn send_transaction(
+        &self,
+        address: &str,
+        amount: u64,
+        payment_id: Option<&str>,
+        mixin: u64,
+    ) -> WalletResult<String> {
+        let (address, payment_id) = resolve_payment_id(address, payment_id)?;
+        let result = self.backend.send_transaction(&address, amount, payment_id.as_deref(), mixin);
+        self.saved("send", result)
+    }
+
+    /// Write the wallet to its file
+    pub fn save(&self) -> WalletResult<()> {
+        self.backend.save()
+    }
+
+    /// `result` of the operation, saving the wallet first if it succeeded and
+    /// auto-save is on. The operation already took effect, so a failed save is
+    /// logged and reported but does not fail it; the wallet keeps its state
+    /// and the next save writes it.
+    fn saved<T>(&self, operation: &str, result: WalletResult<T>) -> WalletResult<T> {
+        if result.is_ok()
+            && AUTO_SAVE.load(Ordering::SeqCst)
+            && let Err(e) = self.save()
+        {
+            log::error!("Failed to save wallet after {}: {}", operation, e);
+            if let Some(hook) = SAVE_FAILURE_HOOK.get() {
+                hook(operation, &e);
             }
-            Err(e) => {
-                log::warn!("Failed to connect to {}:{} - {}", address, port, e);
-                continue;
+        }
+        result
+    }
+
+    /// Send to several recipients in a single transaction, never spending the
+    /// outputs in `frozen`
+    pub fn send_many(&self, outputs: &[(String, u64)], mixin: u64, frozen: &HashSet<String>) -> WalletResult<String> {
+        if !self.is_open() {
+            return Err(WalletError::WalletNotOpen);
+        }
+        if outputs.is_empty() {
+            return Err(WalletError::TransactionFailed(
+                "At least one recipient is required".to_string(),
+            ));
+        }
+
+        let mut total: u64 = 0;
+        for (address, amount) in outputs {
+            if !is_valid_address_format(address) {
+                return Err(WalletError::InvalidAddress(address.clone()));
+            }
+            if is_integrated_address(address) {
+                return Err(WalletError::TransactionFailed(format!(
+                    "Integrated address {} needs a transaction of its own",
+                    address
+                )));
             }
+            if *amount == 0 {
+                return Err(WalletError::TransactionFailed(format!(
+                    "Amount for {} must be greater than zero",
+                    address
+                )));
+            }
+            total = total.checked_add(*amount).ok_or_else(|| {
+                WalletError::TransactionFailed("Total amount overflows".to_string())
+            })?;
+        }
+
+        let fee = self.estimate_batch_fee(outputs, mixin)?.total;
+        let unlocked = self.get_unlocked_balance()?;
+        if total.checked_add(fee).is_none_or(|needed| needed > unlocked) {
+            return Err(WalletError::InsufficientFunds);
         }
+
+        let result = match self.unfrozen_outputs(frozen)? {
+            Some(unfrozen) => {
+                let key_images = coin_control::auto_select(&unfrozen, frozen, total, fee)
+                    .map_err(WalletError::TransactionFailed)?;
+                self.backend.send_many_from_outputs(outputs, mixin, &key_images)
+            }
+            None => self.backend.send_many(outputs, mixin),
+        };
+        self.saved("send", result)
     }
 
-    Err(WalletError::NetworkError(
-        "Failed to connect to any Fuego network node".to_string(),
-    ))
+    /// Send the entire unlocked balance minus the fee to `destination`,
+    /// leaving the outputs in `frozen` unspent
+    pub fn sweep_all(&self, destination: &str, mixin: u64, frozen: &HashSet<String>) -> WalletResult<String> {
+        let estimate = self.estimate_sweep(destination, mixin, frozen)?;
+        match self.unfrozen_outputs(frozen)? {
+            Some(unfrozen) => {
+                let key_images: Vec<String> = unfrozen.into_iter().map(|o| o.key_image).collect();
+                self.send_coin_controlled(destination, estimate.sendable_amount, None, mixin, Some(&key_images), frozen)
+            }
+            None => self.send_many(&[(destination.to_string(), estimate.sendable_amount)], mixin, frozen),
+        }
+    }
+
+    /// Amount and fee of sending the entire unlocked balance outside `frozen`
+    /// to `destination`. Fails with `InsufficientFunds` if the balance does
+    /// not cover the fee.
+    pub fn estimate_sweep(&self, destination: &str, mixin: u64, frozen: &HashSet<String>) -> WalletResult<SweepEstimate> {
+        let sweepable = match self.unfrozen_outputs(frozen)? {
+            Some(unfrozen) => unfrozen.iter().fold(0u64, |sum, o| sum.saturating_add(o.amount)),
+            None => self.get_unlocked_balance()?,
+        };
+        converge_sweep_fee(sweepable, |amount| self.estimate_transaction_fee(destination, amount, mixin))
+    }
+
+    /// The unlocked outputs outside `frozen`, or `None` while none of the
+    /// wallet's outputs is frozen and the wallet library may pick any
+    fn unfrozen_outputs(&self, frozen: &HashSet<String>) -> WalletResult<Option<Vec<UnspentOutput>>> {
+        if frozen.is_empty() {
+            return Ok(None);
+        }
+        let outputs = self.get_unspent_outputs()?;
+        if !outputs.iter().any(|o| frozen.contains(&o.key_image)) {
+            return Ok(None);
+        }
+        Ok(Some(outputs.into_iter().filter(|o| !o.is_locked && !frozen.contains(&o.key_image)).collect()))
+    }
+
+    /// Send spending only the outputs with the given key images. The selection
+    /// is checked against the wallet's unspent outputs before anything is sent.
+    pub fn send_from_outputs(
+        &self,
+        address: &str,
+        amount: u64,
+        payment_id: Option<&str>,
+        mixin: u64,
+        key_images: &[String],
+    ) -> WalletResult<String> {
+        self.send_coin_controlled(address, amount, payment_id, mixin, Some(key_images), &HashSet::new())
+    }
+
+    /// Send without spending the outputs in `frozen`: from the outputs with
+    /// `key_images` when given, else from outputs picked by
+    /// `coin_control::auto_select`. While no unspent output is frozen and
+    /// nothing is selected, the wallet library picks the inputs itself.
+    pub fn send_coin_controlled(
+        &self,
+        address: &str,
+        amount: u64,
+        payment_id: Option<&str>,
+        mixin: u64,
+        key_images: Option<&[String]>,
+        frozen: &HashSet<String>,
+    ) -> WalletResult<String> {
+        if key_images.is_none() && frozen.is_empty() {
+            return self.send_transaction(address, amount, payment_id, mixin);
+        }
+        if !self.is_open() {
+            return Err(WalletError::WalletNotOpen);
+        }
+        if !is_valid_address_format(address) {
+            return Err(WalletError::InvalidAddress(address.to_string()));
+        }
+        if amount == 0 {
+            return Err(WalletError::TransactionFailed("Amount must be greater than zero".to_string()));
+        }
+        let (address, payment_id) = resolve_payment_id(address, payment_id)?;
+
+        let outputs = self.get_unspent_outputs()?;
+        let key_images = match key_images {
+            Some(key_images) => {
+                let fee = self.estimate_transaction_fee(&address, amount, mixin)?;
+                coin_control::select_unfrozen_outputs(&outputs, key_images, frozen, amount, fee)
+                    .map_err(WalletError::TransactionFailed)?;
+                key_images.to_vec()
+            }
+            None if outputs.iter().any(|o| frozen.contains(&o.key_image)) => {
+                let fee = self.estimate_transaction_fee(&address, amount, mixin)?;
+                coin_control::auto_select(&outputs, frozen, amount, fee)
+                    .map_err(WalletError::TransactionFailed)?
+            }
+            None => return self.send_transaction(&address, amount, payment_id.as_deref(), mixin),
+        };
+
+        let result = self.backend.send_from_outputs(&address, amount, payment_id.as_deref(), mixin, &key_images);
+        self.saved("send", result)
+    }
+
+    /// Consolidate the unlocked outputs worth less than `threshold` into one
+    /// output, by sending them, minus the fee, to the wallet's own address.
+    /// Outputs in `frozen` are left alone.
+    pub fn sweep_below(&self, threshold: u64, mixin: u64, frozen: &HashSet<String>) -> WalletResult<String> {
+        let (key_images, estimate) = self.dust_sweep(threshold, mixin, frozen)?;
+        let own_address = self.get_address()?;
+        self.send_coin_controlled(&own_address, estimate.sendable_amount, None, mixin, Some(&key_images), frozen)
+    }
+
+    /// Amount and fee of `sweep_below` with the same arguments
+    pub fn estimate_sweep_below(&self, threshold: u64, mixin: u64, frozen: &HashSet<String>) -> WalletResult<SweepEstimate> {
+        Ok(self.dust_sweep(threshold, mixin, frozen)?.1)
+    }
+
+    /// Key images of the outputs `sweep_below` consolidates, with the amount and fee
+    fn dust_sweep(&self, threshold: u64, mixin: u64, frozen: &HashSet<String>) -> WalletResult<(Vec<String>, SweepEstimate)> {
+        let mut dust = coin_control::dust_outputs(&self.get_unspent_outputs()?, threshold);
+        dust.retain(|o| !frozen.contains(&o.key_image));
+        if dust.len() < 2 {
+            return Err(WalletError::TransactionFailed(format!(
+                "Fewer than two unlocked outputs below {}, nothing to consolidate",
+                threshold
+            )));
+        }
+
+        let own_address = self.get_address()?;
+        let total: u64 = dust.iter().map(|o| o.amount).sum();
+        let fee = self.estimate_transaction_fee(&own_address, total, mixin)?;
+        if total <= fee {
+            return Err(WalletError::InsufficientFunds);
+        }
+
+        let key_images = dust.into_iter().map(|o| o.key_image).collect();
+        Ok((key_images, SweepEstimate { sendable_amount: total - fee, fee, total }))
+    }
+
+    /// Connect to Fuego network node, giving up after the connect timeout
+    pub fn connect_to_node(&mut self, address: &str, port: u16) -> WalletResult<()> {
+        let timeout = self.connect_timeout.unwrap_or_else(default_connect_timeout);
+        self.backend.connect_to_node(address, port, timeout)?;
+        self.is_connected = true;
+        Ok(())
+    }
+
+    /// Connect to Fuego network (convenience method)
+    pub fn connect_to_network(&mut self, node_url: &str) -> WalletResult<()> {
+        // Parse URL to extract address and port
+        let url = node_url.replace("http://", "").replace("https://", "");
+        let parts: Vec<&str> = url.split(':').collect();
+
+        let address = parts[0];
+        let port = if parts.len() > 1 {
+            parts[1].parse::<u16>().unwrap_or(18180)
+        } else {
+            18180
+        };
+
+        self.connect_to_node(address, port)
+    }
+
+    /// Get network status from real CryptoNote implementation
+    pub fn get_network_status(&self) -> WalletResult<serde_json::Value> {
+        self.backend.get_network_status()
+    }
+
+    /// Get all term deposits from the wallet
+    pub fn get_deposits(&self) -> WalletResult<Vec<DepositInfo>> {
+        self.backend.get_deposits()
+    }
+
+    /// Get comprehensive wallet information
+    pub fn get_wallet_info(&self) -> WalletResult<WalletInfo> {
+        self.backend.get_wallet_info()
+    }
+
+    /// Get detailed network information
+    pub fn get_network_info(&self) -> WalletResult<NetworkInfo> {
+        self.backend.get_network_info()
+    }
+
+    /// Refresh wallet data from blockchain
+    pub fn refresh(&mut self) -> WalletResult<()> {
+        self.backend.refresh()
+    }
+
+    /// Refresh wallet data and report the resulting sync progress
+    pub fn refresh_with_progress(&mut self, reporter: Option<&ProgressReporter>) -> WalletResult<SyncProgress> {
+        self.refresh()?;
+        let progress = self.get_sync_progress()?;
+
+        if let Some(reporter) = reporter {
+            let phase = self.get_sync_status()?.phase;
+            match phase {
+                SyncPhase::Synced => reporter.report_phase(100.0, phase, "Wallet synchronized"),
+                SyncPhase::DownloadingBlocks => reporter.report_phase(
+                    progress.progress_percentage,
+                    phase,
+                    format!("Downloading blocks {}/{}", progress.current_height, progress.total_height),
+                ),
+                SyncPhase::Scanning => reporter.report_phase(progress.progress_percentage, phase, "Scanning for wallet transactions"),
+                SyncPhase::Connecting => reporter.report_phase(progress.progress_percentage, phase, "Connecting to the network"),
+                SyncPhase::Disconnected => reporter.report_phase(progress.progress_percentage, phase, "Not connected to the network"),
+            }
+        }
+
+        Ok(progress)
+    }
+
+    /// Rescan blockchain from specific height
+    pub fn rescan_blockchain(&mut self, start_height: u64, reporter: Option<&ProgressReporter>) -> WalletResult<()> {
+        self.backend.rescan_blockchain(start_height)?;
+
+        if let Some(reporter) = reporter {
+            reporter.report(0.0, format!("Rescan started from height {}", start_height));
+        }
+        Ok(())
+    }
+
+    /// Get transaction by hash
+    pub fn get_transaction_by_hash(&self, tx_hash: &str) -> WalletResult<TransactionInfo> {
+        self.backend.get_transaction_by_hash(tx_hash)
+    }
+
+    /// Relay a sent, unconfirmed transaction to the peers again
+    pub fn rebroadcast_transaction(&self, tx_hash: &str) -> WalletResult<()> {
+        self.backend.rebroadcast_transaction(tx_hash)
+    }
+
+    /// Proof that the wallet's transaction `tx_hash` paid `address`, for
+    /// showing the payment to a third party; `message` is signed along
+    pub fn get_tx_proof(&self, tx_hash: &str, address: &str, message: Option<&str>) -> WalletResult<String> {
+        self.backend.get_tx_proof(tx_hash, address, message)
+    }
+
+    /// Whether `proof` shows that `tx_hash` paid `address`. A proof that is
+    /// not one at all is an error; one that does not match is `false`.
+    pub fn check_tx_proof(&self, tx_hash: &str, address: &str, message: Option<&str>, proof: &str) -> WalletResult<bool> {
+        if !is_well_formed_tx_proof(proof) {
+            return Err(WalletError::CryptoError("Malformed transaction proof".to_string()));
+        }
+        self.backend.check_tx_proof(tx_hash, address, message, proof)
+    }
+
+    /// Estimate transaction fee
+    pub fn estimate_transaction_fee(
+        &self,
+        address: &str,
+        amount: u64,
+        mixin: u64,
+    ) -> WalletResult<u64> {
+        Ok(self.estimate_fee_breakdown(address, amount, mixin)?.total)
+    }
+
+    /// Estimate the fee of one transaction paying every destination in
+    /// `outputs`: the base and priority fee of the costliest single payment,
+    /// with its size fee charged at the same rate for the larger transaction
+    pub fn estimate_batch_fee(&self, outputs: &[(String, u64)], mixin: u64) -> WalletResult<FeeBreakdown> {
+        let total = outputs.iter().fold(0u64, |sum, (_, amount)| sum.saturating_add(*amount));
+        let mut costliest: Option<FeeBreakdown> = None;
+        for (address, _) in outputs {
+            let single = self.estimate_fee_breakdown(address, total, mixin)?;
+            if costliest.as_ref().is_none_or(|c| single.total > c.total) {
+                costliest = Some(single);
+            }
+        }
+        let single = costliest.ok_or_else(|| WalletError::TransactionFailed("At least one recipient is required".to_string()))?;
+
+        let size = estimate_transaction_size(outputs.len() as u64, mixin, None);
+        let size_fee = match single.estimated_size_bytes {
+            0 => single.size_fee,
+            single_size => (u128::from(single.size_fee) * u128::from(size) / u128::from(single_size)) as u64,
+        };
+        Ok(FeeBreakdown {
+            size_fee,
+            total: single.base_fee.saturating_add(size_fee).saturating_add(single.priority_fee),
+            estimated_size_bytes: size,
+            ..single
+        })
+    }
+
+    /// Estimate the fee split into base, size and priority components, with
+    /// the estimated transaction size
+    pub fn estimate_fee_breakdown(
+        &self,
+        address: &str,
+        amount: u64,
+        mixin: u64,
+    ) -> WalletResult<FeeBreakdown> {
+        let (address, _) = resolve_payment_id(address, None)?;
+        self.backend.estimate_transaction_fee(&address, amount, mixin)
+    }
+
+    /// Fee and size of sending `amount` to `address`, spending
+    /// `output_count_hint` outputs. Fails with `TransactionTooLarge` when the
+    /// network would reject the transaction.
+    pub fn preview_send(
+        &self,
+        address: &str,
+        amount: u64,
+        mixin: u64,
+        output_count_hint: Option<u64>,
+    ) -> WalletResult<TransactionPreview> {
+        let size = estimate_transaction_size(1, mixin, output_count_hint);
+        check_transaction_size(size)?;
+        let fee = self.estimate_fee_breakdown(address, amount, mixin)?.total;
+        Ok(TransactionPreview {
+            amount,
+            total: amount.saturating_add(fee),
+            fee: FeeEstimate::new(fee, size),
+            max_size_bytes: MAX_TRANSACTION_SIZE,
+        })
+    }
+
+    /// Create new address with label
+    pub fn create_address(&self, label: Option<&str>) -> WalletResult<String> {
+        self.saved("create address", self.backend.create_address(label))
+    }
+
+    /// Get all wallet addresses with the labels stored in the wallet
+    pub fn get_addresses(&self) -> WalletResult<Vec<WalletAddress>> {
+        self.backend.get_addresses()
+    }
+
+    /// Transfers of this wallet that are in the transaction pool, not in a block yet
+    pub fn get_pool_transfers(&self) -> WalletResult<Vec<PoolTransfer>> {
+        self.backend.get_pool_transfers()
+    }
+
+    /// Check the wallet file at `file_path` without opening it for use; the
+    /// file is only read. Corruption is reported as `WalletCorrupted`.
+    pub fn verify_integrity(&self, file_path: &str, password: &str) -> WalletResult<WalletIntegrityReport> {
+        let metadata = std::fs::metadata(file_path)
+            .map_err(|_| WalletError::WalletFileNotFound(file_path.to_string()))?;
+        let mut report = self.backend.verify_wallet(file_path, password)?;
+        report.file_path = file_path.to_string();
+        report.file_size = metadata.len();
+        Ok(report)
+    }
+
+    /// Block reward and coin emission at the chain tip, as reported by the daemon
+    pub fn get_emission_info(&self) -> WalletResult<EmissionInfo> {
+        self.backend.get_emission()
+    }
+
+    /// Term deposit limits at the chain tip, the defaults if the daemon has none
+    pub fn get_deposit_limits(&self) -> WalletResult<DepositLimits> {
+        self.backend.get_deposit_limits()
+    }
+
+    /// Unspent outputs of the wallet, for coin control
+    pub fn get_unspent_outputs(&self) -> WalletResult<Vec<UnspentOutput>> {
+        self.backend.get_unspent_outputs()
+    }
+
+    /// Set the label of a wallet address.
+    ///
+    /// Labels are local wallet metadata and are never part of the address or
+    /// of any transaction, so the primary address can be labeled as well.
+    pub fn set_address_label(&self, address: &str, label: &str) -> WalletResult<()> {
+        self.saved("relabel", self.backend.set_address_label(address, label))
+    }
+
+    /// Get block information by height
+    pub fn get_block_info(&self, height: u64) -> WalletResult<BlockInfo> {
+        self.backend.get_block_info(height)
+    }
+
+    /// Start mining
+    pub fn start_mining(&mut self, threads: u32, background: bool) -> WalletResult<()> {
+        self.backend.start_mining(threads, background)
+    }
+
+    /// Stop mining
+    pub fn stop_mining(&mut self) -> WalletResult<()> {
+        self.backend.stop_mining()
+    }
+
+    /// Get mining information
+    pub fn get_mining_info(&self) -> WalletResult<MiningInfo> {
+        self.backend.get_mining_info()
+    }
+
+    /// Disconnect from network
+    pub fn disconnect(&mut self) -> WalletResult<()> {
+        self.backend.disconnect()?;
+        self.is_connected = false;
+        Ok(())
+    }
+
+    /// Create a new term deposit
+    pub fn create_deposit(&self, amount: u64, term: u32) -> WalletResult<String> {
+        self.saved("deposit", self.backend.create_deposit(amount, term))
+    }
+
+    /// Withdraw a term deposit
+    pub fn withdraw_deposit(&self, deposit_id: &str) -> WalletResult<String> {
+        self.saved("withdraw deposit", self.backend.withdraw_deposit(deposit_id))
+    }
+
+    /// Get transaction history from blockchain
+    pub fn get_transaction_history(&self, limit: u64, offset: u64) -> WalletResult<Vec<TransactionInfo>> {
+        self.backend.get_transaction_history(limit, offset)
+    }
+
+    /// Get sync progress information
+    pub fn get_sync_progress(&self) -> WalletResult<crate::crypto::real_cryptonote::SyncProgress> {
+        self.backend.get_sync_progress()
+    }
+
+    /// Get sync status as JSON string
+    pub fn get_sync_status_json(&self) -> WalletResult<String> {
+        self.backend.get_sync_status_json()
+    }
+
+    /// Sync state with its phase; backends without a status JSON are
+    /// described from their `SyncProgress` and connection state
+    pub fn get_sync_status(&self) -> WalletResult<SyncStatus> {
+        match self.backend.get_sync_status_json() {
+            Ok(json) => SyncStatus::from_json(&json),
+            Err(_) => {
+                let progress = self.get_sync_progress()?;
+                let connected = self.backend.get_network_status().ok()
+                    .and_then(|status| status.get("is_connected").and_then(|v| v.as_bool()))
+                    .unwrap_or(self.is_connected);
+                Ok(SyncStatus::from_progress(&progress, connected))
+            }
+        }
+    }
+
+    /// Add address to address book
+    pub fn add_address_book_entry(&self, address: &str, label: Option<&str>, description: Option<&str>) -> WalletResult<()> {
+        self.backend.add_address_book_entry(address, label, description)
+    }
+
+    /// Remove address from address book
+    pub fn remove_address_book_entry(&self, address: &str) -> WalletResult<()> {
+        self.backend.remove_address_book_entry(address)
+    }
+
+    /// Update address book entry
+    pub fn update_address_book_entry(&self, address: &str, label: Option<&str>, description: Option<&str>) -> WalletResult<()> {
+        self.backend.update_address_book_entry(address, label, description)
+    }
+
+    /// Get address book entries
+    pub fn get_address_book(&self) -> WalletResult<Vec<AddressBookEntry>> {
+        self.backend.get_address_book()
+    }
+
+    /// Mark address as used
+    pub fn mark_address_used(&self, address: &str) -> WalletResult<()> {
+        self.backend.mark_address_used(address)
+    }
+
+    /// Count a send to each of `addresses` that is in the address book, so
+    /// its `last_used_time` and `use_count` stay current. Failures are only
+    /// logged: the send itself went through.
+    pub fn record_contacts_used(&self, addresses: &[String]) {
+        let book = match self.get_address_book() {
+            Ok(book) => book,
+            Err(e) => {
+                log::debug!("Address book unavailable, not recording contact use: {}", e);
+                return;
+            }
+        };
+        for address in addresses.iter().filter(|a| book.iter().any(|e| &e.address == *a)) {
+            if let Err(e) = self.mark_address_used(address) {
+                log::warn!("Failed to mark {} as used: {}", address, e);
+            }
+        }
+    }
+
+    /// Get address book entry by address
+    pub fn get_address_book_entry(&self, address: &str) -> WalletResult<Option<AddressBookEntry>> {
+        self.backend.get_address_book_entry(address)
+    }
+
+    /// Set mining pool configuration
+    pub fn set_mining_pool(&self, pool_address: Option<&str>, worker_name: Option<&str>) -> WalletResult<()> {
+        self.backend.set_mining_pool(pool_address, worker_name)
+    }
+
+    /// Get detailed mining statistics as JSON
+    pub fn get_mining_stats_json(&self) -> WalletResult<String> {
+        self.backend.get_mining_stats_json()
+    }
+
+    /// Generate a new random seed phrase
+    pub fn generate_seed_phrase() -> WalletResult<String> {
+        let seed_str = unsafe { OwnedCStr::from_raw(fuego_wallet_generate_seed_phrase(), fuego_wallet_free_key_string) }
+            .ok_or_else(|| WalletError::Generic("Failed to generate seed phrase".to_string()))?
+            .to_string_lossy();
+
+        Ok(seed_str)
+    }
+
+    /// Validate a seed phrase
+    pub fn validate_seed_phrase(seed_phrase: &str) -> WalletResult<bool> {
+        let seed_c = CString::new(seed_phrase)?;
+        let is_valid = unsafe { fuego_wallet_validate_seed_phrase(seed_c.as_ptr()) };
+        Ok(is_valid)
+    }
+
+    /// Primary address a restore from `seed_phrase` would produce. Nothing is
+    /// created or written; an unknown word is reported by its 1-based position.
+    pub fn address_from_seed(seed_phrase: &str) -> WalletResult<String> {
+        let seed_c = CString::new(seed_phrase)?;
+        let mut invalid_word: i32 = -1;
+        let address = unsafe { OwnedCStr::from_raw(fuego_wallet_address_from_seed(seed_c.as_ptr(), &mut invalid_word), fuego_wallet_free_key_string) };
+
+        let Some(address) = address else {
+            let word = usize::try_from(invalid_word).ok()
+                .and_then(|index| seed_phrase.split_whitespace().nth(index).map(|word| (index, word)));
+            return Err(match word {
+                Some((index, word)) => WalletError::InvalidSeedWord { position: index + 1, word: word.to_string() },
+                None => WalletError::CryptoError("Invalid seed phrase".to_string()),
+            });
+        };
+
+        Ok(address.to_string_lossy())
+    }
+
+    /// Derive keys from seed phrase
+    pub fn derive_keys_from_seed(&self, seed_phrase: &str, password: &str) -> WalletResult<()> {
+        self.backend.derive_keys_from_seed(seed_phrase, password)
+    }
+
+    /// Get seed phrase (requires password for decryption)
+    pub fn get_seed_phrase(&self, password: &str) -> WalletResult<String> {
+        self.backend.get_seed_phrase(password)
+    }
+
+    /// Get view key
+    pub fn get_view_key(&self) -> WalletResult<String> {
+        self.backend.get_view_key()
+    }
+
+    /// Get spend key
+    pub fn get_spend_key(&self) -> WalletResult<String> {
+        self.backend.get_spend_key()
+    }
+
+    /// Check if wallet has keys
+    pub fn has_keys(&self) -> WalletResult<bool> {
+        self.backend.has_keys()
+    }
+
+    /// Export wallet keys
+    pub fn export_keys(&self) -> WalletResult<String> {
+        self.backend.export_keys()
+    }
+
+    /// Import wallet keys
+    pub fn import_keys(&self, view_key: &str, spend_key: &str, address: &str) -> WalletResult<()> {
+        self.backend.import_keys(view_key, spend_key, address)
+    }
+}
+
+// Default Fuego network nodes
+pub const FUEGO_NODES: &[(&str, u16)] = &[
+    ("fuego.spaceportx.net", 18180), // Real Fuego node with live blockchain data
+    ("node1.fuego.network", 18081),
+    ("node2.fuego.network", 18081),
+    ("node3.fuego.network", 18081),
+    ("127.0.0.1", 18081), // Local node for testing
+];
+
+/// RPC port of the wallet's own daemon while it answers
+static LOCAL_NODE_PORT: RwLock<Option<u16>> = RwLock::new(None);
+
+/// Try the daemon on 127.0.0.1:`port` before `FUEGO_NODES`, or stop trying
+/// it with `None`
+pub fn set_local_node(port: Option<u16>) {
+    *LOCAL_NODE_PORT.write_recover() = port;
+}
+
+/// RPC port of the local daemon connections try first
+pub fn local_node() -> Option<u16> {
+    *LOCAL_NODE_PORT.read_recover()
+}
+
+/// Nodes to connect to, the local daemon first while it answers
+pub fn known_nodes() -> Vec<(&'static str, u16)> {
+    local_node().map(|port| ("127.0.0.1", port)).into_iter().chain(FUEGO_NODES.iter().copied()).collect()
+}
+
+/// Fetch real network data from the daemon's `/getinfo`
+pub async fn fetch_fuego_network_data(client: &DaemonClient) -> WalletResult<serde_json::Value> {
+    Ok(client.get_info().await?.to_network_data())
+}
+
+/// The fee depends on the amount sent, which for a sweep is the balance minus
+/// the fee. Starting from the fee for the whole balance, which always covers
+/// the send, keep lowering it to the estimate for the amount left over as
+/// long as that estimate still covers its own send.
+fn converge_sweep_fee<F>(unlocked: u64, estimate: F) -> WalletResult<SweepEstimate>
+where
+    F: Fn(u64) -> WalletResult<u64>,
+{
+    if unlocked <= estimate(1)? {
+        return Err(WalletError::InsufficientFunds);
+    }
+
+    let mut fee = estimate(unlocked)?;
+    for _ in 0..MAX_SWEEP_FEE_ITERATIONS {
+        if fee >= unlocked {
+            return Err(WalletError::InsufficientFunds);
+        }
+        let lower = estimate(unlocked - fee)?;
+        if lower >= fee || estimate(unlocked - lower)? > lower {
+            break;
+        }
+        fee = lower;
+    }
+    if fee >= unlocked {
+        return Err(WalletError::InsufficientFunds);
+    }
+
+    Ok(SweepEstimate {
+        sendable_amount: unlocked - fee,
+        fee,
+        total: unlocked,
+    })
+}
+
+/// Check the textual format of a Fuego address (prefix, length and base58
+/// charset); integrated addresses are longer and must decode in full
+pub fn is_valid_address_format(address: &str) -> bool {
+    address.starts_with("fire")
+        && ((60..=120).contains(&address.len()) || is_integrated_address(address))
+        && bs58::decode(address).into_vec().is_ok()
+}
+
+/// Prefix of a proof that a transaction paid an address
+pub const TX_PROOF_PREFIX: &str = "OutProofV1";
+/// Base58 characters following the prefix, encoding a key derivation and a signature
+pub const TX_PROOF_BODY_LEN: usize = 132;
+
+/// Check the textual format of a payment proof: the prefix followed by the
+/// base58 of a key derivation and a signature
+pub fn is_well_formed_tx_proof(proof: &str) -> bool {
+    proof.strip_prefix(TX_PROOF_PREFIX)
+        .is_some_and(|body| body.len() == TX_PROOF_BODY_LEN && bs58::decode(body).into_vec().is_ok())
+}
+
+/// Check that `address:port` accepts TCP connections within `timeout`
+pub fn probe_node(address: &str, port: u16, timeout: Duration) -> WalletResult<()> {
+    let target = (address.to_string(), port);
+    let deadline = Instant::now() + timeout;
+
+    run_with_timeout(timeout, move || {
+        let mut last_error = "no address found".to_string();
+        for addr in target.to_socket_addrs().map_err(|e| e.to_string())? {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+            match TcpStream::connect_timeout(&addr, remaining) {
+                Ok(_) => return Ok(()),
+                Err(e) => last_error = e.to_string(),
+            }
+        }
+        Err(last_error)
+    })?
+    .map_err(|e| WalletError::NetworkError(format!("Failed to reach Fuego node {}:{}: {}", address, port, e)))
+}
+
+/// Run a blocking network call on a helper thread and stop waiting for it
+/// after `timeout`. Name resolution has no timeout of its own, so this is the
+/// only way to bound it.
+fn run_with_timeout<T, F>(timeout: Duration, call: F) -> WalletResult<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        let _ = sender.send(call());
+    });
+    receiver.recv_timeout(timeout)
+        .map_err(|_| WalletError::NetworkError("connection timed out".to_string()))
+}
+
+/// How `connect_to_fuego_network` retries a node that fails to connect
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ConnectRetrySettings {
+    /// Attempts on each node, the first one included
+    pub max_attempts: u32,
+    /// Delay before the first retry; doubled for each further one
+    pub base_delay_ms: u64,
+}
+
+impl Default for ConnectRetrySettings {
+    fn default() -> Self {
+        Self { max_attempts: 3, base_delay_ms: 500 }
+    }
+}
+
+impl ConnectRetrySettings {
+    pub fn policy(&self) -> RetryPolicy {
+        RetryPolicy::network("connect", None)
+            .with_max_attempts(self.max_attempts.max(1))
+            .with_base_delay(Duration::from_millis(self.base_delay_ms))
+    }
+}
+
+/// Where the automatic connection is in its retries
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ConnectRetryState {
+    /// `host:port` of the node being retried
+    pub node: Option<String>,
+    /// Attempts made on `node` so far
+    pub attempt: u32,
+    pub max_attempts: u32,
+    /// Unix time in milliseconds of the next attempt while backing off
+    pub next_retry_at_ms: Option<u64>,
+    pub last_error: Option<String>,
+}
+
+static CONNECT_RETRY_SETTINGS: RwLock<ConnectRetrySettings> = RwLock::new(ConnectRetrySettings { max_attempts: 3, base_delay_ms: 500 });
+
+static CONNECT_TIMEOUT: RwLock<Duration> = RwLock::new(DEFAULT_CONNECT_TIMEOUT);
+
+static CONNECT_RETRY_STATE: RwLock<ConnectRetryState> = RwLock::new(ConnectRetryState {
+    node: None,
+    attempt: 0,
+    max_attempts: 0,
+    next_retry_at_ms: None,
+    last_error: None,
+});
+
+/// Bumped to stop every backoff in progress
+static CONNECT_RETRY_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// How often a backoff checks whether it was cancelled
+const CONNECT_RETRY_POLL: Duration = Duration::from_millis(50);
+
+/// Retry connects as `settings` say from now on
+pub fn set_connect_retry(settings: ConnectRetrySettings) {
+    *CONNECT_RETRY_SETTINGS.write_recover() = settings;
+}
+
+/// Let every connect wait up to `timeout` for a node from now on, unless
+/// the wallet sets its own
+pub fn set_default_connect_timeout(timeout: Duration) {
+    *CONNECT_TIMEOUT.write_recover() = timeout;
+}
+
+/// Connect timeout of wallets without their own
+pub fn default_connect_timeout() -> Duration {
+    *CONNECT_TIMEOUT.read_recover()
+}
+
+/// Retry state of the latest automatic connection
+pub fn connect_retry_state() -> ConnectRetryState {
+    CONNECT_RETRY_STATE.read_recover().clone()
+}
+
+/// Stop every connection backing off, so a manual connect is not raced by
+/// an automatic one
+pub fn cancel_connect_retries() {
+    CONNECT_RETRY_GENERATION.fetch_add(1, Ordering::SeqCst);
+    *CONNECT_RETRY_STATE.write_recover() = ConnectRetryState::default();
+}
+
+/// Connect to the best available Fuego node. Blocks while backing off
+/// between attempts, so commands call it from `async_wallet::run`.
+pub fn connect_to_fuego_network(wallet: &mut RealCryptoNoteWallet) -> WalletResult<()> {
+    connect_to_fuego_network_until(wallet, &AtomicBool::new(false))
+}
+
+/// `connect_to_fuego_network`, not trying further nodes once `cancelled` is set
+pub fn connect_to_fuego_network_until(wallet: &mut RealCryptoNoteWallet, cancelled: &AtomicBool) -> WalletResult<()> {
+    let policy = CONNECT_RETRY_SETTINGS.read_recover().policy();
+    connect_with_retry(wallet, &known_nodes(), &policy, cancelled)
+}
+
+/// Try `nodes` in order, each up to `policy.max_attempts` times with a
+/// jittered exponential backoff between attempts. Gives up when `cancelled`
+/// is set or `cancel_connect_retries` is called.
+fn connect_with_retry(
+    wallet: &mut RealCryptoNoteWallet,
+    nodes: &[(&str, u16)],
+    policy: &RetryPolicy,
+    cancelled: &AtomicBool,
+) -> WalletResult<()> {
+    let generation = CONNECT_RETRY_GENERATION.load(Ordering::SeqCst);
+    let interrupted = || cancelled.load(Ordering::SeqCst) || CONNECT_RETRY_GENERATION.load(Ordering::SeqCst) != generation;
+    let cancel_error = || WalletError::NetworkError("Connection attempt cancelled".to_string());
+    // A cancelled connect leaves the state to whoever cancelled it
+    let record = |state: ConnectRetryState| {
+        if CONNECT_RETRY_GENERATION.load(Ordering::SeqCst) == generation {
+            *CONNECT_RETRY_STATE.write_recover() = state;
+        }
+    };
+    let attempts = policy.max_attempts.max(1);
+
+    for &(address, port) in nodes {
+        for attempt in 1..=attempts {
+            if interrupted() {
+                return Err(cancel_error());
+            }
+            match wallet.connect_to_node(address, port) {
+                Ok(_) => {
+                    log::info!("Successfully connected to Fuego node: {}:{}", address, port);
+                    record(ConnectRetryState::default());
+                    return Ok(());
+                }
+                Err(e) if attempt < attempts => {
+                    let delay = policy.delay(attempt, rand::random::<f64>());
+                    log::warn!(
+                        "Failed to connect to {}:{} (attempt {}/{}), retrying in {}ms: {}",
+                        address, port, attempt, attempts, delay.as_millis(), e
+                    );
+                    let next_retry_at = std::time::SystemTime::now() + delay;
+                    record(ConnectRetryState {
+                        node: Some(format!("{}:{}", address, port)),
+                        attempt,
+                        max_attempts: attempts,
+                        next_retry_at_ms: next_retry_at.duration_since(std::time::UNIX_EPOCH).ok()
+                            .map(|since| since.as_millis() as u64),
+                        last_error: Some(e.to_string()),
+                    });
+                    let deadline = Instant::now() + delay;
+                    while let Some(left) = deadline.checked_duration_since(Instant::now()).filter(|left| !left.is_zero()) {
+                        if interrupted() {
+                            return Err(cancel_error());
+                        }
+                        std::thread::sleep(left.min(CONNECT_RETRY_POLL));
+                    }
+                }
+                Err(e) => {
+                    log::warn!("Failed to connect to {}:{} - {}", address, port, e);
+                    record(ConnectRetryState {
+                        node: Some(format!("{}:{}", address, port)),
+                        attempt,
+                        max_attempts: attempts,
+                        next_retry_at_ms: None,
+                        last_error: Some(e.to_string()),
+                    });
+                }
+            }
+        }
+    }
+
+    Err(WalletError::NetworkError(
+        "Failed to connect to any Fuego network node".to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::testing::TempDir;
+
+    #[test]
+    fn test_address_format_validation() {
+        let valid = "fire1jNwRRUYGENanfBwVhehZXVcQVFx3dH3D3Z7UNC17FePBr27DDwctyL2ePwDPz4fypwpNQpfXbp6wavubvSn6ToisC5NUy";
+        assert!(is_valid_address_format(valid));
+        assert!(!is_valid_address_format("fire123"));
+        assert!(!is_valid_address_format(&valid.replacen("fire", "XFG0", 1)));
+        assert!(!is_valid_address_format(&valid.replace('N', "0")));
+    }
+
+    #[test]
+    fn test_progress_not_reported_without_open_wallet() {
+        let mut wallet = RealCryptoNoteWallet::new();
+        let (reporter, receiver) = ProgressReporter::channel("rescan");
+
+        assert!(matches!(wallet.rescan_blockchain(0, Some(&reporter)), Err(WalletError::WalletNotOpen)));
+        assert!(matches!(wallet.refresh_with_progress(Some(&reporter)), Err(WalletError::WalletNotOpen)));
+        drop(reporter);
+        assert_eq!(receiver.iter().count(), 0);
+    }
+
+    #[test]
+    fn test_wallet_address_empty_label_is_none() {
+        let json = r#"[{"index":0,"address":"fire1","label":""},{"index":1,"address":"fire2","label":"Savings"}]"#;
+        let addresses: Vec<WalletAddress> = serde_json::from_str(json).unwrap();
+        assert_eq!(addresses[0].label, None);
+        assert_eq!(addresses[1].label.as_deref(), Some("Savings"));
+        assert_eq!(addresses[1].index, 1);
+    }
+
+    #[test]
+    fn test_probe_gives_up_after_timeout() {
+        let timeout = Duration::from_millis(200);
+        let start = Instant::now();
+        let result = run_with_timeout(timeout, || std::thread::sleep(Duration::from_secs(5)));
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert!(matches!(result, Err(WalletError::NetworkError(e)) if e == "connection timed out"));
+
+        // Not routable: the probe ends within the window instead of blocking
+        let start = Instant::now();
+        let _ = probe_node("10.255.255.1", 18180, timeout);
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_probe_reaches_listening_node() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        assert!(probe_node("127.0.0.1", port, Duration::from_secs(5)).is_ok());
+
+        drop(listener);
+        assert!(probe_node("127.0.0.1", port, Duration::from_secs(5)).is_err());
+    }
+
+    #[test]
+    fn test_send_many_requires_open_wallet() {
+        let wallet = RealCryptoNoteWallet::new();
+        let result = wallet.send_many(&[("fire1".to_string(), 1)], 5, &HashSet::new());
+        assert!(matches!(result, Err(WalletError::WalletNotOpen)));
+    }
+
+    #[test]
+    fn test_address_from_seed_is_stable_and_names_bad_word() {
+        let seed = RealCryptoNoteWallet::generate_seed_phrase().unwrap();
+        let address = RealCryptoNoteWallet::address_from_seed(&seed).unwrap();
+        assert!(is_valid_address_format(&address));
+        assert_eq!(RealCryptoNoteWallet::address_from_seed(&seed).unwrap(), address);
+
+        let mut words: Vec<&str> = seed.split_whitespace().collect();
+        words[2] = "notaword";
+        let result = RealCryptoNoteWallet::address_from_seed(&words.join(" "));
+        assert!(matches!(result, Err(WalletError::InvalidSeedWord { position: 3, ref word }) if word == "notaword"));
+        assert!(matches!(RealCryptoNoteWallet::address_from_seed("abandon ability"), Err(WalletError::CryptoError(_))));
+    }
+
+    #[test]
+    fn test_sweep_fee_converges() {
+        // 1000 plus 1 per 1000 atomic units sent
+        let estimate = |amount: u64| Ok(1_000 + amount / 1_000);
+
+        let sweep = converge_sweep_fee(1_000_000, estimate).unwrap();
+        assert_eq!(sweep, SweepEstimate { sendable_amount: 998_002, fee: 1_998, total: 1_000_000 });
+        assert!(estimate(sweep.sendable_amount).unwrap() <= sweep.fee);
+
+        assert!(matches!(converge_sweep_fee(1_000, estimate), Err(WalletError::InsufficientFunds)));
+        assert!(matches!(converge_sweep_fee(0, estimate), Err(WalletError::InsufficientFunds)));
+        assert_eq!(converge_sweep_fee(5_000, |_| Ok(10)).unwrap().sendable_amount, 4_990);
+    }
+
+    #[test]
+    fn test_integrated_address_sends_its_payment_id() {
+        use crate::crypto::address::{encode_address, make_integrated_address, FUEGO_ADDRESS_PREFIX};
+        use crate::crypto::backend::MockWalletBackend;
+
+        let backend = MockWalletBackend::with_balance(1_000_000);
+        let wallet = RealCryptoNoteWallet::with_backend(Box::new(backend.clone()));
+        let standard = encode_address(FUEGO_ADDRESS_PREFIX, &[7u8; 64]);
+        let payment_id = "ab".repeat(32);
+        let integrated = make_integrated_address(&standard, &payment_id).unwrap();
+        assert!(is_valid_address_format(&integrated));
+
+        wallet.send_transaction(&integrated, 1_000, None, 5).unwrap();
+        let conflicting = "cd".repeat(32);
+        assert!(matches!(wallet.send_transaction(&integrated, 1_000, Some(&conflicting), 5), Err(WalletError::TransactionFailed(_))));
+        assert!(wallet.send_many(&[(integrated, 1_000)], 5, &HashSet::new()).is_err());
+
+        let sent = &backend.state().transactions;
+        assert_eq!(sent.len(), 1);
+        assert_eq!((sent[0].destination_addresses.clone(), sent[0].payment_id.clone()), (vec![standard], Some(payment_id)));
+    }
+
+    #[test]
+    fn test_emission_info_unsupported_by_default() {
+        use crate::crypto::backend::MockWalletBackend;
+
+        let backend = MockWalletBackend::with_balance(0);
+        backend.state().network_height = 950_000;
+        let wallet = RealCryptoNoteWallet::with_backend(Box::new(backend.clone()));
+        let emission = wallet.get_emission_info().unwrap();
+        assert_eq!(emission, EmissionInfo::unsupported(950_000));
+        assert_eq!(
+            serde_json::to_value(&emission).unwrap(),
+            serde_json::json!({
+                "supported": false, "height": 950_000, "block_reward": null,
+                "total_emission": null, "circulating_supply": null, "next_reward": null,
+            })
+        );
+
+        // The native library may leave out what its daemon does not report
+        let partial: EmissionInfo = serde_json::from_str(r#"{"supported":true,"height":7,"block_reward":3005769}"#).unwrap();
+        assert_eq!((partial.block_reward, partial.next_reward), (Some(3005769), None));
+
+        let reported = EmissionInfo { supported: true, height: 950_000, block_reward: Some(3005769), ..Default::default() };
+        backend.state().emission = Some(reported.clone());
+        assert_eq!(wallet.get_emission_info().unwrap(), reported);
+    }
+
+    #[test]
+    fn test_verify_integrity_reports_corruption() {
+        use crate::crypto::backend::MockWalletBackend;
+
+        let report = integrity_from_verify_json(
+            r#"{"corrupted":false,"keys_readable":true,"cache_consistent":false,"key_image_count":4,"last_processed_height":900}"#,
+        ).unwrap();
+        assert_eq!((report.key_image_count, report.last_processed_height, report.rescan_recommended), (4, 900, true));
+
+        let recovery = |json: &str| match integrity_from_verify_json(json) {
+            Err(WalletError::WalletCorrupted { recovery, .. }) => recovery,
+            other => panic!("expected corruption, got {:?}", other),
+        };
+        assert_eq!(recovery(r#"{"corrupted":true,"reason":"bad key block","keys_readable":false}"#), RecoveryAction::RestoreFromSeed);
+        assert_eq!(recovery(r#"{"corrupted":true,"reason":"truncated cache","keys_readable":true}"#), RecoveryAction::RestoreFromBackup);
+
+        let dir = TempDir::new("fuego_verify");
+        let path = dir.join("test.wallet");
+        let backend = MockWalletBackend::new();
+        backend.state().sync_height = 1234;
+        let wallet = RealCryptoNoteWallet::with_backend(Box::new(backend.clone()));
+        assert!(matches!(wallet.verify_integrity(path.to_str().unwrap(), "pw"), Err(WalletError::WalletFileNotFound(_))));
+
+        std::fs::write(&path, b"wallet").unwrap();
+        let report = wallet.verify_integrity(path.to_str().unwrap(), "pw").unwrap();
+        assert_eq!((report.file_size, report.last_processed_height, report.rescan_recommended), (6, 1234, false));
+        backend.state().corruption = Some("checksum mismatch".to_string());
+        assert!(matches!(wallet.verify_integrity(path.to_str().unwrap(), "pw"), Err(WalletError::WalletCorrupted { .. })));
+        assert_eq!(std::fs::read(&path).unwrap(), b"wallet");
+    }
+
+    #[test]
+    fn test_fee_breakdown_grows_with_mixin() {
+        use crate::crypto::backend::MockWalletBackend;
+
+        let wallet = RealCryptoNoteWallet::with_backend(Box::new(MockWalletBackend::with_balance(1_000_000)));
+        let address = "fire1jNwRRUYGENanfBwVhehZXVcQVFx3dH3D3Z7UNC17FePBr27DDwctyL2ePwDPz4fypwpNQpfXbp6wavubvSn6ToisC5NUy";
+        let low = wallet.estimate_fee_breakdown(address, 1_000, 0).unwrap();
+        let high = wallet.estimate_fee_breakdown(address, 1_000, 10).unwrap();
+
+        assert_eq!(low.total, low.base_fee + low.size_fee + low.priority_fee);
+        assert_eq!(wallet.estimate_transaction_fee(address, 1_000, 10).unwrap(), high.total);
+        assert_eq!(high.estimated_size_bytes - low.estimated_size_bytes, 10 * 68);
+        assert_eq!(estimated_transaction_size(1, 2, 0), 100 + 40 + 68 + 80);
+    }
+
+    #[test]
+    fn test_transaction_size_matches_reference_sizes() {
+        // One input, payment and change, no decoys
+        assert_eq!(estimate_transaction_size(1, 0, None), 288);
+        // One input ringed with 5 decoys
+        assert_eq!(estimate_transaction_size(1, 5, Some(1)), 100 + (40 + 6 * 68) + 2 * 40);
+        // Four inputs with 2 decoys each, three recipients and change
+        assert_eq!(estimate_transaction_size(3, 2, Some(4)), 1_236);
+
+        // 1015 inputs of 788 bytes at mixin 10 no longer fit
+        assert!(check_transaction_size(estimate_transaction_size(1, 10, Some(1_014))).is_ok());
+        let err = check_transaction_size(estimate_transaction_size(1, 10, Some(1_015))).unwrap_err();
+        assert!(matches!(err, WalletError::TransactionTooLarge { max: MAX_TRANSACTION_SIZE, .. }));
+        assert!(err.to_string().contains("split the payment or lower the mixin"));
+    }
+
+    #[test]
+    fn test_send_preview_reports_size_and_fee_per_kb() {
+        use crate::crypto::backend::MockWalletBackend;
+
+        let wallet = RealCryptoNoteWallet::with_backend(Box::new(MockWalletBackend::with_balance(1_000_000)));
+        let address = "fire1jNwRRUYGENanfBwVhehZXVcQVFx3dH3D3Z7UNC17FePBr27DDwctyL2ePwDPz4fypwpNQpfXbp6wavubvSn6ToisC5NUy";
+        let preview = wallet.preview_send(address, 1_000, 0, None).unwrap();
+
+        assert_eq!(preview.fee.estimated_size_bytes, 288);
+        assert_eq!(preview.fee.per_kb_fee, 10_000 * 1024 / 288);
+        assert_eq!(preview.total, 11_000);
+        let json = serde_json::to_value(preview).unwrap();
+        assert_eq!(json["estimated_size_bytes"], 288);
+        assert!(matches!(wallet.preview_send(address, 1_000, 10, Some(2_000)), Err(WalletError::TransactionTooLarge { .. })));
+    }
+
+    #[test]
+    fn test_tampered_proof_fails_and_malformed_proof_errors() {
+        use crate::crypto::backend::MockWalletBackend;
+
+        let backend = MockWalletBackend::with_balance(1_000_000);
+        let wallet = RealCryptoNoteWallet::with_backend(Box::new(backend));
+        let recipient = wallet.get_address().unwrap();
+        let tx_hash = wallet.send_transaction(&recipient, 1_000, None, 0).unwrap();
+
+        let proof = wallet.get_tx_proof(&tx_hash, &recipient, Some("invoice 7")).unwrap();
+        assert!(is_well_formed_tx_proof(&proof));
+        assert!(wallet.check_tx_proof(&tx_hash, &recipient, Some("invoice 7"), &proof).unwrap());
+        assert!(!wallet.check_tx_proof(&tx_hash, &recipient, Some("invoice 8"), &proof).unwrap());
+
+        let last = proof.chars().last().unwrap();
+        let tampered = format!("{}{}", &proof[..proof.len() - 1], if last == 'z' { 'y' } else { 'z' });
+        assert!(!wallet.check_tx_proof(&tx_hash, &recipient, Some("invoice 7"), &tampered).unwrap());
+
+        for malformed in ["", "OutProofV1", &proof[..proof.len() - 1], &proof.replacen("OutProofV1", "InProofV1", 1), &proof.replace(last, "0")] {
+            assert!(matches!(
+                wallet.check_tx_proof(&tx_hash, &recipient, None, malformed),
+                Err(WalletError::CryptoError(_))
+            ));
+        }
+        assert!(wallet.get_tx_proof("unknown", &recipient, None).is_err());
+    }
+
+    #[test]
+    fn test_sync_phase_read_from_status_or_derived() {
+        let status = |json: &str| SyncStatus::from_json(json).unwrap();
+        let remote = r#""connection_type":"Fuego Network (XFG) - fuego.spaceportx.net""#;
+
+        let downloading = status(&format!(r#"{{"current_height":500,"total_height":965000,"progress_percentage":0.05,
+            "estimated_seconds_remaining":9645,"is_syncing":true,{}}}"#, remote));
+        assert_eq!((downloading.phase, downloading.connection_type), (SyncPhase::DownloadingBlocks, ConnectionType::Remote));
+        assert_eq!(downloading.estimated_seconds_remaining, 9645);
+
+        // Every block is in but the wallet is still going through them
+        let scanning = status(&format!(r#"{{"current_height":965000,"total_height":965000,"is_syncing":true,{}}}"#, remote));
+        assert_eq!(scanning.phase, SyncPhase::Scanning);
+        let scanning = status(&format!(r#"{{"current_height":965000,"total_height":965000,"scanned_height":960000,"is_syncing":false,{}}}"#, remote));
+        assert_eq!(scanning.phase, SyncPhase::Scanning);
+        let synced = status(&format!(r#"{{"current_height":965000,"total_height":965000,"is_syncing":false,{}}}"#, remote));
+        assert_eq!(synced.phase, SyncPhase::Synced);
+
+        assert_eq!(status(&format!(r#"{{"current_height":0,"total_height":0,"is_syncing":true,{}}}"#, remote)).phase, SyncPhase::Connecting);
+        assert_eq!(status(r#"{"current_height":0,"total_height":0,"is_syncing":false,"connection_type":"Disconnected"}"#).phase, SyncPhase::Disconnected);
+        // A phase the native wallet names wins
+        assert_eq!(status(&format!(r#"{{"current_height":10,"total_height":20,"is_syncing":true,"phase":"Scanning",{}}}"#, remote)).phase, SyncPhase::Scanning);
+    }
+
+    #[test]
+    fn test_failed_auto_save_keeps_the_send() {
+        use crate::crypto::backend::MockWalletBackend;
+
+        let backend = MockWalletBackend::with_balance(1_000_000);
+        let wallet = RealCryptoNoteWallet::with_backend(Box::new(backend.clone()));
+        let recipient = backend.state().address.clone();
+
+        wallet.send_transaction(&recipient, 1_000, None, 5).unwrap();
+        assert_eq!(backend.state().saves, 1);
+
+        backend.state().save_error = Some("Disk full".to_string());
+        wallet.send_transaction(&recipient, 2_000, None, 5).unwrap();
+        assert_eq!(backend.state().transactions.len(), 2);
+        assert!(matches!(wallet.save(), Err(WalletError::StorageError(_))));
+
+        backend.state().save_error = None;
+        wallet.save().unwrap();
+        assert_eq!(backend.state().saves, 2);
+    }
+
+    #[test]
+    fn test_create_refuses_to_overwrite_and_returns_the_seed_once() {
+        use crate::crypto::backend::MockWalletBackend;
+
+        let dir = TempDir::new("fuego_create");
+        let path = dir.join("existing.wallet");
+        std::fs::write(&path, b"keys").unwrap();
+        let path = path.to_string_lossy().to_string();
+        let backend = MockWalletBackend::new();
+        let mut wallet = RealCryptoNoteWallet::with_backend(Box::new(backend.clone()));
+
+        let err = wallet.create_new_wallet("pw", &path, None, 0, false).unwrap_err();
+        assert!(matches!(err, WalletError::WalletAlreadyExists(_)));
+        assert!(!backend.state().is_open);
+        assert_eq!(std::fs::read(&path).unwrap(), b"keys");
+
+        let created = wallet.create_new_wallet("pw", &path, None, 0, true).unwrap();
+        assert_eq!(created.seed_phrase.as_deref(), Some(backend.state().seed_phrase.as_str()));
+        assert!(!format!("{:?}", created).contains(&backend.state().seed_phrase));
+        assert!(WalletMetadata::load(std::path::Path::new(&path)).created_at.is_some());
+
+        // A restore returns no seed: the caller has it already
+        let restored = dir.join("restored.wallet").to_string_lossy().to_string();
+        let created = wallet.create_new_wallet("pw", &restored, Some("restored seed"), 0, false).unwrap();
+        assert!(created.seed_phrase.is_none());
+    }
+
+    #[test]
+    fn test_sends_are_counted_for_contacts_only() {
+        use crate::crypto::backend::MockWalletBackend;
+
+        let backend = MockWalletBackend::with_balance(1_000_000);
+        backend.state().address_book.push(AddressBookEntry {
+            address: "fire1alice".to_string(),
+            label: "Alice".to_string(),
+            description: String::new(),
+            created_time: 0,
+            last_used_time: 0,
+            use_count: 0,
+        });
+        let wallet = RealCryptoNoteWallet::with_backend(Box::new(backend.clone()));
+
+        wallet.record_contacts_used(&["fire1alice".to_string(), "fire1stranger".to_string()]);
+        wallet.record_contacts_used(&["fire1alice".to_string()]);
+        let alice = backend.state().address_book[0].clone();
+        assert_eq!(alice.use_count, 2);
+        assert!(alice.last_used_time > 0);
+        assert_eq!(backend.state().address_book.len(), 1);
+    }
+
+    #[test]
+    fn test_native_abi_sizes_match_bindings() {
+        assert_eq!(native_abi_sizes(), AbiSizesFFI::expected());
+        // `NetworkStatus` is not laid out like `NetworkInfo`
+        assert_ne!(AbiSizesFFI::expected().network_status, AbiSizesFFI::expected().network_info);
+    }
+
+    #[test]
+    fn test_connection_type_known_values() {
+        assert_eq!(ConnectionType::from_raw("Disconnected"), ConnectionType::Disconnected);
+        assert_eq!(ConnectionType::from_raw(""), ConnectionType::Disconnected);
+        assert_eq!(
+            ConnectionType::from_raw("Fuego Network (XFG) - 127.0.0.1:18180"),
+            ConnectionType::Local
+        );
+        assert_eq!(
+            ConnectionType::from_raw("Fuego Network (XFG) - fuego.spaceportx.net"),
+            ConnectionType::Remote
+        );
+        assert_eq!(ConnectionType::from_raw("Bootstrap daemon"), ConnectionType::BootstrapDaemon);
+    }
+
+    #[test]
+    fn test_connection_type_unknown_defaults_to_remote() {
+        assert_eq!(ConnectionType::from_raw("RPC"), ConnectionType::Remote);
+    }
+
+    #[test]
+    fn test_connect_retries_each_node_with_backoff() {
+        use crate::crypto::backend::MockWalletBackend;
+        let nodes = [("node1", 18180), ("node2", 18180)];
+        let mut policy = ConnectRetrySettings { max_attempts: 2, base_delay_ms: 1 }.policy();
+        policy.jitter = 0.0;
+        let backend = MockWalletBackend::with_balance(0);
+        let mut wallet = RealCryptoNoteWallet::with_backend(Box::new(backend.clone()));
+        let tried = || backend.state().connect_attempts.iter().map(|(host, _)| host.clone()).collect::<Vec<_>>();
+
+        // node1 gets its second attempt, and connecting clears the retry state
+        backend.state().connect_failures = 1;
+        connect_with_retry(&mut wallet, &nodes, &policy, &AtomicBool::new(false)).unwrap();
+        assert_eq!(tried(), vec!["node1", "node1"]);
+        assert_eq!(connect_retry_state(), ConnectRetryState::default());
+
+        // Both nodes run out of attempts
+        backend.state().connect_attempts.clear();
+        backend.state().connect_failures = 4;
+        assert!(connect_with_retry(&mut wallet, &nodes, &policy, &AtomicBool::new(false)).is_err());
+        assert_eq!(tried(), vec!["node1", "node1", "node2", "node2"]);
+        let state = connect_retry_state();
+        assert_eq!((state.node.as_deref(), state.attempt, state.next_retry_at_ms), (Some("node2:18180"), 2, None));
+
+        // A manual connect stops the backoff in progress
+        backend.state().connect_attempts.clear();
+        backend.state().connect_failures = 1;
+        let slow = ConnectRetrySettings { max_attempts: 2, base_delay_ms: 60_000 }.policy();
+        let started = Instant::now();
+        let cancel = std::thread::spawn(|| {
+            while connect_retry_state().next_retry_at_ms.is_none() {
+                std::thread::sleep(Duration::from_millis(5));
+            }
+            cancel_connect_retries();
+        });
+        assert!(connect_with_retry(&mut wallet, &nodes, &slow, &AtomicBool::new(false)).is_err());
+        cancel.join().unwrap();
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(tried(), vec!["node1"]);
+        assert_eq!(connect_retry_state(), ConnectRetryState::default());
+    }
+
+    #[test]
+    fn test_connection_type_serializes_as_variant_name() {
+        let value = serde_json::to_value(ConnectionType::BootstrapDaemon).unwrap();
+        assert_eq!(value, serde_json::json!("BootstrapDaemon"));
+    }
 }
@@ -0,0 +1,353 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Vanity address search
+//!
+//! Workers generate fresh seeds and derive their primary address until one
+//! reads the chosen pattern right after the `fire` network tag. Progress
+//! carries the attempt count and throughput only; the seed of a match stays
+//! here until it is taken once, after the password is checked again.
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use crate::crypto::address::ALPHABET;
+use crate::utils::error::WalletResult;
+use crate::utils::locks::MutexExt;
+
+/// Event carrying a `VanityProgress`
+pub const VANITY_PROGRESS_EVENT: &str = "vanity://progress";
+/// How often progress is reported
+pub const VANITY_PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+/// Longest pattern searched for; each character multiplies the expected
+/// attempts by up to 58
+pub const MAX_PATTERN_LENGTH: usize = 6;
+/// Start of every Fuego address, set by the network prefix
+const ADDRESS_TAG: &str = "fire";
+
+/// The characters an address should start with after its tag
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VanityPattern {
+    pattern: String,
+    case_sensitive: bool,
+}
+
+impl VanityPattern {
+    /// Refuses patterns no address can match: empty, too long, or with a
+    /// character outside the base58 alphabet (`0`, `O`, `I`, `l` and
+    /// anything not alphanumeric) in every case compared
+    pub fn new(pattern: &str, case_sensitive: bool) -> Result<Self, String> {
+        let pattern = pattern.trim();
+        if pattern.is_empty() {
+            return Err("Vanity pattern is empty".to_string());
+        }
+        if pattern.chars().count() > MAX_PATTERN_LENGTH {
+            return Err(format!("Vanity pattern is longer than {} characters", MAX_PATTERN_LENGTH));
+        }
+        if let Some(c) = pattern.chars().find(|&c| Self::variants(c, case_sensitive) == 0) {
+            return Err(format!("'{}' never appears in an address", c));
+        }
+        Ok(Self { pattern: pattern.to_string(), case_sensitive })
+    }
+
+    /// Whether `address` reads the pattern after its tag
+    pub fn matches(&self, address: &str) -> bool {
+        let Some(rest) = address.strip_prefix(ADDRESS_TAG) else {
+            return false;
+        };
+        let Some(start) = rest.get(..self.pattern.len()) else {
+            return false;
+        };
+        if self.case_sensitive {
+            start == self.pattern
+        } else {
+            start.eq_ignore_ascii_case(&self.pattern)
+        }
+    }
+
+    /// Addresses generated, on average, before one matches
+    pub fn expected_attempts(&self) -> f64 {
+        self.pattern.chars()
+            .map(|c| ALPHABET.len() as f64 / Self::variants(c, self.case_sensitive) as f64)
+            .product()
+    }
+
+    /// Base58 characters `c` matches
+    fn variants(c: char, case_sensitive: bool) -> usize {
+        let in_alphabet = |c: char| c.is_ascii() && ALPHABET.contains(&(c as u8));
+        if case_sensitive || !c.is_ascii_alphabetic() {
+            usize::from(in_alphabet(c))
+        } else {
+            usize::from(in_alphabet(c.to_ascii_lowercase())) + usize::from(in_alphabet(c.to_ascii_uppercase()))
+        }
+    }
+}
+
+/// A generated seed and the primary address it restores
+#[derive(Clone, Serialize)]
+pub struct VanityCandidate {
+    pub address: String,
+    pub seed_phrase: String,
+}
+
+impl std::fmt::Debug for VanityCandidate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VanityCandidate")
+            .field("address", &self.address)
+            .field("seed_phrase", &"<redacted>")
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VanityStatus {
+    Running,
+    Found,
+    Cancelled,
+    Failed,
+}
+
+/// Where a search stands, as reported to the frontend
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VanityProgress {
+    pub status: VanityStatus,
+    pub attempts: u64,
+    pub elapsed_seconds: f64,
+    pub keys_per_second: f64,
+    pub expected_attempts: f64,
+    pub threads: usize,
+    /// Address of the match, once found; its seed is only revealed on request
+    pub found_address: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct Outcome {
+    found: Option<VanityCandidate>,
+    /// Set once the match was revealed, so it is handed out once
+    revealed: bool,
+    error: Option<String>,
+}
+
+/// One search, shared by its workers
+#[derive(Debug)]
+pub struct VanityJob {
+    pattern: VanityPattern,
+    threads: usize,
+    started: Instant,
+    attempts: AtomicU64,
+    cancelled: AtomicBool,
+    /// Workers that have not returned yet
+    running: AtomicUsize,
+    outcome: Mutex<Outcome>,
+}
+
+impl VanityJob {
+    pub fn new(pattern: VanityPattern, threads: usize, started: Instant) -> Self {
+        Self {
+            pattern,
+            threads,
+            started,
+            attempts: AtomicU64::new(0),
+            cancelled: AtomicBool::new(false),
+            running: AtomicUsize::new(threads),
+            outcome: Mutex::new(Outcome::default()),
+        }
+    }
+
+    pub fn pattern(&self) -> &VanityPattern {
+        &self.pattern
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Generate candidates with `generate` until one passes `matches`, the
+    /// search is cancelled, or another worker finds or fails first
+    pub fn run_worker(
+        &self,
+        generate: impl Fn() -> WalletResult<VanityCandidate>,
+        matches: impl Fn(&str) -> bool,
+    ) {
+        while !self.is_stopped() {
+            match generate() {
+                Ok(candidate) => {
+                    self.attempts.fetch_add(1, Ordering::Relaxed);
+                    if matches(&candidate.address) {
+                        let mut outcome = self.outcome.lock_recover();
+                        if outcome.found.is_none() && outcome.error.is_none() {
+                            outcome.found = Some(candidate);
+                        }
+                    }
+                }
+                Err(e) => {
+                    let mut outcome = self.outcome.lock_recover();
+                    outcome.error.get_or_insert_with(|| e.to_string());
+                }
+            }
+        }
+        self.running.fetch_sub(1, Ordering::AcqRel);
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether workers should return
+    fn is_stopped(&self) -> bool {
+        if self.cancelled.load(Ordering::Relaxed) {
+            return true;
+        }
+        let outcome = self.outcome.lock_recover();
+        outcome.found.is_some() || outcome.error.is_some()
+    }
+
+    /// Whether every worker has returned
+    pub fn is_finished(&self) -> bool {
+        self.running.load(Ordering::Acquire) == 0
+    }
+
+    /// Progress as of `now`
+    pub fn progress_at(&self, now: Instant) -> VanityProgress {
+        let attempts = self.attempts.load(Ordering::Relaxed);
+        let elapsed = now.saturating_duration_since(self.started).as_secs_f64();
+        let outcome = self.outcome.lock_recover();
+        let status = if outcome.found.is_some() {
+            VanityStatus::Found
+        } else if outcome.error.is_some() {
+            VanityStatus::Failed
+        } else if self.cancelled.load(Ordering::Relaxed) {
+            VanityStatus::Cancelled
+        } else {
+            VanityStatus::Running
+        };
+        VanityProgress {
+            status,
+            attempts,
+            elapsed_seconds: elapsed,
+            keys_per_second: if elapsed > 0.0 { attempts as f64 / elapsed } else { 0.0 },
+            expected_attempts: self.pattern.expected_attempts(),
+            threads: self.threads,
+            found_address: outcome.found.as_ref().map(|c| c.address.clone()),
+            error: outcome.error.clone(),
+        }
+    }
+
+    pub fn progress(&self) -> VanityProgress {
+        self.progress_at(Instant::now())
+    }
+
+    /// The match with its seed, the first time it is asked for
+    pub fn take_found(&self) -> Option<VanityCandidate> {
+        let mut outcome = self.outcome.lock_recover();
+        if outcome.revealed {
+            return None;
+        }
+        let found = outcome.found.clone()?;
+        outcome.revealed = true;
+        Some(found)
+    }
+}
+
+/// The current vanity search, one at a time
+#[derive(Debug, Default)]
+pub struct VanitySearch {
+    job: Mutex<Option<Arc<VanityJob>>>,
+}
+
+impl VanitySearch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Make `job` the current search, unless one is still running
+    pub fn start(&self, job: VanityJob) -> Result<Arc<VanityJob>, String> {
+        let mut current = self.job.lock_recover();
+        if current.as_ref().is_some_and(|job| !job.is_finished()) {
+            return Err("A vanity search is running already".to_string());
+        }
+        let job = Arc::new(job);
+        *current = Some(job.clone());
+        Ok(job)
+    }
+
+    pub fn current(&self) -> Option<Arc<VanityJob>> {
+        self.job.lock_recover().clone()
+    }
+
+    /// Stop the current search; returns false if there is none running
+    pub fn cancel(&self) -> bool {
+        match self.current() {
+            Some(job) if !job.is_finished() => {
+                job.cancel();
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(index: u64) -> VanityCandidate {
+        VanityCandidate { address: format!("fire{:06}", index), seed_phrase: format!("seed {}", index) }
+    }
+
+    #[test]
+    fn test_patterns_outside_base58_are_refused() {
+        assert!(VanityPattern::new("Fuel", true).is_err()); // lowercase l is not base58
+        assert!(VanityPattern::new("Fuel", false).is_ok());
+        assert!(VanityPattern::new("0x", false).is_err());
+        assert!(VanityPattern::new("Oslo", true).is_err());
+        assert!(VanityPattern::new("Oslo", false).is_ok());
+        assert!(VanityPattern::new("abcdefg", true).is_err());
+        assert!(VanityPattern::new(" ", true).is_err());
+
+        let pattern = VanityPattern::new("Ab", false).unwrap();
+        assert!(pattern.matches("fireaBcd") && pattern.matches("fireAB"));
+        assert!(!pattern.matches("firA") && !pattern.matches("fireb"));
+        assert_eq!(pattern.expected_attempts(), 29.0 * 29.0);
+        assert_eq!(VanityPattern::new("Ab", true).unwrap().expected_attempts(), 58.0 * 58.0);
+        assert!(!VanityPattern::new("Ab", true).unwrap().matches("fireaB"));
+    }
+
+    #[test]
+    fn test_throughput_counts_every_attempt() {
+        let started = Instant::now();
+        let job = VanityJob::new(VanityPattern::new("1", true).unwrap(), 1, started);
+        let next = AtomicU64::new(0);
+        // Mock matcher: the 250th candidate matches
+        job.run_worker(|| Ok(candidate(next.fetch_add(1, Ordering::Relaxed))), |address| address == "fire000249");
+
+        assert!(job.is_finished());
+        let progress = job.progress_at(started + Duration::from_secs(5));
+        assert_eq!((progress.status, progress.attempts), (VanityStatus::Found, 250));
+        assert_eq!((progress.elapsed_seconds, progress.keys_per_second), (5.0, 50.0));
+        assert_eq!(progress.found_address.as_deref(), Some("fire000249"));
+        assert_eq!(job.take_found().unwrap().seed_phrase, "seed 249");
+        assert!(job.take_found().is_none());
+    }
+
+    #[test]
+    fn test_cancel_stops_the_workers() {
+        let search = VanitySearch::new();
+        let job = search.start(VanityJob::new(VanityPattern::new("z", true).unwrap(), 2, Instant::now())).unwrap();
+        let workers: Vec<_> = (0..2).map(|_| {
+            let job = job.clone();
+            std::thread::spawn(move || job.run_worker(|| Ok(candidate(0)), |_| false))
+        }).collect();
+        assert!(search.start(VanityJob::new(VanityPattern::new("z", true).unwrap(), 1, Instant::now())).is_err());
+
+        assert!(search.cancel());
+        workers.into_iter().for_each(|w| w.join().unwrap());
+        assert!(job.is_finished());
+        assert_eq!(job.progress().status, VanityStatus::Cancelled);
+        assert!(job.take_found().is_none());
+        assert!(!search.cancel());
+    }
+}
@@ -0,0 +1,494 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Local daemon
+//!
+//! Runs a `fuegod` for users who would rather not trust a remote node. The
+//! binary is the one set in the network settings or the one bundled next to
+//! the wallet executable. Its output goes to `fuegod.log` in the log
+//! directory, rotated like the wallet's own log. The supervisor polls the
+//! daemon's `/getinfo`; while it answers, wallets connect to it before the
+//! public nodes. A daemon that exits on its own is started again with growing
+//! backoff, at most `MAX_RESTARTS` times, and stopped with the app.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::{Child, Command};
+use crate::crypto::daemon::fetch_info;
+use crate::crypto::real_cryptonote::set_local_node;
+use crate::utils::locks::MutexExt;
+use crate::utils::logging::{self, RotatingFile};
+use crate::utils::storage::APP_DIR_NAME;
+
+pub const DAEMON_LOG_FILE_NAME: &str = "fuegod.log";
+/// Restarts after a crash before the supervisor gives up
+pub const MAX_RESTARTS: u32 = 3;
+/// Wait before the first restart, doubled for each one after it
+const RESTART_BACKOFF: Duration = Duration::from_secs(2);
+const HEALTH_INTERVAL: Duration = Duration::from_secs(10);
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(5);
+/// Time the daemon gets to shut down before it is killed
+const STOP_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Where the local daemon lives and the ports it listens on
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DaemonSettings {
+    /// `fuegod` to run; the bundled one when unset
+    pub binary_path: Option<String>,
+    /// Blockchain directory; `<data_dir>/fuego-wallet/daemon` when unset
+    pub data_dir: Option<String>,
+    pub rpc_port: u16,
+    pub p2p_port: u16,
+}
+
+impl Default for DaemonSettings {
+    fn default() -> Self {
+        Self {
+            binary_path: None,
+            data_dir: None,
+            rpc_port: 18180,
+            p2p_port: 10808,
+        }
+    }
+}
+
+fn binary_name() -> &'static str {
+    if cfg!(windows) { "fuegod.exe" } else { "fuegod" }
+}
+
+/// The configured daemon binary, else the one bundled with the wallet
+pub fn locate_binary(settings: &DaemonSettings) -> Result<PathBuf, String> {
+    if let Some(path) = settings.binary_path.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+        let path = PathBuf::from(path);
+        return if path.is_file() {
+            Ok(path)
+        } else {
+            Err(format!("Daemon binary not found: {}", path.display()))
+        };
+    }
+
+    let exe = std::env::current_exe()
+        .map_err(|e| format!("Failed to locate the wallet executable: {}", e))?;
+    let dir = exe.parent().unwrap_or(Path::new("."));
+    // Next to the executable, or in the resources of a macOS bundle
+    [dir.join(binary_name()), dir.join("../Resources").join(binary_name())]
+        .into_iter()
+        .find(|path| path.is_file())
+        .ok_or_else(|| format!(
+            "No {} bundled in {}; set the daemon path in the network settings",
+            binary_name(),
+            dir.display()
+        ))
+}
+
+/// Everything needed to launch the daemon
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DaemonLaunch {
+    pub binary: PathBuf,
+    pub data_dir: PathBuf,
+    pub rpc_port: u16,
+    pub p2p_port: u16,
+}
+
+impl DaemonLaunch {
+    pub fn from_settings(settings: &DaemonSettings) -> Result<Self, String> {
+        let data_dir = match settings.data_dir.as_deref().map(str::trim).filter(|d| !d.is_empty()) {
+            Some(dir) => PathBuf::from(dir),
+            None => dirs::data_dir()
+                .map(|dir| dir.join(APP_DIR_NAME).join("daemon"))
+                .ok_or("No data directory to keep the blockchain in")?,
+        };
+        Ok(Self {
+            binary: locate_binary(settings)?,
+            data_dir,
+            rpc_port: settings.rpc_port,
+            p2p_port: settings.p2p_port,
+        })
+    }
+
+    /// Command line of the daemon, its RPC reachable from this machine only
+    pub fn args(&self) -> Vec<String> {
+        vec![
+            "--data-dir".to_string(),
+            self.data_dir.display().to_string(),
+            "--rpc-bind-ip".to_string(),
+            "127.0.0.1".to_string(),
+            "--rpc-bind-port".to_string(),
+            self.rpc_port.to_string(),
+            "--p2p-bind-port".to_string(),
+            self.p2p_port.to_string(),
+        ]
+    }
+
+    pub fn rpc_url(&self) -> String {
+        format!("http://127.0.0.1:{}", self.rpc_port)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DaemonState {
+    Stopped,
+    /// Running, but not answering RPC yet
+    Starting,
+    /// Running and answering RPC
+    Running,
+    /// Exited; waiting to be started again
+    Restarting,
+    /// Exited more often than it may be restarted, or could not be started
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonStatus {
+    pub state: DaemonState,
+    pub pid: Option<u32>,
+    pub uptime_secs: Option<u64>,
+    /// Height the daemon last reported
+    pub height: Option<u64>,
+    pub restart_count: u32,
+    pub rpc_port: Option<u16>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug)]
+struct Supervised {
+    state: DaemonState,
+    launch: Option<DaemonLaunch>,
+    child: Option<Child>,
+    started_at: Option<Instant>,
+    restarts: u32,
+    height: Option<u64>,
+    last_error: Option<String>,
+    /// Bumped on every start and stop, ending the monitor of the previous run
+    generation: u64,
+}
+
+/// Starts, watches and stops the local daemon
+#[derive(Debug)]
+pub struct DaemonSupervisor {
+    inner: Mutex<Supervised>,
+    log_dir: Option<PathBuf>,
+    log: Arc<Mutex<Option<RotatingFile>>>,
+    http: reqwest::Client,
+    restart_backoff: Duration,
+    health_interval: Duration,
+}
+
+impl DaemonSupervisor {
+    /// Supervisor writing the daemon's output to `log_dir`, or nowhere
+    pub fn new(log_dir: Option<PathBuf>) -> Self {
+        Self {
+            inner: Mutex::new(Supervised {
+                state: DaemonState::Stopped,
+                launch: None,
+                child: None,
+                started_at: None,
+                restarts: 0,
+                height: None,
+                last_error: None,
+                generation: 0,
+            }),
+            log_dir,
+            log: Arc::new(Mutex::new(None)),
+            http: reqwest::Client::new(),
+            restart_backoff: RESTART_BACKOFF,
+            health_interval: HEALTH_INTERVAL,
+        }
+    }
+
+    /// Wait `restart_backoff` before the first restart and poll the daemon
+    /// every `health_interval`
+    pub fn with_timing(mut self, restart_backoff: Duration, health_interval: Duration) -> Self {
+        self.restart_backoff = restart_backoff;
+        self.health_interval = health_interval;
+        self
+    }
+
+    /// Start the daemon described by `launch` and watch it until `stop`
+    pub fn start(self: &Arc<Self>, launch: DaemonLaunch) -> Result<DaemonStatus, String> {
+        let generation = {
+            let mut inner = self.inner.lock_recover();
+            if let Some(child) = inner.child.as_ref() {
+                return Err(format!("The daemon is already running (pid {})", child.id().unwrap_or_default()));
+            }
+            if let Some(dir) = self.log_dir.as_ref() {
+                let mut log = self.log.lock_recover();
+                if log.is_none() {
+                    *log = Some(
+                        RotatingFile::open(dir.join(DAEMON_LOG_FILE_NAME), logging::MAX_FILE_BYTES, logging::MAX_FILES)
+                            .map_err(|e| format!("Failed to open the daemon log: {}", e))?,
+                    );
+                }
+            }
+            let child = self.spawn(&launch)?;
+            log::info!("Started {} (pid {})", launch.binary.display(), child.id().unwrap_or_default());
+
+            inner.generation += 1;
+            inner.state = DaemonState::Starting;
+            inner.child = Some(child);
+            inner.started_at = Some(Instant::now());
+            inner.launch = Some(launch);
+            inner.restarts = 0;
+            inner.height = None;
+            inner.last_error = None;
+            inner.generation
+        };
+        tokio::spawn(self.clone().monitor(generation));
+        Ok(self.status())
+    }
+
+    /// Ask the daemon to shut down, killing it if it takes too long
+    pub async fn stop(&self) -> Result<(), String> {
+        let child = {
+            let mut inner = self.inner.lock_recover();
+            inner.generation += 1;
+            inner.state = DaemonState::Stopped;
+            inner.started_at = None;
+            inner.child.take()
+        };
+        set_local_node(None);
+        let Some(mut child) = child else { return Ok(()) };
+
+        #[cfg(unix)]
+        if let Some(pid) = child.id() {
+            // SAFETY: `pid` is our own child, which has not been reaped yet
+            unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) };
+            if tokio::time::timeout(STOP_TIMEOUT, child.wait()).await.is_ok() {
+                log::info!("Daemon stopped");
+                return Ok(());
+            }
+            log::warn!("Daemon did not stop within {:?}, killing it", STOP_TIMEOUT);
+        }
+        child.kill().await.map_err(|e| format!("Failed to stop the daemon: {}", e))?;
+        log::info!("Daemon stopped");
+        Ok(())
+    }
+
+    pub fn status(&self) -> DaemonStatus {
+        let inner = self.inner.lock_recover();
+        DaemonStatus {
+            state: inner.state,
+            pid: inner.child.as_ref().and_then(Child::id),
+            uptime_secs: inner.started_at.map(|start| start.elapsed().as_secs()),
+            height: inner.height,
+            restart_count: inner.restarts,
+            rpc_port: inner.launch.as_ref().map(|launch| launch.rpc_port),
+            last_error: inner.last_error.clone(),
+        }
+    }
+
+    /// The last `lines` lines the daemon printed, oldest first
+    pub fn log_tail(&self, lines: usize) -> Result<Vec<String>, String> {
+        match self.log_dir.as_ref() {
+            Some(dir) => logging::tail_file(dir, DAEMON_LOG_FILE_NAME, lines),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn spawn(&self, launch: &DaemonLaunch) -> Result<Child, String> {
+        std::fs::create_dir_all(&launch.data_dir)
+            .map_err(|e| format!("Failed to create daemon data directory: {}", e))?;
+        let mut child = Command::new(&launch.binary)
+            .args(launch.args())
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| format!("Failed to start {}: {}", launch.binary.display(), e))?;
+        if let Some(stdout) = child.stdout.take() {
+            tokio::spawn(capture(stdout, "stdout", self.log.clone()));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            tokio::spawn(capture(stderr, "stderr", self.log.clone()));
+        }
+        Ok(child)
+    }
+
+    /// Watch the run `generation` until it is stopped or given up on
+    async fn monitor(self: Arc<Self>, generation: u64) {
+        loop {
+            tokio::time::sleep(self.health_interval).await;
+
+            let (exited, url) = {
+                let mut inner = self.inner.lock_recover();
+                if inner.generation != generation {
+                    return;
+                }
+                let exited = match inner.child.as_mut().map(Child::try_wait) {
+                    Some(Ok(Some(status))) => Some(status.to_string()),
+                    Some(Err(e)) => Some(e.to_string()),
+                    _ => None,
+                };
+                (exited, inner.launch.as_ref().map(DaemonLaunch::rpc_url))
+            };
+            if let Some(exit) = exited {
+                if !self.restart(generation, exit).await {
+                    return;
+                }
+                continue;
+            }
+
+            let Some(url) = url else { return };
+            let info = fetch_info(&self.http, &url, HEALTH_TIMEOUT).await;
+            let mut inner = self.inner.lock_recover();
+            if inner.generation != generation {
+                return;
+            }
+            match info {
+                Ok(info) => {
+                    if inner.state != DaemonState::Running {
+                        log::info!("Local daemon is answering at {}", url);
+                    }
+                    inner.state = DaemonState::Running;
+                    inner.height = Some(info.height);
+                    set_local_node(inner.launch.as_ref().map(|launch| launch.rpc_port));
+                }
+                Err(e) => {
+                    if inner.state == DaemonState::Running {
+                        log::warn!("Local daemon stopped answering: {}", e);
+                    }
+                    inner.state = DaemonState::Starting;
+                    set_local_node(None);
+                }
+            }
+        }
+    }
+
+    /// Start the daemon again after it exited with `exit`, unless it was
+    /// restarted too often; returns whether it runs again
+    async fn restart(&self, generation: u64, exit: String) -> bool {
+        set_local_node(None);
+        let delay = {
+            let mut inner = self.inner.lock_recover();
+            if inner.generation != generation {
+                return false;
+            }
+            inner.child = None;
+            inner.started_at = None;
+            log::warn!("Daemon exited ({})", exit);
+            if inner.restarts >= MAX_RESTARTS {
+                log::error!("Daemon exited after {} restarts, giving up", MAX_RESTARTS);
+                inner.state = DaemonState::Failed;
+                inner.last_error = Some(format!("Daemon exited ({}) after {} restarts", exit, MAX_RESTARTS));
+                return false;
+            }
+            inner.state = DaemonState::Restarting;
+            inner.last_error = Some(format!("Daemon exited ({})", exit));
+            self.restart_backoff * 2u32.pow(inner.restarts)
+        };
+        tokio::time::sleep(delay).await;
+
+        let mut inner = self.inner.lock_recover();
+        if inner.generation != generation {
+            return false;
+        }
+        let Some(launch) = inner.launch.clone() else { return false };
+        inner.restarts += 1;
+        match self.spawn(&launch) {
+            Ok(child) => {
+                log::info!("Restarted daemon (pid {}), restart {} of {}", child.id().unwrap_or_default(), inner.restarts, MAX_RESTARTS);
+                inner.child = Some(child);
+                inner.started_at = Some(Instant::now());
+                inner.state = DaemonState::Starting;
+                true
+            }
+            Err(e) => {
+                log::error!("{}", e);
+                inner.state = DaemonState::Failed;
+                inner.last_error = Some(e);
+                false
+            }
+        }
+    }
+}
+
+/// Copy the lines of the daemon's `stream` to its log
+async fn capture<R: AsyncRead + Unpin>(reader: R, stream: &'static str, log: Arc<Mutex<Option<RotatingFile>>>) {
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if let Some(file) = log.lock_recover().as_mut() {
+            let stamped = format!("{} {}: {}", chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f"), stream, line);
+            if let Err(e) = file.write_line(&stamped) {
+                eprintln!("Failed to write daemon log: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use crate::utils::testing::TempDir;
+
+    /// Directory holding a fake daemon running `script`, and its log directory
+    fn fake_daemon(script: &str) -> (TempDir, DaemonLaunch) {
+        let dir = TempDir::new("fuego_daemon");
+        let binary = dir.join("fuegod");
+        fs::write(&binary, format!("#!/bin/sh\n{}\n", script)).unwrap();
+        fs::set_permissions(&binary, fs::Permissions::from_mode(0o755)).unwrap();
+        // Nothing answers on port 1, so health checks fail fast
+        let launch = DaemonLaunch { binary, data_dir: dir.join("data"), rpc_port: 1, p2p_port: 2 };
+        (dir, launch)
+    }
+
+    async fn wait_for(what: &str, mut done: impl FnMut() -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while !done() {
+            assert!(Instant::now() < deadline, "timed out waiting for {}", what);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_daemon_started_logged_and_stopped() {
+        let (dir, launch) = fake_daemon("echo \"fake fuegod $*\"\necho warming up >&2\nexec sleep 30");
+        let supervisor = Arc::new(
+            DaemonSupervisor::new(Some(dir.join("logs")))
+                .with_timing(Duration::from_millis(10), Duration::from_millis(50)),
+        );
+
+        let status = supervisor.start(launch.clone()).unwrap();
+        assert_eq!(status.state, DaemonState::Starting);
+        assert!(status.pid.is_some());
+        assert!(supervisor.start(launch).unwrap_err().contains("already running"));
+
+        wait_for("daemon output", || supervisor.log_tail(10).unwrap().len() == 2).await;
+        let tail = supervisor.log_tail(10).unwrap();
+        assert!(tail.iter().any(|line| line.contains("stdout: fake fuegod --data-dir ")));
+        assert!(tail.iter().any(|line| line.ends_with("stderr: warming up")));
+
+        supervisor.stop().await.unwrap();
+        let status = supervisor.status();
+        assert_eq!(status.state, DaemonState::Stopped);
+        assert_eq!(status.pid, None);
+        assert_eq!(status.restart_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_crashing_daemon_restarted_then_given_up() {
+        let (dir, launch) = fake_daemon("echo crashed\nexit 1");
+        let supervisor = Arc::new(
+            DaemonSupervisor::new(Some(dir.join("logs")))
+                .with_timing(Duration::from_millis(10), Duration::from_millis(20)),
+        );
+
+        supervisor.start(launch).unwrap();
+        wait_for("the supervisor to give up", || supervisor.status().state == DaemonState::Failed).await;
+
+        let status = supervisor.status();
+        assert_eq!(status.restart_count, MAX_RESTARTS);
+        assert_eq!(status.pid, None);
+        assert!(status.last_error.unwrap().contains("after 3 restarts"));
+        // The first run and every restart
+        wait_for("all output", || supervisor.log_tail(10).unwrap().len() == 4).await;
+    }
+}
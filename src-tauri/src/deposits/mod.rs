@@ -0,0 +1,385 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Term deposit helpers
+//!
+//! This module tracks term deposits across refreshes, e.g. to tell the user
+//! when a deposit has matured and can be withdrawn, and checks new deposits
+//! against the limits the network reports.
+
+pub mod renewal;
+
+pub use renewal::{DepositRenewalStore, DepositRenewed, RenewalOption, RenewalReport};
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::{Arc, Mutex};
+use crate::crypto::daemon::TARGET_BLOCK_TIME;
+use crate::crypto::real_cryptonote::{DepositInfo, DepositLimits};
+use crate::utils::locks::MutexExt;
+use crate::wallet::payment_uri::format_xfg;
+
+/// Name of the background task that watches for matured deposits
+pub const DEPOSIT_MATURITY_TASK: &str = "deposit_maturity_watch";
+
+/// Wallet balance plus the value held in term deposits
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HoldingsBreakdown {
+    pub balance: u64,
+    pub unlocked_balance: u64,
+    /// Principal of deposits that are still locked
+    pub locked_deposits: u64,
+    /// Principal of matured deposits that have not been withdrawn yet
+    pub unlocked_deposits: u64,
+    /// Interest the open deposits pay out at maturity
+    pub projected_interest: u64,
+    pub deposit_count: usize,
+    pub total: u64,
+    /// False when the deposits could not be read; only the balance is counted then
+    pub deposits_available: bool,
+}
+
+impl HoldingsBreakdown {
+    /// Sum the wallet balance and all deposits that were not withdrawn yet.
+    /// Pass `None` when the deposits could not be read.
+    pub fn compute(balance: u64, unlocked_balance: u64, deposits: Option<&[DepositInfo]>) -> Self {
+        let mut breakdown = Self {
+            balance,
+            unlocked_balance,
+            locked_deposits: 0,
+            unlocked_deposits: 0,
+            projected_interest: 0,
+            deposit_count: 0,
+            total: balance,
+            deposits_available: deposits.is_some(),
+        };
+
+        for deposit in deposits.unwrap_or_default() {
+            if deposit.status == "spent" {
+                continue;
+            }
+            if deposit.status == "locked" {
+                breakdown.locked_deposits = breakdown.locked_deposits.saturating_add(deposit.amount);
+            } else {
+                breakdown.unlocked_deposits = breakdown.unlocked_deposits.saturating_add(deposit.amount);
+            }
+            breakdown.projected_interest = breakdown.projected_interest.saturating_add(deposit.interest);
+            breakdown.deposit_count += 1;
+        }
+
+        breakdown.total = balance
+            .saturating_add(breakdown.locked_deposits)
+            .saturating_add(breakdown.unlocked_deposits)
+            .saturating_add(breakdown.projected_interest);
+        breakdown
+    }
+}
+
+/// The open deposit that matures first
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NextMaturity {
+    pub deposit_id: String,
+    pub unlock_height: u64,
+    /// Unix time the unlock height is expected at, one target block time per block
+    pub estimated_at: u64,
+}
+
+/// Totals over all term deposits, for the deposits overview
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DepositSummary {
+    pub network_height: u64,
+    /// Principal of deposits that are still locked
+    pub total_locked: u64,
+    /// Interest the deposits not withdrawn yet have earned up to `network_height`
+    pub accrued_interest: u64,
+    /// Yearly rate of the deposits not withdrawn yet, weighted by principal
+    pub weighted_average_rate: Option<f64>,
+    /// Deposits by status; those in `errors` are not counted
+    pub by_status: BTreeMap<String, usize>,
+    pub next_maturity: Option<NextMaturity>,
+    /// Interest paid out by withdrawn deposits
+    pub lifetime_interest: u64,
+    /// Deposits whose fields could not be made sense of, left out of every total
+    pub errors: usize,
+}
+
+impl DepositSummary {
+    /// Sum `deposits` at `network_height`, with `now` the current Unix time
+    pub fn compute(deposits: &[DepositInfo], network_height: u64, now: u64) -> Self {
+        let mut summary = Self {
+            network_height,
+            total_locked: 0,
+            accrued_interest: 0,
+            weighted_average_rate: None,
+            by_status: BTreeMap::new(),
+            next_maturity: None,
+            lifetime_interest: 0,
+            errors: 0,
+        };
+        let mut open_principal = 0u128;
+        let mut weighted_rates = 0.0;
+
+        for deposit in deposits {
+            if let Some(problem) = deposit_problem(deposit) {
+                log::warn!("Deposit {} left out of the summary: {}", deposit.id, problem);
+                summary.errors += 1;
+                continue;
+            }
+            *summary.by_status.entry(deposit.status.clone()).or_default() += 1;
+
+            if deposit.status == "spent" {
+                summary.lifetime_interest = summary.lifetime_interest.saturating_add(deposit.interest);
+                continue;
+            }
+            summary.accrued_interest = summary.accrued_interest.saturating_add(accrued_interest(deposit, network_height));
+            open_principal += u128::from(deposit.amount);
+            weighted_rates += deposit.rate * deposit.amount as f64;
+
+            if deposit.status == "locked" {
+                summary.total_locked = summary.total_locked.saturating_add(deposit.amount);
+                let sooner = summary.next_maturity.as_ref().is_none_or(|next| deposit.unlock_height < next.unlock_height);
+                if deposit.unlock_height > network_height && sooner {
+                    let blocks_left = deposit.unlock_height - network_height;
+                    summary.next_maturity = Some(NextMaturity {
+                        deposit_id: deposit.id.clone(),
+                        unlock_height: deposit.unlock_height,
+                        estimated_at: now.saturating_add(blocks_left.saturating_mul(TARGET_BLOCK_TIME)),
+                    });
+                }
+            }
+        }
+
+        if open_principal > 0 {
+            summary.weighted_average_rate = Some(weighted_rates / open_principal as f64);
+        }
+        summary
+    }
+}
+
+/// Why `deposit` cannot be summed, if it cannot
+fn deposit_problem(deposit: &DepositInfo) -> Option<&'static str> {
+    if !matches!(deposit.status.as_str(), "locked" | "unlocked" | "spent") {
+        return Some("unknown status");
+    }
+    if deposit.amount == 0 {
+        return Some("no principal");
+    }
+    if !deposit.rate.is_finite() || deposit.rate < 0.0 {
+        return Some("invalid rate");
+    }
+    if deposit.unlock_height < deposit.creating_height {
+        return Some("unlocks before it was created");
+    }
+    None
+}
+
+/// Interest `deposit` has earned at `height`: its full interest once it
+/// matured, and a share growing linearly with the blocks since it was
+/// created before that
+pub fn accrued_interest(deposit: &DepositInfo, height: u64) -> u64 {
+    if deposit.status != "locked" || height >= deposit.unlock_height {
+        return deposit.interest;
+    }
+    let span = deposit.unlock_height - deposit.creating_height;
+    let elapsed = height.saturating_sub(deposit.creating_height);
+    (u128::from(deposit.interest) * u128::from(elapsed) / u128::from(span)) as u64
+}
+
+/// Deposits passed to a maturity check, with the network height at the time
+type DepositCheck = (Vec<DepositInfo>, u64);
+
+/// Watches term deposits and reports the ones that became withdrawable
+#[derive(Debug, Default)]
+pub struct DepositMaturityWatcher {
+    /// Deposits that have been seen locked at least once
+    locked: Arc<Mutex<HashSet<String>>>,
+    /// Deposits that were already reported as matured
+    notified: Arc<Mutex<HashSet<String>>>,
+    /// Deposits and network height of the latest check
+    last_seen: Arc<Mutex<Option<DepositCheck>>>,
+}
+
+impl DepositMaturityWatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compare deposits against the current network height and return the
+    /// ones that moved from locked to withdrawable since the previous check.
+    ///
+    /// Deposits that are already mature the first time they are seen are
+    /// recorded without being reported, so a restart does not replay
+    /// notifications for old deposits.
+    pub fn check(&self, deposits: &[DepositInfo], network_height: u64) -> Vec<DepositInfo> {
+        *self.last_seen.lock_recover() = Some((deposits.to_vec(), network_height));
+        let mut locked = self.locked.lock_recover();
+        let mut notified = self.notified.lock_recover();
+        let mut matured = Vec::new();
+
+        for deposit in deposits {
+            if deposit.status == "spent" {
+                continue;
+            }
+
+            if deposit.unlock_height > network_height {
+                locked.insert(deposit.id.clone());
+            } else if notified.insert(deposit.id.clone()) && locked.remove(&deposit.id) {
+                matured.push(deposit.clone());
+            }
+        }
+
+        matured
+    }
+
+    /// Deposits and network height passed to the latest `check`
+    pub fn last_seen(&self) -> Option<DepositCheck> {
+        self.last_seen.lock_recover().clone()
+    }
+
+    /// Whether the deposit was already reported as matured
+    pub fn is_notified(&self, deposit_id: &str) -> bool {
+        self.notified.lock_recover().contains(deposit_id)
+    }
+}
+
+/// Check a new deposit of `amount` atomic units for `term` days against
+/// `limits`, quoting the limits it falls outside of
+pub fn check_deposit(limits: &DepositLimits, amount: u64, term: u32) -> Result<(), String> {
+    if amount < limits.min_amount {
+        return Err(format!("Minimum deposit amount is {} XFG", format_xfg(limits.min_amount)));
+    }
+    if term < limits.min_term || term > limits.max_term {
+        return Err(format!("Term must be between {} and {} days", limits.min_term, limits.max_term));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deposit(id: &str, unlock_height: u64, status: &str) -> DepositInfo {
+        DepositInfo {
+            id: id.to_string(),
+            amount: 100_000_000,
+            interest: 1_000_000,
+            term: 30,
+            rate: 0.08,
+            status: status.to_string(),
+            unlock_height,
+            unlock_time: None,
+            creating_transaction_hash: "hash".to_string(),
+            creating_height: 1,
+            creating_time: "0".to_string(),
+            spending_transaction_hash: None,
+            spending_height: None,
+            spending_time: None,
+            deposit_type: "term".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_matured_deposit_reported_once() {
+        let watcher = DepositMaturityWatcher::new();
+        let deposits = vec![deposit("d1", 100, "locked")];
+
+        assert!(watcher.check(&deposits, 90).is_empty());
+
+        let matured = watcher.check(&deposits, 100);
+        assert_eq!(matured.len(), 1);
+        assert_eq!(matured[0].id, "d1");
+        assert!(watcher.is_notified("d1"));
+
+        assert!(watcher.check(&deposits, 110).is_empty());
+    }
+
+    #[test]
+    fn test_already_mature_deposit_not_reported() {
+        let watcher = DepositMaturityWatcher::new();
+        let deposits = vec![deposit("old", 50, "unlocked")];
+
+        assert!(watcher.check(&deposits, 100).is_empty());
+        assert!(watcher.check(&deposits, 101).is_empty());
+    }
+
+    #[test]
+    fn test_holdings_exclude_spent_deposits() {
+        let deposits = vec![
+            deposit("locked", 100, "locked"),
+            deposit("matured", 50, "unlocked"),
+            deposit("withdrawn", 10, "spent"),
+        ];
+        let holdings = HoldingsBreakdown::compute(500_000_000, 400_000_000, Some(&deposits));
+
+        assert!(holdings.deposits_available);
+        assert_eq!(holdings.deposit_count, 2);
+        assert_eq!(holdings.locked_deposits, 100_000_000);
+        assert_eq!(holdings.unlocked_deposits, 100_000_000);
+        assert_eq!(holdings.projected_interest, 2_000_000);
+        assert_eq!(holdings.total, 702_000_000);
+    }
+
+    #[test]
+    fn test_holdings_without_deposits_is_balance() {
+        let holdings = HoldingsBreakdown::compute(500_000_000, 400_000_000, None);
+        assert!(!holdings.deposits_available);
+        assert_eq!(holdings.total, 500_000_000);
+        assert_eq!(holdings.deposit_count, 0);
+    }
+
+    #[test]
+    fn test_deposit_checked_against_reported_limits() {
+        let defaults = DepositLimits::defaults(100);
+        assert!(check_deposit(&defaults, 10_000_000, 365).is_ok());
+        assert_eq!(check_deposit(&defaults, 9_999_999, 30), Err("Minimum deposit amount is 1 XFG".to_string()));
+        assert_eq!(check_deposit(&defaults, 10_000_000, 0), Err("Term must be between 1 and 365 days".to_string()));
+
+        let reported: DepositLimits = serde_json::from_str(
+            r#"{"supported":true,"height":100,"min_amount":8000000000,"min_term":30,"max_term":360,
+                "rates":[{"min_term":30,"rate":0.03},{"min_term":180,"rate":0.08}]}"#,
+        ).unwrap();
+        assert_eq!(check_deposit(&reported, 10_000_000, 90), Err("Minimum deposit amount is 800 XFG".to_string()));
+        assert_eq!(check_deposit(&reported, 8_000_000_000, 365), Err("Term must be between 30 and 360 days".to_string()));
+        assert!(check_deposit(&reported, 8_000_000_000, 360).is_ok());
+        assert_eq!((reported.rate_for(90), reported.rate_for(200), reported.rate_for(10)), (Some(0.03), Some(0.08), None));
+    }
+
+    #[test]
+    fn test_deposit_summary_aggregates_mixed_deposits() {
+        let locked_half = DepositInfo { creating_height: 100, unlock_height: 300, amount: 300_000_000, interest: 9_000_000, rate: 0.03, ..deposit("half", 300, "locked") };
+        let locked_late = DepositInfo { creating_height: 200, unlock_height: 600, amount: 100_000_000, interest: 4_000_000, rate: 0.07, ..deposit("late", 600, "locked") };
+        let matured = DepositInfo { rate: 0.05, ..deposit("matured", 150, "unlocked") };
+        let spent = DepositInfo { interest: 2_500_000, ..deposit("spent", 120, "spent") };
+        let unknown = deposit("odd", 100, "pending");
+        let bad_rate = DepositInfo { rate: f64::NAN, ..deposit("nan", 250, "locked") };
+        let deposits = vec![locked_half, locked_late, matured, spent, unknown, bad_rate];
+
+        let summary = DepositSummary::compute(&deposits, 200, 1_700_000_000);
+        assert_eq!(summary.errors, 2);
+        assert_eq!(summary.total_locked, 400_000_000);
+        // Half of the first deposit's term has passed, none of the second's
+        assert_eq!(summary.accrued_interest, 4_500_000 + 1_000_000);
+        assert_eq!(summary.lifetime_interest, 2_500_000);
+        let rate = summary.weighted_average_rate.unwrap();
+        assert!((rate - (0.03 * 3.0 + 0.07 + 0.05) / 5.0).abs() < 1e-12);
+        let counts: Vec<(&str, usize)> = summary.by_status.iter().map(|(status, count)| (status.as_str(), *count)).collect();
+        assert_eq!(counts, vec![("locked", 2), ("spent", 1), ("unlocked", 1)]);
+        assert_eq!(summary.next_maturity, Some(NextMaturity {
+            deposit_id: "half".to_string(),
+            unlock_height: 300,
+            estimated_at: 1_700_000_000 + 100 * TARGET_BLOCK_TIME,
+        }));
+
+        let empty = DepositSummary::compute(&[], 200, 0);
+        assert_eq!((empty.weighted_average_rate, empty.next_maturity, empty.errors), (None, None, 0));
+    }
+
+    #[test]
+    fn test_spent_deposit_ignored() {
+        let watcher = DepositMaturityWatcher::new();
+
+        assert!(watcher.check(&[deposit("d1", 100, "locked")], 90).is_empty());
+        assert!(watcher.check(&[deposit("d1", 100, "spent")], 120).is_empty());
+        assert!(!watcher.is_notified("d1"));
+    }
+}
@@ -0,0 +1,278 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Deposit auto-renewal
+//!
+//! Deposits the user opted in are withdrawn once they mature and their
+//! principal is locked again in a new deposit, which inherits the option. The
+//! options are kept next to the wallet as `<wallet>.renewals.json`. Renewing
+//! takes two transactions; when the second one fails, e.g. because the
+//! withdrawn funds are not unlocked yet or the node is down, the withdrawal is
+//! remembered and the deposit is recreated on a later check.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use crate::crypto::real_cryptonote::DepositInfo;
+use crate::utils::locks::MutexExt;
+use crate::utils::storage::{load_json_or_default, save_json, sidecar_path};
+
+/// Principal withdrawn from a matured deposit that still has to be deposited again
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingRenewal {
+    pub amount: u64,
+    pub term: u32,
+    pub withdraw_tx: String,
+}
+
+/// Auto-renewal option of one deposit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RenewalOption {
+    pub renew: bool,
+    /// Term in days of the new deposit; the matured deposit's term when unset
+    #[serde(default)]
+    pub term: Option<u32>,
+    #[serde(default)]
+    pub pending: Option<PendingRenewal>,
+    /// Failed renewal attempts in a row
+    #[serde(default)]
+    pub failures: u32,
+}
+
+/// A matured deposit that was locked again
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DepositRenewed {
+    pub previous_id: String,
+    pub deposit_id: String,
+    pub amount: u64,
+    pub term: u32,
+    pub withdraw_tx: String,
+}
+
+/// A renewal attempt that failed and will be retried on the next check
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RenewalFailure {
+    pub deposit_id: String,
+    pub error: String,
+    /// Failed attempts in a row, including this one
+    pub failures: u32,
+}
+
+/// Outcome of one renewal pass
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RenewalReport {
+    pub renewed: Vec<DepositRenewed>,
+    pub failed: Vec<RenewalFailure>,
+}
+
+/// Persisted auto-renewal options, by deposit id
+#[derive(Debug)]
+pub struct DepositRenewalStore {
+    path: PathBuf,
+    entries: Mutex<BTreeMap<String, RenewalOption>>,
+}
+
+impl DepositRenewalStore {
+    /// Path of the renewal options for `wallet_path`
+    pub fn sidecar_path(wallet_path: &Path) -> PathBuf {
+        sidecar_path(wallet_path, ".renewals.json")
+    }
+
+    pub fn for_wallet(wallet_path: &Path) -> Result<Self, String> {
+        Self::open(Self::sidecar_path(wallet_path))
+    }
+
+    /// Load the renewal options at `path`; a missing file means none
+    pub fn open(path: PathBuf) -> Result<Self, String> {
+        let entries = load_json_or_default(&path, "deposit renewals")?;
+        Ok(Self {
+            path,
+            entries: Mutex::new(entries),
+        })
+    }
+
+    pub fn get(&self, deposit_id: &str) -> Option<RenewalOption> {
+        self.entries.lock_recover().get(deposit_id).cloned()
+    }
+
+    pub fn list(&self) -> BTreeMap<String, RenewalOption> {
+        self.entries.lock_recover().clone()
+    }
+
+    /// Turn auto-renewal of `deposit_id` on, renewing for `term` days or the
+    /// deposit's own term, or off. Turning it off also drops a withdrawal
+    /// waiting to be deposited again; its funds simply stay in the wallet.
+    pub fn set(&self, deposit_id: &str, enabled: bool, term: Option<u32>) -> Result<Option<RenewalOption>, String> {
+        let mut entries = self.entries.lock_recover();
+        let option = if enabled {
+            let option = RenewalOption { renew: true, term, pending: None, failures: 0 };
+            entries.insert(deposit_id.to_string(), option.clone());
+            Some(option)
+        } else {
+            entries.remove(deposit_id);
+            None
+        };
+        self.save(&entries)?;
+        Ok(option)
+    }
+
+    /// Renew the opted-in deposits that have matured at `network_height`:
+    /// `withdraw` the deposit, then `create` a new one of its principal. The
+    /// new deposit takes over the option. A failed step is counted and tried
+    /// again on the next call; a deposit spent some other way drops its option.
+    pub fn renew_matured<W, C>(&self, deposits: &[DepositInfo], network_height: u64, mut withdraw: W, mut create: C) -> Result<RenewalReport, String>
+    where
+        W: FnMut(&str) -> Result<String, String>,
+        C: FnMut(u64, u32) -> Result<String, String>,
+    {
+        let mut entries = self.entries.lock_recover();
+        let mut report = RenewalReport::default();
+        let ids: Vec<String> = entries.iter().filter(|(_, option)| option.renew).map(|(id, _)| id.clone()).collect();
+
+        for id in ids {
+            let Some(mut option) = entries.get(&id).cloned() else { continue };
+
+            if option.pending.is_none() {
+                let Some(deposit) = deposits.iter().find(|d| d.id == id) else { continue };
+                if deposit.status == "spent" {
+                    entries.remove(&id);
+                    continue;
+                }
+                if deposit.unlock_height > network_height {
+                    continue;
+                }
+                match withdraw(&id) {
+                    Ok(withdraw_tx) => {
+                        option.pending = Some(PendingRenewal {
+                            amount: deposit.amount,
+                            term: option.term.unwrap_or(deposit.term),
+                            withdraw_tx,
+                        });
+                    }
+                    Err(e) => {
+                        option.failures += 1;
+                        report.failed.push(RenewalFailure { deposit_id: id.clone(), error: e, failures: option.failures });
+                        entries.insert(id, option);
+                        continue;
+                    }
+                }
+            }
+
+            let Some(pending) = option.pending.clone() else { continue };
+            match create(pending.amount, pending.term) {
+                Ok(deposit_id) => {
+                    entries.remove(&id);
+                    entries.insert(deposit_id.clone(), RenewalOption { renew: true, term: option.term, pending: None, failures: 0 });
+                    report.renewed.push(DepositRenewed {
+                        previous_id: id,
+                        deposit_id,
+                        amount: pending.amount,
+                        term: pending.term,
+                        withdraw_tx: pending.withdraw_tx,
+                    });
+                }
+                Err(e) => {
+                    option.failures += 1;
+                    report.failed.push(RenewalFailure { deposit_id: id.clone(), error: e, failures: option.failures });
+                    entries.insert(id, option);
+                }
+            }
+        }
+
+        self.save(&entries)?;
+        Ok(report)
+    }
+
+    fn save(&self, entries: &BTreeMap<String, RenewalOption>) -> Result<(), String> {
+        save_json(&self.path, entries, "deposit renewals")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use crate::utils::testing::TempDir;
+
+    fn temp_store() -> (TempDir, DepositRenewalStore, PathBuf) {
+        let dir = TempDir::new("fuego_renewal");
+        let path = dir.join("test.wallet.renewals.json");
+        (dir, DepositRenewalStore::open(path.clone()).unwrap(), path)
+    }
+
+    fn deposit(id: &str, unlock_height: u64, status: &str) -> DepositInfo {
+        DepositInfo {
+            id: id.to_string(),
+            amount: 100_000_000,
+            interest: 1_000_000,
+            term: 30,
+            rate: 0.08,
+            status: status.to_string(),
+            unlock_height,
+            unlock_time: None,
+            creating_transaction_hash: "hash".to_string(),
+            creating_height: 1,
+            creating_time: "0".to_string(),
+            spending_transaction_hash: None,
+            spending_height: None,
+            spending_time: None,
+            deposit_type: "term".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_matured_deposit_renewed_with_configured_term() {
+        let (_dir, store, path) = temp_store();
+        store.set("d1", true, Some(90)).unwrap();
+        store.set("d2", true, None).unwrap();
+        store.set("d2", false, None).unwrap();
+        let deposits = vec![deposit("d1", 100, "locked"), deposit("d2", 100, "locked")];
+
+        // Not mature yet
+        let report = store.renew_matured(&deposits, 99, |_| panic!("withdrew early"), |_, _| panic!("created early")).unwrap();
+        assert_eq!(report, RenewalReport::default());
+
+        let report = store.renew_matured(
+            &deposits,
+            100,
+            |id| Ok(format!("withdraw-{}", id)),
+            |amount, term| Ok(format!("new-{}-{}", amount, term)),
+        ).unwrap();
+        assert_eq!(report.renewed, vec![DepositRenewed {
+            previous_id: "d1".to_string(),
+            deposit_id: "new-100000000-90".to_string(),
+            amount: 100_000_000,
+            term: 90,
+            withdraw_tx: "withdraw-d1".to_string(),
+        }]);
+
+        // The new deposit carries the option over, on disk too
+        let reopened = DepositRenewalStore::open(path.clone()).unwrap();
+        assert_eq!(reopened.list().keys().collect::<Vec<_>>(), vec!["new-100000000-90"]);
+        assert_eq!(reopened.get("new-100000000-90").unwrap().term, Some(90));
+    }
+
+    #[test]
+    fn test_failed_redeposit_retried_without_withdrawing_again() {
+        let (_dir, store, _) = temp_store();
+        store.set("d1", true, None).unwrap();
+        let deposits = vec![deposit("d1", 100, "unlocked")];
+        let withdrawals = Cell::new(0);
+        let withdraw = |_: &str| {
+            withdrawals.set(withdrawals.get() + 1);
+            Ok("withdraw-tx".to_string())
+        };
+
+        let report = store.renew_matured(&deposits, 100, withdraw, |_, _| Err("Network down".to_string())).unwrap();
+        assert_eq!(report.failed, vec![RenewalFailure { deposit_id: "d1".to_string(), error: "Network down".to_string(), failures: 1 }]);
+        assert_eq!(store.get("d1").unwrap().pending.unwrap().withdraw_tx, "withdraw-tx");
+
+        // The wallet now lists the deposit as spent by the withdrawal
+        let spent = vec![deposit("d1", 100, "spent")];
+        let report = store.renew_matured(&spent, 110, withdraw, |_, term| Ok(format!("new-{}", term))).unwrap();
+        assert_eq!(withdrawals.get(), 1);
+        assert_eq!(report.renewed[0].deposit_id, "new-30");
+        assert!(report.failed.is_empty());
+    }
+}
@@ -0,0 +1,482 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Headless mode
+//!
+//! `--headless` runs the wallet backend without a window, for servers and
+//! scripts. The Tauri commands are served as JSON-RPC 2.0 methods of the same
+//! name over HTTP: `POST /` with a `{"jsonrpc":"2.0","id":1,"method":...,"params":{...}}`
+//! body and an `Authorization: Bearer <token>` header. Parameters are passed
+//! by name, in snake_case or in the camelCase the frontend uses.
+//!
+//! The server listens on 127.0.0.1 on a random port unless `--rpc-bind` says
+//! otherwise, and generates its token unless `--rpc-auth-token` gives one;
+//! both are printed to stdout on startup. It handles one request per
+//! connection and stops accepting on SIGINT, letting requests in flight finish.
+
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinSet;
+use crate::utils::error::ApiError;
+
+/// Address the server binds to when `--rpc-bind` is not given
+pub const DEFAULT_RPC_BIND: &str = "127.0.0.1:0";
+
+/// Largest request head accepted
+const MAX_HEAD_BYTES: usize = 16 * 1024;
+
+/// Largest request body accepted
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// How long a client may take to send its request
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long shutdown waits for requests in flight
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(30);
+
+/// Server options from the command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeadlessOptions {
+    pub bind: SocketAddr,
+    pub auth_token: String,
+}
+
+impl HeadlessOptions {
+    /// Options for a headless run, or `None` when `args` (without the program
+    /// name) do not contain `--headless`. Arguments this mode does not know
+    /// are left to the GUI.
+    pub fn from_args<I>(args: I) -> Result<Option<Self>, String>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut headless = false;
+        let mut bind = None;
+        let mut auth_token = None;
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+            match flag.as_str() {
+                "--headless" => headless = true,
+                "--rpc-bind" | "--rpc-auth-token" => {
+                    let value = inline.or_else(|| args.next())
+                        .ok_or_else(|| format!("{} needs a value", flag))?;
+                    if flag == "--rpc-bind" {
+                        bind = Some(value.parse::<SocketAddr>()
+                            .map_err(|e| format!("Invalid --rpc-bind address {}: {}", value, e))?);
+                    } else if value.is_empty() {
+                        return Err("--rpc-auth-token must not be empty".to_string());
+                    } else {
+                        auth_token = Some(value);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if !headless {
+            if bind.is_some() || auth_token.is_some() {
+                return Err("--rpc-bind and --rpc-auth-token only apply with --headless".to_string());
+            }
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            bind: bind.unwrap_or_else(|| DEFAULT_RPC_BIND.parse().expect("valid default address")),
+            auth_token: auth_token.unwrap_or_else(generate_token),
+        }))
+    }
+}
+
+/// Random token for clients to authenticate with
+pub fn generate_token() -> String {
+    hex::encode(rand::random::<[u8; 32]>())
+}
+
+/// Why a call did not produce a result
+#[derive(Debug, Clone, PartialEq)]
+pub enum RpcError {
+    MethodNotFound(String),
+    InvalidParams(String),
+    /// The command ran and failed
+    Command(ApiError),
+}
+
+impl From<ApiError> for RpcError {
+    fn from(error: ApiError) -> Self {
+        RpcError::Command(error)
+    }
+}
+
+impl RpcError {
+    fn code(&self) -> i64 {
+        match self {
+            RpcError::MethodNotFound(_) => -32601,
+            RpcError::InvalidParams(_) => -32602,
+            RpcError::Command(_) => -32000,
+        }
+    }
+
+    fn into_body(self) -> RpcErrorBody {
+        let code = self.code();
+        match self {
+            RpcError::MethodNotFound(method) => RpcErrorBody { code, message: format!("Method not found: {}", method), data: None },
+            RpcError::InvalidParams(message) => RpcErrorBody { code, message, data: None },
+            RpcError::Command(error) => RpcErrorBody {
+                code,
+                message: error.message.clone(),
+                data: serde_json::to_value(&error).ok(),
+            },
+        }
+    }
+}
+
+/// Runs the method named in a call with its parameters
+pub type RpcHandler = Arc<dyn Fn(String, Value) -> Pin<Box<dyn Future<Output = Result<Value, RpcError>> + Send>> + Send + Sync>;
+
+/// Parameter `name` of a call, looked up in snake_case and then camelCase. A
+/// missing parameter reads as null, so optional parameters may be left out.
+pub fn param<T: DeserializeOwned>(params: &Value, name: &str) -> Result<T, RpcError> {
+    let value = match params {
+        Value::Object(map) => map.get(name).or_else(|| map.get(&camel_case(name))).cloned().unwrap_or(Value::Null),
+        Value::Null => Value::Null,
+        _ => return Err(RpcError::InvalidParams("params must be an object".to_string())),
+    };
+    let missing = value.is_null();
+    serde_json::from_value(value).map_err(|e| {
+        if missing {
+            RpcError::InvalidParams(format!("Missing parameter: {}", name))
+        } else {
+            RpcError::InvalidParams(format!("Invalid parameter {}: {}", name, e))
+        }
+    })
+}
+
+fn camel_case(name: &str) -> String {
+    let mut parts = name.split('_');
+    let mut camel = parts.next().unwrap_or_default().to_string();
+    for part in parts {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            camel.extend(first.to_uppercase());
+            camel.push_str(chars.as_str());
+        }
+    }
+    camel
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcRequest {
+    #[serde(default)]
+    jsonrpc: Option<String>,
+    #[serde(default)]
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Debug, Serialize)]
+struct RpcErrorBody {
+    code: i64,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<Value>,
+}
+
+#[derive(Debug, Serialize)]
+struct RpcResponse {
+    jsonrpc: &'static str,
+    id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<RpcErrorBody>,
+}
+
+impl RpcResponse {
+    fn error(id: Value, code: i64, message: impl Into<String>) -> Self {
+        Self {
+            jsonrpc: "2.0",
+            id,
+            result: None,
+            error: Some(RpcErrorBody { code, message: message.into(), data: None }),
+        }
+    }
+}
+
+/// Serve calls on `listener` until `shutdown` completes, then wait a while
+/// for the requests in flight
+pub async fn serve<S>(listener: TcpListener, auth_token: String, handler: RpcHandler, shutdown: S)
+where
+    S: Future<Output = ()>,
+{
+    let auth_token = Arc::new(auth_token);
+    let mut connections = JoinSet::new();
+    tokio::pin!(shutdown);
+
+    loop {
+        tokio::select! {
+            _ = &mut shutdown => break,
+            accepted = listener.accept() => match accepted {
+                Ok((stream, peer)) => {
+                    let auth_token = auth_token.clone();
+                    let handler = handler.clone();
+                    connections.spawn(async move {
+                        if let Err(e) = handle_connection(stream, &auth_token, handler).await {
+                            log::debug!("RPC connection from {} failed: {}", peer, e);
+                        }
+                    });
+                }
+                Err(e) => log::warn!("Failed to accept RPC connection: {}", e),
+            },
+            // Reap finished connections so the set does not grow
+            Some(_) = connections.join_next(), if !connections.is_empty() => {}
+        }
+    }
+
+    log::info!("RPC server stopping, waiting for {} request(s)", connections.len());
+    if tokio::time::timeout(SHUTDOWN_GRACE, async { while connections.join_next().await.is_some() {} }).await.is_err() {
+        log::warn!("Abandoning {} RPC request(s) still running", connections.len());
+    }
+}
+
+/// Read one request, run it and write the response
+async fn handle_connection(mut stream: TcpStream, auth_token: &str, handler: RpcHandler) -> Result<(), String> {
+    let request = match tokio::time::timeout(READ_TIMEOUT, read_request(&mut stream)).await {
+        Ok(Ok(request)) => request,
+        Ok(Err((status, message))) => return write_response(&mut stream, status, message.as_bytes(), "text/plain").await,
+        Err(_) => return write_response(&mut stream, 408, b"Request timeout", "text/plain").await,
+    };
+
+    if request.method != "POST" {
+        return write_response(&mut stream, 405, b"Use POST", "text/plain").await;
+    }
+    if request.path != "/" && request.path != "/json_rpc" {
+        return write_response(&mut stream, 404, b"Not found", "text/plain").await;
+    }
+    let authorized = request.authorization.as_deref()
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|token| constant_time_eq(token.trim().as_bytes(), auth_token.as_bytes()));
+    if !authorized {
+        return write_response(&mut stream, 401, b"Missing or wrong bearer token", "text/plain").await;
+    }
+
+    let response = dispatch(&request.body, handler).await;
+    let body = serde_json::to_vec(&response).map_err(|e| format!("Failed to serialize response: {}", e))?;
+    write_response(&mut stream, 200, &body, "application/json").await
+}
+
+/// Run the call in `body`
+async fn dispatch(body: &[u8], handler: RpcHandler) -> RpcResponse {
+    let value: Value = match serde_json::from_slice(body) {
+        Ok(value) => value,
+        Err(e) => return RpcResponse::error(Value::Null, -32700, format!("Parse error: {}", e)),
+    };
+    let request: RpcRequest = match serde_json::from_value(value) {
+        Ok(request) => request,
+        Err(e) => return RpcResponse::error(Value::Null, -32600, format!("Invalid request: {}", e)),
+    };
+    if request.jsonrpc.as_deref().is_some_and(|version| version != "2.0") {
+        return RpcResponse::error(request.id, -32600, "Invalid request: jsonrpc must be \"2.0\"");
+    }
+
+    log::debug!("RPC call {}", request.method);
+    match handler(request.method, request.params).await {
+        Ok(result) => RpcResponse { jsonrpc: "2.0", id: request.id, result: Some(result), error: None },
+        Err(e) => RpcResponse { jsonrpc: "2.0", id: request.id, result: None, error: Some(e.into_body()) },
+    }
+}
+
+struct HttpRequest {
+    method: String,
+    path: String,
+    authorization: Option<String>,
+    body: Vec<u8>,
+}
+
+/// Read the request head and a body of `Content-Length` bytes; fails with the
+/// status to answer
+async fn read_request(stream: &mut TcpStream) -> Result<HttpRequest, (u16, String)> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let head_end = loop {
+        if let Some(pos) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+            break pos;
+        }
+        if buffer.len() > MAX_HEAD_BYTES {
+            return Err((431, "Request head too large".to_string()));
+        }
+        let read = stream.read(&mut chunk).await.map_err(|e| (400, e.to_string()))?;
+        if read == 0 {
+            return Err((400, "Connection closed before the request ended".to_string()));
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..head_end]).into_owned();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default().to_string();
+    let path = request_line.next().unwrap_or_default().to_string();
+
+    let mut content_length = 0;
+    let mut authorization = None;
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else { continue };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("content-length") {
+            content_length = value.parse::<usize>().map_err(|_| (400, "Invalid Content-Length".to_string()))?;
+        } else if name.eq_ignore_ascii_case("authorization") {
+            authorization = Some(value.to_string());
+        }
+    }
+    if content_length > MAX_BODY_BYTES {
+        return Err((413, "Request body too large".to_string()));
+    }
+
+    let mut body = buffer.split_off(head_end + 4);
+    while body.len() < content_length {
+        let read = stream.read(&mut chunk).await.map_err(|e| (400, e.to_string()))?;
+        if read == 0 {
+            return Err((400, "Connection closed before the body ended".to_string()));
+        }
+        body.extend_from_slice(&chunk[..read]);
+    }
+    body.truncate(content_length);
+
+    Ok(HttpRequest { method, path, authorization, body })
+}
+
+async fn write_response(stream: &mut TcpStream, status: u16, body: &[u8], content_type: &str) -> Result<(), String> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        413 => "Payload Too Large",
+        431 => "Request Header Fields Too Large",
+        _ => "Error",
+    };
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        reason,
+        content_type,
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).await.map_err(|e| format!("Failed to write response: {}", e))?;
+    stream.write_all(body).await.map_err(|e| format!("Failed to write response: {}", e))?;
+    stream.shutdown().await.map_err(|e| format!("Failed to close connection: {}", e))
+}
+
+/// Compare tokens without leaking through timing where they differ
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Start a server with `handler` on a random port; dropping the sender stops it
+    async fn start(handler: RpcHandler) -> (SocketAddr, tokio::sync::oneshot::Sender<()>, tokio::task::JoinHandle<()>) {
+        let listener = TcpListener::bind(DEFAULT_RPC_BIND).await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(serve(listener, "secret".to_string(), handler, async {
+            let _ = stopped.await;
+        }));
+        (addr, stop, server)
+    }
+
+    /// POST `body` and return the status and response body
+    async fn post(addr: SocketAddr, token: &str, body: &str) -> (u16, String) {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let request = format!(
+            "POST / HTTP/1.1\r\nHost: {}\r\nAuthorization: Bearer {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            addr,
+            token,
+            body.len(),
+            body
+        );
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        let status = response.split_whitespace().nth(1).unwrap().parse().unwrap();
+        let body = response.split_once("\r\n\r\n").unwrap().1.to_string();
+        (status, body)
+    }
+
+    #[test]
+    fn test_options_from_args() {
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(HeadlessOptions::from_args(args(&[])).unwrap(), None);
+        let options = HeadlessOptions::from_args(args(&["--headless"])).unwrap().unwrap();
+        assert_eq!(options.bind, DEFAULT_RPC_BIND.parse().unwrap());
+        assert_eq!(options.auth_token.len(), 64);
+
+        let options = HeadlessOptions::from_args(args(&["--headless", "--rpc-bind", "127.0.0.1:8070", "--rpc-auth-token=abc"])).unwrap().unwrap();
+        assert_eq!((options.bind.port(), options.auth_token.as_str()), (8070, "abc"));
+        assert!(HeadlessOptions::from_args(args(&["--headless", "--rpc-bind", "nowhere"])).unwrap_err().contains("Invalid --rpc-bind"));
+        assert!(HeadlessOptions::from_args(args(&["--rpc-auth-token", "abc"])).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_calls_need_token_and_known_method() {
+        let handler: RpcHandler = Arc::new(|method, params| Box::pin(async move {
+            match method.as_str() {
+                "echo" => Ok(param::<Value>(&params, "some_value")?),
+                _ => Err(RpcError::MethodNotFound(method)),
+            }
+        }));
+        let (addr, stop, server) = start(handler).await;
+
+        let (status, _) = post(addr, "wrong", r#"{"jsonrpc":"2.0","id":1,"method":"echo"}"#).await;
+        assert_eq!(status, 401);
+
+        let (status, body) = post(addr, "secret", r#"{"jsonrpc":"2.0","id":2,"method":"echo","params":{"someValue":5}}"#).await;
+        assert_eq!(status, 200);
+        assert_eq!(serde_json::from_str::<Value>(&body).unwrap(), serde_json::json!({"jsonrpc": "2.0", "id": 2, "result": 5}));
+
+        let (_, body) = post(addr, "secret", r#"{"jsonrpc":"2.0","id":3,"method":"nope"}"#).await;
+        assert_eq!(serde_json::from_str::<Value>(&body).unwrap()["error"]["code"], -32601);
+        let (_, body) = post(addr, "secret", "{not json").await;
+        assert_eq!(serde_json::from_str::<Value>(&body).unwrap()["error"]["code"], -32700);
+
+        drop(stop);
+        server.await.unwrap();
+    }
+
+    /// The wallet commands over HTTP, on the stub wallet library
+    #[cfg(feature = "mock-ffi")]
+    #[tokio::test]
+    async fn test_wallet_get_balance_over_http() {
+        let (addr, stop, server) = start(crate::rpc_handler()).await;
+
+        let (status, body) = post(addr, "secret", r#"{"jsonrpc":"2.0","id":1,"method":"wallet_get_balance","params":{}}"#).await;
+        assert_eq!(status, 200);
+        let response: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(response["result"], 1_000_000_000u64);
+
+        let (_, body) = post(addr, "secret", r#"{"jsonrpc":"2.0","id":2,"method":"validate_address","params":{"address":"fire123"}}"#).await;
+        assert_eq!(serde_json::from_str::<Value>(&body).unwrap()["result"], false);
+        let (_, body) = post(addr, "secret", r#"{"jsonrpc":"2.0","id":3,"method":"estimate_fee","params":{"amount":5}}"#).await;
+        assert_eq!(serde_json::from_str::<Value>(&body).unwrap()["error"]["message"], "Missing parameter: address");
+
+        stop.send(()).unwrap();
+        server.await.unwrap();
+    }
+}
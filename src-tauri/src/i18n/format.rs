@@ -0,0 +1,119 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Number and date formatting by language
+//!
+//! Decimal and grouping separators and the date layout of each bundled
+//! language. Unknown languages format numbers the English way and dates as
+//! ISO 8601. Output for right-to-left languages is wrapped in a left-to-right
+//! isolate, so a sign, the digits and the separators keep their order inside
+//! right-to-left text.
+
+use chrono::{DateTime, Local, TimeZone};
+
+/// Left-to-right isolate and its closing pop
+const LRI: char = '\u{2066}';
+const PDI: char = '\u{2069}';
+
+/// Number and date conventions of a language
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LocaleFormat {
+    pub decimal_separator: char,
+    pub group_separator: &'static str,
+    /// `chrono` format of a date with time
+    pub date_format: &'static str,
+    pub rtl: bool,
+}
+
+impl LocaleFormat {
+    pub fn for_language(language: &str) -> Self {
+        let (decimal_separator, group_separator, date_format) = match language {
+            "en" => ('.', ",", "%m/%d/%Y %I:%M %p"),
+            "de" => (',', ".", "%d.%m.%Y %H:%M"),
+            "es" | "it" | "pt" => (',', ".", "%d/%m/%Y %H:%M"),
+            "fr" => (',', "\u{202f}", "%d/%m/%Y %H:%M"),
+            "ru" => (',', "\u{a0}", "%d.%m.%Y %H:%M"),
+            "ja" | "zh" => ('.', ",", "%Y/%m/%d %H:%M"),
+            "ko" => ('.', ",", "%Y. %m. %d. %H:%M"),
+            "ar" => ('.', ",", "%d/%m/%Y %H:%M"),
+            _ => ('.', ",", "%Y-%m-%d %H:%M"),
+        };
+        Self { decimal_separator, group_separator, date_format, rtl: language == "ar" }
+    }
+
+    /// `value` with its digits grouped by thousands, rounded to `decimals`
+    /// places when given and otherwise with as many as it needs
+    pub fn format_number(&self, value: f64, decimals: Option<usize>) -> String {
+        if !value.is_finite() {
+            return value.to_string();
+        }
+        let plain = match decimals {
+            Some(decimals) => format!("{:.*}", decimals, value),
+            None => value.to_string(),
+        };
+        let (sign, digits) = plain.strip_prefix('-').map_or(("", plain.as_str()), |rest| ("-", rest));
+        let (integer, fraction) = digits.split_once('.').map_or((digits, None), |(i, f)| (i, Some(f)));
+
+        let mut formatted = String::from(sign);
+        for (i, digit) in integer.chars().enumerate() {
+            if i > 0 && (integer.len() - i) % 3 == 0 {
+                formatted.push_str(self.group_separator);
+            }
+            formatted.push(digit);
+        }
+        if let Some(fraction) = fraction {
+            formatted.push(self.decimal_separator);
+            formatted.push_str(fraction);
+        }
+        self.isolate(formatted)
+    }
+
+    /// Unix time `timestamp` in `zone`
+    pub fn format_timestamp<Tz: TimeZone>(&self, timestamp: u64, zone: &Tz) -> String
+    where
+        Tz::Offset: std::fmt::Display,
+    {
+        let Some(utc) = DateTime::from_timestamp(i64::try_from(timestamp).unwrap_or(i64::MAX), 0) else {
+            return timestamp.to_string();
+        };
+        self.isolate(utc.with_timezone(zone).format(self.date_format).to_string())
+    }
+
+    /// Unix time `timestamp` in the local time zone
+    pub fn format_local_timestamp(&self, timestamp: u64) -> String {
+        self.format_timestamp(timestamp, &Local)
+    }
+
+    fn isolate(&self, text: String) -> String {
+        if self.rtl { format!("{}{}{}", LRI, text, PDI) } else { text }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_numbers_follow_the_language() {
+        let en = LocaleFormat::for_language("en");
+        assert_eq!(en.format_number(1_234_567.891, None), "1,234,567.891");
+        assert_eq!(en.format_number(-1234.5, Some(2)), "-1,234.50");
+        assert_eq!(en.format_number(999.0, None), "999");
+        assert_eq!(LocaleFormat::for_language("de").format_number(1_234_567.5, Some(7)), "1.234.567,5000000");
+        assert_eq!(LocaleFormat::for_language("ru").format_number(12_345.25, None), "12\u{a0}345,25");
+        assert_eq!(LocaleFormat::for_language("xx").format_number(1234.5, None), "1,234.5");
+        // Right-to-left text keeps the sign and digits in order
+        assert_eq!(LocaleFormat::for_language("ar").format_number(-1234.5, None), "\u{2066}-1,234.5\u{2069}");
+    }
+
+    #[test]
+    fn test_dates_follow_the_language() {
+        let timestamp = 1_700_000_000; // 2023-11-14 22:13:20 UTC
+        assert_eq!(LocaleFormat::for_language("en").format_timestamp(timestamp, &Utc), "11/14/2023 10:13 PM");
+        assert_eq!(LocaleFormat::for_language("de").format_timestamp(timestamp, &Utc), "14.11.2023 22:13");
+        assert_eq!(LocaleFormat::for_language("ja").format_timestamp(timestamp, &Utc), "2023/11/14 22:13");
+        assert_eq!(LocaleFormat::for_language("xx").format_timestamp(timestamp, &Utc), "2023-11-14 22:13");
+        assert_eq!(LocaleFormat::for_language("ar").format_timestamp(timestamp, &Utc), "\u{2066}14/11/2023 22:13\u{2069}");
+    }
+}
@@ -1,6 +1,15 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
+use crate::settings::{LanguageSource, SettingsManager};
+use crate::utils::storage::{atomic_write, config_path};
+use crate::utils::locks::MutexExt;
+
+pub mod format;
+
+use format::LocaleFormat;
 
 /// Translation structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,264 +29,279 @@ pub struct LanguageInfo {
     pub rtl: bool,
 }
 
+/// CLDR plural category
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PluralCategory {
+    Zero,
+    One,
+    Two,
+    Few,
+    Many,
+    Other,
+}
+
+impl PluralCategory {
+    /// Plural category of an integer `count` in `language`, following the CLDR rules
+    pub fn for_count(language: &str, count: u64) -> Self {
+        match language {
+            "ru" => {
+                let (n10, n100) = (count % 10, count % 100);
+                if n10 == 1 && n100 != 11 {
+                    PluralCategory::One
+                } else if (2..=4).contains(&n10) && !(12..=14).contains(&n100) {
+                    PluralCategory::Few
+                } else {
+                    PluralCategory::Many
+                }
+            }
+            "ar" => match (count, count % 100) {
+                (0, _) => PluralCategory::Zero,
+                (1, _) => PluralCategory::One,
+                (2, _) => PluralCategory::Two,
+                (_, 3..=10) => PluralCategory::Few,
+                (_, 11..=99) => PluralCategory::Many,
+                _ => PluralCategory::Other,
+            },
+            "fr" | "pt" if count <= 1 => PluralCategory::One,
+            "zh" | "ja" | "ko" | "fr" | "pt" => PluralCategory::Other,
+            _ if count == 1 => PluralCategory::One,
+            _ => PluralCategory::Other,
+        }
+    }
+}
+
+/// Plural forms of a translation; only `other` is expected in every language
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PluralForms {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zero: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub one: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub two: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub few: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub many: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub other: Option<String>,
+}
+
+impl PluralForms {
+    /// Drop empty forms, as left by an unfilled translation skeleton
+    fn without_empty(self) -> Self {
+        let keep = |form: Option<String>| form.filter(|f| !f.is_empty());
+        Self {
+            zero: keep(self.zero),
+            one: keep(self.one),
+            two: keep(self.two),
+            few: keep(self.few),
+            many: keep(self.many),
+            other: keep(self.other),
+        }
+    }
+    
+    fn is_empty(&self) -> bool {
+        [&self.zero, &self.one, &self.two, &self.few, &self.many, &self.other]
+            .iter()
+            .all(|form| form.is_none())
+    }
+    
+    /// Form for `category`, falling back to `other`
+    pub fn select(&self, category: PluralCategory) -> Option<&String> {
+        let exact = match category {
+            PluralCategory::Zero => self.zero.as_ref(),
+            PluralCategory::One => self.one.as_ref(),
+            PluralCategory::Two => self.two.as_ref(),
+            PluralCategory::Few => self.few.as_ref(),
+            PluralCategory::Many => self.many.as_ref(),
+            PluralCategory::Other => None,
+        };
+        exact.or(self.other.as_ref())
+    }
+}
+
+/// Value of a translation key: plain text or a set of plural forms
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TranslationValue {
+    Text(String),
+    Plural(PluralForms),
+}
+
+/// Locale resource file (`locales/<code>.json`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocaleFile {
+    /// Language description; override files may omit it
+    #[serde(default)]
+    pub language: Option<LanguageInfo>,
+    pub translations: HashMap<String, TranslationValue>,
+}
+
+/// Translation coverage of a language, measured against the English keys
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranslationCoverage {
+    pub language: String,
+    pub total_keys: usize,
+    pub translated: usize,
+    pub missing: usize,
+    pub percent: f64,
+}
+
 /// Internationalization manager
 #[derive(Debug)]
 pub struct I18nManager {
     current_language: Arc<Mutex<String>>,
     translations: Arc<Mutex<HashMap<String, HashMap<String, String>>>>,
+    plurals: Arc<Mutex<HashMap<String, HashMap<String, PluralForms>>>>,
     languages: Arc<Mutex<Vec<LanguageInfo>>>,
+    locale_dirs: Arc<Mutex<Vec<PathBuf>>>,
+    /// Keys looked up at runtime that fell back to English or to the raw key, per language
+    missing: Arc<Mutex<HashMap<String, HashSet<String>>>>,
 }
 
 impl I18nManager {
     pub fn new() -> Self {
+        Self::with_locale_dirs(Vec::new())
+    }
+    
+    /// Create a manager loading locale files from `locale_dirs`; files in
+    /// later directories are merged on top of earlier ones
+    pub fn with_locale_dirs(locale_dirs: Vec<PathBuf>) -> Self {
         let manager = Self {
             current_language: Arc::new(Mutex::new("en".to_string())),
             translations: Arc::new(Mutex::new(HashMap::new())),
+            plurals: Arc::new(Mutex::new(HashMap::new())),
             languages: Arc::new(Mutex::new(Vec::new())),
+            locale_dirs: Arc::new(Mutex::new(locale_dirs)),
+            missing: Arc::new(Mutex::new(HashMap::new())),
         };
         
-        manager.initialize_languages();
-        manager.initialize_translations();
+        if let Err(e) = manager.reload_translations() {
+            log::error!("Failed to load translations: {}", e);
+        }
         manager
     }
     
-    fn initialize_languages(&self) {
-        let languages = vec![
-            LanguageInfo {
-                code: "en".to_string(),
-                name: "English".to_string(),
-                native_name: "English".to_string(),
-                flag: "🇺🇸".to_string(),
-                rtl: false,
-            },
-            LanguageInfo {
-                code: "es".to_string(),
-                name: "Spanish".to_string(),
-                native_name: "Español".to_string(),
-                flag: "🇪🇸".to_string(),
-                rtl: false,
-            },
-            LanguageInfo {
-                code: "fr".to_string(),
-                name: "French".to_string(),
-                native_name: "Français".to_string(),
-                flag: "🇫🇷".to_string(),
-                rtl: false,
-            },
-            LanguageInfo {
-                code: "de".to_string(),
-                name: "German".to_string(),
-                native_name: "Deutsch".to_string(),
-                flag: "🇩🇪".to_string(),
-                rtl: false,
-            },
-            LanguageInfo {
-                code: "it".to_string(),
-                name: "Italian".to_string(),
-                native_name: "Italiano".to_string(),
-                flag: "🇮🇹".to_string(),
-                rtl: false,
-            },
-            LanguageInfo {
-                code: "pt".to_string(),
-                name: "Portuguese".to_string(),
-                native_name: "Português".to_string(),
-                flag: "🇵🇹".to_string(),
-                rtl: false,
-            },
-            LanguageInfo {
-                code: "ru".to_string(),
-                name: "Russian".to_string(),
-                native_name: "Русский".to_string(),
-                flag: "🇷🇺".to_string(),
-                rtl: false,
-            },
-            LanguageInfo {
-                code: "zh".to_string(),
-                name: "Chinese".to_string(),
-                native_name: "中文".to_string(),
-                flag: "🇨🇳".to_string(),
-                rtl: false,
-            },
-            LanguageInfo {
-                code: "ja".to_string(),
-                name: "Japanese".to_string(),
-                native_name: "日本語".to_string(),
-                flag: "🇯🇵".to_string(),
-                rtl: false,
-            },
-            LanguageInfo {
-                code: "ko".to_string(),
-                name: "Korean".to_string(),
-                native_name: "한국어".to_string(),
-                flag: "🇰🇷".to_string(),
-                rtl: false,
-            },
-            LanguageInfo {
-                code: "ar".to_string(),
-                name: "Arabic".to_string(),
-                native_name: "العربية".to_string(),
-                flag: "🇸🇦".to_string(),
-                rtl: true,
-            },
-        ];
-        
-        *self.languages.lock().unwrap() = languages;
-    }
-    
-    fn initialize_translations(&self) {
-        let mut translations = HashMap::new();
-        
-        // English translations
-        let mut en_translations = HashMap::new();
-        en_translations.insert("app.title".to_string(), "Fuego Wallet".to_string());
-        en_translations.insert("app.subtitle".to_string(), "Secure XFG Wallet".to_string());
-        en_translations.insert("wallet.balance".to_string(), "Balance".to_string());
-        en_translations.insert("wallet.address".to_string(), "Address".to_string());
-        en_translations.insert("wallet.send".to_string(), "Send XFG".to_string());
-        en_translations.insert("wallet.receive".to_string(), "Receive XFG".to_string());
-        en_translations.insert("wallet.transactions".to_string(), "Transactions".to_string());
-        en_translations.insert("wallet.deposits".to_string(), "Term Deposits".to_string());
-        en_translations.insert("network.status".to_string(), "Network Status".to_string());
-        en_translations.insert("network.syncing".to_string(), "Syncing".to_string());
-        en_translations.insert("network.synced".to_string(), "Synced".to_string());
-        en_translations.insert("network.peers".to_string(), "Peers".to_string());
-        en_translations.insert("settings.title".to_string(), "Settings".to_string());
-        en_translations.insert("settings.wallet".to_string(), "Wallet".to_string());
-        en_translations.insert("settings.network".to_string(), "Network".to_string());
-        en_translations.insert("settings.ui".to_string(), "User Interface".to_string());
-        en_translations.insert("settings.security".to_string(), "Security".to_string());
-        en_translations.insert("settings.performance".to_string(), "Performance".to_string());
-        en_translations.insert("backup.title".to_string(), "Backup & Recovery".to_string());
-        en_translations.insert("backup.create".to_string(), "Create Backup".to_string());
-        en_translations.insert("backup.restore".to_string(), "Restore Backup".to_string());
-        en_translations.insert("backup.export".to_string(), "Export Backup".to_string());
-        en_translations.insert("backup.import".to_string(), "Import Backup".to_string());
-        en_translations.insert("common.save".to_string(), "Save".to_string());
-        en_translations.insert("common.cancel".to_string(), "Cancel".to_string());
-        en_translations.insert("common.confirm".to_string(), "Confirm".to_string());
-        en_translations.insert("common.delete".to_string(), "Delete".to_string());
-        en_translations.insert("common.edit".to_string(), "Edit".to_string());
-        en_translations.insert("common.close".to_string(), "Close".to_string());
-        en_translations.insert("common.refresh".to_string(), "Refresh".to_string());
-        en_translations.insert("common.loading".to_string(), "Loading...".to_string());
-        en_translations.insert("common.error".to_string(), "Error".to_string());
-        en_translations.insert("common.success".to_string(), "Success".to_string());
-        en_translations.insert("common.warning".to_string(), "Warning".to_string());
-        en_translations.insert("common.info".to_string(), "Information".to_string());
-        translations.insert("en".to_string(), en_translations);
-        
-        // Spanish translations
-        let mut es_translations = HashMap::new();
-        es_translations.insert("app.title".to_string(), "Cartera Fuego".to_string());
-        es_translations.insert("app.subtitle".to_string(), "Cartera Segura XFG".to_string());
-        es_translations.insert("wallet.balance".to_string(), "Saldo".to_string());
-        es_translations.insert("wallet.address".to_string(), "Dirección".to_string());
-        es_translations.insert("wallet.send".to_string(), "Enviar XFG".to_string());
-        es_translations.insert("wallet.receive".to_string(), "Recibir XFG".to_string());
-        es_translations.insert("wallet.transactions".to_string(), "Transacciones".to_string());
-        es_translations.insert("wallet.deposits".to_string(), "Depósitos a Plazo".to_string());
-        es_translations.insert("network.status".to_string(), "Estado de Red".to_string());
-        es_translations.insert("network.syncing".to_string(), "Sincronizando".to_string());
-        es_translations.insert("network.synced".to_string(), "Sincronizado".to_string());
-        es_translations.insert("network.peers".to_string(), "Pares".to_string());
-        es_translations.insert("settings.title".to_string(), "Configuración".to_string());
-        es_translations.insert("settings.wallet".to_string(), "Cartera".to_string());
-        es_translations.insert("settings.network".to_string(), "Red".to_string());
-        es_translations.insert("settings.ui".to_string(), "Interfaz de Usuario".to_string());
-        es_translations.insert("settings.security".to_string(), "Seguridad".to_string());
-        es_translations.insert("settings.performance".to_string(), "Rendimiento".to_string());
-        es_translations.insert("backup.title".to_string(), "Respaldo y Recuperación".to_string());
-        es_translations.insert("backup.create".to_string(), "Crear Respaldo".to_string());
-        es_translations.insert("backup.restore".to_string(), "Restaurar Respaldo".to_string());
-        es_translations.insert("backup.export".to_string(), "Exportar Respaldo".to_string());
-        es_translations.insert("backup.import".to_string(), "Importar Respaldo".to_string());
-        es_translations.insert("common.save".to_string(), "Guardar".to_string());
-        es_translations.insert("common.cancel".to_string(), "Cancelar".to_string());
-        es_translations.insert("common.confirm".to_string(), "Confirmar".to_string());
-        es_translations.insert("common.delete".to_string(), "Eliminar".to_string());
-        es_translations.insert("common.edit".to_string(), "Editar".to_string());
-        es_translations.insert("common.close".to_string(), "Cerrar".to_string());
-        es_translations.insert("common.refresh".to_string(), "Actualizar".to_string());
-        es_translations.insert("common.loading".to_string(), "Cargando...".to_string());
-        es_translations.insert("common.error".to_string(), "Error".to_string());
-        es_translations.insert("common.success".to_string(), "Éxito".to_string());
-        es_translations.insert("common.warning".to_string(), "Advertencia".to_string());
-        es_translations.insert("common.info".to_string(), "Información".to_string());
-        translations.insert("es".to_string(), es_translations);
-        
-        // French translations
-        let mut fr_translations = HashMap::new();
-        fr_translations.insert("app.title".to_string(), "Portefeuille Fuego".to_string());
-        fr_translations.insert("app.subtitle".to_string(), "Portefeuille Sécurisé XFG".to_string());
-        fr_translations.insert("wallet.balance".to_string(), "Solde".to_string());
-        fr_translations.insert("wallet.address".to_string(), "Adresse".to_string());
-        fr_translations.insert("wallet.send".to_string(), "Envoyer XFG".to_string());
-        fr_translations.insert("wallet.receive".to_string(), "Recevoir XFG".to_string());
-        fr_translations.insert("wallet.transactions".to_string(), "Transactions".to_string());
-        fr_translations.insert("wallet.deposits".to_string(), "Dépôts à Terme".to_string());
-        fr_translations.insert("network.status".to_string(), "Statut du Réseau".to_string());
-        fr_translations.insert("network.syncing".to_string(), "Synchronisation".to_string());
-        fr_translations.insert("network.synced".to_string(), "Synchronisé".to_string());
-        fr_translations.insert("network.peers".to_string(), "Pairs".to_string());
-        fr_translations.insert("settings.title".to_string(), "Paramètres".to_string());
-        fr_translations.insert("settings.wallet".to_string(), "Portefeuille".to_string());
-        fr_translations.insert("settings.network".to_string(), "Réseau".to_string());
-        fr_translations.insert("settings.ui".to_string(), "Interface Utilisateur".to_string());
-        fr_translations.insert("settings.security".to_string(), "Sécurité".to_string());
-        fr_translations.insert("settings.performance".to_string(), "Performance".to_string());
-        fr_translations.insert("backup.title".to_string(), "Sauvegarde et Récupération".to_string());
-        fr_translations.insert("backup.create".to_string(), "Créer Sauvegarde".to_string());
-        fr_translations.insert("backup.restore".to_string(), "Restaurer Sauvegarde".to_string());
-        fr_translations.insert("backup.export".to_string(), "Exporter Sauvegarde".to_string());
-        fr_translations.insert("backup.import".to_string(), "Importer Sauvegarde".to_string());
-        fr_translations.insert("common.save".to_string(), "Enregistrer".to_string());
-        fr_translations.insert("common.cancel".to_string(), "Annuler".to_string());
-        fr_translations.insert("common.confirm".to_string(), "Confirmer".to_string());
-        fr_translations.insert("common.delete".to_string(), "Supprimer".to_string());
-        fr_translations.insert("common.edit".to_string(), "Modifier".to_string());
-        fr_translations.insert("common.close".to_string(), "Fermer".to_string());
-        fr_translations.insert("common.refresh".to_string(), "Actualiser".to_string());
-        fr_translations.insert("common.loading".to_string(), "Chargement...".to_string());
-        fr_translations.insert("common.error".to_string(), "Erreur".to_string());
-        fr_translations.insert("common.success".to_string(), "Succès".to_string());
-        fr_translations.insert("common.warning".to_string(), "Avertissement".to_string());
-        fr_translations.insert("common.info".to_string(), "Information".to_string());
-        translations.insert("fr".to_string(), fr_translations);
-        
-        *self.translations.lock().unwrap() = translations;
+    /// Directory for user-provided translations merged on top of the bundled ones
+    pub fn override_dir() -> Option<PathBuf> {
+        config_path("locales")
+    }
+    
+    /// Replace the directories translations are loaded from
+    pub fn set_locale_dirs(&self, locale_dirs: Vec<PathBuf>) -> Result<(), String> {
+        *self.locale_dirs.lock_recover() = locale_dirs;
+        Ok(())
+    }
+    
+    /// Reload all locale files and return the codes of the languages that loaded.
+    ///
+    /// Malformed files are logged and skipped. If the current language is no
+    /// longer available the manager falls back to English.
+    pub fn reload_translations(&self) -> Result<Vec<String>, String> {
+        let locale_dirs = self.locale_dirs.lock_recover()
+            .clone();
+        
+        let mut translations: HashMap<String, HashMap<String, String>> = HashMap::new();
+        let mut plurals: HashMap<String, HashMap<String, PluralForms>> = HashMap::new();
+        let mut languages: HashMap<String, LanguageInfo> = HashMap::new();
+        
+        for dir in &locale_dirs {
+            let Ok(entries) = fs::read_dir(dir) else {
+                continue;
+            };
+            
+            let mut paths: Vec<PathBuf> = entries
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| path.extension().and_then(|s| s.to_str()) == Some("json"))
+                .collect();
+            paths.sort();
+            
+            for path in paths {
+                let Some(code) = path.file_stem().and_then(|s| s.to_str()).map(String::from) else {
+                    continue;
+                };
+                let locale = match fs::read_to_string(&path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|content| serde_json::from_str::<LocaleFile>(&content).map_err(|e| e.to_string()))
+                {
+                    Ok(locale) => locale,
+                    Err(e) => {
+                        log::warn!("Skipping malformed locale file {}: {}", path.display(), e);
+                        continue;
+                    }
+                };
+                
+                if let Some(mut info) = locale.language {
+                    info.code = code.clone();
+                    languages.insert(code.clone(), info);
+                }
+                let texts = translations.entry(code.clone()).or_default();
+                for (key, value) in locale.translations {
+                    match value {
+                        // Empty values are untranslated entries from a skeleton file
+                        TranslationValue::Text(text) if text.is_empty() => {}
+                        TranslationValue::Text(text) => {
+                            texts.insert(key, text);
+                        }
+                        TranslationValue::Plural(forms) => {
+                            let forms = forms.without_empty();
+                            if forms.is_empty() {
+                                continue;
+                            }
+                            // Plain lookups of a plural key get its `other` form
+                            if let Some(other) = &forms.other {
+                                texts.insert(key.clone(), other.clone());
+                            }
+                            plurals.entry(code.clone()).or_default().insert(key, forms);
+                        }
+                    }
+                }
+            }
+        }
+        
+        // Languages need a description from at least one file to be listed
+        let mut available: Vec<LanguageInfo> = languages.into_values()
+            .filter(|lang| translations.contains_key(&lang.code))
+            .collect();
+        available.sort_by(|a, b| a.code.cmp(&b.code));
+        let codes: Vec<String> = available.iter().map(|lang| lang.code.clone()).collect();
+        
+        *self.translations.lock_recover() = translations;
+        *self.plurals.lock_recover() = plurals;
+        *self.languages.lock_recover() = available;
+        
+        let mut current = self.current_language.lock_recover();
+        if !codes.contains(&current) {
+            *current = "en".to_string();
+        }
+        
+        Ok(codes)
     }
     
     pub fn get_current_language(&self) -> Result<String, String> {
-        self.current_language.lock()
-            .map_err(|e| format!("Failed to lock current language: {}", e))
-            .map(|lang| lang.clone())
+        Ok(self.current_language.lock_recover().clone())
     }
     
     pub fn set_language(&self, language_code: String) -> Result<(), String> {
-        let languages = self.languages.lock()
-            .map_err(|e| format!("Failed to lock languages: {}", e))?;
+        let languages = self.languages.lock_recover();
         
         if !languages.iter().any(|lang| lang.code == language_code) {
             return Err(format!("Unsupported language: {}", language_code));
         }
         
-        *self.current_language.lock()
-            .map_err(|e| format!("Failed to lock current language: {}", e))? = language_code;
+        *self.current_language.lock_recover() = language_code;
         
         Ok(())
     }
     
     pub fn get_available_languages(&self) -> Result<Vec<LanguageInfo>, String> {
-        self.languages.lock()
-            .map_err(|e| format!("Failed to lock languages: {}", e))
-            .map(|langs| langs.clone())
+        Ok(self.languages.lock_recover().clone())
     }
     
     pub fn translate(&self, key: &str) -> Result<String, String> {
         let current_lang = self.get_current_language()?;
-        let translations = self.translations.lock()
-            .map_err(|e| format!("Failed to lock translations: {}", e))?;
+        let translations = self.translations.lock_recover();
         
         if let Some(lang_translations) = translations.get(&current_lang) {
             if let Some(translation) = lang_translations.get(key) {
@@ -285,6 +309,8 @@ impl I18nManager {
             }
         }
         
+        self.record_missing(&current_lang, key);
+        
         // Fallback to English
         if let Some(en_translations) = translations.get("en") {
             if let Some(translation) = en_translations.get(key) {
@@ -306,9 +332,141 @@ impl I18nManager {
         Ok(translation)
     }
     
+    /// Translate a key whose wording depends on `count`, substituting `{count}`
+    /// and `params`.
+    ///
+    /// Lookup order: the exact plural form of the current language, its
+    /// `other` form, the English form, then the key itself.
+    pub fn translate_plural(&self, key: &str, count: u64, params: HashMap<String, String>) -> Result<String, String> {
+        let current_lang = self.get_current_language()?;
+        let plurals = self.plurals.lock_recover();
+        
+        let form = [current_lang.as_str(), "en"].iter()
+            .find_map(|lang| {
+                plurals.get(*lang)
+                    .and_then(|forms| forms.get(key))
+                    .and_then(|forms| forms.select(PluralCategory::for_count(lang, count)))
+                    .map(|form| (*lang, form.clone()))
+            });
+        drop(plurals);
+        
+        let mut translation = match form {
+            Some((lang, form)) => {
+                if lang != current_lang {
+                    self.record_missing(&current_lang, key);
+                }
+                form
+            }
+            None => self.translate(key)?,
+        };
+        
+        translation = translation.replace("{count}", &count.to_string());
+        for (param_key, param_value) in params {
+            translation = translation.replace(&format!("{{{}}}", param_key), &param_value);
+        }
+        
+        Ok(translation)
+    }
+    
+    fn record_missing(&self, language: &str, key: &str) {
+        self.missing.lock_recover()
+            .entry(language.to_string())
+            .or_default()
+            .insert(key.to_string());
+    }
+    
+    /// Keys of a language that are absent from its locale files or were
+    /// looked up at runtime without a translation, sorted
+    pub fn missing_keys(&self, language: &str) -> Result<Vec<String>, String> {
+        let mut keys: HashSet<String> = self.missing.lock_recover()
+            .get(language)
+            .cloned()
+            .unwrap_or_default();
+        
+        let translated = self.translated_keys(language)?;
+        keys.extend(self.translated_keys("en")?
+            .into_iter()
+            .filter(|key| !translated.contains(key)));
+        
+        let mut keys: Vec<String> = keys.into_iter().collect();
+        keys.sort();
+        Ok(keys)
+    }
+    
+    /// Translation coverage of every available language against the English keys
+    pub fn coverage(&self) -> Result<Vec<TranslationCoverage>, String> {
+        let english = self.translated_keys("en")?;
+        let total_keys = english.len();
+        
+        self.get_available_languages()?
+            .into_iter()
+            .map(|lang| {
+                let keys = self.translated_keys(&lang.code)?;
+                let translated = english.iter().filter(|key| keys.contains(*key)).count();
+                let percent = if total_keys == 0 {
+                    100.0
+                } else {
+                    translated as f64 / total_keys as f64 * 100.0
+                };
+                Ok(TranslationCoverage {
+                    language: lang.code,
+                    total_keys,
+                    translated,
+                    missing: total_keys - translated,
+                    percent,
+                })
+            })
+            .collect()
+    }
+    
+    /// Write a locale skeleton with an empty entry for every missing key of
+    /// `language`, ready for translators to fill in. Empty entries are ignored
+    /// when loading, so an unfinished file can be dropped in the override directory.
+    pub fn export_missing(&self, language: &str, path: &std::path::Path) -> Result<usize, String> {
+        let keys = self.missing_keys(language)?;
+        let english_plurals: HashSet<String> = self.plurals.lock_recover()
+            .get("en")
+            .map(|forms| forms.keys().cloned().collect())
+            .unwrap_or_default();
+        
+        let translations: HashMap<String, TranslationValue> = keys.iter()
+            .map(|key| {
+                let value = if english_plurals.contains(key) {
+                    TranslationValue::Plural(PluralForms { other: Some(String::new()), ..Default::default() })
+                } else {
+                    TranslationValue::Text(String::new())
+                };
+                (key.clone(), value)
+            })
+            .collect();
+        
+        let language_info = self.get_available_languages()?
+            .into_iter()
+            .find(|lang| lang.code == language);
+        let skeleton = LocaleFile { language: language_info, translations };
+        let content = serde_json::to_string_pretty(&skeleton)
+            .map_err(|e| format!("Failed to serialize translations: {}", e))?;
+        atomic_write(path, content.as_bytes())?;
+        
+        Ok(keys.len())
+    }
+    
+    /// Keys that have a loaded translation (plain or plural) in `language`
+    fn translated_keys(&self, language: &str) -> Result<HashSet<String>, String> {
+        let mut keys: HashSet<String> = self.translations.lock_recover()
+            .get(language)
+            .map(|texts| texts.keys().cloned().collect())
+            .unwrap_or_default();
+        if let Some(forms) = self.plurals.lock_recover()
+            .get(language)
+        {
+            keys.extend(forms.keys().cloned());
+        }
+        Ok(keys)
+    }
+    
     pub fn add_translation(&self, language_code: String, key: String, value: String) -> Result<(), String> {
-        let mut translations = self.translations.lock()
-            .map_err(|e| format!("Failed to lock translations: {}", e))?;
+        let mut translations = self.translations.lock_recover();
         
         translations.entry(language_code)
             .or_insert_with(HashMap::new)
@@ -319,8 +477,7 @@ impl I18nManager {
     
     pub fn is_rtl(&self) -> Result<bool, String> {
         let current_lang = self.get_current_language()?;
-        let languages = self.languages.lock()
-            .map_err(|e| format!("Failed to lock languages: {}", e))?;
+        let languages = self.languages.lock_recover();
         
         if let Some(lang_info) = languages.iter().find(|lang| lang.code == current_lang) {
             Ok(lang_info.rtl)
@@ -328,6 +485,386 @@ impl I18nManager {
             Ok(false)
         }
     }
+    
+    pub fn get_current_language_info(&self) -> Result<LanguageInfo, String> {
+        let current_lang = self.get_current_language()?;
+        let languages = self.languages.lock_recover();
+        
+        languages.iter()
+            .find(|lang| lang.code == current_lang)
+            .cloned()
+            .ok_or_else(|| format!("Unsupported language: {}", current_lang))
+    }
+
+    /// Number and date conventions of the current language
+    pub fn locale_format(&self) -> LocaleFormat {
+        let mut format = LocaleFormat::for_language(&self.get_current_language().unwrap_or_default());
+        format.rtl = self.is_rtl().unwrap_or(format.rtl);
+        format
+    }
+
+    /// `value` with the separators of the current language
+    pub fn format_number(&self, value: f64) -> String {
+        self.locale_format().format_number(value, None)
+    }
+
+    /// Unix time `ts` as a local date and time in the current language
+    pub fn format_timestamp(&self, ts: u64) -> String {
+        self.locale_format().format_local_timestamp(ts)
+    }
+}
+
+impl I18nManager {
+    /// Switch language and persist the choice in the UI settings, so the
+    /// settings and the active language never disagree
+    pub fn switch_language(&self, language_code: &str, settings: &SettingsManager) -> Result<LanguageInfo, String> {
+        self.set_language(language_code.to_string())?;
+        settings.set_language(language_code, LanguageSource::Explicit)?;
+        self.get_current_language_info()
+    }
+    
+    /// Available language closest to a locale tag such as `pt-BR`, `zh-Hans`
+    /// or `de_DE.UTF-8`: the full tag if a locale file exists for it,
+    /// otherwise its primary language
+    pub fn language_for_locale(&self, locale: &str) -> Option<LanguageInfo> {
+        let languages = self.get_available_languages().ok()?;
+        let tag = locale.split(['.', '@']).next()?.replace('_', "-").to_lowercase();
+        let primary = tag.split('-').next()?.to_string();
+        
+        languages.iter()
+            .find(|lang| lang.code.to_lowercase() == tag)
+            .or_else(|| languages.iter().find(|lang| lang.code.to_lowercase() == primary))
+            .cloned()
+    }
+    
+    /// Available language matching the system locale
+    pub fn detect_system_language(&self) -> Option<LanguageInfo> {
+        system_locale().and_then(|locale| self.language_for_locale(&locale))
+    }
+    
+    /// Use the system language and remember it as auto-detected
+    pub fn apply_system_language(&self, settings: &SettingsManager) -> Result<Option<LanguageInfo>, String> {
+        self.apply_detected_language(system_locale().as_deref(), settings)
+    }
+    
+    fn apply_detected_language(&self, locale: Option<&str>, settings: &SettingsManager) -> Result<Option<LanguageInfo>, String> {
+        let Some(info) = locale.and_then(|locale| self.language_for_locale(locale)) else {
+            return Ok(None);
+        };
+        self.set_language(info.code.clone())?;
+        settings.set_language(&info.code, LanguageSource::Auto)?;
+        Ok(Some(info))
+    }
+    
+    /// Pick the startup language. An explicit choice is always kept; otherwise
+    /// the system locale is detected and persisted as `auto`. English is used
+    /// when neither is available. Returns the language that was applied.
+    pub fn apply_startup_language(&self, settings: &SettingsManager) -> String {
+        self.apply_startup_language_for_locale(system_locale().as_deref(), settings)
+    }
+    
+    fn apply_startup_language_for_locale(&self, locale: Option<&str>, settings: &SettingsManager) -> String {
+        let ui = settings.get_settings().map(|s| s.ui).ok();
+        
+        if let Some(ui) = &ui
+            && ui.has_explicit_language()
+            && self.set_language(ui.language.clone()).is_ok()
+        {
+            return ui.language.clone();
+        }
+        
+        match self.apply_detected_language(locale, settings) {
+            Ok(Some(info)) => return info.code,
+            Ok(None) => {}
+            Err(e) => log::warn!("Failed to apply system language: {}", e),
+        }
+        
+        let fallback = ui.map(|ui| ui.language).unwrap_or_else(|| "en".to_string());
+        if self.set_language(fallback.clone()).is_ok() {
+            return fallback;
+        }
+        let _ = self.set_language("en".to_string());
+        self.get_current_language().unwrap_or_else(|_| "en".to_string())
+    }
+}
+
+/// Locale tag of the operating system (e.g. `pt-BR`), if it can be determined
+pub fn system_locale() -> Option<String> {
+    sys_locale::get_locale().or_else(|| {
+        ["LC_ALL", "LC_MESSAGES", "LANG"].iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.is_empty() && value != "C" && value != "POSIX")
+    })
 }
 
 // Tauri commands are defined in lib.rs
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::testing::TempDir;
+    
+    fn bundled() -> I18nManager {
+        I18nManager::with_locale_dirs(vec![PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("locales")])
+    }
+    
+    fn fixture_dir(files: &[(&str, &str)]) -> TempDir {
+        let dir = TempDir::new("fuego_locales");
+        for (name, content) in files {
+            fs::write(dir.join(name), content).unwrap();
+        }
+        dir
+    }
+    
+    #[test]
+    fn test_bundled_locales_all_load() {
+        let manager = bundled();
+        let languages = manager.get_available_languages().unwrap();
+        assert_eq!(languages.len(), 11);
+        
+        manager.set_language("de".to_string()).unwrap();
+        assert_eq!(manager.translate("wallet.balance").unwrap(), "Guthaben");
+    }
+    
+    #[test]
+    fn test_broken_file_skipped_and_override_merged() {
+        let base = fixture_dir(&[
+            ("en.json", r#"{"language":{"code":"en","name":"English","native_name":"English","flag":"","rtl":false},"translations":{"a":"A","b":"B"}}"#),
+            ("xx.json", "{ this is not json"),
+        ]);
+        let overrides = fixture_dir(&[
+            ("en.json", r#"{"translations":{"b":"Better B"}}"#),
+        ]);
+        
+        let manager = I18nManager::with_locale_dirs(vec![base.to_path_buf(), overrides.to_path_buf()]);
+        let codes: Vec<String> = manager.get_available_languages().unwrap().into_iter().map(|l| l.code).collect();
+        assert_eq!(codes, vec!["en".to_string()]);
+        assert_eq!(manager.translate("a").unwrap(), "A");
+        assert_eq!(manager.translate("b").unwrap(), "Better B");
+        assert!(manager.set_language("xx".to_string()).is_err());
+        
+        // Fixing the file and reloading picks it up
+        fs::write(base.join("xx.json"), r#"{"language":{"code":"xx","name":"X","native_name":"X","flag":"","rtl":false},"translations":{"a":"Ax"}}"#).unwrap();
+        assert_eq!(manager.reload_translations().unwrap().len(), 2);
+        manager.set_language("xx".to_string()).unwrap();
+        assert_eq!(manager.translate("a").unwrap(), "Ax");
+        assert_eq!(manager.translate("b").unwrap(), "Better B");
+    }
+    
+    #[test]
+    fn test_switch_language_persists_to_settings() {
+        let dir = TempDir::new("fuego_i18n");
+        let config_path = dir.join("settings.json");
+        
+        let settings = SettingsManager::with_config_path(config_path.clone()).unwrap();
+        let manager = bundled();
+        let info = manager.switch_language("fr", &settings).unwrap();
+        assert_eq!(info.code, "fr");
+        
+        // A fresh start picks the saved language up again
+        let reloaded = SettingsManager::with_config_path(config_path).unwrap();
+        let saved = reloaded.get_settings().unwrap().ui.language;
+        assert_eq!(saved, "fr");
+        let restarted = bundled();
+        assert_eq!(restarted.apply_startup_language_for_locale(Some("de-DE"), &reloaded), "fr");
+        assert_eq!(restarted.get_current_language().unwrap(), "fr");
+    }
+    
+    #[test]
+    fn test_locale_mapping_table() {
+        let manager = bundled();
+        let code = |locale: &str| manager.language_for_locale(locale).map(|lang| lang.code);
+        
+        assert_eq!(code("pt-BR").as_deref(), Some("pt"));
+        assert_eq!(code("zh-Hans").as_deref(), Some("zh"));
+        assert_eq!(code("zh-Hant-TW").as_deref(), Some("zh"));
+        assert_eq!(code("de_DE.UTF-8").as_deref(), Some("de"));
+        assert_eq!(code("EN-us").as_deref(), Some("en"));
+        assert_eq!(code("sr-Latn-RS"), None);
+        
+        let arabic = manager.language_for_locale("ar-EG").unwrap();
+        assert_eq!(arabic.code, "ar");
+        assert!(arabic.rtl);
+        assert!(!manager.language_for_locale("fr-CA").unwrap().rtl);
+    }
+    
+    #[test]
+    fn test_first_launch_detects_and_marks_auto() {
+        let dir = TempDir::new("fuego_i18n");
+        let settings = SettingsManager::with_config_path(dir.join("settings.json")).unwrap();
+        
+        let manager = bundled();
+        assert_eq!(manager.apply_startup_language_for_locale(Some("ar-SA"), &settings), "ar");
+        assert!(manager.is_rtl().unwrap());
+        let ui = settings.get_settings().unwrap().ui;
+        assert_eq!((ui.language.as_str(), ui.language_source), ("ar", LanguageSource::Auto));
+        
+        // Auto-detection follows the system locale on later starts...
+        assert_eq!(bundled().apply_startup_language_for_locale(Some("es-MX"), &settings), "es");
+        // ...until the user picks a language explicitly
+        manager.switch_language("it", &settings).unwrap();
+        assert_eq!(bundled().apply_startup_language_for_locale(Some("ja-JP"), &settings), "it");
+        assert_eq!(settings.get_settings().unwrap().ui.language_source, LanguageSource::Explicit);
+    }
+    
+    #[test]
+    fn test_switch_to_unsupported_language_keeps_settings() {
+        let dir = TempDir::new("fuego_i18n");
+        
+        let settings = SettingsManager::with_config_path(dir.join("settings.json")).unwrap();
+        let manager = bundled();
+        let err = manager.switch_language("tlh", &settings).unwrap_err();
+        assert!(err.contains("Unsupported language"));
+        assert_eq!(settings.get_settings().unwrap().ui.language, "en");
+        assert_eq!(manager.get_current_language().unwrap(), "en");
+    }
+    
+    #[test]
+    fn test_russian_plural_categories() {
+        assert_eq!(PluralCategory::for_count("ru", 1), PluralCategory::One);
+        assert_eq!(PluralCategory::for_count("ru", 2), PluralCategory::Few);
+        assert_eq!(PluralCategory::for_count("ru", 5), PluralCategory::Many);
+        assert_eq!(PluralCategory::for_count("ru", 11), PluralCategory::Many);
+        assert_eq!(PluralCategory::for_count("ru", 21), PluralCategory::One);
+        assert_eq!(PluralCategory::for_count("ru", 112), PluralCategory::Many);
+        
+        let manager = bundled();
+        manager.set_language("ru".to_string()).unwrap();
+        let count = |n| manager.translate_plural("wallet.transaction_count", n, HashMap::new()).unwrap();
+        assert_eq!(count(1), "1 транзакция");
+        assert_eq!(count(2), "2 транзакции");
+        assert_eq!(count(5), "5 транзакций");
+        assert_eq!(count(21), "21 транзакция");
+    }
+    
+    #[test]
+    fn test_arabic_plural_categories() {
+        let categories: Vec<PluralCategory> = [0, 1, 2, 3, 10, 11, 99, 100, 102, 103]
+            .iter()
+            .map(|n| PluralCategory::for_count("ar", *n))
+            .collect();
+        assert_eq!(categories, vec![
+            PluralCategory::Zero,
+            PluralCategory::One,
+            PluralCategory::Two,
+            PluralCategory::Few,
+            PluralCategory::Few,
+            PluralCategory::Many,
+            PluralCategory::Many,
+            PluralCategory::Other,
+            PluralCategory::Other,
+            PluralCategory::Few,
+        ]);
+        
+        let manager = bundled();
+        manager.set_language("ar".to_string()).unwrap();
+        assert_eq!(manager.translate_plural("wallet.transaction_count", 2, HashMap::new()).unwrap(), "معاملتان");
+    }
+    
+    #[test]
+    fn test_plural_fallback_order() {
+        let base = fixture_dir(&[
+            ("en.json", r#"{"language":{"code":"en","name":"English","native_name":"English","flag":"","rtl":false},"translations":{"items":{"one":"{count} item","other":"{count} items"}}}"#),
+            ("ru.json", r#"{"language":{"code":"ru","name":"Russian","native_name":"Русский","flag":"","rtl":false},"translations":{"files":{"other":"{count} {kind}"}}}"#),
+        ]);
+        let manager = I18nManager::with_locale_dirs(vec![base.to_path_buf()]);
+        manager.set_language("ru".to_string()).unwrap();
+        
+        let mut params = HashMap::new();
+        params.insert("kind".to_string(), "файла".to_string());
+        // Missing `few` form falls back to `other`
+        assert_eq!(manager.translate_plural("files", 3, params).unwrap(), "3 файла");
+        // Missing in Russian falls back to English rules and forms
+        assert_eq!(manager.translate_plural("items", 1, HashMap::new()).unwrap(), "1 item");
+        // Unknown keys come back as-is
+        assert_eq!(manager.translate_plural("missing", 4, HashMap::new()).unwrap(), "missing");
+        // Plain lookups of a plural key use the `other` form
+        manager.set_language("en".to_string()).unwrap();
+        assert_eq!(manager.translate("items").unwrap(), "{count} items");
+    }
+    
+    fn coverage_fixture() -> (TempDir, I18nManager) {
+        let dir = fixture_dir(&[
+            ("en.json", r#"{"language":{"code":"en","name":"English","native_name":"English","flag":"","rtl":false},"translations":{"a":"A","b":"B","c":"C","items":{"one":"{count} item","other":"{count} items"}}}"#),
+            ("de.json", r#"{"language":{"code":"de","name":"German","native_name":"Deutsch","flag":"","rtl":false},"translations":{"a":"A-de","items":{"one":"{count} Element","other":"{count} Elemente"},"c":""}}"#),
+        ]);
+        let manager = I18nManager::with_locale_dirs(vec![dir.to_path_buf()]);
+        (dir, manager)
+    }
+    
+    #[test]
+    fn test_fallbacks_are_tracked() {
+        let (_dir, manager) = coverage_fixture();
+        manager.set_language("de".to_string()).unwrap();
+        
+        assert_eq!(manager.translate("a").unwrap(), "A-de");
+        assert_eq!(manager.translate("b").unwrap(), "B");
+        assert_eq!(manager.translate("not.a.key").unwrap(), "not.a.key");
+        assert_eq!(manager.translate_plural("items", 2, HashMap::new()).unwrap(), "2 Elemente");
+        
+        let missing = manager.missing_keys("de").unwrap();
+        assert_eq!(missing, vec!["b".to_string(), "c".to_string(), "not.a.key".to_string()]);
+        assert!(manager.missing_keys("en").unwrap().is_empty());
+    }
+    
+    #[test]
+    fn test_coverage_math() {
+        let (_dir, manager) = coverage_fixture();
+        let coverage = manager.coverage().unwrap();
+        
+        let de = coverage.iter().find(|c| c.language == "de").unwrap();
+        assert_eq!((de.total_keys, de.translated, de.missing), (4, 2, 2));
+        assert!((de.percent - 50.0).abs() < f64::EPSILON);
+        let en = coverage.iter().find(|c| c.language == "en").unwrap();
+        assert_eq!((en.translated, en.missing), (4, 0));
+        assert!((en.percent - 100.0).abs() < f64::EPSILON);
+    }
+    
+    #[test]
+    fn test_export_missing_skeleton() {
+        let (dir, manager) = coverage_fixture();
+        let path = dir.join("export").join("de.json");
+        assert_eq!(manager.export_missing("de", &path).unwrap(), 2);
+        
+        let skeleton: LocaleFile = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(skeleton.language.unwrap().code, "de");
+        let mut keys: Vec<&String> = skeleton.translations.keys().collect();
+        keys.sort();
+        assert_eq!(keys, vec!["b", "c"]);
+        
+        // An unfilled skeleton in an override directory changes nothing
+        let with_skeleton = I18nManager::with_locale_dirs(vec![dir.to_path_buf(), dir.join("export")]);
+        let de = with_skeleton.coverage().unwrap().into_iter().find(|c| c.language == "de").unwrap();
+        assert_eq!(de.translated, 2);
+    }
+    
+    #[test]
+    fn test_rtl_follows_language() {
+        let manager = bundled();
+        assert!(!manager.is_rtl().unwrap());
+        
+        manager.set_language("ar".to_string()).unwrap();
+        assert!(manager.is_rtl().unwrap());
+        
+        manager.set_language("en".to_string()).unwrap();
+        assert!(!manager.is_rtl().unwrap());
+    }
+    
+    #[test]
+    fn test_current_language_info() {
+        let manager = bundled();
+        manager.set_language("ar".to_string()).unwrap();
+        
+        let info = manager.get_current_language_info().unwrap();
+        assert_eq!(info.code, "ar");
+        assert_eq!(info.name, "Arabic");
+        assert!(info.rtl);
+    }
+    
+    #[test]
+    fn test_unsupported_language_is_rejected() {
+        let manager = bundled();
+        assert!(manager.set_language("xx".to_string()).is_err());
+        assert_eq!(manager.get_current_language().unwrap(), "en");
+    }
+}
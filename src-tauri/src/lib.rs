@@ -12,19 +12,62 @@ pub mod backup;
 pub mod i18n;
 pub mod optimization;
 pub mod advanced;
+pub mod deposits;
+pub mod wallet;
+pub mod headless;
+pub mod daemon;
 
 use log::info;
+use crate::crypto::address::{self, resolve_payment_id};
 use crate::crypto::ffi::CryptoNoteFFI;
-use crate::crypto::real_cryptonote::{RealCryptoNoteWallet, connect_to_fuego_network, fetch_fuego_network_data};
-use crate::security::{SecurityManager, SecurityConfig, PasswordValidator, WalletEncryption};
-use crate::performance::{PerformanceMonitor, PerformanceConfig, Cache, BackgroundTaskManager};
+use crate::crypto::backend::{new_backend, select_backend, selected_backend, smoke_test};
+use crate::crypto::daemon::TARGET_BLOCK_TIME;
+use crate::crypto::demo;
+use crate::crypto::vanity::{VanityCandidate, VanityJob, VanityPattern, VanityProgress, VanitySearch, VanityStatus, VANITY_PROGRESS_EVENT, VANITY_PROGRESS_INTERVAL};
+use crate::crypto::{DaemonClient, DaemonInfo};
+use crate::crypto::real_cryptonote::{RealCryptoNoteWallet, CreatedWallet, ConnectionType, DepositLimits, EmissionInfo, FeeBreakdown, FeeEstimate, TransactionPreview, WalletIntegrityReport, TransferDestination, PoolTransfer, connect_to_fuego_network, connect_to_fuego_network_until, fetch_fuego_network_data, check_transaction_size, estimate_transaction_size, is_valid_address_format, known_nodes, report_save_failures_to, set_auto_save, set_connect_retry, set_default_connect_timeout, default_connect_timeout, cancel_connect_retries, connect_retry_state};
+use crate::security::{LockoutStatus, SecurityManager, SecurityConfig, PasswordValidator, WalletEncryption};
+use crate::performance::{cache_capacity_for_mb, PerformanceMonitor, PerformanceConfig, Cache, BackgroundTaskManager, PerformanceWindow, Regression, TaskStatus};
 use crate::settings::{SettingsManager};
-use crate::backup::{BackupManager};
-use crate::i18n::{I18nManager, LanguageInfo};
+use crate::backup::{BackupManager, BackupData, BackupInfo, BackupMetadata, BackupType, BackupVerification, BackupError, BackupOutcome, RestoreSummary};
+use crate::i18n::{I18nManager, LanguageInfo, TranslationCoverage};
 use crate::optimization::{ResourceMonitor, MemoryOptimization, CPUOptimization, AdvancedCache, ThreadPool, PerformanceProfiler};
-use crate::advanced::{AdvancedWalletManager, AdvancedUIManager, EnhancedWalletInfo, AdvancedTransactionInfo};
+use crate::advanced::balance_history::BALANCE_HISTORY_TASK;
+use crate::advanced::watch::WATCHED_ADDRESS_TASK;
+use crate::advanced::price_alerts::{fetch_quotes, price_alerts_path, FiredAlert, PRICE_ALERT_TASK};
+use crate::advanced::state::{notifications_path, PersistedWalletState, STATE_SAVE_TASK};
+use crate::advanced::notifications::dispatch_action;
+use crate::advanced::themes::{builtin_themes, custom_themes_path, load_custom_themes, resolve_custom_theme, save_custom_themes, select_theme, sync_theme_with_settings, DEFAULT_THEME};
+use crate::advanced::{OperationEntry, WatchedAddress, WatchedAddressDeposit, WatchedAddresses, DashboardSources, DashboardSummary, BalanceHistoryRange, BalanceHistoryStore, BalanceResolution, BalanceSnapshot, UITheme, NavigationHint, NotificationOperation, AdvancedWalletManager, ExplorerClient, TransactionFilter, TransactionSearchResult, FeeAnalytics, FeePriority, TransactionMetadata, TransactionMetadataStore, AdvancedUIManager, EnhancedWalletInfo, AdvancedTransactionInfo, ContactResolver, AlertDirection, PriceAlert, PriceAlerts, UINotification, NotificationAction, NotificationEvent, AddressInfo};
+use crate::deposits::{check_deposit, DepositMaturityWatcher, DepositRenewalStore, DepositSummary, HoldingsBreakdown, RenewalOption, RenewalReport, DEPOSIT_MATURITY_TASK};
+use crate::wallet::async_wallet::{self, OperationClass};
+use crate::wallet::addressbook::{export_address_book, plan_import, read_address_book, AddressBookFormat, MergeStrategy};
+use crate::wallet::coin_control::FrozenOutputs;
+use crate::wallet::mempool::{MEMPOOL_TASK, PENDING_INCOMING_EVENT};
+use crate::wallet::payment_requests::{PAYMENT_REQUEST_EXPIRY_TASK, PAYMENT_REQUEST_UPDATED_EVENT};
+use crate::wallet::balance::{effective_min_confirmations, load_spendable_balance, TX_SPENDABLE_AGE};
+use crate::wallet::payment_uri::{format_xfg, ValidatedPaymentUri};
+use crate::wallet::key_backup::{BackupKeys, KeyBackup};
+use crate::wallet::paper::PaperWallet;
+use crate::wallet::registry::REGISTRY_SYNC_TASK;
+use crate::wallet::sync::{DEFAULT_SYNC_STALL_GRACE, SYNC_STALLED_EVENT, WALLET_UPDATED_EVENT};
+use crate::wallet::wipe::WIPE_CONFIRMATION;
+use crate::wallet::watchdog::{NETWORK_CONNECTED_EVENT, NETWORK_DISCONNECTED_EVENT, RECONNECT_NOTIFY_AFTER};
+use crate::wallet::{BalanceBreakdown, OutputEntry, PaymentUri, MempoolWatcher, PaymentRequest, PaymentRequestStatus, PaymentRequests, DriftAlert, SyncLoopControl, AddressBookImportReport, LockReason, WalletLock, PendingTx, PendingTxStatus, PendingTxTracker, RefreshCoordinator, WalletMetadata, OpenWallet, RegisteredWallet, WalletRegistry, WalletSyncState, ConnectionWatchdog, WatchdogEvent, WipeManifest, WipePlan};
+use crate::wallet::pending::Confirmation;
+use crate::utils::error::{ApiError, ErrorCode, WalletError};
+use crate::utils::crash::{self, CrashReport};
+use crate::headless::{HeadlessOptions, RpcError, RpcHandler};
+use crate::daemon::{DaemonLaunch, DaemonState, DaemonStatus, DaemonSupervisor};
+use crate::crypto::peers::PeerList;
+use crate::utils::logging;
+use crate::utils::progress::ProgressReporter;
+use crate::utils::retry::{record_retries_in, retry_async, RetryPolicy};
+use crate::utils::storage::config_path;
+use tauri::{AppHandle, Emitter, Manager};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 // Global state for security, performance, settings, backup, i18n, optimization, and advanced features
 static SECURITY_MANAGER: std::sync::OnceLock<Arc<SecurityManager>> = std::sync::OnceLock::new();
@@ -40,16 +83,98 @@ static THREAD_POOL: std::sync::OnceLock<Arc<ThreadPool>> = std::sync::OnceLock::
 static PERFORMANCE_PROFILER: std::sync::OnceLock<Arc<PerformanceProfiler>> = std::sync::OnceLock::new();
 static ADVANCED_WALLET_MANAGER: std::sync::OnceLock<Arc<AdvancedWalletManager>> = std::sync::OnceLock::new();
 static ADVANCED_UI_MANAGER: std::sync::OnceLock<Arc<AdvancedUIManager>> = std::sync::OnceLock::new();
+static DEPOSIT_WATCHER: std::sync::OnceLock<Arc<DepositMaturityWatcher>> = std::sync::OnceLock::new();
+static DEPOSIT_RENEWALS: std::sync::OnceLock<Arc<DepositRenewalStore>> = std::sync::OnceLock::new();
+static REFRESH_COORDINATOR: std::sync::OnceLock<Arc<RefreshCoordinator>> = std::sync::OnceLock::new();
+static WALLET_REGISTRY: std::sync::OnceLock<Arc<WalletRegistry>> = std::sync::OnceLock::new();
+static PENDING_TX_TRACKER: std::sync::OnceLock<Arc<PendingTxTracker>> = std::sync::OnceLock::new();
+static WALLET_LOCK: std::sync::OnceLock<Arc<WalletLock>> = std::sync::OnceLock::new();
+static DAEMON_CLIENT: std::sync::OnceLock<Arc<DaemonClient>> = std::sync::OnceLock::new();
+static TX_METADATA_STORE: std::sync::OnceLock<Arc<TransactionMetadataStore>> = std::sync::OnceLock::new();
+static BALANCE_HISTORY: std::sync::OnceLock<Arc<BalanceHistoryStore>> = std::sync::OnceLock::new();
+static WATCHED_ADDRESSES: std::sync::OnceLock<Arc<WatchedAddresses>> = std::sync::OnceLock::new();
+static SYNC_LOOP: std::sync::OnceLock<Arc<SyncLoopControl>> = std::sync::OnceLock::new();
+static MEMPOOL_WATCHER: std::sync::OnceLock<Arc<MempoolWatcher>> = std::sync::OnceLock::new();
+static PAYMENT_REQUESTS: std::sync::OnceLock<Arc<PaymentRequests>> = std::sync::OnceLock::new();
+static FROZEN_OUTPUTS: std::sync::OnceLock<Arc<FrozenOutputs>> = std::sync::OnceLock::new();
+static LOCAL_DAEMON: std::sync::OnceLock<Arc<DaemonSupervisor>> = std::sync::OnceLock::new();
+static PRICE_ALERTS: std::sync::OnceLock<Arc<PriceAlerts>> = std::sync::OnceLock::new();
+static VANITY_SEARCH: std::sync::OnceLock<Arc<VanitySearch>> = std::sync::OnceLock::new();
+
+/// Wallet file used by the desktop commands
+const DEFAULT_WALLET_FILE: &str = "/tmp/fuego_wallet.wallet";
+
+/// Background task reconciling sent transactions with the chain
+const PENDING_TX_TASK: &str = "pending_tx_reconcile";
+
+/// Background task locking the wallet after the configured idle time
+const WALLET_AUTO_LOCK_TASK: &str = "wallet_auto_lock";
+
+/// Wallet info younger than this is served from the cache without refreshing
+const WALLET_INFO_TTL: Duration = Duration::from_secs(2);
+
+/// Wallet info older than this is not served at all
+const WALLET_INFO_MAX_AGE: Duration = Duration::from_secs(30);
+
+/// Set while a background wallet info refresh is running
+static WALLET_INFO_REFRESHING: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// Clears `WALLET_INFO_REFRESHING` when dropped, so a refresh that panics
+/// does not block every later one
+struct WalletInfoRefreshGuard;
+
+impl Drop for WalletInfoRefreshGuard {
+    fn drop(&mut self) {
+        WALLET_INFO_REFRESHING.store(false, std::sync::atomic::Ordering::SeqCst);
+    }
+}
+
+/// Run a command body, recording its duration and outcome under `name` in
+/// the performance metrics
+async fn timed<T>(name: &str, body: impl Future<Output = Result<T, ApiError>>) -> Result<T, ApiError> {
+    match PERFORMANCE_MONITOR.get() {
+        Some(monitor) => monitor.time(name, body).await,
+        None => body.await,
+    }
+}
 
+/// Time a command body with `timed`. The body runs as an async block, so `?`
+/// and `return` end the body rather than the command and the timer always
+/// sees the result; errors are passed on unchanged.
+macro_rules! timed_command {
+    ($name:literal, $body:block) => {
+        timed($name, async move $body).await
+    };
+}
 
 /// Initialize the Tauri application
 pub fn run() {
-    env_logger::init();
+    logging::init(logging::logs_dir());
+    if let Some(dir) = crash::crash_reports_dir() {
+        crash::install_panic_hook(dir, logging::logs_dir());
+    }
     info!("Starting Fuego Desktop Wallet");
 
+    let headless = match HeadlessOptions::from_args(std::env::args().skip(1)) {
+        Ok(headless) => headless,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+
+    if demo::requested(std::env::args().skip(1), std::env::var(demo::DEMO_ENV).ok().as_deref()) {
+        demo::enable(Instant::now());
+    }
+
     // Initialize global state
     initialize_global_state();
 
+    if let Some(options) = headless {
+        run_headless(options);
+        return;
+    }
+
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_fs::init())
@@ -57,19 +182,104 @@ pub fn run() {
         .plugin(tauri_plugin_store::Builder::default().build())
         .invoke_handler(tauri::generate_handler![
             get_wallet_info,
+            get_balance_breakdown,
             get_transactions,
             get_network_status,
             // Phase 1.3 additions
             get_enhanced_wallet_info,
+            get_wallet_metadata,
             get_advanced_transactions,
+            transactions_search,
+            transactions_by_contact,
+            get_pending_transactions,
+            tx_rebroadcast,
+            tx_generate_proof,
+            tx_verify_proof,
+            transaction_set_label,
+            transaction_set_note,
+            transaction_set_category,
+            transaction_set_priority,
+            transaction_add_tag,
+            transaction_remove_tag,
             get_app_settings,
             get_available_app_languages,
+            set_app_language,
+            get_language_direction,
+            get_current_language_info,
+            reload_translations,
+            wallet_get_addresses,
+            wallet_set_address_label,
+            i18n_translate,
+            i18n_detect_system_locale,
+            i18n_missing_keys,
+            i18n_coverage,
+            i18n_export_missing,
+            i18n_translate_plural,
+            i18n_set_language,
+            i18n_get_language,
+            i18n_format_number,
+            i18n_format_date,
             get_notifications,
+            notifications_mark_read,
+            notifications_dismiss,
+            // Deprecated names of notifications_mark_read and notifications_dismiss
+            mark_notification_read,
+            dismiss_notification,
+            notifications_unread_count,
+            theme_list,
+            theme_get,
+            theme_set,
+            theme_reset_to_default,
+            theme_create_custom,
+            notifications_clear_all,
+            notification_invoke_action,
             test_ffi_integration,
             test_real_cryptonote,
             get_fuego_network_data,
+            get_daemon_info,
+            network_get_peers,
+            network_ban_peer,
+            network_unban_peer,
+            get_emission_info,
+            get_deposit_limits,
+            deposit_set_autorenew,
+            wallet_verify_integrity,
             send_transaction,
+            send_many,
+            authorize_large_send,
+            wallet_lock,
+            wallet_unlock,
+            wallet_is_locked,
+            wallet_record_activity,
+            sweep_all,
+            outputs_list,
+            wallet_list_unspent,
+            wallet_freeze_output,
+            wallet_unfreeze_output,
+            wallet_save,
+            // Local daemon commands
+            daemon_start,
+            daemon_stop,
+            daemon_status,
+            daemon_log_tail,
+            wipe_local_data,
+            sweep_below,
             get_term_deposits,
+            get_total_holdings,
+            deposit_summary,
+            fee_analytics,
+            balance_history,
+            price_alert_create,
+            price_alert_list,
+            price_alert_delete,
+            get_dashboard_summary,
+            watch_address_add,
+            watch_address_remove,
+            watch_address_list,
+            create_payment_request,
+            list_payment_requests,
+            get_payment_request,
+            cancel_payment_request,
             create_term_deposit,
             withdraw_term_deposit,
             // fuego-wallet compatibility aliases
@@ -81,30 +291,54 @@ pub fn run() {
             wallet_get_transactions,
             wallet_send_transaction,
             wallet_close,
+            wallet_open_named,
+            wallet_close_named,
+            wallet_list_open,
+            wallet_set_active,
+            wallet_sync_named,
             wallet_refresh,
             wallet_rescan,
+            operations_list,
+            operations_export,
             network_get_status,
             node_connect,
+            node_test,
             node_disconnect,
             deposit_list,
             deposit_create,
             deposit_withdraw,
             estimate_fee,
+            estimate_fee_detailed,
+            send_transaction_preview,
+            estimate_sweep_fee,
             validate_address,
+            parse_and_validate_payment_uri,
+            split_integrated_address,
             // Security commands
             authenticate_user,
             validate_session,
             lock_session,
             unlock_session,
             logout_user,
+            security_get_lockout_status,
+            security_clear_lockout,
             validate_password_strength,
             encrypt_wallet_data,
             decrypt_wallet_data,
             // Performance commands
             get_performance_metrics,
+            get_performance_window,
+            get_performance_regressions,
             get_cache_stats,
+            reconfigure_caches,
+            set_log_level,
+            logs_tail,
+            logs_export,
+            crash_reports_list,
+            crash_report_delete,
             clear_cache,
             get_background_task_status,
+            list_background_tasks,
             enable_background_task,
             disable_background_task,
             // Advanced wallet commands
@@ -131,25 +365,381 @@ pub fn run() {
             get_address_book,
             mark_address_used,
             get_address_book_entry,
+            address_book_export,
+            address_book_import,
+            // Deprecated names of address_book_export and address_book_import
+            addressbook_export,
+            addressbook_import,
             // Enhanced mining commands
             get_mining_stats_json,
             // Secure key management commands
             generate_seed_phrase,
             validate_seed_phrase,
+            restore_preview,
+            vanity_generate,
+            vanity_cancel,
+            vanity_reveal,
             derive_keys_from_seed,
             get_seed_phrase,
+            export_paper_wallet,
+            import_paper_wallet,
             get_view_key,
             get_spend_key,
             has_keys,
             export_keys,
             import_keys,
+            export_key_backup,
+            import_key_backup,
+            // Backup commands
+            backup_create,
+            backup_cancel,
+            backup_restore,
+            backup_verify,
+            backup_import,
+            backup_rebuild_index,
         ])
-        .setup(|_app| {
+        .on_window_event(|_window, event| {
+            if let tauri::WindowEvent::CloseRequested { .. } = event {
+                save_advanced_state();
+            }
+        })
+        .setup(|app| {
+            init_translations(app.path().resource_dir().ok().map(|dir| dir.join("locales")))?;
+
+            if let Some(ui) = ADVANCED_UI_MANAGER.get() {
+                forward_notifications(app.handle().clone(), ui.subscribe_notifications());
+                notify_crash_reports(ui);
+
+                if let Some(path) = custom_themes_path() {
+                    match load_custom_themes(&path) {
+                        Ok(themes) => themes.into_iter().for_each(|theme| ui.add_theme(theme)),
+                        Err(e) => log::warn!("Failed to load custom themes: {}", e),
+                    }
+                }
+                if let Some(settings) = SETTINGS_MANAGER.get() {
+                    match sync_theme_with_settings(ui, settings) {
+                        Ok(theme) => info!("Using theme: {}", theme.name),
+                        Err(e) => log::warn!("Failed to apply saved theme: {}", e),
+                    }
+                }
+            }
+
+            if let Some(settings) = SETTINGS_MANAGER.get() {
+                forward_settings_changes(settings.subscribe());
+            }
+
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(run_background_tasks(handle));
+            tauri::async_runtime::spawn(run_sync_loop(app.handle().clone()));
+            tauri::async_runtime::spawn(run_connection_watchdog(app.handle().clone()));
             info!("Fuego Desktop Wallet initialized successfully");
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|_app, event| {
+            if let tauri::RunEvent::Exit = event {
+                stop_local_daemon();
+            }
+        });
+}
+
+/// Load the translations from `bundled_dir` and the user's override
+/// directory, then apply the saved language
+fn init_translations(bundled_dir: Option<std::path::PathBuf>) -> Result<(), String> {
+    let Some(mgr) = I18N_MANAGER.get() else {
+        return Ok(());
+    };
+    let locale_dirs = bundled_dir.into_iter().chain(I18nManager::override_dir()).collect();
+    mgr.set_locale_dirs(locale_dirs)?;
+    match mgr.reload_translations() {
+        Ok(codes) => info!("Loaded translations for {} languages", codes.len()),
+        Err(e) => log::error!("Failed to load translations: {}", e),
+    }
+
+    if let Some(settings) = SETTINGS_MANAGER.get() {
+        let language = mgr.apply_startup_language(settings);
+        info!("Using language: {}", language);
+    }
+    Ok(())
+}
+
+/// Serve the commands over JSON-RPC instead of opening a window, until SIGINT.
+/// Events and the background tasks need the window and do not run.
+fn run_headless(options: HeadlessOptions) {
+    // Without a bundle the translations are looked for next to the executable
+    let bundled_dir = std::env::current_exe().ok().and_then(|exe| exe.parent().map(|dir| dir.join("locales")));
+    if let Err(e) = init_translations(bundled_dir) {
+        log::error!("Failed to set up translations: {}", e);
+    }
+    if let Some(settings) = SETTINGS_MANAGER.get() {
+        forward_settings_changes(settings.subscribe());
+    }
+
+    tauri::async_runtime::block_on(async move {
+        let listener = match tokio::net::TcpListener::bind(options.bind).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                log::error!("Failed to bind RPC server to {}: {}", options.bind, e);
+                eprintln!("Failed to bind RPC server to {}: {}", options.bind, e);
+                std::process::exit(1);
+            }
+        };
+        let addr = listener.local_addr().unwrap_or(options.bind);
+        if !addr.ip().is_loopback() {
+            log::warn!("RPC server on {} is reachable from other machines", addr);
+        }
+        info!("Fuego wallet RPC server listening on {}", addr);
+        println!("RPC listening on http://{}", addr);
+        println!("RPC auth token: {}", options.auth_token);
+
+        headless::serve(listener, options.auth_token, rpc_handler(), async {
+            if let Err(e) = tokio::signal::ctrl_c().await {
+                log::error!("Failed to listen for SIGINT: {}", e);
+                std::future::pending::<()>().await;
+            }
+        })
+        .await;
+    });
+
+    save_advanced_state();
+    stop_local_daemon();
+    info!("Fuego wallet RPC server stopped");
+}
+
+/// Expand to the call of the command behind an RPC method: the command itself,
+/// or the given expression for commands that need the app handle
+macro_rules! rpc_call {
+    ($name:ident($($arg:ident),*)) => { $name($($arg),*) };
+    ($name:ident($($arg:ident),*), $call:expr) => { $call };
+}
+
+/// Match `method` against the listed commands, reading each argument from
+/// `params` by name, and serialize the command's result
+macro_rules! rpc_methods {
+    ($method:expr, $params:expr; $($name:ident($($arg:ident: $ty:ty),*) $(=> $call:expr)?),* $(,)?) => {
+        match $method {
+            $(stringify!($name) => {
+                $(let $arg: $ty = headless::param($params, stringify!($arg))?;)*
+                let result = rpc_call!($name($($arg),*) $(, $call)?).await?;
+                Ok(serde_json::to_value(result).map_err(ApiError::from)?)
+            })*
+            other => Err(RpcError::MethodNotFound(other.to_string())),
+        }
+    };
+}
+
+/// Run the command `method` for the headless RPC server. Commands only the
+/// window has a use for (language, theme and notification actions) are left out.
+async fn rpc_dispatch(method: String, params: serde_json::Value) -> Result<serde_json::Value, RpcError> {
+    let params = &params;
+    rpc_methods!(method.as_str(), params;
+            get_wallet_info(force_refresh: Option<bool>),
+            get_balance_breakdown(),
+            get_transactions(limit: Option<u64>, offset: Option<u64>),
+            get_network_status(),
+            get_enhanced_wallet_info(),
+            get_wallet_metadata(),
+            get_advanced_transactions(),
+            transactions_search(filter: Option<TransactionFilter>),
+            transactions_by_contact(contact_id: String, filter: Option<TransactionFilter>),
+            get_pending_transactions(),
+            tx_rebroadcast(tx_id: String),
+            tx_generate_proof(tx_hash: String, recipient: String, message: Option<String>),
+            tx_verify_proof(tx_hash: String, recipient: String, proof: String, message: Option<String>),
+            transaction_set_label(tx_hash: String, label: Option<String>),
+            transaction_set_note(tx_hash: String, note: Option<String>),
+            transaction_set_category(tx_hash: String, category: Option<String>),
+            transaction_set_priority(tx_hash: String, priority: Option<FeePriority>),
+            transaction_add_tag(tx_hash: String, tag: String),
+            transaction_remove_tag(tx_hash: String, tag: String),
+            get_app_settings(),
+            get_available_app_languages(),
+            get_language_direction(),
+            get_current_language_info(),
+            reload_translations(),
+            wallet_get_addresses(),
+            wallet_set_address_label(address: String, label: String),
+            i18n_translate(key: String, params: Option<std::collections::HashMap<String, String>>),
+            i18n_missing_keys(language: String),
+            i18n_coverage(),
+            i18n_export_missing(language: String, path: String),
+            i18n_translate_plural(key: String, count: u64, params: Option<std::collections::HashMap<String, String>>),
+            i18n_get_language(),
+            i18n_format_number(value: f64, decimals: Option<usize>),
+            i18n_format_date(timestamp: u64),
+            get_notifications(),
+            notifications_mark_read(id: String),
+            notifications_dismiss(id: String),
+            // Deprecated names of notifications_mark_read and notifications_dismiss
+            mark_notification_read(id: String),
+            dismiss_notification(id: String),
+            notifications_unread_count(),
+            theme_list(),
+            theme_get(name: String),
+            theme_create_custom(theme: UITheme),
+            notifications_clear_all(),
+            test_ffi_integration(),
+            test_real_cryptonote(),
+            get_fuego_network_data(),
+            get_daemon_info(),
+            network_get_peers(),
+            network_ban_peer(address: String, seconds: u64),
+            network_unban_peer(address: String),
+            get_emission_info(),
+            get_deposit_limits(),
+            deposit_set_autorenew(deposit_id: String, enabled: bool, term: Option<u32>),
+            wallet_verify_integrity(file_path: String, password: String),
+            send_transaction(recipient: String, amount: u64, payment_id: Option<String>, mixin: u64, override_token: Option<String>, selected_outputs: Option<Vec<String>>),
+            send_many(outputs: Vec<TransferDestination>, mixin: Option<u64>, override_token: Option<String>),
+            authorize_large_send(session_id: String, password: String),
+            wallet_is_locked(),
+            wallet_record_activity(),
+            sweep_all(destination: String, mixin: Option<u64>, override_token: Option<String>),
+            outputs_list(),
+            wallet_list_unspent(),
+            wallet_freeze_output(key_image: String),
+            wallet_unfreeze_output(key_image: String),
+            wallet_save(),
+            daemon_start(),
+            daemon_stop(),
+            daemon_status(),
+            daemon_log_tail(lines: u32),
+            wipe_local_data(confirm: String, include_wallet: Option<bool>),
+            sweep_below(threshold: u64, mixin: Option<u64>, override_token: Option<String>),
+            get_term_deposits(),
+            get_total_holdings(),
+            deposit_summary(),
+            fee_analytics(),
+            balance_history(range: BalanceHistoryRange, resolution: Option<BalanceResolution>),
+            price_alert_create(direction: AlertDirection, threshold: f64, currency: String, repeat_after: Option<u64>),
+            price_alert_list(),
+            price_alert_delete(id: String),
+            get_dashboard_summary(),
+            watch_address_add(address: String, label: Option<String>),
+            watch_address_remove(address: String),
+            watch_address_list(),
+            create_payment_request(amount: u64, description: String, expiry: Option<u64>),
+            list_payment_requests(),
+            get_payment_request(id: String),
+            cancel_payment_request(id: String),
+            create_term_deposit(amount: u64, term: u32),
+            withdraw_term_deposit(deposit_id: String),
+            wallet_create(password: String, file_path: String, seed_phrase: Option<String>, restore_height: Option<u64>, overwrite: Option<bool>),
+            wallet_open(file_path: String, password: String),
+            wallet_get_info(force_refresh: Option<bool>),
+            wallet_get_balance(wallet_id: Option<String>),
+            wallet_get_address(wallet_id: Option<String>),
+            wallet_get_transactions(limit: Option<u64>, offset: Option<u64>),
+            wallet_send_transaction(recipient: String, amount: u64, payment_id: Option<String>, mixin: Option<u64>, override_token: Option<String>, selected_outputs: Option<Vec<String>>),
+            wallet_close(),
+            wallet_open_named(file_path: String, password: String),
+            wallet_close_named(wallet_id: String),
+            wallet_list_open(),
+            wallet_set_active(wallet_id: String),
+            wallet_sync_named(wallet_id: String),
+            operations_list(limit: Option<usize>, status_filter: Option<String>),
+            operations_export(path: String),
+            network_get_status(),
+            node_connect(address: Option<String>, port: Option<u16>),
+            node_test(host: String, port: u16),
+            node_disconnect(),
+            deposit_list(),
+            deposit_create(amount: u64, term: u32),
+            deposit_withdraw(deposit_id: String),
+            estimate_fee(address: String, amount: u64, mixin: Option<u64>),
+            estimate_fee_detailed(address: String, amount: u64, mixin: Option<u64>),
+            send_transaction_preview(recipient: String, amount: u64, mixin: u64, selected_outputs: Option<Vec<String>>),
+            estimate_sweep_fee(destination: String, mixin: Option<u64>),
+            validate_address(address: String),
+            parse_and_validate_payment_uri(uri: String),
+            split_integrated_address(address: String),
+            authenticate_user(user_id: String, password: String),
+            validate_session(session_id: String),
+            lock_session(session_id: String),
+            unlock_session(session_id: String, password: String),
+            logout_user(session_id: String),
+            security_get_lockout_status(user_id: String),
+            security_clear_lockout(session_id: String, user_id: String),
+            validate_password_strength(password: String),
+            encrypt_wallet_data(data: String, password: String),
+            decrypt_wallet_data(encrypted_data: String, password: String),
+            get_performance_metrics(operation_name: Option<String>),
+            get_performance_window(operation: Option<String>, since_seconds: u64),
+            get_performance_regressions(baseline_window: u64, recent_window: u64),
+            get_cache_stats(),
+            reconfigure_caches(cache_size_mb: Option<u32>),
+            set_log_level(level: String),
+            logs_tail(lines: u32),
+            logs_export(path: String),
+            crash_reports_list(),
+            crash_report_delete(id: String),
+            clear_cache(),
+            get_background_task_status(task_name: String),
+            list_background_tasks(),
+            enable_background_task(task_name: String),
+            disable_background_task(task_name: String),
+            get_wallet_info_advanced(),
+            get_network_info_advanced(),
+            get_transaction_by_hash(tx_hash: String),
+            create_address(label: Option<String>),
+            get_block_info(height: u64),
+            start_mining(threads: u32, background: bool, daemon_address: Option<String>, pool_wallet: Option<String>, pool_password: Option<String>),
+            stop_mining(),
+            get_mining_info(),
+            get_transaction_history(limit: Option<u64>, offset: Option<u64>),
+            get_sync_progress(),
+            get_sync_status_json(),
+            set_mining_pool(pool_address: Option<String>, worker_name: Option<String>),
+            wallet_start_mining(threads: u32, background: bool, daemon_address: Option<String>, pool_wallet: Option<String>, pool_password: Option<String>),
+            wallet_stop_mining(),
+            wallet_set_mining_pool(pool_address: Option<String>, worker_name: Option<String>),
+            get_mining_status(),
+            add_address_book_entry(address: String, label: Option<String>, description: Option<String>),
+            remove_address_book_entry(address: String),
+            update_address_book_entry(address: String, label: Option<String>, description: Option<String>),
+            get_address_book(),
+            mark_address_used(address: String),
+            get_address_book_entry(address: String),
+            address_book_export(path: String, format: Option<AddressBookFormat>),
+            address_book_import(path: String, format: Option<AddressBookFormat>, merge_strategy: Option<MergeStrategy>),
+            addressbook_export(path: String),
+            addressbook_import(path: String, merge: bool),
+            get_mining_stats_json(),
+            generate_seed_phrase(),
+            validate_seed_phrase(seed_phrase: String),
+            restore_preview(seed_phrase: String),
+            vanity_generate(pattern: String, case_sensitive: Option<bool>, max_threads: Option<usize>) => start_vanity_search(None, pattern, case_sensitive, max_threads),
+            vanity_cancel(),
+            vanity_reveal(password: String),
+            derive_keys_from_seed(seed_phrase: String, password: String),
+            get_seed_phrase(password: String),
+            export_paper_wallet(password: String, html_path: Option<String>),
+            import_paper_wallet(blob: String, password: String),
+            get_view_key(),
+            get_spend_key(),
+            has_keys(),
+            export_keys(),
+            import_keys(view_key: String, spend_key: String, address: String),
+            export_key_backup(path: String, password: String),
+            import_key_backup(path: String, password: String),
+            backup_cancel(),
+            backup_restore(backup_id: String),
+            backup_verify(backup_id: String),
+            backup_import(path: String, password: Option<String>),
+            backup_rebuild_index(),
+            // Commands that take the app handle, run without events
+            wallet_refresh() => refresh_wallet(None),
+            wallet_rescan(start_height: Option<u64>) => rescan_wallet(None, start_height),
+            wallet_lock() => lock_wallet(None),
+            wallet_unlock(password: String) => unlock_wallet(None, password),
+            backup_create(name: String, description: Option<String>, backup_type: BackupType) => create_backup(None, name, description, backup_type),
+    )
+}
+
+/// `rpc_dispatch` as the handler of the headless RPC server
+pub(crate) fn rpc_handler() -> RpcHandler {
+    Arc::new(|method, params| Box::pin(rpc_dispatch(method, params)))
 }
 
 /// Initialize global state for security, performance, settings, backup, and i18n
@@ -162,6 +752,14 @@ fn initialize_global_state() {
     // Initialize performance monitor
     let performance_config = PerformanceConfig::default();
     let performance_monitor = Arc::new(PerformanceMonitor::new(performance_config));
+    if let Some(path) = performance_metrics_path() {
+        match performance_monitor.load(&path) {
+            Ok(restored) => info!("Restored {} performance metrics", restored),
+            Err(e) => log::warn!("Failed to restore performance metrics: {}", e),
+        }
+    }
+    record_retries_in(performance_monitor.clone());
+    report_save_failures_to(notify_save_failure);
     PERFORMANCE_MONITOR.set(performance_monitor).unwrap();
 
     // Initialize cache
@@ -175,6 +773,18 @@ fn initialize_global_state() {
     // Initialize settings manager
     match SettingsManager::new() {
         Ok(settings_manager) => {
+            if let Ok(settings) = settings_manager.get_settings() {
+                if let Err(e) = logging::set_level(&settings.performance.log_level) {
+                    log::warn!("{}", e);
+                }
+                if settings.wallet.demo_mode {
+                    demo::enable(Instant::now());
+                }
+                select_backend(demo::backend_for(settings.wallet.backend));
+                set_auto_save(settings.wallet.auto_save);
+                set_connect_retry(settings.network.connect_retry);
+                set_default_connect_timeout(Duration::from_secs(u64::from(settings.network.connection_timeout)));
+            }
             SETTINGS_MANAGER.set(Arc::new(settings_manager)).unwrap();
             info!("Settings manager initialized successfully");
         }
@@ -222,602 +832,1182 @@ fn initialize_global_state() {
     
     let optimization_cache = Arc::new(AdvancedCache::new(1000));
     OPTIMIZATION_CACHE.set(optimization_cache).unwrap();
+    if let Some(settings) = SETTINGS_MANAGER.get().and_then(|s| s.get_settings().ok()) {
+        configure_caches(settings.performance.cache_size_mb);
+    }
     
     let thread_pool = Arc::new(ThreadPool::new(8));
     THREAD_POOL.set(thread_pool).unwrap();
+    VANITY_SEARCH.set(Arc::new(VanitySearch::new())).unwrap();
     
     let performance_profiler = Arc::new(PerformanceProfiler::new());
     PERFORMANCE_PROFILER.set(performance_profiler).unwrap();
 
     // Initialize advanced components
     let advanced_wallet_manager = Arc::new(AdvancedWalletManager::new());
+    match advanced_wallet_manager.load_from(&PersistedWalletState::sidecar_path(Path::new(DEFAULT_WALLET_FILE))) {
+        Ok(true) => info!("Restored advanced wallet state"),
+        Ok(false) => {}
+        Err(e) => log::warn!("Failed to restore advanced wallet state: {}", e),
+    }
     ADVANCED_WALLET_MANAGER.set(advanced_wallet_manager).unwrap();
     
     let advanced_ui_manager = Arc::new(AdvancedUIManager::new());
+    for theme in builtin_themes() {
+        advanced_ui_manager.add_theme(theme);
+    }
+    if let Err(e) = advanced_ui_manager.load_notifications_from(&notifications_path(Path::new(DEFAULT_WALLET_FILE))) {
+        log::warn!("Failed to restore notifications: {}", e);
+    }
     ADVANCED_UI_MANAGER.set(advanced_ui_manager).unwrap();
 
-    info!("Global state initialized successfully");
-}
-
-/// Get wallet information (using real CryptoNote)
-#[tauri::command]
-async fn get_wallet_info() -> Result<serde_json::Value, String> {
-    let mut real_wallet = RealCryptoNoteWallet::new();
-    
-    // Try to open existing wallet first, then create if needed
-    let wallet_result = real_wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
-        .or_else(|_| real_wallet.create_wallet("fuego_password", "/tmp/fuego_wallet.wallet", None, 0));
-    
-    if let Err(e) = wallet_result {
-        return Err(format!("Failed to open/create wallet: {}", e));
+    // Initialize deposit maturity watcher
+    DEPOSIT_WATCHER.set(Arc::new(DepositMaturityWatcher::new())).unwrap();
+    REFRESH_COORDINATOR.set(Arc::new(RefreshCoordinator::new())).unwrap();
+    WALLET_REGISTRY.set(Arc::new(WalletRegistry::default())).unwrap();
+    DAEMON_CLIENT.set(Arc::new(DaemonClient::for_known_nodes())).unwrap();
+    LOCAL_DAEMON.set(Arc::new(DaemonSupervisor::new(logging::logs_dir()))).unwrap();
+    PENDING_TX_TRACKER.set(Arc::new(PendingTxTracker::new())).unwrap();
+    WALLET_LOCK.set(Arc::new(WalletLock::new())).unwrap();
+    let refresh_interval = SETTINGS_MANAGER.get()
+        .and_then(|mgr| mgr.get_settings().ok())
+        .map(|settings| settings.ui.refresh_interval)
+        .unwrap_or(5);
+    SYNC_LOOP.set(Arc::new(SyncLoopControl::new(
+        Duration::from_secs(u64::from(refresh_interval)),
+        Path::new(DEFAULT_WALLET_FILE).exists(),
+    ))).unwrap();
+    BACKGROUND_TASKS.get().unwrap().register_task(WALLET_AUTO_LOCK_TASK.to_string(), Duration::from_secs(30));
+    BACKGROUND_TASKS.get().unwrap().register_task(PENDING_TX_TASK.to_string(), Duration::from_secs(30));
+    MEMPOOL_WATCHER.set(Arc::new(MempoolWatcher::new())).unwrap();
+    BACKGROUND_TASKS.get().unwrap().register_task(MEMPOOL_TASK.to_string(), Duration::from_secs(20));
+    BACKGROUND_TASKS.get().unwrap().register_task(REGISTRY_SYNC_TASK.to_string(), Duration::from_secs(60));
+
+    // A corrupt metadata file is left alone rather than overwritten by an empty store
+    match TransactionMetadataStore::for_wallet(Path::new(DEFAULT_WALLET_FILE)) {
+        Ok(store) => {
+            TX_METADATA_STORE.set(Arc::new(store)).unwrap();
+        }
+        Err(e) => {
+            log::error!("Failed to load transaction metadata: {}", e);
+        }
     }
-    
-    // Connect to Fuego network
-    if let Err(e) = connect_to_fuego_network(&mut real_wallet) {
-        log::warn!("Failed to connect to Fuego network: {}", e);
-        // Continue without network connection
+    match BalanceHistoryStore::for_wallet(Path::new(DEFAULT_WALLET_FILE)) {
+        Ok(store) => {
+            BALANCE_HISTORY.set(Arc::new(store)).unwrap();
+        }
+        Err(e) => {
+            log::error!("Failed to load balance history: {}", e);
+        }
     }
-    
-    let balance = real_wallet.get_balance().map_err(|e| e.to_string())?;
-    let unlocked_balance = real_wallet.get_unlocked_balance().map_err(|e| e.to_string())?;
-    let address = real_wallet.get_address().map_err(|e| e.to_string())?;
-    
-    Ok(serde_json::json!({
-        "address": address,
-        "balance": balance,
-        "unlocked_balance": unlocked_balance,
-        "is_open": real_wallet.is_open(),
-        "is_encrypted": true,
-        "is_real": true
-    }))
-}
+    BACKGROUND_TASKS.get().unwrap().register_task(BALANCE_HISTORY_TASK.to_string(), Duration::from_secs(60 * 60));
+    match WatchedAddresses::for_wallet(Path::new(DEFAULT_WALLET_FILE)) {
+        Ok(store) => {
+            WATCHED_ADDRESSES.set(Arc::new(store)).unwrap();
+        }
+        Err(e) => {
+            log::error!("Failed to load watched addresses: {}", e);
+        }
+    }
+    BACKGROUND_TASKS.get().unwrap().register_task(WATCHED_ADDRESS_TASK.to_string(), Duration::from_secs(120));
+    match PaymentRequests::for_wallet(Path::new(DEFAULT_WALLET_FILE)) {
+        Ok(store) => {
+            PAYMENT_REQUESTS.set(Arc::new(store)).unwrap();
+        }
+        Err(e) => {
+            log::error!("Failed to load payment requests: {}", e);
+        }
+    }
+    BACKGROUND_TASKS.get().unwrap().register_task(PAYMENT_REQUEST_EXPIRY_TASK.to_string(), Duration::from_secs(60));
+    match price_alerts_path().map(PriceAlerts::open) {
+        Some(Ok(alerts)) => {
+            PRICE_ALERTS.set(Arc::new(alerts)).unwrap();
+        }
+        Some(Err(e)) => log::error!("Failed to load price alerts: {}", e),
+        None => log::warn!("No config directory, price alerts are unavailable"),
+    }
+    BACKGROUND_TASKS.get().unwrap().register_task(PRICE_ALERT_TASK.to_string(), Duration::from_secs(5 * 60));
+    match FrozenOutputs::for_wallet(Path::new(DEFAULT_WALLET_FILE)) {
+        Ok(store) => {
+            FROZEN_OUTPUTS.set(Arc::new(store)).unwrap();
+        }
+        Err(e) => {
+            log::error!("Failed to load frozen outputs: {}", e);
+        }
+    }
+    match DepositRenewalStore::for_wallet(Path::new(DEFAULT_WALLET_FILE)) {
+        Ok(store) => {
+            DEPOSIT_RENEWALS.set(Arc::new(store)).unwrap();
+        }
+        Err(e) => {
+            log::error!("Failed to load deposit renewals: {}", e);
+        }
+    }
+    BACKGROUND_TASKS.get().unwrap().register_task(DEPOSIT_MATURITY_TASK.to_string(), Duration::from_secs(60));
+    BACKGROUND_TASKS.get().unwrap().register_task(STATE_SAVE_TASK.to_string(), Duration::from_secs(300));
 
-/// Get transactions (real implementation)
-#[tauri::command]
-async fn get_transactions(limit: Option<u64>, offset: Option<u64>) -> Result<Vec<serde_json::Value>, String> {
-    let mut real_wallet = RealCryptoNoteWallet::new();
+    info!("Global state initialized successfully");
+}
 
-    // Try to open wallet and get real transactions
-    let _ = real_wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
-        .or_else(|_| real_wallet.create_wallet("fuego_password", "/tmp/fuego_wallet.wallet", None, 0));
+/// Tell the user that a change to the wallet was not written to its file
+fn notify_save_failure(operation: &str, error: &WalletError) {
+    let Some(ui) = ADVANCED_UI_MANAGER.get() else {
+        return;
+    };
+    ui.add_notification(UINotification {
+        id: uuid::Uuid::new_v4().to_string(),
+        title: "Wallet not saved".to_string(),
+        message: format!("The wallet could not be saved after the {}: {}. The change is kept and written with the next save.", operation, error),
+        notification_type: "error".to_string(),
+        timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or(Duration::from_secs(0)).as_secs(),
+        is_read: false,
+        is_dismissed: false,
+        actions: vec![],
+        duration: None,
+    });
+}
 
-    // Get real transaction history from blockchain
-    match real_wallet.get_transaction_history(limit.unwrap_or(10), offset.unwrap_or(0)) {
-        Ok(transactions) => {
-            let mapped: Vec<serde_json::Value> = transactions
-                .into_iter()
-                .map(|tx| serde_json::json!({
-                    "id": tx.id,
-                    "hash": tx.hash,
-                    "amount": tx.amount,
-                    "fee": tx.fee,
-                    "height": tx.height,
-                    "timestamp": tx.timestamp,
-                    "confirmations": tx.confirmations,
-                    "is_confirmed": tx.is_confirmed,
-                    "is_pending": tx.is_pending,
-                    "payment_id": tx.payment_id,
-                    "destination_addresses": tx.destination_addresses,
-                    "source_addresses": tx.source_addresses,
-                    "unlock_time": tx.unlock_time,
-                    "extra": tx.extra
-                }))
-                .collect();
-            Ok(mapped)
-        }
+/// Point the user at crash reports written since they last looked
+fn notify_crash_reports(ui: &AdvancedUIManager) {
+    let Some(dir) = crash::crash_reports_dir() else {
+        return;
+    };
+    let count = match crash::unreviewed_count(&dir) {
+        Ok(count) => count,
         Err(e) => {
-            log::error!("Failed to get transaction history: {}", e);
-            Err(format!("Failed to get transaction history: {}", e))
+            log::warn!("{}", e);
+            return;
         }
+    };
+    if count == 0 {
+        return;
     }
+    ui.add_notification(UINotification {
+        id: uuid::Uuid::new_v4().to_string(),
+        title: "The wallet crashed".to_string(),
+        message: format!("{} crash report(s) were saved. Export them to help us fix the problem.", count),
+        notification_type: "crash".to_string(),
+        timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or(Duration::from_secs(0)).as_secs(),
+        is_read: false,
+        is_dismissed: false,
+        actions: vec![NotificationAction {
+            id: "crash_reports".to_string(),
+            label: "View reports".to_string(),
+            action_type: "open_crash_reports".to_string(),
+            is_primary: true,
+        }],
+        duration: None,
+    });
 }
 
-/// Get enhanced wallet information for advanced UI (Phase 1.3)
-#[tauri::command]
-async fn get_enhanced_wallet_info() -> Result<serde_json::Value, String> {
-    let mut real_wallet = RealCryptoNoteWallet::new();
+/// Drive registered background tasks; each task runs when its interval has elapsed and it is enabled
+async fn run_background_tasks(app: AppHandle) {
+    let tasks = BACKGROUND_TASKS.get().unwrap().clone();
+    loop {
+        tokio::time::sleep(Duration::from_secs(5)).await;
+
+        if tasks.should_run(DEPOSIT_MATURITY_TASK) {
+            match tauri::async_runtime::spawn_blocking(check_deposit_maturity).await {
+                Ok(Ok((matured, renewals))) => {
+                    for deposit in matured {
+                        if let Err(e) = app.emit("deposit-matured", &deposit) {
+                            log::warn!("Failed to emit deposit-matured event: {}", e);
+                        }
+                    }
+                    for renewed in renewals.renewed {
+                        if let Err(e) = app.emit("deposit-renewed", &renewed) {
+                            log::warn!("Failed to emit deposit-renewed event: {}", e);
+                        }
+                    }
+                }
+                Ok(Err(e)) => log::warn!("Deposit maturity check failed: {}", e),
+                Err(e) => log::error!("Deposit maturity task panicked: {}", e),
+            }
+            tasks.mark_completed(DEPOSIT_MATURITY_TASK);
+        }
 
-    // Open or create wallet
-    let _ = real_wallet
-        .open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
-        .or_else(|_| real_wallet.create_wallet("fuego_password", "/tmp/fuego_wallet.wallet", None, 0));
+        if tasks.should_run(PENDING_TX_TASK) {
+            match tauri::async_runtime::spawn_blocking(reconcile_pending_transactions).await {
+                Ok(Ok(reconciled)) => {
+                    for pending in reconciled {
+                        if let Err(e) = app.emit("pending-transaction-updated", &pending) {
+                            log::warn!("Failed to emit pending-transaction-updated event: {}", e);
+                        }
+                    }
+                }
+                Ok(Err(e)) => log::warn!("Pending transaction check failed: {}", e),
+                Err(e) => log::error!("Pending transaction task panicked: {}", e),
+            }
+            tasks.mark_completed(PENDING_TX_TASK);
+        }
 
-    // Attempt network connect (best-effort)
-    let _ = connect_to_fuego_network(&mut real_wallet);
+        if tasks.should_run(MEMPOOL_TASK) {
+            match tauri::async_runtime::spawn_blocking(poll_mempool).await {
+                Ok(Ok(arrived)) => {
+                    for transfer in arrived {
+                        let payload = serde_json::json!({ "hash": transfer.hash, "amount": transfer.amount });
+                        if let Err(e) = app.emit(PENDING_INCOMING_EVENT, payload) {
+                            log::warn!("Failed to emit {} event: {}", PENDING_INCOMING_EVENT, e);
+                        }
+                    }
+                }
+                Ok(Err(e)) => log::warn!("Mempool poll failed: {}", e),
+                Err(e) => log::error!("Mempool task panicked: {}", e),
+            }
+            tasks.mark_completed(MEMPOOL_TASK);
+        }
 
-    // Gather info
-    let balance = real_wallet.get_balance().map_err(|e| e.to_string())?;
-    let unlocked_balance = real_wallet.get_unlocked_balance().map_err(|e| e.to_string())?;
-    let address = real_wallet.get_address().map_err(|e| e.to_string())?;
-    let network = real_wallet.get_network_status().unwrap_or_else(|_| serde_json::json!({
-        "is_connected": false,
-        "peer_count": 0,
-        "sync_height": 0,
-        "network_height": 0,
-        "is_syncing": false,
-        "connection_type": "Disconnected"
-    }));
+        if tasks.should_run(REGISTRY_SYNC_TASK) {
+            // Each wallet opened by name syncs on its own; one failing leaves the others
+            for wallet in WALLET_REGISTRY.get().map(|registry| registry.wallets()).unwrap_or_default() {
+                let id = wallet.id.clone();
+                match tauri::async_runtime::spawn_blocking(move || wallet.sync()).await {
+                    Ok(Ok(_)) => {}
+                    Ok(Err(e)) => log::warn!("Sync of wallet {} failed: {}", id, e),
+                    Err(e) => log::error!("Sync of wallet {} panicked: {}", id, e),
+                }
+            }
+            tasks.mark_completed(REGISTRY_SYNC_TASK);
+        }
 
-    // Update advanced manager snapshot
-    if let Some(manager) = ADVANCED_WALLET_MANAGER.get().cloned() {
-        manager.update_wallet_info(EnhancedWalletInfo {
-            address: address.clone(),
-            balance,
-            unlocked_balance,
-            locked_balance: balance.saturating_sub(unlocked_balance),
-            total_received: balance,
-            total_sent: 0,
-            transaction_count: 0,
-            is_synced: network.get("is_syncing").and_then(|v| v.as_bool()).map(|s| !s).unwrap_or(false),
-            sync_height: network.get("sync_height").and_then(|v| v.as_u64()).unwrap_or(0),
-            network_height: network.get("network_height").and_then(|v| v.as_u64()).unwrap_or(0),
-            daemon_height: network.get("network_height").and_then(|v| v.as_u64()).unwrap_or(0),
-            is_connected: network.get("is_connected").and_then(|v| v.as_bool()).unwrap_or(false),
-            peer_count: network.get("peer_count").and_then(|v| v.as_u64()).unwrap_or(0) as u32,
-            last_block_time: None,
-            wallet_version: env!("CARGO_PKG_VERSION").to_string(),
-            seed_phrase: None,
-            view_key: None,
-            spend_key: None,
-            restore_height: 0,
-            auto_refresh: true,
-            refresh_from_block_height: 0,
-            subaddress_count: 0,
-            subaddress_lookahead: 0,
-            wallet_creation_time: None,
-            last_backup_time: None,
-            last_sync_time: Some(SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or(Duration::from_secs(0)).as_secs()),
-            sync_speed: 0.0,
-            estimated_sync_time: None,
-        });
-    }
+        if tasks.should_run(WALLET_AUTO_LOCK_TASK) {
+            let idle_timeout = SETTINGS_MANAGER.get()
+                .and_then(|settings| settings.get_settings().ok())
+                .filter(|settings| settings.security.auto_lock)
+                .map(|settings| Duration::from_secs(u64::from(settings.security.lock_timeout_minutes) * 60));
+            if let (Some(timeout), Some(lock)) = (idle_timeout, WALLET_LOCK.get())
+                && lock.lock_if_idle(timeout)
+            {
+                info!("Wallet locked after {} seconds idle", timeout.as_secs());
+                let _ = app.emit("wallet-locked", serde_json::json!({ "reason": LockReason::Idle }));
+            }
+            tasks.mark_completed(WALLET_AUTO_LOCK_TASK);
+        }
 
-    Ok(serde_json::json!({
-        "address": address,
-        "balance": balance,
-        "unlocked_balance": unlocked_balance,
-        "is_connected": network.get("is_connected").and_then(|v| v.as_bool()).unwrap_or(false),
-        "network": network,
-    }))
-}
+        if tasks.should_run(BALANCE_HISTORY_TASK) {
+            match tauri::async_runtime::spawn_blocking(take_balance_snapshot).await {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => log::warn!("Balance snapshot failed: {}", e),
+                Err(e) => log::error!("Balance history task panicked: {}", e),
+            }
+            tasks.mark_completed(BALANCE_HISTORY_TASK);
+        }
 
-/// Get advanced transactions snapshot (placeholder)
-#[tauri::command]
-async fn get_advanced_transactions() -> Result<Vec<serde_json::Value>, String> {
-    if let Some(manager) = ADVANCED_WALLET_MANAGER.get().cloned() {
-        let txs: Vec<AdvancedTransactionInfo> = manager.get_advanced_transactions();
-        let mapped: Vec<serde_json::Value> = txs
-            .into_iter()
-            .map(|t| serde_json::json!({
-                "id": t.id,
-                "hash": t.hash,
-                "amount": t.amount,
-                "fee": t.fee,
-                "timestamp": t.timestamp,
-                "is_confirmed": t.is_confirmed,
-                "address": t.destination_addresses.get(0).cloned().unwrap_or_default()
-            }))
-            .collect();
-        Ok(mapped)
-    } else {
-        Ok(vec![])
+        if tasks.should_run(WATCHED_ADDRESS_TASK) {
+            match poll_watched_addresses().await {
+                Ok(deposits) => {
+                    for deposit in deposits {
+                        if let Err(e) = app.emit("watched-address-received", &deposit) {
+                            log::warn!("Failed to emit watched-address-received event: {}", e);
+                        }
+                    }
+                }
+                Err(e) => log::warn!("{}", e),
+            }
+            tasks.mark_completed(WATCHED_ADDRESS_TASK);
+        }
+
+        if tasks.should_run(PRICE_ALERT_TASK) {
+            match check_price_alerts().await {
+                Ok(fired) => {
+                    for fired in fired {
+                        if let Err(e) = app.emit("price-alert", &fired) {
+                            log::warn!("Failed to emit price-alert event: {}", e);
+                        }
+                    }
+                }
+                Err(e) => log::warn!("Price alert check failed: {}", e),
+            }
+            tasks.mark_completed(PRICE_ALERT_TASK);
+        }
+
+        if tasks.should_run(PAYMENT_REQUEST_EXPIRY_TASK) {
+            if let Some(requests) = PAYMENT_REQUESTS.get() {
+                match requests.expire_due(chrono::Utc::now().timestamp().max(0) as u64) {
+                    Ok(expired) => emit_payment_request_updates(&app, &expired),
+                    Err(e) => log::warn!("Payment request expiry failed: {}", e),
+                }
+            }
+            tasks.mark_completed(PAYMENT_REQUEST_EXPIRY_TASK);
+        }
+
+        if tasks.should_run(STATE_SAVE_TASK) {
+            if let Err(e) = tauri::async_runtime::spawn_blocking(save_advanced_state).await {
+                log::error!("State save task panicked: {}", e);
+            }
+            tasks.mark_completed(STATE_SAVE_TASK);
+        }
     }
 }
 
-/// Get application settings
-#[tauri::command]
-async fn get_app_settings() -> Result<serde_json::Value, String> {
-    let mgr = SETTINGS_MANAGER.get().ok_or("Settings manager not initialized")?;
-    let settings = mgr.get_settings()?;
-    Ok(serde_json::to_value(settings).map_err(|e| e.to_string())?)
+/// Append the wallet's current balance to the balance history if it changed
+/// or the last snapshot is a day old
+fn record_balance_snapshot(wallet: &RealCryptoNoteWallet) -> Result<bool, String> {
+    let Some(history) = BALANCE_HISTORY.get() else { return Ok(false) };
+    let balance = wallet.get_balance().map_err(|e| e.to_string())?;
+    let unlocked_balance = wallet.get_unlocked_balance().map_err(|e| e.to_string())?;
+    history.record(BalanceSnapshot {
+        timestamp: chrono::Utc::now().timestamp().max(0) as u64,
+        balance,
+        unlocked_balance,
+        locked_balance: balance.saturating_sub(unlocked_balance),
+        price_usd: None,
+    })
 }
 
-/// Get available application languages
-#[tauri::command]
-async fn get_available_app_languages() -> Result<Vec<LanguageInfo>, String> {
-    let mgr = I18N_MANAGER.get().ok_or("I18n manager not initialized")?;
-    mgr.get_available_languages()
+/// Daily snapshot for balances that did not change, and compaction of old entries
+fn take_balance_snapshot() -> Result<(), String> {
+    let history = BALANCE_HISTORY.get().ok_or("Balance history not initialized")?;
+    let mut real_wallet = RealCryptoNoteWallet::new();
+    real_wallet.open_wallet(DEFAULT_WALLET_FILE, "fuego_password")
+        .map_err(|e| format!("Failed to open wallet: {}", e))?;
+    record_balance_snapshot(&real_wallet)?;
+    history.compact(chrono::Utc::now().timestamp().max(0) as u64)?;
+    Ok(())
 }
 
-/// Get UI notifications
+/// Everything the dashboard shows, from in-memory state only; never touches
+/// the wallet or the network
 #[tauri::command]
-async fn get_notifications() -> Result<Vec<serde_json::Value>, String> {
-    if let Some(ui) = ADVANCED_UI_MANAGER.get().cloned() {
-        let items = ui.get_notifications();
-        let mapped: Vec<serde_json::Value> = items.into_iter().map(|n| serde_json::to_value(n).unwrap_or(serde_json::json!({}))).collect();
-        Ok(mapped)
-    } else {
-        Ok(vec![])
-    }
+async fn get_dashboard_summary() -> Result<DashboardSummary, ApiError> {
+    timed_command!("get_dashboard_summary", {
+        let sources = DashboardSources {
+            wallet: ADVANCED_WALLET_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Advanced wallet manager"))?,
+            ui: ADVANCED_UI_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Advanced UI manager"))?,
+            cache: CACHE.get().map(|cache| cache.as_ref()),
+            wallet_info_key: &wallet_info_cache_key(DEFAULT_WALLET_FILE),
+            history: BALANCE_HISTORY.get().map(|history| history.as_ref()),
+            deposits: DEPOSIT_WATCHER.get().map(|watcher| watcher.as_ref()),
+        };
+        Ok(sources.summarize(chrono::Utc::now().timestamp().max(0) as u64))
+    })
 }
 
-// (Removed legacy deposit-address placeholder functions)
+fn watched_addresses() -> Result<&'static WatchedAddresses, String> {
+    WATCHED_ADDRESSES.get().map(|s| s.as_ref()).ok_or_else(|| "Watched addresses not initialized".to_string())
+}
 
-/// Get network status (using real CryptoNote)
+/// Follow the balance of an address outside this wallet
 #[tauri::command]
-async fn get_network_status() -> Result<serde_json::Value, String> {
-    let mut real_wallet = RealCryptoNoteWallet::new();
-    
-    let _ = real_wallet
-        .open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
-        .or_else(|_| real_wallet.create_wallet("fuego_password", "/tmp/fuego_wallet.wallet", None, 0));
-    
-    // Only connect if not already connected
-    if let Err(e) = connect_to_fuego_network(&mut real_wallet) {
-        log::warn!("Network connect attempt failed: {}", e);
+async fn watch_address_add(address: String, label: Option<String>) -> Result<WatchedAddress, ApiError> {
+    if !is_valid_address_format(&address) {
+        return Err(WalletError::InvalidAddress(address).into());
     }
-    
-    real_wallet.get_network_status().map_err(|e| e.to_string())
+    Ok(watched_addresses()?.add(&address, label.as_deref().unwrap_or_default(), is_valid_address_format)?)
 }
 
-// ===== fuego-wallet compatibility aliases =====
-
 #[tauri::command]
-async fn wallet_create(password: String, file_path: String, seed_phrase: Option<String>, restore_height: Option<u64>) -> Result<String, String> {
-    let mut wallet = RealCryptoNoteWallet::new();
-    wallet.create_wallet(&password, &file_path, seed_phrase.as_deref(), restore_height.unwrap_or(0))
-        .map_err(|e| e.to_string())?;
-    let address = wallet.get_address().map_err(|e| e.to_string())?;
-    Ok(address)
+async fn watch_address_remove(address: String) -> Result<bool, ApiError> {
+    Ok(watched_addresses()?.remove(&address)?)
 }
 
 #[tauri::command]
-async fn wallet_open(file_path: String, password: String) -> Result<String, String> {
-    let mut wallet = RealCryptoNoteWallet::new();
-    wallet.open_wallet(&file_path, &password).map_err(|e| e.to_string())?;
-    let address = wallet.get_address().map_err(|e| e.to_string())?;
-    Ok(address)
+async fn watch_address_list() -> Result<Vec<WatchedAddress>, ApiError> {
+    Ok(watched_addresses()?.list())
 }
 
-#[tauri::command]
-async fn wallet_close() -> Result<(), String> {
-    let mut wallet = RealCryptoNoteWallet::new();
-    // Best-effort: open then close. In a real implementation, use a shared instance.
-    let _ = wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password");
-    wallet.close_wallet();
-    Ok(())
+/// Poll the watched addresses through the explorers, unless backing off,
+/// and notify about the ones that received funds
+async fn poll_watched_addresses() -> Result<Vec<WatchedAddressDeposit>, String> {
+    let store = watched_addresses()?;
+    if !store.poll_due() {
+        return Ok(Vec::new());
+    }
+
+    let explorers = ADVANCED_WALLET_MANAGER.get()
+        .map(|mgr| mgr.get_explorers())
+        .unwrap_or_default();
+    let client = ExplorerClient::new(explorers, CACHE.get().cloned());
+    let now = chrono::Utc::now().timestamp().max(0) as u64;
+    let deposits = store.poll(|address| {
+        let client = client.clone();
+        async move { client.fetch_address_summary(&address).await }
+    }, now).await?;
+
+    if let Some(ui) = ADVANCED_UI_MANAGER.get() {
+        for deposit in &deposits {
+            let name = if deposit.label.is_empty() { &deposit.address } else { &deposit.label };
+            ui.add_notification(UINotification {
+                id: uuid::Uuid::new_v4().to_string(),
+                title: "Watched address received funds".to_string(),
+                message: format!("{} received {} XFG", name, format_xfg(deposit.received)),
+                notification_type: "watch".to_string(),
+                timestamp: now,
+                is_read: false,
+                is_dismissed: false,
+                actions: Vec::new(),
+                duration: None,
+            });
+        }
+    }
+    Ok(deposits)
 }
 
-#[tauri::command]
-async fn wallet_get_info() -> Result<serde_json::Value, String> { get_wallet_info().await }
+/// Fetch the XFG price in the currencies of the price alerts and raise a
+/// notification for each alert it fires. Nothing is fetched without alerts.
+async fn check_price_alerts() -> Result<Vec<FiredAlert>, String> {
+    let Some(alerts) = PRICE_ALERTS.get() else {
+        return Ok(Vec::new());
+    };
+    let currencies = alerts.currencies();
+    if currencies.is_empty() {
+        return Ok(Vec::new());
+    }
 
-#[tauri::command]
-async fn wallet_get_balance() -> Result<u64, String> {
-    let mut wallet = RealCryptoNoteWallet::new();
-    let _ = wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
-        .or_else(|_| wallet.create_wallet("fuego_password", "/tmp/fuego_wallet.wallet", None, 0));
-    wallet.get_balance().map_err(|e| e.to_string())
+    let quotes = fetch_quotes(&reqwest::Client::new(), &currencies).await?;
+    let now = chrono::Utc::now().timestamp().max(0) as u64;
+    let fired = alerts.evaluate(&quotes, now)?;
+
+    if let Some(ui) = ADVANCED_UI_MANAGER.get() {
+        for fired in &fired {
+            let currency = fired.alert.currency.to_uppercase();
+            let direction = match fired.alert.direction {
+                AlertDirection::Above => "above",
+                AlertDirection::Below => "below",
+            };
+            ui.add_notification(UINotification {
+                id: uuid::Uuid::new_v4().to_string(),
+                title: "XFG price alert".to_string(),
+                message: format!("XFG is at {} {}, {} your alert at {} {}", fired.price, currency, direction, fired.alert.threshold, currency),
+                notification_type: "price_alert".to_string(),
+                timestamp: now,
+                is_read: false,
+                is_dismissed: false,
+                actions: Vec::new(),
+                duration: None,
+            });
+        }
+    }
+    Ok(fired)
 }
 
+/// Alert when XFG goes `direction` `threshold` in `currency`; with
+/// `repeat_after` seconds the alert fires again after that cool-down
 #[tauri::command]
-async fn wallet_get_address() -> Result<String, String> {
-    let mut wallet = RealCryptoNoteWallet::new();
-    let _ = wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
-        .or_else(|_| wallet.create_wallet("fuego_password", "/tmp/fuego_wallet.wallet", None, 0));
-    wallet.get_address().map_err(|e| e.to_string())
+async fn price_alert_create(direction: AlertDirection, threshold: f64, currency: String, repeat_after: Option<u64>) -> Result<PriceAlert, ApiError> {
+    let alerts = PRICE_ALERTS.get().ok_or_else(|| ApiError::not_initialized("Price alerts"))?;
+    let now = chrono::Utc::now().timestamp().max(0) as u64;
+    alerts.create(direction, threshold, &currency, repeat_after, now).map_err(ApiError::invalid_input)
 }
 
 #[tauri::command]
-async fn wallet_get_transactions(limit: Option<u64>, offset: Option<u64>) -> Result<Vec<serde_json::Value>, String> {
-    get_transactions(limit, offset).await
+async fn price_alert_list() -> Result<Vec<PriceAlert>, ApiError> {
+    let alerts = PRICE_ALERTS.get().ok_or_else(|| ApiError::not_initialized("Price alerts"))?;
+    Ok(alerts.list())
 }
 
 #[tauri::command]
-async fn wallet_send_transaction(recipient: String, amount: u64, payment_id: Option<String>, mixin: Option<u64>) -> Result<String, String> {
-    send_transaction(recipient, amount, payment_id, mixin.unwrap_or(5)).await
+async fn price_alert_delete(id: String) -> Result<(), ApiError> {
+    let alerts = PRICE_ALERTS.get().ok_or_else(|| ApiError::not_initialized("Price alerts"))?;
+    alerts.delete(&id).map_err(ApiError::not_found)
 }
 
+/// Balance points for the balance chart, oldest first
 #[tauri::command]
-async fn wallet_refresh() -> Result<(), String> {
-    let mut wallet = RealCryptoNoteWallet::new();
-    let _ = wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
-        .or_else(|_| wallet.create_wallet("fuego_password", "/tmp/fuego_wallet.wallet", None, 0));
-    wallet.refresh().map_err(|e| e.to_string())
+async fn balance_history(range: BalanceHistoryRange, resolution: Option<BalanceResolution>) -> Result<Vec<BalanceSnapshot>, ApiError> {
+    let history = BALANCE_HISTORY.get().ok_or_else(|| ApiError::not_initialized("Balance history"))?;
+    let now = chrono::Utc::now().timestamp().max(0) as u64;
+    Ok(history.history(range, resolution.unwrap_or_default(), now))
 }
 
-#[tauri::command]
-async fn wallet_rescan(start_height: Option<u64>) -> Result<(), String> {
-    let mut wallet = RealCryptoNoteWallet::new();
-    let _ = wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
-        .or_else(|_| wallet.create_wallet("fuego_password", "/tmp/fuego_wallet.wallet", None, 0));
-    wallet.rescan_blockchain(start_height.unwrap_or(0)).map_err(|e| e.to_string())
+/// Keep the sync loop interval in line with `UISettings::refresh_interval`
+fn forward_settings_changes(receiver: std::sync::mpsc::Receiver<settings::AppSettings>) {
+    tauri::async_runtime::spawn_blocking(move || {
+        for settings in receiver {
+            if let Some(sync_loop) = SYNC_LOOP.get() {
+                sync_loop.set_interval(Duration::from_secs(u64::from(settings.ui.refresh_interval)));
+            }
+            configure_caches(settings.performance.cache_size_mb);
+            if let Err(e) = logging::set_level(&settings.performance.log_level) {
+                log::warn!("{}", e);
+            }
+            select_backend(demo::backend_for(settings.wallet.backend));
+            set_auto_save(settings.wallet.auto_save);
+            set_connect_retry(settings.network.connect_retry);
+            set_default_connect_timeout(Duration::from_secs(u64::from(settings.network.connection_timeout)));
+            // The spendable balance depends on the confirmation policy
+            invalidate_wallet_info();
+        }
+    });
 }
 
-#[tauri::command]
-async fn network_get_status() -> Result<serde_json::Value, String> { get_network_status().await }
+/// Refresh the wallet every `UISettings::refresh_interval` and emit
+/// `wallet-updated`, so the frontend listens instead of polling. Paused while
+/// the wallet is locked or closed.
+async fn run_sync_loop(app: AppHandle) {
+    let control = SYNC_LOOP.get().unwrap().clone();
+    loop {
+        control.wait().await;
+
+        let locked = WALLET_LOCK.get().is_some_and(|lock| lock.is_locked());
+        if !control.should_sync(locked) {
+            continue;
+        }
+        match tauri::async_runtime::spawn_blocking(sync_wallet).await {
+            Ok(Ok((info, paid_requests))) => {
+                if let Err(e) = app.emit(WALLET_UPDATED_EVENT, &info) {
+                    log::warn!("Failed to emit {} event: {}", WALLET_UPDATED_EVENT, e);
+                }
+                emit_payment_request_updates(&app, &paid_requests);
+                check_sync_drift(&app, &info["network"]);
+            }
+            Ok(Err(e)) => log::warn!("Wallet sync failed: {}", e),
+            Err(e) => log::error!("Wallet sync task panicked: {}", e),
+        }
+    }
+}
 
-#[tauri::command]
-async fn node_connect(address: Option<String>, port: Option<u16>) -> Result<(), String> {
-    let mut wallet = RealCryptoNoteWallet::new();
-    let _ = wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
-        .or_else(|_| wallet.create_wallet("fuego_password", "/tmp/fuego_wallet.wallet", None, 0));
-    if let Some(addr) = address {
-        wallet.connect_to_node(&addr, port.unwrap_or(18180)).map_err(|e| e.to_string())
-    } else {
-        connect_to_fuego_network(&mut wallet).map_err(|e| e.to_string())
+/// Sample how far the wallet is behind the daemon, and tell the user when
+/// sync stalled or caught up again
+fn check_sync_drift(app: &AppHandle, network: &serde_json::Value) {
+    let (Some(sync_height), Some(network_height)) = (
+        network.get("sync_height").and_then(|v| v.as_u64()),
+        network.get("network_height").and_then(|v| v.as_u64()),
+    ) else {
+        return;
+    };
+    let settings = SETTINGS_MANAGER.get().and_then(|mgr| mgr.get_settings().ok());
+    let threshold = settings.as_ref().map_or(10, |s| u64::from(s.network.sync_threshold));
+    let grace = settings.and_then(|s| s.network.sync_stall_grace_secs)
+        .map_or(DEFAULT_SYNC_STALL_GRACE, Duration::from_secs);
+
+    let Some(control) = SYNC_LOOP.get() else { return };
+    let (Some(alert), drift) = control.record_drift(sync_height, network_height, threshold, grace) else {
+        return;
+    };
+
+    if let Some(ui) = ADVANCED_UI_MANAGER.get() {
+        match alert {
+            DriftAlert::Raised => {
+                log::warn!("Sync stalled {} blocks behind the daemon", drift.gap);
+                ui.add_notification(UINotification {
+                    id: SYNC_STALLED_NOTIFICATION.to_string(),
+                    title: "Sync stalled".to_string(),
+                    message: format!(
+                        "The wallet is {} blocks behind the network (at {} of {}) and has not caught up for {} minutes.",
+                        drift.gap, drift.sync_height, drift.network_height, drift.stalled_secs / 60
+                    ),
+                    notification_type: "warning".to_string(),
+                    timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or(Duration::from_secs(0)).as_secs(),
+                    is_read: false,
+                    is_dismissed: false,
+                    actions: vec![],
+                    duration: None,
+                });
+            }
+            DriftAlert::Cleared => {
+                log::info!("Sync caught up with the daemon");
+                ui.dismiss_notification(SYNC_STALLED_NOTIFICATION);
+            }
+        }
+    }
+    let payload = serde_json::json!({ "stalled": alert == DriftAlert::Raised, "drift": drift });
+    if let Err(e) = app.emit(SYNC_STALLED_EVENT, &payload) {
+        log::warn!("Failed to emit {} event: {}", SYNC_STALLED_EVENT, e);
     }
 }
 
-#[tauri::command]
-async fn node_disconnect() -> Result<(), String> {
-    let mut wallet = RealCryptoNoteWallet::new();
-    let _ = wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
-        .or_else(|_| wallet.create_wallet("fuego_password", "/tmp/fuego_wallet.wallet", None, 0));
-    wallet.disconnect().map_err(|e| e.to_string())
+/// ID of the stalled-sync notification, so catching up can dismiss it
+const SYNC_STALLED_NOTIFICATION: &str = "sync-stalled";
+
+/// How often the connection watchdog checks the wallet's connection
+const WATCHDOG_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Keep the wallet connected: check it every `WATCHDOG_INTERVAL` and, unless
+/// auto-connect is off, reconnect it with backoff
+async fn run_connection_watchdog(app: AppHandle) {
+    let mut watchdog = ConnectionWatchdog::default();
+    let mut wallet: Option<RealCryptoNoteWallet> = None;
+    loop {
+        tokio::time::sleep(WATCHDOG_INTERVAL).await;
+
+        let auto_connect = SETTINGS_MANAGER.get()
+            .and_then(|s| s.get_settings().ok())
+            .is_none_or(|s| s.network.auto_connect);
+        let checked = tauri::async_runtime::spawn_blocking(move || {
+            let mut real_wallet = match wallet {
+                Some(real_wallet) => real_wallet,
+                None => {
+                    let mut real_wallet = RealCryptoNoteWallet::new();
+                    if let Err(e) = real_wallet.open_wallet(DEFAULT_WALLET_FILE, "fuego_password") {
+                        log::debug!("Connection watchdog waiting for the wallet: {}", e);
+                        return (watchdog, None, Vec::new());
+                    }
+                    real_wallet
+                }
+            };
+            let events = watchdog.check(&mut real_wallet, &known_nodes(), auto_connect, Instant::now());
+            (watchdog, Some(real_wallet), events)
+        }).await;
+        let events = match checked {
+            Ok((checked_watchdog, checked_wallet, events)) => {
+                watchdog = checked_watchdog;
+                wallet = checked_wallet;
+                events
+            }
+            Err(e) => {
+                log::error!("Connection watchdog task panicked: {}", e);
+                watchdog = ConnectionWatchdog::default();
+                wallet = None;
+                continue;
+            }
+        };
+
+        for event in events {
+            let name = match &event {
+                WatchdogEvent::Connected { .. } => NETWORK_CONNECTED_EVENT,
+                WatchdogEvent::Disconnected => NETWORK_DISCONNECTED_EVENT,
+                WatchdogEvent::ReconnectFailed { failures, retry_in_secs, error, .. } => {
+                    if *failures == RECONNECT_NOTIFY_AFTER {
+                        notify_connection_lost(*failures, *retry_in_secs, error);
+                    }
+                    continue;
+                }
+            };
+            if let Err(e) = app.emit(name, &event) {
+                log::warn!("Failed to emit {} event: {}", name, e);
+            }
+        }
+    }
 }
 
-#[tauri::command]
-async fn deposit_list() -> Result<Vec<serde_json::Value>, String> { get_term_deposits().await }
+/// Tell the user that reconnecting keeps failing
+fn notify_connection_lost(failures: u32, retry_in_secs: u64, error: &str) {
+    let Some(ui) = ADVANCED_UI_MANAGER.get() else {
+        return;
+    };
+    ui.add_notification(UINotification {
+        id: uuid::Uuid::new_v4().to_string(),
+        title: "Connection lost".to_string(),
+        message: format!("Reconnecting to the Fuego network failed {} times ({}). Retrying in {} seconds.", failures, error, retry_in_secs),
+        notification_type: "warning".to_string(),
+        timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or(Duration::from_secs(0)).as_secs(),
+        is_read: false,
+        is_dismissed: false,
+        actions: vec![],
+        duration: None,
+    });
+}
+
+/// One pass of the sync loop: refresh through the coordinator, so it is shared
+/// with `wallet_refresh`, then update the manager snapshot and match incoming
+/// payments to payment requests
+fn sync_wallet() -> Result<(serde_json::Value, Vec<PaymentRequest>), String> {
+    let coordinator = REFRESH_COORDINATOR.get().ok_or("Refresh coordinator not initialized")?;
+    with_open_wallet(|real_wallet| {
+        let _ = connect_to_fuego_network(real_wallet);
+
+        coordinator.refresh(|| {
+            real_wallet.refresh().map_err(|e| e.to_string())?;
+            if let Err(e) = record_balance_snapshot(real_wallet) {
+                log::warn!("Failed to record balance snapshot: {}", e);
+            }
+            Ok(())
+        })?;
+        let paid_requests = match match_payment_requests(real_wallet) {
+            Ok(paid_requests) => paid_requests,
+            Err(e) => {
+                log::warn!("Failed to match payment requests: {}", e);
+                Vec::new()
+            }
+        };
+        Ok((snapshot_wallet(real_wallet)?, paid_requests))
+    })
+    .map_err(|e| e.to_string())
+}
+
+/// Number of recent transactions checked against open payment requests
+const PAYMENT_REQUEST_MATCH_WINDOW: u64 = 200;
+
+/// Match confirmed incoming transactions to payment requests by payment ID,
+/// and notify about the requests that were paid
+fn match_payment_requests(wallet: &RealCryptoNoteWallet) -> Result<Vec<PaymentRequest>, String> {
+    let Some(requests) = PAYMENT_REQUESTS.get() else { return Ok(Vec::new()) };
+    if !requests.list().iter().any(|r| r.is_open()) {
+        return Ok(Vec::new());
+    }
+
+    let transactions = wallet.get_transaction_history(PAYMENT_REQUEST_MATCH_WINDOW, 0)
+        .map_err(|e| format!("Failed to get transactions: {}", e))?;
+    let changed = requests.match_payments(transactions.iter()
+        .filter(|tx| tx.is_confirmed && tx.amount > 0)
+        .filter_map(|tx| tx.payment_id.as_deref().map(|payment_id| (tx.hash.as_str(), payment_id, tx.amount as u64))))?;
+
+    if let Some(ui) = ADVANCED_UI_MANAGER.get() {
+        let now = chrono::Utc::now().timestamp().max(0) as u64;
+        for request in &changed {
+            let title = match request.status {
+                PaymentRequestStatus::Paid { .. } => "Payment request paid",
+                _ => "Payment request partially paid",
+            };
+            let name = if request.description.is_empty() { &request.payment_id } else { &request.description };
+            ui.add_notification(UINotification {
+                id: uuid::Uuid::new_v4().to_string(),
+                title: title.to_string(),
+                message: format!("{}: received {} of {} XFG", name, format_xfg(request.received_amount()), format_xfg(request.amount)),
+                notification_type: "payment_request".to_string(),
+                timestamp: now,
+                is_read: false,
+                is_dismissed: false,
+                actions: Vec::new(),
+                duration: None,
+            });
+        }
+    }
+    Ok(changed)
+}
+
+fn emit_payment_request_updates(app: &AppHandle, requests: &[PaymentRequest]) {
+    for request in requests {
+        if let Err(e) = app.emit(PAYMENT_REQUEST_UPDATED_EVENT, request) {
+            log::warn!("Failed to emit {} event: {}", PAYMENT_REQUEST_UPDATED_EVENT, e);
+        }
+    }
+}
+
+fn payment_requests() -> Result<&'static PaymentRequests, String> {
+    PAYMENT_REQUESTS.get().map(|s| s.as_ref()).ok_or_else(|| "Payment requests not initialized".to_string())
+}
 
+/// Request `amount` atomic units to this wallet under a fresh payment ID;
+/// `expiry` is in seconds from now
 #[tauri::command]
-async fn deposit_create(amount: u64, term: u32) -> Result<String, String> { create_term_deposit(amount, term).await }
+async fn create_payment_request(amount: u64, description: String, expiry: Option<u64>) -> Result<PaymentRequest, ApiError> {
+    let requests = payment_requests()?;
+    let address = tauri::async_runtime::spawn_blocking(|| {
+        let mut real_wallet = RealCryptoNoteWallet::new();
+        real_wallet.open_wallet(DEFAULT_WALLET_FILE, "fuego_password")
+            .map_err(|e| ApiError::from(e).context("Failed to open wallet"))?;
+        real_wallet.get_address().map_err(|e| ApiError::from(e).context("Failed to get address"))
+    })
+    .await
+    .map_err(|e| ApiError::from(e).context("Payment request task failed"))??;
+    if amount == 0 {
+        return Err(ApiError::invalid_input("Payment request amount must be greater than zero"));
+    }
+    Ok(requests.create(&address, amount, &description, expiry, chrono::Utc::now().timestamp().max(0) as u64)?)
+}
 
 #[tauri::command]
-async fn deposit_withdraw(deposit_id: String) -> Result<String, String> { withdraw_term_deposit(deposit_id).await }
+async fn list_payment_requests() -> Result<Vec<PaymentRequest>, ApiError> {
+    Ok(payment_requests()?.list())
+}
 
 #[tauri::command]
-async fn estimate_fee(address: String, amount: u64, mixin: Option<u64>) -> Result<u64, String> {
-    let mut real_wallet = RealCryptoNoteWallet::new();
-    let _ = real_wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
-        .or_else(|_| real_wallet.create_wallet("fuego_password", "/tmp/fuego_wallet.wallet", None, 0));
-    real_wallet.estimate_transaction_fee(&address, amount, mixin.unwrap_or(5)).map_err(|e| e.to_string())
+async fn get_payment_request(id: String) -> Result<PaymentRequest, ApiError> {
+    payment_requests()?.get(&id).ok_or_else(|| ApiError::not_found(format!("Payment request not found: {}", id)))
 }
 
 #[tauri::command]
-async fn validate_address(address: String) -> Result<bool, String> {
-    // Real validation: attempt lightweight checks and delegate to CryptoNote wallet if available
-    // 1) Prefix and length sanity
-    if !address.starts_with("fire") || address.len() < 60 || address.len() > 120 {
-        return Ok(false);
-    }
-    // 2) Base58 decode check (rejects invalid charset/length)
-    if bs58::decode(&address).into_vec().is_err() {
-        return Ok(false);
+async fn cancel_payment_request(id: String) -> Result<PaymentRequest, ApiError> {
+    Ok(payment_requests()?.cancel(&id)?)
+}
+
+/// Record a sent transaction as pending, in the tracker and in the transaction list
+fn track_sent_transaction(hash: &str, amount: u64, destinations: Vec<String>, payment_id: Option<String>) {
+    let now = chrono::Utc::now().timestamp().max(0) as u64;
+    // The balance changed, so the cached wallet info is out of date
+    invalidate_wallet_info();
+    if let Some(tracker) = PENDING_TX_TRACKER.get() {
+        tracker.track(hash, amount, destinations.clone(), payment_id.clone(), now);
     }
-    // 3) Ask wallet to accept address in fee estimator (no-op but validates formatting at native layer)
-    let mut wallet = RealCryptoNoteWallet::new();
-    let _ = wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
-        .or_else(|_| wallet.create_wallet("fuego_password", "/tmp/fuego_wallet.wallet", None, 0));
-    let mixin = 5u64;
-    match wallet.estimate_transaction_fee(&address, 1, mixin) {
-        Ok(_) => Ok(true),
-        Err(_) => Ok(false),
+    if let Some(manager) = ADVANCED_WALLET_MANAGER.get() {
+        manager.upsert_transaction(AdvancedTransactionInfo::pending_send(hash, amount, destinations, payment_id, now));
     }
 }
 
-/// Test FFI integration
-#[tauri::command]
-async fn test_ffi_integration() -> Result<serde_json::Value, String> {
-    let mut ffi = CryptoNoteFFI::new();
-    
-    // Test wallet creation
-    let create_result = ffi.create_wallet("test_password", "/tmp/test.wallet", None, 0);
-    if create_result.is_err() {
-        return Err(format!("FFI wallet creation failed: {:?}", create_result.err()));
-    }
-    
-    // Test wallet operations
-    let balance = ffi.get_balance().map_err(|e| e.to_string())?;
-    let unlocked_balance = ffi.get_unlocked_balance().map_err(|e| e.to_string())?;
-    let address = ffi.get_address().map_err(|e| e.to_string())?;
-    let is_open = ffi.is_open();
-    
-    // Test transaction sending
-    let tx_result = ffi.send_transaction("FUEGO9876543210fedcba", 100000000, None, 5);
-    if tx_result.is_err() {
-        return Err(format!("FFI transaction failed: {:?}", tx_result.err()));
+/// Look up pending transactions in the wallet; promote the confirmed ones
+/// and drop the ones that timed out from the transaction list
+fn reconcile_pending_transactions() -> Result<Vec<PendingTx>, String> {
+    let tracker = PENDING_TX_TRACKER.get().ok_or("Pending transaction tracker not initialized")?;
+    if tracker.is_empty() {
+        return Ok(Vec::new());
     }
-    
-    Ok(serde_json::json!({
-        "status": "success",
-        "message": "FFI integration working correctly",
-        "wallet": {
-            "is_open": is_open,
-            "balance": balance,
-            "unlocked_balance": unlocked_balance,
-            "address": address
-        },
-        "transaction": {
-            "hash": tx_result.unwrap()
+
+    let now = chrono::Utc::now().timestamp().max(0) as u64;
+    let reconciled = with_open_wallet(|real_wallet| {
+        let _ = connect_to_fuego_network(real_wallet);
+        Ok(tracker.reconcile(|hash| {
+            real_wallet.get_transaction_by_hash(hash).ok()
+                .filter(|tx| tx.is_confirmed)
+                .map(|tx| Confirmation { height: tx.height, confirmations: tx.confirmations })
+        }, now))
+    })
+    .map_err(|e| e.to_string())?;
+
+    if let Some(manager) = ADVANCED_WALLET_MANAGER.get() {
+        for pending in &reconciled {
+            match pending.status {
+                PendingTxStatus::Confirmed => {
+                    manager.confirm_transaction(&pending.hash, pending.height.unwrap_or(0), pending.confirmations);
+                }
+                PendingTxStatus::Failed => {
+                    log::warn!("Transaction {} was not confirmed in time", pending.hash);
+                    manager.remove_transaction(&pending.hash);
+                }
+                PendingTxStatus::Pending => {}
+            }
         }
-    }))
+    }
+    Ok(reconciled)
 }
 
-/// Test real CryptoNote integration
-#[tauri::command]
-async fn test_real_cryptonote() -> Result<serde_json::Value, String> {
-    let mut real_wallet = RealCryptoNoteWallet::new();
-    
-    // Test wallet creation
-    let create_result = real_wallet.create_wallet("test_password", "/tmp/test_real.wallet", None, 0);
-    if create_result.is_err() {
-        return Err(format!("Real CryptoNote wallet creation failed: {:?}", create_result.err()));
+/// Scan the transaction pool for incoming transfers; list new ones as pending
+/// and promote or drop the ones that left the pool. Returns the new arrivals.
+fn poll_mempool() -> Result<Vec<PoolTransfer>, String> {
+    let watcher = MEMPOOL_WATCHER.get().ok_or("Mempool watcher not initialized")?;
+    if !SYNC_LOOP.get().is_some_and(|sync| sync.is_wallet_open()) {
+        return Ok(Vec::new());
     }
-    
-    // Test wallet operations
-    let balance = real_wallet.get_balance().map_err(|e| e.to_string())?;
-    let unlocked_balance = real_wallet.get_unlocked_balance().map_err(|e| e.to_string())?;
-    let address = real_wallet.get_address().map_err(|e| e.to_string())?;
-    let is_open = real_wallet.is_open();
-    
-    // Test network connection
-    let network_result = connect_to_fuego_network(&mut real_wallet);
-    let network_status = real_wallet.get_network_status().map_err(|e| e.to_string())?;
-    
-    // Test transaction sending
-    let tx_result = real_wallet.send_transaction("fire1234567890abcdef", 100000000, None, 5);
-    if tx_result.is_err() {
-        return Err(format!("Real CryptoNote transaction failed: {:?}", tx_result.err()));
+
+    let changes = with_open_wallet(|real_wallet| {
+        connect_to_fuego_network(real_wallet)
+            .map_err(|e| ApiError::from(e).context("Failed to connect to Fuego network"))?;
+        let pool = real_wallet.get_pool_transfers()
+            .map_err(|e| ApiError::from(e).context("Failed to get pool transfers"))?;
+
+        Ok(watcher.update(pool, |hash| {
+            real_wallet.get_transaction_by_hash(hash).ok()
+                .filter(|tx| tx.is_confirmed)
+                .map(|tx| Confirmation { height: tx.height, confirmations: tx.confirmations })
+        }))
+    })
+    .map_err(|e| e.to_string())?;
+    for transfer in &changes.conflicted {
+        log::warn!("Double spend reported for incoming transaction {}", transfer.hash);
     }
-    
-    Ok(serde_json::json!({
-        "status": "success",
-        "message": "Real CryptoNote integration working correctly",
-        "wallet": {
-            "is_open": is_open,
-            "balance": balance,
-            "unlocked_balance": unlocked_balance,
-            "address": address
-        },
-        "network": {
-            "connection_result": if network_result.is_ok() { "success" } else { "failed" },
-            "status": network_status
-        },
-        "transaction": {
-            "hash": tx_result.unwrap()
-        }
-    }))
+    if let Some(manager) = ADVANCED_WALLET_MANAGER.get() {
+        changes.apply_to(manager);
+    }
+    Ok(changes.arrived)
 }
 
-/// Get real Fuego network data from fuego.spaceportx.net
+/// Transactions sent from this wallet that are not confirmed yet
+#[tauri::command]
+async fn get_pending_transactions() -> Result<Vec<PendingTx>, ApiError> {
+    let tracker = PENDING_TX_TRACKER.get().ok_or_else(|| ApiError::not_initialized("Pending transaction tracker"))?;
+    Ok(tracker.pending())
+}
+
+/// Relay a pending transaction sent from this wallet to the peers again,
+/// e.g. after it dropped out of the transaction pool
 #[tauri::command]
-async fn get_fuego_network_data() -> Result<serde_json::Value, String> {
-    match fetch_fuego_network_data().await {
-        Ok(data) => {
-            log::info!("Fetched real Fuego network data: height={}, peers={}", 
-                      data["height"], data["peer_count"]);
-            Ok(data)
+async fn tx_rebroadcast(tx_id: String) -> Result<PendingTx, ApiError> {
+    timed_command!("tx_rebroadcast", {
+        let tracker = PENDING_TX_TRACKER.get().ok_or_else(|| ApiError::not_initialized("Pending transaction tracker"))?;
+        if tracker.get(&tx_id).is_none() {
+            return Err(ApiError::not_found(format!(
+                "Transaction {} is not pending; only unconfirmed transactions sent from this wallet can be rebroadcast",
+                tx_id
+            )));
         }
-        Err(e) => {
-            log::error!("Failed to fetch Fuego network data: {}", e);
-            Err(format!("Failed to fetch network data: {}", e))
+
+        let hash = tx_id.clone();
+        let result = tauri::async_runtime::spawn_blocking(move || with_open_wallet(|real_wallet| {
+            connect_to_fuego_network(real_wallet)
+                .map_err(|e| ApiError::from(e).context("Failed to connect to Fuego network"))?;
+            // The tracker only learns about a confirmation when it next reconciles
+            if real_wallet.get_transaction_by_hash(&hash).is_ok_and(|tx| tx.is_confirmed) {
+                return Err(ApiError::invalid_input(format!("Transaction {} is already confirmed", hash)));
+            }
+            real_wallet.rebroadcast_transaction(&hash)
+                .map_err(|e| ApiError::from(e).context("Failed to rebroadcast transaction"))
+        })).await?;
+        result?;
+
+        let now = chrono::Utc::now().timestamp().max(0) as u64;
+        let pending = tracker.record_rebroadcast(&tx_id, now)
+            .ok_or_else(|| ApiError::not_found(format!("Transaction {} is no longer pending", tx_id)))?;
+        log::info!("Rebroadcast transaction {} ({} times)", tx_id, pending.rebroadcast_count);
+        Ok(pending)
+    })
+}
+
+/// Prove to a third party that transaction `tx_hash` of this wallet paid
+/// `recipient`. The optional `message` is signed along, so the proof cannot
+/// be reused for another purpose.
+#[tauri::command]
+async fn tx_generate_proof(tx_hash: String, recipient: String, message: Option<String>) -> Result<String, ApiError> {
+    timed_command!("tx_generate_proof", {
+        ensure_wallet_unlocked()?;
+        if !is_valid_address_format(&recipient) {
+            return Err(ApiError::from(WalletError::InvalidAddress(recipient)));
         }
-    }
+
+        async_wallet::run("tx_generate_proof", operation_timeout(OperationClass::Read), move || {
+            with_open_wallet(|real_wallet| {
+                real_wallet.get_tx_proof(&tx_hash, &recipient, message.as_deref())
+                    .map_err(|e| ApiError::from(e).context("Failed to generate transaction proof"))
+            })
+        })
+        .await
+    })
 }
 
-/// Send a transaction
+/// Check a proof from `tx_generate_proof`. A proof that does not match the
+/// transaction, recipient and message is `false`; a string that is not a
+/// proof at all is an error.
 #[tauri::command]
-async fn send_transaction(
-    recipient: String,
-    amount: u64,
-    payment_id: Option<String>,
-    mixin: u64,
-) -> Result<String, String> {
-    let mut real_wallet = RealCryptoNoteWallet::new();
-    
-    // Try to open existing wallet first
-    let wallet_result = real_wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
-        .or_else(|_| real_wallet.create_wallet("fuego_password", "/tmp/fuego_wallet.wallet", None, 0));
-    
-    if let Err(e) = wallet_result {
-        return Err(format!("Failed to open/create wallet: {}", e));
+async fn tx_verify_proof(tx_hash: String, recipient: String, proof: String, message: Option<String>) -> Result<bool, ApiError> {
+    timed_command!("tx_verify_proof", {
+        async_wallet::run("tx_verify_proof", operation_timeout(OperationClass::Read), move || {
+            with_open_wallet(|real_wallet| {
+                real_wallet.check_tx_proof(&tx_hash, &recipient, message.as_deref(), proof.trim())
+                    .map_err(|e| ApiError::from(e).context("Failed to verify transaction proof"))
+            })
+        })
+        .await
+    })
+}
+
+/// Persist the advanced wallet manager next to the active wallet file
+fn save_advanced_state() {
+    save_wallet_state(&active_wallet_path());
+    if let (Some(monitor), Some(path)) = (PERFORMANCE_MONITOR.get(), performance_metrics_path())
+        && let Err(e) = monitor.save(&path)
+    {
+        log::error!("Failed to save performance metrics: {}", e);
     }
-    
-    // Connect to Fuego network
-    if let Err(e) = connect_to_fuego_network(&mut real_wallet) {
-        log::warn!("Failed to connect to Fuego network: {}", e);
-        // Continue without network connection
+}
+
+/// Persist the advanced wallet manager and the notifications next to `wallet_path`
+fn save_wallet_state(wallet_path: &Path) {
+    if let Some(manager) = ADVANCED_WALLET_MANAGER.get()
+        && let Err(e) = manager.save_to(&PersistedWalletState::sidecar_path(wallet_path))
+    {
+        log::error!("Failed to save advanced wallet state: {}", e);
     }
-    
-    // Send transaction
-    match real_wallet.send_transaction(&recipient, amount, payment_id.as_deref(), mixin) {
-        Ok(tx_hash) => {
-            log::info!("Transaction sent successfully: {}", tx_hash);
-            Ok(tx_hash)
-        }
-        Err(e) => {
-            log::error!("Failed to send transaction: {}", e);
-            Err(format!("Failed to send transaction: {}", e))
-        }
+    if let Some(ui) = ADVANCED_UI_MANAGER.get()
+        && let Err(e) = ui.save_notifications_to(&notifications_path(wallet_path))
+    {
+        log::error!("Failed to save notifications: {}", e);
     }
 }
 
-/// Get term deposits (staking/investment positions)
-#[tauri::command]
-async fn get_term_deposits() -> Result<Vec<serde_json::Value>, String> {
-    let mut real_wallet = RealCryptoNoteWallet::new();
-    
-    // Try to open existing wallet first
-    let wallet_result = real_wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
-        .or_else(|_| real_wallet.create_wallet("fuego_password", "/tmp/fuego_wallet.wallet", None, 0));
-    
-    if let Err(e) = wallet_result {
-        return Err(format!("Failed to open/create wallet: {}", e));
+/// After the active wallet changed from the one at `previous`, save its
+/// advanced state and notifications and load those of the wallet active now
+fn switch_wallet_state(previous: &Path) {
+    let current = active_wallet_path();
+    if current == previous {
+        return;
     }
-    
-    // Connect to Fuego network
-    let _ = connect_to_fuego_network(&mut real_wallet);
-    
-    // Get real deposits from CryptoNote wallet
-    match real_wallet.get_deposits() {
-        Ok(deposits) => {
-            let mut deposit_list = Vec::new();
-            
-            for deposit in deposits {
-                let deposit_json = serde_json::json!({
-                    "id": deposit.id,
-                    "amount": deposit.amount,
-                    "interest": deposit.interest,
-                    "term": deposit.term,
-                    "rate": deposit.rate,
-                    "status": deposit.status,
-                    "unlock_height": deposit.unlock_height,
-                    "unlock_time": deposit.unlock_time,
-                    "creating_transaction_hash": deposit.creating_transaction_hash,
-                    "creating_height": deposit.creating_height,
-                    "creating_time": deposit.creating_time,
-                    "spending_transaction_hash": deposit.spending_transaction_hash,
-                    "spending_height": deposit.spending_height,
-                    "spending_time": deposit.spending_time,
-                    "type": deposit.deposit_type
-                });
-                deposit_list.push(deposit_json);
-            }
-            
-            log::info!("Retrieved {} term deposits from blockchain", deposit_list.len());
-            Ok(deposit_list)
-        }
-        Err(e) => {
-            log::error!("Failed to get deposits: {}", e);
-            Err(format!("Failed to get deposits: {}", e))
-        }
+    save_wallet_state(previous);
+    if let Some(manager) = ADVANCED_WALLET_MANAGER.get()
+        && let Err(e) = manager.switch_to(&PersistedWalletState::sidecar_path(&current))
+    {
+        log::warn!("Failed to restore advanced wallet state: {}", e);
+    }
+    if let Some(ui) = ADVANCED_UI_MANAGER.get()
+        && let Err(e) = ui.switch_notifications_to(&notifications_path(&current))
+    {
+        log::warn!("Failed to restore notifications: {}", e);
     }
+    invalidate_wallet_info();
 }
 
-/// Create a new term deposit (stake XFG for interest)
-#[tauri::command]
-async fn create_term_deposit(amount: u64, term: u32) -> Result<String, String> {
-    let mut real_wallet = RealCryptoNoteWallet::new();
-    
-    // Try to open existing wallet first
-    let wallet_result = real_wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
-        .or_else(|_| real_wallet.create_wallet("fuego_password", "/tmp/fuego_wallet.wallet", None, 0));
-    
-    if let Err(e) = wallet_result {
-        return Err(format!("Failed to open/create wallet: {}", e));
-    }
-    
-    // Connect to Fuego network
-    let _ = connect_to_fuego_network(&mut real_wallet);
-    
-    // Validate deposit parameters
-    if amount < 10000000 { // Minimum 1 XFG
-        return Err("Minimum deposit amount is 1 XFG".to_string());
+/// Performance metrics are kept with the settings, as they span wallets
+fn performance_metrics_path() -> Option<std::path::PathBuf> {
+    config_path("performance_metrics.json")
+}
+
+/// Fetch deposits, report the ones that just matured and push a notification for
+/// each, then renew the matured deposits that are set to auto-renew
+fn check_deposit_maturity() -> Result<(Vec<crate::crypto::real_cryptonote::DepositInfo>, RenewalReport), String> {
+    let watcher = DEPOSIT_WATCHER.get().ok_or("Deposit watcher not initialized")?;
+    with_open_wallet(|real_wallet| Ok(check_wallet_deposits(watcher, real_wallet)?))
+        .map_err(|e| e.to_string())
+}
+
+fn check_wallet_deposits(
+    watcher: &DepositMaturityWatcher,
+    real_wallet: &mut RealCryptoNoteWallet,
+) -> Result<(Vec<crate::crypto::real_cryptonote::DepositInfo>, RenewalReport), String> {
+    let _ = connect_to_fuego_network(real_wallet);
+
+    let deposits = real_wallet.get_deposits()
+        .map_err(|e| format!("Failed to get deposits: {}", e))?;
+    let network_height = real_wallet.get_network_status()
+        .map_err(|e| format!("Failed to get network status: {}", e))?
+        .get("network_height")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0);
+
+    let matured = watcher.check(&deposits, network_height);
+    let renewals = DEPOSIT_RENEWALS.get();
+    if let Some(ui) = ADVANCED_UI_MANAGER.get() {
+        // Deposits about to be renewed get a notification once that is done
+        let auto_renewed = |id: &str| renewals.and_then(|store| store.get(id)).is_some_and(|option| option.renew);
+        for deposit in matured.iter().filter(|deposit| !auto_renewed(&deposit.id)) {
+            ui.add_notification(UINotification {
+                id: uuid::Uuid::new_v4().to_string(),
+                title: "Deposit matured".to_string(),
+                message: format!(
+                    "Term deposit {} of {} XFG can now be withdrawn",
+                    deposit.id,
+                    format_xfg(deposit.amount)
+                ),
+                notification_type: "deposit".to_string(),
+                timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or(Duration::from_secs(0)).as_secs(),
+                is_read: false,
+                is_dismissed: false,
+                actions: vec![NotificationAction {
+                    id: deposit.id.clone(),
+                    label: "Withdraw".to_string(),
+                    action_type: "deposit_withdraw".to_string(),
+                    is_primary: true,
+                }],
+                duration: None,
+            });
+        }
     }
-    
-    if term < 1 || term > 365 { // Term between 1 and 365 days
-        return Err("Term must be between 1 and 365 days".to_string());
+
+    // Renewing sends transactions, which waits until the wallet is unlocked
+    let unlocked = WALLET_LOCK.get().is_some_and(|lock| !lock.is_locked());
+    let report = match renewals {
+        Some(store) if unlocked => store.renew_matured(
+            &deposits,
+            network_height,
+            |id| real_wallet.withdraw_deposit(id).map_err(|e| e.to_string()),
+            |amount, term| real_wallet.create_deposit(amount, term).map_err(|e| e.to_string()),
+        )?,
+        _ => RenewalReport::default(),
+    };
+    for renewed in &report.renewed {
+        log::info!("Renewed term deposit {} as {} for {} days", renewed.previous_id, renewed.deposit_id, renewed.term);
     }
-    
-    // Create real deposit transaction using CryptoNote
-    match real_wallet.create_deposit(amount, term) {
-        Ok(deposit_id) => {
-            log::info!("Created term deposit: {} XFG for {} days (ID: {})", amount / 10000000, term, deposit_id);
-            Ok(deposit_id)
+    if let Some(ui) = ADVANCED_UI_MANAGER.get() {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or(Duration::from_secs(0)).as_secs();
+        for renewed in &report.renewed {
+            ui.add_notification(UINotification {
+                id: uuid::Uuid::new_v4().to_string(),
+                title: "Deposit renewed".to_string(),
+                message: format!(
+                    "Term deposit {} matured and {} XFG were deposited again for {} days",
+                    renewed.previous_id,
+                    format_xfg(renewed.amount),
+                    renewed.term
+                ),
+                notification_type: "deposit".to_string(),
+                timestamp,
+                is_read: false,
+                is_dismissed: false,
+                actions: vec![],
+                duration: None,
+            });
         }
-        Err(e) => {
-            log::error!("Failed to create deposit: {}", e);
-            Err(format!("Failed to create deposit: {}", e))
+        // Retries run every check; only the first failure in a row is shown
+        for failure in report.failed.iter().filter(|failure| failure.failures == 1) {
+            log::warn!("Failed to renew term deposit {}: {}", failure.deposit_id, failure.error);
+            ui.add_notification(UINotification {
+                id: uuid::Uuid::new_v4().to_string(),
+                title: "Deposit renewal failed".to_string(),
+                message: format!("Term deposit {} could not be renewed and will be retried: {}", failure.deposit_id, failure.error),
+                notification_type: "deposit".to_string(),
+                timestamp,
+                is_read: false,
+                is_dismissed: false,
+                actions: vec![],
+                duration: None,
+            });
         }
     }
+
+    Ok((matured, report))
 }
 
-/// Withdraw a term deposit (claim principal + interest)
+/// Get wallet information (using real CryptoNote)
+#[tauri::command]
+async fn get_wallet_info(force_refresh: Option<bool>) -> Result<serde_json::Value, ApiError> {
+    timed_command!("get_wallet_info", {
+        let cache = CACHE.get().ok_or_else(|| ApiError::not_initialized("Cache"))?.clone();
+        let key = wallet_info_cache_key(DEFAULT_WALLET_FILE);
+        // A cache miss loads the wallet and connects, which blocks
+        let (info, stale) = {
+            let (cache, key) = (cache.clone(), key.clone());
+            async_wallet::run("get_wallet_info", operation_timeout(OperationClass::Read), move || {
+                cache.get_or_load(&key, WALLET_INFO_TTL, WALLET_INFO_MAX_AGE, force_refresh.unwrap_or(false), load_wallet_info)
+                    .map_err(ApiError::from)
+            })
+            .await?
+        };
+
+        // Serve the slightly old value now and bring it up to date for the next poll
+        if stale && !WALLET_INFO_REFRESHING.swap(true, std::sync::atomic::Ordering::SeqCst) {
+            tauri::async_runtime::spawn_blocking(move || {
+                let _refreshing = WalletInfoRefreshGuard;
+                match load_wallet_info() {
+                    Ok(info) => cache.set_with_ttl(key, info, WALLET_INFO_MAX_AGE),
+                    Err(e) => log::warn!("Background wallet info refresh failed: {}", e),
+                }
+            });
+        }
+        Ok(info)
+    })
+}
+
+/// Where the locked part of the balance is locked: unconfirmed change and
+/// transfers, term deposits and maturing mined outputs, each with the blocks
+/// and estimated time until it unlocks
 #[tauri::command]
-async fn withdraw_term_deposit(deposit_id: String) -> Result<String, String> {
+async fn get_balance_breakdown() -> Result<BalanceBreakdown, ApiError> {
+    timed_command!("get_balance_breakdown", {
+        async_wallet::run("get_balance_breakdown", operation_timeout(OperationClass::Read), || {
+            let mut real_wallet = RealCryptoNoteWallet::new();
+            real_wallet.open_wallet(DEFAULT_WALLET_FILE, "fuego_password")
+                .map_err(|e| ApiError::from(e).context("Failed to open wallet"))?;
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or(Duration::from_secs(0)).as_secs();
+            let breakdown = BalanceBreakdown::load(&real_wallet, now, min_confirmations())
+                .map_err(|e| ApiError::from(e).context("Failed to break down balance"))?;
+            if let Some(discrepancy) = breakdown.discrepancy {
+                log::warn!("Locked balance of {} is off by {} from its sources", breakdown.locked_balance, discrepancy);
+            }
+            Ok(breakdown)
+        })
+        .await
+    })
+}
+
+fn wallet_info_cache_key(wallet_path: &str) -> String {
+    format!("wallet_info:{}", wallet_path)
+}
+
+/// Drop the cached wallet info after something changed the balance
+fn invalidate_wallet_info() {
+    if let Some(cache) = CACHE.get() {
+        cache.remove(&wallet_info_cache_key(DEFAULT_WALLET_FILE));
+    }
+}
+
+/// Confirmations incoming transfers need to be spendable, from the settings
+fn min_confirmations() -> u64 {
+    effective_min_confirmations(SETTINGS_MANAGER.get()
+        .and_then(|mgr| mgr.get_settings().ok())
+        .and_then(|settings| settings.wallet.min_confirmations))
+}
+
+/// Refuse to send `amount` when the confirmation policy is stricter than the
+/// protocol and holds back too much of the unlocked balance
+fn check_spendable(wallet: &RealCryptoNoteWallet, amount: u64) -> Result<(), ApiError> {
+    let required = min_confirmations();
+    if required <= TX_SPENDABLE_AGE {
+        return Ok(());
+    }
+    let spendable = load_spendable_balance(wallet, required)
+        .map_err(|e| ApiError::from(e).context("Failed to compute spendable balance"))?;
+    if amount > spendable.spendable_balance {
+        return Err(ApiError::new(ErrorCode::InsufficientFunds, format!(
+            "Only {} XFG is spendable: {} incoming transfers have fewer than {} confirmations",
+            format_xfg(spendable.spendable_balance), spendable.maturing.len(), required
+        )));
+    }
+    Ok(())
+}
+
+/// Assemble the wallet info from the native wallet
+fn load_wallet_info() -> Result<serde_json::Value, String> {
     let mut real_wallet = RealCryptoNoteWallet::new();
     
-    // Try to open existing wallet first
+    // Try to open existing wallet first, then create if needed
     let wallet_result = real_wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
         .or_else(|_| real_wallet.create_wallet("fuego_password", "/tmp/fuego_wallet.wallet", None, 0));
     
@@ -826,26 +2016,1940 @@ async fn withdraw_term_deposit(deposit_id: String) -> Result<String, String> {
     }
     
     // Connect to Fuego network
-    let _ = connect_to_fuego_network(&mut real_wallet);
-    
-    // Withdraw deposit using real CryptoNote functionality
-    match real_wallet.withdraw_deposit(&deposit_id) {
-        Ok(tx_hash) => {
-            log::info!("Withdrew term deposit: {} (TX: {})", deposit_id, tx_hash);
-            Ok(tx_hash)
-        }
-        Err(e) => {
-            log::error!("Failed to withdraw deposit: {}", e);
-            Err(format!("Failed to withdraw deposit: {}", e))
-        }
+    if let Err(e) = connect_to_fuego_network(&mut real_wallet) {
+        log::warn!("Failed to connect to Fuego network: {}", e);
+        // Continue without network connection
     }
+    
+    let balance = real_wallet.get_balance().map_err(|e| e.to_string())?;
+    let unlocked_balance = real_wallet.get_unlocked_balance().map_err(|e| e.to_string())?;
+    let address = real_wallet.get_address().map_err(|e| e.to_string())?;
+    let spendable_balance = load_spendable_balance(&real_wallet, min_confirmations())
+        .map(|spendable| spendable.spendable_balance)
+        .unwrap_or_else(|e| {
+            log::warn!("Failed to apply the confirmation policy, reporting the unlocked balance: {}", e);
+            unlocked_balance
+        });
+    
+    Ok(serde_json::json!({
+        "address": address,
+        "balance": balance,
+        "unlocked_balance": unlocked_balance,
+        "spendable_balance": spendable_balance,
+        "is_open": real_wallet.is_open(),
+        "is_encrypted": true,
+        "is_real": !demo::is_enabled(),
+        "demo": demo::is_enabled()
+    }))
+}
+
+/// Get transactions (real implementation)
+#[tauri::command]
+async fn get_transactions(limit: Option<u64>, offset: Option<u64>) -> Result<Vec<serde_json::Value>, ApiError> {
+    timed_command!("get_transactions", {
+        async_wallet::run("get_transactions", operation_timeout(OperationClass::Read), move || {
+            with_active_wallet(|real_wallet| {
+                // Get real transaction history from blockchain
+                match real_wallet.get_transaction_history(limit.unwrap_or(10), offset.unwrap_or(0)) {
+                    Ok(transactions) => {
+                        let mapped: Vec<serde_json::Value> = transactions
+                            .into_iter()
+                            .map(|tx| serde_json::json!({
+                                "id": tx.id,
+                                "hash": tx.hash,
+                                "amount": tx.amount,
+                                "fee": tx.fee,
+                                "height": tx.height,
+                                "timestamp": tx.timestamp,
+                                "confirmations": tx.confirmations,
+                                "is_confirmed": tx.is_confirmed,
+                                "is_pending": tx.is_pending,
+                                "payment_id": tx.payment_id,
+                                "destination_addresses": tx.destination_addresses,
+                                "source_addresses": tx.source_addresses,
+                                "unlock_time": tx.unlock_time,
+                                "extra": tx.extra
+                            }))
+                            .collect();
+                        Ok(mapped)
+                    }
+                    Err(e) => {
+                        log::error!("Failed to get transaction history: {}", e);
+                        Err(ApiError::from(e).context("Failed to get transaction history"))
+                    }
+                }
+            })
+        })
+        .await
+    })
+}
+
+/// Get enhanced wallet information for advanced UI (Phase 1.3)
+#[tauri::command]
+async fn get_enhanced_wallet_info() -> Result<serde_json::Value, ApiError> {
+    async_wallet::run("get_enhanced_wallet_info", operation_timeout(OperationClass::Read), move || {
+        let mut real_wallet = RealCryptoNoteWallet::new();
+
+        // Open or create wallet
+        let _ = real_wallet
+            .open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
+            .or_else(|_| real_wallet.create_wallet("fuego_password", "/tmp/fuego_wallet.wallet", None, 0));
+
+        // Attempt network connect (best-effort)
+        let _ = connect_to_fuego_network(&mut real_wallet);
+
+        Ok(snapshot_wallet(&real_wallet)?)
+    })
+    .await
+}
+
+/// Read balances and network status, update the advanced manager snapshot
+/// and return them for the frontend
+fn snapshot_wallet(real_wallet: &RealCryptoNoteWallet) -> Result<serde_json::Value, String> {
+    let balance = real_wallet.get_balance().map_err(|e| e.to_string())?;
+    let unlocked_balance = real_wallet.get_unlocked_balance().map_err(|e| e.to_string())?;
+    let address = real_wallet.get_address().map_err(|e| e.to_string())?;
+    let network = real_wallet.get_network_status().unwrap_or_else(|_| serde_json::json!({
+        "is_connected": false,
+        "peer_count": 0,
+        "sync_height": 0,
+        "network_height": 0,
+        "is_syncing": false,
+        "connection_type": ConnectionType::Disconnected,
+        "connection_type_raw": ""
+    }));
+
+    let sync_phase = real_wallet.get_sync_status().map(|status| status.phase).ok();
+    let metadata = WalletMetadata::load(Path::new(DEFAULT_WALLET_FILE));
+
+    // Update advanced manager snapshot
+    if let Some(manager) = ADVANCED_WALLET_MANAGER.get().cloned() {
+        manager.update_wallet_info(EnhancedWalletInfo {
+            address: address.clone(),
+            balance,
+            unlocked_balance,
+            locked_balance: balance.saturating_sub(unlocked_balance),
+            total_received: balance,
+            total_sent: 0,
+            transaction_count: 0,
+            is_synced: network.get("is_syncing").and_then(|v| v.as_bool()).map(|s| !s).unwrap_or(false),
+            sync_height: network.get("sync_height").and_then(|v| v.as_u64()).unwrap_or(0),
+            network_height: network.get("network_height").and_then(|v| v.as_u64()).unwrap_or(0),
+            daemon_height: network.get("network_height").and_then(|v| v.as_u64()).unwrap_or(0),
+            is_connected: network.get("is_connected").and_then(|v| v.as_bool()).unwrap_or(false),
+            peer_count: network.get("peer_count").and_then(|v| v.as_u64()).unwrap_or(0) as u32,
+            last_block_time: None,
+            wallet_version: env!("CARGO_PKG_VERSION").to_string(),
+            seed_phrase: None,
+            view_key: None,
+            spend_key: None,
+            restore_height: 0,
+            auto_refresh: true,
+            refresh_from_block_height: 0,
+            subaddress_count: 0,
+            subaddress_lookahead: 0,
+            wallet_creation_time: metadata.created_at,
+            last_backup_time: metadata.last_backup_at,
+            last_sync_time: Some(SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or(Duration::from_secs(0)).as_secs()),
+            sync_speed: 0.0,
+            estimated_sync_time: None,
+        });
+    }
+
+    Ok(serde_json::json!({
+        "address": address,
+        "balance": balance,
+        "unlocked_balance": unlocked_balance,
+        "is_connected": network.get("is_connected").and_then(|v| v.as_bool()).unwrap_or(false),
+        "network": network,
+        "sync_phase": sync_phase,
+        "wallet_creation_time": metadata.created_at,
+        "last_backup_time": metadata.last_backup_at,
+    }))
+}
+
+/// Get the metadata recorded alongside the wallet file
+#[tauri::command]
+async fn get_wallet_metadata() -> Result<WalletMetadata, ApiError> {
+    Ok(WalletMetadata::load(Path::new(DEFAULT_WALLET_FILE)))
+}
+
+/// Search transactions with filters, sorting and pagination
+#[tauri::command]
+async fn transactions_search(filter: Option<TransactionFilter>) -> Result<TransactionSearchResult, ApiError> {
+    let manager = ADVANCED_WALLET_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Advanced wallet manager"))?;
+    let mut result = manager.search_transactions(&filter.unwrap_or_default(), TX_METADATA_STORE.get().map(|s| s.as_ref()));
+    let resolver = contact_resolver();
+    result.items.iter_mut().for_each(|tx| resolver.resolve_transaction(tx));
+    Ok(result)
+}
+
+/// Transactions to or from an address book contact, narrowed further by `filter`
+#[tauri::command]
+async fn transactions_by_contact(contact_id: String, filter: Option<TransactionFilter>) -> Result<TransactionSearchResult, ApiError> {
+    if !contact_resolver().is_contact(&contact_id) {
+        return Err(ApiError::not_found(format!("Contact {} is not in the address book", contact_id)));
+    }
+    let filter = TransactionFilter { address: Some(contact_id), ..filter.unwrap_or_default() };
+    transactions_search(Some(filter)).await
+}
+
+/// Names for transaction addresses from the address book and the wallet's
+/// own addresses; names nothing when the wallet cannot be read
+fn contact_resolver() -> ContactResolver {
+    let mut real_wallet = RealCryptoNoteWallet::new();
+    if let Err(e) = real_wallet.open_wallet(DEFAULT_WALLET_FILE, "fuego_password") {
+        log::debug!("Wallet not open, transaction addresses stay unnamed: {}", e);
+        return ContactResolver::default();
+    }
+    let address_book = real_wallet.get_address_book().unwrap_or_else(|e| {
+        log::debug!("Address book unavailable: {}", e);
+        Vec::new()
+    });
+    let own = real_wallet.get_addresses().unwrap_or_else(|e| {
+        log::debug!("Wallet addresses unavailable: {}", e);
+        Vec::new()
+    });
+    ContactResolver::new(&address_book, &own)
+}
+
+fn tx_metadata_store() -> Result<&'static TransactionMetadataStore, String> {
+    TX_METADATA_STORE.get().map(|s| s.as_ref()).ok_or_else(|| "Transaction metadata store not initialized".to_string())
+}
+
+/// Set or clear (with `None`) the label of a transaction
+#[tauri::command]
+async fn transaction_set_label(tx_hash: String, label: Option<String>) -> Result<TransactionMetadata, ApiError> {
+    Ok(tx_metadata_store()?.set_label(&tx_hash, label)?)
+}
+
+/// Set or clear (with `None`) the note of a transaction
+#[tauri::command]
+async fn transaction_set_note(tx_hash: String, note: Option<String>) -> Result<TransactionMetadata, ApiError> {
+    Ok(tx_metadata_store()?.set_note(&tx_hash, note)?)
+}
+
+/// Set or clear (with `None`) the category of a transaction
+#[tauri::command]
+async fn transaction_set_category(tx_hash: String, category: Option<String>) -> Result<TransactionMetadata, ApiError> {
+    Ok(tx_metadata_store()?.set_category(&tx_hash, category)?)
+}
+
+/// Record (or clear with `None`) the fee priority a transaction was sent with
+#[tauri::command]
+async fn transaction_set_priority(tx_hash: String, priority: Option<FeePriority>) -> Result<TransactionMetadata, ApiError> {
+    let metadata = tx_metadata_store()?.set_priority(&tx_hash, priority)?;
+    if let Some(cache) = CACHE.get() {
+        cache.remove(FEE_ANALYTICS_CACHE_KEY);
+    }
+    Ok(metadata)
+}
+
+#[tauri::command]
+async fn transaction_add_tag(tx_hash: String, tag: String) -> Result<TransactionMetadata, ApiError> {
+    Ok(tx_metadata_store()?.add_tag(&tx_hash, &tag)?)
+}
+
+#[tauri::command]
+async fn transaction_remove_tag(tx_hash: String, tag: String) -> Result<TransactionMetadata, ApiError> {
+    Ok(tx_metadata_store()?.remove_tag(&tx_hash, &tag)?)
+}
+
+/// Get advanced transactions snapshot (placeholder)
+#[tauri::command]
+async fn get_advanced_transactions() -> Result<Vec<serde_json::Value>, ApiError> {
+    if let Some(manager) = ADVANCED_WALLET_MANAGER.get().cloned() {
+        let mut txs: Vec<AdvancedTransactionInfo> = manager.get_advanced_transactions();
+        let resolver = contact_resolver();
+        txs.iter_mut().for_each(|tx| resolver.resolve_transaction(tx));
+        let current_height = manager.get_enhanced_wallet_info()
+            .map(|info| info.network_height)
+            .unwrap_or(0);
+        let current_ts = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let mapped: Vec<serde_json::Value> = txs
+            .into_iter()
+            .map(|t| {
+                let spendable = t.is_spendable(current_height, current_ts);
+                let outputs: Vec<serde_json::Value> = t.outputs.iter()
+                    .map(|o| serde_json::json!({
+                        "amount": o.amount,
+                        "global_index": o.global_index,
+                        "public_key": o.public_key,
+                        "spendable": spendable
+                    }))
+                    .collect();
+                serde_json::json!({
+                    "id": t.id,
+                    "hash": t.hash,
+                    "amount": t.amount,
+                    "fee": t.fee,
+                    "timestamp": t.timestamp,
+                    "is_confirmed": t.is_confirmed,
+                    "unlock_time": t.unlock_time,
+                    "spendable": spendable,
+                    "outputs": outputs,
+                    "address": t.destination_addresses.get(0).cloned().unwrap_or_default(),
+                    "resolved_addresses": t.resolved_addresses
+                })
+            })
+            .collect();
+        Ok(mapped)
+    } else {
+        Ok(vec![])
+    }
+}
+
+/// Get application settings
+#[tauri::command]
+async fn get_app_settings() -> Result<serde_json::Value, ApiError> {
+    let mgr = SETTINGS_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Settings manager"))?;
+    let settings = mgr.get_settings().map_err(ApiError::settings)?;
+    Ok(serde_json::to_value(settings)?)
+}
+
+/// Get available application languages
+#[tauri::command]
+async fn get_available_app_languages() -> Result<Vec<LanguageInfo>, ApiError> {
+    let mgr = I18N_MANAGER.get().ok_or_else(|| ApiError::not_initialized("I18n manager"))?;
+    mgr.get_available_languages().map_err(ApiError::settings)
+}
+
+/// Switch the application language
+#[tauri::command]
+async fn set_app_language(app: AppHandle, language: String) -> Result<serde_json::Value, ApiError> {
+    let info = change_language(&app, &language).map_err(ApiError::settings)?;
+    Ok(serde_json::json!({
+        "language": info.code,
+        "rtl": info.rtl
+    }))
+}
+
+/// Switch language, persist it in the UI settings and notify the frontend
+fn change_language(app: &AppHandle, language: &str) -> Result<LanguageInfo, String> {
+    let mgr = I18N_MANAGER.get().ok_or("I18n manager not initialized")?;
+    let settings = SETTINGS_MANAGER.get().ok_or("Settings manager not initialized")?;
+    let info = mgr.switch_language(language, settings)?;
+    let _ = app.emit("i18n://language-changed", &info);
+    Ok(info)
+}
+
+/// Translate a key in the current language, substituting `{name}` parameters
+#[tauri::command]
+async fn i18n_translate(key: String, params: Option<std::collections::HashMap<String, String>>) -> Result<String, ApiError> {
+    let mgr = I18N_MANAGER.get().ok_or_else(|| ApiError::not_initialized("I18n manager"))?;
+    match params {
+        Some(params) => mgr.translate_with_params(&key, params),
+        None => mgr.translate(&key),
+    }.map_err(ApiError::settings)
+}
+
+/// Translate a key whose wording depends on `count`
+#[tauri::command]
+async fn i18n_translate_plural(key: String, count: u64, params: Option<std::collections::HashMap<String, String>>) -> Result<String, ApiError> {
+    let mgr = I18N_MANAGER.get().ok_or_else(|| ApiError::not_initialized("I18n manager"))?;
+    mgr.translate_plural(&key, count, params.unwrap_or_default()).map_err(ApiError::settings)
+}
+
+/// List the keys a language is missing, including runtime fallbacks
+#[tauri::command]
+async fn i18n_missing_keys(language: String) -> Result<Vec<String>, ApiError> {
+    let mgr = I18N_MANAGER.get().ok_or_else(|| ApiError::not_initialized("I18n manager"))?;
+    mgr.missing_keys(&language).map_err(ApiError::settings)
+}
+
+/// Translation coverage of every available language
+#[tauri::command]
+async fn i18n_coverage() -> Result<Vec<TranslationCoverage>, ApiError> {
+    let mgr = I18N_MANAGER.get().ok_or_else(|| ApiError::not_initialized("I18n manager"))?;
+    mgr.coverage().map_err(ApiError::settings)
+}
+
+/// Write a skeleton locale file with the missing keys of a language
+#[tauri::command]
+async fn i18n_export_missing(language: String, path: String) -> Result<usize, ApiError> {
+    let mgr = I18N_MANAGER.get().ok_or_else(|| ApiError::not_initialized("I18n manager"))?;
+    mgr.export_missing(&language, Path::new(&path)).map_err(ApiError::settings)
+}
+
+/// Detect the system locale and the closest available language.
+/// With `apply`, switch to it and keep following the system language.
+#[tauri::command]
+async fn i18n_detect_system_locale(app: AppHandle, apply: Option<bool>) -> Result<serde_json::Value, ApiError> {
+    let mgr = I18N_MANAGER.get().ok_or_else(|| ApiError::not_initialized("I18n manager"))?;
+    let locale = crate::i18n::system_locale();
+
+    let language = if apply.unwrap_or(false) {
+        let settings = SETTINGS_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Settings manager"))?;
+        let info = mgr.apply_system_language(settings).map_err(ApiError::settings)?;
+        if let Some(info) = &info {
+            let _ = app.emit("i18n://language-changed", info);
+        }
+        info
+    } else {
+        mgr.detect_system_language()
+    };
+
+    Ok(serde_json::json!({
+        "locale": locale,
+        "language": language
+    }))
+}
+
+/// Switch the application language and persist the choice
+#[tauri::command]
+async fn i18n_set_language(app: AppHandle, code: String) -> Result<LanguageInfo, ApiError> {
+    change_language(&app, &code).map_err(ApiError::settings)
+}
+
+/// Get the current application language
+#[tauri::command]
+async fn i18n_get_language() -> Result<LanguageInfo, ApiError> {
+    let mgr = I18N_MANAGER.get().ok_or_else(|| ApiError::not_initialized("I18n manager"))?;
+    mgr.get_current_language_info().map_err(ApiError::settings)
+}
+
+/// Format a number with the separators of the current language, rounded to
+/// `decimals` places when given
+#[tauri::command]
+async fn i18n_format_number(value: f64, decimals: Option<usize>) -> Result<String, ApiError> {
+    let mgr = I18N_MANAGER.get().ok_or_else(|| ApiError::not_initialized("I18n manager"))?;
+    Ok(mgr.locale_format().format_number(value, decimals))
+}
+
+/// Format a Unix time as a local date and time in the current language
+#[tauri::command]
+async fn i18n_format_date(timestamp: u64) -> Result<String, ApiError> {
+    let mgr = I18N_MANAGER.get().ok_or_else(|| ApiError::not_initialized("I18n manager"))?;
+    Ok(mgr.format_timestamp(timestamp))
+}
+
+/// Get the layout direction of the current language
+#[tauri::command]
+async fn get_language_direction() -> Result<serde_json::Value, ApiError> {
+    let mgr = I18N_MANAGER.get().ok_or_else(|| ApiError::not_initialized("I18n manager"))?;
+    Ok(serde_json::json!({
+        "code": mgr.get_current_language().map_err(ApiError::settings)?,
+        "rtl": mgr.is_rtl().map_err(ApiError::settings)?
+    }))
+}
+
+/// Get full information about the current language
+#[tauri::command]
+async fn get_current_language_info() -> Result<LanguageInfo, ApiError> {
+    let mgr = I18N_MANAGER.get().ok_or_else(|| ApiError::not_initialized("I18n manager"))?;
+    mgr.get_current_language_info().map_err(ApiError::settings)
+}
+
+/// Reload translations from the bundled and user locale directories
+#[tauri::command]
+async fn reload_translations() -> Result<Vec<String>, ApiError> {
+    let mgr = I18N_MANAGER.get().ok_or_else(|| ApiError::not_initialized("I18n manager"))?;
+    mgr.reload_translations().map_err(ApiError::settings)
+}
+
+/// Get UI notifications
+#[tauri::command]
+async fn get_notifications() -> Result<Vec<serde_json::Value>, ApiError> {
+    if let Some(ui) = ADVANCED_UI_MANAGER.get().cloned() {
+        let items = ui.get_notifications();
+        let mapped: Vec<serde_json::Value> = items.into_iter().map(|n| serde_json::to_value(n).unwrap_or(serde_json::json!({}))).collect();
+        Ok(mapped)
+    } else {
+        Ok(vec![])
+    }
+}
+
+/// Mark a notification as read
+#[tauri::command]
+async fn notifications_mark_read(id: String) -> Result<bool, ApiError> {
+    let ui = ADVANCED_UI_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Advanced UI manager"))?;
+    Ok(ui.mark_notification_read(&id))
+}
+
+/// Dismiss a notification
+#[tauri::command]
+async fn notifications_dismiss(id: String) -> Result<bool, ApiError> {
+    let ui = ADVANCED_UI_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Advanced UI manager"))?;
+    Ok(ui.dismiss_notification(&id))
+}
+
+/// Deprecated: `notifications_mark_read`
+#[tauri::command]
+async fn mark_notification_read(id: String) -> Result<bool, ApiError> {
+    notifications_mark_read(id).await
+}
+
+/// Deprecated: `notifications_dismiss`
+#[tauri::command]
+async fn dismiss_notification(id: String) -> Result<bool, ApiError> {
+    notifications_dismiss(id).await
+}
+
+#[tauri::command]
+async fn theme_list() -> Result<Vec<UITheme>, ApiError> {
+    let ui = ADVANCED_UI_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Advanced UI manager"))?;
+    Ok(ui.get_themes())
+}
+
+#[tauri::command]
+async fn theme_get(name: String) -> Result<UITheme, ApiError> {
+    let ui = ADVANCED_UI_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Advanced UI manager"))?;
+    ui.get_theme(&name).ok_or_else(|| ApiError::not_found(format!("Unknown theme: {}", name)))
+}
+
+/// Select a theme, save the choice in the UI settings and emit `ui://theme-changed`
+#[tauri::command]
+async fn theme_set(app: AppHandle, name: String) -> Result<UITheme, ApiError> {
+    let ui = ADVANCED_UI_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Advanced UI manager"))?;
+    let settings = SETTINGS_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Settings manager"))?;
+    if ui.get_theme(&name).is_none() {
+        return Err(ApiError::not_found(format!("Unknown theme: {}", name)));
+    }
+    let theme = select_theme(ui, settings, &name).map_err(ApiError::settings)?;
+    let _ = app.emit("ui://theme-changed", &theme);
+    Ok(theme)
+}
+
+/// Go back to the default dark theme, save it in the UI settings and emit
+/// `ui://theme-changed`
+#[tauri::command]
+async fn theme_reset_to_default(app: AppHandle) -> Result<UITheme, ApiError> {
+    let ui = ADVANCED_UI_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Advanced UI manager"))?;
+    let settings = SETTINGS_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Settings manager"))?;
+    let theme = select_theme(ui, settings, DEFAULT_THEME).map_err(ApiError::settings)?;
+    let _ = app.emit("ui://theme-changed", &theme);
+    Ok(theme)
+}
+
+/// Add or replace a custom theme; entries it leaves out come from the dark or light theme
+#[tauri::command]
+async fn theme_create_custom(theme: UITheme) -> Result<UITheme, ApiError> {
+    let ui = ADVANCED_UI_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Advanced UI manager"))?;
+    let theme = resolve_custom_theme(theme).map_err(ApiError::invalid_input)?;
+    ui.add_theme(theme.clone());
+    let path = custom_themes_path().ok_or("Failed to get config directory")?;
+    save_custom_themes(ui, &path)?;
+    Ok(theme)
+}
+
+/// Number of notifications neither read nor dismissed
+#[tauri::command]
+async fn notifications_unread_count() -> Result<usize, ApiError> {
+    let ui = ADVANCED_UI_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Advanced UI manager"))?;
+    Ok(ui.unread_count())
+}
+
+#[tauri::command]
+async fn notifications_clear_all() -> Result<(), ApiError> {
+    let ui = ADVANCED_UI_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Advanced UI manager"))?;
+    ui.clear_notifications();
+    Ok(())
+}
+
+/// Run the backend side of a notification action and return where the frontend should go
+#[tauri::command]
+async fn notification_invoke_action(app: AppHandle, notification_id: String, action_id: String) -> Result<NavigationHint, ApiError> {
+    let ui = ADVANCED_UI_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Advanced UI manager"))?;
+    let action = ui.find_notification_action(&notification_id, &action_id)
+        .ok_or_else(|| format!("Notification action not found: {}/{}", notification_id, action_id))?;
+    let (operation, hint) = dispatch_action(&action)?;
+
+    if operation == NotificationOperation::RetryBackup {
+        // Progress and the outcome are reported through the backup:// events
+        tauri::async_runtime::spawn(async move {
+            let _ = backup_create(app, "Retried backup".to_string(), None, BackupType::Full).await;
+        });
+    }
+    ui.mark_notification_read(&notification_id);
+    Ok(hint)
+}
+
+// (Removed legacy deposit-address placeholder functions)
+
+/// Get network status (using real CryptoNote)
+#[tauri::command]
+async fn get_network_status() -> Result<serde_json::Value, ApiError> {
+    timed_command!("get_network_status", {
+        async_wallet::run("get_network_status", operation_timeout(OperationClass::Read), move || {
+            let mut real_wallet = RealCryptoNoteWallet::new();
+
+            let _ = real_wallet
+                .open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
+                .or_else(|_| real_wallet.create_wallet("fuego_password", "/tmp/fuego_wallet.wallet", None, 0));
+
+            // Only connect if not already connected
+            if let Err(e) = connect_to_fuego_network(&mut real_wallet) {
+                log::warn!("Network connect attempt failed: {}", e);
+            }
+
+            let mut status = real_wallet.get_network_status().map_err(ApiError::from)?;
+            if let Some(fields) = status.as_object_mut() {
+                fields.insert("connect_retry".to_string(), serde_json::to_value(connect_retry_state()).map_err(ApiError::from)?);
+                let drift = SYNC_LOOP.get().map(|control| control.drift()).unwrap_or_default();
+                fields.insert("sync_drift".to_string(), serde_json::to_value(drift).map_err(ApiError::from)?);
+            }
+            Ok(status)
+        })
+        .await
+    })
+}
+
+// ===== fuego-wallet compatibility aliases =====
+
+/// Create a wallet, refusing to replace an existing file unless `overwrite`.
+/// A new wallet's seed phrase is returned here and only here.
+#[tauri::command]
+async fn wallet_create(password: String, file_path: String, seed_phrase: Option<String>, restore_height: Option<u64>, overwrite: Option<bool>) -> Result<CreatedWallet, ApiError> {
+    timed_command!("wallet_create", {
+        let mut wallet = RealCryptoNoteWallet::new();
+        let created = wallet.create_new_wallet(&password, &file_path, seed_phrase.as_deref(), restore_height.unwrap_or(0), overwrite.unwrap_or(false))
+            .map_err(ApiError::from)?;
+        if let Some(sync_loop) = SYNC_LOOP.get() {
+            sync_loop.set_wallet_open(true);
+        }
+        Ok(created)
+    })
+}
+
+#[tauri::command]
+async fn wallet_open(file_path: String, password: String) -> Result<String, ApiError> {
+    timed_command!("wallet_open", {
+        demo::check_wallet_path(&file_path)?;
+        let mut wallet = RealCryptoNoteWallet::new();
+        wallet.open_wallet(&file_path, &password).map_err(ApiError::from)?;
+        let address = wallet.get_address().map_err(ApiError::from)?;
+        if let Some(sync_loop) = SYNC_LOOP.get() {
+            sync_loop.set_wallet_open(true);
+        }
+        Ok(address)
+    })
+}
+
+/// Open a wallet alongside those already open and return the id that
+/// commands take to work on it. A wallet already open returns its id.
+#[tauri::command]
+async fn wallet_open_named(file_path: String, password: String) -> Result<String, ApiError> {
+    timed_command!("wallet_open_named", {
+        demo::check_wallet_path(&file_path)?;
+        let registry = wallet_registry()?;
+        if let Some(id) = registry.id_for_path(&file_path) {
+            return Ok(id);
+        }
+        let previous = active_wallet_path();
+        let id = async_wallet::run("wallet_open_named", operation_timeout(OperationClass::Read), move || {
+            let mut wallet = RealCryptoNoteWallet::new();
+            wallet.open_wallet(&file_path, &password).map_err(ApiError::from)?;
+            Ok::<_, ApiError>(registry.register(&file_path, wallet)?)
+        })
+        .await?;
+        switch_wallet_state(&previous);
+        Ok(id)
+    })
+}
+
+/// Close one wallet opened by name; the others stay open
+#[tauri::command]
+async fn wallet_close_named(wallet_id: String) -> Result<(), ApiError> {
+    let registry = wallet_registry()?;
+    let previous = active_wallet_path();
+    async_wallet::run("wallet_close_named", operation_timeout(OperationClass::Read), move || {
+        Ok::<_, ApiError>(registry.close(&wallet_id)?)
+    })
+    .await?;
+    switch_wallet_state(&previous);
+    Ok(())
+}
+
+/// Wallets opened by name, with which one is active and how each last synced
+#[tauri::command]
+async fn wallet_list_open() -> Result<Vec<OpenWallet>, ApiError> {
+    Ok(wallet_registry()?.list())
+}
+
+/// Make a wallet opened by name the one commands without an id work on
+#[tauri::command]
+async fn wallet_set_active(wallet_id: String) -> Result<(), ApiError> {
+    let previous = active_wallet_path();
+    wallet_registry()?.set_active(&wallet_id)?;
+    switch_wallet_state(&previous);
+    Ok(())
+}
+
+/// Refresh one wallet opened by name now
+#[tauri::command]
+async fn wallet_sync_named(wallet_id: String) -> Result<WalletSyncState, ApiError> {
+    let wallet = wallet_registry()?.get(&wallet_id)?;
+    async_wallet::run("wallet_sync_named", operation_timeout(OperationClass::Sync), move || {
+        Ok(wallet.sync()?)
+    })
+    .await
+}
+
+fn wallet_registry() -> Result<Arc<WalletRegistry>, ApiError> {
+    WALLET_REGISTRY.get().cloned().ok_or_else(|| ApiError::not_initialized("Wallet registry"))
+}
+
+/// The wallet opened by name that `wallet_id` names, or the active one
+/// without an id; `None` when no wallet was opened by name
+fn registered_wallet(wallet_id: Option<&str>) -> Result<Option<Arc<RegisteredWallet>>, ApiError> {
+    match wallet_id {
+        Some(id) => Ok(Some(wallet_registry()?.get(id)?)),
+        None => Ok(WALLET_REGISTRY.get().and_then(|registry| registry.active())),
+    }
+}
+
+/// Run `f` on the active registered wallet, or on the default wallet file
+/// while none is registered. Commands called without a wallet id go through
+/// here so they work on the wallet the user switched to.
+fn with_active_wallet<T>(f: impl FnOnce(&mut RealCryptoNoteWallet) -> Result<T, ApiError>) -> Result<T, ApiError> {
+    if let Some(active) = WALLET_REGISTRY.get().and_then(|registry| registry.active()) {
+        return active.with_wallet(f);
+    }
+    let mut wallet = RealCryptoNoteWallet::new();
+    wallet.open_wallet(DEFAULT_WALLET_FILE, "fuego_password")
+        .or_else(|_| wallet.create_wallet("fuego_password", DEFAULT_WALLET_FILE, None, 0))
+        .map_err(|e| ApiError::from(e).context("Failed to open/create wallet"))?;
+    f(&mut wallet)
+}
+
+/// `with_active_wallet` for work that must not create a wallet: without a
+/// registered wallet the default wallet file has to exist
+fn with_open_wallet<T>(f: impl FnOnce(&mut RealCryptoNoteWallet) -> Result<T, ApiError>) -> Result<T, ApiError> {
+    if let Some(active) = WALLET_REGISTRY.get().and_then(|registry| registry.active()) {
+        return active.with_wallet(f);
+    }
+    if !Path::new(DEFAULT_WALLET_FILE).exists() {
+        return Err(ApiError::from(WalletError::WalletNotOpen));
+    }
+    let mut wallet = RealCryptoNoteWallet::new();
+    wallet.open_wallet(DEFAULT_WALLET_FILE, "fuego_password")
+        .map_err(|e| ApiError::from(e).context("Failed to open wallet"))?;
+    f(&mut wallet)
+}
+
+/// File of the active registered wallet, or the default wallet file while
+/// none is registered; per-wallet state is kept next to it
+fn active_wallet_path() -> PathBuf {
+    WALLET_REGISTRY.get()
+        .and_then(|registry| registry.active())
+        .map(|active| PathBuf::from(&active.file_path))
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_WALLET_FILE))
+}
+
+/// Close the active wallet, or the default one when none was opened by name
+#[tauri::command]
+async fn wallet_close() -> Result<(), ApiError> {
+    timed_command!("wallet_close", {
+        if let Some(active) = registered_wallet(None)? {
+            let registry = wallet_registry()?;
+            let previous = PathBuf::from(&active.file_path);
+            async_wallet::run("wallet_close", operation_timeout(OperationClass::Read), move || {
+                Ok::<_, ApiError>(registry.close(&active.id)?)
+            })
+            .await?;
+            switch_wallet_state(&previous);
+            return Ok(());
+        }
+        let mut wallet = RealCryptoNoteWallet::new();
+        // Best-effort: open then close. In a real implementation, use a shared instance.
+        let _ = wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password");
+        wallet.close_wallet();
+        if let Some(sync_loop) = SYNC_LOOP.get() {
+            sync_loop.set_wallet_open(false);
+        }
+        Ok(())
+    })
+}
+
+#[tauri::command]
+async fn wallet_get_info(force_refresh: Option<bool>) -> Result<serde_json::Value, ApiError> { get_wallet_info(force_refresh).await }
+
+/// Balance of the wallet `wallet_id`, or of the active wallet without one
+#[tauri::command]
+async fn wallet_get_balance(wallet_id: Option<String>) -> Result<u64, ApiError> {
+    timed_command!("wallet_get_balance", {
+        if let Some(registered) = registered_wallet(wallet_id.as_deref())? {
+            return async_wallet::run("wallet_get_balance", operation_timeout(OperationClass::Read), move || {
+                Ok(registered.with_wallet(|wallet| wallet.get_balance())?)
+            })
+            .await;
+        }
+        let mut wallet = RealCryptoNoteWallet::new();
+        let _ = wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
+            .or_else(|_| wallet.create_wallet("fuego_password", "/tmp/fuego_wallet.wallet", None, 0));
+        wallet.get_balance().map_err(ApiError::from)
+    })
+}
+
+/// Address of the wallet `wallet_id`, or of the active wallet without one
+#[tauri::command]
+async fn wallet_get_address(wallet_id: Option<String>) -> Result<String, ApiError> {
+    timed_command!("wallet_get_address", {
+        if let Some(registered) = registered_wallet(wallet_id.as_deref())? {
+            return async_wallet::run("wallet_get_address", operation_timeout(OperationClass::Read), move || {
+                Ok(registered.with_wallet(|wallet| wallet.get_address())?)
+            })
+            .await;
+        }
+        let mut wallet = RealCryptoNoteWallet::new();
+        let _ = wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
+            .or_else(|_| wallet.create_wallet("fuego_password", "/tmp/fuego_wallet.wallet", None, 0));
+        wallet.get_address().map_err(ApiError::from)
+    })
+}
+
+#[tauri::command]
+async fn wallet_get_transactions(limit: Option<u64>, offset: Option<u64>) -> Result<Vec<serde_json::Value>, ApiError> {
+    get_transactions(limit, offset).await
+}
+
+#[tauri::command]
+async fn wallet_send_transaction(
+    recipient: String,
+    amount: u64,
+    payment_id: Option<String>,
+    mixin: Option<u64>,
+    override_token: Option<String>,
+    selected_outputs: Option<Vec<String>>,
+) -> Result<String, ApiError> {
+    send_transaction(recipient, amount, payment_id, mixin.unwrap_or(5), override_token, selected_outputs).await
+}
+
+#[tauri::command]
+async fn wallet_refresh(app: AppHandle) -> Result<(), ApiError> {
+    refresh_wallet(Some(app)).await
+}
+
+/// Refresh the wallet, reporting progress to `app` when there is one
+async fn refresh_wallet(app: Option<AppHandle>) -> Result<(), ApiError> {
+    timed_command!("wallet_refresh", {
+        let coordinator = REFRESH_COORDINATOR.get().ok_or_else(|| ApiError::not_initialized("Refresh coordinator"))?.clone();
+        async_wallet::run("refresh", operation_timeout(OperationClass::Sync), move || {
+            coordinator.refresh(|| {
+                let reporter = forward_progress(app, "refresh", None);
+                with_active_wallet(|wallet| {
+                    wallet.refresh_with_progress(Some(&reporter))?;
+                    if let Err(e) = record_balance_snapshot(wallet) {
+                        log::warn!("Failed to record balance snapshot: {}", e);
+                    }
+                    Ok(())
+                })
+                .map_err(|e| e.to_string())
+            })
+            .map_err(|e| ApiError::new(ErrorCode::SyncFailed, e))
+        })
+        .await
+    })
+}
+
+#[tauri::command]
+async fn wallet_rescan(app: AppHandle, start_height: Option<u64>) -> Result<(), ApiError> {
+    rescan_wallet(Some(app), start_height).await
+}
+
+/// Rescan from `start_height` and sync, reporting progress to `app` when there is one
+async fn rescan_wallet(app: Option<AppHandle>, start_height: Option<u64>) -> Result<(), ApiError> {
+    timed_command!("wallet_rescan", {
+        let wallet_manager = ADVANCED_WALLET_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Advanced wallet manager"))?.clone();
+        let start_height = start_height.unwrap_or(0);
+        let operation_id = wallet_manager.start_operation("rescan");
+        wallet_manager.set_operation_detail(&operation_id, "start_height", &start_height.to_string());
+
+        let reporter = forward_progress(app, "rescan", Some(operation_id.clone()));
+        let result: Result<u64, ApiError> = async_wallet::run_cancellable("rescan", operation_timeout(OperationClass::Sync), move |cancelled| {
+            with_active_wallet(|wallet| {
+                wallet.rescan_blockchain(start_height, Some(&reporter))?;
+
+                // Keep refreshing until synced, stopping early if the height stalls
+                // or nobody waits for the rescan any more
+                let mut last_height = None;
+                loop {
+                    let progress = wallet.refresh_with_progress(Some(&reporter))?;
+                    if !progress.is_syncing || last_height == Some(progress.current_height) || cancelled.load(std::sync::atomic::Ordering::SeqCst) {
+                        return Ok(progress.current_height);
+                    }
+                    last_height = Some(progress.current_height);
+                }
+            })
+        })
+        .await;
+
+        match result {
+            Ok(height) => {
+                wallet_manager.end_operation(&operation_id, "completed", Some(format!("Synced to height {}", height)), None);
+                Ok(())
+            }
+            Err(e) => {
+                wallet_manager.end_operation(&operation_id, "failed", None, Some(e.message.clone()));
+                Err(e)
+            }
+        }
+    })
+}
+
+/// Recorded wallet operations, newest first, optionally only those with `status_filter`
+#[tauri::command]
+async fn operations_list(limit: Option<usize>, status_filter: Option<String>) -> Result<Vec<OperationEntry>, ApiError> {
+    let manager = ADVANCED_WALLET_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Advanced wallet manager"))?;
+    Ok(manager.list_operations(limit, status_filter.as_deref()))
+}
+
+/// Write the operation history to `path` as JSON; returns the number of operations
+#[tauri::command]
+async fn operations_export(path: String) -> Result<usize, ApiError> {
+    let manager = ADVANCED_WALLET_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Advanced wallet manager"))?;
+    Ok(manager.export_operations(Path::new(&path))?)
+}
+
+/// Emit notification changes as `notification` / `notification-updated` events
+fn forward_notifications(app: AppHandle, receiver: std::sync::mpsc::Receiver<NotificationEvent>) {
+    tauri::async_runtime::spawn_blocking(move || {
+        for event in receiver {
+            let _ = match event {
+                NotificationEvent::Added(notification) => app.emit("notification", &notification),
+                NotificationEvent::Updated(notification) => app.emit("notification-updated", &notification),
+                NotificationEvent::Cleared => app.emit("notifications-cleared", ()),
+            };
+        }
+    });
+}
+
+/// Create a progress reporter whose updates are emitted as `<operation>://progress`
+/// events and, given an operation ID, recorded as that operation's progress
+fn forward_progress(app: Option<AppHandle>, operation: &str, operation_id: Option<String>) -> ProgressReporter {
+    let (reporter, receiver) = ProgressReporter::channel(operation);
+    let event = format!("{}://progress", operation);
+    tauri::async_runtime::spawn_blocking(move || {
+        for update in receiver {
+            if let (Some(id), Some(manager)) = (&operation_id, ADVANCED_WALLET_MANAGER.get()) {
+                manager.update_operation_progress(id, f64::from(update.percent) / 100.0);
+            }
+            if let Some(app) = &app {
+                let _ = app.emit(&event, &update);
+            }
+        }
+    });
+    reporter
+}
+
+#[tauri::command]
+async fn network_get_status() -> Result<serde_json::Value, ApiError> { get_network_status().await }
+
+#[tauri::command]
+async fn node_connect(address: Option<String>, port: Option<u16>) -> Result<(), ApiError> {
+    timed_command!("node_connect", {
+        cancel_connect_retries();
+        let policy = RetryPolicy::network("node_connect", None);
+        retry_async(&policy, || {
+            let address = address.clone();
+            async_wallet::run_cancellable("node_connect", operation_timeout(OperationClass::Connect), move |cancelled| {
+                // Connect failures stay `WalletError`s, which the retry policy classifies
+                with_active_wallet(|wallet| Ok(match &address {
+                    Some(addr) => wallet.connect_to_node(addr, port.unwrap_or(18180)),
+                    None => connect_to_fuego_network_until(wallet, cancelled),
+                }))
+                .unwrap_or_else(|e| Err(WalletError::WalletOpenFailed(e.message)))
+            })
+        })
+        .await
+        .map_err(ApiError::from)
+    })
+}
+
+/// Check a node before saving it: connect, read its height and version and
+/// measure latency, without touching the wallet's connection
+#[tauri::command]
+async fn node_test(host: String, port: u16) -> Result<serde_json::Value, ApiError> {
+    timed_command!("node_test", {
+        let host = host.trim();
+        if host.is_empty() {
+            return Err(ApiError::invalid_input("Node host is empty"));
+        }
+        let report = crate::crypto::daemon::test_node(host, port, default_connect_timeout()).await;
+        serde_json::to_value(report).map_err(|e| ApiError::from(e).context("Failed to serialize node test report"))
+    })
+}
+
+#[tauri::command]
+async fn node_disconnect() -> Result<(), ApiError> {
+    timed_command!("node_disconnect", {
+        with_active_wallet(|wallet| Ok(wallet.disconnect()?))
+    })
+}
+
+#[tauri::command]
+async fn deposit_list() -> Result<Vec<serde_json::Value>, ApiError> { get_term_deposits().await }
+
+#[tauri::command]
+async fn deposit_create(amount: u64, term: u32) -> Result<String, ApiError> { create_term_deposit(amount, term).await }
+
+#[tauri::command]
+async fn deposit_withdraw(deposit_id: String) -> Result<String, ApiError> { withdraw_term_deposit(deposit_id).await }
+
+/// Fee of sending `amount` to `address`, with the estimated size and fee per kilobyte
+#[tauri::command]
+async fn estimate_fee(address: String, amount: u64, mixin: Option<u64>) -> Result<FeeEstimate, ApiError> {
+    timed_command!("estimate_fee", {
+        let breakdown = with_active_wallet(|real_wallet| Ok(real_wallet.estimate_fee_breakdown(&address, amount, mixin.unwrap_or(5))?))?;
+        Ok(FeeEstimate::new(breakdown.total, breakdown.estimated_size_bytes))
+    })
+}
+
+/// Fee of sending `amount` to `address` split into base, size and priority
+/// components, with the estimated transaction size for the chosen mixin
+#[tauri::command]
+async fn estimate_fee_detailed(address: String, amount: u64, mixin: Option<u64>) -> Result<FeeBreakdown, ApiError> {
+    timed_command!("estimate_fee_detailed", {
+        with_active_wallet(|real_wallet| {
+            real_wallet.estimate_fee_breakdown(&address, amount, mixin.unwrap_or(5))
+                .map_err(|e| ApiError::from(e).context("Failed to estimate fee"))
+        })
+    })
+}
+
+/// Sent transactions scanned by `fee_analytics`
+const FEE_ANALYTICS_HISTORY_WINDOW: u64 = 100_000;
+
+const FEE_ANALYTICS_CACHE_KEY: &str = "fee_analytics";
+const FEE_ANALYTICS_TTL: Duration = Duration::from_secs(60);
+
+/// Fees paid by the sent transactions of the history: totals, average, share
+/// of the amount sent, a monthly series and the most expensive transaction.
+/// Transactions with a recorded fee priority are compared with the slow
+/// priority estimate for the same transfer.
+#[tauri::command]
+async fn fee_analytics() -> Result<FeeAnalytics, ApiError> {
+    timed_command!("fee_analytics", {
+        let cache = CACHE.get().ok_or_else(|| ApiError::not_initialized("Cache"))?;
+        if let Some(analytics) = cache.get(FEE_ANALYTICS_CACHE_KEY).and_then(|value| serde_json::from_value(value).ok()) {
+            return Ok(analytics);
+        }
+
+        let analytics = async_wallet::run("fee_analytics", operation_timeout(OperationClass::Read), move || {
+            let mut real_wallet = RealCryptoNoteWallet::new();
+            real_wallet.open_wallet(DEFAULT_WALLET_FILE, "fuego_password")
+                .or_else(|_| real_wallet.create_wallet("fuego_password", DEFAULT_WALLET_FILE, None, 0))
+                .map_err(|e| ApiError::from(e).context("Failed to open/create wallet"))?;
+
+            let transactions = real_wallet.get_transaction_history(FEE_ANALYTICS_HISTORY_WINDOW, 0)
+                .map_err(|e| ApiError::from(e).context("Failed to get transaction history"))?;
+            let store = TX_METADATA_STORE.get();
+            let own_address = real_wallet.get_address().unwrap_or_default();
+            Ok::<_, ApiError>(FeeAnalytics::compute(
+                &transactions,
+                |hash| store.and_then(|s| s.get(hash)).and_then(|metadata| metadata.priority),
+                |tx| {
+                    let address = tx.destination_addresses.first().unwrap_or(&own_address);
+                    real_wallet.estimate_fee_breakdown(address, tx.amount.unsigned_abs(), 5)
+                        .map(|breakdown| breakdown.base_fee + breakdown.size_fee)
+                        .map_err(|e| log::debug!("No slow fee estimate for {}: {}", tx.hash, e))
+                        .ok()
+                },
+            ))
+        })
+        .await?;
+
+        cache.set_with_ttl(FEE_ANALYTICS_CACHE_KEY.to_string(), serde_json::to_value(&analytics)?, FEE_ANALYTICS_TTL);
+        Ok(analytics)
+    })
+}
+
+/// Amount left to send and fee when sweeping the whole unlocked balance
+#[tauri::command]
+async fn estimate_sweep_fee(destination: String, mixin: Option<u64>) -> Result<serde_json::Value, ApiError> {
+    timed_command!("estimate_sweep_fee", {
+        let mut real_wallet = RealCryptoNoteWallet::new();
+        real_wallet.open_wallet(DEFAULT_WALLET_FILE, "fuego_password")
+            .or_else(|_| real_wallet.create_wallet("fuego_password", DEFAULT_WALLET_FILE, None, 0))
+            .map_err(|e| ApiError::from(e).context("Failed to open/create wallet"))?;
+
+        let frozen = frozen_outputs()?.key_images();
+        match real_wallet.estimate_sweep(&destination, mixin.unwrap_or(5), &frozen) {
+            Ok(estimate) => Ok(serde_json::to_value(estimate)?),
+            Err(WalletError::InsufficientFunds) => {
+                Err(ApiError::new(ErrorCode::InsufficientFunds, "Balance too low to sweep: the unlocked balance does not cover the fee"))
+            }
+            Err(e) => Err(ApiError::from(e).context("Failed to estimate sweep fee")),
+        }
+    })
+}
+
+/// Standard address and payment ID of an integrated address
+#[tauri::command]
+async fn split_integrated_address(address: String) -> Result<serde_json::Value, ApiError> {
+    let (standard_address, payment_id) = address::split_integrated_address(address.trim())?;
+    Ok(serde_json::json!({
+        "address": standard_address,
+        "payment_id": payment_id
+    }))
+}
+
+#[tauri::command]
+async fn validate_address(address: String) -> Result<bool, ApiError> {
+    // Real validation: attempt lightweight checks and delegate to CryptoNote wallet if available
+    // 1) Prefix, length and base58 charset sanity
+    if !is_valid_address_format(&address) {
+        return Ok(false);
+    }
+    // 2) Ask wallet to accept address in fee estimator (no-op but validates formatting at native layer)
+    let mut wallet = RealCryptoNoteWallet::new();
+    let _ = wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
+        .or_else(|_| wallet.create_wallet("fuego_password", "/tmp/fuego_wallet.wallet", None, 0));
+    let mixin = 5u64;
+    match wallet.estimate_transaction_fee(&address, 1, mixin) {
+        Ok(_) => Ok(true),
+        Err(_) => Ok(false),
+    }
+}
+
+/// Check a scanned `fuego:` URI before it fills in the send form, returning
+/// its address, amount in atomic units and XFG, payment ID and label.
+/// Malformed URIs, bad addresses and bad amounts fail with their own codes.
+#[tauri::command]
+async fn parse_and_validate_payment_uri(uri: String) -> Result<ValidatedPaymentUri, ApiError> {
+    Ok(PaymentUri::validate(&uri)?)
+}
+
+/// Test FFI integration
+#[tauri::command]
+async fn test_ffi_integration() -> Result<serde_json::Value, ApiError> {
+    let mut ffi = CryptoNoteFFI::new();
+    let mut report = smoke_test(&mut ffi, "test_password", "/tmp/test.wallet")
+        .map_err(|e| ApiError::from(e).context("FFI integration failed"))?;
+
+    report["status"] = "success".into();
+    report["message"] = "FFI integration working correctly".into();
+    Ok(report)
+}
+
+/// Test real CryptoNote integration on the selected backend
+#[tauri::command]
+async fn test_real_cryptonote() -> Result<serde_json::Value, ApiError> {
+    async_wallet::run("test_real_cryptonote", operation_timeout(OperationClass::Connect), move || {
+        let mut backend = new_backend(selected_backend());
+        let mut report = smoke_test(backend.as_mut(), "test_password", "/tmp/test_real.wallet")
+            .map_err(|e| ApiError::from(e).context("Real CryptoNote integration failed"))?;
+
+        // Test network connection
+        let mut real_wallet = RealCryptoNoteWallet::with_backend(backend);
+        let network_result = connect_to_fuego_network(&mut real_wallet);
+        let network_status = real_wallet.get_network_status().map_err(ApiError::from)?;
+
+        report["status"] = "success".into();
+        report["message"] = "Real CryptoNote integration working correctly".into();
+        report["network"] = serde_json::json!({
+            "connection_result": if network_result.is_ok() { "success" } else { "failed" },
+            "status": network_status
+        });
+        Ok(report)
+    })
+    .await
+}
+
+/// Get real Fuego network data from fuego.spaceportx.net
+#[tauri::command]
+async fn get_fuego_network_data() -> Result<serde_json::Value, ApiError> {
+    timed_command!("get_fuego_network_data", {
+        let client = DAEMON_CLIENT.get().ok_or_else(|| ApiError::not_initialized("Daemon client"))?;
+        match fetch_fuego_network_data(client).await {
+            Ok(data) => {
+                log::info!("Fetched real Fuego network data: height={}, peers={}", 
+                          data["height"], data["peer_count"]);
+                Ok(data)
+            }
+            Err(e) => {
+                log::error!("Failed to fetch Fuego network data: {}", e);
+                Err(ApiError::from(e).context("Failed to fetch network data"))
+            }
+        }
+    })
+}
+
+/// Get daemon and chain info from the live daemon (cached briefly; `stale` when
+/// the daemon is unreachable) and fill the daemon fields of the network info
+#[tauri::command]
+async fn get_daemon_info() -> Result<DaemonInfo, ApiError> {
+    timed_command!("get_daemon_info", {
+        let client = DAEMON_CLIENT.get().ok_or_else(|| ApiError::not_initialized("Daemon client"))?;
+        let info = client.get_info().await.map_err(|e| ApiError::from(e).context("Failed to get daemon info"))?;
+
+        if let Some(manager) = ADVANCED_WALLET_MANAGER.get()
+            && let Some(mut network_info) = manager.get_network_info()
+        {
+            info.apply_to(&mut network_info);
+            manager.update_network_info(network_info);
+        }
+        Ok(info)
+    })
+}
+
+/// Peers of the node in use: address, port, direction, height, last seen and
+/// average latency. Nodes that cannot list them give none and a warning.
+#[tauri::command]
+async fn network_get_peers() -> Result<PeerList, ApiError> {
+    timed_command!("network_get_peers", {
+        let client = DAEMON_CLIENT.get().ok_or_else(|| ApiError::not_initialized("Daemon client"))?;
+        client.get_peers().await.map_err(|e| ApiError::from(e).context("Failed to list peers"))
+    })
+}
+
+/// Have the node in use refuse the peer at `address` for `seconds`
+#[tauri::command]
+async fn network_ban_peer(address: String, seconds: u64) -> Result<(), ApiError> {
+    if seconds == 0 {
+        return Err(ApiError::invalid_input("Ban duration must be at least one second"));
+    }
+    let client = DAEMON_CLIENT.get().ok_or_else(|| ApiError::not_initialized("Daemon client"))?;
+    client.set_peer_ban(&address, seconds).await
+        .map_err(|e| ApiError::from(e).context("Failed to ban peer"))?;
+    log::info!("Banned peer {} for {}s", address, seconds);
+    Ok(())
+}
+
+/// Lift the ban of the peer at `address` on the node in use
+#[tauri::command]
+async fn network_unban_peer(address: String) -> Result<(), ApiError> {
+    let client = DAEMON_CLIENT.get().ok_or_else(|| ApiError::not_initialized("Daemon client"))?;
+    client.set_peer_ban(&address, 0).await
+        .map_err(|e| ApiError::from(e).context("Failed to unban peer"))?;
+    log::info!("Unbanned peer {}", address);
+    Ok(())
+}
+
+/// Block reward and coin emission at the chain tip, cached until the next
+/// block. Daemons without emission data give `supported: false` and null
+/// figures rather than an error.
+#[tauri::command]
+async fn get_emission_info() -> Result<EmissionInfo, ApiError> {
+    timed_command!("get_emission_info", {
+        async_wallet::run("get_emission_info", operation_timeout(OperationClass::Read), move || {
+            let mut real_wallet = RealCryptoNoteWallet::new();
+
+            real_wallet.open_wallet(DEFAULT_WALLET_FILE, "fuego_password")
+                .or_else(|_| real_wallet.create_wallet("fuego_password", DEFAULT_WALLET_FILE, None, 0))
+                .map_err(|e| ApiError::from(e).context("Failed to open/create wallet"))?;
+
+            if let Err(e) = connect_to_fuego_network(&mut real_wallet) {
+                log::warn!("Failed to connect to Fuego network: {}", e);
+            }
+
+            let height = real_wallet.get_network_info()
+                .map_err(|e| ApiError::from(e).context("Failed to get network info"))?
+                .network_height;
+            let cache = CACHE.get().ok_or_else(|| ApiError::not_initialized("Cache"))?;
+            let key = format!("emission:{}", height);
+            if let Some(emission) = cache.get(&key).and_then(|value| serde_json::from_value(value).ok()) {
+                return Ok(emission);
+            }
+
+            let emission = real_wallet.get_emission_info()
+                .map_err(|e| ApiError::from(e).context("Failed to get emission info"))?;
+            cache.set_with_ttl(key, serde_json::to_value(&emission)?, Duration::from_secs(TARGET_BLOCK_TIME));
+
+            if let Some(block_reward) = emission.block_reward
+                && let Some(manager) = ADVANCED_WALLET_MANAGER.get()
+                && let Some(mut network_info) = manager.get_network_info()
+            {
+                network_info.block_reward = block_reward;
+                manager.update_network_info(network_info);
+            }
+            Ok(emission)
+        })
+        .await
+    })
+}
+
+/// Term deposit limits at the chain tip, cached until the next block. Daemons
+/// that do not report them, or fail to, give the built-in defaults.
+fn deposit_limits(wallet: &RealCryptoNoteWallet) -> Result<DepositLimits, ApiError> {
+    let height = wallet.get_network_info()
+        .map_err(|e| ApiError::from(e).context("Failed to get network info"))?
+        .network_height;
+    let cache = CACHE.get().ok_or_else(|| ApiError::not_initialized("Cache"))?;
+    let key = format!("deposit_limits:{}", height);
+    if let Some(limits) = cache.get(&key).and_then(|value| serde_json::from_value(value).ok()) {
+        return Ok(limits);
+    }
+
+    let limits = wallet.get_deposit_limits().unwrap_or_else(|e| {
+        log::warn!("Failed to get deposit limits, using the defaults: {}", e);
+        DepositLimits::defaults(height)
+    });
+    cache.set_with_ttl(key, serde_json::to_value(&limits)?, Duration::from_secs(TARGET_BLOCK_TIME));
+    Ok(limits)
+}
+
+/// Minimum amount, term range and rate table of term deposits, for the UI to
+/// show valid ranges
+#[tauri::command]
+async fn get_deposit_limits() -> Result<DepositLimits, ApiError> {
+    timed_command!("get_deposit_limits", {
+        async_wallet::run("get_deposit_limits", operation_timeout(OperationClass::Read), move || {
+            let mut real_wallet = RealCryptoNoteWallet::new();
+
+            real_wallet.open_wallet(DEFAULT_WALLET_FILE, "fuego_password")
+                .or_else(|_| real_wallet.create_wallet("fuego_password", DEFAULT_WALLET_FILE, None, 0))
+                .map_err(|e| ApiError::from(e).context("Failed to open/create wallet"))?;
+
+            if let Err(e) = connect_to_fuego_network(&mut real_wallet) {
+                log::warn!("Failed to connect to Fuego network: {}", e);
+            }
+
+            deposit_limits(&real_wallet)
+        })
+        .await
+    })
+}
+
+/// Renew a term deposit automatically when it matures, for `term` days or
+/// the deposit's own term, or stop doing so
+#[tauri::command]
+async fn deposit_set_autorenew(deposit_id: String, enabled: bool, term: Option<u32>) -> Result<Option<RenewalOption>, ApiError> {
+    timed_command!("deposit_set_autorenew", {
+        let store = DEPOSIT_RENEWALS.get().ok_or_else(|| ApiError::not_initialized("Deposit renewals"))?.clone();
+        if enabled {
+            let id = deposit_id.clone();
+            async_wallet::run("deposit_set_autorenew", operation_timeout(OperationClass::Read), move || {
+                let mut real_wallet = RealCryptoNoteWallet::new();
+                real_wallet.open_wallet(DEFAULT_WALLET_FILE, "fuego_password")
+                    .map_err(|e| ApiError::from(e).context("Failed to open wallet"))?;
+                if let Err(e) = connect_to_fuego_network(&mut real_wallet) {
+                    log::warn!("Failed to connect to Fuego network: {}", e);
+                }
+
+                let deposits = real_wallet.get_deposits()
+                    .map_err(|e| ApiError::from(e).context("Failed to get deposits"))?;
+                match deposits.iter().find(|deposit| deposit.id == id) {
+                    None => return Err(ApiError::not_found(format!("Deposit not found: {}", id))),
+                    Some(deposit) if deposit.status == "spent" => {
+                        return Err(ApiError::invalid_input(format!("Deposit {} was already withdrawn", id)));
+                    }
+                    Some(_) => {}
+                }
+                if let Some(term) = term {
+                    let limits = deposit_limits(&real_wallet)?;
+                    if term < limits.min_term || term > limits.max_term {
+                        return Err(ApiError::invalid_input(format!("Term must be between {} and {} days", limits.min_term, limits.max_term)));
+                    }
+                }
+                Ok(())
+            })
+            .await?;
+        }
+        Ok(store.set(&deposit_id, enabled, term)?)
+    })
+}
+
+/// Check a wallet file without opening it for use or changing it. A corrupted
+/// file gives a `wallet_corrupted` error whose details suggest restoring from
+/// the seed or from a backup.
+#[tauri::command]
+async fn wallet_verify_integrity(file_path: String, password: String) -> Result<WalletIntegrityReport, ApiError> {
+    timed_command!("wallet_verify_integrity", {
+        let report = tauri::async_runtime::spawn_blocking(move || {
+            RealCryptoNoteWallet::new().verify_integrity(&file_path, &password)
+        }).await?;
+        let report = report.map_err(|e| ApiError::from(e).context("Wallet integrity check failed"))?;
+        if report.rescan_recommended {
+            log::warn!("Wallet {} needs a rescan from height {}", report.file_path, report.last_processed_height);
+        }
+        Ok(report)
+    })
+}
+
+/// How long a wallet operation of `class` may block before it is abandoned
+fn operation_timeout(class: OperationClass) -> Duration {
+    SETTINGS_MANAGER.get()
+        .and_then(|mgr| mgr.get_settings().ok())
+        .map(|settings| settings.network.operation_timeouts)
+        .unwrap_or_default()
+        .for_class(class)
+}
+
+/// Refuse spending operations while the wallet is locked
+fn ensure_wallet_unlocked() -> Result<(), ApiError> {
+    let lock = WALLET_LOCK.get().ok_or_else(|| ApiError::not_initialized("Wallet lock"))?;
+    Ok(lock.ensure_unlocked()?)
+}
+
+/// Lock the wallet: viewing keeps working, spending is refused until unlocked
+#[tauri::command]
+async fn wallet_lock(app: AppHandle) -> Result<(), ApiError> {
+    lock_wallet(Some(&app)).await
+}
+
+async fn lock_wallet(app: Option<&AppHandle>) -> Result<(), ApiError> {
+    let lock = WALLET_LOCK.get().ok_or_else(|| ApiError::not_initialized("Wallet lock"))?;
+    if lock.lock(LockReason::Manual) && let Some(app) = app {
+        let _ = app.emit("wallet-locked", serde_json::json!({ "reason": LockReason::Manual }));
+    }
+    Ok(())
+}
+
+/// Unlock the wallet after re-checking the password
+#[tauri::command]
+async fn wallet_unlock(app: AppHandle, password: String) -> Result<(), ApiError> {
+    unlock_wallet(Some(&app), password).await
+}
+
+async fn unlock_wallet(app: Option<&AppHandle>, password: String) -> Result<(), ApiError> {
+    let lock = WALLET_LOCK.get().ok_or_else(|| ApiError::not_initialized("Wallet lock"))?;
+    let security_manager = SECURITY_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Security manager"))?;
+    lock.unlock(&password, |p| security_manager.verify_password(p))
+        .map_err(ApiError::from)?;
+    if let Some(app) = app {
+        let _ = app.emit("wallet-unlocked", ());
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn wallet_is_locked() -> Result<bool, ApiError> {
+    let lock = WALLET_LOCK.get().ok_or_else(|| ApiError::not_initialized("Wallet lock"))?;
+    Ok(lock.is_locked())
+}
+
+/// Record user activity so the wallet is not locked as idle
+#[tauri::command]
+async fn wallet_record_activity() -> Result<(), ApiError> {
+    let lock = WALLET_LOCK.get().ok_or_else(|| ApiError::not_initialized("Wallet lock"))?;
+    lock.touch();
+    Ok(())
+}
+
+/// Block sends above the configured `security.max_send_atomic` cap unless a
+/// valid token from `authorize_large_send` is supplied
+fn enforce_send_cap(amount: u64, override_token: Option<&str>) -> Result<(), ApiError> {
+    let settings = SETTINGS_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Settings manager"))?;
+    let cap = settings.get_settings().map_err(ApiError::settings)?.security.max_send_atomic;
+    let security_manager = SECURITY_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Security manager"))?;
+    Ok(security_manager.check_send_cap(amount, cap, override_token)?)
+}
+
+/// Authorize a single send above the safety cap; the returned token is valid for 60 seconds
+#[tauri::command]
+async fn authorize_large_send(session_id: String, password: String) -> Result<String, ApiError> {
+    let security_manager = SECURITY_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Security manager"))?;
+    security_manager.authorize_large_send(&session_id, &password).map_err(ApiError::security)
+}
+
+/// What `send_transaction` with the same arguments would cost: fee, estimated
+/// size and fee per kilobyte. Fails when the transaction would be too large.
+#[tauri::command]
+async fn send_transaction_preview(
+    recipient: String,
+    amount: u64,
+    mixin: u64,
+    selected_outputs: Option<Vec<String>>,
+) -> Result<TransactionPreview, ApiError> {
+    timed_command!("send_transaction_preview", {
+        let inputs = selected_outputs.map(|key_images| key_images.len() as u64).filter(|&n| n > 0);
+        async_wallet::run("send_transaction_preview", operation_timeout(OperationClass::Read), move || {
+            let mut real_wallet = RealCryptoNoteWallet::new();
+            real_wallet.open_wallet(DEFAULT_WALLET_FILE, "fuego_password")
+                .map_err(|e| ApiError::from(e).context("Failed to open wallet"))?;
+            real_wallet.preview_send(&recipient, amount, mixin, inputs)
+                .map_err(|e| ApiError::from(e).context("Failed to preview transaction"))
+        })
+        .await
+    })
+}
+
+/// Send a transaction
+#[tauri::command]
+async fn send_transaction(
+    recipient: String,
+    amount: u64,
+    payment_id: Option<String>,
+    mixin: u64,
+    override_token: Option<String>,
+    selected_outputs: Option<Vec<String>>,
+) -> Result<String, ApiError> {
+    timed_command!("send_transaction", {
+        ensure_wallet_unlocked()?;
+        let frozen = frozen_outputs()?.key_images();
+        enforce_send_cap(amount, override_token.as_deref())?;
+        let inputs = selected_outputs.as_ref().map(|key_images| key_images.len() as u64).filter(|&n| n > 0);
+        check_transaction_size(estimate_transaction_size(1, mixin, inputs))?;
+        // An integrated address brings its own payment ID
+        let (recipient, payment_id) = resolve_payment_id(&recipient, payment_id.as_deref())?;
+
+        let (to, pid) = (recipient.clone(), payment_id.clone());
+        let tx_hash = async_wallet::run("send_transaction", operation_timeout(OperationClass::Send), move || {
+            with_active_wallet(|real_wallet| {
+                // Connect to Fuego network
+                if let Err(e) = connect_to_fuego_network(real_wallet) {
+                    log::warn!("Failed to connect to Fuego network: {}", e);
+                    // Continue without network connection
+                }
+
+                check_spendable(real_wallet, amount)?;
+
+                // Send transaction, from the chosen outputs only if there are any,
+                // and never from frozen ones
+                let selected = selected_outputs.as_deref().filter(|key_images| !key_images.is_empty());
+                let tx_hash = real_wallet.send_coin_controlled(&to, amount, pid.as_deref(), mixin, selected, &frozen)
+                .map_err(|e| {
+                    log::error!("Failed to send transaction: {}", e);
+                    ApiError::from(e).context("Failed to send transaction")
+                })?;
+                real_wallet.record_contacts_used(std::slice::from_ref(&to));
+                Ok(tx_hash)
+            })
+        })
+        .await?;
+
+        log::info!("Transaction sent successfully: {}", tx_hash);
+        track_sent_transaction(&tx_hash, amount, vec![recipient], payment_id);
+        Ok(tx_hash)
+    })
+}
+
+/// Send to several recipients in a single transaction
+#[tauri::command]
+async fn send_many(outputs: Vec<TransferDestination>, mixin: Option<u64>, override_token: Option<String>) -> Result<String, ApiError> {
+    timed_command!("send_many", {
+        ensure_wallet_unlocked()?;
+        let frozen = frozen_outputs()?.key_images();
+        let total = outputs.iter().fold(0u64, |sum, o| sum.saturating_add(o.amount));
+        enforce_send_cap(total, override_token.as_deref())?;
+        check_transaction_size(estimate_transaction_size(outputs.len() as u64, mixin.unwrap_or(5), None))?;
+
+        let outputs: Vec<(String, u64)> = outputs.into_iter().map(|o| (o.address, o.amount)).collect();
+        let batch = outputs.clone();
+        let tx_hash = async_wallet::run("send_many", operation_timeout(OperationClass::Send), move || {
+            with_active_wallet(|real_wallet| {
+                if let Err(e) = connect_to_fuego_network(real_wallet) {
+                    log::warn!("Failed to connect to Fuego network: {}", e);
+                }
+
+                check_spendable(real_wallet, total)?;
+                let tx_hash = real_wallet.send_many(&batch, mixin.unwrap_or(5), &frozen).map_err(|e| {
+                    log::error!("Failed to send batch transaction: {}", e);
+                    ApiError::from(e).context("Failed to send transaction")
+                })?;
+                let recipients: Vec<String> = batch.into_iter().map(|(address, _)| address).collect();
+                real_wallet.record_contacts_used(&recipients);
+                Ok(tx_hash)
+            })
+        })
+        .await?;
+
+        log::info!("Batch transaction sent successfully: {}", tx_hash);
+        let destinations = outputs.into_iter().map(|(address, _)| address).collect();
+        track_sent_transaction(&tx_hash, total, destinations, None);
+        Ok(tx_hash)
+    })
+}
+
+/// Send the entire unlocked balance, minus the fee, to one address. The
+/// swept amount is held to the send cap like any other send.
+#[tauri::command]
+async fn sweep_all(destination: String, mixin: Option<u64>, override_token: Option<String>) -> Result<String, ApiError> {
+    timed_command!("sweep_all", {
+        ensure_wallet_unlocked()?;
+        let frozen = frozen_outputs()?.key_images();
+        let recipient = destination.clone();
+        let (tx_hash, swept) = async_wallet::run("sweep_all", operation_timeout(OperationClass::Send), move || {
+            with_active_wallet(|real_wallet| {
+                if let Err(e) = connect_to_fuego_network(real_wallet) {
+                    log::warn!("Failed to connect to Fuego network: {}", e);
+                }
+
+                // The swept amount depends on the fee, so estimate it before checking the cap
+                let estimate = real_wallet.estimate_sweep(&destination, mixin.unwrap_or(5), &frozen)
+                    .map_err(|e| ApiError::from(e).context("Failed to sweep balance"))?;
+                enforce_send_cap(estimate.sendable_amount, override_token.as_deref())?;
+                check_spendable(real_wallet, estimate.total)?;
+
+                match real_wallet.sweep_all(&destination, mixin.unwrap_or(5), &frozen) {
+                    Ok(tx_hash) => {
+                        log::info!("Swept unlocked balance to {}: {}", destination, tx_hash);
+                        real_wallet.record_contacts_used(std::slice::from_ref(&destination));
+                        Ok((tx_hash, estimate.sendable_amount))
+                    }
+                    Err(e) => {
+                        log::error!("Failed to sweep balance: {}", e);
+                        Err(ApiError::from(e).context("Failed to sweep balance"))
+                    }
+                }
+            })
+        })
+        .await?;
+
+        track_sent_transaction(&tx_hash, swept, vec![recipient], None);
+        Ok(tx_hash)
+    })
+}
+
+fn frozen_outputs() -> Result<&'static FrozenOutputs, ApiError> {
+    FROZEN_OUTPUTS.get().map(|s| s.as_ref()).ok_or_else(|| ApiError::not_initialized("Frozen outputs"))
+}
+
+/// List the wallet's unspent outputs for coin control, oldest first
+#[tauri::command]
+async fn outputs_list() -> Result<Vec<OutputEntry>, ApiError> {
+    timed_command!("outputs_list", {
+        let frozen = frozen_outputs()?.key_images();
+        let mut real_wallet = RealCryptoNoteWallet::new();
+
+        real_wallet.open_wallet(DEFAULT_WALLET_FILE, "fuego_password")
+            .or_else(|_| real_wallet.create_wallet("fuego_password", DEFAULT_WALLET_FILE, None, 0))
+            .map_err(|e| ApiError::from(e).context("Failed to open/create wallet"))?;
+
+        let outputs = real_wallet.get_unspent_outputs()
+            .map_err(|e| ApiError::from(e).context("Failed to list outputs"))?;
+        let current_height = real_wallet.get_sync_progress()
+            .map(|progress| progress.current_height)
+            .unwrap_or_else(|_| outputs.iter().map(|o| o.block_height).max().unwrap_or(0));
+        Ok(crate::wallet::coin_control::with_age(outputs, current_height, &frozen))
+    })
+}
+
+#[tauri::command]
+async fn wallet_list_unspent() -> Result<Vec<OutputEntry>, ApiError> { outputs_list().await }
+
+/// Keep the output with `key_image` out of sends until it is unfrozen; returns
+/// false if it already was frozen
+#[tauri::command]
+async fn wallet_freeze_output(key_image: String) -> Result<bool, ApiError> {
+    timed_command!("wallet_freeze_output", {
+        let store = frozen_outputs()?;
+        let key_image = key_image.trim().to_string();
+        let outputs = async_wallet::run("wallet_freeze_output", operation_timeout(OperationClass::Read), move || {
+            let mut real_wallet = RealCryptoNoteWallet::new();
+            real_wallet.open_wallet(DEFAULT_WALLET_FILE, "fuego_password")
+                .map_err(|e| ApiError::from(e).context("Failed to open wallet"))?;
+            real_wallet.get_unspent_outputs()
+                .map_err(|e| ApiError::from(e).context("Failed to list outputs"))
+        })
+        .await?;
+        if !outputs.iter().any(|o| o.key_image == key_image) {
+            return Err(ApiError::not_found(format!("Unknown output: {}", key_image)));
+        }
+        Ok(store.freeze(&key_image)?)
+    })
+}
+
+/// Let the output with `key_image` be spent again; returns false if it was not frozen
+#[tauri::command]
+async fn wallet_unfreeze_output(key_image: String) -> Result<bool, ApiError> {
+    Ok(frozen_outputs()?.unfreeze(key_image.trim())?)
+}
+
+/// Write the wallet to its file now, whether or not auto-save is on
+#[tauri::command]
+async fn wallet_save() -> Result<(), ApiError> {
+    timed_command!("wallet_save", {
+        async_wallet::run("wallet_save", operation_timeout(OperationClass::Read), move || {
+            let mut real_wallet = RealCryptoNoteWallet::new();
+            real_wallet.open_wallet(DEFAULT_WALLET_FILE, "fuego_password")
+                .map_err(|e| ApiError::from(e).context("Failed to open wallet"))?;
+            real_wallet.save()
+                .map_err(|e| ApiError::from(e).context("Failed to save wallet"))
+        })
+        .await
+    })
+}
+
+fn local_daemon() -> Result<Arc<DaemonSupervisor>, ApiError> {
+    LOCAL_DAEMON.get().cloned().ok_or_else(|| ApiError::not_initialized("Local daemon"))
+}
+
+/// Stop the local daemon, if running, before the app exits
+fn stop_local_daemon() {
+    if let Some(daemon) = LOCAL_DAEMON.get()
+        && let Err(e) = tauri::async_runtime::block_on(daemon.stop())
+    {
+        log::error!("Failed to stop the local daemon: {}", e);
+    }
+}
+
+/// Start the daemon set in the network settings, or the bundled one
+#[tauri::command]
+async fn daemon_start() -> Result<DaemonStatus, ApiError> {
+    let settings = SETTINGS_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Settings manager"))?;
+    let network = settings.get_settings().map_err(ApiError::settings)?.network;
+    let launch = DaemonLaunch::from_settings(&network.daemon)
+        .map_err(|e| ApiError::from(e).context("Failed to start the local daemon"))?;
+    Ok(local_daemon()?.start(launch)?)
+}
+
+/// Stop the local daemon; wallets go back to the public nodes
+#[tauri::command]
+async fn daemon_stop() -> Result<(), ApiError> {
+    Ok(local_daemon()?.stop().await?)
+}
+
+/// State, pid, uptime, height and restart count of the local daemon
+#[tauri::command]
+async fn daemon_status() -> Result<DaemonStatus, ApiError> {
+    Ok(local_daemon()?.status())
+}
+
+/// The last `lines` lines the local daemon printed, oldest first
+#[tauri::command]
+async fn daemon_log_tail(lines: u32) -> Result<Vec<String>, ApiError> {
+    Ok(local_daemon()?.log_tail(lines as usize)?)
+}
+
+/// Securely remove the settings, backups, logs, caches and wallet sidecars,
+/// and the wallet file too with `include_wallet`. Runs only when `confirm` is
+/// "DELETE", the wallet is closed and the local daemon is stopped.
+#[tauri::command]
+async fn wipe_local_data(confirm: String, include_wallet: Option<bool>) -> Result<WipeManifest, ApiError> {
+    if confirm != WIPE_CONFIRMATION {
+        return Err(ApiError::invalid_input(format!("Type {} to confirm wiping all local data", WIPE_CONFIRMATION)));
+    }
+    if SYNC_LOOP.get().is_some_and(|sync_loop| sync_loop.is_wallet_open()) {
+        return Err(ApiError::new(ErrorCode::WalletAlreadyOpen, "Close the wallet before wiping local data"));
+    }
+    if LOCAL_DAEMON.get().is_some_and(|daemon| !matches!(daemon.status().state, DaemonState::Stopped | DaemonState::Failed)) {
+        return Err(ApiError::invalid_input("Stop the local daemon before wiping local data"));
+    }
+
+    let plan = WipePlan::new(WipePlan::app_dirs(), Path::new(DEFAULT_WALLET_FILE), include_wallet.unwrap_or(false));
+    let registry = WALLET_REGISTRY.get().cloned();
+    let manifest = tauri::async_runtime::spawn_blocking(move || {
+        // Wallets opened by name keep their files open; close them before deleting
+        if let Some(registry) = registry {
+            registry.close_all();
+        }
+        plan.execute()
+    })
+    .await
+        .map_err(|e| ApiError::new(ErrorCode::Internal, format!("Failed to wipe local data: {}", e)))?;
+    log::warn!("Wiped local data: {} paths removed, {} failed", manifest.removed.len(), manifest.failed.len());
+    Ok(manifest)
+}
+
+/// Consolidate unlocked outputs below `threshold` atomic units into one,
+/// holding the consolidated amount to the send cap
+#[tauri::command]
+async fn sweep_below(threshold: u64, mixin: Option<u64>, override_token: Option<String>) -> Result<String, ApiError> {
+    timed_command!("sweep_below", {
+        ensure_wallet_unlocked()?;
+        let frozen = frozen_outputs()?.key_images();
+        let (tx_hash, consolidated, own_address) = async_wallet::run("sweep_below", operation_timeout(OperationClass::Send), move || {
+            with_active_wallet(|real_wallet| {
+                if let Err(e) = connect_to_fuego_network(real_wallet) {
+                    log::warn!("Failed to connect to Fuego network: {}", e);
+                }
+
+                let estimate = real_wallet.estimate_sweep_below(threshold, mixin.unwrap_or(5), &frozen)
+                    .map_err(|e| ApiError::from(e).context("Failed to consolidate outputs"))?;
+                enforce_send_cap(estimate.sendable_amount, override_token.as_deref())?;
+                check_spendable(real_wallet, estimate.total)?;
+
+                match real_wallet.sweep_below(threshold, mixin.unwrap_or(5), &frozen) {
+                    Ok(tx_hash) => {
+                        log::info!("Consolidated outputs below {}: {}", threshold, tx_hash);
+                        let own_address = real_wallet.get_address().unwrap_or_default();
+                        Ok((tx_hash, estimate.sendable_amount, own_address))
+                    }
+                    Err(e) => {
+                        log::error!("Failed to consolidate outputs: {}", e);
+                        Err(ApiError::from(e).context("Failed to consolidate outputs"))
+                    }
+                }
+            })
+        })
+        .await?;
+
+        // Consolidation pays the wallet's own address
+        track_sent_transaction(&tx_hash, consolidated, vec![own_address], None);
+        Ok(tx_hash)
+    })
+}
+
+/// Get the wallet balance plus principal and projected interest of open term deposits
+#[tauri::command]
+async fn get_total_holdings() -> Result<HoldingsBreakdown, ApiError> {
+    timed_command!("get_total_holdings", {
+        async_wallet::run("get_total_holdings", operation_timeout(OperationClass::Read), move || {
+            let mut real_wallet = RealCryptoNoteWallet::new();
+
+            real_wallet.open_wallet(DEFAULT_WALLET_FILE, "fuego_password")
+                .or_else(|_| real_wallet.create_wallet("fuego_password", DEFAULT_WALLET_FILE, None, 0))
+                .map_err(|e| ApiError::from(e).context("Failed to open/create wallet"))?;
+
+            let _ = connect_to_fuego_network(&mut real_wallet);
+
+            let balance = real_wallet.get_balance().map_err(ApiError::from)?;
+            let unlocked_balance = real_wallet.get_unlocked_balance().map_err(ApiError::from)?;
+
+            let deposits = match real_wallet.get_deposits() {
+                Ok(deposits) => Some(deposits),
+                Err(e) => {
+                    log::warn!("Deposits unavailable, reporting wallet balance only: {}", e);
+                    None
+                }
+            };
+
+            Ok(HoldingsBreakdown::compute(balance, unlocked_balance, deposits.as_deref()))
+        })
+        .await
+    })
+}
+
+/// How long a deposit summary is served from the cache
+const DEPOSIT_SUMMARY_TTL: Duration = Duration::from_secs(60);
+
+/// Totals over all term deposits: principal locked, interest accrued so far
+/// and earned by withdrawn deposits, average rate, counts by status and the
+/// next maturity
+#[tauri::command]
+async fn deposit_summary() -> Result<DepositSummary, ApiError> {
+    timed_command!("deposit_summary", {
+        let cache = CACHE.get().ok_or_else(|| ApiError::not_initialized("Cache"))?;
+        if let Some(summary) = cache.get("deposit_summary").and_then(|value| serde_json::from_value(value).ok()) {
+            return Ok(summary);
+        }
+
+        let summary = async_wallet::run("deposit_summary", operation_timeout(OperationClass::Read), move || {
+            let mut real_wallet = RealCryptoNoteWallet::new();
+            real_wallet.open_wallet(DEFAULT_WALLET_FILE, "fuego_password")
+                .or_else(|_| real_wallet.create_wallet("fuego_password", DEFAULT_WALLET_FILE, None, 0))
+                .map_err(|e| ApiError::from(e).context("Failed to open/create wallet"))?;
+            let _ = connect_to_fuego_network(&mut real_wallet);
+
+            let deposits = real_wallet.get_deposits().map_err(|e| ApiError::from(e).context("Failed to get deposits"))?;
+            let height = real_wallet.get_network_info().map(|info| info.network_height).unwrap_or_else(|e| {
+                log::warn!("Network height unavailable for the deposit summary: {}", e);
+                0
+            });
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or(Duration::from_secs(0)).as_secs();
+            Ok::<_, ApiError>(DepositSummary::compute(&deposits, height, now))
+        })
+        .await?;
+
+        cache.set_with_ttl("deposit_summary".to_string(), serde_json::to_value(&summary)?, DEPOSIT_SUMMARY_TTL);
+        Ok(summary)
+    })
+}
+
+/// Get term deposits (staking/investment positions)
+#[tauri::command]
+async fn get_term_deposits() -> Result<Vec<serde_json::Value>, ApiError> {
+    timed_command!("get_term_deposits", {
+        async_wallet::run("get_term_deposits", operation_timeout(OperationClass::Read), move || {
+            with_active_wallet(|real_wallet| {
+                // Connect to Fuego network
+                let _ = connect_to_fuego_network(real_wallet);
+
+                // Get real deposits from CryptoNote wallet
+                match real_wallet.get_deposits() {
+                    Ok(deposits) => {
+                        let mut deposit_list = Vec::new();
+
+                        for deposit in deposits {
+                            let deposit_json = serde_json::json!({
+                                "id": deposit.id,
+                                "amount": deposit.amount,
+                                "interest": deposit.interest,
+                                "term": deposit.term,
+                                "rate": deposit.rate,
+                                "status": deposit.status,
+                                "unlock_height": deposit.unlock_height,
+                                "unlock_time": deposit.unlock_time,
+                                "creating_transaction_hash": deposit.creating_transaction_hash,
+                                "creating_height": deposit.creating_height,
+                                "creating_time": deposit.creating_time,
+                                "spending_transaction_hash": deposit.spending_transaction_hash,
+                                "spending_height": deposit.spending_height,
+                                "spending_time": deposit.spending_time,
+                                "type": deposit.deposit_type
+                            });
+                            deposit_list.push(deposit_json);
+                        }
+
+                        log::info!("Retrieved {} term deposits from blockchain", deposit_list.len());
+                        Ok(deposit_list)
+                    }
+                    Err(e) => {
+                        log::error!("Failed to get deposits: {}", e);
+                        Err(ApiError::from(e).context("Failed to get deposits"))
+                    }
+                }
+            })
+        })
+        .await
+    })
+}
+
+/// Create a new term deposit (stake XFG for interest)
+#[tauri::command]
+async fn create_term_deposit(amount: u64, term: u32) -> Result<String, ApiError> {
+    timed_command!("create_term_deposit", {
+        ensure_wallet_unlocked()?;
+        let deposit_id = async_wallet::run("create_term_deposit", operation_timeout(OperationClass::Send), move || {
+            with_active_wallet(|real_wallet| {
+                // Connect to Fuego network
+                let _ = connect_to_fuego_network(real_wallet);
+
+                // Validate deposit parameters against the network's limits
+                let limits = deposit_limits(real_wallet)?;
+                check_deposit(&limits, amount, term).map_err(ApiError::invalid_input)?;
+
+                // Create real deposit transaction using CryptoNote
+                match real_wallet.create_deposit(amount, term) {
+                    Ok(deposit_id) => {
+                        log::info!("Created term deposit: {} XFG for {} days (ID: {})", format_xfg(amount), term, deposit_id);
+                        Ok(deposit_id)
+                    }
+                    Err(e) => {
+                        log::error!("Failed to create deposit: {}", e);
+                        Err(ApiError::from(e).context("Failed to create deposit"))
+                    }
+                }
+            })
+        })
+        .await?;
+
+        // The deposited amount left the balance
+        invalidate_wallet_info();
+        Ok(deposit_id)
+    })
+}
+
+/// Withdraw a term deposit (claim principal + interest)
+#[tauri::command]
+async fn withdraw_term_deposit(deposit_id: String) -> Result<String, ApiError> {
+    timed_command!("withdraw_term_deposit", {
+        ensure_wallet_unlocked()?;
+        let tx_hash = async_wallet::run("withdraw_term_deposit", operation_timeout(OperationClass::Send), move || {
+            with_active_wallet(|real_wallet| {
+                // Connect to Fuego network
+                let _ = connect_to_fuego_network(real_wallet);
+
+                // Withdraw deposit using real CryptoNote functionality
+                match real_wallet.withdraw_deposit(&deposit_id) {
+                    Ok(tx_hash) => {
+                        log::info!("Withdrew term deposit: {} (TX: {})", deposit_id, tx_hash);
+                        Ok(tx_hash)
+                    }
+                    Err(e) => {
+                        log::error!("Failed to withdraw deposit: {}", e);
+                        Err(ApiError::from(e).context("Failed to withdraw deposit"))
+                    }
+                }
+            })
+        })
+        .await?;
+
+        invalidate_wallet_info();
+        Ok(tx_hash)
+    })
 }
 
 // ===== PHASE 2.2: SECURITY & PERFORMANCE COMMANDS =====
 
 /// Authenticate user with password
 #[tauri::command]
-async fn authenticate_user(user_id: String, password: String) -> Result<String, String> {
+async fn authenticate_user(user_id: String, password: String) -> Result<String, ApiError> {
     let timer = PERFORMANCE_MONITOR.get().unwrap().start_timing("authenticate_user".to_string());
     
     let security_manager = SECURITY_MANAGER.get().unwrap();
@@ -858,14 +3962,14 @@ async fn authenticate_user(user_id: String, password: String) -> Result<String,
         Err(e) => {
             timer.finish(false);
             log::warn!("Authentication failed for user {}: {}", user_id, e);
-            Err(e)
+            Err(ApiError::security(e))
         }
     }
 }
 
 /// Validate user session
 #[tauri::command]
-async fn validate_session(session_id: String) -> Result<String, String> {
+async fn validate_session(session_id: String) -> Result<String, ApiError> {
     let timer = PERFORMANCE_MONITOR.get().unwrap().start_timing("validate_session".to_string());
     
     let security_manager = SECURITY_MANAGER.get().unwrap();
@@ -877,35 +3981,52 @@ async fn validate_session(session_id: String) -> Result<String, String> {
         }
         Err(e) => {
             timer.finish(false);
-            Err(e)
+            Err(ApiError::security(e))
         }
     }
 }
 
 /// Lock session for sensitive operations
 #[tauri::command]
-async fn lock_session(session_id: String) -> Result<(), String> {
+async fn lock_session(session_id: String) -> Result<(), ApiError> {
     let security_manager = SECURITY_MANAGER.get().unwrap();
-    security_manager.lock_session(&session_id)
+    security_manager.lock_session(&session_id).map_err(ApiError::security)
 }
 
 /// Unlock session with password
 #[tauri::command]
-async fn unlock_session(session_id: String, password: String) -> Result<(), String> {
+async fn unlock_session(session_id: String, password: String) -> Result<(), ApiError> {
     let security_manager = SECURITY_MANAGER.get().unwrap();
-    security_manager.unlock_session(&session_id, &password)
+    security_manager.unlock_session(&session_id, &password).map_err(ApiError::security)
 }
 
 /// Logout user and destroy session
 #[tauri::command]
-async fn logout_user(session_id: String) -> Result<(), String> {
+async fn logout_user(session_id: String) -> Result<(), ApiError> {
     let security_manager = SECURITY_MANAGER.get().unwrap();
-    security_manager.logout(&session_id)
+    security_manager.logout(&session_id).map_err(ApiError::security)
+}
+
+/// Whether `user_id` is locked out after failed logins, and for how long
+#[tauri::command]
+async fn security_get_lockout_status(user_id: String) -> Result<LockoutStatus, ApiError> {
+    let security_manager = SECURITY_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Security manager"))?;
+    Ok(security_manager.lockout_status(&user_id))
+}
+
+/// Clear the failed logins of `user_id` from a valid, unlocked session;
+/// returns the attempt count left. Limited to a few clears per user an hour.
+#[tauri::command]
+async fn security_clear_lockout(session_id: String, user_id: String) -> Result<u32, ApiError> {
+    let security_manager = SECURITY_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Security manager"))?;
+    let attempts = security_manager.clear_lockout(&session_id, &user_id).map_err(ApiError::security)?;
+    log::info!("Login lockout of {} cleared", user_id);
+    Ok(attempts)
 }
 
 /// Validate password strength
 #[tauri::command]
-async fn validate_password_strength(password: String) -> Result<serde_json::Value, String> {
+async fn validate_password_strength(password: String) -> Result<serde_json::Value, ApiError> {
     match PasswordValidator::validate_strength(&password) {
         Ok(_) => {
             let score = PasswordValidator::calculate_strength_score(&password);
@@ -935,19 +4056,19 @@ async fn validate_password_strength(password: String) -> Result<serde_json::Valu
 
 /// Encrypt wallet data
 #[tauri::command]
-async fn encrypt_wallet_data(data: String, password: String) -> Result<String, String> {
-    WalletEncryption::encrypt_data(&data, &password)
+async fn encrypt_wallet_data(data: String, password: String) -> Result<String, ApiError> {
+    WalletEncryption::encrypt_data(&data, &password).map_err(ApiError::security)
 }
 
 /// Decrypt wallet data
 #[tauri::command]
-async fn decrypt_wallet_data(encrypted_data: String, password: String) -> Result<String, String> {
-    WalletEncryption::decrypt_data(&encrypted_data, &password)
+async fn decrypt_wallet_data(encrypted_data: String, password: String) -> Result<String, ApiError> {
+    WalletEncryption::decrypt_data(&encrypted_data, &password).map_err(ApiError::security)
 }
 
 /// Get performance metrics
 #[tauri::command]
-async fn get_performance_metrics(operation_name: Option<String>) -> Result<serde_json::Value, String> {
+async fn get_performance_metrics(operation_name: Option<String>) -> Result<serde_json::Value, ApiError> {
     let monitor = PERFORMANCE_MONITOR.get().unwrap();
     
     if let Some(name) = operation_name {
@@ -972,9 +4093,24 @@ async fn get_performance_metrics(operation_name: Option<String>) -> Result<serde
     }
 }
 
+/// Get duration percentiles of the operations recorded in the last `since_seconds`
+#[tauri::command]
+async fn get_performance_window(operation: Option<String>, since_seconds: u64) -> Result<PerformanceWindow, ApiError> {
+    let monitor = PERFORMANCE_MONITOR.get().ok_or_else(|| ApiError::not_initialized("Performance monitor"))?;
+    Ok(monitor.get_performance_window(operation.as_deref(), since_seconds))
+}
+
+/// Operations whose latency in the last `recent_window` seconds grew past the
+/// `baseline_window` seconds before
+#[tauri::command]
+async fn get_performance_regressions(baseline_window: u64, recent_window: u64) -> Result<Vec<Regression>, ApiError> {
+    let monitor = PERFORMANCE_MONITOR.get().ok_or_else(|| ApiError::not_initialized("Performance monitor"))?;
+    Ok(monitor.detect_regressions(baseline_window, recent_window))
+}
+
 /// Get cache statistics
 #[tauri::command]
-async fn get_cache_stats() -> Result<serde_json::Value, String> {
+async fn get_cache_stats() -> Result<serde_json::Value, ApiError> {
     let cache = CACHE.get().unwrap();
     let stats = cache.stats();
     Ok(serde_json::json!({
@@ -990,34 +4126,127 @@ async fn get_cache_stats() -> Result<serde_json::Value, String> {
     }))
 }
 
+/// Size the caches for `cache_size_mb`, evicting entries that no longer fit
+fn configure_caches(cache_size_mb: u32) -> serde_json::Value {
+    let capacity = cache_capacity_for_mb(cache_size_mb);
+    let mut evicted = 0;
+    if let Some(cache) = CACHE.get() {
+        evicted += cache.resize(capacity);
+    }
+    if let Some(cache) = OPTIMIZATION_CACHE.get() {
+        evicted += cache.resize(capacity);
+    }
+    if evicted > 0 {
+        log::info!("Caches resized to {} entries, {} evicted", capacity, evicted);
+    }
+    serde_json::json!({
+        "cache_size_mb": cache_size_mb,
+        "max_entries": capacity,
+        "evicted": evicted,
+    })
+}
+
+/// Size the caches for `PerformanceSettings::cache_size_mb`; a given
+/// `cache_size_mb` is saved to the settings first
+#[tauri::command]
+async fn reconfigure_caches(cache_size_mb: Option<u32>) -> Result<serde_json::Value, ApiError> {
+    let settings = SETTINGS_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Settings manager"))?;
+    let mut performance = settings.get_settings().map_err(ApiError::settings)?.performance;
+    if let Some(size) = cache_size_mb {
+        performance.cache_size_mb = size;
+        settings.update_performance_settings(performance.clone()).map_err(ApiError::settings)?;
+    }
+    Ok(configure_caches(performance.cache_size_mb))
+}
+
+/// Log at `level` from now on and save it as `PerformanceSettings::log_level`
+#[tauri::command]
+async fn set_log_level(level: String) -> Result<String, ApiError> {
+    let filter = logging::set_level(&level).map_err(ApiError::invalid_input)?;
+    let settings = SETTINGS_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Settings manager"))?;
+    let mut performance = settings.get_settings().map_err(ApiError::settings)?.performance;
+    performance.log_level = filter.to_string().to_lowercase();
+    settings.update_performance_settings(performance.clone()).map_err(ApiError::settings)?;
+    Ok(performance.log_level)
+}
+
+fn logs_dir() -> Result<std::path::PathBuf, ApiError> {
+    logging::logs_dir().ok_or_else(|| ApiError::not_found("No data directory to keep logs in"))
+}
+
+/// The last `lines` log lines, oldest first, with seeds and keys redacted
+#[tauri::command]
+async fn logs_tail(lines: u32) -> Result<Vec<String>, ApiError> {
+    logging::tail(&logs_dir()?, lines as usize).map_err(ApiError::from)
+}
+
+/// Write all log files, redacted, to `path` for attaching to a bug report;
+/// returns the number of lines written
+#[tauri::command]
+async fn logs_export(path: String) -> Result<usize, ApiError> {
+    let lines = logging::export(&logs_dir()?, Path::new(&path))
+        .map_err(|e| ApiError::from(e).context("Failed to export logs"))?;
+    log::info!("Exported {} log lines to {}", lines, path);
+    Ok(lines)
+}
+
+fn crash_reports_dir() -> Result<std::path::PathBuf, ApiError> {
+    crash::crash_reports_dir().ok_or_else(|| ApiError::not_found("No data directory to keep crash reports in"))
+}
+
+/// Crash reports, newest first; listing marks them as reviewed, so the
+/// startup notification does not repeat
+#[tauri::command]
+async fn crash_reports_list() -> Result<Vec<CrashReport>, ApiError> {
+    crash::list_reports(&crash_reports_dir()?).map_err(ApiError::from)
+}
+
+/// Delete crash report `id`
+#[tauri::command]
+async fn crash_report_delete(id: String) -> Result<(), ApiError> {
+    crash::delete_report(&crash_reports_dir()?, &id).map_err(ApiError::invalid_input)
+}
+
 /// Clear cache
 #[tauri::command]
-async fn clear_cache() -> Result<(), String> {
+async fn clear_cache() -> Result<(), ApiError> {
     let cache = CACHE.get().unwrap();
     cache.clear();
     log::info!("Cache cleared");
     Ok(())
 }
 
+/// A task status as the frontend reads it: `last_run` in seconds ago
+fn task_status_json(status: TaskStatus) -> serde_json::Value {
+    serde_json::json!({
+        "name": status.name,
+        "enabled": status.enabled,
+        "last_run": status.last_run.elapsed().as_secs(),
+        "next_run_in": status.next_run_in.as_secs()
+    })
+}
+
 /// Get background task status
 #[tauri::command]
-async fn get_background_task_status(task_name: String) -> Result<serde_json::Value, String> {
+async fn get_background_task_status(task_name: String) -> Result<serde_json::Value, ApiError> {
     let task_manager = BACKGROUND_TASKS.get().unwrap();
     
     match task_manager.get_task_status(&task_name) {
-        Some(status) => Ok(serde_json::json!({
-            "name": status.name,
-            "enabled": status.enabled,
-            "last_run": status.last_run.elapsed().as_secs(),
-            "next_run_in": status.next_run_in.as_secs()
-        })),
-        None => Err("Task not found".to_string())
+        Some(status) => Ok(task_status_json(status)),
+        None => Err(ApiError::not_found("Task not found"))
     }
 }
 
+/// Status of every registered background task, by name
+#[tauri::command]
+async fn list_background_tasks() -> Result<Vec<serde_json::Value>, ApiError> {
+    let task_manager = BACKGROUND_TASKS.get().ok_or_else(|| ApiError::not_initialized("Background task manager"))?;
+    Ok(task_manager.list_tasks().into_iter().map(task_status_json).collect())
+}
+
 /// Enable background task
 #[tauri::command]
-async fn enable_background_task(task_name: String) -> Result<(), String> {
+async fn enable_background_task(task_name: String) -> Result<(), ApiError> {
     let task_manager = BACKGROUND_TASKS.get().unwrap();
     task_manager.set_task_enabled(&task_name, true);
     log::info!("Background task {} enabled", task_name);
@@ -1026,7 +4255,7 @@ async fn enable_background_task(task_name: String) -> Result<(), String> {
 
 /// Disable background task
 #[tauri::command]
-async fn disable_background_task(task_name: String) -> Result<(), String> {
+async fn disable_background_task(task_name: String) -> Result<(), ApiError> {
     let task_manager = BACKGROUND_TASKS.get().unwrap();
     task_manager.set_task_enabled(&task_name, false);
     log::info!("Background task {} disabled", task_name);
@@ -1037,121 +4266,184 @@ async fn disable_background_task(task_name: String) -> Result<(), String> {
 
 // Get comprehensive wallet information
 #[tauri::command]
-async fn get_wallet_info_advanced() -> Result<serde_json::Value, String> {
-    let mut real_wallet = RealCryptoNoteWallet::new();
+async fn get_wallet_info_advanced() -> Result<serde_json::Value, ApiError> {
+    timed_command!("get_wallet_info_advanced", {
+        let mut real_wallet = RealCryptoNoteWallet::new();
+
+        let _ = real_wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
+            .or_else(|_| real_wallet.create_wallet("fuego_password", "/tmp/fuego_wallet.wallet", None, 0));
+
+        match real_wallet.get_wallet_info() {
+            Ok(info) => Ok(serde_json::json!({
+                "address": info.address,
+                "balance": info.balance,
+                "unlocked_balance": info.unlocked_balance,
+                "locked_balance": info.locked_balance,
+                "total_received": info.total_received,
+                "total_sent": info.total_sent,
+                "transaction_count": info.transaction_count,
+                "is_synced": info.is_synced,
+                "sync_height": info.sync_height,
+                "network_height": info.network_height,
+                "daemon_height": info.daemon_height,
+                "is_connected": info.is_connected,
+                "peer_count": info.peer_count,
+                "last_block_time": info.last_block_time
+            })),
+            Err(e) => Err(ApiError::from(e).context("Failed to get wallet info"))
+        }
+    })
+}
 
-    let _ = real_wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
-        .or_else(|_| real_wallet.create_wallet("fuego_password", "/tmp/fuego_wallet.wallet", None, 0));
+// Get detailed network information
+#[tauri::command]
+async fn get_network_info_advanced() -> Result<serde_json::Value, ApiError> {
+    timed_command!("get_network_info_advanced", {
+        let mut real_wallet = RealCryptoNoteWallet::new();
+
+        let _ = real_wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
+            .or_else(|_| real_wallet.create_wallet("fuego_password", "/tmp/fuego_wallet.wallet", None, 0));
+
+        match real_wallet.get_network_info() {
+            Ok(info) => Ok(serde_json::json!({
+                "is_connected": info.is_connected,
+                "peer_count": info.peer_count,
+                "sync_height": info.sync_height,
+                "network_height": info.network_height,
+                "is_syncing": info.is_syncing,
+                "connection_type": ConnectionType::from_raw(&info.connection_type),
+                "connection_type_raw": info.connection_type,
+                "last_sync_time": info.last_sync_time,
+                "sync_speed": info.sync_speed,
+                "estimated_sync_time": info.estimated_sync_time
+            })),
+            Err(e) => Err(ApiError::from(e).context("Failed to get network info"))
+        }
+    })
+}
 
-    match real_wallet.get_wallet_info() {
-        Ok(info) => Ok(serde_json::json!({
-            "address": info.address,
-            "balance": info.balance,
-            "unlocked_balance": info.unlocked_balance,
-            "locked_balance": info.locked_balance,
-            "total_received": info.total_received,
-            "total_sent": info.total_sent,
-            "transaction_count": info.transaction_count,
-            "is_synced": info.is_synced,
-            "sync_height": info.sync_height,
-            "network_height": info.network_height,
-            "daemon_height": info.daemon_height,
-            "is_connected": info.is_connected,
-            "peer_count": info.peer_count,
-            "last_block_time": info.last_block_time
-        })),
-        Err(e) => Err(format!("Failed to get wallet info: {}", e))
-    }
+// Get transaction by hash
+#[tauri::command]
+async fn get_transaction_by_hash(tx_hash: String) -> Result<serde_json::Value, ApiError> {
+    timed_command!("get_transaction_by_hash", {
+        let hash = tx_hash.clone();
+        let wallet_lookup = async_wallet::run("get_transaction_by_hash", operation_timeout(OperationClass::Read), move || {
+            with_active_wallet(|real_wallet| Ok(real_wallet.get_transaction_by_hash(&hash)))
+        })
+        .await?;
+        let wallet_error = match wallet_lookup {
+            Ok(tx) => return Ok(serde_json::json!({
+                "id": tx.id,
+                "hash": tx.hash,
+                "amount": tx.amount,
+                "fee": tx.fee,
+                "height": tx.height,
+                "timestamp": tx.timestamp,
+                "confirmations": tx.confirmations,
+                "is_confirmed": tx.is_confirmed,
+                "is_pending": tx.is_pending,
+                "payment_id": tx.payment_id,
+                "destination_addresses": tx.destination_addresses,
+                "source_addresses": tx.source_addresses,
+                "unlock_time": tx.unlock_time,
+                "extra": tx.extra,
+                "metadata": TX_METADATA_STORE.get().and_then(|s| s.get(&tx.hash))
+            })),
+            Err(e) => e.to_string(),
+        };
+
+        // Not a wallet transaction; look it up on the configured explorers
+        let explorers = ADVANCED_WALLET_MANAGER.get()
+            .map(|mgr| mgr.get_explorers())
+            .unwrap_or_default();
+        let client = ExplorerClient::new(explorers, CACHE.get().cloned());
+        let mut tx = client.fetch_transaction(&tx_hash).await
+            .map_err(|e| format!("Failed to get transaction: {}; {}", wallet_error, e))?;
+        if let (Some(object), Some(metadata)) = (tx.as_object_mut(), TX_METADATA_STORE.get().and_then(|s| s.get(&tx_hash))) {
+            object.insert("metadata".to_string(), serde_json::json!(metadata));
+        }
+        Ok(tx)
+    })
 }
 
-// Get detailed network information
+// Create new address
 #[tauri::command]
-async fn get_network_info_advanced() -> Result<serde_json::Value, String> {
-    let mut real_wallet = RealCryptoNoteWallet::new();
+async fn create_address(label: Option<String>) -> Result<String, ApiError> {
+    timed_command!("create_address", {
+        let mut real_wallet = RealCryptoNoteWallet::new();
 
-    let _ = real_wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
-        .or_else(|_| real_wallet.create_wallet("fuego_password", "/tmp/fuego_wallet.wallet", None, 0));
+        let _ = real_wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
+            .or_else(|_| real_wallet.create_wallet("fuego_password", "/tmp/fuego_wallet.wallet", None, 0));
 
-    match real_wallet.get_network_info() {
-        Ok(info) => Ok(serde_json::json!({
-            "is_connected": info.is_connected,
-            "peer_count": info.peer_count,
-            "sync_height": info.sync_height,
-            "network_height": info.network_height,
-            "is_syncing": info.is_syncing,
-            "connection_type": info.connection_type,
-            "last_sync_time": info.last_sync_time,
-            "sync_speed": info.sync_speed,
-            "estimated_sync_time": info.estimated_sync_time
-        })),
-        Err(e) => Err(format!("Failed to get network info: {}", e))
-    }
+        match real_wallet.create_address(label.as_deref()) {
+            Ok(address) => {
+                if let (Ok(addresses), Some(mgr)) = (real_wallet.get_addresses(), ADVANCED_WALLET_MANAGER.get()) {
+                    mgr.reconcile_addresses(&addresses);
+                }
+                Ok(address)
+            }
+            Err(e) => Err(ApiError::from(e).context("Failed to create address"))
+        }
+    })
 }
 
-// Get transaction by hash
+/// Get the wallet's addresses with their labels, reconciled into the advanced wallet manager
 #[tauri::command]
-async fn get_transaction_by_hash(tx_hash: String) -> Result<serde_json::Value, String> {
-    let mut real_wallet = RealCryptoNoteWallet::new();
+async fn wallet_get_addresses() -> Result<Vec<AddressInfo>, ApiError> {
+    timed_command!("wallet_get_addresses", {
+        let mut real_wallet = RealCryptoNoteWallet::new();
 
-    let _ = real_wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
-        .or_else(|_| real_wallet.create_wallet("fuego_password", "/tmp/fuego_wallet.wallet", None, 0));
+        let _ = real_wallet.open_wallet(DEFAULT_WALLET_FILE, "fuego_password")
+            .or_else(|_| real_wallet.create_wallet("fuego_password", DEFAULT_WALLET_FILE, None, 0));
 
-    match real_wallet.get_transaction_by_hash(&tx_hash) {
-        Ok(tx) => Ok(serde_json::json!({
-            "id": tx.id,
-            "hash": tx.hash,
-            "amount": tx.amount,
-            "fee": tx.fee,
-            "height": tx.height,
-            "timestamp": tx.timestamp,
-            "confirmations": tx.confirmations,
-            "is_confirmed": tx.is_confirmed,
-            "is_pending": tx.is_pending,
-            "payment_id": tx.payment_id,
-            "destination_addresses": tx.destination_addresses,
-            "source_addresses": tx.source_addresses,
-            "unlock_time": tx.unlock_time,
-            "extra": tx.extra
-        })),
-        Err(e) => Err(format!("Failed to get transaction: {}", e))
-    }
+        let addresses = real_wallet.get_addresses()
+            .map_err(|e| ApiError::from(e).context("Failed to get addresses"))?;
+
+        let mgr = ADVANCED_WALLET_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Advanced wallet manager"))?;
+        Ok(mgr.reconcile_addresses(&addresses))
+    })
 }
 
-// Create new address
+/// Set the label of a wallet address and return the reconciled address list
 #[tauri::command]
-async fn create_address(label: Option<String>) -> Result<String, String> {
+async fn wallet_set_address_label(address: String, label: String) -> Result<Vec<AddressInfo>, ApiError> {
     let mut real_wallet = RealCryptoNoteWallet::new();
 
-    let _ = real_wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
-        .or_else(|_| real_wallet.create_wallet("fuego_password", "/tmp/fuego_wallet.wallet", None, 0));
+    let _ = real_wallet.open_wallet(DEFAULT_WALLET_FILE, "fuego_password")
+        .or_else(|_| real_wallet.create_wallet("fuego_password", DEFAULT_WALLET_FILE, None, 0));
 
-    match real_wallet.create_address(label.as_deref()) {
-        Ok(address) => Ok(address),
-        Err(e) => Err(format!("Failed to create address: {}", e))
-    }
+    real_wallet.set_address_label(&address, label.trim())
+        .map_err(|e| ApiError::from(e).context("Failed to set address label"))?;
+    let addresses = real_wallet.get_addresses()
+        .map_err(|e| ApiError::from(e).context("Failed to get addresses"))?;
+
+    let mgr = ADVANCED_WALLET_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Advanced wallet manager"))?;
+    Ok(mgr.reconcile_addresses(&addresses))
 }
 
 // Get block information
 #[tauri::command]
-async fn get_block_info(height: u64) -> Result<serde_json::Value, String> {
-    let mut real_wallet = RealCryptoNoteWallet::new();
-
-    let _ = real_wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
-        .or_else(|_| real_wallet.create_wallet("fuego_password", "/tmp/fuego_wallet.wallet", None, 0));
-
-    match real_wallet.get_block_info(height) {
-        Ok(block) => Ok(serde_json::json!({
-            "height": block.height,
-            "hash": block.hash,
-            "timestamp": block.timestamp,
-            "difficulty": block.difficulty,
-            "reward": block.reward,
-            "size": block.size,
-            "transaction_count": block.transaction_count,
-            "is_main_chain": block.is_main_chain
-        })),
-        Err(e) => Err(format!("Failed to get block info: {}", e))
-    }
+async fn get_block_info(height: u64) -> Result<serde_json::Value, ApiError> {
+    timed_command!("get_block_info", {
+        let mut real_wallet = RealCryptoNoteWallet::new();
+
+        let _ = real_wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
+            .or_else(|_| real_wallet.create_wallet("fuego_password", "/tmp/fuego_wallet.wallet", None, 0));
+
+        match real_wallet.get_block_info(height) {
+            Ok(block) => Ok(serde_json::json!({
+                "height": block.height,
+                "hash": block.hash,
+                "timestamp": block.timestamp,
+                "difficulty": block.difficulty,
+                "reward": block.reward,
+                "size": block.size,
+                "transaction_count": block.transaction_count,
+                "is_main_chain": block.is_main_chain
+            })),
+            Err(e) => Err(ApiError::from(e).context("Failed to get block info"))
+        }
+    })
 }
 
 // Mining commands
@@ -1162,45 +4454,48 @@ async fn start_mining(
     daemon_address: Option<String>,
     pool_wallet: Option<String>,
     pool_password: Option<String>
-) -> Result<bool, String> {
-    let mut real_wallet = RealCryptoNoteWallet::new();
-
-    let _ = real_wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
-        .or_else(|_| real_wallet.create_wallet("fuego_password", "/tmp/fuego_wallet.wallet", None, 0));
-
-    // If daemon address is provided, connect for solo mining
-    if let Some(address) = daemon_address {
-        let parts: Vec<&str> = address.split(':').collect();
-        let host = parts[0];
-        let port: u16 = parts.get(1).and_then(|p| p.parse().ok()).unwrap_or(18180);
-        if let Err(e) = real_wallet.connect_to_node(host, port) {
-            eprintln!("Failed to connect solo daemon {}:{} - {}", host, port, e);
+) -> Result<bool, ApiError> {
+    async_wallet::run("start_mining", operation_timeout(OperationClass::Read), move || {
+        let mut real_wallet = RealCryptoNoteWallet::new();
+
+        let _ = real_wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
+            .or_else(|_| real_wallet.create_wallet("fuego_password", "/tmp/fuego_wallet.wallet", None, 0));
+
+        // If daemon address is provided, connect for solo mining
+        if let Some(address) = daemon_address {
+            let parts: Vec<&str> = address.split(':').collect();
+            let host = parts[0];
+            let port: u16 = parts.get(1).and_then(|p| p.parse().ok()).unwrap_or(18180);
+            if let Err(e) = real_wallet.connect_to_node(host, port) {
+                eprintln!("Failed to connect solo daemon {}:{} - {}", host, port, e);
+            }
+        } else {
+            let _ = connect_to_fuego_network(&mut real_wallet);
         }
-    } else {
-        let _ = connect_to_fuego_network(&mut real_wallet);
-    }
 
-    // If pool wallet is provided, configure pool mining
-    if let Some(wallet_addr) = pool_wallet {
-        let worker = pool_password.clone().unwrap_or_else(|| "worker".to_string());
-        if let Err(e) = real_wallet.set_mining_pool(None, Some(&worker)) {
-            eprintln!("Failed to set mining pool worker: {}", e);
+        // If pool wallet is provided, configure pool mining
+        if let Some(wallet_addr) = pool_wallet {
+            let worker = pool_password.clone().unwrap_or_else(|| "worker".to_string());
+            if let Err(e) = real_wallet.set_mining_pool(None, Some(&worker)) {
+                eprintln!("Failed to set mining pool worker: {}", e);
+            }
+            // Note: Pool URL is set via set_mining_pool(pool_address, worker_name) when provided by UI
+            let _ = wallet_addr; // Wallet used internally by daemon/pool; native layer manages it.
         }
-        // Note: Pool URL is set via set_mining_pool(pool_address, worker_name) when provided by UI
-        let _ = wallet_addr; // Wallet used internally by daemon/pool; native layer manages it.
-    }
 
-    match real_wallet.start_mining(threads, background) {
-        Ok(_) => Ok(true),
-        Err(e) => {
-            eprintln!("Failed to start mining: {}", e);
-            Ok(false)
+        match real_wallet.start_mining(threads, background) {
+            Ok(_) => Ok(true),
+            Err(e) => {
+                eprintln!("Failed to start mining: {}", e);
+                Ok(false)
+            }
         }
-    }
+    })
+    .await
 }
 
 #[tauri::command]
-async fn stop_mining() -> Result<(), String> {
+async fn stop_mining() -> Result<(), ApiError> {
     let mut real_wallet = RealCryptoNoteWallet::new();
 
     let _ = real_wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
@@ -1208,12 +4503,12 @@ async fn stop_mining() -> Result<(), String> {
 
     match real_wallet.stop_mining() {
         Ok(_) => Ok(()),
-        Err(e) => Err(format!("Failed to stop mining: {}", e))
+        Err(e) => Err(ApiError::from(e).context("Failed to stop mining"))
     }
 }
 
 #[tauri::command]
-async fn get_mining_info() -> Result<serde_json::Value, String> {
+async fn get_mining_info() -> Result<serde_json::Value, ApiError> {
     let mut real_wallet = RealCryptoNoteWallet::new();
 
     let _ = real_wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
@@ -1229,67 +4524,79 @@ async fn get_mining_info() -> Result<serde_json::Value, String> {
             "worker_name": info.worker_name,
             "threads": info.threads
         })),
-        Err(e) => Err(format!("Failed to get mining info: {}", e))
+        Err(e) => Err(ApiError::from(e).context("Failed to get mining info"))
     }
 }
 
 // Get transaction history
 #[tauri::command]
-async fn get_transaction_history(limit: Option<u64>, offset: Option<u64>) -> Result<Vec<serde_json::Value>, String> {
-    let mut real_wallet = RealCryptoNoteWallet::new();
-
-    let _ = real_wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
-        .or_else(|_| real_wallet.create_wallet("fuego_password", "/tmp/fuego_wallet.wallet", None, 0));
-
-    match real_wallet.get_transaction_history(limit.unwrap_or(50), offset.unwrap_or(0)) {
-        Ok(transactions) => {
-            let mapped: Vec<serde_json::Value> = transactions
-                .into_iter()
-                .map(|tx| serde_json::json!({
-                    "id": tx.id,
-                    "hash": tx.hash,
-                    "amount": tx.amount,
-                    "fee": tx.fee,
-                    "height": tx.height,
-                    "timestamp": tx.timestamp,
-                    "confirmations": tx.confirmations,
-                    "is_confirmed": tx.is_confirmed,
-                    "is_pending": tx.is_pending,
-                    "payment_id": tx.payment_id,
-                    "destination_addresses": tx.destination_addresses,
-                    "source_addresses": tx.source_addresses,
-                    "unlock_time": tx.unlock_time,
-                    "extra": tx.extra
-                }))
-                .collect();
-            Ok(mapped)
-        }
-        Err(e) => Err(format!("Failed to get transaction history: {}", e))
-    }
+async fn get_transaction_history(limit: Option<u64>, offset: Option<u64>) -> Result<Vec<serde_json::Value>, ApiError> {
+    timed_command!("get_transaction_history", {
+        async_wallet::run("get_transaction_history", operation_timeout(OperationClass::Read), move || {
+            with_active_wallet(|real_wallet| {
+                match real_wallet.get_transaction_history(limit.unwrap_or(50), offset.unwrap_or(0)) {
+                    Ok(transactions) => {
+                        let mapped: Vec<serde_json::Value> = transactions
+                            .into_iter()
+                            .map(|tx| serde_json::json!({
+                                "id": tx.id,
+                                "hash": tx.hash,
+                                "amount": tx.amount,
+                                "fee": tx.fee,
+                                "height": tx.height,
+                                "timestamp": tx.timestamp,
+                                "confirmations": tx.confirmations,
+                                "is_confirmed": tx.is_confirmed,
+                                "is_pending": tx.is_pending,
+                                "payment_id": tx.payment_id,
+                                "destination_addresses": tx.destination_addresses,
+                                "source_addresses": tx.source_addresses,
+                                "unlock_time": tx.unlock_time,
+                                "extra": tx.extra
+                            }))
+                            .collect();
+                        Ok(mapped)
+                    }
+                    Err(e) => Err(ApiError::from(e).context("Failed to get transaction history"))
+                }
+            })
+        })
+        .await
+    })
 }
 
 // Sync progress commands
+/// Sync progress, with the phase the sync is in: `connecting`,
+/// `downloading_blocks`, `scanning`, `synced` or `disconnected`
 #[tauri::command]
-async fn get_sync_progress() -> Result<serde_json::Value, String> {
-    let mut real_wallet = RealCryptoNoteWallet::new();
-
-    let _ = real_wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
-        .or_else(|_| real_wallet.create_wallet("fuego_password", "/tmp/fuego_wallet.wallet", None, 0));
-
-    match real_wallet.get_sync_progress() {
-        Ok(progress) => Ok(serde_json::json!({
-            "current_height": progress.current_height,
-            "total_height": progress.total_height,
-            "progress_percentage": progress.progress_percentage,
-            "estimated_time_remaining": progress.estimated_time_remaining,
-            "is_syncing": progress.is_syncing
-        })),
-        Err(e) => Err(format!("Failed to get sync progress: {}", e))
-    }
+async fn get_sync_progress() -> Result<serde_json::Value, ApiError> {
+    timed_command!("get_sync_progress", {
+        let mut real_wallet = RealCryptoNoteWallet::new();
+
+        let _ = real_wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
+            .or_else(|_| real_wallet.create_wallet("fuego_password", "/tmp/fuego_wallet.wallet", None, 0));
+
+        match real_wallet.get_sync_progress() {
+            Ok(progress) => {
+                let phase = real_wallet.get_sync_status()
+                    .map(|status| status.phase)
+                    .map_err(|e| ApiError::from(e).context("Failed to get sync status"))?;
+                Ok(serde_json::json!({
+                    "current_height": progress.current_height,
+                    "total_height": progress.total_height,
+                    "progress_percentage": progress.progress_percentage,
+                    "estimated_time_remaining": progress.estimated_time_remaining,
+                    "is_syncing": progress.is_syncing,
+                    "phase": phase
+                }))
+            }
+            Err(e) => Err(ApiError::from(e).context("Failed to get sync progress"))
+        }
+    })
 }
 
 #[tauri::command]
-async fn get_sync_status_json() -> Result<String, String> {
+async fn get_sync_status_json() -> Result<String, ApiError> {
     let mut real_wallet = RealCryptoNoteWallet::new();
 
     let _ = real_wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
@@ -1297,13 +4604,13 @@ async fn get_sync_status_json() -> Result<String, String> {
 
     match real_wallet.get_sync_status_json() {
         Ok(json) => Ok(json),
-        Err(e) => Err(format!("Failed to get sync status JSON: {}", e))
+        Err(e) => Err(ApiError::from(e).context("Failed to get sync status JSON"))
     }
 }
 
 // Address book commands
 #[tauri::command]
-async fn add_address_book_entry(address: String, label: Option<String>, description: Option<String>) -> Result<(), String> {
+async fn add_address_book_entry(address: String, label: Option<String>, description: Option<String>) -> Result<(), ApiError> {
     let mut real_wallet = RealCryptoNoteWallet::new();
 
     let _ = real_wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
@@ -1311,12 +4618,12 @@ async fn add_address_book_entry(address: String, label: Option<String>, descript
 
     match real_wallet.add_address_book_entry(&address, label.as_deref(), description.as_deref()) {
         Ok(_) => Ok(()),
-        Err(e) => Err(format!("Failed to add address book entry: {}", e))
+        Err(e) => Err(ApiError::from(e).context("Failed to add address book entry"))
     }
 }
 
 #[tauri::command]
-async fn remove_address_book_entry(address: String) -> Result<(), String> {
+async fn remove_address_book_entry(address: String) -> Result<(), ApiError> {
     let mut real_wallet = RealCryptoNoteWallet::new();
 
     let _ = real_wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
@@ -1324,12 +4631,12 @@ async fn remove_address_book_entry(address: String) -> Result<(), String> {
 
     match real_wallet.remove_address_book_entry(&address) {
         Ok(_) => Ok(()),
-        Err(e) => Err(format!("Failed to remove address book entry: {}", e))
+        Err(e) => Err(ApiError::from(e).context("Failed to remove address book entry"))
     }
 }
 
 #[tauri::command]
-async fn update_address_book_entry(address: String, label: Option<String>, description: Option<String>) -> Result<(), String> {
+async fn update_address_book_entry(address: String, label: Option<String>, description: Option<String>) -> Result<(), ApiError> {
     let mut real_wallet = RealCryptoNoteWallet::new();
 
     let _ = real_wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
@@ -1337,12 +4644,12 @@ async fn update_address_book_entry(address: String, label: Option<String>, descr
 
     match real_wallet.update_address_book_entry(&address, label.as_deref(), description.as_deref()) {
         Ok(_) => Ok(()),
-        Err(e) => Err(format!("Failed to update address book entry: {}", e))
+        Err(e) => Err(ApiError::from(e).context("Failed to update address book entry"))
     }
 }
 
 #[tauri::command]
-async fn get_address_book() -> Result<Vec<serde_json::Value>, String> {
+async fn get_address_book() -> Result<Vec<serde_json::Value>, ApiError> {
     let mut real_wallet = RealCryptoNoteWallet::new();
 
     let _ = real_wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
@@ -1363,12 +4670,78 @@ async fn get_address_book() -> Result<Vec<serde_json::Value>, String> {
                 .collect();
             Ok(mapped)
         }
-        Err(e) => Err(format!("Failed to get address book: {}", e))
+        Err(e) => Err(ApiError::from(e).context("Failed to get address book"))
+    }
+}
+
+/// Write the whole address book to a JSON or CSV file
+#[tauri::command]
+async fn address_book_export(path: String, format: Option<AddressBookFormat>) -> Result<usize, ApiError> {
+    let mut real_wallet = RealCryptoNoteWallet::new();
+    real_wallet.open_wallet(DEFAULT_WALLET_FILE, "fuego_password")
+        .map_err(|e| ApiError::from(e).context("Failed to open wallet"))?;
+
+    let entries = real_wallet.get_address_book()
+        .map_err(|e| ApiError::from(e).context("Failed to get address book"))?;
+    let count = entries.len();
+    export_address_book(Path::new(&path), entries, format.unwrap_or_default())?;
+    Ok(count)
+}
+
+/// Add the entries of a JSON or CSV address book file. Addresses already
+/// known are skipped or overwritten by `merge_strategy`, or the address book
+/// is replaced; rows that cannot be imported are reported with their line.
+#[tauri::command]
+async fn address_book_import(path: String, format: Option<AddressBookFormat>, merge_strategy: Option<MergeStrategy>) -> Result<AddressBookImportReport, ApiError> {
+    let rows = read_address_book(Path::new(&path), format.unwrap_or_default())?;
+    let strategy = merge_strategy.unwrap_or_default();
+    let mut real_wallet = RealCryptoNoteWallet::new();
+    real_wallet.open_wallet(DEFAULT_WALLET_FILE, "fuego_password")
+        .map_err(|e| ApiError::from(e).context("Failed to open wallet"))?;
+
+    let existing = real_wallet.get_address_book()
+        .map_err(|e| ApiError::from(e).context("Failed to get address book"))?;
+    let (plan, mut report) = plan_import(&existing, rows, strategy, is_valid_address_format);
+
+    if strategy == MergeStrategy::Replace {
+        for entry in &existing {
+            real_wallet.remove_address_book_entry(&entry.address)
+                .map_err(|e| ApiError::from(e).context("Failed to clear address book"))?;
+        }
+    }
+    for (line, entry) in plan.add {
+        let label = Some(entry.label.as_str()).filter(|l| !l.is_empty());
+        let description = Some(entry.description.as_str()).filter(|d| !d.is_empty());
+        if let Err(e) = real_wallet.add_address_book_entry(&entry.address, label, description) {
+            log::warn!("Failed to import address book entry {}: {}", entry.address, e);
+            report.record_failure(line, e.to_string());
+        }
+    }
+    for (line, entry) in plan.update {
+        if let Err(e) = real_wallet.update_address_book_entry(&entry.address, Some(&entry.label), Some(&entry.description)) {
+            log::warn!("Failed to overwrite address book entry {}: {}", entry.address, e);
+            report.record_failure(line, e.to_string());
+        }
     }
+    Ok(report)
 }
 
+/// Deprecated: `address_book_export` to JSON
 #[tauri::command]
-async fn mark_address_used(address: String) -> Result<(), String> {
+async fn addressbook_export(path: String) -> Result<usize, ApiError> {
+    address_book_export(path, None).await
+}
+
+/// Deprecated: `address_book_import` from JSON. With `merge` addresses that
+/// are already known are skipped, otherwise the address book is replaced.
+#[tauri::command]
+async fn addressbook_import(path: String, merge: bool) -> Result<AddressBookImportReport, ApiError> {
+    let strategy = if merge { MergeStrategy::Skip } else { MergeStrategy::Replace };
+    address_book_import(path, None, Some(strategy)).await
+}
+
+#[tauri::command]
+async fn mark_address_used(address: String) -> Result<(), ApiError> {
     let mut real_wallet = RealCryptoNoteWallet::new();
 
     let _ = real_wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
@@ -1376,12 +4749,12 @@ async fn mark_address_used(address: String) -> Result<(), String> {
 
     match real_wallet.mark_address_used(&address) {
         Ok(_) => Ok(()),
-        Err(e) => Err(format!("Failed to mark address as used: {}", e))
+        Err(e) => Err(ApiError::from(e).context("Failed to mark address as used"))
     }
 }
 
 #[tauri::command]
-async fn get_address_book_entry(address: String) -> Result<Option<serde_json::Value>, String> {
+async fn get_address_book_entry(address: String) -> Result<Option<serde_json::Value>, ApiError> {
     let mut real_wallet = RealCryptoNoteWallet::new();
 
     let _ = real_wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
@@ -1397,12 +4770,12 @@ async fn get_address_book_entry(address: String) -> Result<Option<serde_json::Va
             "use_count": entry.use_count
         }))),
         Ok(None) => Ok(None),
-        Err(e) => Err(format!("Failed to get address book entry: {}", e))
+        Err(e) => Err(ApiError::from(e).context("Failed to get address book entry"))
     }
 }
 
 #[tauri::command]
-async fn set_mining_pool(pool_address: Option<String>, worker_name: Option<String>) -> Result<(), String> {
+async fn set_mining_pool(pool_address: Option<String>, worker_name: Option<String>) -> Result<(), ApiError> {
     let mut real_wallet = RealCryptoNoteWallet::new();
 
     let _ = real_wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
@@ -1410,12 +4783,12 @@ async fn set_mining_pool(pool_address: Option<String>, worker_name: Option<Strin
 
     match real_wallet.set_mining_pool(pool_address.as_deref(), worker_name.as_deref()) {
         Ok(_) => Ok(()),
-        Err(e) => Err(format!("Failed to set mining pool: {}", e))
+        Err(e) => Err(ApiError::from(e).context("Failed to set mining pool"))
     }
 }
 
 #[tauri::command]
-async fn get_mining_stats_json() -> Result<String, String> {
+async fn get_mining_stats_json() -> Result<String, ApiError> {
     let mut real_wallet = RealCryptoNoteWallet::new();
 
     let _ = real_wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
@@ -1423,7 +4796,7 @@ async fn get_mining_stats_json() -> Result<String, String> {
 
     match real_wallet.get_mining_stats_json() {
         Ok(json) => Ok(json),
-        Err(e) => Err(format!("Failed to get mining statistics: {}", e))
+        Err(e) => Err(ApiError::from(e).context("Failed to get mining statistics"))
     }
 }
 
@@ -1435,44 +4808,139 @@ async fn wallet_start_mining(
     daemon_address: Option<String>,
     pool_wallet: Option<String>,
     pool_password: Option<String>
-) -> Result<bool, String> {
+) -> Result<bool, ApiError> {
     start_mining(threads, background, daemon_address, pool_wallet, pool_password).await
 }
 
 #[tauri::command]
-async fn wallet_stop_mining() -> Result<(), String> {
+async fn wallet_stop_mining() -> Result<(), ApiError> {
     stop_mining().await
 }
 
 #[tauri::command]
-async fn wallet_set_mining_pool(pool_address: Option<String>, worker_name: Option<String>) -> Result<(), String> {
+async fn wallet_set_mining_pool(pool_address: Option<String>, worker_name: Option<String>) -> Result<(), ApiError> {
     set_mining_pool(pool_address, worker_name).await
 }
 
 #[tauri::command]
-async fn get_mining_status() -> Result<String, String> {
+async fn get_mining_status() -> Result<String, ApiError> {
     get_mining_stats_json().await
 }
 
 // Secure key management commands
 #[tauri::command]
-async fn generate_seed_phrase() -> Result<String, String> {
+async fn generate_seed_phrase() -> Result<String, ApiError> {
     match RealCryptoNoteWallet::generate_seed_phrase() {
         Ok(seed) => Ok(seed),
-        Err(e) => Err(format!("Failed to generate seed phrase: {}", e))
+        Err(e) => Err(ApiError::from(e).context("Failed to generate seed phrase"))
     }
 }
 
 #[tauri::command]
-async fn validate_seed_phrase(seed_phrase: String) -> Result<bool, String> {
+async fn validate_seed_phrase(seed_phrase: String) -> Result<bool, ApiError> {
     match RealCryptoNoteWallet::validate_seed_phrase(&seed_phrase) {
         Ok(is_valid) => Ok(is_valid),
-        Err(e) => Err(format!("Failed to validate seed phrase: {}", e))
+        Err(e) => Err(ApiError::from(e).context("Failed to validate seed phrase"))
+    }
+}
+
+/// Check a seed phrase and show the primary address restoring it would give,
+/// without creating a wallet or writing any file
+#[tauri::command]
+async fn restore_preview(seed_phrase: String) -> Result<serde_json::Value, ApiError> {
+    let seed_phrase = seed_phrase.split_whitespace().collect::<Vec<_>>().join(" ");
+    let is_valid = RealCryptoNoteWallet::validate_seed_phrase(&seed_phrase)
+        .map_err(|e| ApiError::from(e).context("Failed to validate seed phrase"))?;
+    if !is_valid {
+        return Err(ApiError::invalid_input(format!(
+            "Invalid seed phrase: expected 12, 18 or 24 words, got {}",
+            seed_phrase.split_whitespace().count()
+        )));
+    }
+    let address = RealCryptoNoteWallet::address_from_seed(&seed_phrase)
+        .map_err(|e| ApiError::from(e).context("Invalid seed phrase"))?;
+    Ok(serde_json::json!({
+        "address": address,
+        "word_count": seed_phrase.split_whitespace().count(),
+    }))
+}
+
+/// Search for an address reading `pattern` after `fire` on up to
+/// `max_threads` pool threads. Progress is emitted as `vanity://progress`;
+/// the seed of the match is only returned by `vanity_reveal`.
+#[tauri::command]
+async fn vanity_generate(app: AppHandle, pattern: String, case_sensitive: Option<bool>, max_threads: Option<usize>) -> Result<VanityProgress, ApiError> {
+    start_vanity_search(Some(app), pattern, case_sensitive, max_threads).await
+}
+
+async fn start_vanity_search(app: Option<AppHandle>, pattern: String, case_sensitive: Option<bool>, max_threads: Option<usize>) -> Result<VanityProgress, ApiError> {
+    let search = VANITY_SEARCH.get().ok_or_else(|| ApiError::not_initialized("Vanity search"))?;
+    let pool = THREAD_POOL.get().ok_or_else(|| ApiError::not_initialized("Thread pool"))?;
+    let pattern = VanityPattern::new(&pattern, case_sensitive.unwrap_or(false)).map_err(ApiError::invalid_input)?;
+    let threads = max_threads
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()))
+        .clamp(1, pool.size());
+    let job = search.start(VanityJob::new(pattern, threads, Instant::now())).map_err(ApiError::invalid_input)?;
+
+    for _ in 0..threads {
+        let job = job.clone();
+        pool.execute(move || {
+            let pattern = job.pattern().clone();
+            job.run_worker(
+                || {
+                    let seed_phrase = RealCryptoNoteWallet::generate_seed_phrase()?;
+                    let address = RealCryptoNoteWallet::address_from_seed(&seed_phrase)?;
+                    Ok(VanityCandidate { address, seed_phrase })
+                },
+                |address| pattern.matches(address),
+            );
+        });
+    }
+
+    let reporter = job.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(VANITY_PROGRESS_INTERVAL).await;
+            let finished = reporter.is_finished();
+            let progress = reporter.progress();
+            if let Some(app) = &app {
+                let _ = app.emit(VANITY_PROGRESS_EVENT, &progress);
+            }
+            if finished {
+                log::info!("Vanity search ended {:?} after {} attempts", progress.status, progress.attempts);
+                break;
+            }
+        }
+    });
+
+    log::info!("Vanity search started on {} threads", threads);
+    Ok(job.progress())
+}
+
+/// Stop the running vanity search
+#[tauri::command]
+async fn vanity_cancel() -> Result<bool, ApiError> {
+    let search = VANITY_SEARCH.get().ok_or_else(|| ApiError::not_initialized("Vanity search"))?;
+    Ok(search.cancel())
+}
+
+/// The seed and address of the vanity match, once, after checking the password
+#[tauri::command]
+async fn vanity_reveal(password: String) -> Result<VanityCandidate, ApiError> {
+    let search = VANITY_SEARCH.get().ok_or_else(|| ApiError::not_initialized("Vanity search"))?;
+    let security_manager = SECURITY_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Security manager"))?;
+    if !security_manager.verify_password(&password) {
+        return Err(WalletError::InvalidPassword.into());
+    }
+    let job = search.current().ok_or_else(|| ApiError::not_found("No vanity search"))?;
+    match job.progress().status {
+        VanityStatus::Found => job.take_found().ok_or_else(|| ApiError::not_found("The vanity match was revealed already")),
+        status => Err(ApiError::not_found(format!("The vanity search has no match ({:?})", status))),
     }
 }
 
 #[tauri::command]
-async fn derive_keys_from_seed(seed_phrase: String, password: String) -> Result<(), String> {
+async fn derive_keys_from_seed(seed_phrase: String, password: String) -> Result<(), ApiError> {
     let mut real_wallet = RealCryptoNoteWallet::new();
 
     let _ = real_wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
@@ -1480,12 +4948,12 @@ async fn derive_keys_from_seed(seed_phrase: String, password: String) -> Result<
 
     match real_wallet.derive_keys_from_seed(&seed_phrase, &password) {
         Ok(_) => Ok(()),
-        Err(e) => Err(format!("Failed to derive keys from seed: {}", e))
+        Err(e) => Err(ApiError::from(e).context("Failed to derive keys from seed"))
     }
 }
 
 #[tauri::command]
-async fn get_seed_phrase(password: String) -> Result<String, String> {
+async fn get_seed_phrase(password: String) -> Result<String, ApiError> {
     let mut real_wallet = RealCryptoNoteWallet::new();
 
     let _ = real_wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
@@ -1493,12 +4961,64 @@ async fn get_seed_phrase(password: String) -> Result<String, String> {
 
     match real_wallet.get_seed_phrase(&password) {
         Ok(seed) => Ok(seed),
-        Err(e) => Err(format!("Failed to get seed phrase: {}", e))
+        Err(e) => Err(ApiError::from(e).context("Failed to get seed phrase"))
+    }
+}
+
+/// Export the address, its QR code and the seed phrase encrypted with
+/// `password` as a printable bundle, and as an HTML page at `html_path` if
+/// given. The plain seed is never written.
+#[tauri::command]
+async fn export_paper_wallet(password: String, html_path: Option<String>) -> Result<PaperWallet, ApiError> {
+    ensure_wallet_unlocked()?;
+    let paper = async_wallet::run("export_paper_wallet", operation_timeout(OperationClass::Read), move || {
+        let mut real_wallet = RealCryptoNoteWallet::new();
+        real_wallet.open_wallet(DEFAULT_WALLET_FILE, "fuego_password")
+            .map_err(|e| ApiError::from(e).context("Failed to open wallet"))?;
+        let address = real_wallet.get_address()?;
+        let seed_phrase = real_wallet.get_seed_phrase(&password)
+            .map_err(|e| ApiError::from(e).context("Failed to get seed phrase"))?;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or(Duration::from_secs(0)).as_secs();
+        PaperWallet::new(&address, &seed_phrase, &password, now).map_err(ApiError::security)
+    })
+    .await?;
+
+    if let Some(path) = &html_path {
+        paper.write_html(Path::new(path)).map_err(|e| ApiError::from(WalletError::StorageError(e)))?;
     }
+    log::info!(target: "audit", "Paper wallet exported for {}{}", paper.address,
+        html_path.as_deref().map(|path| format!(" to {}", path)).unwrap_or_default());
+    Ok(paper)
+}
+
+/// Decrypt a paper wallet bundle for the seed restore flow: the seed phrase
+/// and the address restoring it gives, which must be the printed one
+#[tauri::command]
+async fn import_paper_wallet(blob: String, password: String) -> Result<serde_json::Value, ApiError> {
+    let paper = PaperWallet::parse(&blob).map_err(ApiError::invalid_input)?;
+    // Deriving the key from the password is deliberately slow
+    async_wallet::run("import_paper_wallet", operation_timeout(OperationClass::Read), move || {
+        let seed_phrase = paper.decrypt_seed(&password)?;
+        let address = RealCryptoNoteWallet::address_from_seed(&seed_phrase)
+            .map_err(|e| ApiError::from(e).context("Invalid seed phrase in paper wallet"))?;
+        if address != paper.address {
+            return Err(ApiError::invalid_input(format!(
+                "The seed restores {}, not the printed address {}",
+                address, paper.address
+            )));
+        }
+        log::info!(target: "audit", "Paper wallet imported for {}", address);
+        Ok(serde_json::json!({
+            "address": address,
+            "word_count": seed_phrase.split_whitespace().count(),
+            "seed_phrase": seed_phrase,
+        }))
+    })
+    .await
 }
 
 #[tauri::command]
-async fn get_view_key() -> Result<String, String> {
+async fn get_view_key() -> Result<String, ApiError> {
     let mut real_wallet = RealCryptoNoteWallet::new();
 
     let _ = real_wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
@@ -1506,12 +5026,12 @@ async fn get_view_key() -> Result<String, String> {
 
     match real_wallet.get_view_key() {
         Ok(key) => Ok(key),
-        Err(e) => Err(format!("Failed to get view key: {}", e))
+        Err(e) => Err(ApiError::from(e).context("Failed to get view key"))
     }
 }
 
 #[tauri::command]
-async fn get_spend_key() -> Result<String, String> {
+async fn get_spend_key() -> Result<String, ApiError> {
     let mut real_wallet = RealCryptoNoteWallet::new();
 
     let _ = real_wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
@@ -1519,12 +5039,12 @@ async fn get_spend_key() -> Result<String, String> {
 
     match real_wallet.get_spend_key() {
         Ok(key) => Ok(key),
-        Err(e) => Err(format!("Failed to get spend key: {}", e))
+        Err(e) => Err(ApiError::from(e).context("Failed to get spend key"))
     }
 }
 
 #[tauri::command]
-async fn has_keys() -> Result<bool, String> {
+async fn has_keys() -> Result<bool, ApiError> {
     let mut real_wallet = RealCryptoNoteWallet::new();
 
     let _ = real_wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
@@ -1532,12 +5052,12 @@ async fn has_keys() -> Result<bool, String> {
 
     match real_wallet.has_keys() {
         Ok(has_keys) => Ok(has_keys),
-        Err(e) => Err(format!("Failed to check if wallet has keys: {}", e))
+        Err(e) => Err(ApiError::from(e).context("Failed to check if wallet has keys"))
     }
 }
 
 #[tauri::command]
-async fn export_keys() -> Result<String, String> {
+async fn export_keys() -> Result<String, ApiError> {
     let mut real_wallet = RealCryptoNoteWallet::new();
 
     let _ = real_wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
@@ -1545,12 +5065,12 @@ async fn export_keys() -> Result<String, String> {
 
     match real_wallet.export_keys() {
         Ok(keys) => Ok(keys),
-        Err(e) => Err(format!("Failed to export keys: {}", e))
+        Err(e) => Err(ApiError::from(e).context("Failed to export keys"))
     }
 }
 
 #[tauri::command]
-async fn import_keys(view_key: String, spend_key: String, address: String) -> Result<(), String> {
+async fn import_keys(view_key: String, spend_key: String, address: String) -> Result<(), ApiError> {
     let mut real_wallet = RealCryptoNoteWallet::new();
 
     let _ = real_wallet.open_wallet("/tmp/fuego_wallet.wallet", "fuego_password")
@@ -1558,8 +5078,277 @@ async fn import_keys(view_key: String, spend_key: String, address: String) -> Re
 
     match real_wallet.import_keys(&view_key, &spend_key, &address) {
         Ok(_) => Ok(()),
-        Err(e) => Err(format!("Failed to import keys: {}", e))
+        Err(e) => Err(ApiError::from(e).context("Failed to import keys"))
     }
 }
 
+/// Write the address and view and spend keys, encrypted with `password`, to
+/// a `.keybackup` file at `path`; returns the path written
+#[tauri::command]
+async fn export_key_backup(path: String, password: String) -> Result<String, ApiError> {
+    ensure_wallet_unlocked()?;
+    async_wallet::run("export_key_backup", operation_timeout(OperationClass::Read), move || {
+        let mut real_wallet = RealCryptoNoteWallet::new();
+        real_wallet.open_wallet(DEFAULT_WALLET_FILE, "fuego_password")
+            .map_err(|e| ApiError::from(e).context("Failed to open wallet"))?;
+        let keys = BackupKeys {
+            address: real_wallet.get_address()?,
+            view_key: real_wallet.get_view_key().map_err(|e| ApiError::from(e).context("Failed to get view key"))?,
+            spend_key: real_wallet.get_spend_key().map_err(|e| ApiError::from(e).context("Failed to get spend key"))?,
+        };
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or(Duration::from_secs(0)).as_secs();
+        let backup = KeyBackup::new(&keys, &password, now).map_err(ApiError::security)?;
+        let written = backup.write(Path::new(&path))?;
+        log::info!(target: "audit", "Key backup of {} exported to {}", keys.address, written.display());
+        Ok(written.to_string_lossy().into_owned())
+    })
+    .await
+}
+
+/// Restore the keys of the open wallet from a `.keybackup` file; returns the address
+#[tauri::command]
+async fn import_key_backup(path: String, password: String) -> Result<String, ApiError> {
+    ensure_wallet_unlocked()?;
+    async_wallet::run("import_key_backup", operation_timeout(OperationClass::Read), move || {
+        let keys = KeyBackup::read(Path::new(&path))?.decrypt(&password)?;
+        let mut real_wallet = RealCryptoNoteWallet::new();
+        real_wallet.open_wallet(DEFAULT_WALLET_FILE, "fuego_password")
+            .map_err(|e| ApiError::from(e).context("Failed to open wallet"))?;
+        real_wallet.import_keys(&keys.view_key, &keys.spend_key, &keys.address)
+            .map_err(|e| ApiError::from(e).context("Failed to import keys"))?;
+        log::info!(target: "audit", "Key backup of {} imported from {}", keys.address, path);
+        Ok(keys.address)
+    })
+    .await
+}
+
 // ===== PHASE 2.3: PRODUCTION FEATURES COMMANDS =====
+
+/// Gather the data that goes into a backup of the given type
+async fn collect_backup_data(backup_type: &BackupType) -> BackupData {
+    let (wallet, transactions, settings, network) = match backup_type {
+        BackupType::Full => (true, true, true, true),
+        BackupType::WalletOnly => (true, false, false, false),
+        BackupType::SettingsOnly => (false, false, true, false),
+        BackupType::TransactionsOnly => (false, true, false, false),
+    };
+    
+    BackupData {
+        wallet_info: if wallet { get_wallet_info(Some(true)).await.ok() } else { None },
+        transactions: if transactions { get_transactions(Some(100_000), Some(0)).await.ok() } else { None },
+        settings: if settings { get_app_settings().await.ok() } else { None },
+        network_status: if network { get_network_status().await.ok() } else { None },
+        wallet_file: if wallet { std::fs::read(DEFAULT_WALLET_FILE).ok() } else { None },
+        metadata: BackupMetadata {
+            version: "1.0.0".to_string(),
+            created_at: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or(Duration::from_secs(0)).as_secs(),
+            backup_type: backup_type.clone(),
+            fuego_version: env!("CARGO_PKG_VERSION").to_string(),
+            platform: std::env::consts::OS.to_string(),
+            name: None,
+            description: None,
+            password_hash: None,
+        },
+    }
+}
+
+/// Create a backup, emitting `backup://progress` while the archive is written
+/// and `backup://completed` or `backup://failed` at the end
+#[tauri::command]
+async fn backup_create(
+    app: AppHandle,
+    name: String,
+    description: Option<String>,
+    backup_type: BackupType,
+) -> Result<BackupOutcome, ApiError> {
+    create_backup(Some(app), name, description, backup_type).await
+}
+
+/// Create a backup, sending the `backup://` events to `app` when there is one
+async fn create_backup(
+    app: Option<AppHandle>,
+    name: String,
+    description: Option<String>,
+    backup_type: BackupType,
+) -> Result<BackupOutcome, ApiError> {
+    let backup_manager = BACKUP_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Backup manager"))?.clone();
+    let wallet_manager = ADVANCED_WALLET_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Advanced wallet manager"))?.clone();
+    
+    let operation_id = wallet_manager.start_operation("backup");
+    backup_manager.begin();
+    let data = collect_backup_data(&backup_type).await;
+    
+    let progress_app = app.clone();
+    let progress_operation = operation_id.clone();
+    let progress_manager = wallet_manager.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        backup_manager.create_backup_with_progress(
+            name,
+            description.unwrap_or_default(),
+            backup_type,
+            data,
+            |progress| {
+                if progress.total_bytes > 0 {
+                    progress_manager.update_operation_progress(
+                        &progress_operation,
+                        progress.bytes_written as f64 / progress.total_bytes as f64,
+                    );
+                }
+                if let Some(app) = &progress_app {
+                    let _ = app.emit("backup://progress", progress);
+                }
+            },
+        )
+    })
+    .await
+    .map_err(|e| ApiError::from(e).context("Backup task failed"))?;
+    
+    match result {
+        Ok(outcome) => {
+            let info = &outcome.info;
+            if matches!(info.backup_type, BackupType::Full | BackupType::WalletOnly)
+                && let Err(e) = WalletMetadata::record_backup(Path::new(DEFAULT_WALLET_FILE))
+            {
+                log::warn!("Failed to record wallet backup time: {}", e);
+            }
+            wallet_manager.end_operation(&operation_id, "completed", Some(info.id.clone()), None);
+            if let Some(app) = &app {
+                let _ = app.emit("backup://completed", &outcome);
+            }
+            log::info!("Backup {} created ({} bytes)", info.id, outcome.bytes_written);
+            Ok(outcome)
+        }
+        Err(e) => {
+            let cancelled = e == BackupError::Cancelled;
+            let status = if cancelled { "cancelled" } else { "failed" };
+            wallet_manager.end_operation(&operation_id, status, None, Some(e.to_string()));
+            if let Some(app) = &app {
+                let _ = app.emit("backup://failed", serde_json::json!({
+                    "error": e.to_string(),
+                    "cancelled": cancelled
+                }));
+            }
+            log::warn!("Backup {}: {}", status, e);
+            Err(ApiError::from(e))
+        }
+    }
+}
+
+/// Abort the backup currently being created
+#[tauri::command]
+async fn backup_cancel() -> Result<(), ApiError> {
+    let backup_manager = BACKUP_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Backup manager"))?;
+    backup_manager.cancel();
+    log::info!("Backup cancellation requested");
+    Ok(())
+}
+
+/// Restore a backup and apply it to the settings, wallet file and transaction history
+#[tauri::command]
+async fn backup_restore(backup_id: String) -> Result<RestoreSummary, ApiError> {
+    let backup_manager = BACKUP_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Backup manager"))?;
+    let settings_manager = SETTINGS_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Settings manager"))?;
+    let wallet_manager = ADVANCED_WALLET_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Advanced wallet manager"))?;
+    
+    let data = backup_manager.restore_backup(backup_id.clone()).map_err(ApiError::backup)?;
+    let summary = backup_manager.apply_restore(
+        &data,
+        settings_manager,
+        wallet_manager,
+        Path::new(DEFAULT_WALLET_FILE),
+        |path| {
+            let mut wallet = RealCryptoNoteWallet::new();
+            wallet.open_wallet(&path.to_string_lossy(), "fuego_password")
+                .map_err(|e| e.to_string())
+        },
+    );
+    
+    if let Some(cache) = CACHE.get() {
+        cache.clear();
+    }
+    
+    if summary.errors.is_empty() {
+        log::info!("Backup {} restored", backup_id);
+    } else {
+        log::warn!("Backup {} partially restored: {:?}", backup_id, summary.errors);
+    }
+    Ok(summary)
+}
+
+/// Re-check a backup archive against its checksum manifest
+#[tauri::command]
+async fn backup_verify(backup_id: String) -> Result<BackupVerification, ApiError> {
+    let backup_manager = BACKUP_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Backup manager"))?;
+    let verification = backup_manager.verify_backup(backup_id.clone()).map_err(ApiError::backup)?;
+    if verification.is_legacy {
+        log::warn!("Backup {} has no checksum manifest and can only be restored unverified", backup_id);
+    } else if !verification.is_valid {
+        log::warn!("Backup {} failed verification: {:?}", backup_id, verification);
+    }
+    Ok(verification)
+}
+
+/// Import an external backup archive into the managed backup directory
+#[tauri::command]
+async fn backup_import(path: String, password: Option<String>) -> Result<BackupInfo, ApiError> {
+    let backup_manager = BACKUP_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Backup manager"))?;
+    let info = backup_manager.import_backup(Path::new(&path), password.as_deref()).map_err(ApiError::backup)?;
+    log::info!("Imported backup {} as {}", path, info.id);
+    Ok(info)
+}
+
+/// Rebuild the backups index from the archives on disk
+#[tauri::command]
+async fn backup_rebuild_index() -> Result<Vec<BackupInfo>, ApiError> {
+    let backup_manager = BACKUP_MANAGER.get().ok_or_else(|| ApiError::not_initialized("Backup manager"))?;
+    let backups = backup_manager.rebuild_index().map_err(ApiError::backup)?;
+    log::info!("Rebuilt backups index with {} entries", backups.len());
+    Ok(backups)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::backend::MockWalletBackend;
+
+    const RECIPIENT: &str = "fire1jNwRRUYGENanfBwVhehZXVcQVFx3dH3D3Z7UNC17FePBr27DDwctyL2ePwDPz4fypwpNQpfXbp6wavubvSn6ToisC5NUy";
+
+    fn mock_wallet(fee: u64) -> (MockWalletBackend, RealCryptoNoteWallet) {
+        let backend = MockWalletBackend::with_balance(1_000_000);
+        {
+            let mut state = backend.state();
+            state.fee = fee;
+            state.network_height = 100;
+            state.blocks_per_refresh = 10;
+        }
+        let wallet = RealCryptoNoteWallet::with_backend(Box::new(backend.clone()));
+        (backend, wallet)
+    }
+
+    #[tokio::test]
+    async fn test_commands_follow_the_active_wallet() {
+        let registry = WALLET_REGISTRY.get_or_init(|| Arc::new(WalletRegistry::default())).clone();
+        REFRESH_COORDINATOR.get_or_init(|| Arc::new(RefreshCoordinator::new()));
+        WALLET_LOCK.get_or_init(|| Arc::new(WalletLock::new()));
+
+        let (first, first_wallet) = mock_wallet(100);
+        let (second, second_wallet) = mock_wallet(200);
+        let sent = second_wallet.send_transaction(RECIPIENT, 1_000, None, 0).unwrap();
+        let first_id = registry.register("/wallets/first.wallet", first_wallet).unwrap();
+        let second_id = registry.register("/wallets/second.wallet", second_wallet).unwrap();
+        wallet_set_active(second_id).await.unwrap();
+
+        refresh_wallet(None).await.unwrap();
+        assert_eq!(second.state().sync_height, 10);
+        assert_eq!(first.state().sync_height, 0);
+
+        assert_eq!(estimate_fee_detailed(RECIPIENT.to_string(), 1_000, None).await.unwrap().base_fee, 200);
+        let proof = tx_generate_proof(sent.clone(), RECIPIENT.to_string(), None).await.unwrap();
+        assert!(tx_verify_proof(sent.clone(), RECIPIENT.to_string(), proof, None).await.unwrap());
+
+        wallet_set_active(first_id).await.unwrap();
+        assert_eq!(estimate_fee_detailed(RECIPIENT.to_string(), 1_000, None).await.unwrap().base_fee, 100);
+        assert!(tx_generate_proof(sent, RECIPIENT.to_string(), None).await.is_err());
+        registry.close_all();
+    }
+}
@@ -11,6 +11,7 @@ use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use std::thread;
 use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use crate::utils::locks::MutexExt;
 
 /// Performance metrics for monitoring
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -158,7 +159,7 @@ impl ResourceMonitor {
     
     /// Get current performance metrics
     pub fn get_metrics(&self) -> PerformanceMetrics {
-        self.metrics.lock().unwrap().clone()
+        self.metrics.lock_recover().clone()
     }
     
     /// Optimize memory usage
@@ -261,7 +262,7 @@ impl ResourceMonitor {
 #[derive(Debug)]
 pub struct AdvancedCache<K, V> {
     data: Arc<Mutex<HashMap<K, CacheEntry<V>>>>,
-    max_size: usize,
+    max_size: AtomicUsize,
     stats: Arc<Mutex<CacheStats>>,
 }
 
@@ -281,7 +282,7 @@ where
     pub fn new(max_size: usize) -> Self {
         Self {
             data: Arc::new(Mutex::new(HashMap::new())),
-            max_size,
+            max_size: AtomicUsize::new(max_size),
             stats: Arc::new(Mutex::new(CacheStats::new(max_size))),
         }
     }
@@ -312,7 +313,7 @@ where
     pub fn insert(&self, key: K, value: V) {
         if let Ok(mut data) = self.data.lock() {
             // Check if we need to evict entries
-            if data.len() >= self.max_size {
+            if data.len() >= self.max_size.load(Ordering::Relaxed) {
                 self.evict_lru(&mut data);
             }
             
@@ -346,6 +347,26 @@ where
         }
     }
     
+    /// Change the capacity, evicting the least recently used entries that no
+    /// longer fit. Returns the number of entries evicted.
+    pub fn resize(&self, max_size: usize) -> usize {
+        let max_size = max_size.max(1);
+        let Ok(mut data) = self.data.lock() else {
+            return 0;
+        };
+        self.max_size.store(max_size, Ordering::Relaxed);
+
+        let before = data.len();
+        while data.len() > max_size {
+            self.evict_lru(&mut data);
+        }
+        if let Ok(mut stats) = self.stats.lock() {
+            stats.max_size = max_size;
+            stats.size.store(data.len(), Ordering::Relaxed);
+        }
+        before - data.len()
+    }
+
     /// Clear the cache
     pub fn clear(&self) {
         if let Ok(mut data) = self.data.lock() {
@@ -359,7 +380,7 @@ where
     
     /// Get cache statistics
     pub fn stats(&self) -> CacheStats {
-        let stats = self.stats.lock().unwrap();
+        let stats = self.stats.lock_recover();
         CacheStats {
             hits: AtomicU64::new(stats.hits.load(Ordering::Relaxed)),
             misses: AtomicU64::new(stats.misses.load(Ordering::Relaxed)),
@@ -387,7 +408,7 @@ impl ThreadPool {
         for _ in 0..size {
             let receiver = Arc::clone(&receiver);
             let worker = thread::spawn(move || {
-                while let Ok(job) = receiver.lock().unwrap().recv() {
+                while let Ok(job) = receiver.lock_recover().recv() {
                     job();
                 }
             });
@@ -397,6 +418,11 @@ impl ThreadPool {
         Self { workers, sender }
     }
     
+    /// Number of worker threads
+    pub fn size(&self) -> usize {
+        self.workers.len()
+    }
+
     /// Execute a job in the thread pool
     pub fn execute<F>(&self, f: F)
     where
@@ -447,7 +473,7 @@ impl PerformanceProfiler {
     
     /// Get timing results
     pub fn get_results(&self) -> HashMap<String, Duration> {
-        self.results.lock().unwrap().clone()
+        self.results.lock_recover().clone()
     }
     
     /// Clear all timing data
@@ -496,7 +522,7 @@ impl<T> MemoryPool<T> {
     
     /// Get pool size
     pub fn size(&self) -> usize {
-        self.pool.lock().unwrap().len()
+        self.pool.lock_recover().len()
     }
 }
 
@@ -512,6 +538,22 @@ mod tests {
         assert_eq!(cache.get(&"key1"), Some("value1"));
         assert_eq!(cache.get(&"key2"), None);
     }
+
+    #[test]
+    fn test_cache_resize_evicts_least_recently_used() {
+        let cache = AdvancedCache::new(10);
+        for i in 0..5 {
+            cache.insert(i, i);
+            thread::sleep(Duration::from_millis(2));
+        }
+        cache.get(&0);
+
+        assert_eq!(cache.resize(2), 3);
+        assert_eq!(cache.get(&0), Some(0));
+        assert_eq!(cache.get(&4), Some(4));
+        assert_eq!(cache.get(&1), None);
+        assert_eq!(cache.stats().max_size, 2);
+    }
     
     #[test]
     fn test_performance_profiler() {
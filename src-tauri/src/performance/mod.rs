@@ -4,9 +4,32 @@
 //! Performance optimization module for Fuego Desktop Wallet
 
 use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use serde::{Deserialize, Serialize};
+use crate::utils::storage::atomic_write;
+use crate::utils::locks::MutexExt;
+
+/// Most metrics kept on disk; the oldest are dropped beyond this
+pub const MAX_PERSISTED_METRICS: usize = 20_000;
+
+/// Growth of an operation's average or p95 latency flagged as a regression
+pub const REGRESSION_FACTOR: f64 = 1.5;
+/// Fewest samples in each window for an operation to be compared
+pub const MIN_REGRESSION_SAMPLES: usize = 5;
+
+/// Assumed size of one cached value, used to turn the cache budget of
+/// `PerformanceSettings::cache_size_mb` into an entry count. Cached values are
+/// mostly daemon and explorer JSON responses of a few kilobytes.
+pub const APPROX_CACHE_ENTRY_BYTES: usize = 4 * 1024;
+
+/// Number of cache entries that fit in `cache_size_mb`, at least one
+pub fn cache_capacity_for_mb(cache_size_mb: u32) -> usize {
+    (cache_size_mb as usize * 1024 * 1024 / APPROX_CACHE_ENTRY_BYTES).max(1)
+}
 
 /// Performance metrics
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -70,7 +93,7 @@ impl<T> CacheEntry<T> {
 #[derive(Debug)]
 pub struct Cache<T> {
     data: Arc<Mutex<HashMap<String, CacheEntry<T>>>>,
-    max_size: usize,
+    max_size: AtomicUsize,
     default_ttl: Duration,
 }
 
@@ -78,14 +101,14 @@ impl<T: Clone> Cache<T> {
     pub fn new(max_size: usize, default_ttl: Duration) -> Self {
         Self {
             data: Arc::new(Mutex::new(HashMap::new())),
-            max_size,
+            max_size: AtomicUsize::new(max_size),
             default_ttl,
         }
     }
     
     /// Get cached value
     pub fn get(&self, key: &str) -> Option<T> {
-        let mut cache = self.data.lock().unwrap();
+        let mut cache = self.data.lock_recover();
         
         if let Some(entry) = cache.get(key) {
             if entry.is_expired() {
@@ -105,37 +128,96 @@ impl<T: Clone> Cache<T> {
     
     /// Set cached value with custom TTL
     pub fn set_with_ttl(&self, key: String, value: T, ttl: Duration) {
-        let mut cache = self.data.lock().unwrap();
+        let mut cache = self.data.lock_recover();
         
         // Remove expired entries
         self.cleanup_expired(&mut cache);
         
         // Check size limit
-        if cache.len() >= self.max_size {
-            // Remove oldest entry
-            if let Some(oldest_key) = cache.keys().next().cloned() {
-                cache.remove(&oldest_key);
-            }
+        if !cache.contains_key(&key) {
+            Self::evict_oldest(&mut cache, self.max_size().saturating_sub(1));
         }
         
         cache.insert(key, CacheEntry::new(value, ttl));
     }
+
+    pub fn max_size(&self) -> usize {
+        self.max_size.load(Ordering::Relaxed)
+    }
+
+    /// Change the capacity. Entries that no longer fit are evicted, expired
+    /// ones first, then the oldest. Returns the number of entries evicted.
+    pub fn resize(&self, max_size: usize) -> usize {
+        let max_size = max_size.max(1);
+        let mut cache = self.data.lock_recover();
+        self.max_size.store(max_size, Ordering::Relaxed);
+
+        let before = cache.len();
+        if before > max_size {
+            self.cleanup_expired(&mut cache);
+            Self::evict_oldest(&mut cache, max_size);
+        }
+        before - cache.len()
+    }
+
+    /// Drop the oldest entries until at most `keep` remain
+    fn evict_oldest(cache: &mut HashMap<String, CacheEntry<T>>, keep: usize) {
+        if cache.len() <= keep {
+            return;
+        }
+        let mut by_age: Vec<(String, Instant)> = cache.iter().map(|(k, e)| (k.clone(), e.created_at)).collect();
+        by_age.sort_by_key(|(_, created_at)| *created_at);
+        let excess = cache.len() - keep;
+        for (key, _) in by_age.into_iter().take(excess) {
+            cache.remove(&key);
+        }
+    }
     
+    /// Return the value cached for `key` if it is younger than `fresh_for`,
+    /// otherwise `load` it and cache it for `ttl`.
+    ///
+    /// A value older than `fresh_for` that has not expired yet is returned
+    /// without loading and flagged stale (`true`), so the caller can refresh it
+    /// off the hot path. `force` always loads.
+    pub fn get_or_load<F>(&self, key: &str, fresh_for: Duration, ttl: Duration, force: bool, load: F) -> Result<(T, bool), String>
+    where
+        F: FnOnce() -> Result<T, String>,
+    {
+        if !force && let Some((value, age)) = self.get_with_age(key) {
+            return Ok((value, age >= fresh_for));
+        }
+        let value = load()?;
+        self.set_with_ttl(key.to_string(), value.clone(), ttl);
+        Ok((value, false))
+    }
+
+    fn get_with_age(&self, key: &str) -> Option<(T, Duration)> {
+        let mut cache = self.data.lock_recover();
+        match cache.get(key) {
+            Some(entry) if entry.is_expired() => {
+                cache.remove(key);
+                None
+            }
+            Some(entry) => Some((entry.data.clone(), entry.created_at.elapsed())),
+            None => None,
+        }
+    }
+
     /// Remove cached value
     pub fn remove(&self, key: &str) {
-        let mut cache = self.data.lock().unwrap();
+        let mut cache = self.data.lock_recover();
         cache.remove(key);
     }
     
     /// Clear all cached values
     pub fn clear(&self) {
-        let mut cache = self.data.lock().unwrap();
+        let mut cache = self.data.lock_recover();
         cache.clear();
     }
     
     /// Get cache statistics
     pub fn stats(&self) -> CacheStats {
-        let cache = self.data.lock().unwrap();
+        let cache = self.data.lock_recover();
         let total_entries = cache.len();
         let expired_entries = cache.values().filter(|entry| entry.is_expired()).count();
         
@@ -143,7 +225,7 @@ impl<T: Clone> Cache<T> {
             total_entries,
             expired_entries,
             active_entries: total_entries - expired_entries,
-            max_size: self.max_size,
+            max_size: self.max_size(),
         }
     }
     
@@ -186,10 +268,22 @@ impl PerformanceMonitor {
             config: self.config.clone(),
         }
     }
+
+    /// Run `operation` and record it as `operation_name`, successful if it
+    /// returns `Ok`. The result is handed back unchanged.
+    pub async fn time<T, E, F>(&self, operation_name: &str, operation: F) -> Result<T, E>
+    where
+        F: Future<Output = Result<T, E>>,
+    {
+        let timer = self.start_timing(operation_name.to_string());
+        let result = operation.await;
+        timer.finish(result.is_ok());
+        result
+    }
     
     /// Get performance metrics
     pub fn get_metrics(&self, operation_name: Option<&str>) -> Vec<PerformanceMetrics> {
-        let metrics = self.metrics.lock().unwrap();
+        let metrics = self.metrics.lock_recover();
         
         if let Some(name) = operation_name {
             metrics.iter()
@@ -203,7 +297,7 @@ impl PerformanceMonitor {
     
     /// Get average performance for operation
     pub fn get_average_performance(&self, operation_name: &str) -> Option<AveragePerformance> {
-        let metrics = self.metrics.lock().unwrap();
+        let metrics = self.metrics.lock_recover();
         let operation_metrics: Vec<_> = metrics.iter()
             .filter(|m| m.operation_name == operation_name)
             .collect();
@@ -225,6 +319,147 @@ impl PerformanceMonitor {
         })
     }
     
+    /// Duration statistics of the metrics recorded in the last `since_seconds`,
+    /// optionally limited to one operation
+    pub fn get_performance_window(&self, operation_name: Option<&str>, since_seconds: u64) -> PerformanceWindow {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.window_since(operation_name, now.saturating_sub(since_seconds), since_seconds)
+    }
+    
+    fn window_since(&self, operation_name: Option<&str>, cutoff: u64, since_seconds: u64) -> PerformanceWindow {
+        self.window_between(operation_name, cutoff, u64::MAX, since_seconds)
+    }
+    
+    /// Statistics of the metrics recorded from `from` up to, not including, `until`
+    fn window_between(&self, operation_name: Option<&str>, from: u64, until: u64, since_seconds: u64) -> PerformanceWindow {
+        let metrics = self.metrics.lock_recover();
+        let windowed: Vec<&PerformanceMetrics> = metrics.iter()
+            .filter(|m| m.timestamp >= from && m.timestamp < until)
+            .filter(|m| operation_name.is_none_or(|name| m.operation_name == name))
+            .collect();
+        
+        let mut window = PerformanceWindow {
+            operation_name: operation_name.map(String::from),
+            since_seconds,
+            ..Default::default()
+        };
+        if windowed.is_empty() {
+            return window;
+        }
+        
+        let mut durations: Vec<u64> = windowed.iter().map(|m| m.duration_ms).collect();
+        durations.sort_unstable();
+        
+        window.sample_count = durations.len();
+        window.average_duration_ms = durations.iter().sum::<u64>() / durations.len() as u64;
+        window.p50_ms = percentile(&durations, 50.0);
+        window.p95_ms = percentile(&durations, 95.0);
+        window.p99_ms = percentile(&durations, 99.0);
+        window.max_ms = durations[durations.len() - 1];
+        window.success_rate = windowed.iter().filter(|m| m.success).count() as f64 / windowed.len() as f64;
+        window
+    }
+    
+    /// Operations that got slower: the last `recent_window` seconds against the
+    /// `baseline_window` seconds before them. An operation is flagged when its
+    /// average or p95 grew by more than `REGRESSION_FACTOR`; those with fewer
+    /// than `MIN_REGRESSION_SAMPLES` in either window are left out. Worst first.
+    pub fn detect_regressions(&self, baseline_window: u64, recent_window: u64) -> Vec<Regression> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.regressions_at(now, baseline_window, recent_window)
+    }
+    
+    fn regressions_at(&self, now: u64, baseline_window: u64, recent_window: u64) -> Vec<Regression> {
+        let recent_from = now.saturating_sub(recent_window);
+        let baseline_from = recent_from.saturating_sub(baseline_window);
+        let mut operations: Vec<String> = self.metrics.lock_recover().iter()
+            .filter(|m| m.timestamp >= baseline_from)
+            .map(|m| m.operation_name.clone())
+            .collect();
+        operations.sort_unstable();
+        operations.dedup();
+        
+        let growth = |recent: u64, baseline: u64| recent as f64 / baseline.max(1) as f64;
+        let mut regressions: Vec<Regression> = operations.into_iter()
+            .filter_map(|name| {
+                let baseline = self.window_between(Some(&name), baseline_from, recent_from, baseline_window);
+                let recent = self.window_between(Some(&name), recent_from, u64::MAX, recent_window);
+                if baseline.sample_count < MIN_REGRESSION_SAMPLES || recent.sample_count < MIN_REGRESSION_SAMPLES {
+                    return None;
+                }
+                let average_factor = growth(recent.average_duration_ms, baseline.average_duration_ms);
+                let p95_factor = growth(recent.p95_ms, baseline.p95_ms);
+                (average_factor > REGRESSION_FACTOR || p95_factor > REGRESSION_FACTOR).then_some(Regression {
+                    operation_name: name,
+                    baseline,
+                    recent,
+                    average_factor,
+                    p95_factor,
+                })
+            })
+            .collect();
+        regressions.sort_by(|a, b| b.average_factor.max(b.p95_factor).total_cmp(&a.average_factor.max(a.p95_factor)));
+        regressions
+    }
+    
+    /// Restore metrics saved by an earlier session, keeping those still within
+    /// the retention period. A missing file restores nothing; a corrupt one is
+    /// ignored with a warning. Returns the number of metrics restored.
+    pub fn load(&self, path: &Path) -> Result<usize, String> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(format!("Failed to read performance metrics: {}", e)),
+        };
+        let mut saved: Vec<PerformanceMetrics> = match serde_json::from_str(&content) {
+            Ok(saved) => saved,
+            Err(e) => {
+                log::warn!("Ignoring corrupt performance metrics file {}: {}", path.display(), e);
+                return Ok(0);
+            }
+        };
+
+        let cutoff_time = self.retention_cutoff();
+        saved.retain(|m| m.timestamp > cutoff_time);
+        let restored = saved.len();
+
+        let mut metrics = self.metrics.lock_recover();
+        saved.append(&mut metrics);
+        *metrics = saved;
+        Ok(restored)
+    }
+
+    /// Write the metrics within the retention period to `path`, at most
+    /// `MAX_PERSISTED_METRICS` of the newest
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let cutoff_time = self.retention_cutoff();
+        let mut kept: Vec<PerformanceMetrics> = self.metrics.lock_recover().iter()
+            .filter(|m| m.timestamp > cutoff_time)
+            .cloned()
+            .collect();
+        kept.sort_by_key(|m| m.timestamp);
+        let excess = kept.len().saturating_sub(MAX_PERSISTED_METRICS);
+        kept.drain(..excess);
+
+        let content = serde_json::to_string(&kept)
+            .map_err(|e| format!("Failed to serialize performance metrics: {}", e))?;
+        atomic_write(path, content.as_bytes())
+    }
+
+    fn retention_cutoff(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+            .saturating_sub(self.config.metrics_retention_days as u64 * 24 * 60 * 60)
+    }
+
     /// Cleanup old metrics
     pub fn cleanup_old_metrics(&self) {
         let cutoff_time = SystemTime::now()
@@ -232,7 +467,7 @@ impl PerformanceMonitor {
             .unwrap()
             .as_secs() - (self.config.metrics_retention_days as u64 * 24 * 60 * 60);
         
-        let mut metrics = self.metrics.lock().unwrap();
+        let mut metrics = self.metrics.lock_recover();
         metrics.retain(|m| m.timestamp > cutoff_time);
     }
 }
@@ -261,7 +496,7 @@ impl PerformanceTimer {
             success,
         };
         
-        let mut metrics = self.monitor.lock().unwrap();
+        let mut metrics = self.monitor.lock_recover();
         metrics.push(metric);
         
         // Cleanup old metrics if enabled
@@ -303,6 +538,38 @@ pub struct AveragePerformance {
     pub total_calls: usize,
 }
 
+/// Duration statistics over a time window of recorded metrics
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PerformanceWindow {
+    pub operation_name: Option<String>,
+    pub since_seconds: u64,
+    pub sample_count: usize,
+    pub average_duration_ms: u64,
+    pub p50_ms: u64,
+    pub p95_ms: u64,
+    pub p99_ms: u64,
+    pub max_ms: u64,
+    pub success_rate: f64,
+}
+
+/// An operation slower lately than before
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Regression {
+    pub operation_name: String,
+    pub baseline: PerformanceWindow,
+    pub recent: PerformanceWindow,
+    /// Recent average over the baseline average
+    pub average_factor: f64,
+    /// Recent p95 over the baseline p95
+    pub p95_factor: f64,
+}
+
+/// Nearest-rank percentile of sorted, non-empty `values`
+fn percentile(sorted: &[u64], pct: f64) -> u64 {
+    let rank = ((pct / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
 /// Background task manager for performance optimization
 #[derive(Debug)]
 pub struct BackgroundTaskManager {
@@ -326,7 +593,7 @@ impl BackgroundTaskManager {
     
     /// Register a background task
     pub fn register_task(&self, name: String, interval: Duration) {
-        let mut tasks = self.tasks.lock().unwrap();
+        let mut tasks = self.tasks.lock_recover();
         tasks.insert(name.clone(), BackgroundTask {
             name,
             interval,
@@ -337,7 +604,7 @@ impl BackgroundTaskManager {
     
     /// Check if task should run
     pub fn should_run(&self, task_name: &str) -> bool {
-        let tasks = self.tasks.lock().unwrap();
+        let tasks = self.tasks.lock_recover();
         
         if let Some(task) = tasks.get(task_name) {
             task.enabled && task.last_run.elapsed() >= task.interval
@@ -348,7 +615,7 @@ impl BackgroundTaskManager {
     
     /// Mark task as completed
     pub fn mark_completed(&self, task_name: &str) {
-        let mut tasks = self.tasks.lock().unwrap();
+        let mut tasks = self.tasks.lock_recover();
         
         if let Some(task) = tasks.get_mut(task_name) {
             task.last_run = Instant::now();
@@ -357,7 +624,7 @@ impl BackgroundTaskManager {
     
     /// Enable/disable task
     pub fn set_task_enabled(&self, task_name: &str, enabled: bool) {
-        let mut tasks = self.tasks.lock().unwrap();
+        let mut tasks = self.tasks.lock_recover();
         
         if let Some(task) = tasks.get_mut(task_name) {
             task.enabled = enabled;
@@ -366,19 +633,32 @@ impl BackgroundTaskManager {
     
     /// Get task status
     pub fn get_task_status(&self, task_name: &str) -> Option<TaskStatus> {
-        let tasks = self.tasks.lock().unwrap();
+        let tasks = self.tasks.lock_recover();
         
         if let Some(task) = tasks.get(task_name) {
-            Some(TaskStatus {
-                name: task.name.clone(),
-                enabled: task.enabled,
-                last_run: task.last_run,
-                next_run_in: task.interval.saturating_sub(task.last_run.elapsed()),
-            })
+            Some(task.status())
         } else {
             None
         }
     }
+
+    /// Status of every registered task, by name
+    pub fn list_tasks(&self) -> Vec<TaskStatus> {
+        let mut statuses: Vec<TaskStatus> = self.tasks.lock_recover().values().map(BackgroundTask::status).collect();
+        statuses.sort_by(|a, b| a.name.cmp(&b.name));
+        statuses
+    }
+}
+
+impl BackgroundTask {
+    fn status(&self) -> TaskStatus {
+        TaskStatus {
+            name: self.name.clone(),
+            enabled: self.enabled,
+            last_run: self.last_run,
+            next_run_in: self.interval.saturating_sub(self.last_run.elapsed()),
+        }
+    }
 }
 
 /// Task status information
@@ -406,7 +686,7 @@ impl<T> BatchProcessor<T> {
     
     /// Add item to batch
     pub fn add_item(&self, item: T) -> Option<Vec<T>> {
-        let mut buffer = self.buffer.lock().unwrap();
+        let mut buffer = self.buffer.lock_recover();
         buffer.push(item);
         
         if buffer.len() >= self.batch_size {
@@ -418,13 +698,13 @@ impl<T> BatchProcessor<T> {
     
     /// Get current batch
     pub fn get_batch(&self) -> Vec<T> {
-        let mut buffer = self.buffer.lock().unwrap();
+        let mut buffer = self.buffer.lock_recover();
         buffer.drain(..).collect()
     }
     
     /// Check if batch is ready
     pub fn is_batch_ready(&self) -> bool {
-        let buffer = self.buffer.lock().unwrap();
+        let buffer = self.buffer.lock_recover();
         buffer.len() >= self.batch_size
     }
 }
@@ -434,6 +714,7 @@ mod tests {
     use super::*;
     use std::thread;
     use std::time::Duration;
+    use crate::utils::testing::TempDir;
 
     #[test]
     fn test_cache_basic_operations() {
@@ -448,6 +729,57 @@ mod tests {
         assert_eq!(cache.get("key1"), None);
     }
     
+    #[test]
+    fn test_cache_capacity_follows_size_setting() {
+        // 4 KiB per entry: the default 100 MB holds 25,600 entries
+        assert_eq!(cache_capacity_for_mb(100), 25_600);
+        assert_eq!(cache_capacity_for_mb(1), 256);
+        assert_eq!(cache_capacity_for_mb(0), 1);
+
+        let cache = Cache::new(cache_capacity_for_mb(1), Duration::from_secs(60));
+        for i in 0..10 {
+            cache.set(format!("key{}", i), i);
+            std::thread::sleep(Duration::from_millis(2));
+        }
+        assert_eq!(cache.resize(4), 6);
+        assert_eq!(cache.stats().max_size, 4);
+        // The newest entries stay
+        assert_eq!(cache.get("key9"), Some(9));
+        assert_eq!(cache.get("key5"), None);
+
+        cache.set("key10".to_string(), 10);
+        assert_eq!(cache.stats().total_entries, 4);
+        assert_eq!(cache.get("key6"), None);
+    }
+
+    #[test]
+    fn test_get_or_load_reuses_fresh_values() {
+        let cache = Cache::new(10, Duration::from_secs(60));
+        let loads = std::cell::Cell::new(0);
+        let load = || {
+            loads.set(loads.get() + 1);
+            Ok(format!("info {}", loads.get()))
+        };
+        let fresh = Duration::from_millis(100);
+        let ttl = Duration::from_secs(5);
+
+        assert_eq!(cache.get_or_load("wallet", fresh, ttl, false, load).unwrap(), ("info 1".to_string(), false));
+        assert_eq!(cache.get_or_load("wallet", fresh, ttl, false, load).unwrap(), ("info 1".to_string(), false));
+        assert_eq!(loads.get(), 1);
+
+        assert_eq!(cache.get_or_load("wallet", fresh, ttl, true, load).unwrap(), ("info 2".to_string(), false));
+        assert_eq!(loads.get(), 2);
+
+        // Past the fresh window the old value is served and flagged for refresh
+        thread::sleep(Duration::from_millis(120));
+        assert_eq!(cache.get_or_load("wallet", fresh, ttl, false, load).unwrap(), ("info 2".to_string(), true));
+        assert_eq!(loads.get(), 2);
+
+        // Failed loads are not cached
+        assert!(cache.get_or_load("other", fresh, ttl, false, || Err("offline".to_string())).is_err());
+        assert_eq!(cache.get("other"), None);
+    }
+
     #[test]
     fn test_performance_monitor() {
         let monitor = PerformanceMonitor::new(PerformanceConfig::default());
@@ -462,6 +794,123 @@ mod tests {
         assert!(metrics[0].success);
     }
     
+    #[test]
+    fn test_time_records_both_outcomes() {
+        let monitor = PerformanceMonitor::new(PerformanceConfig::default());
+        let ok: Result<u64, String> = tauri::async_runtime::block_on(monitor.time("send", async { Ok(7) }));
+        let err: Result<u64, String> = tauri::async_runtime::block_on(monitor.time("send", async { Err("offline".to_string()) }));
+        assert_eq!((ok, err), (Ok(7), Err("offline".to_string())));
+
+        let metrics = monitor.get_metrics(Some("send"));
+        assert_eq!(metrics.iter().map(|m| m.success).collect::<Vec<_>>(), vec![true, false]);
+    }
+    
+    fn record(monitor: &PerformanceMonitor, name: &str, duration_ms: u64, timestamp: u64) {
+        monitor.metrics.lock_recover().push(PerformanceMetrics {
+            operation_name: name.to_string(),
+            duration_ms,
+            memory_usage_mb: 0.0,
+            timestamp,
+            success: duration_ms < 1000,
+        });
+    }
+    
+    #[test]
+    fn test_performance_window_percentiles() {
+        let monitor = PerformanceMonitor::new(PerformanceConfig::default());
+        for i in 1..=100 {
+            record(&monitor, "send", i * 10, 1_000);
+        }
+        record(&monitor, "send", 99_999, 10);
+        record(&monitor, "sync", 5, 1_000);
+        
+        let window = monitor.window_since(Some("send"), 500, 60);
+        assert_eq!(window.sample_count, 100);
+        assert_eq!(window.average_duration_ms, 505);
+        assert_eq!((window.p50_ms, window.p95_ms, window.p99_ms, window.max_ms), (500, 950, 990, 1000));
+        assert!((window.success_rate - 0.99).abs() < 1e-9);
+        
+        assert_eq!(monitor.window_since(None, 500, 60).sample_count, 101);
+    }
+    
+    #[test]
+    fn test_regressions_compare_recent_latency_with_the_baseline() {
+        let monitor = PerformanceMonitor::new(PerformanceConfig::default());
+        for i in 0..10 {
+            // Baseline hour before the last ten minutes, then the recent ones
+            record(&monitor, "send", 100, 8_000 + i);
+            record(&monitor, "send", 400, 9_500 + i);
+            record(&monitor, "sync", 200, 8_000 + i);
+            record(&monitor, "sync", 220, 9_500 + i);
+        }
+        // Too few recent samples to judge, however slow
+        for i in 0..10 {
+            record(&monitor, "backup", 50, 8_000 + i);
+        }
+        record(&monitor, "backup", 5_000, 9_500);
+        
+        let regressions = monitor.regressions_at(10_000, 3_600, 600);
+        assert_eq!(regressions.len(), 1);
+        let send = &regressions[0];
+        assert_eq!(send.operation_name, "send");
+        assert_eq!((send.baseline.sample_count, send.recent.sample_count), (10, 10));
+        assert!((send.average_factor - 4.0).abs() < 1e-9);
+        assert!((send.p95_factor - 4.0).abs() < 1e-9);
+    }
+    
+    #[test]
+    fn test_empty_performance_window_is_zeroed() {
+        let monitor = PerformanceMonitor::new(PerformanceConfig::default());
+        record(&monitor, "send", 100, 10);
+        
+        let window = monitor.get_performance_window(Some("send"), 60);
+        assert_eq!(window.sample_count, 0);
+        assert_eq!((window.p50_ms, window.p99_ms, window.average_duration_ms), (0, 0, 0));
+        assert_eq!(window.operation_name.as_deref(), Some("send"));
+        assert_eq!(window.since_seconds, 60);
+    }
+    
+    #[test]
+    fn test_metrics_survive_restart() {
+        let dir = TempDir::new("fuego_metrics");
+        let path = dir.join("metrics.json");
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+
+        let monitor = PerformanceMonitor::new(PerformanceConfig::default());
+        record(&monitor, "expired", 10, now - 30 * 24 * 60 * 60);
+        for i in 0..MAX_PERSISTED_METRICS as u64 + 5 {
+            record(&monitor, "send", 10, now - 1_000 + i / 100);
+        }
+        record(&monitor, "sync", 20, now);
+        monitor.save(&path).unwrap();
+
+        let restarted = PerformanceMonitor::new(PerformanceConfig::default());
+        record(&restarted, "startup", 30, now);
+        assert_eq!(restarted.load(&path).unwrap(), MAX_PERSISTED_METRICS);
+        assert!(restarted.get_metrics(Some("expired")).is_empty());
+        assert_eq!(restarted.get_metrics(Some("sync")).len(), 1);
+        assert_eq!(restarted.get_metrics(None).last().unwrap().operation_name, "startup");
+
+        fs::write(&path, "{not json").unwrap();
+        let fresh = PerformanceMonitor::new(PerformanceConfig::default());
+        assert_eq!(fresh.load(&path).unwrap(), 0);
+        assert_eq!(fresh.load(&dir.join("missing.json")).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_list_tasks_snapshots_every_task() {
+        let manager = BackgroundTaskManager::new();
+        assert!(manager.list_tasks().is_empty());
+        manager.register_task("sync".to_string(), Duration::from_secs(60));
+        manager.register_task("backup".to_string(), Duration::from_secs(3600));
+        manager.set_task_enabled("sync", false);
+
+        let tasks = manager.list_tasks();
+        let summary: Vec<(&str, bool)> = tasks.iter().map(|t| (t.name.as_str(), t.enabled)).collect();
+        assert_eq!(summary, vec![("backup", true), ("sync", false)]);
+        assert!(tasks[0].next_run_in > Duration::from_secs(3500));
+    }
+
     #[test]
     fn test_batch_processor() {
         let processor = BatchProcessor::new(3);
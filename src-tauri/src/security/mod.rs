@@ -5,8 +5,19 @@
 
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use serde::{Deserialize, Serialize};
+use crate::utils::error::WalletError;
+use crate::utils::locks::MutexExt;
+
+/// How long a large-send authorization token stays valid
+pub const LARGE_SEND_TOKEN_TTL: Duration = Duration::from_secs(60);
+
+/// How many times one user's lockout can be cleared per `LOCKOUT_CLEAR_WINDOW`
+pub const MAX_LOCKOUT_CLEARS: usize = 3;
+
+/// Period over which lockout clears are counted, one hour
+pub const LOCKOUT_CLEAR_WINDOW: Duration = Duration::from_secs(3600);
 
 /// Security configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,12 +50,24 @@ pub struct UserSession {
     pub is_locked: bool,
 }
 
+/// Whether a user is locked out of authenticating
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockoutStatus {
+    pub user_id: String,
+    pub locked_out: bool,
+    pub failed_attempts: u32,
+    /// Until the lockout ends, 0 when not locked out
+    pub seconds_remaining: u64,
+}
+
 /// Security manager for handling authentication and session management
 #[derive(Debug)]
 pub struct SecurityManager {
     config: SecurityConfig,
     sessions: Arc<Mutex<HashMap<String, UserSession>>>,
     failed_attempts: Arc<Mutex<HashMap<String, (u32, u64)>>>, // (attempts, last_attempt_time)
+    large_send_tokens: Arc<Mutex<HashMap<String, Instant>>>, // token -> issued at
+    lockout_clears: Arc<Mutex<HashMap<String, Vec<Instant>>>>, // user -> recent clears
 }
 
 impl SecurityManager {
@@ -53,6 +76,8 @@ impl SecurityManager {
             config,
             sessions: Arc::new(Mutex::new(HashMap::new())),
             failed_attempts: Arc::new(Mutex::new(HashMap::new())),
+            large_send_tokens: Arc::new(Mutex::new(HashMap::new())),
+            lockout_clears: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
@@ -80,7 +105,7 @@ impl SecurityManager {
 
     /// Validate session
     pub fn validate_session(&self, session_id: &str) -> Result<String, String> {
-        let sessions = self.sessions.lock().unwrap();
+        let sessions = self.sessions.lock_recover();
         
         if let Some(session) = sessions.get(session_id) {
             let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
@@ -101,7 +126,7 @@ impl SecurityManager {
 
     /// Update session activity
     pub fn update_session_activity(&self, session_id: &str) -> Result<(), String> {
-        let mut sessions = self.sessions.lock().unwrap();
+        let mut sessions = self.sessions.lock_recover();
         
         if let Some(session) = sessions.get_mut(session_id) {
             session.last_activity = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
@@ -113,7 +138,7 @@ impl SecurityManager {
 
     /// Lock session (for sensitive operations)
     pub fn lock_session(&self, session_id: &str) -> Result<(), String> {
-        let mut sessions = self.sessions.lock().unwrap();
+        let mut sessions = self.sessions.lock_recover();
         
         if let Some(session) = sessions.get_mut(session_id) {
             session.is_locked = true;
@@ -129,7 +154,7 @@ impl SecurityManager {
             return Err("Invalid password".to_string());
         }
 
-        let mut sessions = self.sessions.lock().unwrap();
+        let mut sessions = self.sessions.lock_recover();
         
         if let Some(session) = sessions.get_mut(session_id) {
             session.is_locked = false;
@@ -142,31 +167,95 @@ impl SecurityManager {
 
     /// Logout and destroy session
     pub fn logout(&self, session_id: &str) -> Result<(), String> {
-        let mut sessions = self.sessions.lock().unwrap();
+        let mut sessions = self.sessions.lock_recover();
         sessions.remove(session_id);
         Ok(())
     }
 
+    /// Issue a single-use token allowing one send above the configured cap.
+    /// Requires a valid session and re-checks the password.
+    pub fn authorize_large_send(&self, session_id: &str, password: &str) -> Result<String, String> {
+        self.validate_session(session_id)?;
+        if !self.validate_password(password) {
+            return Err("Invalid password".to_string());
+        }
+
+        let token = uuid::Uuid::new_v4().to_string();
+        let mut tokens = self.large_send_tokens.lock_recover();
+        tokens.retain(|_, issued| issued.elapsed() < LARGE_SEND_TOKEN_TTL);
+        tokens.insert(token.clone(), Instant::now());
+        Ok(token)
+    }
+
+    /// Check a send of `amount` against `cap`. Sends above the cap need an
+    /// unexpired override token, which is consumed by the check.
+    pub fn check_send_cap(&self, amount: u64, cap: Option<u64>, override_token: Option<&str>) -> Result<(), WalletError> {
+        let Some(cap) = cap else { return Ok(()) };
+        if amount <= cap {
+            return Ok(());
+        }
+
+        let issued = override_token.and_then(|token| self.large_send_tokens.lock_recover().remove(token));
+        match issued {
+            Some(issued) if issued.elapsed() < LARGE_SEND_TOKEN_TTL => Ok(()),
+            _ => Err(WalletError::LargeSendBlocked { amount, cap }),
+        }
+    }
+
+    /// Check a password without touching sessions
+    pub fn verify_password(&self, password: &str) -> bool {
+        self.validate_password(password)
+    }
+
+    /// Failed attempts of a user and how long a lockout has left
+    pub fn lockout_status(&self, user_id: &str) -> LockoutStatus {
+        let (attempts, last_attempt) = self.failed_attempts.lock_recover().get(user_id).copied().unwrap_or((0, 0));
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        let seconds_remaining = if attempts >= self.config.max_login_attempts {
+            self.config.lockout_duration_seconds.saturating_sub(now.saturating_sub(last_attempt))
+        } else {
+            0
+        };
+        LockoutStatus {
+            user_id: user_id.to_string(),
+            locked_out: seconds_remaining > 0,
+            failed_attempts: attempts,
+            seconds_remaining,
+        }
+    }
+
+    /// Clear the failed attempts of `user_id` on behalf of the valid, unlocked
+    /// session `session_id`. Each user's attempts can be cleared at most
+    /// `MAX_LOCKOUT_CLEARS` times per `LOCKOUT_CLEAR_WINDOW`, so clearing
+    /// cannot be used to keep guessing passwords. Returns the attempt count
+    /// left, always 0.
+    pub fn clear_lockout(&self, session_id: &str, user_id: &str) -> Result<u32, String> {
+        self.validate_session(session_id)?;
+
+        let mut clears = self.lockout_clears.lock_recover();
+        let recent = clears.entry(user_id.to_string()).or_default();
+        recent.retain(|cleared| cleared.elapsed() < LOCKOUT_CLEAR_WINDOW);
+        if recent.len() >= MAX_LOCKOUT_CLEARS {
+            return Err(format!(
+                "The lockout of {} was cleared {} times in the last hour; wait for it to expire",
+                user_id, MAX_LOCKOUT_CLEARS
+            ));
+        }
+        recent.push(Instant::now());
+        drop(clears);
+
+        self.clear_failed_attempts(user_id);
+        Ok(self.lockout_status(user_id).failed_attempts)
+    }
+
     /// Check if user is locked out
     fn is_user_locked_out(&self, user_id: &str) -> bool {
-        let failed_attempts = self.failed_attempts.lock().unwrap();
-        
-        if let Some((attempts, last_attempt)) = failed_attempts.get(user_id) {
-            let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
-            
-            if *attempts >= self.config.max_login_attempts {
-                if now - last_attempt < self.config.lockout_duration_seconds {
-                    return true;
-                }
-            }
-        }
-        
-        false
+        self.lockout_status(user_id).locked_out
     }
 
     /// Record failed login attempt
     fn record_failed_attempt(&self, user_id: &str) {
-        let mut failed_attempts = self.failed_attempts.lock().unwrap();
+        let mut failed_attempts = self.failed_attempts.lock_recover();
         let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
         
         let attempts = failed_attempts.get(user_id).map(|(a, _)| *a).unwrap_or(0);
@@ -175,7 +264,7 @@ impl SecurityManager {
 
     /// Clear failed attempts for user
     fn clear_failed_attempts(&self, user_id: &str) {
-        let mut failed_attempts = self.failed_attempts.lock().unwrap();
+        let mut failed_attempts = self.failed_attempts.lock_recover();
         failed_attempts.remove(user_id);
     }
 
@@ -191,7 +280,7 @@ impl SecurityManager {
             is_locked: false,
         };
         
-        let mut sessions = self.sessions.lock().unwrap();
+        let mut sessions = self.sessions.lock_recover();
         sessions.insert(session_id.clone(), session);
         
         session_id
@@ -253,6 +342,12 @@ impl PasswordValidator {
     }
 }
 
+/// AES-GCM nonce size in an `encrypt_data` envelope
+const AES_GCM_NONCE_LEN: usize = 12;
+
+/// AES-GCM authentication tag size; every ciphertext is at least this long
+const AES_GCM_TAG_LEN: usize = 16;
+
 /// Wallet encryption utilities
 pub struct WalletEncryption;
 
@@ -277,7 +372,7 @@ impl WalletEncryption {
         let cipher = Aes256Gcm::new(key);
 
         // Random nonce
-        let mut nonce_bytes = [0u8; 12];
+        let mut nonce_bytes = [0u8; AES_GCM_NONCE_LEN];
         OsRng.fill_bytes(&mut nonce_bytes);
         let nonce = Nonce::from_slice(&nonce_bytes);
 
@@ -293,12 +388,16 @@ impl WalletEncryption {
         Ok(out.to_string())
     }
     
-    /// Decrypt sensitive data with AES-256-GCM using Argon2-derived key
-    pub fn decrypt_data(encrypted_data: &str, password: &str) -> Result<String, String> {
-        use aes_gcm::{Aes256Gcm, Key, Nonce, KeyInit};
-        use aes_gcm::aead::{Aead};
-        use argon2::{Argon2, PasswordHasher};
-        use argon2::password_hash::{SaltString, PasswordHasher as _};
+    /// Check that `encrypted_data` is a well-formed `encrypt_data` envelope,
+    /// without deriving a key. A failure here means the data is damaged,
+    /// not that the password is wrong.
+    pub fn check_envelope(encrypted_data: &str) -> Result<(), String> {
+        Self::parse_envelope(encrypted_data).map(|_| ())
+    }
+
+    /// Salt, nonce and ciphertext of an envelope
+    fn parse_envelope(encrypted_data: &str) -> Result<(argon2::password_hash::SaltString, Vec<u8>, Vec<u8>), String> {
+        use argon2::password_hash::SaltString;
         use base64::{Engine as _, engine::general_purpose};
 
         let v: serde_json::Value = serde_json::from_str(encrypted_data).map_err(|e| format!("JSON error: {}", e))?;
@@ -307,6 +406,25 @@ impl WalletEncryption {
         let c_b64 = v.get("c").and_then(|x| x.as_str()).ok_or("Missing ciphertext")?;
 
         let salt = SaltString::from_b64(s).map_err(|e| format!("Salt error: {}", e))?;
+        let nonce = general_purpose::STANDARD.decode(n_b64).map_err(|e| format!("Nonce decode: {}", e))?;
+        if nonce.len() != AES_GCM_NONCE_LEN {
+            return Err(format!("Nonce is {} bytes, expected {}", nonce.len(), AES_GCM_NONCE_LEN));
+        }
+        let ciphertext = general_purpose::STANDARD.decode(c_b64).map_err(|e| format!("Ciphertext decode: {}", e))?;
+        if ciphertext.len() < AES_GCM_TAG_LEN {
+            return Err(format!("Ciphertext is {} bytes, shorter than the authentication tag", ciphertext.len()));
+        }
+        Ok((salt, nonce, ciphertext))
+    }
+
+    /// Decrypt sensitive data with AES-256-GCM using Argon2-derived key
+    pub fn decrypt_data(encrypted_data: &str, password: &str) -> Result<String, String> {
+        use aes_gcm::{Aes256Gcm, Key, Nonce, KeyInit};
+        use aes_gcm::aead::{Aead};
+        use argon2::{Argon2, PasswordHasher};
+        use argon2::password_hash::{PasswordHasher as _};
+
+        let (salt, nonce_bytes, ciphertext) = Self::parse_envelope(encrypted_data)?;
         let argon2 = Argon2::default();
         // Derive same key
         let hash = argon2.hash_password(password.as_bytes(), &salt)
@@ -315,9 +433,7 @@ impl WalletEncryption {
         let key = Key::<Aes256Gcm>::from_slice(&key_bytes);
         let cipher = Aes256Gcm::new(key);
 
-        let nonce_bytes = general_purpose::STANDARD.decode(n_b64).map_err(|e| format!("Nonce decode: {}", e))?;
         let nonce = Nonce::from_slice(&nonce_bytes);
-        let ciphertext = general_purpose::STANDARD.decode(c_b64).map_err(|e| format!("Ciphertext decode: {}", e))?;
         let plaintext = cipher.decrypt(nonce, ciphertext.as_ref()).map_err(|e| format!("Decrypt error: {}", e))?;
         String::from_utf8(plaintext).map_err(|e| format!("UTF-8 error: {}", e))
     }
@@ -357,4 +473,72 @@ mod tests {
         assert!(user_id.is_ok());
         assert_eq!(user_id.unwrap(), "test_user");
     }
+
+    #[test]
+    fn test_clear_lockout_needs_session_and_is_rate_limited() {
+        let manager = SecurityManager::new(SecurityConfig::default());
+        let session_id = manager.authenticate("support", "fuego_password").unwrap();
+        let lock_out = || {
+            for _ in 0..5 {
+                assert!(manager.authenticate("alice", "wrong").is_err());
+            }
+        };
+
+        lock_out();
+        let status = manager.lockout_status("alice");
+        assert!(status.locked_out);
+        assert_eq!(status.failed_attempts, 5);
+        assert!(status.seconds_remaining > 0 && status.seconds_remaining <= 300);
+        assert!(manager.authenticate("alice", "fuego_password").is_err());
+
+        assert!(manager.clear_lockout("no_such_session", "alice").is_err());
+        manager.lock_session(&session_id).unwrap();
+        assert!(manager.clear_lockout(&session_id, "alice").is_err());
+        manager.unlock_session(&session_id, "fuego_password").unwrap();
+
+        assert_eq!(manager.clear_lockout(&session_id, "alice"), Ok(0));
+        assert!(!manager.lockout_status("alice").locked_out);
+        for _ in 1..MAX_LOCKOUT_CLEARS {
+            lock_out();
+            assert_eq!(manager.clear_lockout(&session_id, "alice"), Ok(0));
+        }
+        lock_out();
+        assert!(manager.clear_lockout(&session_id, "alice").is_err());
+        assert!(manager.lockout_status("alice").locked_out);
+        // The limit is per user
+        assert_eq!(manager.clear_lockout(&session_id, "bob"), Ok(0));
+    }
+
+    #[test]
+    fn test_large_send_requires_single_use_token() {
+        let manager = SecurityManager::new(SecurityConfig::default());
+        let session_id = manager.authenticate("test_user", "fuego_password").unwrap();
+
+        assert!(manager.check_send_cap(1_000, None, None).is_ok());
+        assert!(manager.check_send_cap(1_000, Some(1_000), None).is_ok());
+        let err = manager.check_send_cap(1_001, Some(1_000), None).unwrap_err();
+        assert!(matches!(err, WalletError::LargeSendBlocked { amount: 1_001, cap: 1_000 }));
+        assert!(err.to_string().contains("cap of 1000"));
+
+        assert!(manager.authorize_large_send(&session_id, "wrong").is_err());
+        assert!(manager.authorize_large_send("no_such_session", "fuego_password").is_err());
+
+        let token = manager.authorize_large_send(&session_id, "fuego_password").unwrap();
+        assert!(manager.check_send_cap(5_000, Some(1_000), Some(&token)).is_ok());
+        assert!(manager.check_send_cap(5_000, Some(1_000), Some(&token)).is_err());
+    }
+
+    #[test]
+    fn test_large_send_token_expires() {
+        let manager = SecurityManager::new(SecurityConfig::default());
+        let session_id = manager.authenticate("test_user", "fuego_password").unwrap();
+        let token = manager.authorize_large_send(&session_id, "fuego_password").unwrap();
+
+        let expired = Instant::now().checked_sub(LARGE_SEND_TOKEN_TTL + Duration::from_secs(1)).unwrap();
+        manager.large_send_tokens.lock_recover().insert(token.clone(), expired);
+
+        assert!(manager.check_send_cap(5_000, Some(1_000), Some(&token)).is_err());
+        // An amount under the cap does not touch the token
+        assert!(manager.check_send_cap(500, Some(1_000), Some("unknown")).is_ok());
+    }
 }
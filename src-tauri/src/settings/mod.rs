@@ -1,7 +1,14 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
 use std::sync::{Arc, Mutex};
+use crate::crypto::backend::BackendKind;
+use crate::crypto::real_cryptonote::ConnectRetrySettings;
+use crate::utils::locks::MutexExt;
+use crate::utils::storage::APP_DIR_NAME;
+use crate::wallet::async_wallet::OperationTimeouts;
+use crate::daemon::DaemonSettings;
 
 /// Application settings structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,6 +28,16 @@ pub struct WalletSettings {
     pub default_mixin: u32,
     pub confirm_transactions: bool,
     pub show_advanced_options: bool,
+    /// Implementation wallets run on
+    #[serde(default)]
+    pub backend: BackendKind,
+    /// Run on generated demo data instead of a wallet; applies from the next start
+    #[serde(default)]
+    pub demo_mode: bool,
+    /// Confirmations incoming transfers need before they count as
+    /// spendable; the protocol's own if unset or lower
+    #[serde(default)]
+    pub min_confirmations: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,13 +47,42 @@ pub struct NetworkSettings {
     pub auto_connect: bool,
     pub connection_timeout: u32,
     pub max_peers: u32,
+    /// Blocks the wallet may be behind the daemon and still count as synced
     pub sync_threshold: u32,
+    /// Seconds the wallet may stay behind without catching up before the
+    /// user is told sync stalled; `DEFAULT_SYNC_STALL_GRACE` if unset
+    #[serde(default)]
+    pub sync_stall_grace_secs: Option<u64>,
+    /// How long wallet operations may block before they are abandoned
+    #[serde(default)]
+    pub operation_timeouts: OperationTimeouts,
+    /// The `fuegod` the wallet can run itself
+    #[serde(default)]
+    pub daemon: DaemonSettings,
+    /// How often and how far apart each node is retried when connecting
+    #[serde(default)]
+    pub connect_retry: ConnectRetrySettings,
+}
+
+/// How the UI language was chosen
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LanguageSource {
+    /// Never chosen; the built-in default is in use
+    #[default]
+    Default,
+    /// Detected from the system locale; re-detected on every start
+    Auto,
+    /// Picked by the user; never overridden by detection
+    Explicit,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UISettings {
     pub theme: String,
     pub language: String,
+    #[serde(default)]
+    pub language_source: LanguageSource,
     pub currency_display: String,
     pub decimal_places: u8,
     pub auto_refresh: bool,
@@ -52,6 +98,9 @@ pub struct SecuritySettings {
     pub require_password_for_send: bool,
     pub encrypt_wallet_file: bool,
     pub session_timeout_minutes: u32,
+    /// Sends above this many atomic units need a one-time authorization
+    #[serde(default)]
+    pub max_send_atomic: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -73,6 +122,9 @@ impl Default for AppSettings {
                 default_mixin: 5,
                 confirm_transactions: true,
                 show_advanced_options: false,
+                backend: BackendKind::Native,
+                demo_mode: false,
+                min_confirmations: None,
             },
             network: NetworkSettings {
                 node_address: "fuego.spaceportx.net".to_string(),
@@ -81,10 +133,15 @@ impl Default for AppSettings {
                 connection_timeout: 30,
                 max_peers: 50,
                 sync_threshold: 10,
+                sync_stall_grace_secs: None,
+                operation_timeouts: OperationTimeouts::default(),
+                daemon: DaemonSettings::default(),
+                connect_retry: ConnectRetrySettings::default(),
             },
             ui: UISettings {
                 theme: "dark".to_string(),
                 language: "en".to_string(),
+                language_source: LanguageSource::Default,
                 currency_display: "XFG".to_string(),
                 decimal_places: 7,
                 auto_refresh: true,
@@ -98,6 +155,7 @@ impl Default for AppSettings {
                 require_password_for_send: true,
                 encrypt_wallet_file: true,
                 session_timeout_minutes: 60,
+                max_send_atomic: None,
             },
             performance: PerformanceSettings {
                 enable_caching: true,
@@ -110,24 +168,92 @@ impl Default for AppSettings {
     }
 }
 
+impl UISettings {
+    /// Whether the language was picked by the user. Settings written before the
+    /// source was recorded count as explicit when they hold a non-default language.
+    pub fn has_explicit_language(&self) -> bool {
+        match self.language_source {
+            LanguageSource::Explicit => true,
+            LanguageSource::Auto => false,
+            LanguageSource::Default => self.language != "en",
+        }
+    }
+}
+
+impl AppSettings {
+    /// Build settings from a possibly older JSON layout, filling fields that
+    /// did not exist yet with their defaults
+    pub fn migrate(value: serde_json::Value) -> Result<Self, String> {
+        if !value.is_object() {
+            return Err("Settings must be a JSON object".to_string());
+        }
+        let mut merged = serde_json::to_value(AppSettings::default())
+            .map_err(|e| format!("Failed to serialize default settings: {}", e))?;
+        merge_json(&mut merged, value);
+        serde_json::from_value(merged)
+            .map_err(|e| format!("Failed to parse settings: {}", e))
+    }
+    
+    /// Check that the settings values are within sane bounds
+    pub fn validate(&self) -> Result<(), String> {
+        if self.network.node_address.trim().is_empty() {
+            return Err("Node address must not be empty".to_string());
+        }
+        if self.network.node_port == 0 {
+            return Err("Node port must not be 0".to_string());
+        }
+        if self.ui.decimal_places > 12 {
+            return Err("Decimal places must be between 0 and 12".to_string());
+        }
+        if self.ui.language.trim().is_empty() {
+            return Err("Language must not be empty".to_string());
+        }
+        if self.performance.log_level.trim().parse::<log::LevelFilter>().is_err() {
+            return Err(format!("Invalid log level: {}", self.performance.log_level));
+        }
+        Ok(())
+    }
+}
+
+/// Recursively merge `patch` into `base`, keeping keys of `base` missing from `patch`
+fn merge_json(base: &mut serde_json::Value, patch: serde_json::Value) {
+    match (base, patch) {
+        (serde_json::Value::Object(base_map), serde_json::Value::Object(patch_map)) => {
+            for (key, value) in patch_map {
+                match base_map.get_mut(&key) {
+                    Some(existing) => merge_json(existing, value),
+                    None => {
+                        base_map.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, patch) => *base = patch,
+    }
+}
+
 /// Settings manager
 #[derive(Debug)]
 pub struct SettingsManager {
     settings: Arc<Mutex<AppSettings>>,
     config_path: PathBuf,
+    subscribers: Arc<Mutex<Vec<Sender<AppSettings>>>>,
 }
 
 impl SettingsManager {
     pub fn new() -> Result<Self, String> {
         let config_dir = dirs::config_dir()
             .ok_or("Failed to get config directory")?
-            .join("fuego-wallet");
+            .join(APP_DIR_NAME);
         
         fs::create_dir_all(&config_dir)
             .map_err(|e| format!("Failed to create config directory: {}", e))?;
         
-        let config_path = config_dir.join("settings.json");
-        
+        Self::with_config_path(config_dir.join("settings.json"))
+    }
+    
+    /// Create a settings manager backed by a specific settings file
+    pub fn with_config_path(config_path: PathBuf) -> Result<Self, String> {
         let settings = if config_path.exists() {
             Self::load_from_file(&config_path)?
         } else {
@@ -139,62 +265,106 @@ impl SettingsManager {
         Ok(Self {
             settings: Arc::new(Mutex::new(settings)),
             config_path,
+            subscribers: Arc::new(Mutex::new(Vec::new())),
         })
     }
+
+    /// Receive the full settings after every change from now on; subscribers
+    /// whose receiver was dropped are removed.
+    pub fn subscribe(&self) -> Receiver<AppSettings> {
+        let (sender, receiver) = mpsc::channel();
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            subscribers.push(sender);
+        }
+        receiver
+    }
+
+    fn publish(&self, settings: &AppSettings) {
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            subscribers.retain(|subscriber| subscriber.send(settings.clone()).is_ok());
+        }
+    }
     
     pub fn get_settings(&self) -> Result<AppSettings, String> {
-        self.settings.lock()
-            .map_err(|e| format!("Failed to lock settings: {}", e))
-            .map(|s| s.clone())
+        Ok(self.settings.lock_recover().clone())
     }
     
     pub fn update_settings(&self, new_settings: AppSettings) -> Result<(), String> {
         Self::save_to_file(&self.config_path, &new_settings)?;
-        *self.settings.lock()
-            .map_err(|e| format!("Failed to lock settings: {}", e))? = new_settings;
+        *self.settings.lock_recover() = new_settings.clone();
+        self.publish(&new_settings);
         Ok(())
     }
     
     pub fn update_wallet_settings(&self, wallet_settings: WalletSettings) -> Result<(), String> {
-        let mut settings = self.settings.lock()
-            .map_err(|e| format!("Failed to lock settings: {}", e))?;
+        let mut settings = self.settings.lock_recover();
         settings.wallet = wallet_settings;
         Self::save_to_file(&self.config_path, &settings)?;
+        self.publish(&settings);
         Ok(())
     }
     
     pub fn update_network_settings(&self, network_settings: NetworkSettings) -> Result<(), String> {
-        let mut settings = self.settings.lock()
-            .map_err(|e| format!("Failed to lock settings: {}", e))?;
+        let mut settings = self.settings.lock_recover();
         settings.network = network_settings;
         Self::save_to_file(&self.config_path, &settings)?;
+        self.publish(&settings);
         Ok(())
     }
     
     pub fn update_ui_settings(&self, ui_settings: UISettings) -> Result<(), String> {
-        let mut settings = self.settings.lock()
-            .map_err(|e| format!("Failed to lock settings: {}", e))?;
+        let mut settings = self.settings.lock_recover();
         settings.ui = ui_settings;
         Self::save_to_file(&self.config_path, &settings)?;
+        self.publish(&settings);
+        Ok(())
+    }
+    
+    /// Persist the UI language and how it was chosen
+    pub fn set_language(&self, language_code: &str, source: LanguageSource) -> Result<(), String> {
+        let mut settings = self.settings.lock_recover();
+        settings.ui.language = language_code.to_string();
+        settings.ui.language_source = source;
+        Self::save_to_file(&self.config_path, &settings)?;
+        self.publish(&settings);
+        Ok(())
+    }
+    
+    /// Persist the selected UI theme
+    pub fn set_theme(&self, theme_name: &str) -> Result<(), String> {
+        let mut settings = self.settings.lock_recover();
+        let mut updated = settings.clone();
+        updated.ui.theme = theme_name.to_string();
+        Self::save_to_file(&self.config_path, &updated)?;
+        *settings = updated;
+        self.publish(&settings);
         Ok(())
     }
     
     pub fn update_security_settings(&self, security_settings: SecuritySettings) -> Result<(), String> {
-        let mut settings = self.settings.lock()
-            .map_err(|e| format!("Failed to lock settings: {}", e))?;
+        let mut settings = self.settings.lock_recover();
         settings.security = security_settings;
         Self::save_to_file(&self.config_path, &settings)?;
+        self.publish(&settings);
         Ok(())
     }
     
     pub fn update_performance_settings(&self, performance_settings: PerformanceSettings) -> Result<(), String> {
-        let mut settings = self.settings.lock()
-            .map_err(|e| format!("Failed to lock settings: {}", e))?;
+        let mut settings = self.settings.lock_recover();
         settings.performance = performance_settings;
         Self::save_to_file(&self.config_path, &settings)?;
+        self.publish(&settings);
         Ok(())
     }
     
+    /// Migrate, validate and apply settings coming from an external source (e.g. a backup)
+    pub fn import_settings(&self, value: serde_json::Value) -> Result<AppSettings, String> {
+        let settings = AppSettings::migrate(value)?;
+        settings.validate()?;
+        self.update_settings(settings.clone())?;
+        Ok(settings)
+    }
+    
     pub fn reset_to_defaults(&self) -> Result<(), String> {
         let default_settings = AppSettings::default();
         self.update_settings(default_settings)
@@ -217,3 +387,61 @@ impl SettingsManager {
 }
 
 // Tauri commands are defined in lib.rs
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::testing::TempDir;
+    
+    #[test]
+    fn test_migrate_fills_missing_fields() {
+        let legacy = serde_json::json!({
+            "ui": { "theme": "light", "language": "fr" }
+        });
+        let settings = AppSettings::migrate(legacy).unwrap();
+        assert_eq!(settings.ui.theme, "light");
+        assert_eq!(settings.ui.language, "fr");
+        assert_eq!(settings.network.node_port, 18180);
+    }
+    
+    #[test]
+    fn test_validate_rejects_bad_values() {
+        let mut settings = AppSettings::default();
+        assert!(settings.validate().is_ok());
+        settings.network.node_port = 0;
+        assert!(settings.validate().is_err());
+    }
+    
+    #[test]
+    fn test_import_settings_persists() {
+        let dir = TempDir::new("fuego_settings");
+        let path = dir.join("settings.json");
+        
+        let manager = SettingsManager::with_config_path(path.clone()).unwrap();
+        manager.import_settings(serde_json::json!({ "ui": { "theme": "light" } })).unwrap();
+        
+        let reloaded = SettingsManager::with_config_path(path).unwrap();
+        assert_eq!(reloaded.get_settings().unwrap().ui.theme, "light");
+    }
+    
+    #[test]
+    fn test_changes_reach_subscribers() {
+        let dir = TempDir::new("fuego_settings");
+        let manager = SettingsManager::with_config_path(dir.join("settings.json")).unwrap();
+        let receiver = manager.subscribe();
+        
+        let mut ui = manager.get_settings().unwrap().ui;
+        ui.refresh_interval = 42;
+        manager.update_ui_settings(ui).unwrap();
+        manager.set_theme("light").unwrap();
+        
+        let updates: Vec<AppSettings> = receiver.try_iter().collect();
+        assert_eq!(updates.len(), 2);
+        assert_eq!(updates[0].ui.refresh_interval, 42);
+        assert_eq!(updates[1].ui.theme, "light");
+        
+        drop(receiver);
+        manager.set_theme("dark").unwrap();
+        assert!(manager.subscribers.lock_recover().is_empty());
+    }
+}
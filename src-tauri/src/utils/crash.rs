@@ -0,0 +1,227 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Crash reports
+//!
+//! A panic in a command thread only kills that handler, so it easily goes
+//! unnoticed. The panic hook writes each panic to
+//! `<data_dir>/fuego-wallet/crash-reports/<timestamp>.json`, together with the
+//! backtrace, the app version and the last log lines, and the next start
+//! points the user at the reports they have not looked at yet. Everything in
+//! a report passes through `logging::redact`, so reports can be shared as is.
+
+use std::backtrace::Backtrace;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::panic::PanicHookInfo;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use super::logging;
+use super::storage::APP_DIR_NAME;
+
+/// Log lines kept in a report
+pub const CRASH_LOG_LINES: usize = 100;
+
+/// A panic written by the panic hook
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CrashReport {
+    /// File stem of the report, a UTC timestamp
+    pub id: String,
+    /// Unix seconds
+    pub timestamp: u64,
+    pub app_version: String,
+    pub thread: String,
+    pub message: String,
+    /// `file:line:column` of the panic
+    pub location: Option<String>,
+    pub backtrace: String,
+    pub log_tail: Vec<String>,
+    /// Listed through `list_reports` since it was written
+    #[serde(default)]
+    pub reviewed: bool,
+}
+
+/// Directory crash reports are written to
+pub fn crash_reports_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(APP_DIR_NAME).join("crash-reports"))
+}
+
+/// Write a report to `dir` for every panic, then run the previous hook. The
+/// log tail is read from `logs_dir`.
+pub fn install_panic_hook(dir: PathBuf, logs_dir: Option<PathBuf>) {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        match write_report(&dir, logs_dir.as_deref(), info) {
+            Ok(path) => eprintln!("Crash report written to {}", path.display()),
+            Err(e) => eprintln!("Failed to write crash report: {}", e),
+        }
+        previous(info);
+    }));
+}
+
+/// Write the report for `info`. Runs inside the panic hook, so nothing here
+/// may panic: a panic while panicking aborts the app.
+fn write_report(dir: &Path, logs_dir: Option<&Path>, info: &PanicHookInfo) -> io::Result<PathBuf> {
+    let payload = info.payload();
+    let message = payload.downcast_ref::<&str>().map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "Box<dyn Any>".to_string());
+    let now = chrono::Utc::now();
+    let mut report = CrashReport {
+        id: now.format("%Y%m%dT%H%M%S%3fZ").to_string(),
+        timestamp: now.timestamp().max(0) as u64,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        thread: std::thread::current().name().unwrap_or("<unnamed>").to_string(),
+        message: logging::redact(&message),
+        location: info.location().map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column())),
+        backtrace: Backtrace::force_capture().to_string().lines()
+            .map(logging::redact)
+            .collect::<Vec<_>>()
+            .join("\n"),
+        log_tail: logs_dir
+            .and_then(|logs| logging::tail(logs, CRASH_LOG_LINES).ok())
+            .unwrap_or_default(),
+        reviewed: false,
+    };
+
+    fs::create_dir_all(dir)?;
+    // Panics in the same millisecond get a numbered suffix
+    let stem = report.id.clone();
+    for attempt in 0..100 {
+        if attempt > 0 {
+            report.id = format!("{}-{}", stem, attempt);
+        }
+        let path = dir.join(format!("{}.json", report.id));
+        let mut file = match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        };
+        let json = serde_json::to_vec_pretty(&report).map_err(io::Error::other)?;
+        file.write_all(&json)?;
+        return Ok(path);
+    }
+    Err(io::Error::new(io::ErrorKind::AlreadyExists, "too many crash reports at once"))
+}
+
+/// Reports in `dir`, newest first. Unreadable files are skipped.
+pub fn read_reports(dir: &Path) -> Result<Vec<CrashReport>, String> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read crash reports: {}", e)),
+    };
+    let mut reports: Vec<CrashReport> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| match fs::read(&path).map_err(|e| e.to_string())
+            .and_then(|bytes| serde_json::from_slice(&bytes).map_err(|e| e.to_string()))
+        {
+            Ok(report) => Some(report),
+            Err(e) => {
+                log::warn!("Skipping crash report {}: {}", path.display(), e);
+                None
+            }
+        })
+        .collect();
+    reports.sort_by(|a, b| b.id.cmp(&a.id));
+    Ok(reports)
+}
+
+/// Reports in `dir`, newest first, marking them as reviewed. The returned
+/// reports still show whether they had been reviewed before.
+pub fn list_reports(dir: &Path) -> Result<Vec<CrashReport>, String> {
+    let reports = read_reports(dir)?;
+    for report in reports.iter().filter(|report| !report.reviewed) {
+        let reviewed = CrashReport { reviewed: true, ..report.clone() };
+        let json = serde_json::to_vec_pretty(&reviewed)
+            .map_err(|e| format!("Failed to serialize crash report: {}", e))?;
+        super::storage::atomic_write(&report_path(dir, &report.id)?, &json)?;
+    }
+    Ok(reports)
+}
+
+/// Number of reports in `dir` not listed yet
+pub fn unreviewed_count(dir: &Path) -> Result<usize, String> {
+    Ok(read_reports(dir)?.iter().filter(|report| !report.reviewed).count())
+}
+
+/// Delete the report `id` from `dir`
+pub fn delete_report(dir: &Path, id: &str) -> Result<(), String> {
+    let path = report_path(dir, id)?;
+    fs::remove_file(&path).map_err(|e| format!("Failed to delete crash report {}: {}", id, e))
+}
+
+/// Path of the report `id`; ids are file stems, so anything reaching outside
+/// `dir` is refused
+fn report_path(dir: &Path, id: &str) -> Result<PathBuf, String> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(format!("Invalid crash report id: {}", id));
+    }
+    Ok(dir.join(format!("{}.json", id)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::testing::TempDir;
+
+    #[test]
+    fn test_panic_in_thread_writes_report() {
+        let dir = TempDir::new("fuego_crash");
+        let logs = dir.join("logs");
+        fs::create_dir_all(&logs).unwrap();
+        fs::write(logs.join(logging::LOG_FILE_NAME), format!("opened wallet\nview_key={}\n", "ab".repeat(32))).unwrap();
+
+        install_panic_hook(dir.to_path_buf(), Some(logs));
+        let secret = "c".repeat(64);
+        let result = std::thread::Builder::new()
+            .name("crashing-command".to_string())
+            .spawn(move || panic!("controlled panic with key {}", secret))
+            .unwrap()
+            .join();
+        let _ = std::panic::take_hook();
+        assert!(result.is_err());
+
+        // Other tests may panic while the hook is installed; find ours
+        let reports = read_reports(&dir).unwrap();
+        let report = reports.iter().find(|report| report.thread == "crashing-command").expect("no crash report written");
+        assert_eq!(report.message, "controlled panic with key [REDACTED]");
+        assert!(report.location.as_deref().is_some_and(|location| location.contains("crash.rs")));
+        assert_eq!(report.app_version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(report.log_tail, vec!["opened wallet".to_string(), "view_key=[REDACTED]".to_string()]);
+        assert!(!report.reviewed);
+    }
+
+    #[test]
+    fn test_list_marks_reviewed_and_delete() {
+        let dir = TempDir::new("fuego_crash");
+        for id in ["20260101T000000000Z", "20260102T000000000Z"] {
+            let report = CrashReport {
+                id: id.to_string(),
+                timestamp: 0,
+                app_version: "0.1.0".to_string(),
+                thread: "main".to_string(),
+                message: "boom".to_string(),
+                location: None,
+                backtrace: String::new(),
+                log_tail: Vec::new(),
+                reviewed: false,
+            };
+            fs::write(dir.join(format!("{}.json", id)), serde_json::to_vec(&report).unwrap()).unwrap();
+        }
+        fs::write(dir.join("broken.json"), b"{").unwrap();
+
+        assert_eq!(unreviewed_count(&dir).unwrap(), 2);
+        let listed = list_reports(&dir).unwrap();
+        assert_eq!(listed.iter().map(|r| r.id.as_str()).collect::<Vec<_>>(), vec!["20260102T000000000Z", "20260101T000000000Z"]);
+        assert!(listed.iter().all(|report| !report.reviewed));
+        assert_eq!(unreviewed_count(&dir).unwrap(), 0);
+
+        delete_report(&dir, "20260101T000000000Z").unwrap();
+        assert_eq!(read_reports(&dir).unwrap().len(), 1);
+        assert!(delete_report(&dir, "../settings").is_err());
+        assert!(delete_report(&dir, "20260101T000000000Z").is_err());
+    }
+}
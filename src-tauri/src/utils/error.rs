@@ -3,8 +3,11 @@
 
 //! Error handling for the wallet
 //! 
-//! This module defines error types and result aliases used throughout the application.
+//! This module defines error types and result aliases used throughout the
+//! application, and `ApiError`, the error payload of the Tauri commands.
 
+use serde::{Deserialize, Serialize};
+use serde_json::json;
 use thiserror::Error;
 
 /// Wallet result type alias
@@ -19,6 +22,9 @@ pub enum WalletError {
     #[error("Wallet is already open")]
     WalletAlreadyOpen,
     
+    #[error("A wallet already exists at {0}")]
+    WalletAlreadyExists(String),
+    
     #[error("Invalid password")]
     InvalidPassword,
     
@@ -43,6 +49,15 @@ pub enum WalletError {
     #[error("Insufficient funds")]
     InsufficientFunds,
     
+    #[error("Wallet is locked")]
+    WalletLocked,
+    
+    #[error("Large send blocked: {amount} exceeds the configured cap of {cap} atomic units, authorize it first")]
+    LargeSendBlocked { amount: u64, cap: u64 },
+    
+    #[error("Transaction of about {size} bytes is over the network maximum of {max} bytes, split the payment or lower the mixin")]
+    TransactionTooLarge { size: u64, max: u64 },
+    
     #[error("Synchronization failed: {0}")]
     SyncFailed(String),
     
@@ -52,6 +67,18 @@ pub enum WalletError {
     #[error("Crypto error: {0}")]
     CryptoError(String),
     
+    #[error("Seed word {position} is not a valid seed word: {word}")]
+    InvalidSeedWord { position: usize, word: String },
+    
+    #[error("Wallet file is corrupted: {reason}")]
+    WalletCorrupted { reason: String, recovery: RecoveryAction },
+    
+    #[error("{operation} did not finish within {timeout_ms}ms")]
+    OperationTimeout { operation: String, timeout_ms: u64 },
+    
+    #[error("Backup file is damaged: {0}")]
+    CorruptBackup(String),
+    
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
     
@@ -65,8 +92,382 @@ pub enum WalletError {
     Generic(String),
 }
 
+/// How to get a wallet back whose file is corrupted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecoveryAction {
+    /// The keys are gone; the seed phrase recreates them
+    RestoreFromSeed,
+    /// The keys are intact but other wallet data is lost
+    RestoreFromBackup,
+}
+
 impl From<anyhow::Error> for WalletError {
     fn from(err: anyhow::Error) -> Self {
         WalletError::Generic(err.to_string())
     }
 }
+
+/// What went wrong, as the frontend switches on it and the i18n layer
+/// translates it (`error.<code>`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    WalletNotOpen,
+    WalletAlreadyOpen,
+    WalletAlreadyExists,
+    InvalidPassword,
+    WalletFileNotFound,
+    WalletCreationFailed,
+    WalletOpenFailed,
+    NetworkError,
+    TransactionFailed,
+    InvalidAddress,
+    InsufficientFunds,
+    WalletLocked,
+    LargeSendBlocked,
+    TransactionTooLarge,
+    SyncFailed,
+    StorageError,
+    CryptoError,
+    InvalidSeedWord,
+    WalletCorrupted,
+    OperationTimeout,
+    CorruptBackup,
+    IoError,
+    SerializationError,
+    InvalidInput,
+    InvalidPaymentUri,
+    InvalidAmount,
+    SecurityError,
+    SettingsError,
+    BackupError,
+    BackupCancelled,
+    InsufficientDiskSpace,
+    NotInitialized,
+    NotFound,
+    Internal,
+}
+
+impl ErrorCode {
+    /// Every code, for the translation files and tests
+    pub const ALL: [ErrorCode; 34] = [
+        ErrorCode::WalletNotOpen,
+        ErrorCode::WalletAlreadyOpen,
+        ErrorCode::WalletAlreadyExists,
+        ErrorCode::InvalidPassword,
+        ErrorCode::WalletFileNotFound,
+        ErrorCode::WalletCreationFailed,
+        ErrorCode::WalletOpenFailed,
+        ErrorCode::NetworkError,
+        ErrorCode::TransactionFailed,
+        ErrorCode::InvalidAddress,
+        ErrorCode::InsufficientFunds,
+        ErrorCode::WalletLocked,
+        ErrorCode::LargeSendBlocked,
+        ErrorCode::TransactionTooLarge,
+        ErrorCode::SyncFailed,
+        ErrorCode::StorageError,
+        ErrorCode::CryptoError,
+        ErrorCode::InvalidSeedWord,
+        ErrorCode::WalletCorrupted,
+        ErrorCode::OperationTimeout,
+        ErrorCode::CorruptBackup,
+        ErrorCode::IoError,
+        ErrorCode::SerializationError,
+        ErrorCode::InvalidInput,
+        ErrorCode::InvalidPaymentUri,
+        ErrorCode::InvalidAmount,
+        ErrorCode::SecurityError,
+        ErrorCode::SettingsError,
+        ErrorCode::BackupError,
+        ErrorCode::BackupCancelled,
+        ErrorCode::InsufficientDiskSpace,
+        ErrorCode::NotInitialized,
+        ErrorCode::NotFound,
+        ErrorCode::Internal,
+    ];
+
+    /// Translation key of the code's generic message, e.g. `error.wallet_locked`
+    pub fn translation_key(self) -> String {
+        let code = serde_json::to_value(self).ok().and_then(|v| v.as_str().map(str::to_string)).unwrap_or_default();
+        format!("error.{}", code)
+    }
+}
+
+/// Error returned by every Tauri command. `message` is readable English for
+/// logs and fallbacks; the frontend decides on `code`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiError {
+    pub code: ErrorCode,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+}
+
+impl ApiError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    pub fn with_details(mut self, details: serde_json::Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    /// Prefix the message, keeping code and details: `"<context>: <message>"`
+    pub fn context(mut self, context: &str) -> Self {
+        self.message = format!("{}: {}", context, self.message);
+        self
+    }
+
+    pub fn invalid_input(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::InvalidInput, message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::NotFound, message)
+    }
+
+    /// A global manager that `initialize_global_state` did not set up
+    pub fn not_initialized(component: &str) -> Self {
+        Self::new(ErrorCode::NotInitialized, format!("{} not initialized", component))
+    }
+
+    pub fn security(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::SecurityError, message)
+    }
+
+    pub fn settings(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::SettingsError, message)
+    }
+
+    pub fn backup(message: impl Into<String>) -> Self {
+        let message = message.into();
+        let code = if message == crate::backup::BACKUP_CANCELLED {
+            ErrorCode::BackupCancelled
+        } else {
+            ErrorCode::BackupError
+        };
+        Self::new(code, message)
+    }
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl From<crate::backup::BackupError> for ApiError {
+    fn from(err: crate::backup::BackupError) -> Self {
+        use crate::backup::BackupError;
+        match err {
+            BackupError::InsufficientDiskSpace { required, available } => {
+                Self::new(ErrorCode::InsufficientDiskSpace, err.to_string())
+                    .with_details(json!({ "required": required, "available": available }))
+            }
+            BackupError::Cancelled => Self::new(ErrorCode::BackupCancelled, err.to_string()),
+            BackupError::Failed(message) => Self::new(ErrorCode::BackupError, message),
+        }
+    }
+}
+
+impl From<WalletError> for ApiError {
+    fn from(err: WalletError) -> Self {
+        // No wildcard arm: a new variant must be given a code here
+        let (code, details) = match &err {
+            WalletError::WalletNotOpen => (ErrorCode::WalletNotOpen, None),
+            WalletError::WalletAlreadyOpen => (ErrorCode::WalletAlreadyOpen, None),
+            WalletError::WalletAlreadyExists(path) => (ErrorCode::WalletAlreadyExists, Some(json!({ "path": path }))),
+            WalletError::InvalidPassword => (ErrorCode::InvalidPassword, None),
+            WalletError::WalletFileNotFound(path) => (ErrorCode::WalletFileNotFound, Some(json!({ "path": path }))),
+            WalletError::WalletCreationFailed(_) => (ErrorCode::WalletCreationFailed, None),
+            WalletError::WalletOpenFailed(_) => (ErrorCode::WalletOpenFailed, None),
+            WalletError::NetworkError(_) => (ErrorCode::NetworkError, None),
+            WalletError::TransactionFailed(_) => (ErrorCode::TransactionFailed, None),
+            WalletError::InvalidAddress(address) => (ErrorCode::InvalidAddress, Some(json!({ "address": address }))),
+            WalletError::InsufficientFunds => (ErrorCode::InsufficientFunds, None),
+            WalletError::WalletLocked => (ErrorCode::WalletLocked, None),
+            WalletError::LargeSendBlocked { amount, cap } => {
+                (ErrorCode::LargeSendBlocked, Some(json!({ "amount": amount, "cap": cap })))
+            }
+            WalletError::TransactionTooLarge { size, max } => {
+                (ErrorCode::TransactionTooLarge, Some(json!({ "size": size, "max": max })))
+            }
+            WalletError::SyncFailed(_) => (ErrorCode::SyncFailed, None),
+            WalletError::StorageError(_) => (ErrorCode::StorageError, None),
+            WalletError::CryptoError(_) => (ErrorCode::CryptoError, None),
+            WalletError::InvalidSeedWord { position, word } => {
+                (ErrorCode::InvalidSeedWord, Some(json!({ "position": position, "word": word })))
+            }
+            WalletError::WalletCorrupted { reason, recovery } => {
+                (ErrorCode::WalletCorrupted, Some(json!({ "reason": reason, "recovery": recovery })))
+            }
+            WalletError::OperationTimeout { operation, timeout_ms } => {
+                (ErrorCode::OperationTimeout, Some(json!({ "operation": operation, "timeout_ms": timeout_ms })))
+            }
+            WalletError::CorruptBackup(_) => (ErrorCode::CorruptBackup, None),
+            WalletError::IoError(_) => (ErrorCode::IoError, None),
+            WalletError::SerializationError(_) => (ErrorCode::SerializationError, None),
+            WalletError::StringError(_) => (ErrorCode::InvalidInput, None),
+            WalletError::Generic(_) => (ErrorCode::Internal, None),
+        };
+        Self {
+            code,
+            message: err.to_string(),
+            details,
+        }
+    }
+}
+
+impl From<std::io::Error> for ApiError {
+    fn from(err: std::io::Error) -> Self {
+        WalletError::from(err).into()
+    }
+}
+
+impl From<serde_json::Error> for ApiError {
+    fn from(err: serde_json::Error) -> Self {
+        WalletError::from(err).into()
+    }
+}
+
+/// A blocking task that panicked or was cancelled
+impl From<tauri::Error> for ApiError {
+    fn from(err: tauri::Error) -> Self {
+        Self::new(ErrorCode::Internal, err.to_string())
+    }
+}
+
+/// Errors of the string-typed helpers carry no code of their own
+impl From<String> for ApiError {
+    fn from(message: String) -> Self {
+        Self::new(ErrorCode::Internal, message)
+    }
+}
+
+impl From<&str> for ApiError {
+    fn from(message: &str) -> Self {
+        Self::new(ErrorCode::Internal, message)
+    }
+}
+
+/// Result of a Tauri command
+pub type ApiResult<T> = Result<T, ApiError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One of each variant. The match has no wildcard, so adding a variant
+    /// fails to compile until it is listed here, and so mapped and tested.
+    fn every_wallet_error() -> Vec<WalletError> {
+        let sample = |err: &WalletError| match err {
+            WalletError::WalletNotOpen
+            | WalletError::WalletAlreadyOpen
+            | WalletError::WalletAlreadyExists(_)
+            | WalletError::InvalidPassword
+            | WalletError::WalletFileNotFound(_)
+            | WalletError::WalletCreationFailed(_)
+            | WalletError::WalletOpenFailed(_)
+            | WalletError::NetworkError(_)
+            | WalletError::TransactionFailed(_)
+            | WalletError::InvalidAddress(_)
+            | WalletError::InsufficientFunds
+            | WalletError::WalletLocked
+            | WalletError::LargeSendBlocked { .. }
+            | WalletError::TransactionTooLarge { .. }
+            | WalletError::SyncFailed(_)
+            | WalletError::StorageError(_)
+            | WalletError::CryptoError(_)
+            | WalletError::InvalidSeedWord { .. }
+            | WalletError::WalletCorrupted { .. }
+            | WalletError::OperationTimeout { .. }
+            | WalletError::CorruptBackup(_)
+            | WalletError::IoError(_)
+            | WalletError::SerializationError(_)
+            | WalletError::StringError(_)
+            | WalletError::Generic(_) => (),
+        };
+        let errors = vec![
+            WalletError::WalletNotOpen,
+            WalletError::WalletAlreadyOpen,
+            WalletError::WalletAlreadyExists("x".to_string()),
+            WalletError::InvalidPassword,
+            WalletError::WalletFileNotFound("/tmp/w".to_string()),
+            WalletError::WalletCreationFailed("x".to_string()),
+            WalletError::WalletOpenFailed("x".to_string()),
+            WalletError::NetworkError("x".to_string()),
+            WalletError::TransactionFailed("x".to_string()),
+            WalletError::InvalidAddress("fire1".to_string()),
+            WalletError::InsufficientFunds,
+            WalletError::WalletLocked,
+            WalletError::LargeSendBlocked { amount: 5, cap: 1 },
+            WalletError::TransactionTooLarge { size: 900_000, max: 799_400 },
+            WalletError::SyncFailed("x".to_string()),
+            WalletError::StorageError("x".to_string()),
+            WalletError::CryptoError("x".to_string()),
+            WalletError::InvalidSeedWord { position: 3, word: "nope".to_string() },
+            WalletError::WalletCorrupted { reason: "x".to_string(), recovery: RecoveryAction::RestoreFromSeed },
+            WalletError::OperationTimeout { operation: "refresh".to_string(), timeout_ms: 1000 },
+            WalletError::CorruptBackup("x".to_string()),
+            WalletError::IoError(std::io::Error::other("x")),
+            WalletError::SerializationError(serde_json::from_str::<u8>("x").unwrap_err()),
+            WalletError::StringError(std::ffi::CString::new("a\0b").unwrap_err()),
+            WalletError::Generic("x".to_string()),
+        ];
+        errors.iter().for_each(sample);
+        errors
+    }
+
+    #[test]
+    fn test_every_wallet_error_has_its_own_code() {
+        let errors = every_wallet_error();
+        let codes: std::collections::HashSet<ErrorCode> = errors.into_iter()
+            .map(|err| {
+                let message = err.to_string();
+                let api = ApiError::from(err);
+                assert_eq!(api.message, message);
+                assert!(ErrorCode::ALL.contains(&api.code));
+                api.code
+            })
+            .collect();
+        assert_eq!(codes.len(), 25);
+
+        let blocked = ApiError::from(WalletError::LargeSendBlocked { amount: 5, cap: 1 });
+        assert_eq!(blocked.details, Some(json!({ "amount": 5, "cap": 1 })));
+
+        let corrupted = ApiError::from(WalletError::WalletCorrupted {
+            reason: "key data unreadable".to_string(),
+            recovery: RecoveryAction::RestoreFromSeed,
+        });
+        assert_eq!(corrupted.details, Some(json!({ "reason": "key data unreadable", "recovery": "restore_from_seed" })));
+    }
+
+    #[test]
+    fn test_serializes_code_for_the_frontend() {
+        let err = ApiError::from(WalletError::WalletLocked).context("Failed to send transaction");
+        assert_eq!(
+            serde_json::to_value(&err).unwrap(),
+            json!({ "code": "wallet_locked", "message": "Failed to send transaction: Wallet is locked" })
+        );
+        assert_eq!(ErrorCode::WalletLocked.translation_key(), "error.wallet_locked");
+        assert_eq!(ApiError::backup(crate::backup::BACKUP_CANCELLED).code, ErrorCode::BackupCancelled);
+        assert_eq!(ApiError::from("Boom").code, ErrorCode::Internal);
+    }
+
+    #[test]
+    fn test_every_code_has_an_english_message() {
+        let locale: serde_json::Value = serde_json::from_str(include_str!("../../locales/en.json")).unwrap();
+        for code in ErrorCode::ALL {
+            assert!(locale["translations"][code.translation_key()].is_string(), "missing {}", code.translation_key());
+        }
+    }
+}
@@ -0,0 +1,107 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Locks that survive a panic
+//!
+//! A thread panicking while it holds a lock poisons it, and unwrapping the
+//! poisoned lock panics every later command that touches the same manager.
+//! The state behind the managers' locks stays usable after such a panic: at
+//! worst one update is half applied. These helpers take the guard anyway and
+//! log a warning, so one failed operation does not take the whole app down.
+
+use std::sync::{Condvar, LockResult, Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// `Mutex::lock` that recovers from poisoning
+pub trait MutexExt<T: ?Sized> {
+    fn lock_recover(&self) -> MutexGuard<'_, T>;
+}
+
+/// `RwLock::read` and `RwLock::write` that recover from poisoning
+pub trait RwLockExt<T: ?Sized> {
+    fn read_recover(&self) -> RwLockReadGuard<'_, T>;
+    fn write_recover(&self) -> RwLockWriteGuard<'_, T>;
+}
+
+/// `Condvar::wait_while` that recovers from poisoning
+pub trait CondvarExt {
+    fn wait_while_recover<'a, T, F>(&self, guard: MutexGuard<'a, T>, condition: F) -> MutexGuard<'a, T>
+    where
+        F: FnMut(&mut T) -> bool;
+}
+
+impl<T: ?Sized> MutexExt<T> for Mutex<T> {
+    fn lock_recover(&self) -> MutexGuard<'_, T> {
+        recover::<T, _>(self.lock())
+    }
+}
+
+impl<T: ?Sized> RwLockExt<T> for RwLock<T> {
+    fn read_recover(&self) -> RwLockReadGuard<'_, T> {
+        recover::<T, _>(self.read())
+    }
+
+    fn write_recover(&self) -> RwLockWriteGuard<'_, T> {
+        recover::<T, _>(self.write())
+    }
+}
+
+impl CondvarExt for Condvar {
+    fn wait_while_recover<'a, T, F>(&self, guard: MutexGuard<'a, T>, condition: F) -> MutexGuard<'a, T>
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        recover::<T, _>(self.wait_while(guard, condition))
+    }
+}
+
+fn recover<T: ?Sized, G>(result: LockResult<G>) -> G {
+    result.unwrap_or_else(|poisoned| {
+        log::warn!("Recovering lock on {} poisoned by a panic", std::any::type_name::<T>());
+        poisoned.into_inner()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_poisoned_lock_is_still_usable() {
+        let counter = Arc::new(Mutex::new(1));
+        let table = Arc::new(RwLock::new(vec![1]));
+
+        let (c, t) = (counter.clone(), table.clone());
+        let result = std::thread::spawn(move || {
+            let _counter = c.lock_recover();
+            let _table = t.write_recover();
+            panic!("operation failed while holding the locks");
+        })
+        .join();
+        assert!(result.is_err());
+        assert!(counter.is_poisoned() && table.is_poisoned());
+
+        *counter.lock_recover() += 1;
+        table.write_recover().push(2);
+        assert_eq!(*counter.lock_recover(), 2);
+        assert_eq!(*table.read_recover(), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_wait_on_poisoned_lock() {
+        let state = Arc::new((Mutex::new(None), Condvar::new()));
+
+        let s = state.clone();
+        let result = std::thread::spawn(move || {
+            let mut value = s.0.lock_recover();
+            *value = Some(7);
+            s.1.notify_all();
+            panic!("operation failed after publishing its result");
+        })
+        .join();
+        assert!(result.is_err());
+
+        let value = state.1.wait_while_recover(state.0.lock_recover(), |value| value.is_none());
+        assert_eq!(*value, Some(7));
+    }
+}
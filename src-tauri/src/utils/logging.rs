@@ -0,0 +1,400 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! File logging
+//!
+//! Desktop users never see stderr, so every record is also appended to
+//! `<data_dir>/fuego-wallet/logs/fuego-wallet.log`. Once the file would grow
+//! past `MAX_FILE_BYTES` it becomes `fuego-wallet.log.1`, older files move up
+//! by one and the oldest is dropped, so at most `MAX_FILES` files are kept.
+//! Logs leaving the app through `tail` or `export` are passed through
+//! `redact` first, so a bug report never carries a seed, key or password.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use log::{LevelFilter, Log, Metadata, Record};
+use super::storage::APP_DIR_NAME;
+
+pub const LOG_FILE_NAME: &str = "fuego-wallet.log";
+/// Size a log file may reach before it is rotated
+pub const MAX_FILE_BYTES: u64 = 5 * 1024 * 1024;
+/// Log files kept, the current one included
+pub const MAX_FILES: usize = 5;
+
+/// Replaces everything `redact` masks
+const REDACTED: &str = "[REDACTED]";
+/// Consecutive lowercase words read as a mnemonic seed
+const SEED_WORD_RUN: usize = 12;
+/// Hex digits in a private key; longer runs are masked as well
+const KEY_HEX_LEN: usize = 64;
+/// Names whose `name: value` or `name=value` values are masked. Longer names
+/// starting with one of these (`seed_phrase`, `spend_key_hex`) count too.
+const SENSITIVE_NAMES: [&str; 11] = [
+    "password",
+    "passphrase",
+    "mnemonic",
+    "seed",
+    "secret",
+    "private_key",
+    "private key",
+    "spend_key",
+    "spend key",
+    "view_key",
+    "view key",
+];
+
+static LOGGER: OnceLock<FileLogger> = OnceLock::new();
+
+/// Directory the log files are written to
+pub fn logs_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(APP_DIR_NAME).join("logs"))
+}
+
+/// Install the logger, writing to stderr and, when it can be opened, to the
+/// log file in `dir`. Records are logged at `info` until `set_level` is called.
+pub fn init(dir: Option<PathBuf>) {
+    let file = dir.and_then(|dir| {
+        match RotatingFile::open(dir.join(LOG_FILE_NAME), MAX_FILE_BYTES, MAX_FILES) {
+            Ok(file) => Some(file),
+            Err(e) => {
+                eprintln!("Failed to open log file in {}: {}", dir.display(), e);
+                None
+            }
+        }
+    });
+    let logger = LOGGER.get_or_init(|| FileLogger { file: Mutex::new(file) });
+    if log::set_logger(logger).is_ok() {
+        log::set_max_level(LevelFilter::Info);
+    }
+}
+
+/// Log at `level` ("off", "error", "warn", "info", "debug" or "trace") from now on
+pub fn set_level(level: &str) -> Result<LevelFilter, String> {
+    let filter = level.trim().parse::<LevelFilter>()
+        .map_err(|_| format!("Invalid log level: {}", level))?;
+    if filter != log::max_level() {
+        log::set_max_level(filter);
+        log::info!("Log level set to {}", filter);
+    }
+    Ok(filter)
+}
+
+/// The last `lines` lines logged to `dir`, oldest first and redacted, reaching
+/// into rotated files when the current one is shorter
+pub fn tail(dir: &Path, lines: usize) -> Result<Vec<String>, String> {
+    tail_file(dir, LOG_FILE_NAME, lines)
+}
+
+/// `tail` of the log `file_name` in `dir`, rotated like the wallet's own
+pub fn tail_file(dir: &Path, file_name: &str, lines: usize) -> Result<Vec<String>, String> {
+    let mut tail: Vec<String> = Vec::new();
+    for path in log_files(dir, file_name) {
+        if tail.len() >= lines {
+            break;
+        }
+        let content = read_log(&path)?;
+        let wanted = lines - tail.len();
+        let mut older: Vec<String> = content.lines().rev().take(wanted).map(redact).collect();
+        older.reverse();
+        older.append(&mut tail);
+        tail = older;
+    }
+    Ok(tail)
+}
+
+/// Write all log files in `dir`, oldest first and redacted, to `destination`,
+/// returning the number of lines written
+pub fn export(dir: &Path, destination: &Path) -> Result<usize, String> {
+    let mut exported = String::new();
+    let mut count = 0;
+    for path in log_files(dir, LOG_FILE_NAME).iter().rev() {
+        for line in read_log(path)?.lines() {
+            exported.push_str(&redact(line));
+            exported.push('\n');
+            count += 1;
+        }
+    }
+    super::storage::atomic_write(destination, exported.as_bytes())?;
+    Ok(count)
+}
+
+/// Existing files of the log `file_name` in `dir`, newest first
+fn log_files(dir: &Path, file_name: &str) -> Vec<PathBuf> {
+    (0..MAX_FILES)
+        .map(|index| rotated_path(&dir.join(file_name), index))
+        .filter(|path| path.exists())
+        .collect()
+}
+
+fn read_log(path: &Path) -> Result<String, String> {
+    let bytes = fs::read(path)
+        .map_err(|e| format!("Failed to read log file {}: {}", path.display(), e))?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// `path` for `index` 0, `path.<index>` for rotated files
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    if index == 0 {
+        return path.to_path_buf();
+    }
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}", index));
+    path.with_file_name(name)
+}
+
+/// Mask seeds, keys and passwords in a log line: runs of `SEED_WORD_RUN` or
+/// more lowercase words, values of `SENSITIVE_NAMES` and runs of at least
+/// `KEY_HEX_LEN` hex digits. Transaction hashes look like keys and are masked
+/// too; losing them is the price of never leaking a key.
+pub fn redact(line: &str) -> String {
+    let line = redact_word_runs(line);
+    let line = redact_named_values(&line);
+    redact_hex_runs(&line)
+}
+
+fn redact_word_runs(line: &str) -> String {
+    let is_word = |token: &str| {
+        let word = token.trim_end();
+        !word.is_empty() && word.bytes().all(|b| b.is_ascii_lowercase())
+    };
+    let tokens: Vec<&str> = line.split_inclusive(char::is_whitespace).collect();
+    let mut redacted = String::with_capacity(line.len());
+    let mut index = 0;
+    while index < tokens.len() {
+        let run = tokens[index..].iter().take_while(|token| is_word(token)).count();
+        if run >= SEED_WORD_RUN {
+            let last = tokens[index + run - 1];
+            redacted.push_str(REDACTED);
+            redacted.push_str(&last[last.trim_end().len()..]);
+            index += run;
+        } else {
+            redacted.push_str(tokens[index]);
+            index += 1;
+        }
+    }
+    redacted
+}
+
+fn redact_named_values(line: &str) -> String {
+    let lower = line.to_ascii_lowercase();
+    let bytes = lower.as_bytes();
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for name in SENSITIVE_NAMES {
+        for (start, _) in lower.match_indices(name) {
+            if start > 0 && bytes[start - 1].is_ascii_alphanumeric() {
+                continue;
+            }
+            let mut pos = start + name.len();
+            while pos < bytes.len() && (bytes[pos].is_ascii_alphanumeric() || bytes[pos] == b'_') {
+                pos += 1;
+            }
+            pos += bytes[pos..].iter().take_while(|&&b| b == b'"' || b == b'\'' || b == b' ').count();
+            if pos >= bytes.len() || !matches!(bytes[pos], b':' | b'=') {
+                continue;
+            }
+            pos += 1;
+            pos += bytes[pos..].iter().take_while(|&&b| b == b' ').count();
+            let value_end = match bytes.get(pos) {
+                Some(&quote @ (b'"' | b'\'')) => {
+                    pos += 1;
+                    bytes[pos..].iter().position(|&b| b == quote).map_or(bytes.len(), |end| pos + end)
+                }
+                _ => bytes[pos..].iter()
+                    .position(|&b| b.is_ascii_whitespace() || matches!(b, b',' | b';' | b'}' | b')'))
+                    .map_or(bytes.len(), |end| pos + end),
+            };
+            if value_end > pos {
+                ranges.push((pos, value_end));
+            }
+        }
+    }
+    ranges.sort_unstable();
+
+    let mut redacted = String::with_capacity(line.len());
+    let mut copied = 0;
+    for (start, end) in ranges {
+        if start < copied {
+            continue;
+        }
+        redacted.push_str(&line[copied..start]);
+        redacted.push_str(REDACTED);
+        copied = end;
+    }
+    redacted.push_str(&line[copied..]);
+    redacted
+}
+
+fn redact_hex_runs(line: &str) -> String {
+    let mut redacted = String::with_capacity(line.len());
+    let mut run_start = None;
+    for (index, c) in line.char_indices().chain(std::iter::once((line.len(), ' '))) {
+        match (c.is_ascii_hexdigit(), run_start) {
+            (true, None) => run_start = Some(index),
+            (true, Some(_)) => {}
+            (false, start) => {
+                if let Some(start) = start {
+                    if index - start >= KEY_HEX_LEN {
+                        redacted.push_str(REDACTED);
+                    } else {
+                        redacted.push_str(&line[start..index]);
+                    }
+                    run_start = None;
+                }
+                if index < line.len() {
+                    redacted.push(c);
+                }
+            }
+        }
+    }
+    redacted
+}
+
+/// Append-only log file rotated by size
+#[derive(Debug)]
+pub struct RotatingFile {
+    path: PathBuf,
+    max_bytes: u64,
+    max_files: usize,
+    file: File,
+    size: u64,
+}
+
+impl RotatingFile {
+    /// Open `path` for appending, creating it and its directory if needed
+    pub fn open(path: PathBuf, max_bytes: u64, max_files: usize) -> io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self { path, max_bytes, max_files: max_files.max(1), file, size })
+    }
+
+    /// Append `line`, rotating first if it would push the file past `max_bytes`
+    pub fn write_line(&mut self, line: &str) -> io::Result<()> {
+        let len = line.len() as u64 + 1;
+        if self.size > 0 && self.size + len > self.max_bytes {
+            self.rotate()?;
+        }
+        self.file.write_all(line.as_bytes())?;
+        self.file.write_all(b"\n")?;
+        self.size += len;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        for index in (1..self.max_files).rev() {
+            let from = rotated_path(&self.path, index - 1);
+            if from.exists() {
+                fs::rename(from, rotated_path(&self.path, index))?;
+            }
+        }
+        self.file = OpenOptions::new().create(true).write(true).truncate(true).open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+/// Logger writing every record to stderr and the log file
+struct FileLogger {
+    file: Mutex<Option<RotatingFile>>,
+}
+
+/// Target prefix of this crate's records; other crates are logged from `warn` up
+fn own_target() -> &'static str {
+    module_path!().split("::").next().unwrap_or_default()
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        let max = if metadata.target().starts_with(own_target()) {
+            log::max_level()
+        } else {
+            log::max_level().min(LevelFilter::Warn)
+        };
+        metadata.level() <= max
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = format!(
+            "{} {:<5} {}: {}",
+            chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f"),
+            record.level(),
+            record.target(),
+            record.args()
+        );
+        eprintln!("{}", line);
+        if let Ok(mut file) = self.file.lock()
+            && let Some(file) = file.as_mut()
+            && let Err(e) = file.write_line(&line)
+        {
+            eprintln!("Failed to write log file: {}", e);
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut file) = self.file.lock()
+            && let Some(file) = file.as_mut()
+        {
+            let _ = file.file.flush();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::testing::TempDir;
+
+    const SEED: &str = "abbey abducts ability able abnormal abort abrasive absorb abyss academy aces aching acidic";
+
+    #[test]
+    fn test_redaction() {
+        let key = "a".repeat(64);
+        assert_eq!(redact(&format!("spend key {} loaded", key)), "spend key [REDACTED] loaded");
+        assert_eq!(redact(&format!("Restoring: {}", SEED)), "Restoring: [REDACTED]");
+        assert_eq!(redact("login password=hunter2, retrying"), "login password=[REDACTED], retrying");
+        assert_eq!(redact(r#"{"seed_phrase": "one two", "height": 5}"#), r#"{"seed_phrase": "[REDACTED]", "height": 5}"#);
+        assert_eq!(redact("View key: 0badc0de"), "View key: [REDACTED]");
+
+        // Ordinary lines are left alone
+        let line = "Synced to height 123456 from fuego.spaceportx.net:18180 in 350ms";
+        assert_eq!(redact(line), line);
+        assert_eq!(redact("short hex deadbeef stays"), "short hex deadbeef stays");
+        assert_eq!(redact("the wallet is now being synced with the node"), "the wallet is now being synced with the node");
+    }
+
+    #[test]
+    fn test_rotation_keeps_max_files() {
+        let dir = TempDir::new("fuego_logs");
+        let mut file = RotatingFile::open(dir.join(LOG_FILE_NAME), 64, MAX_FILES).unwrap();
+        for index in 0..40 {
+            file.write_line(&format!("line {:02} padded to twenty", index)).unwrap();
+        }
+
+        let files = log_files(&dir, LOG_FILE_NAME);
+        assert_eq!(files.len(), MAX_FILES);
+        assert!(!rotated_path(&dir.join(LOG_FILE_NAME), MAX_FILES).exists());
+        for path in &files {
+            assert!(fs::metadata(path).unwrap().len() <= 64);
+        }
+
+        // The newest lines are in the current file, older ones in rotated files
+        let tail = tail(&dir, 4).unwrap();
+        assert_eq!(tail.len(), 4);
+        assert_eq!(tail[3], "line 39 padded to twenty");
+        assert_eq!(tail[0], "line 36 padded to twenty");
+
+        let exported = dir.join("export.log");
+        let count = export(&dir, &exported).unwrap();
+        let content = fs::read_to_string(&exported).unwrap();
+        assert_eq!(content.lines().count(), count);
+        assert!(content.ends_with("line 39 padded to twenty\n"));
+        assert!(!content.contains("line 00 "));
+    }
+}
@@ -5,6 +5,16 @@
 //! 
 //! This module contains utility functions and error handling.
 
+pub mod crash;
 pub mod error;
+pub mod locks;
+pub mod logging;
+pub mod progress;
+pub mod qr;
+pub mod retry;
+pub mod storage;
+#[cfg(test)]
+pub mod testing;
+pub mod unlock;
 
-pub use error::{WalletError, WalletResult};
+pub use error::{ApiError, ApiResult, ErrorCode, RecoveryAction, WalletError, WalletResult};
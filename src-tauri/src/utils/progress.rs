@@ -0,0 +1,106 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Progress reporting
+//!
+//! Long operations (rescan, sync, ...) hold a `ProgressReporter` and push
+//! updates through it. The command layer owns the receiving end of the
+//! channel and turns the updates into events for the frontend.
+
+use serde::{Deserialize, Serialize};
+use std::sync::mpsc::{self, Receiver, Sender};
+use crate::crypto::real_cryptonote::SyncPhase;
+
+/// A single progress update
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProgressUpdate {
+    pub operation: String,
+    pub percent: f32,
+    pub message: String,
+    /// Sync phase, for updates of syncing operations
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phase: Option<SyncPhase>,
+}
+
+/// Sending half of a progress channel
+#[derive(Debug, Clone)]
+pub struct ProgressReporter {
+    operation: String,
+    sender: Sender<ProgressUpdate>,
+}
+
+impl ProgressReporter {
+    /// Create a reporter for `operation` together with the receiver its updates go to
+    pub fn channel(operation: &str) -> (Self, Receiver<ProgressUpdate>) {
+        let (sender, receiver) = mpsc::channel();
+        let reporter = Self {
+            operation: operation.to_string(),
+            sender,
+        };
+        (reporter, receiver)
+    }
+
+    pub fn operation(&self) -> &str {
+        &self.operation
+    }
+
+    /// Report progress; `percent` is clamped to 0..=100. Updates sent after the
+    /// receiver went away are dropped, an operation never fails because nobody listens.
+    pub fn report(&self, percent: f32, message: impl Into<String>) {
+        self.send(percent, None, message.into());
+    }
+
+    /// `report` of a sync that is in `phase`
+    pub fn report_phase(&self, percent: f32, phase: SyncPhase, message: impl Into<String>) {
+        self.send(percent, Some(phase), message.into());
+    }
+
+    fn send(&self, percent: f32, phase: Option<SyncPhase>, message: String) {
+        let percent = if percent.is_finite() { percent.clamp(0.0, 100.0) } else { 0.0 };
+        let _ = self.sender.send(ProgressUpdate {
+            operation: self.operation.clone(),
+            percent,
+            message,
+            phase,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_updates_arrive_in_order() {
+        let (reporter, receiver) = ProgressReporter::channel("rescan");
+        reporter.report(0.0, "Starting");
+        reporter.report(50.0, "Halfway");
+        reporter.report(100.0, "Done");
+        drop(reporter);
+
+        let updates: Vec<ProgressUpdate> = receiver.iter().collect();
+        let percents: Vec<f32> = updates.iter().map(|u| u.percent).collect();
+        assert_eq!(percents, vec![0.0, 50.0, 100.0]);
+        assert!(updates.iter().all(|u| u.operation == "rescan"));
+        assert_eq!(updates[1].message, "Halfway");
+    }
+
+    #[test]
+    fn test_percent_is_clamped() {
+        let (reporter, receiver) = ProgressReporter::channel("sync");
+        reporter.report(-5.0, "");
+        reporter.report(250.0, "");
+        reporter.report(f32::NAN, "");
+        drop(reporter);
+
+        let percents: Vec<f32> = receiver.iter().map(|u| u.percent).collect();
+        assert_eq!(percents, vec![0.0, 100.0, 0.0]);
+    }
+
+    #[test]
+    fn test_report_without_receiver_is_ignored() {
+        let (reporter, receiver) = ProgressReporter::channel("sync");
+        drop(receiver);
+        reporter.report(10.0, "nobody listens");
+    }
+}
@@ -0,0 +1,381 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! QR codes
+//!
+//! A small encoder for the codes the wallet prints: byte mode, error
+//! correction level L, versions 1 to 6. That holds up to 134 bytes, enough
+//! for a `fuego:` URI of a standard address. Versions from 7 on carry version
+//! information blocks and more alignment patterns and are not supported.
+
+/// Codewords, error correction codewords per block and blocks of versions
+/// 1 to 6 at level L
+const VERSIONS: [(usize, usize, usize); 6] = [(26, 7, 1), (44, 10, 1), (70, 15, 1), (100, 20, 1), (134, 26, 1), (172, 18, 2)];
+/// Format bits of error correction level L
+const LEVEL_L: u32 = 1;
+/// Light modules around the code, as the standard requires
+const QUIET_ZONE: usize = 4;
+
+/// A QR code as a square of modules, `true` for dark
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QrCode {
+    pub version: usize,
+    pub size: usize,
+    modules: Vec<bool>,
+}
+
+impl QrCode {
+    /// Encode `data` in the smallest version holding it
+    pub fn encode(data: &[u8]) -> Result<Self, String> {
+        let version = (1..=VERSIONS.len())
+            .find(|&v| Self::data_capacity(v) >= data.len() + 2)
+            .ok_or_else(|| format!("{} bytes do not fit in a QR code", data.len()))?;
+        let codewords = Self::codewords(version, data);
+
+        let mut code = Self::blank(version);
+        let reserved = code.draw_function_patterns();
+        code.draw_codewords(&codewords, &reserved);
+        let mask = (0..8)
+            .min_by_key(|&mask| {
+                let mut candidate = code.clone();
+                candidate.apply_mask(mask, &reserved);
+                candidate.draw_format(mask);
+                candidate.penalty()
+            })
+            .unwrap_or(0);
+        code.apply_mask(mask, &reserved);
+        code.draw_format(mask);
+        Ok(code)
+    }
+
+    pub fn is_dark(&self, x: usize, y: usize) -> bool {
+        self.modules[y * self.size + x]
+    }
+
+    /// The code as an SVG drawing, quiet zone included, one unit per module
+    pub fn to_svg(&self) -> String {
+        let extent = self.size + 2 * QUIET_ZONE;
+        let mut path = String::new();
+        for y in 0..self.size {
+            for x in (0..self.size).filter(|&x| self.is_dark(x, y)) {
+                path.push_str(&format!("M{},{}h1v1h-1z", x + QUIET_ZONE, y + QUIET_ZONE));
+            }
+        }
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {0} {0}\" shape-rendering=\"crispEdges\">\
+             <rect width=\"{0}\" height=\"{0}\" fill=\"#fff\"/><path d=\"{1}\" fill=\"#000\"/></svg>",
+            extent, path
+        )
+    }
+
+    /// Data bytes a version holds, mode and length header included
+    fn data_capacity(version: usize) -> usize {
+        let (total, ec, blocks) = VERSIONS[version - 1];
+        total - ec * blocks
+    }
+
+    /// Data and error correction codewords of `data`, interleaved by block
+    fn codewords(version: usize, data: &[u8]) -> Vec<u8> {
+        let (_, ec_len, blocks) = VERSIONS[version - 1];
+        let capacity = Self::data_capacity(version);
+
+        // Byte mode, 8-bit length, the data and a terminator of up to 4 bits
+        let mut bits = BitBuffer::default();
+        bits.push(0b0100, 4);
+        bits.push(data.len() as u32, 8);
+        data.iter().for_each(|&byte| bits.push(byte as u32, 8));
+        bits.push(0, (capacity * 8 - bits.len()).min(4));
+        let mut bytes = bits.into_bytes();
+        for pad in [0xEC, 0x11].into_iter().cycle().take(capacity - bytes.len()) {
+            bytes.push(pad);
+        }
+
+        let block_len = capacity / blocks;
+        let data_blocks: Vec<&[u8]> = bytes.chunks(block_len).collect();
+        let ec_blocks: Vec<Vec<u8>> = data_blocks.iter().map(|block| reed_solomon(block, ec_len)).collect();
+        let mut interleaved = Vec::with_capacity(capacity + ec_len * blocks);
+        for i in 0..block_len {
+            interleaved.extend(data_blocks.iter().map(|block| block[i]));
+        }
+        for i in 0..ec_len {
+            interleaved.extend(ec_blocks.iter().map(|block| block[i]));
+        }
+        interleaved
+    }
+
+    fn blank(version: usize) -> Self {
+        let size = 17 + 4 * version;
+        Self { version, size, modules: vec![false; size * size] }
+    }
+
+    fn set(&mut self, x: usize, y: usize, dark: bool) {
+        self.modules[y * self.size + x] = dark;
+    }
+
+    /// Draw the finder, timing and alignment patterns and the dark module,
+    /// and return which modules they and the format bits take
+    fn draw_function_patterns(&mut self) -> Vec<bool> {
+        let size = self.size;
+        let mut reserved = vec![false; size * size];
+        let mut draw = |code: &mut Self, x: usize, y: usize, dark: bool| {
+            code.set(x, y, dark);
+            reserved[y * size + x] = true;
+        };
+
+        for i in 0..size {
+            draw(self, 6, i, i % 2 == 0);
+            draw(self, i, 6, i % 2 == 0);
+        }
+        // Finders with their light separators
+        for (cx, cy) in [(3, 3), (size - 4, 3), (3, size - 4)] {
+            for dy in -4i32..=4 {
+                for dx in -4i32..=4 {
+                    let (x, y) = (cx as i32 + dx, cy as i32 + dy);
+                    if (0..size as i32).contains(&x) && (0..size as i32).contains(&y) {
+                        let distance = dx.abs().max(dy.abs());
+                        draw(self, x as usize, y as usize, distance != 2 && distance != 4);
+                    }
+                }
+            }
+        }
+        if self.version >= 2 {
+            let center = 4 * self.version as i32 + 10;
+            for dy in -2i32..=2 {
+                for dx in -2i32..=2 {
+                    draw(self, (center + dx) as usize, (center + dy) as usize, dx.abs().max(dy.abs()) != 1);
+                }
+            }
+        }
+        // Format bits, drawn for real once the mask is chosen
+        for i in (0..9).filter(|&i| i != 6) {
+            draw(self, 8, i, false);
+            draw(self, i, 8, false);
+        }
+        for i in 0..8 {
+            draw(self, size - 1 - i, 8, false);
+            draw(self, 8, size - 1 - i, false);
+        }
+        draw(self, 8, size - 8, true);
+        reserved
+    }
+
+    /// Place codewords in the zigzag from the bottom right corner
+    fn draw_codewords(&mut self, codewords: &[u8], reserved: &[bool]) {
+        let size = self.size;
+        let total_bits = codewords.len() * 8;
+        let mut bit = 0;
+        let mut right = size as i32 - 1;
+        while right >= 1 {
+            if right == 6 {
+                right = 5;
+            }
+            let upward = (right + 1) & 2 == 0;
+            for vertical in 0..size {
+                let y = if upward { size - 1 - vertical } else { vertical };
+                for x in [right as usize, right as usize - 1] {
+                    if !reserved[y * size + x] && bit < total_bits {
+                        self.set(x, y, codewords[bit / 8] >> (7 - bit % 8) & 1 == 1);
+                        bit += 1;
+                    }
+                }
+            }
+            right -= 2;
+        }
+    }
+
+    fn apply_mask(&mut self, mask: u32, reserved: &[bool]) {
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let flip = match mask {
+                    0 => (x + y) % 2 == 0,
+                    1 => y % 2 == 0,
+                    2 => x % 3 == 0,
+                    3 => (x + y) % 3 == 0,
+                    4 => (x / 3 + y / 2) % 2 == 0,
+                    5 => x * y % 2 + x * y % 3 == 0,
+                    6 => (x * y % 2 + x * y % 3) % 2 == 0,
+                    _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+                };
+                if flip && !reserved[y * self.size + x] {
+                    let index = y * self.size + x;
+                    self.modules[index] = !self.modules[index];
+                }
+            }
+        }
+    }
+
+    /// Both copies of the level and mask, BCH protected
+    fn draw_format(&mut self, mask: u32) {
+        let data = LEVEL_L << 3 | mask;
+        let mut remainder = data;
+        for _ in 0..10 {
+            remainder = (remainder << 1) ^ ((remainder >> 9) * 0x537);
+        }
+        let bits = (data << 10 | remainder) ^ 0x5412;
+        let bit = |i: usize| bits >> i & 1 == 1;
+
+        let size = self.size;
+        for i in 0..6 {
+            self.set(8, i, bit(i));
+        }
+        self.set(8, 7, bit(6));
+        self.set(8, 8, bit(7));
+        self.set(7, 8, bit(8));
+        for i in 9..15 {
+            self.set(14 - i, 8, bit(i));
+        }
+        for i in 0..8 {
+            self.set(size - 1 - i, 8, bit(i));
+        }
+        for i in 8..15 {
+            self.set(8, size - 15 + i, bit(i));
+        }
+    }
+
+    /// How hard the code is to scan, by the four rules of the standard
+    fn penalty(&self) -> usize {
+        let size = self.size;
+        let mut penalty = 0;
+        let lines = (0..size).map(|y| (0..size).map(|x| self.is_dark(x, y)).collect::<Vec<_>>())
+            .chain((0..size).map(|x| (0..size).map(|y| self.is_dark(x, y)).collect()));
+        const FINDER_LIKE: [bool; 11] = [true, false, true, true, true, false, true, false, false, false, false];
+        for line in lines {
+            let mut run = 1;
+            for i in 1..=size {
+                if i < size && line[i] == line[i - 1] {
+                    run += 1;
+                    continue;
+                }
+                if run >= 5 {
+                    penalty += run - 2;
+                }
+                run = 1;
+            }
+            penalty += line.windows(FINDER_LIKE.len())
+                .filter(|w| w.iter().eq(FINDER_LIKE.iter()) || w.iter().eq(FINDER_LIKE.iter().rev()))
+                .count() * 40;
+        }
+        for y in 1..size {
+            for x in 1..size {
+                let dark = self.is_dark(x, y);
+                if self.is_dark(x - 1, y) == dark && self.is_dark(x, y - 1) == dark && self.is_dark(x - 1, y - 1) == dark {
+                    penalty += 3;
+                }
+            }
+        }
+        let dark = self.modules.iter().filter(|&&m| m).count();
+        let percent = dark * 100 / self.modules.len();
+        penalty + percent.abs_diff(50) / 5 * 10
+    }
+}
+
+#[derive(Default)]
+struct BitBuffer {
+    bits: Vec<bool>,
+}
+
+impl BitBuffer {
+    fn push(&mut self, value: u32, count: usize) {
+        self.bits.extend((0..count).rev().map(|i| value >> i & 1 == 1));
+    }
+
+    fn len(&self) -> usize {
+        self.bits.len()
+    }
+
+    /// The bits, zero-padded to whole bytes
+    fn into_bytes(self) -> Vec<u8> {
+        self.bits.chunks(8)
+            .map(|chunk| chunk.iter().enumerate().fold(0u8, |byte, (i, &b)| byte | (u8::from(b) << (7 - i))))
+            .collect()
+    }
+}
+
+/// Multiply in GF(256) with the QR polynomial x^8 + x^4 + x^3 + x^2 + 1
+fn gf_multiply(a: u8, b: u8) -> u8 {
+    let mut product: u16 = 0;
+    for i in (0..8).rev() {
+        product = (product << 1) ^ ((product >> 7) * 0x11D);
+        product ^= ((b >> i) & 1) as u16 * a as u16;
+    }
+    product as u8
+}
+
+/// Generator polynomial of degree `degree`, leading coefficient left out
+fn generator(degree: usize) -> Vec<u8> {
+    let mut coefficients = vec![0u8; degree];
+    coefficients[degree - 1] = 1;
+    let mut root = 1u8;
+    for _ in 0..degree {
+        for j in 0..degree {
+            coefficients[j] = gf_multiply(coefficients[j], root);
+            if j + 1 < degree {
+                coefficients[j] ^= coefficients[j + 1];
+            }
+        }
+        root = gf_multiply(root, 0x02);
+    }
+    coefficients
+}
+
+/// Error correction codewords of a block
+fn reed_solomon(data: &[u8], ec_len: usize) -> Vec<u8> {
+    let generator = generator(ec_len);
+    let mut remainder = vec![0u8; ec_len];
+    for &byte in data {
+        let factor = byte ^ remainder.remove(0);
+        remainder.push(0);
+        for (r, &g) in remainder.iter_mut().zip(&generator) {
+            *r ^= gf_multiply(g, factor);
+        }
+    }
+    remainder
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Evaluate a polynomial, highest coefficient first, at `x`
+    fn evaluate(polynomial: &[u8], x: u8) -> u8 {
+        polynomial.iter().fold(0, |acc, &c| gf_multiply(acc, x) ^ c)
+    }
+
+    #[test]
+    fn test_error_correction_codewords_are_a_codeword() {
+        // The standard's example: "01234567" at 1-M
+        assert_eq!(
+            reed_solomon(&[0x10, 0x20, 0x0C, 0x56, 0x61, 0x80, 0xEC, 0x11, 0xEC, 0x11, 0xEC, 0x11, 0xEC, 0x11, 0xEC, 0x11], 10),
+            vec![0xA5, 0x24, 0xD4, 0xC1, 0xED, 0x36, 0xC7, 0x87, 0x2C, 0x55]
+        );
+        let data: Vec<u8> = (0..108).map(|i| (i * 37 % 256) as u8).collect();
+        let message: Vec<u8> = data.iter().copied().chain(reed_solomon(&data, 26)).collect();
+        let mut root = 1u8;
+        for _ in 0..26 {
+            assert_eq!(evaluate(&message, root), 0);
+            root = gf_multiply(root, 2);
+        }
+    }
+
+    #[test]
+    fn test_address_uri_fits_version_5() {
+        let uri = format!("fuego:fire{}", "A".repeat(94));
+        let code = QrCode::encode(uri.as_bytes()).unwrap();
+        assert_eq!((code.version, code.size), (5, 37));
+        // Finder patterns in three corners, timing between them
+        for (x, y) in [(0, 0), (36, 0), (0, 36), (3, 3), (33, 3), (3, 33)] {
+            assert!(code.is_dark(x, y));
+        }
+        assert!(!code.is_dark(7, 7) && !code.is_dark(1, 1));
+        assert!((8..29).all(|i| code.is_dark(i, 6) == (i % 2 == 0)));
+        assert!(code.is_dark(8, 29));
+        // Both copies of the format bits agree
+        let first: Vec<bool> = (0..6).map(|i| code.is_dark(8, i)).collect();
+        let second: Vec<bool> = (0..6).map(|i| code.is_dark(36 - i, 8)).collect();
+        assert_eq!(first, second);
+        assert!(code.to_svg().starts_with("<svg"));
+
+        assert_eq!(QrCode::encode(b"fuego").unwrap().version, 1);
+        assert!(QrCode::encode(&[b'a'; 140]).is_err());
+    }
+}
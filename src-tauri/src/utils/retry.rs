@@ -0,0 +1,242 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Retrying network calls
+//!
+//! `retry_async` runs an operation until it succeeds, fails with an error that
+//! retrying cannot fix, or runs out of attempts. Delays between attempts grow
+//! exponentially and are shortened by a random jitter so that clients which
+//! failed together do not retry together. Every retry is recorded in the
+//! performance metrics as `retry:<name>`, which makes flaky nodes and
+//! explorers show up in the diagnostics view.
+
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use crate::performance::PerformanceMonitor;
+use super::error::WalletError;
+
+/// Monitor retries are recorded in, set once at startup
+static RETRY_MONITOR: OnceLock<Arc<PerformanceMonitor>> = OnceLock::new();
+
+/// Record retries of every `retry_async` call in `monitor`
+pub fn record_retries_in(monitor: Arc<PerformanceMonitor>) {
+    let _ = RETRY_MONITOR.set(monitor);
+}
+
+/// How often and how patiently an operation is retried
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Name retries are recorded under
+    pub name: &'static str,
+    /// Attempts in total, the first one included; at least one is made
+    pub max_attempts: u32,
+    /// Delay before the first retry; doubled for each further one
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Share of each delay, from 0.0 to 1.0, that is randomly cut off
+    pub jitter: f64,
+    /// Give up on a single attempt after this long and count it as a timeout
+    pub attempt_timeout: Option<Duration>,
+}
+
+impl RetryPolicy {
+    /// Three attempts, 250ms then 500ms apart, each bounded by `attempt_timeout`
+    pub fn network(name: &'static str, attempt_timeout: Option<Duration>) -> Self {
+        Self {
+            name,
+            max_attempts: 3,
+            base_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(5),
+            jitter: 0.2,
+            attempt_timeout,
+        }
+    }
+
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    pub fn with_base_delay(mut self, base_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self
+    }
+
+    /// Delay before retry number `retry` (1 for the first retry), with
+    /// `jitter_sample` in 0.0..1.0 choosing how much of the jitter is applied
+    pub fn delay(&self, retry: u32, jitter_sample: f64) -> Duration {
+        let exponent = retry.saturating_sub(1).min(31);
+        let delay = self.base_delay.saturating_mul(1u32 << exponent).min(self.max_delay);
+        let cut = self.jitter.clamp(0.0, 1.0) * jitter_sample.clamp(0.0, 1.0);
+        delay.mul_f64(1.0 - cut)
+    }
+}
+
+/// Errors that tell whether trying again may help
+pub trait Retryable {
+    /// Timeouts, refused or reset connections and server-side failures are
+    /// worth retrying; invalid input or failed authentication are not
+    fn is_retryable(&self) -> bool;
+
+    /// The error for an attempt that ran longer than the policy allows
+    fn timed_out(after: Duration) -> Self;
+}
+
+impl Retryable for WalletError {
+    fn is_retryable(&self) -> bool {
+        matches!(self, WalletError::NetworkError(message) if is_transient(message))
+    }
+
+    fn timed_out(after: Duration) -> Self {
+        WalletError::NetworkError(format!("timed out after {}ms", after.as_millis()))
+    }
+}
+
+impl Retryable for String {
+    fn is_retryable(&self) -> bool {
+        is_transient(self)
+    }
+
+    fn timed_out(after: Duration) -> Self {
+        format!("timed out after {}ms", after.as_millis())
+    }
+}
+
+/// Whether an error message describes a failure that may go away by itself
+pub fn is_transient(message: &str) -> bool {
+    const TRANSIENT: [&str; 10] = [
+        "timed out",
+        "timeout",
+        "connection refused",
+        "connection reset",
+        "connection closed",
+        "broken pipe",
+        "error sending request",
+        "temporarily unavailable",
+        "http 5",
+        "http 429",
+    ];
+    let message = message.to_ascii_lowercase();
+    TRANSIENT.iter().any(|pattern| message.contains(pattern))
+}
+
+/// Run `operation` under `policy`, sleeping with tokio between attempts
+pub async fn retry_async<T, E, Op, Fut>(policy: &RetryPolicy, operation: Op) -> Result<T, E>
+where
+    E: Retryable + std::fmt::Display,
+    Op: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    retry_with(policy, operation, tokio::time::sleep, rand::random::<f64>).await
+}
+
+/// `retry_async` with the sleep and the jitter source passed in
+async fn retry_with<T, E, Op, Fut, S, SleepFut, J>(
+    policy: &RetryPolicy,
+    mut operation: Op,
+    mut sleep: S,
+    mut jitter_sample: J,
+) -> Result<T, E>
+where
+    E: Retryable + std::fmt::Display,
+    Op: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    S: FnMut(Duration) -> SleepFut,
+    SleepFut: Future<Output = ()>,
+    J: FnMut() -> f64,
+{
+    let attempts = policy.max_attempts.max(1);
+    let mut attempt = 1;
+    loop {
+        let timer = RETRY_MONITOR.get().map(|monitor| monitor.start_timing(format!("retry:{}", policy.name)));
+        let result = match policy.attempt_timeout {
+            Some(limit) => tokio::time::timeout(limit, operation()).await
+                .unwrap_or_else(|_| Err(E::timed_out(limit))),
+            None => operation().await,
+        };
+
+        match result {
+            Err(e) if attempt < attempts && e.is_retryable() => {
+                let delay = policy.delay(attempt, jitter_sample());
+                log::warn!(
+                    "{} failed (attempt {}/{}), retrying in {}ms: {}",
+                    policy.name, attempt, attempts, delay.as_millis(), e
+                );
+                if let Some(timer) = timer {
+                    timer.finish(false);
+                }
+                sleep(delay).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    fn policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            name: "test",
+            max_attempts,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(350),
+            jitter: 0.0,
+            attempt_timeout: None,
+        }
+    }
+
+    /// Run `retry_with` over `outcomes`, returning the result, the number of
+    /// attempts made and the delays slept
+    fn run(policy: &RetryPolicy, outcomes: Vec<Result<u32, String>>) -> (Result<u32, String>, usize, Vec<Duration>) {
+        let outcomes = RefCell::new(outcomes.into_iter());
+        let attempts = RefCell::new(0);
+        let slept = RefCell::new(Vec::new());
+        let result = tauri::async_runtime::block_on(retry_with(
+            policy,
+            || {
+                *attempts.borrow_mut() += 1;
+                let outcome = outcomes.borrow_mut().next().expect("no outcome left");
+                async move { outcome }
+            },
+            |delay| {
+                slept.borrow_mut().push(delay);
+                async {}
+            },
+            || 0.5,
+        ));
+        (result, attempts.into_inner(), slept.into_inner())
+    }
+
+    #[test]
+    fn test_backoff_sequence() {
+        let refused = || Err("connection refused".to_string());
+        let (result, attempts, slept) = run(&policy(5), vec![refused(), refused(), refused(), refused(), Ok(7)]);
+        assert_eq!((result, attempts), (Ok(7), 5));
+        let millis: Vec<u128> = slept.iter().map(|d| d.as_millis()).collect();
+        assert_eq!(millis, vec![100, 200, 350, 350]);
+
+        // Out of attempts: the last error is returned
+        let (result, attempts, slept) = run(&policy(2), vec![refused(), Err("HTTP 503".to_string())]);
+        assert_eq!((result, attempts, slept.len()), (Err("HTTP 503".to_string()), 2, 1));
+
+        let jittered = RetryPolicy { jitter: 0.5, ..policy(3) };
+        assert_eq!(jittered.delay(2, 0.0), Duration::from_millis(200));
+        assert_eq!(jittered.delay(2, 1.0), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_permanent_errors_are_not_retried() {
+        let (result, attempts, slept) = run(&policy(5), vec![Err("Invalid address: fire1".to_string())]);
+        assert_eq!((result, attempts), (Err("Invalid address: fire1".to_string()), 1));
+        assert!(slept.is_empty());
+
+        assert!(!WalletError::InvalidPassword.is_retryable());
+        assert!(!WalletError::NetworkError("HTTP 401".to_string()).is_retryable());
+        assert!(WalletError::NetworkError("connection timed out".to_string()).is_retryable());
+        assert!(WalletError::timed_out(Duration::from_secs(1)).is_retryable());
+    }
+}
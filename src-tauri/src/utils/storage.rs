@@ -0,0 +1,112 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Storage helpers
+//!
+//! File writes that must never leave a half-written file behind go through
+//! here, as do the paths and JSON loading of the files the wallet keeps next
+//! to itself and in the app's config directory.
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Name of the app's directories under the config and data directories
+pub const APP_DIR_NAME: &str = "fuego-wallet";
+
+/// `<wallet_path><suffix>`, the path of a file kept next to the wallet
+pub fn sidecar_path(wallet_path: &Path, suffix: &str) -> PathBuf {
+    let mut name = wallet_path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// `name` in the app's config directory
+pub fn config_path(name: &str) -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(APP_DIR_NAME).join(name))
+}
+
+/// Parse the JSON file at `path`; `None` when there is no file. `what` names
+/// the contents in error messages.
+pub fn load_json<T: DeserializeOwned>(path: &Path, what: &str) -> Result<Option<T>, String> {
+    match fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map(Some)
+            .map_err(|e| format!("Failed to parse {}: {}", what, e)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(format!("Failed to read {}: {}", what, e)),
+    }
+}
+
+/// `load_json`, with a missing file meaning the default value
+pub fn load_json_or_default<T: DeserializeOwned + Default>(path: &Path, what: &str) -> Result<T, String> {
+    load_json(path, what).map(Option::unwrap_or_default)
+}
+
+/// Write `value` to `path` as pretty-printed JSON, atomically
+pub fn save_json<T: Serialize + ?Sized>(path: &Path, value: &T, what: &str) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize {}: {}", what, e))?;
+    atomic_write(path, content.as_bytes())
+}
+
+/// Write `contents` to `path` atomically.
+///
+/// The data is written to a temporary sibling file, flushed to disk and then
+/// renamed over the destination, so readers observe either the old or the new
+/// file but never a partial one.
+pub fn atomic_write(path: &Path, contents: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directory {}: {}", parent.display(), e))?;
+    }
+
+    let tmp_path = temp_path_for(path);
+    let result = (|| {
+        let mut file = fs::File::create(&tmp_path)
+            .map_err(|e| format!("Failed to create temporary file: {}", e))?;
+        file.write_all(contents)
+            .map_err(|e| format!("Failed to write temporary file: {}", e))?;
+        file.sync_all()
+            .map_err(|e| format!("Failed to sync temporary file: {}", e))?;
+        fs::rename(&tmp_path, path)
+            .map_err(|e| format!("Failed to move file into place: {}", e))
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+/// Temporary sibling path used while writing `path`
+pub(crate) fn temp_path_for(path: &Path) -> PathBuf {
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "file".to_string());
+    path.with_file_name(format!(".{}.{}.tmp", file_name, uuid::Uuid::new_v4().simple()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::testing::TempDir;
+
+    #[test]
+    fn test_atomic_write_replaces_contents() {
+        let dir = TempDir::new("fuego_storage");
+        let path = dir.join("data.json");
+
+        atomic_write(&path, b"first").unwrap();
+        atomic_write(&path, b"second").unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), b"second");
+        // No temporary files are left behind
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+    }
+}
@@ -0,0 +1,59 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Unlock time helpers
+//!
+//! CryptoNote stores a single `unlock_time` per transaction whose meaning
+//! depends on its magnitude: below `MAX_BLOCK_NUMBER` it is a block height,
+//! otherwise a unix timestamp.
+
+/// Values below this are block heights, values at or above are unix timestamps
+pub const MAX_BLOCK_NUMBER: u64 = 500_000_000;
+
+/// Blocks of tolerance when comparing against a height-based unlock time
+pub const LOCKED_TX_ALLOWED_DELTA_BLOCKS: u64 = 1;
+
+/// Seconds of tolerance when comparing against a time-based unlock time (one block)
+pub const LOCKED_TX_ALLOWED_DELTA_SECONDS: u64 = 480;
+
+/// Whether an output with `unlock_time` can be spent at `current_height` / `current_ts`
+pub fn is_unlocked(unlock_time: u64, current_height: u64, current_ts: u64) -> bool {
+    if unlock_time < MAX_BLOCK_NUMBER {
+        // The next block to be mined is `current_height`, i.e. the last one is `current_height - 1`
+        current_height.saturating_add(LOCKED_TX_ALLOWED_DELTA_BLOCKS).saturating_sub(1) >= unlock_time
+    } else {
+        current_ts.saturating_add(LOCKED_TX_ALLOWED_DELTA_SECONDS) >= unlock_time
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_height_based_unlock() {
+        assert!(is_unlocked(0, 0, 0));
+        assert!(is_unlocked(100, 100, 0));
+        assert!(!is_unlocked(101, 100, 0));
+        // The timestamp is irrelevant for height-based locks
+        assert!(!is_unlocked(101, 100, u64::MAX));
+    }
+
+    #[test]
+    fn test_timestamp_based_unlock() {
+        let unlock = 1_700_000_000;
+        assert!(!is_unlocked(unlock, u64::MAX, unlock - LOCKED_TX_ALLOWED_DELTA_SECONDS - 1));
+        assert!(is_unlocked(unlock, 0, unlock - LOCKED_TX_ALLOWED_DELTA_SECONDS));
+        assert!(is_unlocked(unlock, 0, unlock));
+    }
+
+    #[test]
+    fn test_threshold_boundary() {
+        // Just below the threshold the value is a height
+        assert!(is_unlocked(MAX_BLOCK_NUMBER - 1, MAX_BLOCK_NUMBER - 1, 0));
+        assert!(!is_unlocked(MAX_BLOCK_NUMBER - 1, 1_000, MAX_BLOCK_NUMBER));
+        // At the threshold it is a timestamp
+        assert!(is_unlocked(MAX_BLOCK_NUMBER, 0, MAX_BLOCK_NUMBER));
+        assert!(!is_unlocked(MAX_BLOCK_NUMBER, MAX_BLOCK_NUMBER, 0));
+    }
+}
@@ -0,0 +1,407 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Address book export and import
+//!
+//! The address book lives in the native wallet. These helpers move it to and
+//! from a portable JSON or CSV file and decide which entries of an import file
+//! end up in the wallet. CSV files have a header row naming the columns, in any
+//! order, so files from other wallets import as long as they have an
+//! `address` column.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use crate::crypto::real_cryptonote::AddressBookEntry;
+use crate::utils::storage::atomic_write;
+
+/// Format version written to export files
+pub const ADDRESS_BOOK_EXPORT_VERSION: u32 = 1;
+
+/// Columns of a CSV export, in order
+pub const CSV_COLUMNS: [&str; 4] = ["label", "address", "description", "created_time"];
+
+/// Layout of an address book export file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddressBookExport {
+    pub version: u32,
+    pub exported_at: u64,
+    pub entries: Vec<AddressBookEntry>,
+}
+
+/// File format of an export or import
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AddressBookFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+/// What an import does with addresses already in the address book
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MergeStrategy {
+    /// Keep the existing entry
+    #[default]
+    Skip,
+    /// Replace the label and description of the existing entry
+    Overwrite,
+    /// Clear the address book first
+    Replace,
+}
+
+/// A row of an import file that was not imported
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InvalidRow {
+    /// Line the row starts on in a CSV file, or position of the entry in a JSON file
+    pub line: usize,
+    pub reason: String,
+}
+
+/// Outcome of an address book import
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressBookImportReport {
+    pub imported: usize,
+    /// Entries already in the address book, or repeated in the file
+    pub skipped: usize,
+    pub invalid: Vec<InvalidRow>,
+}
+
+impl AddressBookImportReport {
+    /// Count an entry that passed validation but could not be added
+    pub fn record_failure(&mut self, line: usize, reason: String) {
+        self.imported = self.imported.saturating_sub(1);
+        self.invalid.push(InvalidRow { line, reason });
+    }
+}
+
+/// One row read from an import file
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportRow {
+    pub line: usize,
+    pub entry: Result<AddressBookEntry, String>,
+}
+
+/// Entries of an import to write to the wallet, each with its row's line
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImportPlan {
+    pub add: Vec<(usize, AddressBookEntry)>,
+    /// Existing addresses to give the imported label and description
+    pub update: Vec<(usize, AddressBookEntry)>,
+}
+
+/// Write `entries` to `path` as an export file
+pub fn export_address_book(path: &Path, entries: Vec<AddressBookEntry>, format: AddressBookFormat) -> Result<(), String> {
+    let content = match format {
+        AddressBookFormat::Json => {
+            let export = AddressBookExport {
+                version: ADDRESS_BOOK_EXPORT_VERSION,
+                exported_at: chrono::Utc::now().timestamp().max(0) as u64,
+                entries,
+            };
+            serde_json::to_string_pretty(&export)
+                .map_err(|e| format!("Failed to serialize address book: {}", e))?
+        }
+        AddressBookFormat::Csv => to_csv(&entries),
+    };
+    atomic_write(path, content.as_bytes())
+}
+
+/// Read the rows of an import file. Fails only when the file as a whole
+/// cannot be read; rows that cannot be read come back as errors.
+pub fn read_address_book(path: &Path, format: AddressBookFormat) -> Result<Vec<ImportRow>, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read address book file: {}", e))?;
+    match format {
+        AddressBookFormat::Json => {
+            let export: AddressBookExport = serde_json::from_str(&content)
+                .map_err(|e| format!("Failed to parse address book file: {}", e))?;
+            if export.version > ADDRESS_BOOK_EXPORT_VERSION {
+                return Err(format!(
+                    "Address book file version {} is newer than supported version {}",
+                    export.version, ADDRESS_BOOK_EXPORT_VERSION
+                ));
+            }
+            Ok(export.entries.into_iter()
+                .enumerate()
+                .map(|(index, entry)| ImportRow { line: index + 1, entry: Ok(entry) })
+                .collect())
+        }
+        AddressBookFormat::Csv => from_csv(&content),
+    }
+}
+
+fn to_csv(entries: &[AddressBookEntry]) -> String {
+    let mut csv = CSV_COLUMNS.join(",");
+    csv.push_str("\r\n");
+    for entry in entries {
+        let fields = [
+            csv_field(&entry.label),
+            csv_field(&entry.address),
+            csv_field(&entry.description),
+            entry.created_time.to_string(),
+        ];
+        csv.push_str(&fields.join(","));
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+/// `value` quoted when it holds a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Records of `content` with the line each starts on. Quoted fields may hold
+/// separators, doubled quotes and line breaks; blank lines are left out.
+fn csv_records(content: &str) -> Vec<(usize, Result<Vec<String>, String>)> {
+    let mut records = Vec::new();
+    let mut chars = content.chars().peekable();
+    let mut line = 1;
+    while chars.peek().is_some() {
+        let start = line;
+        let mut fields = Vec::new();
+        let mut field = String::new();
+        let mut quoted = false;
+        let mut error = None;
+        loop {
+            match chars.next() {
+                None if quoted => {
+                    error = Some("unterminated quoted field".to_string());
+                    break;
+                }
+                None => break,
+                Some('"') if quoted => {
+                    if chars.peek() == Some(&'"') {
+                        chars.next();
+                        field.push('"');
+                    } else {
+                        quoted = false;
+                    }
+                }
+                Some('"') if field.is_empty() => quoted = true,
+                Some(c) if quoted => {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    field.push(c);
+                }
+                Some(',') => fields.push(std::mem::take(&mut field)),
+                Some('\r') if chars.peek() == Some(&'\n') => {}
+                Some('\n') => {
+                    line += 1;
+                    break;
+                }
+                Some(c) => field.push(c),
+            }
+        }
+        fields.push(field);
+        if error.is_none() && fields.len() == 1 && fields[0].trim().is_empty() {
+            continue;
+        }
+        records.push((start, error.map_or(Ok(fields), Err)));
+    }
+    records
+}
+
+fn from_csv(content: &str) -> Result<Vec<ImportRow>, String> {
+    let mut records = csv_records(content.trim_start_matches('\u{feff}')).into_iter();
+    let header = match records.next() {
+        Some((_, Ok(header))) => header,
+        Some((_, Err(e))) => return Err(format!("Failed to parse address book header: {}", e)),
+        None => return Ok(Vec::new()),
+    };
+    let column = |name: &str| header.iter().position(|h| h.trim().eq_ignore_ascii_case(name));
+    let address_column = column("address").ok_or("Address book file has no address column")?;
+    let (label_column, description_column, created_column) = (column("label"), column("description"), column("created_time"));
+
+    Ok(records
+        .map(|(line, record)| {
+            let entry = record.and_then(|fields| {
+                if fields.len() != header.len() {
+                    return Err(format!("expected {} fields, found {}", header.len(), fields.len()));
+                }
+                let get = |index: Option<usize>| index.map(|i| fields[i].clone()).unwrap_or_default();
+                let created = get(created_column);
+                let created_time = if created.trim().is_empty() {
+                    0
+                } else {
+                    created.trim().parse().map_err(|_| format!("invalid created_time {:?}", created))?
+                };
+                Ok(AddressBookEntry {
+                    address: fields[address_column].clone(),
+                    label: get(label_column),
+                    description: get(description_column),
+                    created_time,
+                    last_used_time: 0,
+                    use_count: 0,
+                })
+            });
+            ImportRow { line, entry }
+        })
+        .collect())
+}
+
+/// Pick the rows of an import to write to the wallet.
+///
+/// Rows that could not be read or whose address fails `is_valid` are
+/// reported invalid with their line. Rows repeating an earlier address of the
+/// file are skipped. Addresses already in `existing` are skipped or updated
+/// according to `strategy`; with `MergeStrategy::Replace` the caller clears the
+/// address book first, so they are added again.
+pub fn plan_import<F>(
+    existing: &[AddressBookEntry],
+    rows: Vec<ImportRow>,
+    strategy: MergeStrategy,
+    is_valid: F,
+) -> (ImportPlan, AddressBookImportReport)
+where
+    F: Fn(&str) -> bool,
+{
+    let known: HashSet<&str> = if strategy == MergeStrategy::Replace {
+        HashSet::new()
+    } else {
+        existing.iter().map(|e| e.address.as_str()).collect()
+    };
+    let mut seen = HashSet::new();
+    let mut report = AddressBookImportReport::default();
+    let mut plan = ImportPlan::default();
+
+    for ImportRow { line, entry } in rows {
+        let mut entry = match entry {
+            Ok(entry) => entry,
+            Err(reason) => {
+                report.invalid.push(InvalidRow { line, reason });
+                continue;
+            }
+        };
+        entry.address = entry.address.trim().to_string();
+        if !is_valid(&entry.address) {
+            let reason = format!("invalid address {:?}", entry.address);
+            report.invalid.push(InvalidRow { line, reason });
+        } else if !seen.insert(entry.address.clone()) {
+            report.skipped += 1;
+        } else if !known.contains(entry.address.as_str()) {
+            report.imported += 1;
+            plan.add.push((line, entry));
+        } else if strategy == MergeStrategy::Overwrite {
+            report.imported += 1;
+            plan.update.push((line, entry));
+        } else {
+            report.skipped += 1;
+        }
+    }
+
+    (plan, report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::testing::TempDir;
+
+    fn entry(address: &str, label: &str) -> AddressBookEntry {
+        AddressBookEntry {
+            address: address.to_string(),
+            label: label.to_string(),
+            description: String::new(),
+            created_time: 1,
+            last_used_time: 0,
+            use_count: 0,
+        }
+    }
+
+    fn is_valid(address: &str) -> bool {
+        address.starts_with("fire")
+    }
+
+    fn rows(entries: Vec<AddressBookEntry>) -> Vec<ImportRow> {
+        entries.into_iter().enumerate().map(|(i, entry)| ImportRow { line: i + 1, entry: Ok(entry) }).collect()
+    }
+
+    #[test]
+    fn test_export_round_trip() {
+        let dir = TempDir::new("fuego_addressbook");
+        let path = dir.join("contacts.json");
+
+        export_address_book(&path, vec![entry("fire1alice", "Alice"), entry("fire1bob", "Bob")], AddressBookFormat::Json).unwrap();
+        let entries = read_address_book(&path, AddressBookFormat::Json).unwrap();
+        assert_eq!(entries.iter().map(|r| r.entry.as_ref().unwrap().label.as_str()).collect::<Vec<_>>(), vec!["Alice", "Bob"]);
+
+        fs::write(&path, r#"{"version": 9, "exported_at": 0, "entries": []}"#).unwrap();
+        assert!(read_address_book(&path, AddressBookFormat::Json).unwrap_err().contains("newer than supported"));
+
+        // Labels and descriptions with separators, quotes and line breaks survive CSV
+        let csv_path = dir.join("contacts.csv");
+        let mut tricky = entry("fire1carol", "Carol, \"the\" accountant");
+        tricky.description = "Invoices\nmonthly".to_string();
+        tricky.created_time = 1_700_000_000;
+        let exported = vec![entry("fire1alice", "Alice"), tricky];
+        export_address_book(&csv_path, exported.clone(), AddressBookFormat::Csv).unwrap();
+        let imported: Vec<AddressBookEntry> = read_address_book(&csv_path, AddressBookFormat::Csv).unwrap()
+            .into_iter()
+            .map(|r| r.entry.unwrap())
+            .collect();
+        assert_eq!(imported, exported);
+    }
+
+    #[test]
+    fn test_malformed_csv_rows_are_reported_by_line() {
+        let csv = "address,label,created_time\n\
+                   fire1alice,\"Smith, Alice\",5\n\
+                   fire1bob,Bob\n\
+                   \n\
+                   fire1carol,Carol,yesterday\n\
+                   XFGnotanaddress,Mallory,\n\
+                   fire1dave,\"Dave";
+        let rows = from_csv(csv).unwrap();
+        assert_eq!(rows.iter().map(|r| r.line).collect::<Vec<_>>(), vec![2, 3, 5, 6, 7]);
+        assert_eq!(rows[0].entry.as_ref().unwrap().label, "Smith, Alice");
+
+        let (plan, report) = plan_import(&[], rows, MergeStrategy::Skip, is_valid);
+        assert_eq!(plan.add.iter().map(|(line, e)| (*line, e.address.as_str())).collect::<Vec<_>>(), vec![(2, "fire1alice")]);
+        assert_eq!(report.imported, 1);
+        assert_eq!(report.invalid, vec![
+            InvalidRow { line: 3, reason: "expected 3 fields, found 2".to_string() },
+            InvalidRow { line: 5, reason: "invalid created_time \"yesterday\"".to_string() },
+            InvalidRow { line: 6, reason: "invalid address \"XFGnotanaddress\"".to_string() },
+            InvalidRow { line: 7, reason: "unterminated quoted field".to_string() },
+        ]);
+        assert!(from_csv("label,name\nAlice,alice\n").unwrap_err().contains("no address column"));
+    }
+
+    #[test]
+    fn test_import_merge_strategies() {
+        let existing = vec![entry("fire1alice", "Alice")];
+        let incoming = || rows(vec![
+            entry("fire1alice", "Alice again"),
+            entry(" fire1bob ", "Bob"),
+            entry("fire1bob", "Bob twice"),
+            entry("XFGnotanaddress", "Mallory"),
+        ]);
+        let labels = |entries: &[(usize, AddressBookEntry)]| entries.iter().map(|(_, e)| e.label.clone()).collect::<Vec<_>>();
+
+        let (plan, report) = plan_import(&existing, incoming(), MergeStrategy::Skip, is_valid);
+        assert_eq!(labels(&plan.add), vec!["Bob"]);
+        assert!(plan.update.is_empty());
+        assert_eq!(report, AddressBookImportReport {
+            imported: 1,
+            skipped: 2,
+            invalid: vec![InvalidRow { line: 4, reason: "invalid address \"XFGnotanaddress\"".to_string() }],
+        });
+
+        let (plan, report) = plan_import(&existing, incoming(), MergeStrategy::Overwrite, is_valid);
+        assert_eq!((labels(&plan.add), labels(&plan.update)), (vec!["Bob".to_string()], vec!["Alice again".to_string()]));
+        assert_eq!((report.imported, report.skipped, report.invalid.len()), (2, 1, 1));
+
+        let (plan, report) = plan_import(&existing, incoming(), MergeStrategy::Replace, is_valid);
+        assert_eq!(labels(&plan.add), vec!["Alice again", "Bob"]);
+        assert_eq!((report.imported, report.skipped, report.invalid.len()), (2, 1, 1));
+    }
+}
@@ -0,0 +1,163 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Blocking wallet calls from async commands
+//!
+//! Every call into the native wallet blocks its thread until the library
+//! returns, which for a refresh or a connect to a slow node can take minutes.
+//! Commands hand such calls to `run`, which moves them onto tokio's blocking
+//! pool so the executor keeps serving other commands, and which gives up on a
+//! call that outlives its timeout with `WalletError::OperationTimeout` rather
+//! than leaving the frontend waiting forever. An abandoned call still runs to
+//! its end on the blocking thread; calls made of several steps, like trying
+//! one node after another, can check the cancel flag of `run_cancellable` and
+//! stop early.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use crate::utils::error::WalletError;
+
+/// Kinds of wallet operations, each with its own timeout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperationClass {
+    /// Balances, addresses, status and other reads
+    Read,
+    /// Connecting to a node
+    Connect,
+    /// Building and broadcasting a transaction
+    Send,
+    /// Refreshing and rescanning
+    Sync,
+}
+
+/// Seconds each class of operation may run before it is abandoned
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OperationTimeouts {
+    pub read_secs: u64,
+    pub connect_secs: u64,
+    pub send_secs: u64,
+    pub sync_secs: u64,
+}
+
+impl Default for OperationTimeouts {
+    fn default() -> Self {
+        Self {
+            read_secs: 30,
+            connect_secs: 90,
+            send_secs: 120,
+            sync_secs: 900,
+        }
+    }
+}
+
+impl OperationTimeouts {
+    /// Timeout of `class`, at least one second
+    pub fn for_class(&self, class: OperationClass) -> Duration {
+        let secs = match class {
+            OperationClass::Read => self.read_secs,
+            OperationClass::Connect => self.connect_secs,
+            OperationClass::Send => self.send_secs,
+            OperationClass::Sync => self.sync_secs,
+        };
+        Duration::from_secs(secs.max(1))
+    }
+}
+
+/// Sets the flag when dropped, i.e. once the caller stopped waiting
+struct CancelOnDrop(Arc<AtomicBool>);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Run the blocking `call` on the blocking pool, failing with
+/// `OperationTimeout` if it takes longer than `timeout`
+pub async fn run<T, E, F>(operation: &'static str, timeout: Duration, call: F) -> Result<T, E>
+where
+    T: Send + 'static,
+    E: From<WalletError> + Send + 'static,
+    F: FnOnce() -> Result<T, E> + Send + 'static,
+{
+    run_cancellable(operation, timeout, move |_| call()).await
+}
+
+/// `run`, passing `call` a flag that is set once nobody waits for the result
+/// any more: the timeout passed or the command was dropped
+pub async fn run_cancellable<T, E, F>(operation: &'static str, timeout: Duration, call: F) -> Result<T, E>
+where
+    T: Send + 'static,
+    E: From<WalletError> + Send + 'static,
+    F: FnOnce(&AtomicBool) -> Result<T, E> + Send + 'static,
+{
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let _cancel_on_drop = CancelOnDrop(cancelled.clone());
+    let task = tokio::task::spawn_blocking(move || call(&cancelled));
+
+    match tokio::time::timeout(timeout, task).await {
+        Ok(Ok(result)) => result,
+        Ok(Err(e)) => Err(WalletError::Generic(format!("{} task failed: {}", operation, e)).into()),
+        Err(_) => {
+            log::warn!("{} did not finish within {:?}, abandoning it", operation, timeout);
+            Err(WalletError::OperationTimeout {
+                operation: operation.to_string(),
+                timeout_ms: timeout.as_millis() as u64,
+            }
+            .into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+    use crate::utils::error::WalletResult;
+    use crate::crypto::backend::MockWalletBackend;
+    use crate::crypto::real_cryptonote::RealCryptoNoteWallet;
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_slow_call_does_not_block_fast_read() {
+        let backend = MockWalletBackend::with_balance(5_000);
+        backend.state().refresh_delay = Duration::from_millis(800);
+
+        let mut slow = RealCryptoNoteWallet::with_backend(Box::new(backend.clone()));
+        let fast = RealCryptoNoteWallet::with_backend(Box::new(backend));
+        let start = Instant::now();
+        let refresh = tokio::spawn(run("refresh", Duration::from_secs(10), move || -> WalletResult<()> { slow.refresh() }));
+        // Let the refresh get going before reading
+        tokio::task::yield_now().await;
+
+        let balance: WalletResult<u64> = run("get_balance", Duration::from_secs(10), move || fast.get_balance()).await;
+        assert_eq!(balance.unwrap(), 5_000);
+        assert!(start.elapsed() < Duration::from_millis(400), "balance read waited {:?}", start.elapsed());
+
+        refresh.await.unwrap().unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(800));
+    }
+
+    #[tokio::test]
+    async fn test_hung_call_times_out_and_is_cancelled() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let result: WalletResult<()> = run_cancellable("connect", Duration::from_millis(100), move |cancelled| {
+            while !cancelled.load(Ordering::SeqCst) {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            let _ = tx.send(());
+            Ok(())
+        })
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(WalletError::OperationTimeout { ref operation, timeout_ms: 100 }) if operation == "connect"
+        ));
+        // The abandoned call saw the flag and stopped
+        assert!(rx.recv_timeout(Duration::from_secs(2)).is_ok());
+        assert_eq!(OperationTimeouts { read_secs: 0, ..Default::default() }.for_class(OperationClass::Read), Duration::from_secs(1));
+    }
+}
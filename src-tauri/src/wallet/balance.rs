@@ -0,0 +1,417 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Locked balance breakdown
+//!
+//! Explains the part of the balance that cannot be spent yet by where it is
+//! locked: change and incoming transfers waiting for confirmations, term
+//! deposits, and mined outputs still maturing. Each locked output is matched to
+//! the transaction in its block; change of a send that is still pending has no
+//! block yet and is matched to the pending send instead.
+//!
+//! A wallet can also hold incoming transfers back for longer than the protocol
+//! does: with `min_confirmations` above `TX_SPENDABLE_AGE`, unlocked incoming
+//! outputs with fewer confirmations are left out of the spendable balance.
+
+use serde::{Deserialize, Serialize};
+use crate::crypto::daemon::TARGET_BLOCK_TIME;
+use crate::crypto::real_cryptonote::{DepositInfo, RealCryptoNoteWallet, TransactionInfo, UnspentOutput};
+use crate::utils::error::WalletResult;
+
+/// Confirmations before a transfer output can be spent, as `CRYPTONOTE_DEFAULT_TX_SPENDABLE_AGE`
+pub const TX_SPENDABLE_AGE: u64 = 10;
+/// Blocks before a mined output can be spent, as `CRYPTONOTE_MINED_MONEY_UNLOCK_WINDOW`
+pub const MINED_MONEY_UNLOCK_WINDOW: u64 = 60;
+/// Unlock times below this are block heights, from it on Unix times
+const MAX_BLOCK_NUMBER: u64 = 500_000_000;
+/// Transactions searched for the ones locked outputs came from
+const BREAKDOWN_HISTORY_WINDOW: u64 = 200;
+
+/// Why an amount is locked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LockSource {
+    /// Change of a send, until it has enough confirmations
+    Change,
+    /// A received transfer, until it has enough confirmations
+    Incoming,
+    /// A term deposit, until its term ends
+    Deposit,
+    /// A mined reward, until it matures
+    Coinbase,
+}
+
+/// One locked amount and when it unlocks
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LockedAmount {
+    pub source: LockSource,
+    pub amount: u64,
+    pub tx_hash: Option<String>,
+    pub deposit_id: Option<String>,
+    pub unlock_height: u64,
+    pub blocks_remaining: u64,
+    /// Seconds until unlock at the network block time
+    pub estimated_seconds_remaining: u64,
+}
+
+/// An unlocked incoming transfer still held back by the confirmation policy
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaturingTransfer {
+    pub tx_hash: Option<String>,
+    pub amount: u64,
+    pub block_height: u64,
+    pub confirmations: u64,
+    /// Blocks until it has `min_confirmations`
+    pub blocks_remaining: u64,
+}
+
+/// The unlocked balance less what the confirmation policy holds back
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpendableBalance {
+    pub spendable_balance: u64,
+    pub min_confirmations: u64,
+    pub maturing: Vec<MaturingTransfer>,
+}
+
+/// Confirmations required under the `min_confirmations` setting; never
+/// fewer than the protocol requires
+pub fn effective_min_confirmations(setting: Option<u64>) -> u64 {
+    setting.unwrap_or(TX_SPENDABLE_AGE).max(TX_SPENDABLE_AGE)
+}
+
+/// What of `unlocked_balance` can be spent when incoming transfers need
+/// `min_confirmations` at `current_height`. Confirmations are counted from
+/// the height each output was seen at, so a reorg that moved the tip below
+/// it leaves the output with none. Change of the wallet's own sends is not
+/// held back; an output is only known to be change when its transaction
+/// hash is reported, so outputs without one are held back like transfers.
+pub fn spendable_balance(
+    unlocked_balance: u64,
+    current_height: u64,
+    min_confirmations: u64,
+    outputs: &[UnspentOutput],
+    transactions: &[TransactionInfo],
+) -> SpendableBalance {
+    let mut spendable = SpendableBalance { spendable_balance: unlocked_balance, min_confirmations, maturing: Vec::new() };
+    if min_confirmations <= TX_SPENDABLE_AGE {
+        return spendable;
+    }
+    for output in outputs.iter().filter(|o| !o.is_locked && o.block_height > 0) {
+        let confirmations = current_height.saturating_sub(output.block_height);
+        if confirmations >= min_confirmations {
+            continue;
+        }
+        let tx = output.tx_hash.as_deref().and_then(|hash| transactions.iter().find(|tx| tx.hash == hash));
+        if tx.is_some_and(|tx| tx.amount < 0) {
+            continue;
+        }
+        spendable.maturing.push(MaturingTransfer {
+            tx_hash: output.tx_hash.clone(),
+            amount: output.amount,
+            block_height: output.block_height,
+            confirmations,
+            blocks_remaining: min_confirmations - confirmations,
+        });
+    }
+    let held: u64 = spendable.maturing.iter().map(|m| m.amount).sum();
+    spendable.spendable_balance = unlocked_balance.saturating_sub(held);
+    spendable
+}
+
+/// The locked part of the balance, by source
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BalanceBreakdown {
+    pub balance: u64,
+    pub unlocked_balance: u64,
+    /// `balance - unlocked_balance`
+    pub locked_balance: u64,
+    pub current_height: u64,
+    pub locked: Vec<LockedAmount>,
+    /// Locked balance the sources do not account for; negative when they
+    /// account for more than is locked. `None` when they sum exactly.
+    pub discrepancy: Option<i64>,
+    /// The unlocked balance under the confirmation policy
+    pub spendable_balance: u64,
+    pub min_confirmations: u64,
+    /// Unlocked transfers the confirmation policy still holds back
+    pub maturing: Vec<MaturingTransfer>,
+}
+
+/// What a breakdown is computed from
+#[derive(Debug, Clone, Copy)]
+pub struct BalanceSources<'a> {
+    pub balance: u64,
+    pub unlocked_balance: u64,
+    pub current_height: u64,
+    /// Unix time, for unlock times given as a time rather than a height
+    pub now: u64,
+    pub outputs: &'a [UnspentOutput],
+    pub transactions: &'a [TransactionInfo],
+    pub deposits: &'a [DepositInfo],
+    /// Confirmations incoming transfers need to be spendable
+    pub min_confirmations: u64,
+}
+
+impl BalanceBreakdown {
+    pub fn compute(sources: BalanceSources<'_>) -> Self {
+        let height = sources.current_height;
+        let locked_entry = |source, amount, tx_hash, deposit_id, unlock_height: u64| {
+            let blocks_remaining = unlock_height.saturating_sub(height);
+            LockedAmount {
+                source,
+                amount,
+                tx_hash,
+                deposit_id,
+                unlock_height,
+                blocks_remaining,
+                estimated_seconds_remaining: blocks_remaining * TARGET_BLOCK_TIME,
+            }
+        };
+
+        let mut pending_sends = sources.transactions.iter().filter(|tx| tx.is_pending && tx.amount < 0);
+        let mut locked = Vec::new();
+        for output in sources.outputs.iter().filter(|o| o.is_locked) {
+            let tx = sources.transactions.iter()
+                .find(|tx| tx.height > 0 && tx.height == output.block_height)
+                .or_else(|| pending_sends.next());
+            // Mined rewards have no sender and pay no fee
+            let coinbase = tx.is_some_and(|tx| tx.amount > 0 && tx.fee == 0 && tx.source_addresses.is_empty());
+            let source = match tx {
+                _ if coinbase => LockSource::Coinbase,
+                Some(tx) if tx.amount < 0 => LockSource::Change,
+                _ => LockSource::Incoming,
+            };
+            let window = if coinbase { MINED_MONEY_UNLOCK_WINDOW } else { TX_SPENDABLE_AGE };
+            // An output not in a block yet counts its confirmations from the next one
+            let block_height = if output.block_height == 0 { height } else { output.block_height };
+            let unlock_height = (block_height + window)
+                .max(tx.and_then(|tx| tx.unlock_time).map_or(0, |t| unlock_time_height(t, height, sources.now)));
+            locked.push(locked_entry(source, output.amount, tx.map(|tx| tx.hash.clone()), None, unlock_height));
+        }
+        for deposit in sources.deposits.iter().filter(|d| d.status != "spent" && d.unlock_height > height) {
+            locked.push(locked_entry(
+                LockSource::Deposit,
+                deposit.amount,
+                Some(deposit.creating_transaction_hash.clone()),
+                Some(deposit.id.clone()),
+                deposit.unlock_height,
+            ));
+        }
+
+        let locked_balance = sources.balance.saturating_sub(sources.unlocked_balance);
+        let explained: u64 = locked.iter().map(|l| l.amount).sum();
+        let discrepancy = (explained != locked_balance).then(|| locked_balance as i64 - explained as i64);
+        let spendable = spendable_balance(sources.unlocked_balance, height, sources.min_confirmations, sources.outputs, sources.transactions);
+        Self {
+            balance: sources.balance,
+            unlocked_balance: sources.unlocked_balance,
+            locked_balance,
+            current_height: height,
+            locked,
+            discrepancy,
+            spendable_balance: spendable.spendable_balance,
+            min_confirmations: spendable.min_confirmations,
+            maturing: spendable.maturing,
+        }
+    }
+
+    /// Breakdown of `wallet`'s balance at its sync height
+    pub fn load(wallet: &RealCryptoNoteWallet, now: u64, min_confirmations: u64) -> WalletResult<Self> {
+        let info = wallet.get_wallet_info()?;
+        let outputs = wallet.get_unspent_outputs()?;
+        let transactions = wallet.get_transaction_history(BREAKDOWN_HISTORY_WINDOW, 0)?;
+        let deposits = wallet.get_deposits()?;
+        Ok(Self::compute(BalanceSources {
+            balance: info.balance,
+            unlocked_balance: info.unlocked_balance,
+            current_height: info.sync_height,
+            now,
+            outputs: &outputs,
+            transactions: &transactions,
+            deposits: &deposits,
+            min_confirmations,
+        }))
+    }
+}
+
+/// `spendable_balance` of `wallet` at its sync height
+pub fn load_spendable_balance(wallet: &RealCryptoNoteWallet, min_confirmations: u64) -> WalletResult<SpendableBalance> {
+    let unlocked_balance = wallet.get_unlocked_balance()?;
+    if min_confirmations <= TX_SPENDABLE_AGE {
+        return Ok(spendable_balance(unlocked_balance, 0, min_confirmations, &[], &[]));
+    }
+    let height = wallet.get_wallet_info()?.sync_height;
+    let outputs = wallet.get_unspent_outputs()?;
+    let transactions = wallet.get_transaction_history(BREAKDOWN_HISTORY_WINDOW, 0)?;
+    Ok(spendable_balance(unlocked_balance, height, min_confirmations, &outputs, &transactions))
+}
+
+/// Height at which an unlock time, a height or a Unix time, has passed
+fn unlock_time_height(unlock_time: u64, height: u64, now: u64) -> u64 {
+    if unlock_time < MAX_BLOCK_NUMBER {
+        unlock_time
+    } else {
+        height + unlock_time.saturating_sub(now).div_ceil(TARGET_BLOCK_TIME)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(amount: u64, block_height: u64, is_locked: bool) -> UnspentOutput {
+        UnspentOutput {
+            amount,
+            global_index: block_height,
+            key_image: format!("{:064x}", block_height),
+            block_height,
+            is_locked,
+            tx_hash: None,
+        }
+    }
+
+    fn output_of(hash: &str, amount: u64, block_height: u64) -> UnspentOutput {
+        UnspentOutput { tx_hash: Some(hash.to_string()), ..output(amount, block_height, false) }
+    }
+
+    fn tx(hash: &str, amount: i64, fee: u64, height: u64, sources: &[&str]) -> TransactionInfo {
+        TransactionInfo {
+            id: hash.to_string(),
+            hash: hash.to_string(),
+            amount,
+            fee,
+            height,
+            timestamp: 0,
+            confirmations: 0,
+            is_confirmed: height > 0,
+            is_pending: height == 0,
+            payment_id: None,
+            destination_addresses: Vec::new(),
+            source_addresses: sources.iter().map(|s| s.to_string()).collect(),
+            unlock_time: None,
+            extra: None,
+        }
+    }
+
+    fn deposit(id: &str, amount: u64, unlock_height: u64, status: &str) -> DepositInfo {
+        DepositInfo {
+            id: id.to_string(),
+            amount,
+            interest: 0,
+            term: 5040,
+            rate: 0.0,
+            status: status.to_string(),
+            unlock_height,
+            unlock_time: None,
+            creating_transaction_hash: format!("tx_{}", id),
+            creating_height: 0,
+            creating_time: String::new(),
+            spending_transaction_hash: None,
+            spending_height: None,
+            spending_time: None,
+            deposit_type: "term".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_each_lock_source_is_explained() {
+        let outputs = vec![
+            output(500, 1_000, false),
+            output(70, 995, true),   // change of a confirmed send
+            output(30, 0, true),     // change of a pending send
+            output(200, 998, true),  // incoming, unlocking at a set time
+            output(900, 990, true),  // mined
+        ];
+        let mut incoming = tx("in", 200, 10, 998, &["fire1sender"]);
+        incoming.unlock_time = Some(1_000_000_000 + 20 * TARGET_BLOCK_TIME);
+        let transactions = vec![
+            tx("sent", -100, 10, 995, &["fire1me"]),
+            tx("pending", -50, 10, 0, &["fire1me"]),
+            incoming,
+            tx("mined", 900, 0, 990, &[]),
+        ];
+        let deposits = vec![deposit("d1", 1_000, 1_500, "locked"), deposit("d2", 400, 900, "unlocked")];
+
+        let breakdown = BalanceBreakdown::compute(BalanceSources {
+            balance: 2_700,
+            unlocked_balance: 500,
+            current_height: 1_000,
+            now: 1_000_000_000,
+            outputs: &outputs,
+            transactions: &transactions,
+            deposits: &deposits,
+            min_confirmations: TX_SPENDABLE_AGE,
+        });
+
+        let summary: Vec<(LockSource, u64, Option<&str>, u64)> = breakdown.locked.iter()
+            .map(|l| (l.source, l.amount, l.tx_hash.as_deref(), l.blocks_remaining))
+            .collect();
+        assert_eq!(summary, vec![
+            (LockSource::Change, 70, Some("sent"), 5),
+            (LockSource::Change, 30, Some("pending"), 10),
+            (LockSource::Incoming, 200, Some("in"), 20),
+            (LockSource::Coinbase, 900, Some("mined"), 50),
+            (LockSource::Deposit, 1_000, Some("tx_d1"), 500),
+        ]);
+        assert_eq!(breakdown.locked[3].estimated_seconds_remaining, 50 * TARGET_BLOCK_TIME);
+        assert_eq!(breakdown.locked_balance, 2_200);
+        assert_eq!(breakdown.discrepancy, None);
+    }
+
+    #[test]
+    fn test_unexplained_locked_balance_is_flagged() {
+        let outputs = vec![output(100, 95, true)];
+        let transactions = vec![tx("in", 100, 10, 95, &["fire1sender"])];
+        let breakdown = BalanceBreakdown::compute(BalanceSources {
+            balance: 1_000,
+            unlocked_balance: 850,
+            current_height: 100,
+            now: 0,
+            outputs: &outputs,
+            transactions: &transactions,
+            deposits: &[],
+            min_confirmations: TX_SPENDABLE_AGE,
+        });
+        assert_eq!(breakdown.discrepancy, Some(50));
+    }
+
+    #[test]
+    fn test_min_confirmations_hold_back_recent_incoming() {
+        let outputs = vec![
+            output_of("old", 1_000, 900),  // old enough
+            output_of("recent", 300, 985), // incoming, 15 confirmations
+            output_of("sent", 200, 988),   // change of a send, 12 confirmations
+            output(50, 995, true),         // still locked by the protocol
+        ];
+        let transactions = vec![
+            tx("old", 1_000, 10, 900, &["fire1sender"]),
+            // A send in the same block does not make the incoming output change
+            tx("same_block", -20, 10, 985, &["fire1me"]),
+            tx("recent", 300, 10, 985, &["fire1sender"]),
+            tx("sent", -500, 10, 988, &["fire1me"]),
+            tx("new", 50, 10, 995, &["fire1sender"]),
+        ];
+        let at = |height, min| spendable_balance(1_500, height, min, &outputs, &transactions);
+
+        // The protocol default holds nothing back
+        assert_eq!(at(1_000, effective_min_confirmations(None)).spendable_balance, 1_500);
+        assert_eq!(effective_min_confirmations(Some(3)), TX_SPENDABLE_AGE);
+
+        let strict = at(1_000, 20);
+        assert_eq!(strict.spendable_balance, 1_200);
+        assert_eq!(strict.maturing, vec![MaturingTransfer {
+            tx_hash: Some("recent".to_string()),
+            amount: 300,
+            block_height: 985,
+            confirmations: 15,
+            blocks_remaining: 5,
+        }]);
+
+        // The setting raised mid-session takes the older output too
+        assert_eq!(at(1_000, 120).spendable_balance, 200);
+        // A reorg that moved the tip back leaves the transfer fewer confirmations
+        assert_eq!(at(980, 20).maturing.iter().map(|m| m.confirmations).collect::<Vec<_>>(), vec![0]);
+        assert_eq!(at(980, 20).spendable_balance, 1_200);
+        assert_eq!(at(1_005, 20).spendable_balance, 1_500);
+    }
+}
@@ -0,0 +1,316 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Coin control
+//!
+//! Lets the user choose which unspent outputs fund a send. A selection is
+//! checked here before it reaches the wallet library: every key image must
+//! belong to one of the wallet's unspent outputs, none of them may be locked
+//! or frozen, and together they must cover the amount and the fee.
+//!
+//! Frozen outputs are kept out of every send until they are unfrozen. The
+//! wallet library does not know about them, so a send without a selection
+//! picks its inputs here whenever some output is frozen. The frozen key images
+//! are kept next to the wallet as `<wallet>.frozen.json`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use crate::crypto::real_cryptonote::UnspentOutput;
+use crate::utils::locks::MutexExt;
+use crate::utils::storage::{load_json_or_default, save_json, sidecar_path};
+
+/// Unspent output as listed for coin control
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutputEntry {
+    #[serde(flatten)]
+    pub output: UnspentOutput,
+    /// Blocks since the output was created
+    pub age_in_blocks: u64,
+    /// Kept out of sends until unfrozen
+    pub is_frozen: bool,
+}
+
+/// List `outputs` with their age at `current_height` and whether they are in
+/// `frozen`, oldest first
+pub fn with_age(outputs: Vec<UnspentOutput>, current_height: u64, frozen: &HashSet<String>) -> Vec<OutputEntry> {
+    let mut entries: Vec<OutputEntry> = outputs.into_iter()
+        .map(|output| OutputEntry {
+            age_in_blocks: current_height.saturating_sub(output.block_height),
+            is_frozen: frozen.contains(&output.key_image),
+            output,
+        })
+        .collect();
+    entries.sort_by(|a, b| b.age_in_blocks.cmp(&a.age_in_blocks).then_with(|| a.output.global_index.cmp(&b.output.global_index)));
+    entries
+}
+
+/// Check that the outputs with `key_images` may fund a send of `amount` plus
+/// `fee`, and return them
+pub fn select_outputs(outputs: &[UnspentOutput], key_images: &[String], amount: u64, fee: u64) -> Result<Vec<UnspentOutput>, String> {
+    select_unfrozen_outputs(outputs, key_images, &HashSet::new(), amount, fee)
+}
+
+/// `select_outputs`, refusing the outputs in `frozen`
+pub fn select_unfrozen_outputs(
+    outputs: &[UnspentOutput],
+    key_images: &[String],
+    frozen: &HashSet<String>,
+    amount: u64,
+    fee: u64,
+) -> Result<Vec<UnspentOutput>, String> {
+    if key_images.is_empty() {
+        return Err("No outputs selected".to_string());
+    }
+
+    let mut seen = HashSet::new();
+    let mut selected = Vec::with_capacity(key_images.len());
+    for key_image in key_images {
+        if !seen.insert(key_image.as_str()) {
+            return Err(format!("Output selected twice: {}", key_image));
+        }
+        let output = outputs.iter().find(|o| o.key_image == *key_image)
+            .ok_or_else(|| format!("Unknown output: {}", key_image))?;
+        if output.is_locked {
+            return Err(format!("Output is still locked: {}", key_image));
+        }
+        if frozen.contains(key_image) {
+            return Err(format!("Output is frozen: {}", key_image));
+        }
+        selected.push(output.clone());
+    }
+
+    let available = selected.iter().fold(0u64, |sum, o| sum.saturating_add(o.amount));
+    let needed = amount.saturating_add(fee);
+    if available < needed {
+        return Err(format!(
+            "Selected outputs hold {} but {} is needed for the amount and fee",
+            available, needed
+        ));
+    }
+    Ok(selected)
+}
+
+/// Pick unlocked outputs outside `frozen` that cover `amount` plus `fee`,
+/// largest first so the transaction stays small, and return their key images
+pub fn auto_select(outputs: &[UnspentOutput], frozen: &HashSet<String>, amount: u64, fee: u64) -> Result<Vec<String>, String> {
+    let mut candidates: Vec<&UnspentOutput> = outputs.iter()
+        .filter(|o| !o.is_locked && !frozen.contains(&o.key_image))
+        .collect();
+    candidates.sort_by(|a, b| b.amount.cmp(&a.amount).then_with(|| a.global_index.cmp(&b.global_index)));
+
+    let needed = amount.saturating_add(fee);
+    let mut available = 0u64;
+    let mut selected = Vec::new();
+    for output in candidates {
+        if available >= needed {
+            break;
+        }
+        available = available.saturating_add(output.amount);
+        selected.push(output.key_image.clone());
+    }
+    if available < needed {
+        return Err(format!(
+            "Unlocked outputs that are not frozen hold {} but {} is needed for the amount and fee",
+            available, needed
+        ));
+    }
+    Ok(selected)
+}
+
+/// Persisted key images of the outputs the user froze
+#[derive(Debug)]
+pub struct FrozenOutputs {
+    path: PathBuf,
+    key_images: Mutex<BTreeSet<String>>,
+}
+
+impl FrozenOutputs {
+    /// Path of the frozen outputs of `wallet_path`
+    pub fn sidecar_path(wallet_path: &Path) -> PathBuf {
+        sidecar_path(wallet_path, ".frozen.json")
+    }
+
+    pub fn for_wallet(wallet_path: &Path) -> Result<Self, String> {
+        Self::open(Self::sidecar_path(wallet_path))
+    }
+
+    /// Load the frozen outputs at `path`; a missing file means none
+    pub fn open(path: PathBuf) -> Result<Self, String> {
+        let key_images = load_json_or_default(&path, "frozen outputs")?;
+        Ok(Self {
+            path,
+            key_images: Mutex::new(key_images),
+        })
+    }
+
+    pub fn key_images(&self) -> HashSet<String> {
+        self.key_images.lock_recover().iter().cloned().collect()
+    }
+
+    /// Freeze the output with `key_image`; returns false if it already was
+    pub fn freeze(&self, key_image: &str) -> Result<bool, String> {
+        let mut key_images = self.key_images.lock_recover();
+        if !key_images.insert(key_image.to_string()) {
+            return Ok(false);
+        }
+        self.save(&key_images)?;
+        Ok(true)
+    }
+
+    /// Unfreeze the output with `key_image`; returns false if it was not frozen
+    pub fn unfreeze(&self, key_image: &str) -> Result<bool, String> {
+        let mut key_images = self.key_images.lock_recover();
+        if !key_images.remove(key_image) {
+            return Ok(false);
+        }
+        self.save(&key_images)?;
+        Ok(true)
+    }
+
+    fn save(&self, key_images: &BTreeSet<String>) -> Result<(), String> {
+        save_json(&self.path, key_images, "frozen outputs")
+    }
+}
+
+/// Unlocked outputs worth less than `threshold`
+pub fn dust_outputs(outputs: &[UnspentOutput], threshold: u64) -> Vec<UnspentOutput> {
+    outputs.iter().filter(|o| !o.is_locked && o.amount < threshold).cloned().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::backend::MockWalletBackend;
+    use crate::crypto::real_cryptonote::RealCryptoNoteWallet;
+    use crate::utils::testing::TempDir;
+
+    fn output(index: u64, amount: u64, block_height: u64, is_locked: bool) -> UnspentOutput {
+        UnspentOutput {
+            amount,
+            global_index: index,
+            key_image: format!("ki{}", index),
+            block_height,
+            is_locked,
+            tx_hash: None,
+        }
+    }
+
+    fn key_images(images: &[&str]) -> Vec<String> {
+        images.iter().map(|k| k.to_string()).collect()
+    }
+
+    #[test]
+    fn test_selection_validation() {
+        let outputs = vec![output(1, 600, 10, false), output(2, 500, 20, false), output(3, 900, 95, true)];
+
+        let selected = select_outputs(&outputs, &key_images(&["ki1", "ki2"]), 1_000, 100).unwrap();
+        assert_eq!(selected.iter().map(|o| o.global_index).collect::<Vec<_>>(), vec![1, 2]);
+
+        assert!(select_outputs(&outputs, &key_images(&["ki1", "ki2"]), 1_000, 101).unwrap_err().contains("1101 is needed"));
+        assert!(select_outputs(&outputs, &key_images(&["ki1", "ki3"]), 100, 10).unwrap_err().contains("still locked"));
+        assert!(select_outputs(&outputs, &key_images(&["ki1", "nope"]), 100, 10).unwrap_err().contains("Unknown output"));
+        assert!(select_outputs(&outputs, &key_images(&["ki1", "ki1"]), 100, 10).unwrap_err().contains("twice"));
+        assert!(select_outputs(&outputs, &[], 100, 10).is_err());
+
+        let listed = with_age(outputs.clone(), 100, &HashSet::from(["ki2".to_string()]));
+        assert_eq!(listed.iter().map(|e| (e.output.global_index, e.age_in_blocks, e.is_frozen)).collect::<Vec<_>>(),
+            vec![(1, 90, false), (2, 80, true), (3, 5, false)]);
+        assert_eq!(dust_outputs(&outputs, 800), vec![outputs[0].clone(), outputs[1].clone()]);
+    }
+
+    #[test]
+    fn test_frozen_outputs_kept_out_of_sends() {
+        let dir = TempDir::new("fuego_frozen");
+        let path = dir.join("test.wallet.frozen.json");
+        let store = FrozenOutputs::open(path.clone()).unwrap();
+        assert!(store.freeze("ki1").unwrap());
+        assert!(!store.freeze("ki1").unwrap());
+        let frozen = FrozenOutputs::open(path).unwrap().key_images();
+        assert_eq!(frozen, HashSet::from(["ki1".to_string()]));
+
+        let outputs = vec![output(1, 5_000, 10, false), output(2, 600, 20, false), output(3, 500, 30, false), output(4, 9_000, 95, true)];
+        assert_eq!(auto_select(&outputs, &frozen, 1_000, 50).unwrap(), key_images(&["ki2", "ki3"]));
+        assert!(auto_select(&outputs, &frozen, 1_100, 50).unwrap_err().contains("1150 is needed"));
+        assert_eq!(auto_select(&outputs, &HashSet::new(), 1_000, 50).unwrap(), key_images(&["ki1"]));
+        assert!(select_unfrozen_outputs(&outputs, &key_images(&["ki1"]), &frozen, 100, 10).unwrap_err().contains("frozen"));
+
+        // A send without a selection leaves the frozen output alone
+        let backend = MockWalletBackend::with_balance(15_100);
+        backend.state().outputs = outputs.clone();
+        backend.state().fee = 50;
+        let wallet = RealCryptoNoteWallet::with_backend(Box::new(backend.clone()));
+        let recipient = "fire1jNwRRUYGENanfBwVhehZXVcQVFx3dH3D3Z7UNC17FePBr27DDwctyL2ePwDPz4fypwpNQpfXbp6wavubvSn6ToisC5NUy";
+        wallet.send_coin_controlled(recipient, 1_000, None, 0, None, &frozen).unwrap();
+        let left: Vec<String> = backend.state().outputs.iter().map(|o| o.key_image.clone()).collect();
+        assert!(left.contains(&"ki1".to_string()) && !left.contains(&"ki2".to_string()));
+        assert!(wallet.send_coin_controlled(recipient, 1_000, None, 0, None, &frozen).is_err());
+
+        assert!(store.unfreeze("ki1").unwrap());
+        assert!(store.key_images().is_empty());
+    }
+
+    #[test]
+    fn test_frozen_outputs_survive_batch_sends_and_sweeps() {
+        let recipient = "fire1jNwRRUYGENanfBwVhehZXVcQVFx3dH3D3Z7UNC17FePBr27DDwctyL2ePwDPz4fypwpNQpfXbp6wavubvSn6ToisC5NUy";
+        let wallet_with_outputs = || {
+            let backend = MockWalletBackend::with_balance(3_140_000);
+            backend.state().outputs = vec![
+                output(1, 3_000_000, 10, false),
+                output(2, 40_000, 12, false),
+                output(3, 60_000, 15, false),
+                output(4, 40_000, 16, false),
+            ];
+            backend.state().fee = 1_000;
+            let wallet = RealCryptoNoteWallet::with_backend(Box::new(backend.clone()));
+            (backend, wallet)
+        };
+        let unspent = |backend: &MockWalletBackend, key_image: &str| backend.state().outputs.iter().any(|o| o.key_image == key_image);
+        let frozen = HashSet::from(["ki1".to_string()]);
+
+        let (backend, wallet) = wallet_with_outputs();
+        let batch = [(recipient.to_string(), 50_000), (recipient.to_string(), 30_000)];
+        wallet.send_many(&batch, 5, &frozen).unwrap();
+        assert!(unspent(&backend, "ki1") && !unspent(&backend, "ki3"));
+        assert!(wallet.send_many(&[(recipient.to_string(), 200_000)], 5, &frozen).is_err());
+
+        let (backend, wallet) = wallet_with_outputs();
+        assert_eq!(wallet.estimate_sweep(recipient, 5, &frozen).unwrap().sendable_amount, 139_000);
+        let hash = wallet.sweep_all(recipient, 5, &frozen).unwrap();
+        assert_eq!(wallet.get_transaction_by_hash(&hash).unwrap().amount, -139_000);
+        assert!(unspent(&backend, "ki1"));
+
+        let (backend, wallet) = wallet_with_outputs();
+        wallet.sweep_below(100_000, 5, &HashSet::from(["ki2".to_string()])).unwrap();
+        assert!(unspent(&backend, "ki2") && !unspent(&backend, "ki3") && !unspent(&backend, "ki4"));
+    }
+
+    #[test]
+    fn test_sweep_below_consolidates_dust() {
+        let backend = MockWalletBackend::with_balance(3_100_000);
+        backend.state().outputs = vec![
+            output(1, 40_000, 10, false),
+            output(2, 60_000, 12, false),
+            output(3, 3_000_000, 15, false),
+        ];
+        let wallet = RealCryptoNoteWallet::with_backend(Box::new(backend.clone()));
+
+        assert!(wallet.sweep_below(50_000, 5, &HashSet::new()).unwrap_err().to_string().contains("nothing to consolidate"));
+        assert_eq!(wallet.estimate_sweep_below(100_000, 5, &HashSet::new()).unwrap().sendable_amount, 90_000);
+        wallet.sweep_below(100_000, 5, &HashSet::new()).unwrap();
+
+        let outputs = wallet.get_unspent_outputs().unwrap();
+        assert_eq!(outputs.len(), 2);
+        assert_eq!((outputs[1].amount, outputs[1].is_locked), (90_000, true));
+        assert_eq!(wallet.get_balance().unwrap(), 3_090_000);
+        assert_eq!(wallet.get_unlocked_balance().unwrap(), 3_000_000);
+
+        // Spending from an output that is gone, or still locked, never reaches the backend
+        let recipient = "fire1jNwRRUYGENanfBwVhehZXVcQVFx3dH3D3Z7UNC17FePBr27DDwctyL2ePwDPz4fypwpNQpfXbp6wavubvSn6ToisC5NUy";
+        let locked = outputs[1].key_image.clone();
+        assert!(wallet.send_from_outputs(recipient, 1_000, None, 5, &key_images(&["ki1"])).is_err());
+        assert!(wallet.send_from_outputs(recipient, 1_000, None, 5, &[locked]).is_err());
+        assert_eq!(backend.state().transactions.len(), 1);
+    }
+}
@@ -0,0 +1,140 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Wallet lock
+//!
+//! Whether the wallet may spend, independent of the security session. A locked
+//! wallet still shows balance, history and addresses, but sending and deposit
+//! operations are refused until the password is entered again.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use crate::utils::error::WalletError;
+use crate::utils::locks::MutexExt;
+
+/// Why the wallet was locked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LockReason {
+    Manual,
+    Idle,
+}
+
+#[derive(Debug)]
+struct LockState {
+    locked: Option<LockReason>,
+    last_activity: Instant,
+}
+
+/// Spend lock shared by all wallet commands
+#[derive(Debug)]
+pub struct WalletLock {
+    state: Mutex<LockState>,
+}
+
+impl WalletLock {
+    /// Create an unlocked wallet lock
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(LockState {
+                locked: None,
+                last_activity: Instant::now(),
+            }),
+        }
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.state.lock_recover().locked.is_some()
+    }
+
+    /// Lock the wallet; returns false if it was already locked
+    pub fn lock(&self, reason: LockReason) -> bool {
+        let mut state = self.state.lock_recover();
+        if state.locked.is_some() {
+            return false;
+        }
+        state.locked = Some(reason);
+        true
+    }
+
+    /// Unlock after `verify` accepted the password
+    pub fn unlock<F>(&self, password: &str, verify: F) -> Result<(), WalletError>
+    where
+        F: FnOnce(&str) -> bool,
+    {
+        if !verify(password) {
+            return Err(WalletError::InvalidPassword);
+        }
+        let mut state = self.state.lock_recover();
+        state.locked = None;
+        state.last_activity = Instant::now();
+        Ok(())
+    }
+
+    /// Record user activity, postponing the idle lock
+    pub fn touch(&self) {
+        self.state.lock_recover().last_activity = Instant::now();
+    }
+
+    /// Fail with `WalletLocked` unless the wallet may spend; counts as activity
+    pub fn ensure_unlocked(&self) -> Result<(), WalletError> {
+        let mut state = self.state.lock_recover();
+        if state.locked.is_some() {
+            return Err(WalletError::WalletLocked);
+        }
+        state.last_activity = Instant::now();
+        Ok(())
+    }
+
+    /// Lock the wallet if it has been idle for `timeout`; returns whether it was locked now
+    pub fn lock_if_idle(&self, timeout: Duration) -> bool {
+        let mut state = self.state.lock_recover();
+        if state.locked.is_some() || state.last_activity.elapsed() < timeout {
+            return false;
+        }
+        state.locked = Some(LockReason::Idle);
+        true
+    }
+}
+
+impl Default for WalletLock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locked_wallet_refuses_spending_until_unlocked() {
+        let lock = WalletLock::new();
+        assert!(lock.ensure_unlocked().is_ok());
+
+        assert!(lock.lock(LockReason::Manual));
+        assert!(!lock.lock(LockReason::Manual));
+        assert!(matches!(lock.ensure_unlocked(), Err(WalletError::WalletLocked)));
+
+        assert!(matches!(lock.unlock("wrong", |p| p == "secret"), Err(WalletError::InvalidPassword)));
+        assert!(lock.is_locked());
+        lock.unlock("secret", |p| p == "secret").unwrap();
+        assert!(lock.ensure_unlocked().is_ok());
+    }
+
+    #[test]
+    fn test_idle_wallet_is_locked() {
+        let lock = WalletLock::new();
+        assert!(!lock.lock_if_idle(Duration::from_secs(60)));
+
+        std::thread::sleep(Duration::from_millis(20));
+        lock.touch();
+        assert!(!lock.lock_if_idle(Duration::from_millis(15)));
+
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(lock.lock_if_idle(Duration::from_millis(15)));
+        assert!(!lock.lock_if_idle(Duration::from_millis(15)));
+        assert!(lock.is_locked());
+    }
+}
@@ -0,0 +1,195 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Mempool monitoring
+//!
+//! Incoming payments only reach the history once they are in a block. The
+//! watcher follows the wallet's incoming transfers in the daemon's transaction
+//! pool so they can be shown as pending right away, and works out what became
+//! of them once they leave the pool: mined into a block, or evicted.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use crate::advanced::{AdvancedTransactionInfo, AdvancedWalletManager};
+use crate::crypto::real_cryptonote::PoolTransfer;
+use crate::utils::locks::MutexExt;
+use super::pending::Confirmation;
+
+/// Background task polling the transaction pool
+pub const MEMPOOL_TASK: &str = "mempool_poll";
+
+/// Event emitted for each incoming transfer first seen in the pool
+pub const PENDING_INCOMING_EVENT: &str = "wallet://pending-incoming";
+
+/// What changed in the pool since the previous poll
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MempoolChanges {
+    /// Incoming transfers that entered the pool
+    pub arrived: Vec<PoolTransfer>,
+    /// Tracked transfers the daemon now reports a double spend for
+    pub conflicted: Vec<PoolTransfer>,
+    /// Transfers that left the pool because they were mined
+    pub confirmed: Vec<(String, Confirmation)>,
+    /// Transfers that left the pool without reaching a block
+    pub evicted: Vec<String>,
+}
+
+impl MempoolChanges {
+    pub fn is_empty(&self) -> bool {
+        self.arrived.is_empty() && self.conflicted.is_empty() && self.confirmed.is_empty() && self.evicted.is_empty()
+    }
+
+    /// Reflect the changes in the manager's transaction list
+    pub fn apply_to(&self, manager: &AdvancedWalletManager) {
+        for transfer in self.arrived.iter().chain(&self.conflicted) {
+            manager.upsert_transaction(AdvancedTransactionInfo::pending_receive(
+                &transfer.hash,
+                transfer.amount.max(0) as u64,
+                transfer.payment_id.clone(),
+                transfer.timestamp,
+                transfer.double_spend_seen,
+            ));
+        }
+        for (hash, confirmation) in &self.confirmed {
+            manager.confirm_transaction(hash, confirmation.height, confirmation.confirmations);
+        }
+        for hash in &self.evicted {
+            manager.remove_transaction(hash);
+        }
+    }
+}
+
+/// Incoming transfers currently in the pool
+#[derive(Debug, Default)]
+pub struct MempoolWatcher {
+    tracked: Mutex<HashMap<String, PoolTransfer>>,
+}
+
+impl MempoolWatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Transfers seen in the pool at the last update
+    pub fn tracked(&self) -> Vec<PoolTransfer> {
+        let mut tracked: Vec<PoolTransfer> = self.tracked.lock_recover().values().cloned().collect();
+        tracked.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then_with(|| a.hash.cmp(&b.hash)));
+        tracked
+    }
+
+    /// Compare the wallet's transfers in the pool with the previous update.
+    ///
+    /// Outgoing transfers are left to the pending transaction tracker. For
+    /// tracked transfers that are gone from the pool, `lookup` returns the
+    /// confirmation of those the wallet has seen in a block; the rest were evicted.
+    pub fn update<F>(&self, pool: Vec<PoolTransfer>, lookup: F) -> MempoolChanges
+    where
+        F: Fn(&str) -> Option<Confirmation>,
+    {
+        let mut changes = MempoolChanges::default();
+        let incoming: HashMap<String, PoolTransfer> = pool.into_iter()
+            .filter(|t| t.amount > 0)
+            .map(|t| (t.hash.clone(), t))
+            .collect();
+
+        let gone: Vec<String> = {
+            let mut tracked = self.tracked.lock_recover();
+            for (hash, transfer) in &incoming {
+                match tracked.get(hash) {
+                    None => changes.arrived.push(transfer.clone()),
+                    Some(previous) if transfer.double_spend_seen && !previous.double_spend_seen => {
+                        changes.conflicted.push(transfer.clone());
+                    }
+                    Some(_) => {}
+                }
+            }
+            let gone = tracked.keys().filter(|hash| !incoming.contains_key(*hash)).cloned().collect();
+            *tracked = incoming;
+            gone
+        };
+
+        // Look up without holding the lock; the wallet call can be slow
+        for hash in gone {
+            match lookup(&hash) {
+                Some(confirmation) => changes.confirmed.push((hash, confirmation)),
+                None => changes.evicted.push(hash),
+            }
+        }
+
+        changes.arrived.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then_with(|| a.hash.cmp(&b.hash)));
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transfer(hash: &str, amount: i64) -> PoolTransfer {
+        PoolTransfer {
+            hash: hash.to_string(),
+            amount,
+            payment_id: None,
+            timestamp: 1_700_000_000,
+            double_spend_seen: false,
+        }
+    }
+
+    fn find(manager: &AdvancedWalletManager, hash: &str) -> Option<AdvancedTransactionInfo> {
+        manager.get_advanced_transactions().into_iter().find(|t| t.hash == hash)
+    }
+
+    #[test]
+    fn test_pending_incoming_is_confirmed() {
+        let watcher = MempoolWatcher::new();
+        let manager = AdvancedWalletManager::new();
+
+        let changes = watcher.update(vec![transfer("in", 500), transfer("out", -200)], |_| None);
+        assert_eq!(changes.arrived, vec![transfer("in", 500)]);
+        changes.apply_to(&manager);
+        let pending = find(&manager, "in").unwrap();
+        assert!(pending.is_pending && !pending.is_confirmed);
+        assert_eq!((pending.amount, pending.confirmations), (500, 0));
+        assert!(find(&manager, "out").is_none());
+
+        // Still in the pool: nothing to report
+        assert!(watcher.update(vec![transfer("in", 500)], |_| None).is_empty());
+
+        let mined = Confirmation { height: 1_000, confirmations: 1 };
+        let changes = watcher.update(Vec::new(), |hash| (hash == "in").then_some(mined));
+        assert_eq!(changes.confirmed, vec![("in".to_string(), mined)]);
+        changes.apply_to(&manager);
+        let confirmed = find(&manager, "in").unwrap();
+        assert!(confirmed.is_confirmed && !confirmed.is_pending);
+        assert_eq!(confirmed.height, 1_000);
+        assert!(watcher.tracked().is_empty());
+    }
+
+    #[test]
+    fn test_pending_incoming_is_evicted() {
+        let watcher = MempoolWatcher::new();
+        let manager = AdvancedWalletManager::new();
+        watcher.update(vec![transfer("in", 500)], |_| None).apply_to(&manager);
+        assert!(find(&manager, "in").is_some());
+
+        let changes = watcher.update(Vec::new(), |_| None);
+        assert_eq!(changes.evicted, vec!["in".to_string()]);
+        changes.apply_to(&manager);
+        assert!(find(&manager, "in").is_none());
+    }
+
+    #[test]
+    fn test_double_spend_is_flagged() {
+        let watcher = MempoolWatcher::new();
+        let manager = AdvancedWalletManager::new();
+        watcher.update(vec![transfer("in", 500)], |_| None).apply_to(&manager);
+
+        let mut conflicted = transfer("in", 500);
+        conflicted.double_spend_seen = true;
+        let changes = watcher.update(vec![conflicted.clone()], |_| None);
+        assert_eq!(changes.conflicted, vec![conflicted]);
+        changes.apply_to(&manager);
+        assert!(find(&manager, "in").unwrap().double_spend_seen);
+        assert_eq!(manager.get_advanced_transactions().len(), 1);
+    }
+}
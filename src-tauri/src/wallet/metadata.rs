@@ -0,0 +1,105 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Wallet metadata sidecar
+//!
+//! Small facts about a wallet that the native wallet file does not record,
+//! stored next to it as `<wallet>.meta.json`.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::utils::storage::{save_json, sidecar_path};
+
+/// Metadata persisted next to a wallet file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WalletMetadata {
+    #[serde(default)]
+    pub created_at: Option<u64>,
+    #[serde(default)]
+    pub last_backup_at: Option<u64>,
+}
+
+impl WalletMetadata {
+    /// Path of the sidecar file for `wallet_path`
+    pub fn sidecar_path(wallet_path: &Path) -> PathBuf {
+        sidecar_path(wallet_path, ".meta.json")
+    }
+
+    /// Load the metadata of a wallet; a missing or unreadable sidecar yields empty metadata
+    pub fn load(wallet_path: &Path) -> Self {
+        fs::read_to_string(Self::sidecar_path(wallet_path))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Persist the metadata of a wallet
+    pub fn save(&self, wallet_path: &Path) -> Result<(), String> {
+        save_json(&Self::sidecar_path(wallet_path), self, "wallet metadata")
+    }
+
+    /// Record the creation time of a newly created wallet
+    pub fn record_creation(wallet_path: &Path) -> Result<(), String> {
+        let mut metadata = Self::load(wallet_path);
+        metadata.created_at = Some(now());
+        metadata.last_backup_at = None;
+        metadata.save(wallet_path)
+    }
+
+    /// Record that the wallet was just backed up
+    pub fn record_backup(wallet_path: &Path) -> Result<(), String> {
+        let mut metadata = Self::load(wallet_path);
+        metadata.last_backup_at = Some(now());
+        metadata.save(wallet_path)
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::testing::TempDir;
+
+    fn temp_wallet() -> (TempDir, PathBuf) {
+        let dir = TempDir::new("fuego_meta");
+        let wallet = dir.join("test.wallet");
+        (dir, wallet)
+    }
+
+    #[test]
+    fn test_missing_sidecar_is_empty() {
+        let (_dir, wallet) = temp_wallet();
+        assert_eq!(WalletMetadata::load(&wallet), WalletMetadata::default());
+    }
+
+    #[test]
+    fn test_creation_and_backup_are_recorded() {
+        let (_dir, wallet) = temp_wallet();
+        WalletMetadata::record_creation(&wallet).unwrap();
+        assert!(WalletMetadata::sidecar_path(&wallet).ends_with("test.wallet.meta.json"));
+
+        let created = WalletMetadata::load(&wallet);
+        assert!(created.created_at.is_some());
+        assert!(created.last_backup_at.is_none());
+
+        WalletMetadata::record_backup(&wallet).unwrap();
+        let backed_up = WalletMetadata::load(&wallet);
+        assert_eq!(backed_up.created_at, created.created_at);
+        assert!(backed_up.last_backup_at.is_some());
+    }
+
+    #[test]
+    fn test_corrupt_sidecar_degrades_to_empty() {
+        let (_dir, wallet) = temp_wallet();
+        fs::write(WalletMetadata::sidecar_path(&wallet), b"not json").unwrap();
+        assert_eq!(WalletMetadata::load(&wallet), WalletMetadata::default());
+    }
+}
@@ -0,0 +1,40 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Wallet helpers
+//! 
+//! This module contains state kept alongside wallet files and coordination
+//! of wallet operations shared by several commands.
+
+pub mod addressbook;
+pub mod async_wallet;
+pub mod balance;
+pub mod coin_control;
+pub mod key_backup;
+pub mod lock;
+pub mod mempool;
+pub mod metadata;
+pub mod payment_requests;
+pub mod payment_uri;
+pub mod paper;
+pub mod pending;
+pub mod refresh;
+pub mod registry;
+pub mod sync;
+pub mod watchdog;
+pub mod wipe;
+
+pub use addressbook::AddressBookImportReport;
+pub use balance::{BalanceBreakdown, SpendableBalance};
+pub use coin_control::OutputEntry;
+pub use lock::{LockReason, WalletLock};
+pub use mempool::{MempoolChanges, MempoolWatcher};
+pub use metadata::WalletMetadata;
+pub use payment_requests::{PaymentRequest, PaymentRequestStatus, PaymentRequests};
+pub use payment_uri::PaymentUri;
+pub use pending::{PendingTx, PendingTxStatus, PendingTxTracker};
+pub use refresh::RefreshCoordinator;
+pub use registry::{OpenWallet, RegisteredWallet, WalletRegistry, WalletSyncState};
+pub use sync::{DriftAlert, SyncDrift, SyncDriftMonitor, SyncLoopControl};
+pub use watchdog::{ConnectionWatchdog, WatchdogEvent};
+pub use wipe::{WipeManifest, WipePlan};
@@ -0,0 +1,149 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Paper wallets
+//!
+//! A printable bundle of the public address, a QR code of its `fuego:` URI
+//! and the seed phrase in a `WalletEncryption` envelope. The plain seed is
+//! never part of the bundle: restoring from paper takes the password the
+//! bundle was exported with.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use crate::security::WalletEncryption;
+use crate::utils::error::{WalletError, WalletResult};
+use crate::utils::qr::QrCode;
+use crate::utils::storage::atomic_write;
+use super::payment_uri::PaymentUri;
+
+/// Format of the bundle, for readers of older or newer ones
+pub const PAPER_WALLET_VERSION: u32 = 1;
+
+/// What each printed field is
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaperWalletLabels {
+    pub title: String,
+    pub address: String,
+    pub qr_payload: String,
+    pub encrypted_seed: String,
+    pub instructions: String,
+}
+
+impl Default for PaperWalletLabels {
+    fn default() -> Self {
+        Self {
+            title: "Fuego (XFG) paper wallet".to_string(),
+            address: "Public address: share this to receive XFG".to_string(),
+            qr_payload: "Address QR code".to_string(),
+            encrypted_seed: "Encrypted seed phrase: keep this private".to_string(),
+            instructions: "To restore, import this bundle in the wallet with the password it was exported with. \
+                Without the password the seed cannot be recovered."
+                .to_string(),
+        }
+    }
+}
+
+/// A paper wallet as exported
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaperWallet {
+    pub version: u32,
+    pub address: String,
+    /// `fuego:` URI of the address, as encoded in the QR code
+    pub qr_payload: String,
+    /// Seed phrase in a `WalletEncryption` envelope
+    pub encrypted_seed: String,
+    /// Unix time of the export
+    pub created_at: u64,
+    #[serde(default)]
+    pub labels: PaperWalletLabels,
+}
+
+impl PaperWallet {
+    /// Bundle `address` with `seed_phrase` encrypted under `password`
+    pub fn new(address: &str, seed_phrase: &str, password: &str, created_at: u64) -> Result<Self, String> {
+        if password.is_empty() {
+            return Err("A paper wallet needs a password".to_string());
+        }
+        Ok(Self {
+            version: PAPER_WALLET_VERSION,
+            address: address.to_string(),
+            qr_payload: PaymentUri::new(address).to_uri(),
+            encrypted_seed: WalletEncryption::encrypt_data(seed_phrase, password)?,
+            created_at,
+            labels: PaperWalletLabels::default(),
+        })
+    }
+
+    /// Read an exported bundle
+    pub fn parse(blob: &str) -> Result<Self, String> {
+        let paper: Self = serde_json::from_str(blob.trim()).map_err(|e| format!("Not a paper wallet: {}", e))?;
+        if paper.version > PAPER_WALLET_VERSION {
+            return Err(format!("Paper wallet version {} is newer than this wallet supports", paper.version));
+        }
+        Ok(paper)
+    }
+
+    /// The seed phrase; a wrong password is `InvalidPassword`
+    pub fn decrypt_seed(&self, password: &str) -> WalletResult<String> {
+        WalletEncryption::decrypt_data(&self.encrypted_seed, password).map_err(|_| WalletError::InvalidPassword)
+    }
+
+    /// A printable page with the address, its QR code and the encrypted seed
+    pub fn to_html(&self) -> Result<String, String> {
+        let qr = QrCode::encode(self.qr_payload.as_bytes())?;
+        let labels = &self.labels;
+        Ok(format!(
+            "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>{title}</title>\n\
+             <style>body{{font-family:sans-serif;max-width:48em;margin:2em auto}}\
+             .qr{{width:16em}}.mono{{font-family:monospace;word-break:break-all}}</style></head>\n\
+             <body><h1>{title}</h1>\n\
+             <h2>{address_label}</h2><p class=\"mono\">{address}</p>\n\
+             <h2>{qr_label}</h2><div class=\"qr\">{qr}</div>\n\
+             <h2>{seed_label}</h2><p class=\"mono\">{encrypted_seed}</p>\n\
+             <p>{instructions}</p></body></html>\n",
+            title = escape_html(&labels.title),
+            address_label = escape_html(&labels.address),
+            address = escape_html(&self.address),
+            qr_label = escape_html(&labels.qr_payload),
+            qr = qr.to_svg(),
+            seed_label = escape_html(&labels.encrypted_seed),
+            encrypted_seed = escape_html(&self.encrypted_seed),
+            instructions = escape_html(&labels.instructions),
+        ))
+    }
+
+    pub fn write_html(&self, path: &Path) -> Result<(), String> {
+        atomic_write(path, self.to_html()?.as_bytes())
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEED: &str = "abbey ability able about above absent absorb abstract absurd abuse access accident";
+
+    #[test]
+    fn test_paper_wallet_round_trip() {
+        let address = format!("fire{}", "A".repeat(94));
+        let paper = PaperWallet::new(&address, SEED, "correct horse", 1_700_000_000).unwrap();
+        assert_eq!(paper.qr_payload, format!("fuego:{}", address));
+
+        let blob = serde_json::to_string(&paper).unwrap();
+        assert!(!blob.contains("abbey"));
+        let restored = PaperWallet::parse(&blob).unwrap();
+        assert_eq!(restored, paper);
+        assert_eq!(restored.decrypt_seed("correct horse").unwrap(), SEED);
+        assert!(matches!(restored.decrypt_seed("wrong horse"), Err(WalletError::InvalidPassword)));
+
+        let html = paper.to_html().unwrap();
+        assert!(html.contains(&address) && html.contains("<svg"));
+        assert!(!html.contains("abbey"));
+        assert!(PaperWallet::new(&address, SEED, "", 0).is_err());
+        assert!(PaperWallet::parse("{\"address\":\"fire\"}").is_err());
+    }
+}
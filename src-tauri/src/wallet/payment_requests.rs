@@ -0,0 +1,297 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Payment requests
+//!
+//! A request asks for an amount under a payment ID of its own, so incoming
+//! transactions can be matched back to it. Requests and their status are kept
+//! next to the wallet as `<wallet>.requests.json`. Partial payments add up;
+//! a request is paid once the received total reaches the requested amount.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use crate::utils::storage::{load_json_or_default, save_json, sidecar_path};
+use crate::utils::locks::MutexExt;
+use super::payment_uri::PaymentUri;
+
+/// Background task expiring payment requests
+pub const PAYMENT_REQUEST_EXPIRY_TASK: &str = "payment_request_expiry";
+
+/// Event emitted when a payment request is paid, partially paid or expires
+pub const PAYMENT_REQUEST_UPDATED_EVENT: &str = "payment-request-updated";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum PaymentRequestStatus {
+    Pending,
+    PartiallyPaid { received_amount: u64 },
+    /// `tx_hash` is the transaction that completed the payment
+    Paid { tx_hash: String, received_amount: u64 },
+    Expired,
+    Cancelled,
+}
+
+/// A request for payment and what has been received for it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PaymentRequest {
+    pub id: String,
+    pub payment_id: String,
+    pub address: String,
+    /// Requested amount in atomic units
+    pub amount: u64,
+    pub description: String,
+    pub uri: String,
+    pub created_at: u64,
+    pub expires_at: Option<u64>,
+    pub status: PaymentRequestStatus,
+    /// Transactions counted towards this request
+    #[serde(default)]
+    pub tx_hashes: Vec<String>,
+}
+
+impl PaymentRequest {
+    /// Whether payments are still matched to this request. Expired requests
+    /// are, as funds sent late still arrived.
+    pub fn is_open(&self) -> bool {
+        matches!(
+            self.status,
+            PaymentRequestStatus::Pending | PaymentRequestStatus::PartiallyPaid { .. } | PaymentRequestStatus::Expired
+        )
+    }
+
+    pub fn received_amount(&self) -> u64 {
+        match self.status {
+            PaymentRequestStatus::PartiallyPaid { received_amount }
+            | PaymentRequestStatus::Paid { received_amount, .. } => received_amount,
+            _ => 0,
+        }
+    }
+
+    /// Count an incoming payment; returns false if it was already counted
+    pub fn apply_payment(&mut self, tx_hash: &str, amount: u64) -> bool {
+        if !self.is_open() || self.tx_hashes.iter().any(|h| h == tx_hash) {
+            return false;
+        }
+        let received_amount = self.received_amount().saturating_add(amount);
+        self.tx_hashes.push(tx_hash.to_string());
+        self.status = if received_amount >= self.amount {
+            PaymentRequestStatus::Paid { tx_hash: tx_hash.to_string(), received_amount }
+        } else {
+            PaymentRequestStatus::PartiallyPaid { received_amount }
+        };
+        true
+    }
+}
+
+/// Persisted list of payment requests
+#[derive(Debug)]
+pub struct PaymentRequests {
+    path: PathBuf,
+    entries: Mutex<Vec<PaymentRequest>>,
+}
+
+impl PaymentRequests {
+    /// Path of the payment request list for `wallet_path`
+    pub fn sidecar_path(wallet_path: &Path) -> PathBuf {
+        sidecar_path(wallet_path, ".requests.json")
+    }
+
+    pub fn for_wallet(wallet_path: &Path) -> Result<Self, String> {
+        Self::open(Self::sidecar_path(wallet_path))
+    }
+
+    /// Load the requests at `path`; a missing file means no requests
+    pub fn open(path: PathBuf) -> Result<Self, String> {
+        let entries = load_json_or_default(&path, "payment requests")?;
+        Ok(Self {
+            path,
+            entries: Mutex::new(entries),
+        })
+    }
+
+    /// Newest first
+    pub fn list(&self) -> Vec<PaymentRequest> {
+        let mut requests = self.entries.lock_recover().clone();
+        requests.sort_by(|a, b| b.created_at.cmp(&a.created_at).then_with(|| a.id.cmp(&b.id)));
+        requests
+    }
+
+    pub fn get(&self, id: &str) -> Option<PaymentRequest> {
+        self.entries.lock_recover().iter().find(|r| r.id == id).cloned()
+    }
+
+    /// Request `amount` atomic units to `address` under a fresh payment ID,
+    /// expiring `expires_in` seconds after `now` if given
+    pub fn create(&self, address: &str, amount: u64, description: &str, expires_in: Option<u64>, now: u64) -> Result<PaymentRequest, String> {
+        if amount == 0 {
+            return Err("Payment request amount must be greater than zero".to_string());
+        }
+
+        let mut entries = self.entries.lock_recover();
+        let payment_id = loop {
+            let candidate = new_payment_id();
+            if !entries.iter().any(|r| r.payment_id == candidate) {
+                break candidate;
+            }
+        };
+        let description = description.trim().to_string();
+        let uri = PaymentUri {
+            address: address.to_string(),
+            amount: Some(amount),
+            payment_id: Some(payment_id.clone()),
+            label: (!description.is_empty()).then(|| description.clone()),
+        }.to_uri();
+
+        let request = PaymentRequest {
+            id: uuid::Uuid::new_v4().to_string(),
+            payment_id,
+            address: address.to_string(),
+            amount,
+            description,
+            uri,
+            created_at: now,
+            expires_at: expires_in.map(|secs| now.saturating_add(secs)),
+            status: PaymentRequestStatus::Pending,
+            tx_hashes: Vec::new(),
+        };
+        entries.push(request.clone());
+        self.save(&entries)?;
+        Ok(request)
+    }
+
+    /// Cancel a request that has not been paid
+    pub fn cancel(&self, id: &str) -> Result<PaymentRequest, String> {
+        let mut entries = self.entries.lock_recover();
+        let request = entries.iter_mut().find(|r| r.id == id)
+            .ok_or_else(|| format!("Payment request not found: {}", id))?;
+        if matches!(request.status, PaymentRequestStatus::Paid { .. }) {
+            return Err(format!("Payment request {} is already paid", id));
+        }
+        request.status = PaymentRequestStatus::Cancelled;
+        let cancelled = request.clone();
+        self.save(&entries)?;
+        Ok(cancelled)
+    }
+
+    /// Mark requests past their expiry that received nothing as expired
+    pub fn expire_due(&self, now: u64) -> Result<Vec<PaymentRequest>, String> {
+        let mut entries = self.entries.lock_recover();
+        let mut expired = Vec::new();
+        for request in entries.iter_mut() {
+            if request.status == PaymentRequestStatus::Pending && request.expires_at.is_some_and(|at| now >= at) {
+                request.status = PaymentRequestStatus::Expired;
+                expired.push(request.clone());
+            }
+        }
+        if !expired.is_empty() {
+            self.save(&entries)?;
+        }
+        Ok(expired)
+    }
+
+    /// Match incoming payments, given as `(tx_hash, payment_id, amount)`,
+    /// to open requests by payment ID. Returns the requests that changed.
+    pub fn match_payments<'a, I>(&self, payments: I) -> Result<Vec<PaymentRequest>, String>
+    where
+        I: IntoIterator<Item = (&'a str, &'a str, u64)>,
+    {
+        let mut entries = self.entries.lock_recover();
+        let mut changed: Vec<PaymentRequest> = Vec::new();
+        for (tx_hash, payment_id, amount) in payments {
+            let Some(request) = entries.iter_mut()
+                .find(|r| r.is_open() && r.payment_id.eq_ignore_ascii_case(payment_id.trim()))
+            else {
+                continue;
+            };
+            if request.apply_payment(tx_hash, amount) {
+                changed.retain(|r| r.id != request.id);
+                changed.push(request.clone());
+            }
+        }
+        if !changed.is_empty() {
+            self.save(&entries)?;
+        }
+        Ok(changed)
+    }
+
+    fn save(&self, entries: &[PaymentRequest]) -> Result<(), String> {
+        save_json(&self.path, entries, "payment requests")
+    }
+}
+
+/// 32 random bytes as hex, the CryptoNote payment ID format
+fn new_payment_id() -> String {
+    hex::encode(rand::random::<[u8; 32]>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::testing::TempDir;
+
+    fn temp_store() -> (TempDir, PaymentRequests, PathBuf) {
+        let dir = TempDir::new("fuego_requests");
+        let path = dir.join("test.wallet.requests.json");
+        (dir, PaymentRequests::open(path.clone()).unwrap(), path)
+    }
+
+    #[test]
+    fn test_exact_over_and_under_payment() {
+        let (_dir, store, path) = temp_store();
+        let exact = store.create("fire1shop", 5_000_000, "Invoice #123", None, 100).unwrap();
+        let over = store.create("fire1shop", 5_000_000, "Invoice #124", None, 100).unwrap();
+        let under = store.create("fire1shop", 5_000_000, "", None, 100).unwrap();
+        assert_eq!(exact.payment_id.len(), 64);
+        assert_ne!(exact.payment_id, over.payment_id);
+        assert!(exact.uri.contains(&exact.payment_id) && exact.uri.contains("amount=0.5"));
+
+        let upper_id = over.payment_id.to_uppercase();
+        let changed = store.match_payments([
+            ("tx1", exact.payment_id.as_str(), 5_000_000),
+            ("tx2", upper_id.as_str(), 7_000_000),
+            ("tx3", under.payment_id.as_str(), 2_000_000),
+            ("tx4", "unrelated", 9_000_000),
+        ]).unwrap();
+        assert_eq!(changed.len(), 3);
+        assert_eq!(store.get(&exact.id).unwrap().status,
+            PaymentRequestStatus::Paid { tx_hash: "tx1".to_string(), received_amount: 5_000_000 });
+        assert_eq!(store.get(&over.id).unwrap().status,
+            PaymentRequestStatus::Paid { tx_hash: "tx2".to_string(), received_amount: 7_000_000 });
+        assert_eq!(store.get(&under.id).unwrap().status,
+            PaymentRequestStatus::PartiallyPaid { received_amount: 2_000_000 });
+
+        // The same transaction seen again is not counted twice; a second one completes it
+        let changed = store.match_payments([
+            ("tx3", under.payment_id.as_str(), 2_000_000),
+            ("tx5", under.payment_id.as_str(), 3_000_000),
+        ]).unwrap();
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].status,
+            PaymentRequestStatus::Paid { tx_hash: "tx5".to_string(), received_amount: 5_000_000 });
+
+        let reopened = PaymentRequests::open(path.clone()).unwrap();
+        assert_eq!(reopened.get(&under.id).unwrap().tx_hashes, vec!["tx3", "tx5"]);
+    }
+
+    #[test]
+    fn test_expiry_and_cancel() {
+        let (_dir, store, _) = temp_store();
+        let expiring = store.create("fire1shop", 100, "Coffee", Some(60), 1_000).unwrap();
+        let cancelled = store.create("fire1shop", 100, "Tea", None, 1_000).unwrap();
+
+        assert!(store.expire_due(1_059).unwrap().is_empty());
+        let expired = store.expire_due(1_060).unwrap();
+        assert_eq!(expired.iter().map(|r| r.id.as_str()).collect::<Vec<_>>(), vec![expiring.id.as_str()]);
+
+        store.cancel(&cancelled.id).unwrap();
+        let changed = store.match_payments([
+            ("late", expiring.payment_id.as_str(), 100),
+            ("ignored", cancelled.payment_id.as_str(), 100),
+        ]).unwrap();
+        assert_eq!(changed.len(), 1);
+        assert!(matches!(store.get(&expiring.id).unwrap().status, PaymentRequestStatus::Paid { .. }));
+        assert!(store.cancel(&expiring.id).unwrap_err().contains("already paid"));
+        assert!(store.create("fire1shop", 0, "Nothing", None, 1_000).is_err());
+    }
+}
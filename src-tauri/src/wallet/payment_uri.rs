@@ -0,0 +1,257 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! `fuego:` payment URIs
+//!
+//! The form handed out in QR codes and links:
+//! `fuego:<address>?amount=<XFG>&payment_id=<hex>&label=<text>`. Amounts are
+//! written in XFG with up to seven decimals and kept in atomic units here.
+
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use crate::crypto::address::{decode_address, resolve_payment_id};
+use crate::utils::error::{ApiError, ErrorCode};
+
+/// Scheme of Fuego payment URIs
+pub const URI_SCHEME: &str = "fuego";
+
+/// Atomic units in one XFG
+pub const ATOMIC_UNITS_PER_XFG: u64 = 10_000_000;
+
+/// Most XFG there will ever be, in atomic units, as `MONEY_SUPPLY`
+pub const MONEY_SUPPLY: u64 = 80_000_088_000_008;
+
+const XFG_DECIMALS: usize = 7;
+const PAYMENT_ID_HEX_LEN: usize = 64;
+
+/// Why a payment URI cannot fill in the send form
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaymentUriError {
+    /// Not a `fuego:` URI, or one with a broken parameter
+    Malformed(String),
+    InvalidAddress(String),
+    /// Not a number of XFG, zero, or more than will ever exist
+    InvalidAmount(String),
+}
+
+impl std::fmt::Display for PaymentUriError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Malformed(reason) => write!(f, "Invalid payment URI: {}", reason),
+            Self::InvalidAddress(address) => write!(f, "Invalid address: {}", address),
+            Self::InvalidAmount(amount) => write!(f, "Invalid amount: {}", amount),
+        }
+    }
+}
+
+impl From<PaymentUriError> for ApiError {
+    fn from(err: PaymentUriError) -> Self {
+        let code = match err {
+            PaymentUriError::Malformed(_) => ErrorCode::InvalidPaymentUri,
+            PaymentUriError::InvalidAddress(_) => ErrorCode::InvalidAddress,
+            PaymentUriError::InvalidAmount(_) => ErrorCode::InvalidAmount,
+        };
+        ApiError::new(code, err.to_string())
+    }
+}
+
+/// A payment URI checked and normalized for the send form
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidatedPaymentUri {
+    pub address: String,
+    pub amount_atomic: Option<u64>,
+    /// The amount in XFG, as `format_xfg` writes it
+    pub amount_display: Option<String>,
+    /// Lowercase hex; the embedded one for an integrated address
+    pub payment_id: Option<String>,
+    pub label: Option<String>,
+    pub valid: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaymentUri {
+    pub address: String,
+    /// Requested amount in atomic units
+    pub amount: Option<u64>,
+    pub payment_id: Option<String>,
+    pub label: Option<String>,
+}
+
+impl PaymentUri {
+    pub fn new(address: &str) -> Self {
+        Self {
+            address: address.to_string(),
+            amount: None,
+            payment_id: None,
+            label: None,
+        }
+    }
+
+    /// Render as a `fuego:` URI, percent-encoding the parameters
+    pub fn to_uri(&self) -> String {
+        let mut url = Url::parse(&format!("{}:{}", URI_SCHEME, self.address))
+            .expect("fuego: URI with a plain address is always valid");
+        let mut query = Vec::new();
+        if let Some(amount) = self.amount {
+            query.push(("amount", format_xfg(amount)));
+        }
+        if let Some(payment_id) = &self.payment_id {
+            query.push(("payment_id", payment_id.clone()));
+        }
+        if let Some(label) = &self.label {
+            query.push(("label", label.clone()));
+        }
+        if !query.is_empty() {
+            url.query_pairs_mut().extend_pairs(query);
+        }
+        url.to_string()
+    }
+
+    /// Parse a `fuego:` URI; unknown parameters are ignored
+    pub fn parse(uri: &str) -> Result<Self, String> {
+        Self::parse_uri(uri).map_err(|e| e.to_string())
+    }
+
+    /// Parse a `fuego:` URI and check it for the send form: the address must
+    /// decode, the amount be neither zero nor beyond the money supply and the
+    /// payment ID be 64 hex characters agreeing with an integrated address
+    pub fn validate(uri: &str) -> Result<ValidatedPaymentUri, PaymentUriError> {
+        let payment = Self::parse_uri(uri)?;
+        decode_address(&payment.address).map_err(|_| PaymentUriError::InvalidAddress(payment.address.clone()))?;
+        if let Some(amount) = payment.amount
+            && (amount == 0 || amount > MONEY_SUPPLY)
+        {
+            return Err(PaymentUriError::InvalidAmount(format_xfg(amount)));
+        }
+        if let Some(payment_id) = &payment.payment_id
+            && (payment_id.len() != PAYMENT_ID_HEX_LEN || !payment_id.chars().all(|c| c.is_ascii_hexdigit()))
+        {
+            return Err(PaymentUriError::Malformed(format!("payment ID must be {} hex characters", PAYMENT_ID_HEX_LEN)));
+        }
+        let (_, payment_id) = resolve_payment_id(&payment.address, payment.payment_id.as_deref())
+            .map_err(|e| PaymentUriError::Malformed(e.to_string()))?;
+
+        Ok(ValidatedPaymentUri {
+            amount_display: payment.amount.map(format_xfg),
+            amount_atomic: payment.amount,
+            payment_id: payment_id.map(|id| id.to_ascii_lowercase()),
+            address: payment.address,
+            label: payment.label,
+            valid: true,
+        })
+    }
+
+    fn parse_uri(uri: &str) -> Result<Self, PaymentUriError> {
+        let url = Url::parse(uri.trim()).map_err(|e| PaymentUriError::Malformed(e.to_string()))?;
+        if url.scheme() != URI_SCHEME {
+            return Err(PaymentUriError::Malformed(format!("expected the {} scheme, got {}", URI_SCHEME, url.scheme())));
+        }
+        let address = url.path().trim_start_matches('/');
+        if address.is_empty() {
+            return Err(PaymentUriError::Malformed("missing address".to_string()));
+        }
+
+        let mut payment = Self::new(address);
+        for (key, value) in url.query_pairs() {
+            match key.as_ref() {
+                "amount" | "tx_amount" => {
+                    payment.amount = Some(parse_xfg(&value).map_err(|_| PaymentUriError::InvalidAmount(value.to_string()))?);
+                }
+                "payment_id" => payment.payment_id = Some(value.into_owned()),
+                "label" | "recipient_name" => payment.label = Some(value.into_owned()),
+                _ => {}
+            }
+        }
+        Ok(payment)
+    }
+}
+
+/// Atomic units as an XFG amount without trailing zeros, e.g. `0.5`
+pub fn format_xfg(atomic: u64) -> String {
+    let whole = atomic / ATOMIC_UNITS_PER_XFG;
+    let fraction = atomic % ATOMIC_UNITS_PER_XFG;
+    if fraction == 0 {
+        return whole.to_string();
+    }
+    let fraction = format!("{:0width$}", fraction, width = XFG_DECIMALS);
+    format!("{}.{}", whole, fraction.trim_end_matches('0'))
+}
+
+/// Parse an XFG amount such as `0.5` into atomic units
+pub fn parse_xfg(amount: &str) -> Result<u64, String> {
+    let invalid = || format!("Invalid amount: {}", amount);
+    let (whole, fraction) = amount.trim().split_once('.').unwrap_or((amount.trim(), ""));
+    if (whole.is_empty() && fraction.is_empty())
+        || fraction.len() > XFG_DECIMALS
+        || !whole.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+
+    let whole: u64 = if whole.is_empty() { 0 } else { whole.parse().map_err(|_| invalid())? };
+    let fraction: u64 = format!("{:0<width$}", fraction, width = XFG_DECIMALS).parse().map_err(|_| invalid())?;
+    whole.checked_mul(ATOMIC_UNITS_PER_XFG)
+        .and_then(|atomic| atomic.checked_add(fraction))
+        .ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uri_round_trip() {
+        let payment = PaymentUri {
+            address: "fire1merchant".to_string(),
+            amount: Some(5_000_000),
+            payment_id: Some("ab".repeat(32)),
+            label: Some("Invoice #123 & co".to_string()),
+        };
+        let uri = payment.to_uri();
+        assert!(uri.starts_with("fuego:fire1merchant?amount=0.5&payment_id="));
+        assert!(!uri.contains(' ') && !uri.contains("#123"));
+        assert_eq!(PaymentUri::parse(&uri).unwrap(), payment);
+
+        assert_eq!(PaymentUri::new("fire1merchant").to_uri(), "fuego:fire1merchant");
+        assert!(PaymentUri::parse("bitcoin:1abc").is_err());
+    }
+
+    #[test]
+    fn test_validate_normalizes_and_tells_errors_apart() {
+        use crate::crypto::address::{encode_address, make_integrated_address, FUEGO_ADDRESS_PREFIX};
+
+        let address = encode_address(FUEGO_ADDRESS_PREFIX, &(0..64).collect::<Vec<u8>>());
+        let validated = PaymentUri::validate(&format!("fuego:{}?amount=1.50&payment_id={}&label=Shop", address, "AB".repeat(32))).unwrap();
+        assert_eq!(validated, ValidatedPaymentUri {
+            address: address.clone(),
+            amount_atomic: Some(15_000_000),
+            amount_display: Some("1.5".to_string()),
+            payment_id: Some("ab".repeat(32)),
+            label: Some("Shop".to_string()),
+            valid: true,
+        });
+        let integrated = make_integrated_address(&address, &"cd".repeat(32)).unwrap();
+        assert_eq!(PaymentUri::validate(&format!("fuego:{}", integrated)).unwrap().payment_id, Some("cd".repeat(32)));
+
+        let code = |uri: String| ApiError::from(PaymentUri::validate(&uri).unwrap_err()).code;
+        assert_eq!(code("bitcoin:1abc".to_string()), ErrorCode::InvalidPaymentUri);
+        assert_eq!(code(format!("fuego:{}?payment_id=xyz", address)), ErrorCode::InvalidPaymentUri);
+        assert_eq!(code(format!("fuego:{}?payment_id={}", integrated, "ef".repeat(32))), ErrorCode::InvalidPaymentUri);
+        assert_eq!(code("fuego:fire1notanaddress".to_string()), ErrorCode::InvalidAddress);
+        assert_eq!(code(format!("fuego:{}?amount=abc", address)), ErrorCode::InvalidAmount);
+        assert_eq!(code(format!("fuego:{}?amount=0", address)), ErrorCode::InvalidAmount);
+        assert_eq!(code(format!("fuego:{}?amount=9000000", address)), ErrorCode::InvalidAmount);
+    }
+
+    #[test]
+    fn test_xfg_amounts() {
+        assert_eq!(format_xfg(12_345_000_000), "1234.5");
+        assert_eq!(format_xfg(1), "0.0000001");
+        assert_eq!(format_xfg(20_000_000), "2");
+        assert_eq!(parse_xfg("0.5").unwrap(), 5_000_000);
+        assert_eq!(parse_xfg("3").unwrap(), 30_000_000);
+        assert!(parse_xfg("0.00000001").is_err());
+        assert!(parse_xfg("-1").is_err());
+        assert!(parse_xfg(".").is_err());
+    }
+}
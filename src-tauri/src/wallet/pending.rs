@@ -0,0 +1,230 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Pending transaction tracking
+//!
+//! A sent transaction only shows up in the history after the wallet has seen
+//! it in a block. The tracker remembers what was sent so the UI can show it
+//! as in flight, and reconciliation promotes entries to confirmed, or gives
+//! up on them after a timeout. Reconciled entries leave the pending set.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use crate::utils::locks::MutexExt;
+
+/// How long a transaction may stay unconfirmed before it is reported failed
+pub const DEFAULT_PENDING_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PendingTxStatus {
+    Pending,
+    Confirmed,
+    Failed,
+}
+
+/// A transaction sent by this wallet that is not confirmed yet
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingTx {
+    pub hash: String,
+    /// Total sent, in atomic units
+    pub amount: u64,
+    pub destinations: Vec<String>,
+    pub payment_id: Option<String>,
+    /// Unix time the transaction was submitted
+    pub submitted_at: u64,
+    pub status: PendingTxStatus,
+    pub height: Option<u64>,
+    pub confirmations: u32,
+    /// Times the transaction was relayed to the peers again
+    #[serde(default)]
+    pub rebroadcast_count: u32,
+    /// Unix time of the last rebroadcast
+    #[serde(default)]
+    pub last_rebroadcast_at: Option<u64>,
+}
+
+/// Where the wallet found a transaction in the chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Confirmation {
+    pub height: u64,
+    pub confirmations: u32,
+}
+
+/// Set of sent transactions waiting for confirmation
+#[derive(Debug)]
+pub struct PendingTxTracker {
+    entries: Mutex<HashMap<String, PendingTx>>,
+    timeout: Duration,
+}
+
+impl PendingTxTracker {
+    pub fn new() -> Self {
+        Self::with_timeout(DEFAULT_PENDING_TIMEOUT)
+    }
+
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            timeout,
+        }
+    }
+
+    /// Start tracking a transaction submitted at `submitted_at`
+    pub fn track(
+        &self,
+        hash: &str,
+        amount: u64,
+        destinations: Vec<String>,
+        payment_id: Option<String>,
+        submitted_at: u64,
+    ) -> PendingTx {
+        let pending = PendingTx {
+            hash: hash.to_string(),
+            amount,
+            destinations,
+            payment_id,
+            submitted_at,
+            status: PendingTxStatus::Pending,
+            height: None,
+            confirmations: 0,
+            rebroadcast_count: 0,
+            last_rebroadcast_at: None,
+        };
+        self.entries.lock_recover().insert(hash.to_string(), pending.clone());
+        pending
+    }
+
+    /// Transactions still waiting, oldest first
+    pub fn pending(&self) -> Vec<PendingTx> {
+        let mut pending: Vec<PendingTx> = self.entries.lock_recover().values().cloned().collect();
+        pending.sort_by(|a, b| a.submitted_at.cmp(&b.submitted_at).then_with(|| a.hash.cmp(&b.hash)));
+        pending
+    }
+
+    /// The pending transaction `hash`, if it is still waiting
+    pub fn get(&self, hash: &str) -> Option<PendingTx> {
+        self.entries.lock_recover().get(hash).cloned()
+    }
+
+    /// Count a rebroadcast of `hash` at `now`. `None` if the transaction is
+    /// not pending (anymore).
+    pub fn record_rebroadcast(&self, hash: &str, now: u64) -> Option<PendingTx> {
+        let mut entries = self.entries.lock_recover();
+        let entry = entries.get_mut(hash)?;
+        entry.rebroadcast_count += 1;
+        entry.last_rebroadcast_at = Some(now);
+        Some(entry.clone())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.lock_recover().is_empty()
+    }
+
+    /// Check every pending transaction with `lookup`, which returns the
+    /// confirmation of transactions the wallet has seen in a block.
+    ///
+    /// Confirmed transactions and those older than the timeout are removed
+    /// from the pending set and returned with their new status.
+    pub fn reconcile<F>(&self, lookup: F, now: u64) -> Vec<PendingTx>
+    where
+        F: Fn(&str) -> Option<Confirmation>,
+    {
+        let hashes: Vec<String> = self.entries.lock_recover().keys().cloned().collect();
+        // Look up without holding the lock; the wallet call can be slow
+        let confirmations: Vec<(String, Option<Confirmation>)> = hashes.into_iter()
+            .map(|hash| {
+                let confirmation = lookup(&hash);
+                (hash, confirmation)
+            })
+            .collect();
+
+        let mut entries = self.entries.lock_recover();
+        let mut reconciled = Vec::new();
+        for (hash, confirmation) in confirmations {
+            let Some(entry) = entries.get(&hash) else { continue };
+            let timed_out = now.saturating_sub(entry.submitted_at) >= self.timeout.as_secs();
+            if confirmation.is_none() && !timed_out {
+                continue;
+            }
+
+            let mut entry = entries.remove(&hash).unwrap();
+            match confirmation {
+                Some(confirmation) => {
+                    entry.status = PendingTxStatus::Confirmed;
+                    entry.height = Some(confirmation.height);
+                    entry.confirmations = confirmation.confirmations;
+                }
+                None => entry.status = PendingTxStatus::Failed,
+            }
+            reconciled.push(entry);
+        }
+        reconciled.sort_by_key(|p| p.submitted_at);
+        reconciled
+    }
+}
+
+impl Default for PendingTxTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_confirmed_transactions_leave_the_pending_set() {
+        let tracker = PendingTxTracker::new();
+        tracker.track("aa", 1_000, vec!["fire1dest".to_string()], None, 100);
+        tracker.track("bb", 2_000, vec!["fire1dest".to_string()], Some("pid".to_string()), 90);
+        assert_eq!(tracker.pending().iter().map(|p| p.hash.as_str()).collect::<Vec<_>>(), vec!["bb", "aa"]);
+
+        let reconciled = tracker.reconcile(
+            |hash| (hash == "aa").then_some(Confirmation { height: 500, confirmations: 1 }),
+            200,
+        );
+        assert_eq!(reconciled.len(), 1);
+        assert_eq!(reconciled[0].status, PendingTxStatus::Confirmed);
+        assert_eq!(reconciled[0].height, Some(500));
+
+        let remaining = tracker.pending();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].hash, "bb");
+        assert_eq!(remaining[0].status, PendingTxStatus::Pending);
+
+        // Reconciling again does not report "aa" a second time
+        assert!(tracker.reconcile(|_| Some(Confirmation { height: 501, confirmations: 2 }), 210)
+            .iter().all(|p| p.hash == "bb"));
+        assert!(tracker.is_empty());
+    }
+
+    #[test]
+    fn test_rebroadcasts_are_counted_while_pending() {
+        let tracker = PendingTxTracker::new();
+        tracker.track("aa", 1_000, Vec::new(), None, 100);
+        assert!(tracker.record_rebroadcast("unknown", 150).is_none());
+
+        tracker.record_rebroadcast("aa", 150).unwrap();
+        let pending = tracker.record_rebroadcast("aa", 180).unwrap();
+        assert_eq!((pending.rebroadcast_count, pending.last_rebroadcast_at), (2, Some(180)));
+        assert_eq!(tracker.get("aa"), Some(pending));
+
+        tracker.reconcile(|_| Some(Confirmation { height: 500, confirmations: 1 }), 200);
+        assert!(tracker.record_rebroadcast("aa", 210).is_none());
+    }
+
+    #[test]
+    fn test_unconfirmed_transactions_fail_after_timeout() {
+        let tracker = PendingTxTracker::with_timeout(Duration::from_secs(60));
+        tracker.track("aa", 1_000, Vec::new(), None, 100);
+
+        assert!(tracker.reconcile(|_| None, 159).is_empty());
+        let reconciled = tracker.reconcile(|_| None, 160);
+        assert_eq!(reconciled[0].status, PendingTxStatus::Failed);
+        assert!(tracker.is_empty());
+    }
+}
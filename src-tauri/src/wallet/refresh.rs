@@ -0,0 +1,231 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Refresh coordination
+//!
+//! Several commands and the frontend's polling can ask for a wallet refresh at
+//! the same time. The coordinator makes sure only one refresh hits the daemon:
+//! callers arriving while a refresh is running wait for its result, and a
+//! successful refresh is reused for a short minimum interval.
+
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+use crate::utils::locks::{CondvarExt, MutexExt};
+
+/// Default minimum time between two refreshes
+pub const DEFAULT_MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+type RefreshOutcome = Result<(), String>;
+
+/// A refresh that is currently running
+#[derive(Debug, Default)]
+struct InFlight {
+    result: Mutex<Option<RefreshOutcome>>,
+    done: Condvar,
+}
+
+impl InFlight {
+    fn wait(&self) -> RefreshOutcome {
+        let result = self.done.wait_while_recover(self.result.lock_recover(), |result| result.is_none());
+        result.clone().expect("the wait only ends once the refresh has stored its result")
+    }
+}
+
+/// Coalesces concurrent refresh requests into a single refresh
+#[derive(Debug)]
+pub struct RefreshCoordinator {
+    in_flight: Arc<Mutex<Option<Arc<InFlight>>>>,
+    last_success: Arc<Mutex<Option<Instant>>>,
+    min_interval: Mutex<Duration>,
+}
+
+impl RefreshCoordinator {
+    pub fn new() -> Self {
+        Self::with_min_interval(DEFAULT_MIN_REFRESH_INTERVAL)
+    }
+
+    pub fn with_min_interval(min_interval: Duration) -> Self {
+        Self {
+            in_flight: Arc::new(Mutex::new(None)),
+            last_success: Arc::new(Mutex::new(None)),
+            min_interval: Mutex::new(min_interval),
+        }
+    }
+
+    /// Change the minimum interval between refreshes
+    pub fn set_min_interval(&self, min_interval: Duration) {
+        *self.min_interval.lock_recover() = min_interval;
+    }
+
+    /// Run `refresh` unless another refresh is in flight or one succeeded
+    /// within the minimum interval.
+    ///
+    /// Callers that arrive while a refresh is running block until it finishes
+    /// and get its result. Failed refreshes are not reused, so the next caller
+    /// retries.
+    pub fn refresh<F>(&self, refresh: F) -> Result<(), String>
+    where
+        F: FnOnce() -> Result<(), String>,
+    {
+        let current = {
+            let mut in_flight = self.in_flight.lock_recover();
+            if let Some(current) = in_flight.as_ref() {
+                Some(current.clone())
+            } else {
+                let min_interval = *self.min_interval.lock_recover();
+                if let Some(last) = *self.last_success.lock_recover()
+                    && last.elapsed() < min_interval
+                {
+                    return Ok(());
+                }
+                *in_flight = Some(Arc::new(InFlight::default()));
+                None
+            }
+        };
+
+        if let Some(current) = current {
+            return current.wait();
+        }
+
+        let mut guard = FinishOnDrop { coordinator: self, finished: false };
+        let result = refresh();
+        guard.finished = true;
+        self.finish(&result);
+
+        result
+    }
+
+    /// Record the outcome of the running refresh and wake its waiters
+    fn finish(&self, result: &RefreshOutcome) {
+        let mut in_flight = self.in_flight.lock_recover();
+        if result.is_ok() {
+            *self.last_success.lock_recover() = Some(Instant::now());
+        }
+        if let Some(current) = in_flight.take() {
+            *current.result.lock_recover() = Some(result.clone());
+            current.done.notify_all();
+        }
+    }
+}
+
+/// Ends the refresh with an error if the refresh closure panics, so waiters
+/// wake up and the next caller starts a new refresh
+struct FinishOnDrop<'a> {
+    coordinator: &'a RefreshCoordinator,
+    finished: bool,
+}
+
+impl Drop for FinishOnDrop<'_> {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.coordinator.finish(&Err("Refresh panicked".to_string()));
+        }
+    }
+}
+
+impl Default for RefreshCoordinator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Barrier;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+
+    #[test]
+    fn test_concurrent_refreshes_are_coalesced() {
+        let coordinator = Arc::new(RefreshCoordinator::with_min_interval(Duration::from_secs(60)));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let barrier = Arc::new(Barrier::new(8));
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let coordinator = coordinator.clone();
+                let calls = calls.clone();
+                let barrier = barrier.clone();
+                thread::spawn(move || {
+                    barrier.wait();
+                    coordinator.refresh(|| {
+                        calls.fetch_add(1, Ordering::SeqCst);
+                        thread::sleep(Duration::from_millis(50));
+                        Ok(())
+                    })
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            assert!(handle.join().unwrap().is_ok());
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_refresh_runs_again_after_interval() {
+        let coordinator = RefreshCoordinator::with_min_interval(Duration::from_millis(20));
+        let calls = AtomicUsize::new(0);
+        let count = || {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        };
+
+        coordinator.refresh(count).unwrap();
+        coordinator.refresh(count).unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        thread::sleep(Duration::from_millis(30));
+        coordinator.refresh(count).unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_failed_refresh_is_retried() {
+        let coordinator = RefreshCoordinator::new();
+        let calls = AtomicUsize::new(0);
+
+        let result = coordinator.refresh(|| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err("daemon unreachable".to_string())
+        });
+        assert_eq!(result, Err("daemon unreachable".to_string()));
+
+        coordinator.refresh(|| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }).unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_panicking_refresh_releases_waiters() {
+        let coordinator = Arc::new(RefreshCoordinator::new());
+        let started = Arc::new(Barrier::new(2));
+
+        let panicking = {
+            let (coordinator, started) = (coordinator.clone(), started.clone());
+            thread::spawn(move || {
+                coordinator.refresh(|| {
+                    started.wait();
+                    thread::sleep(Duration::from_millis(50));
+                    panic!("daemon client panicked");
+                })
+            })
+        };
+        started.wait();
+        let waited = coordinator.refresh(|| Ok(()));
+        assert!(panicking.join().is_err());
+        assert_eq!(waited, Err("Refresh panicked".to_string()));
+
+        // Nothing is left in flight, so the next caller refreshes
+        let calls = AtomicUsize::new(0);
+        coordinator.refresh(|| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }).unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
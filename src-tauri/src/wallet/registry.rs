@@ -0,0 +1,312 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Open wallet registry
+//!
+//! Wallets opened by name stay open here, each under an id the frontend passes
+//! back to commands, with its own sync state. One of them is the active
+//! wallet, which commands called without an id work on. At most
+//! `MAX_OPEN_WALLETS` are open at a time.
+
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::crypto::real_cryptonote::{RealCryptoNoteWallet, SyncStatus};
+use crate::utils::error::{WalletError, WalletResult};
+use crate::utils::locks::MutexExt;
+
+/// Most wallets open at the same time
+pub const MAX_OPEN_WALLETS: usize = 8;
+
+/// Background task syncing the registered wallets
+pub const REGISTRY_SYNC_TASK: &str = "registry_sync";
+
+/// Outcome of the last sync of one registered wallet
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WalletSyncState {
+    /// Unix time of the last successful sync
+    pub last_synced_at: Option<u64>,
+    pub status: Option<SyncStatus>,
+    pub last_error: Option<String>,
+}
+
+/// A wallet kept open under an id
+pub struct RegisteredWallet {
+    pub id: String,
+    pub file_path: String,
+    wallet: Mutex<RealCryptoNoteWallet>,
+    sync: Mutex<WalletSyncState>,
+}
+
+impl RegisteredWallet {
+    /// Run `f` on the wallet, one caller at a time
+    pub fn with_wallet<T>(&self, f: impl FnOnce(&mut RealCryptoNoteWallet) -> T) -> T {
+        f(&mut self.wallet.lock_recover())
+    }
+
+    /// Refresh the wallet and record the outcome in its sync state
+    pub fn sync(&self) -> WalletResult<WalletSyncState> {
+        let result = self.with_wallet(|wallet| {
+            wallet.refresh()?;
+            wallet.get_sync_status()
+        });
+        let mut sync = self.sync.lock_recover();
+        match result {
+            Ok(status) => {
+                sync.last_synced_at = Some(SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0));
+                sync.status = Some(status);
+                sync.last_error = None;
+                Ok(sync.clone())
+            }
+            Err(e) => {
+                sync.last_error = Some(e.to_string());
+                Err(e)
+            }
+        }
+    }
+
+    pub fn sync_state(&self) -> WalletSyncState {
+        self.sync.lock_recover().clone()
+    }
+}
+
+/// A registered wallet as listed to the frontend
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpenWallet {
+    pub id: String,
+    pub file_path: String,
+    pub is_active: bool,
+    pub sync: WalletSyncState,
+}
+
+#[derive(Default)]
+struct RegistryState {
+    /// In the order they were opened
+    wallets: Vec<Arc<RegisteredWallet>>,
+    active: Option<String>,
+}
+
+/// Wallets open at the same time, keyed by id
+pub struct WalletRegistry {
+    state: Mutex<RegistryState>,
+    capacity: usize,
+}
+
+impl std::fmt::Debug for WalletRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WalletRegistry")
+            .field("open", &self.state.lock_recover().wallets.len())
+            .field("capacity", &self.capacity)
+            .finish()
+    }
+}
+
+impl Default for WalletRegistry {
+    fn default() -> Self {
+        Self::new(MAX_OPEN_WALLETS)
+    }
+}
+
+impl WalletRegistry {
+    /// Registry holding at most `capacity` wallets
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            state: Mutex::new(RegistryState::default()),
+            capacity,
+        }
+    }
+
+    /// Id of the wallet open from `file_path`, if any
+    pub fn id_for_path(&self, file_path: &str) -> Option<String> {
+        self.state.lock_recover().wallets.iter().find(|w| w.file_path == file_path).map(|w| w.id.clone())
+    }
+
+    /// Register the opened `wallet` and return its id. The first wallet
+    /// becomes the active one. When `file_path` is registered already, its id
+    /// is returned and `wallet` closed; when the registry is full, `wallet` is
+    /// closed and an error returned.
+    pub fn register(&self, file_path: &str, mut wallet: RealCryptoNoteWallet) -> WalletResult<String> {
+        let mut state = self.state.lock_recover();
+        if let Some(existing) = state.wallets.iter().find(|w| w.file_path == file_path) {
+            wallet.close_wallet();
+            return Ok(existing.id.clone());
+        }
+        if state.wallets.len() >= self.capacity {
+            wallet.close_wallet();
+            return Err(WalletError::WalletOpenFailed(format!(
+                "{} wallets are open already, close one first",
+                self.capacity
+            )));
+        }
+
+        let id = uuid::Uuid::new_v4().to_string();
+        state.wallets.push(Arc::new(RegisteredWallet {
+            id: id.clone(),
+            file_path: file_path.to_string(),
+            wallet: Mutex::new(wallet),
+            sync: Mutex::new(WalletSyncState::default()),
+        }));
+        if state.active.is_none() {
+            state.active = Some(id.clone());
+        }
+        Ok(id)
+    }
+
+    pub fn get(&self, id: &str) -> WalletResult<Arc<RegisteredWallet>> {
+        self.state.lock_recover().wallets.iter()
+            .find(|w| w.id == id)
+            .cloned()
+            .ok_or(WalletError::WalletNotOpen)
+    }
+
+    /// The wallet commands without an id work on
+    pub fn active(&self) -> Option<Arc<RegisteredWallet>> {
+        let state = self.state.lock_recover();
+        let active = state.active.as_deref()?;
+        state.wallets.iter().find(|w| w.id == active).cloned()
+    }
+
+    pub fn set_active(&self, id: &str) -> WalletResult<()> {
+        let mut state = self.state.lock_recover();
+        if !state.wallets.iter().any(|w| w.id == id) {
+            return Err(WalletError::WalletNotOpen);
+        }
+        state.active = Some(id.to_string());
+        Ok(())
+    }
+
+    /// Close and unregister a wallet. Closing the active wallet makes the
+    /// earliest opened of the others active.
+    pub fn close(&self, id: &str) -> WalletResult<()> {
+        let removed = {
+            let mut state = self.state.lock_recover();
+            let index = state.wallets.iter().position(|w| w.id == id).ok_or(WalletError::WalletNotOpen)?;
+            let removed = state.wallets.remove(index);
+            if state.active.as_deref() == Some(id) {
+                state.active = state.wallets.first().map(|w| w.id.clone());
+            }
+            removed
+        };
+        // Outside the registry lock: waits for a sync of this wallet to finish
+        removed.with_wallet(|wallet| wallet.close_wallet());
+        Ok(())
+    }
+
+    /// Close and unregister every wallet
+    pub fn close_all(&self) {
+        let removed = {
+            let mut state = self.state.lock_recover();
+            state.active = None;
+            std::mem::take(&mut state.wallets)
+        };
+        for wallet in removed {
+            wallet.with_wallet(|wallet| wallet.close_wallet());
+        }
+    }
+
+    /// Every registered wallet, in the order they were opened
+    pub fn wallets(&self) -> Vec<Arc<RegisteredWallet>> {
+        self.state.lock_recover().wallets.clone()
+    }
+
+    pub fn list(&self) -> Vec<OpenWallet> {
+        let state = self.state.lock_recover();
+        state.wallets.iter()
+            .map(|w| OpenWallet {
+                id: w.id.clone(),
+                file_path: w.file_path.clone(),
+                is_active: state.active.as_deref() == Some(w.id.as_str()),
+                sync: w.sync_state(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::backend::MockWalletBackend;
+
+    fn mock_wallet(balance: u64) -> (MockWalletBackend, RealCryptoNoteWallet) {
+        let backend = MockWalletBackend::with_balance(balance);
+        let wallet = RealCryptoNoteWallet::with_backend(Box::new(backend.clone()));
+        (backend, wallet)
+    }
+
+    #[test]
+    fn test_wallets_stay_open_side_by_side() {
+        let registry = WalletRegistry::new(2);
+        let (_, first) = mock_wallet(100);
+        let (second_backend, second) = mock_wallet(200);
+        let first_id = registry.register("/wallets/a.wallet", first).unwrap();
+        let second_id = registry.register("/wallets/b.wallet", second).unwrap();
+
+        assert_eq!(registry.active().unwrap().id, first_id);
+        assert_eq!(registry.get(&second_id).unwrap().with_wallet(|w| w.get_balance()).unwrap(), 200);
+
+        // Opening a path again hands back its id
+        let (_, again) = mock_wallet(0);
+        assert_eq!(registry.register("/wallets/b.wallet", again).unwrap(), second_id);
+        // The registry is bounded
+        let (third_backend, third) = mock_wallet(300);
+        assert!(registry.register("/wallets/c.wallet", third).is_err());
+        assert!(!third_backend.state().is_open);
+
+        // Each wallet has its own sync state
+        registry.get(&second_id).unwrap().sync().unwrap();
+        let listed = registry.list();
+        assert!(listed[0].sync.last_synced_at.is_none());
+        assert!(listed[1].sync.last_synced_at.is_some());
+
+        // Closing the active wallet leaves the other open, and active
+        registry.close(&first_id).unwrap();
+        assert!(matches!(registry.get(&first_id), Err(WalletError::WalletNotOpen)));
+        assert_eq!(registry.active().unwrap().id, second_id);
+        assert!(second_backend.state().is_open);
+        assert_eq!(registry.list().len(), 1);
+
+        registry.close_all();
+        assert!(registry.list().is_empty() && registry.active().is_none());
+        assert!(!second_backend.state().is_open);
+    }
+
+    #[test]
+    fn test_other_opens_leave_registered_native_wallets_alone() {
+        let registry = WalletRegistry::default();
+        let mut first = RealCryptoNoteWallet::new();
+        first.create_wallet("password", "/tmp/fuego_registry_a.wallet", None, 0).unwrap();
+        let mut second = RealCryptoNoteWallet::new();
+        second.create_wallet("password", "/tmp/fuego_registry_b.wallet", None, 0).unwrap();
+        let first_address = first.get_address().unwrap();
+        let second_address = second.get_address().unwrap();
+        registry.register("/tmp/fuego_registry_a.wallet", first).unwrap();
+        let second_id = registry.register("/tmp/fuego_registry_b.wallet", second).unwrap();
+        registry.set_active(&second_id).unwrap();
+
+        // A command opening the default wallet on its own, then dropping it
+        let mut legacy = RealCryptoNoteWallet::new();
+        legacy.open_wallet("/tmp/fuego_registry_legacy.wallet", "password").unwrap();
+        drop(legacy);
+
+        let active = registry.active().unwrap();
+        assert_eq!(active.with_wallet(|w| w.get_address()).unwrap(), second_address);
+        assert!(active.with_wallet(|w| w.get_balance()).is_ok());
+        let listed: Vec<String> = registry.wallets().iter().map(|w| w.with_wallet(|w| w.get_address()).unwrap()).collect();
+        assert_eq!(listed, vec![first_address, second_address]);
+        registry.close_all();
+    }
+
+    #[test]
+    fn test_active_wallet_must_be_registered() {
+        let registry = WalletRegistry::default();
+        assert!(registry.active().is_none());
+        assert!(matches!(registry.set_active("missing"), Err(WalletError::WalletNotOpen)));
+
+        let (_, first) = mock_wallet(1);
+        let (_, second) = mock_wallet(2);
+        registry.register("/wallets/a.wallet", first).unwrap();
+        let second_id = registry.register("/wallets/b.wallet", second).unwrap();
+        registry.set_active(&second_id).unwrap();
+        assert_eq!(registry.active().unwrap().with_wallet(|w| w.get_balance()).unwrap(), 2);
+    }
+}
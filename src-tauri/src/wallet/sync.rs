@@ -0,0 +1,250 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Background sync loop control
+//!
+//! A single backend loop refreshes the wallet and tells the frontend through
+//! events, instead of every screen polling on its own. This is the state the
+//! loop is steered by: its interval, which follows `UISettings::refresh_interval`
+//! live, and whether a wallet is open at all. Changing either wakes the loop.
+//!
+//! Each sync also samples how far the wallet is behind the daemon. A gap that
+//! stays above the threshold without shrinking for longer than the grace
+//! period means sync is stalled, rather than catching up slowly.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+use crate::utils::locks::MutexExt;
+
+/// Event emitted after each sync with the updated wallet info
+pub const WALLET_UPDATED_EVENT: &str = "wallet-updated";
+
+/// Shortest allowed interval, whatever the settings say
+pub const MIN_SYNC_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Event emitted when sync stalls behind the daemon and again, with
+/// `stalled` false, once it caught up
+pub const SYNC_STALLED_EVENT: &str = "sync-stalled";
+
+/// How long sync may go without catching up before it counts as stalled
+pub const DEFAULT_SYNC_STALL_GRACE: Duration = Duration::from_secs(600);
+
+/// Interval, open state and wake-up signal of the sync loop
+#[derive(Debug)]
+pub struct SyncLoopControl {
+    interval: Mutex<Duration>,
+    wallet_open: AtomicBool,
+    wake: Notify,
+    drift: Mutex<SyncDriftMonitor>,
+}
+
+impl SyncLoopControl {
+    pub fn new(interval: Duration, wallet_open: bool) -> Self {
+        Self {
+            interval: Mutex::new(interval.max(MIN_SYNC_INTERVAL)),
+            wallet_open: AtomicBool::new(wallet_open),
+            wake: Notify::new(),
+            drift: Mutex::new(SyncDriftMonitor::new()),
+        }
+    }
+
+    pub fn interval(&self) -> Duration {
+        *self.interval.lock_recover()
+    }
+
+    /// Change the interval; a loop waiting on the old one restarts its wait
+    pub fn set_interval(&self, interval: Duration) {
+        let interval = interval.max(MIN_SYNC_INTERVAL);
+        let mut current = self.interval.lock_recover();
+        if *current != interval {
+            *current = interval;
+            self.wake.notify_one();
+        }
+    }
+
+    pub fn is_wallet_open(&self) -> bool {
+        self.wallet_open.load(Ordering::SeqCst)
+    }
+
+    /// Record that a wallet was opened or closed; opening syncs right away
+    pub fn set_wallet_open(&self, open: bool) {
+        if !self.wallet_open.swap(open, Ordering::SeqCst) && open {
+            self.wake.notify_one();
+        }
+    }
+
+    /// Whether the loop should sync now: a wallet is open and not locked
+    pub fn should_sync(&self, locked: bool) -> bool {
+        self.is_wallet_open() && !locked
+    }
+
+    /// Sample the heights after a sync, see `SyncDriftMonitor::record`
+    pub fn record_drift(&self, sync_height: u64, network_height: u64, threshold: u64, grace: Duration) -> (Option<DriftAlert>, SyncDrift) {
+        let mut monitor = self.drift.lock_recover();
+        let alert = monitor.record(sync_height, network_height, threshold, grace, Instant::now());
+        (alert, monitor.current())
+    }
+
+    /// How far the wallet was behind at the last sync
+    pub fn drift(&self) -> SyncDrift {
+        self.drift.lock_recover().current()
+    }
+
+    /// Wait one interval, or less if the interval changed or a wallet was opened
+    pub async fn wait(&self) {
+        let interval = self.interval();
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {}
+            _ = self.wake.notified() => {}
+        }
+    }
+}
+
+/// How far the wallet is behind the daemon
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DriftState {
+    /// Within the threshold
+    #[default]
+    InSync,
+    /// Behind, but the gap shrank within the grace period
+    CatchingUp,
+    /// Behind, and the gap has not shrunk for the grace period
+    Stalled,
+}
+
+/// The latest height sample and what it says
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncDrift {
+    pub sync_height: u64,
+    pub network_height: u64,
+    /// Blocks the wallet is behind
+    pub gap: u64,
+    pub state: DriftState,
+    /// Seconds since the gap last shrank
+    pub stalled_secs: u64,
+    /// Whether the user was told sync is stalled
+    pub alerted: bool,
+}
+
+/// Change of the stall alert after a sample
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DriftAlert {
+    Raised,
+    Cleared,
+}
+
+/// Height samples of the sync loop and the stall alert they lead to
+#[derive(Debug, Default)]
+pub struct SyncDriftMonitor {
+    /// Smallest gap since the wallet fell behind, and when it was sampled
+    low_water: Option<(u64, Instant)>,
+    current: SyncDrift,
+}
+
+impl SyncDriftMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn current(&self) -> SyncDrift {
+        self.current.clone()
+    }
+
+    /// Record the heights after a sync at `now`. While the gap is above
+    /// `threshold` blocks, each sample that shrinks it below every earlier
+    /// one means sync is catching up; none for `grace` means it stalled and
+    /// raises the alert. The alert is cleared once the gap is back within
+    /// `threshold`.
+    pub fn record(&mut self, sync_height: u64, network_height: u64, threshold: u64, grace: Duration, now: Instant) -> Option<DriftAlert> {
+        let gap = network_height.saturating_sub(sync_height);
+        let was_alerted = self.current.alerted;
+        self.current = SyncDrift { sync_height, network_height, gap, ..SyncDrift::default() };
+
+        if gap <= threshold {
+            self.low_water = None;
+            return was_alerted.then_some(DriftAlert::Cleared);
+        }
+
+        let (lowest, since) = match self.low_water {
+            Some((lowest, since)) if gap >= lowest => (lowest, since),
+            _ => (gap, now),
+        };
+        self.low_water = Some((lowest, since));
+        let stalled_for = now.saturating_duration_since(since);
+        let stalled = stalled_for >= grace;
+        self.current.state = if stalled { DriftState::Stalled } else { DriftState::CatchingUp };
+        self.current.stalled_secs = stalled_for.as_secs();
+        self.current.alerted = was_alerted || stalled;
+        (!was_alerted && stalled).then_some(DriftAlert::Raised)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_interval_change_wakes_the_loop() {
+        let control = std::sync::Arc::new(SyncLoopControl::new(Duration::from_secs(60), true));
+        let waiter = control.clone();
+        let start = Instant::now();
+        let handle = tokio::spawn(async move { waiter.wait().await });
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        control.set_interval(Duration::from_secs(5));
+        handle.await.unwrap();
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert_eq!(control.interval(), Duration::from_secs(5));
+
+        control.set_interval(Duration::ZERO);
+        assert_eq!(control.interval(), MIN_SYNC_INTERVAL);
+    }
+
+    #[test]
+    fn test_pauses_while_locked_or_closed() {
+        let control = SyncLoopControl::new(Duration::from_secs(5), true);
+        assert!(control.should_sync(false));
+        assert!(!control.should_sync(true));
+
+        control.set_wallet_open(false);
+        assert!(!control.should_sync(false));
+        control.set_wallet_open(true);
+        assert!(control.should_sync(false));
+    }
+
+    #[test]
+    fn test_drift_alert_only_when_the_gap_stops_shrinking() {
+        let mut monitor = SyncDriftMonitor::new();
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let grace = Duration::from_secs(60);
+
+        assert_eq!(monitor.record(100, 105, 10, grace, at(0)), None);
+        assert_eq!(monitor.current().state, DriftState::InSync);
+
+        // Far behind but catching up: no alert however long it takes
+        for (i, height) in (0..10).map(|i| (i, 100 + i * 50)) {
+            assert_eq!(monitor.record(height, 1000, 10, grace, at(i * 30)), None);
+        }
+        assert_eq!(monitor.current().state, DriftState::CatchingUp);
+
+        // The gap stops shrinking: stalled, alerted once the grace period passed
+        assert_eq!(monitor.record(550, 1000, 10, grace, at(300)), None);
+        assert_eq!(monitor.record(550, 1010, 10, grace, at(320)), None);
+        assert_eq!(monitor.current().state, DriftState::CatchingUp);
+        assert_eq!(monitor.record(550, 1010, 10, grace, at(330)), Some(DriftAlert::Raised));
+        assert_eq!(monitor.current().state, DriftState::Stalled);
+        assert_eq!(monitor.record(550, 1020, 10, grace, at(390)), None);
+        let drift = monitor.current();
+        assert_eq!((drift.gap, drift.stalled_secs, drift.alerted), (470, 120, true));
+
+        // Catching up again keeps the alert until the gap closes
+        assert_eq!(monitor.record(900, 1020, 10, grace, at(420)), None);
+        assert_eq!(monitor.record(1015, 1020, 10, grace, at(450)), Some(DriftAlert::Cleared));
+        assert_eq!(monitor.current(), SyncDrift { sync_height: 1015, network_height: 1020, gap: 5, ..SyncDrift::default() });
+    }
+}
@@ -0,0 +1,269 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Connection watchdog
+//!
+//! Checks that the wallet is still connected to a node and reconnects it when
+//! it is not. Each attempt tries the node with the best health score, so a
+//! node that just failed drops behind the others and the next attempt moves
+//! on. Failed attempts back off from `RECONNECT_BASE_DELAY`, doubling up to
+//! `RECONNECT_MAX_DELAY`; a successful one starts over.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use crate::crypto::real_cryptonote::RealCryptoNoteWallet;
+use crate::utils::retry::RetryPolicy;
+
+/// Event emitted when the wallet (re)connects, with the node
+pub const NETWORK_CONNECTED_EVENT: &str = "network://connected";
+/// Event emitted when the wallet loses its node
+pub const NETWORK_DISCONNECTED_EVENT: &str = "network://disconnected";
+
+pub const RECONNECT_BASE_DELAY: Duration = Duration::from_secs(5);
+pub const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(300);
+/// Failed attempts in a row after which the user is told
+pub const RECONNECT_NOTIFY_AFTER: u32 = 3;
+
+/// Exponentially growing delay between attempts, following a `RetryPolicy`
+/// that never runs out of attempts
+#[derive(Debug, Clone, PartialEq)]
+pub struct Backoff {
+    policy: RetryPolicy,
+    attempts: u32,
+}
+
+impl Backoff {
+    pub fn new(base: Duration, max: Duration) -> Self {
+        let policy = RetryPolicy {
+            max_delay: max,
+            ..RetryPolicy::network("reconnect", None)
+                .with_max_attempts(u32::MAX)
+                .with_base_delay(base)
+        };
+        Self { policy, attempts: 0 }
+    }
+
+    /// Delay before the next attempt: `base`, doubled for every earlier one,
+    /// at most `max`. No jitter is applied, so the delay the user is shown
+    /// is the one waited.
+    pub fn next_delay(&mut self) -> Duration {
+        self.attempts = self.attempts.saturating_add(1);
+        self.policy.delay(self.attempts, 0.0)
+    }
+
+    pub fn reset(&mut self) {
+        self.attempts = 0;
+    }
+}
+
+/// Connection record of one node
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NodeHealth {
+    pub successes: u32,
+    pub failures: u32,
+    pub consecutive_failures: u32,
+    /// Moving average of the connect times, in milliseconds
+    pub latency_ms: Option<f64>,
+}
+
+impl NodeHealth {
+    /// Higher is better: the share of successful connects, less 25 for every
+    /// failure in a row and a little for slow connects. Untried nodes score 50.
+    pub fn score(&self) -> f64 {
+        let attempts = self.successes + self.failures;
+        let reliability = if attempts == 0 { 0.5 } else { f64::from(self.successes) / f64::from(attempts) };
+        reliability * 100.0 - f64::from(self.consecutive_failures) * 25.0 - self.latency_ms.unwrap_or(0.0) / 100.0
+    }
+
+    fn record(&mut self, result: Result<Duration, ()>) {
+        match result {
+            Ok(latency) => {
+                let latency = latency.as_secs_f64() * 1000.0;
+                self.successes += 1;
+                self.consecutive_failures = 0;
+                self.latency_ms = Some(self.latency_ms.map_or(latency, |average| average * 0.7 + latency * 0.3));
+            }
+            Err(()) => {
+                self.failures += 1;
+                self.consecutive_failures += 1;
+            }
+        }
+    }
+}
+
+/// What a watchdog check found or did
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum WatchdogEvent {
+    Connected { host: String, port: u16 },
+    Disconnected,
+    /// An attempt failed; the next one is in `retry_in_secs`
+    ReconnectFailed { host: String, port: u16, failures: u32, retry_in_secs: u64, error: String },
+}
+
+/// Watches one wallet's connection and reconnects it
+#[derive(Debug)]
+pub struct ConnectionWatchdog {
+    backoff: Backoff,
+    health: HashMap<(String, u16), NodeHealth>,
+    connected: Option<bool>,
+    failures: u32,
+    next_attempt: Option<Instant>,
+}
+
+impl Default for ConnectionWatchdog {
+    fn default() -> Self {
+        Self::new(Backoff::new(RECONNECT_BASE_DELAY, RECONNECT_MAX_DELAY))
+    }
+}
+
+impl ConnectionWatchdog {
+    pub fn new(backoff: Backoff) -> Self {
+        Self {
+            backoff,
+            health: HashMap::new(),
+            connected: None,
+            failures: 0,
+            next_attempt: None,
+        }
+    }
+
+    /// Failed attempts since the wallet was last connected
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    pub fn health(&self, host: &str, port: u16) -> Option<&NodeHealth> {
+        self.health.get(&(host.to_string(), port))
+    }
+
+    /// `nodes` best first by health score, in their given order among equals
+    pub fn ranked<'a>(&self, nodes: &[(&'a str, u16)]) -> Vec<(&'a str, u16)> {
+        let mut ranked = nodes.to_vec();
+        ranked.sort_by(|a, b| {
+            let score = |(host, port): &(&str, u16)| self.health(host, *port).map_or(NodeHealth::default().score(), NodeHealth::score);
+            score(b).total_cmp(&score(a))
+        });
+        ranked
+    }
+
+    /// Check `wallet` at `now`: report a lost or regained connection and,
+    /// with `auto_connect` and once the backoff has passed, try the best of
+    /// `nodes`
+    pub fn check(&mut self, wallet: &mut RealCryptoNoteWallet, nodes: &[(&str, u16)], auto_connect: bool, now: Instant) -> Vec<WatchdogEvent> {
+        let mut events = Vec::new();
+        let connected = wallet.get_network_status().ok()
+            .and_then(|status| status.get("is_connected").and_then(|v| v.as_bool()))
+            .unwrap_or(false);
+
+        if connected {
+            self.connected = Some(true);
+            self.failures = 0;
+            self.next_attempt = None;
+            self.backoff.reset();
+            return events;
+        }
+        if self.connected != Some(false) {
+            self.connected = Some(false);
+            events.push(WatchdogEvent::Disconnected);
+        }
+        if !auto_connect || self.next_attempt.is_some_and(|at| now < at) {
+            return events;
+        }
+        let Some(&(host, port)) = self.ranked(nodes).first() else { return events };
+
+        let started = Instant::now();
+        let result = wallet.connect_to_node(host, port);
+        let health = self.health.entry((host.to_string(), port)).or_default();
+        match result {
+            Ok(()) => {
+                health.record(Ok(started.elapsed()));
+                log::info!("Reconnected to {}:{}", host, port);
+                self.connected = Some(true);
+                self.failures = 0;
+                self.next_attempt = None;
+                self.backoff.reset();
+                events.push(WatchdogEvent::Connected { host: host.to_string(), port });
+            }
+            Err(e) => {
+                health.record(Err(()));
+                self.failures += 1;
+                let delay = self.backoff.next_delay();
+                self.next_attempt = Some(now + delay);
+                log::warn!("Reconnecting to {}:{} failed ({} in a row), retrying in {:?}: {}", host, port, self.failures, delay, e);
+                events.push(WatchdogEvent::ReconnectFailed {
+                    host: host.to_string(),
+                    port,
+                    failures: self.failures,
+                    retry_in_secs: delay.as_secs(),
+                    error: e.to_string(),
+                });
+            }
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::backend::MockWalletBackend;
+
+    const NODES: &[(&str, u16)] = &[("node1", 18180), ("node2", 18180), ("node3", 18180)];
+
+    #[test]
+    fn test_backoff_doubles_up_to_the_cap() {
+        let mut backoff = Backoff::new(RECONNECT_BASE_DELAY, RECONNECT_MAX_DELAY);
+        let delays: Vec<u64> = (0..9).map(|_| backoff.next_delay().as_secs()).collect();
+        assert_eq!(delays, vec![5, 10, 20, 40, 80, 160, 300, 300, 300]);
+        backoff.reset();
+        assert_eq!(backoff.next_delay(), RECONNECT_BASE_DELAY);
+    }
+
+    #[test]
+    fn test_watchdog_reconnects_through_scripted_failures() {
+        let backend = MockWalletBackend::with_balance(0);
+        backend.state().connect_failures = 3;
+        let mut wallet = RealCryptoNoteWallet::with_backend(Box::new(backend.clone()));
+        let mut watchdog = ConnectionWatchdog::default();
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        // Disconnected, and the first attempt fails
+        let events = watchdog.check(&mut wallet, NODES, true, at(0));
+        assert_eq!(events[0], WatchdogEvent::Disconnected);
+        assert!(matches!(&events[1], WatchdogEvent::ReconnectFailed { failures: 1, retry_in_secs: 5, .. }));
+        // Nothing is tried again before the backoff has passed
+        assert!(watchdog.check(&mut wallet, NODES, true, at(4)).is_empty());
+
+        let events = watchdog.check(&mut wallet, NODES, true, at(5));
+        assert!(matches!(&events[..], [WatchdogEvent::ReconnectFailed { failures: 2, retry_in_secs: 10, .. }]));
+        let events = watchdog.check(&mut wallet, NODES, true, at(15));
+        assert!(matches!(&events[..], [WatchdogEvent::ReconnectFailed { failures: 3, retry_in_secs: 20, .. }]));
+        let events = watchdog.check(&mut wallet, NODES, true, at(35));
+        assert_eq!(events, vec![WatchdogEvent::Connected { host: "node1".to_string(), port: 18180 }]);
+        assert_eq!(watchdog.failures(), 0);
+
+        // Each failed node went to the back: all three were tried before node1 again
+        let tried: Vec<String> = backend.state().connect_attempts.iter().map(|(host, _)| host.clone()).collect();
+        assert_eq!(tried, vec!["node1", "node2", "node3", "node1"]);
+
+        // Losing the connection again starts the backoff over
+        backend.state().is_connected = false;
+        backend.state().connect_failures = 1;
+        let events = watchdog.check(&mut wallet, NODES, true, at(40));
+        assert!(matches!(&events[1], WatchdogEvent::ReconnectFailed { failures: 1, retry_in_secs: 5, .. }));
+    }
+
+    #[test]
+    fn test_watchdog_only_reports_without_auto_connect() {
+        let backend = MockWalletBackend::with_balance(0);
+        let mut wallet = RealCryptoNoteWallet::with_backend(Box::new(backend.clone()));
+        let mut watchdog = ConnectionWatchdog::default();
+
+        assert_eq!(watchdog.check(&mut wallet, NODES, false, Instant::now()), vec![WatchdogEvent::Disconnected]);
+        assert!(watchdog.check(&mut wallet, NODES, false, Instant::now()).is_empty());
+        assert!(backend.state().connect_attempts.is_empty());
+    }
+}
@@ -0,0 +1,202 @@
+// Copyright (c) 2024 Fuego Private Banking Network
+// Distributed under the MIT/X11 software license
+
+//! Wiping local data
+//!
+//! Removes everything the wallet keeps on this machine, for when the machine
+//! changes hands: the app's config and data directories and the sidecar files
+//! next to the wallet, plus the wallet file itself only when asked. Files are
+//! overwritten with zeros before removal, which is best effort on SSDs and
+//! copy-on-write filesystems. Only directories named `APP_DIR_NAME` are
+//! removed, and symlinks are removed without following them, so nothing
+//! outside the app's own files is touched.
+
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use crate::advanced::state::PersistedWalletState;
+use crate::advanced::{BalanceHistoryStore, TransactionMetadataStore, WatchedAddresses};
+use crate::deposits::DepositRenewalStore;
+use crate::wallet::{PaymentRequests, WalletMetadata};
+use crate::wallet::coin_control::FrozenOutputs;
+use crate::advanced::state::notifications_path;
+use crate::utils::storage::APP_DIR_NAME;
+
+/// What `confirm` must be for a wipe to run
+pub const WIPE_CONFIRMATION: &str = "DELETE";
+
+const OVERWRITE_CHUNK: usize = 64 * 1024;
+
+/// What a wipe removed
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WipeManifest {
+    pub removed: Vec<String>,
+    pub bytes_overwritten: u64,
+    pub wallet_removed: bool,
+    /// Paths that could not be removed, with the reason
+    pub failed: Vec<String>,
+}
+
+/// The directories and files a wipe removes
+#[derive(Debug, Clone)]
+pub struct WipePlan {
+    dirs: Vec<PathBuf>,
+    files: Vec<PathBuf>,
+    wallet: Option<PathBuf>,
+}
+
+impl WipePlan {
+    /// The app's config and data directories
+    pub fn app_dirs() -> Vec<PathBuf> {
+        let mut dirs: Vec<PathBuf> = [dirs::config_dir(), dirs::data_dir()]
+            .into_iter()
+            .flatten()
+            .map(|dir| dir.join(APP_DIR_NAME))
+            .collect();
+        dirs.dedup();
+        dirs
+    }
+
+    /// Wipe `app_dirs` and the sidecars of `wallet_path`, and the wallet
+    /// itself with `include_wallet`
+    pub fn new(app_dirs: Vec<PathBuf>, wallet_path: &Path, include_wallet: bool) -> Self {
+        let files = vec![
+            WalletMetadata::sidecar_path(wallet_path),
+            FrozenOutputs::sidecar_path(wallet_path),
+            PaymentRequests::sidecar_path(wallet_path),
+            DepositRenewalStore::sidecar_path(wallet_path),
+            PersistedWalletState::sidecar_path(wallet_path),
+            notifications_path(wallet_path),
+            TransactionMetadataStore::sidecar_path(wallet_path),
+            BalanceHistoryStore::sidecar_path(wallet_path),
+            WatchedAddresses::sidecar_path(wallet_path),
+        ];
+        Self {
+            dirs: app_dirs,
+            files,
+            wallet: include_wallet.then(|| wallet_path.to_path_buf()),
+        }
+    }
+
+    /// Remove everything in the plan, carrying on past what cannot be removed
+    pub fn execute(&self) -> WipeManifest {
+        let mut manifest = WipeManifest::default();
+        for dir in &self.dirs {
+            if dir.file_name().is_none_or(|name| name != APP_DIR_NAME) {
+                manifest.failed.push(format!("{}: not an app directory", dir.display()));
+                continue;
+            }
+            if fs::symlink_metadata(dir).is_ok() {
+                remove_path(dir, &mut manifest);
+            }
+        }
+        for file in self.files.iter().chain(&self.wallet) {
+            match fs::symlink_metadata(file) {
+                Ok(meta) if meta.is_dir() => manifest.failed.push(format!("{}: not a file", file.display())),
+                Ok(_) => remove_path(file, &mut manifest),
+                Err(_) => {}
+            }
+        }
+        manifest.wallet_removed = self.wallet.as_ref().is_some_and(|wallet| fs::symlink_metadata(wallet).is_err());
+        manifest
+    }
+}
+
+/// Remove `path` and, for a directory, everything under it, overwriting
+/// regular files first and never following symlinks
+fn remove_path(path: &Path, manifest: &mut WipeManifest) {
+    let result = match fs::symlink_metadata(path) {
+        Ok(meta) if meta.is_dir() => {
+            match fs::read_dir(path) {
+                Ok(entries) => {
+                    for entry in entries.flatten() {
+                        remove_path(&entry.path(), manifest);
+                    }
+                    fs::remove_dir(path).map_err(|e| e.to_string())
+                }
+                Err(e) => Err(e.to_string()),
+            }
+        }
+        Ok(meta) if meta.is_file() => {
+            overwrite(path, meta.len())
+                .map(|written| manifest.bytes_overwritten += written)
+                .and_then(|_| fs::remove_file(path).map_err(|e| e.to_string()))
+        }
+        Ok(_) => fs::remove_file(path).map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    match result {
+        Ok(()) => manifest.removed.push(path.display().to_string()),
+        Err(e) => manifest.failed.push(format!("{}: {}", path.display(), e)),
+    }
+}
+
+/// Overwrite the `len` bytes of the file at `path` with zeros
+fn overwrite(path: &Path, len: u64) -> Result<u64, String> {
+    let mut file = OpenOptions::new().write(true).open(path)
+        .map_err(|e| format!("Failed to open for overwriting: {}", e))?;
+    let zeros = [0u8; OVERWRITE_CHUNK];
+    let mut left = len;
+    while left > 0 {
+        let chunk = left.min(OVERWRITE_CHUNK as u64) as usize;
+        file.write_all(&zeros[..chunk])
+            .map_err(|e| format!("Failed to overwrite: {}", e))?;
+        left -= chunk as u64;
+    }
+    file.sync_all().map_err(|e| format!("Failed to sync overwrite: {}", e))?;
+    Ok(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::testing::TempDir;
+
+    #[test]
+    fn test_wipe_removes_app_data_and_sidecars_but_keeps_the_wallet() {
+        let root = TempDir::new("fuego_wipe");
+        let app_dir = root.join(APP_DIR_NAME);
+        fs::create_dir_all(app_dir.join("backups")).unwrap();
+        fs::write(app_dir.join("settings.json"), b"{}").unwrap();
+        fs::write(app_dir.join("backups").join("backup.dat"), b"secret").unwrap();
+        let wallet = root.join("test.wallet");
+        fs::write(&wallet, b"keys").unwrap();
+        fs::write(WalletMetadata::sidecar_path(&wallet), b"{}").unwrap();
+        fs::write(notifications_path(&wallet), b"[]").unwrap();
+        // A directory that is not the app's is left alone
+        let other = root.join("other");
+        fs::create_dir_all(&other).unwrap();
+
+        let manifest = WipePlan::new(vec![app_dir.clone(), other.clone()], &wallet, false).execute();
+
+        assert!(!app_dir.exists());
+        assert!(!WalletMetadata::sidecar_path(&wallet).exists());
+        assert!(!notifications_path(&wallet).exists());
+        assert!(wallet.exists() && other.exists());
+        assert!(!manifest.wallet_removed);
+        assert_eq!(manifest.bytes_overwritten, 2 + 6 + 2 + 2);
+        assert_eq!(manifest.removed.len(), 6);
+        assert_eq!(manifest.failed.len(), 1);
+
+        let manifest = WipePlan::new(Vec::new(), &wallet, true).execute();
+        assert!(manifest.wallet_removed && !wallet.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_wipe_does_not_follow_symlinks() {
+        let root = TempDir::new("fuego_wipe");
+        let app_dir = root.join(APP_DIR_NAME);
+        fs::create_dir_all(&app_dir).unwrap();
+        let outside = root.join("outside.txt");
+        fs::write(&outside, b"keep me").unwrap();
+        std::os::unix::fs::symlink(&outside, app_dir.join("link")).unwrap();
+
+        let manifest = WipePlan::new(vec![app_dir.clone()], &root.join("none.wallet"), false).execute();
+
+        assert!(!app_dir.exists());
+        assert_eq!(fs::read(&outside).unwrap(), b"keep me");
+        assert_eq!(manifest.bytes_overwritten, 0);
+    }
+}
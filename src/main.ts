@@ -7,6 +7,22 @@ let networkStatus: any = null;
 let deposits: any[] = [];
 let availableFonts: string[] = [];
 
+// Error payload of every backend command
+interface ApiError {
+  code: string;
+  message: string;
+  details?: Record<string, unknown>;
+}
+
+function isApiError(e: unknown): e is ApiError {
+  return typeof e === 'object' && e !== null && 'code' in e && 'message' in e;
+}
+
+// Readable text for an error thrown by `invoke`
+function errorMessage(e: unknown): string {
+  return isApiError(e) ? e.message : String(e);
+}
+
 // DOM elements
 let walletStatusEl: HTMLElement | null;
 let balanceEl: HTMLElement | null;
@@ -67,7 +83,7 @@ function showWelcomeModal() {
     try {
       await invoke('wallet_open', { filePath: path, password });
       await afterWalletOpened(modal);
-    } catch (e) { alert(`Open failed: ${e}`); }
+    } catch (e) { alert(`Open failed: ${errorMessage(e)}`); }
   });
 
   document.querySelector('#create-confirm')?.addEventListener('click', async () => {
@@ -75,10 +91,13 @@ function showWelcomeModal() {
     const password = (document.querySelector('#create-password') as HTMLInputElement)?.value ?? '';
     if (!path || !password) { alert('Enter path and password'); return; }
     try {
-      await invoke('wallet_create', { password, filePath: path, seedPhrase: null, restoreHeight: 0 });
+      const created = await invoke<{ address: string; seed_phrase: string | null }>('wallet_create', { password, filePath: path, seedPhrase: null, restoreHeight: 0 });
+      if (created.seed_phrase) {
+        alert(`Write down your seed phrase and keep it safe. It is shown only this once:\n\n${created.seed_phrase}`);
+      }
       await invoke('wallet_open', { filePath: path, password });
       await afterWalletOpened(modal);
-    } catch (e) { alert(`Create failed: ${e}`); }
+    } catch (e) { alert(`Create failed: ${errorMessage(e)}`); }
   });
 
   document.querySelector('#import-seed-confirm')?.addEventListener('click', async () => {
@@ -91,7 +110,7 @@ function showWelcomeModal() {
       await invoke('wallet_create', { password, filePath: path, seedPhrase: seed, restoreHeight: rh });
       await invoke('wallet_open', { filePath: path, password });
       await afterWalletOpened(modal);
-    } catch (e) { alert(`Import by mnemonic failed: ${e}`); }
+    } catch (e) { alert(`Import by mnemonic failed: ${errorMessage(e)}`); }
   });
 
   document.querySelector('#import-keys-confirm')?.addEventListener('click', async () => {
@@ -107,7 +126,7 @@ function showWelcomeModal() {
       await invoke('wallet_open', { filePath: path, password });
       await invoke('import_keys', { viewKey, spendKey, address });
       await afterWalletOpened(modal);
-    } catch (e) { alert(`Import by keys failed: ${e}`); }
+    } catch (e) { alert(`Import by keys failed: ${errorMessage(e)}`); }
   });
 }
 
@@ -231,7 +250,7 @@ async function createDeposit() {
     await refresh();
   } catch (error) {
     console.error('Failed to create deposit:', error);
-    alert(`Failed to create deposit: ${error}`);
+    alert(`Failed to create deposit: ${errorMessage(error)}`);
   }
 }
 
@@ -244,7 +263,7 @@ async function withdrawDeposit(depositId: string) {
     await refresh();
   } catch (error) {
     console.error('Failed to withdraw deposit:', error);
-    alert(`Failed to withdraw deposit: ${error}`);
+    alert(`Failed to withdraw deposit: ${errorMessage(error)}`);
   }
 }
 
@@ -445,10 +464,17 @@ async function sendTransaction() {
     await refresh();
   } catch (error) {
     console.error("Failed to send transaction:", error);
-    alert(`Failed to send transaction: ${error}`);
+    alert(`Failed to send transaction: ${errorMessage(error)}`);
   }
 }
 
+// Names of the sync phases reported by get_sync_progress
+const SYNC_PHASE_LABELS: Record<string, string> = {
+  connecting: "Connecting",
+  downloading_blocks: "Downloading blocks",
+  scanning: "Scanning for transactions",
+};
+
 // Update sync progress display (consolidated)
 function updateSyncDisplay(syncProgress: any) {
   const syncProgressTextEl = document.querySelector("#sync-progress-text");
@@ -462,7 +488,8 @@ function updateSyncDisplay(syncProgress: any) {
       const currentHeight = syncProgress.current_height ? syncProgress.current_height.toLocaleString() : '0';
       const totalHeight = syncProgress.total_height ? syncProgress.total_height.toLocaleString() : '0';
       
-      syncProgressTextEl.textContent = `🔄 Syncing... ${progress}%`;
+      const phase = SYNC_PHASE_LABELS[syncProgress.phase] || "Syncing";
+      syncProgressTextEl.textContent = `🔄 ${phase}... ${progress}%`;
       syncDetailsEl.textContent = `Block ${currentHeight} of ${totalHeight}`;
 
       // Update progress bar
@@ -591,7 +618,7 @@ async function startMining() {
     }
   } catch (error) {
     console.error("Failed to start mining:", error);
-    alert(`Failed to start mining: ${error}`);
+    alert(`Failed to start mining: ${errorMessage(error)}`);
   }
 }
 
@@ -616,7 +643,7 @@ async function stopMining() {
 
   } catch (error) {
     console.error("Failed to stop mining:", error);
-    alert(`Failed to stop mining: ${error}`);
+    alert(`Failed to stop mining: ${errorMessage(error)}`);
   }
 }
 